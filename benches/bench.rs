@@ -1,3 +1,7 @@
+// `bench_function_over_inputs` is deprecated in newer `criterion` releases; keep using it until the
+// benchmarks are ported to `BenchmarkGroup`s.
+#![allow(deprecated)]
+
 use criterion::{criterion_group, criterion_main, Criterion};
 use ff::Field;
 use threshold_crypto::poly::Poly;
@@ -121,10 +125,39 @@ mod public_key_set_benches {
         );
     }
 
+    /// Benchmarks combining signatures for ten messages at once
+    fn combine_many(c: &mut Criterion) {
+        let mut rng = XorShiftRng::from_seed(RNG_SEED);
+        c.bench_function_over_inputs(
+            "Combine Many Signatures",
+            move |b, &&threshold| {
+                let sk_set = SecretKeySet::random(threshold, &mut rng);
+                let pk_set = sk_set.public_keys();
+                let sigs: BTreeMap<_, Vec<_>> = (0..=threshold)
+                    .map(|i| {
+                        let sk_share = sk_set.secret_key_share(i);
+                        (
+                            i,
+                            (0..10)
+                                .map(|m| sk_share.sign(format!("msg {}", m)))
+                                .collect(),
+                        )
+                    })
+                    .collect();
+                b.iter(|| {
+                    pk_set
+                        .combine_many(&sigs)
+                        .expect("could not combine signatures");
+                })
+            },
+            &TEST_THRESHOLDS,
+        );
+    }
+
     criterion_group! {
         name = public_key_set_benches;
         config = Criterion::default();
-        targets = combine_signatures,
+        targets = combine_signatures, combine_many,
     }
 }
 
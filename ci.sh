@@ -14,6 +14,9 @@ cargo test
 cargo test --all-features
 cargo test --release
 cargo test --all-features --release
+./uniffi/build-bindings.sh
+# The host CPU's optimizations don't apply to WebAssembly.
+RUSTFLAGS="-D warnings" cargo build --target wasm32-unknown-unknown --no-default-features --features wasm
 cargo doc
 cargo deadlinks --dir target/doc/threshold_crypto/
 cargo audit
@@ -0,0 +1,750 @@
+//! Command line tool for `threshold_crypto` key ceremonies and operations.
+//!
+//! All keys, shares, signatures and ciphertexts are read from and written to files in the crate's
+//! canonical `to_bytes` encodings. With `--hex`, files are written as hex strings instead; hex
+//! files are detected automatically when reading. Secret keys and shares are written to files
+//! that only their owner can read.
+//!
+//! The `convert` subcommand translates a file between these encodings and the crate's `serde`
+//! serializations in JSON and `bincode`, the format of files written by earlier versions.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process;
+
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use hex_fmt::HexFmt;
+use serde::de::DeserializeOwned;
+use threshold_crypto::error::Error;
+use threshold_crypto::serde_impl::SerdeSecret;
+use threshold_crypto::{
+    Ciphertext, DecryptionShare, PublicKey, PublicKeySet, SecretKey, SecretKeySet, SecretKeyShare,
+    Signature, SignatureShare,
+};
+use zeroize::Zeroizing;
+
+/// The result of a subcommand, with a human-readable error message.
+type CliResult<T> = Result<T, String>;
+
+fn main() {
+    let matches = app().get_matches();
+    if let Err(err) = run(&matches) {
+        eprintln!("error: {}", err);
+        process::exit(1);
+    }
+}
+
+/// Returns the command line interface definition.
+fn app() -> App<'static, 'static> {
+    let file = |name: &'static str, help: &'static str| {
+        Arg::with_name(name)
+            .long(name)
+            .takes_value(true)
+            .value_name("FILE")
+            .required(true)
+            .help(help)
+    };
+    let indexed = |name: &'static str, help: &'static str| {
+        Arg::with_name(name)
+            .long(name)
+            .takes_value(true)
+            .value_name("INDEX:FILE")
+            .multiple(true)
+            .number_of_values(1)
+            .required(true)
+            .help(help)
+    };
+    App::new("threshold-crypto")
+        .about("Key ceremonies and threshold operations with threshold_crypto")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(
+            Arg::with_name("hex")
+                .long("hex")
+                .global(true)
+                .help("Write output files as hex instead of binary"),
+        )
+        .subcommand(
+            SubCommand::with_name("keygen")
+                .about("Generates a random secret key")
+                .arg(file("out", "The secret key file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("public-key")
+                .about("Writes the public key of a secret key")
+                .arg(file("secret-key", "The secret key file"))
+                .arg(file("out", "The public key file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("split")
+                .about("Splits a secret key into shares; any threshold + 1 of them can sign")
+                .arg(file("secret-key", "The secret key file"))
+                .arg(
+                    Arg::with_name("threshold")
+                        .long("threshold")
+                        .takes_value(true)
+                        .required(true),
+                )
+                .arg(
+                    Arg::with_name("shares")
+                        .long("shares")
+                        .takes_value(true)
+                        .required(true)
+                        .help("The number of shares to write"),
+                )
+                .arg(
+                    Arg::with_name("out-dir")
+                        .long("out-dir")
+                        .takes_value(true)
+                        .value_name("DIR")
+                        .required(true)
+                        .help("Where to write public_key_set.bin and share_<i>.bin"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("reconstruct")
+                .about("Reconstructs the secret key from threshold + 1 shares")
+                .arg(file("pk-set", "The public key set file"))
+                .arg(indexed("share", "A secret key share file"))
+                .arg(file("out", "The secret key file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("sign-share")
+                .about("Signs a message with a secret key share")
+                .arg(file("share", "The secret key share file"))
+                .arg(file("message", "The message file"))
+                .arg(file("out", "The signature share file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("combine")
+                .about("Verifies signature shares and combines them into a signature")
+                .arg(file("pk-set", "The public key set file"))
+                .arg(file("message", "The message file"))
+                .arg(indexed("sig-share", "A signature share file"))
+                .arg(file("out", "The signature file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("verify")
+                .about("Verifies a signature with a public key or the master key of a key set")
+                .arg(file("key", "The public key or public key set file"))
+                .arg(file("message", "The message file"))
+                .arg(file("signature", "The signature file")),
+        )
+        .subcommand(
+            SubCommand::with_name("encrypt")
+                .about("Encrypts a message to a public key or the master key of a key set")
+                .arg(file("key", "The public key or public key set file"))
+                .arg(file("message", "The message file"))
+                .arg(file("out", "The ciphertext file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("decrypt-share")
+                .about("Produces a decryption share for a ciphertext")
+                .arg(file("share", "The secret key share file"))
+                .arg(file("ciphertext", "The ciphertext file"))
+                .arg(file("out", "The decryption share file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("decrypt")
+                .about("Verifies decryption shares and combines them to decrypt a ciphertext")
+                .arg(file("pk-set", "The public key set file"))
+                .arg(file("ciphertext", "The ciphertext file"))
+                .arg(indexed("dec-share", "A decryption share file"))
+                .arg(file("out", "The plaintext file to write")),
+        )
+        .subcommand(
+            SubCommand::with_name("convert")
+                .about("Converts a file between the binary, hex, JSON and bincode formats")
+                .arg(file("in", "The file to convert"))
+                .arg(file("out", "The converted file to write"))
+                .arg(
+                    Arg::with_name("type")
+                        .long("type")
+                        .takes_value(true)
+                        .possible_values(TYPES)
+                        .required(true)
+                        .help("The type of the value in the file"),
+                )
+                .arg(
+                    Arg::with_name("from")
+                        .long("from")
+                        .takes_value(true)
+                        .possible_values(FORMATS)
+                        .help("The format of the input; binary or hex if omitted"),
+                )
+                .arg(
+                    Arg::with_name("to")
+                        .long("to")
+                        .takes_value(true)
+                        .possible_values(FORMATS)
+                        .required(true),
+                ),
+        )
+}
+
+/// The types of values that `convert` accepts.
+const TYPES: &[&str] = &[
+    "secret-key",
+    "secret-key-share",
+    "public-key",
+    "public-key-set",
+    "signature",
+    "signature-share",
+    "ciphertext",
+    "decryption-share",
+];
+
+/// The formats that `convert` translates between.
+const FORMATS: &[&str] = &["bin", "hex", "json", "bincode"];
+
+/// A file format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Format {
+    /// The canonical `to_bytes` encoding.
+    Bin,
+    /// The canonical encoding as a hex string.
+    Hex,
+    /// The `serde` serialization in JSON.
+    Json,
+    /// The `serde` serialization in `bincode`, as written by earlier versions of the tool.
+    Bincode,
+}
+
+impl Format {
+    /// Parses the name of a format in `FORMATS`.
+    fn parse(name: &str) -> CliResult<Format> {
+        match name {
+            "bin" => Ok(Format::Bin),
+            "hex" => Ok(Format::Hex),
+            "json" => Ok(Format::Json),
+            "bincode" => Ok(Format::Bincode),
+            _ => Err(format!("unknown format {}", name)),
+        }
+    }
+}
+
+/// A value that can be read from and written to files.
+trait Value: Sized + DeserializeOwned {
+    /// Whether the value is secret, so that only the file's owner may read it.
+    const SECRET: bool;
+
+    /// Returns the canonical encoding.
+    fn encode(&self) -> Zeroizing<Vec<u8>>;
+
+    /// Decodes the canonical encoding.
+    fn decode(bytes: &[u8]) -> CliResult<Self>;
+
+    /// Returns the `serde` serialization in JSON.
+    fn to_json(&self) -> CliResult<Zeroizing<Vec<u8>>>;
+
+    /// Returns the `serde` serialization in `bincode`.
+    fn to_bincode(&self) -> CliResult<Zeroizing<Vec<u8>>>;
+}
+
+/// Implements `Value` for types with `to_bytes`, `from_slice` and `Serialize`, or for secret
+/// types, `SerializeSecret`.
+macro_rules! impl_value {
+    ($type:ty, $secret:expr, $ser:expr) => {
+        impl Value for $type {
+            const SECRET: bool = $secret;
+
+            fn encode(&self) -> Zeroizing<Vec<u8>> {
+                Zeroizing::new(self.to_bytes().to_vec())
+            }
+
+            fn decode(bytes: &[u8]) -> CliResult<Self> {
+                <$type>::from_slice(bytes).map_err(|err| err.to_string())
+            }
+
+            fn to_json(&self) -> CliResult<Zeroizing<Vec<u8>>> {
+                let mut json = serde_json::to_vec_pretty(&$ser(self)).map_err(|e| e.to_string())?;
+                json.push(b'\n');
+                Ok(Zeroizing::new(json))
+            }
+
+            fn to_bincode(&self) -> CliResult<Zeroizing<Vec<u8>>> {
+                let bytes = bincode::serialize(&$ser(self)).map_err(|e| e.to_string())?;
+                Ok(Zeroizing::new(bytes))
+            }
+        }
+    };
+}
+
+impl_value!(SecretKey, true, SerdeSecret);
+impl_value!(SecretKeyShare, true, SerdeSecret);
+impl_value!(PublicKey, false, std::convert::identity);
+impl_value!(PublicKeySet, false, std::convert::identity);
+impl_value!(Signature, false, std::convert::identity);
+impl_value!(SignatureShare, false, std::convert::identity);
+impl_value!(Ciphertext, false, std::convert::identity);
+impl_value!(DecryptionShare, false, std::convert::identity);
+
+/// Runs the subcommand given on the command line.
+fn run(matches: &ArgMatches) -> CliResult<()> {
+    let hex = matches.is_present("hex");
+    let (name, sub) = matches.subcommand();
+    let sub = sub.ok_or("missing subcommand")?;
+    let hex = hex || sub.is_present("hex");
+    let format = if hex { Format::Hex } else { Format::Bin };
+    let path = |name: &str| PathBuf::from(sub.value_of(name).expect("required argument"));
+    match name {
+        "keygen" => write(&path("out"), &SecretKey::random(), format),
+        "public-key" => {
+            let sk: SecretKey = read(&path("secret-key"))?;
+            write(&path("out"), &sk.public_key(), format)
+        }
+        "split" => {
+            let sk: SecretKey = read(&path("secret-key"))?;
+            let threshold = parse_number(sub, "threshold")?;
+            let count = parse_number(sub, "shares")?;
+            if count <= threshold {
+                return Err("the number of shares must be greater than the threshold".into());
+            }
+            let sk_set = SecretKeySet::from_secret_key(&sk, threshold, &mut rand::thread_rng())
+                .map_err(|e| e.to_string())?;
+            let dir = path("out-dir");
+            fs::create_dir_all(&dir).map_err(|err| format!("{}: {}", dir.display(), err))?;
+            write(
+                &dir.join("public_key_set.bin"),
+                &sk_set.public_keys(),
+                format,
+            )?;
+            for i in 0..count {
+                let share = sk_set.secret_key_share(i);
+                write(&dir.join(format!("share_{}.bin", i)), &share, format)?;
+            }
+            Ok(())
+        }
+        "reconstruct" => {
+            let pk_set: PublicKeySet = read(&path("pk-set"))?;
+            let shares: BTreeMap<usize, SecretKeyShare> = read_indexed(sub, "share")?;
+            let sk = reconstruct(&pk_set, &shares)?;
+            write(&path("out"), &sk, format)
+        }
+        "sign-share" => {
+            let share: SecretKeyShare = read(&path("share"))?;
+            let msg = read_raw(&path("message"))?;
+            write(&path("out"), &share.sign(msg), format)
+        }
+        "combine" => {
+            let pk_set: PublicKeySet = read(&path("pk-set"))?;
+            let msg = read_raw(&path("message"))?;
+            let shares: BTreeMap<usize, SignatureShare> = read_indexed(sub, "sig-share")?;
+            for (i, share) in &shares {
+                if !pk_set.public_key_share(*i).verify(share, &msg) {
+                    return Err(format!("signature share {} is invalid", i));
+                }
+            }
+            let sig = pk_set
+                .combine_signatures(&shares)
+                .map_err(|err| err.to_string())?;
+            write(&path("out"), &sig, format)
+        }
+        "verify" => {
+            let pk = read_public_key(&path("key"))?;
+            let msg = read_raw(&path("message"))?;
+            let sig: Signature = read(&path("signature"))?;
+            if pk.verify(&sig, msg) {
+                println!("signature is valid");
+                Ok(())
+            } else {
+                Err("signature is invalid".into())
+            }
+        }
+        "encrypt" => {
+            let pk = read_public_key(&path("key"))?;
+            let msg = read_raw(&path("message"))?;
+            write(&path("out"), &pk.encrypt(msg), format)
+        }
+        "decrypt-share" => {
+            let share: SecretKeyShare = read(&path("share"))?;
+            let ct: Ciphertext = read(&path("ciphertext"))?;
+            let dec_share = share.decrypt_share(&ct).ok_or("ciphertext is invalid")?;
+            write(&path("out"), &dec_share, format)
+        }
+        "decrypt" => {
+            let pk_set: PublicKeySet = read(&path("pk-set"))?;
+            let ct: Ciphertext = read(&path("ciphertext"))?;
+            if !ct.verify() {
+                return Err("ciphertext is invalid".into());
+            }
+            let shares: BTreeMap<usize, DecryptionShare> = read_indexed(sub, "dec-share")?;
+            for (i, share) in &shares {
+                if !pk_set
+                    .public_key_share(*i)
+                    .verify_decryption_share(share, &ct)
+                {
+                    return Err(format!("decryption share {} is invalid", i));
+                }
+            }
+            let msg = pk_set
+                .decrypt(&shares, &ct)
+                .map_err(|err| err.to_string())?;
+            write_raw(&path("out"), &msg)
+        }
+        "convert" => {
+            let from = sub.value_of("from").map(Format::parse).transpose()?;
+            let to = Format::parse(sub.value_of("to").expect("required argument"))?;
+            let (input, output) = (path("in"), path("out"));
+            match sub.value_of("type").expect("required argument") {
+                "secret-key" => convert::<SecretKey>(&input, from, &output, to),
+                "secret-key-share" => convert::<SecretKeyShare>(&input, from, &output, to),
+                "public-key" => convert::<PublicKey>(&input, from, &output, to),
+                "public-key-set" => convert::<PublicKeySet>(&input, from, &output, to),
+                "signature" => convert::<Signature>(&input, from, &output, to),
+                "signature-share" => convert::<SignatureShare>(&input, from, &output, to),
+                "ciphertext" => convert::<Ciphertext>(&input, from, &output, to),
+                "decryption-share" => convert::<DecryptionShare>(&input, from, &output, to),
+                name => Err(format!("unknown type {}", name)),
+            }
+        }
+        _ => Err(format!("unknown subcommand {}", name)),
+    }
+}
+
+/// Reads a value in one format and writes it in another.
+fn convert<T: Value>(
+    input: &Path,
+    from: Option<Format>,
+    output: &Path,
+    to: Format,
+) -> CliResult<()> {
+    let value: T = match from {
+        None => read(input)?,
+        Some(from) => read_as(input, from)?,
+    };
+    write(output, &value, to)
+}
+
+/// Interpolates the master key from `threshold + 1` shares and checks it against the key set.
+fn reconstruct(
+    pk_set: &PublicKeySet,
+    shares: &BTreeMap<usize, SecretKeyShare>,
+) -> CliResult<SecretKey> {
+    SecretKeySet::recover(shares, pk_set).map_err(|err| match err {
+        Error::NotEnoughShares => format!("need {} shares", pk_set.threshold() + 1),
+        Error::InvalidShare(i) => format!("secret key share {} doesn't match the key set", i),
+        err => err.to_string(),
+    })
+}
+
+/// Reads either a `PublicKey` or the master key of a `PublicKeySet`.
+fn read_public_key(path: &Path) -> CliResult<PublicKey> {
+    read::<PublicKey>(path).or_else(|_| read::<PublicKeySet>(path).map(|pks| pks.public_key()))
+}
+
+/// Parses a required numeric argument.
+fn parse_number(matches: &ArgMatches, name: &str) -> CliResult<usize> {
+    let value = matches.value_of(name).expect("required argument");
+    value
+        .parse()
+        .map_err(|_| format!("--{} must be a number, got {}", name, value))
+}
+
+/// Reads all `INDEX:FILE` values of the given argument.
+fn read_indexed<T: Value>(matches: &ArgMatches, name: &str) -> CliResult<BTreeMap<usize, T>> {
+    let mut result = BTreeMap::new();
+    for value in matches.values_of(name).into_iter().flatten() {
+        let mut parts = value.splitn(2, ':');
+        let (index, file) = match (parts.next(), parts.next()) {
+            (Some(index), Some(file)) => (index, file),
+            _ => return Err(format!("--{} expects INDEX:FILE, got {}", name, value)),
+        };
+        let index = index
+            .parse()
+            .map_err(|_| format!("invalid index in --{} {}", name, value))?;
+        if result.insert(index, read(Path::new(file))?).is_some() {
+            return Err(format!("duplicate index {} in --{}", index, name));
+        }
+    }
+    Ok(result)
+}
+
+/// Reads a value in the canonical encoding, accepting hex-encoded files.
+fn read<T: Value>(path: &Path) -> CliResult<T> {
+    let bytes = Zeroizing::new(read_raw(path)?);
+    let decoded = decode_hex(&bytes).map(Zeroizing::new);
+    T::decode(decoded.as_ref().unwrap_or(&bytes))
+        .map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Reads a value in the given format.
+fn read_as<T: Value>(path: &Path, format: Format) -> CliResult<T> {
+    let bytes = Zeroizing::new(read_raw(path)?);
+    let result = match format {
+        Format::Bin => T::decode(&bytes),
+        Format::Hex => match decode_hex(&bytes).map(Zeroizing::new) {
+            Some(decoded) => T::decode(&decoded),
+            None => Err("invalid hex string".to_string()),
+        },
+        Format::Json => serde_json::from_slice(&bytes).map_err(|err| err.to_string()),
+        Format::Bincode => bincode::deserialize(&bytes).map_err(|err| err.to_string()),
+    };
+    result.map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Writes a value in the given format. Files with secret values are only readable by their owner.
+fn write<T: Value>(path: &Path, value: &T, format: Format) -> CliResult<()> {
+    let bytes = match format {
+        Format::Bin => value.encode(),
+        Format::Hex => Zeroizing::new(format!("{}\n", HexFmt(&*value.encode())).into_bytes()),
+        Format::Json => value.to_json()?,
+        Format::Bincode => value.to_bincode()?,
+    };
+    if T::SECRET {
+        write_secret_raw(path, &bytes)
+    } else {
+        write_raw(path, &bytes)
+    }
+}
+
+fn read_raw(path: &Path) -> CliResult<Vec<u8>> {
+    fs::read(path).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+fn write_raw(path: &Path, bytes: &[u8]) -> CliResult<()> {
+    fs::write(path, bytes).map_err(|err| format!("{}: {}", path.display(), err))
+}
+
+/// Writes secret bytes to a file that, on Unix, only its owner can read and write. If the file
+/// already exists, its permissions are restricted before it is overwritten.
+fn write_secret_raw(path: &Path, bytes: &[u8]) -> CliResult<()> {
+    let err = |err: std::io::Error| format!("{}: {}", path.display(), err);
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+    let mut file = options.open(path).map_err(err)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let permissions = fs::Permissions::from_mode(0o600);
+        file.set_permissions(permissions).map_err(err)?;
+    }
+    file.write_all(bytes).map_err(err)
+}
+
+/// Decodes a hex string, ignoring surrounding whitespace. Returns `None` if it isn't valid hex.
+fn decode_hex(text: &[u8]) -> Option<Vec<u8>> {
+    let text = std::str::from_utf8(text).ok()?.trim();
+    if text.is_empty() || text.len() % 2 != 0 {
+        return None;
+    }
+    (0..text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(text.get(i..i + 2)?, 16).ok())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the tool with the given arguments.
+    fn cli(args: &[&str]) -> CliResult<()> {
+        let matches = app()
+            .get_matches_from_safe(std::iter::once("threshold-crypto").chain(args.iter().cloned()))
+            .map_err(|err| err.to_string())?;
+        run(&matches)
+    }
+
+    #[test]
+    fn test_ceremony() {
+        let dir = std::env::temp_dir().join(format!("threshold-crypto-cli-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let p = |name: &str| dir.join(name).to_str().unwrap().to_string();
+        fs::write(p("msg"), b"Launch the rocket").unwrap();
+
+        cli(&["keygen", "--out", &p("sk"), "--hex"]).unwrap();
+        cli(&["public-key", "--secret-key", &p("sk"), "--out", &p("pk")]).unwrap();
+        cli(&[
+            "split",
+            "--secret-key",
+            &p("sk"),
+            "--threshold",
+            "1",
+            "--shares",
+            "3",
+            "--out-dir",
+            &p("shares"),
+        ])
+        .unwrap();
+        let pk_set = p("shares/public_key_set.bin");
+        let share = |i: usize| p(&format!("shares/share_{}.bin", i));
+
+        for i in &[0, 2] {
+            let out = p(&format!("sig{}", i));
+            cli(&[
+                "sign-share",
+                "--share",
+                &share(*i),
+                "--message",
+                &p("msg"),
+                "--out",
+                &out,
+            ])
+            .unwrap();
+        }
+        cli(&[
+            "combine",
+            "--pk-set",
+            &pk_set,
+            "--message",
+            &p("msg"),
+            "--sig-share",
+            &format!("0:{}", p("sig0")),
+            "--sig-share",
+            &format!("2:{}", p("sig2")),
+            "--out",
+            &p("sig"),
+        ])
+        .unwrap();
+        // The combined signature verifies with both the original and the key set's public key.
+        cli(&[
+            "verify",
+            "--key",
+            &p("pk"),
+            "--message",
+            &p("msg"),
+            "--signature",
+            &p("sig"),
+        ])
+        .unwrap();
+        cli(&[
+            "verify",
+            "--key",
+            &pk_set,
+            "--message",
+            &p("msg"),
+            "--signature",
+            &p("sig"),
+        ])
+        .unwrap();
+        // A signature share is not a valid signature.
+        let sig0 = p("sig0");
+        assert!(cli(&[
+            "verify",
+            "--key",
+            &p("pk"),
+            "--message",
+            &p("msg"),
+            "--signature",
+            &sig0
+        ])
+        .is_err());
+
+        cli(&[
+            "encrypt",
+            "--key",
+            &pk_set,
+            "--message",
+            &p("msg"),
+            "--out",
+            &p("ct"),
+        ])
+        .unwrap();
+        for i in &[1, 2] {
+            let out = p(&format!("dec{}", i));
+            cli(&[
+                "decrypt-share",
+                "--share",
+                &share(*i),
+                "--ciphertext",
+                &p("ct"),
+                "--out",
+                &out,
+            ])
+            .unwrap();
+        }
+        cli(&[
+            "decrypt",
+            "--pk-set",
+            &pk_set,
+            "--ciphertext",
+            &p("ct"),
+            "--dec-share",
+            &format!("1:{}", p("dec1")),
+            "--dec-share",
+            &format!("2:{}", p("dec2")),
+            "--out",
+            &p("plain"),
+        ])
+        .unwrap();
+        assert_eq!(fs::read(p("msg")).unwrap(), fs::read(p("plain")).unwrap());
+
+        cli(&[
+            "reconstruct",
+            "--pk-set",
+            &pk_set,
+            "--share",
+            &format!("0:{}", share(0)),
+            "--share",
+            &format!("1:{}", share(1)),
+            "--out",
+            &p("sk2"),
+        ])
+        .unwrap();
+        let convert = |from: &str, to: &str, input: &str, out: &str| {
+            cli(&[
+                "convert",
+                "--type",
+                "secret-key",
+                "--in",
+                &p(input),
+                "--out",
+                &p(out),
+                "--from",
+                from,
+                "--to",
+                to,
+            ])
+        };
+        convert("hex", "bin", "sk", "sk_bin").unwrap();
+        assert_eq!(fs::read(p("sk_bin")).unwrap(), fs::read(p("sk2")).unwrap());
+        convert("bin", "json", "sk_bin", "sk_json").unwrap();
+        convert("json", "bincode", "sk_json", "sk_bincode").unwrap();
+        convert("bincode", "hex", "sk_bincode", "sk_hex").unwrap();
+        assert_eq!(fs::read(p("sk")).unwrap(), fs::read(p("sk_hex")).unwrap());
+        assert!(convert("json", "bin", "sk_bin", "sk_invalid").is_err());
+
+        // Files written by earlier versions are the `bincode` serialization.
+        let pk_set_value: PublicKeySet = read(Path::new(&pk_set)).unwrap();
+        fs::write(p("pk_set_old"), bincode::serialize(&pk_set_value).unwrap()).unwrap();
+        cli(&[
+            "convert",
+            "--type",
+            "public-key-set",
+            "--in",
+            &p("pk_set_old"),
+            "--out",
+            &p("pk_set_new"),
+            "--from",
+            "bincode",
+            "--to",
+            "bin",
+        ])
+        .unwrap();
+        assert_eq!(
+            fs::read(&pk_set).unwrap(),
+            fs::read(p("pk_set_new")).unwrap()
+        );
+        assert_eq!(pk_set_value.to_bytes(), fs::read(&pk_set).unwrap());
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let mode = |path: &str| fs::metadata(path).unwrap().permissions().mode() & 0o777;
+            for secret in &[p("sk"), p("sk2"), p("sk_json"), p("sk_bincode"), share(0)] {
+                assert_eq!(0o600, mode(secret));
+            }
+            // Existing files are restricted before secrets are written to them.
+            fs::set_permissions(p("sk_hex"), fs::Permissions::from_mode(0o644)).unwrap();
+            convert("bin", "hex", "sk_bin", "sk_hex").unwrap();
+            assert_eq!(0o600, mode(&p("sk_hex")));
+        }
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,64 @@
+/*
+ * C interface of the threshold_crypto crate, built with the `ffi` feature. See the `ffi` module's
+ * documentation for the conventions.
+ */
+#ifndef THRESHOLD_CRYPTO_H
+#define THRESHOLD_CRYPTO_H
+
+#include <stddef.h>
+#include <stdint.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* The size of signatures and signature shares. */
+#define TC_SIG_SIZE 96
+
+/* Return codes. */
+#define TC_OK 0
+#define TC_ERR_NULL (-1)
+#define TC_ERR_INVALID (-2)
+#define TC_ERR_COMBINE (-3)
+#define TC_ERR_PANIC (-4)
+
+/* Opaque handles. */
+typedef struct TcSecretKeySet TcSecretKeySet;
+typedef struct TcSecretKeyShare TcSecretKeyShare;
+typedef struct TcPublicKeySet TcPublicKeySet;
+
+/* A byte buffer allocated by the library, zeroed and released with `tc_buffer_free`. */
+typedef struct TcBuffer {
+    uint8_t *data;
+    size_t len;
+} TcBuffer;
+
+void tc_buffer_free(TcBuffer buf);
+
+TcSecretKeySet *tc_secret_key_set_random(size_t threshold);
+void tc_secret_key_set_free(TcSecretKeySet *sk_set);
+TcSecretKeyShare *tc_secret_key_set_share(const TcSecretKeySet *sk_set, uint64_t i);
+TcPublicKeySet *tc_secret_key_set_public_keys(const TcSecretKeySet *sk_set);
+
+TcSecretKeyShare *tc_secret_key_share_from_bytes(const uint8_t *bytes, size_t len);
+TcBuffer tc_secret_key_share_to_bytes(const TcSecretKeyShare *share);
+void tc_secret_key_share_free(TcSecretKeyShare *share);
+int32_t tc_secret_key_share_sign(const TcSecretKeyShare *share, const uint8_t *msg,
+                                 size_t msg_len, uint8_t *sig_out);
+
+TcPublicKeySet *tc_public_key_set_from_bytes(const uint8_t *bytes, size_t len);
+TcBuffer tc_public_key_set_to_bytes(const TcPublicKeySet *pk_set);
+void tc_public_key_set_free(TcPublicKeySet *pk_set);
+int32_t tc_public_key_set_verify_share(const TcPublicKeySet *pk_set, uint64_t i,
+                                       const uint8_t *sig, const uint8_t *msg, size_t msg_len);
+int32_t tc_public_key_set_combine_signatures(const TcPublicKeySet *pk_set,
+                                             const uint64_t *indices, const uint8_t *shares,
+                                             size_t count, uint8_t *sig_out);
+int32_t tc_public_key_set_verify(const TcPublicKeySet *pk_set, const uint8_t *sig,
+                                 const uint8_t *msg, size_t msg_len);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* THRESHOLD_CRYPTO_H */
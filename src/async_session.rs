@@ -0,0 +1,421 @@
+//! Asynchronous share holders and a driver that collects their shares concurrently.
+//!
+//! Coordinators usually request shares from remote participants over the network. The
+//! [`AsyncShareSigner`] and [`AsyncShareDecryptor`] traits are the asynchronous counterparts of
+//! the traits in [`signer`](../signer/index.html), and [`sign`] and [`decrypt`] fan out the
+//! requests, feed the responses into a [`SigningSession`] or [`DecryptionSession`], and stop as
+//! soon as `threshold + 1` valid shares have arrived. Each participant gets its own timeout.
+//!
+//! The driver doesn't depend on any particular async runtime: Timeouts are created by a
+//! [`Timer`], so e.g. a Tokio application can implement it with `tokio::time::sleep`. The
+//! [`ThreadTimer`] works anywhere, but spawns a thread per timeout.
+
+use std::collections::BTreeMap;
+use std::future::{poll_fn, Future};
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+use std::time::Duration;
+
+use crate::error::{Error, Result};
+#[cfg(feature = "encryption")]
+use crate::session::DecryptionSession;
+use crate::session::SigningSession;
+use crate::signer::InMemoryShare;
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{G2Affine, Signature, SignatureShare};
+
+/// A boxed future returned by an asynchronous share holder.
+pub type ShareFuture<'a, T> = Pin<Box<dyn Future<Output = Result<T>> + Send + 'a>>;
+
+/// A boxed future that completes after a timeout.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Something that holds the secret key share with a given index and can asynchronously sign
+/// with it, e.g. a remote participant or a network-attached HSM.
+pub trait AsyncShareSigner {
+    /// Returns the index of the held share, as passed to `SecretKeySet::secret_key_share`.
+    fn index(&self) -> usize;
+
+    /// Signs the given element of `G2`.
+    fn sign_g2(&self, hash: G2Affine) -> ShareFuture<'_, SignatureShare>;
+}
+
+#[cfg(feature = "encryption")]
+/// Something that holds the secret key share with a given index and can asynchronously produce
+/// decryption shares with it.
+pub trait AsyncShareDecryptor {
+    /// Returns the index of the held share, as passed to `SecretKeySet::secret_key_share`.
+    fn index(&self) -> usize;
+
+    /// Returns a decryption share, without validating the ciphertext.
+    fn decrypt_share_no_verify<'a>(
+        &'a self,
+        ct: &'a Ciphertext,
+    ) -> ShareFuture<'a, DecryptionShare>;
+
+    /// Returns a decryption share, or `Error::InvalidCiphertext` if the ciphertext isn't valid
+    /// for the given associated data.
+    fn decrypt_share_with_ad<'a>(
+        &'a self,
+        ct: &'a Ciphertext,
+        ad: &[u8],
+    ) -> ShareFuture<'a, DecryptionShare> {
+        if !ct.verify_with_ad(ad) {
+            return Box::pin(async { Err(Error::InvalidCiphertext) });
+        }
+        self.decrypt_share_no_verify(ct)
+    }
+}
+
+impl AsyncShareSigner for InMemoryShare {
+    fn index(&self) -> usize {
+        crate::signer::IndexedShare::index(self)
+    }
+
+    fn sign_g2(&self, hash: G2Affine) -> ShareFuture<'_, SignatureShare> {
+        let result = crate::signer::ShareSigner::sign_g2(self, hash);
+        Box::pin(async move { result })
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl AsyncShareDecryptor for InMemoryShare {
+    fn index(&self) -> usize {
+        crate::signer::IndexedShare::index(self)
+    }
+
+    fn decrypt_share_no_verify<'a>(
+        &'a self,
+        ct: &'a Ciphertext,
+    ) -> ShareFuture<'a, DecryptionShare> {
+        let result = crate::signer::ShareDecryptor::decrypt_share_no_verify(self, ct);
+        Box::pin(async move { result })
+    }
+}
+
+/// A source of timeouts, usually backed by the application's async runtime.
+pub trait Timer {
+    /// Returns a future that completes after the given duration.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// A runtime-independent `Timer` that spawns a thread for each timeout.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ThreadTimer;
+
+impl Timer for ThreadTimer {
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let state: Arc<Mutex<(bool, Option<Waker>)>> = Arc::new(Mutex::new((false, None)));
+        let thread_state = state.clone();
+        thread::spawn(move || {
+            thread::sleep(duration);
+            let mut guard = thread_state.lock().expect("timer state poisoned");
+            guard.0 = true;
+            if let Some(waker) = guard.1.take() {
+                waker.wake();
+            }
+        });
+        Box::pin(poll_fn(move |cx: &mut Context<'_>| {
+            let mut guard = state.lock().expect("timer state poisoned");
+            if guard.0 {
+                Poll::Ready(())
+            } else {
+                guard.1 = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        }))
+    }
+}
+
+/// The reason a participant didn't contribute a share.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Failure {
+    /// The participant didn't respond before its timeout.
+    TimedOut,
+    /// The participant's backend failed, or its share was rejected by the session.
+    Rejected(Error),
+}
+
+/// The result of an asynchronous signing or decryption round.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Outcome<T> {
+    /// The combined result, or `Error::NotEnoughShares` if too few valid shares arrived.
+    pub result: Result<T>,
+    /// The participants that failed to contribute a valid share.
+    ///
+    /// Participants whose requests were still pending when the session completed are not
+    /// included.
+    pub failures: BTreeMap<usize, Failure>,
+}
+
+/// Requests signature shares from all `signers` concurrently, until the session is complete.
+///
+/// Each request is abandoned if it doesn't complete within `timeout`.
+pub async fn sign<S, T>(
+    session: &mut SigningSession,
+    signers: &[S],
+    timeout: Duration,
+    timer: &T,
+) -> Outcome<Signature>
+where
+    S: AsyncShareSigner,
+    T: Timer + ?Sized,
+{
+    let hash = session.hash();
+    let pending = signers
+        .iter()
+        .map(|signer| (signer.index(), signer.sign_g2(hash), timer.sleep(timeout)))
+        .collect();
+    let failures = collect(session, pending).await;
+    Outcome {
+        result: session.signature(),
+        failures,
+    }
+}
+
+#[cfg(feature = "encryption")]
+/// Requests decryption shares from all `decryptors` concurrently, until the session is complete.
+///
+/// The session has already validated the ciphertext for its associated data, so the shares are
+/// requested without validating it again. Each request is abandoned if it doesn't complete within `timeout`.
+pub async fn decrypt<D, T>(
+    session: &mut DecryptionSession,
+    decryptors: &[D],
+    timeout: Duration,
+    timer: &T,
+) -> Outcome<Vec<u8>>
+where
+    D: AsyncShareDecryptor,
+    T: Timer + ?Sized,
+{
+    let ct = session.ciphertext().clone();
+    let pending = decryptors
+        .iter()
+        .map(|dec| {
+            (
+                dec.index(),
+                dec.decrypt_share_no_verify(&ct),
+                timer.sleep(timeout),
+            )
+        })
+        .collect();
+    let failures = collect(session, pending).await;
+    Outcome {
+        result: session.decrypt(),
+        failures,
+    }
+}
+
+/// A session that accepts shares of type `V`.
+trait Collector<V> {
+    fn add(&mut self, i: usize, share: V) -> Result<bool>;
+    fn is_complete(&self) -> bool;
+}
+
+impl Collector<SignatureShare> for SigningSession {
+    fn add(&mut self, i: usize, share: SignatureShare) -> Result<bool> {
+        self.add_share(i, share)
+    }
+
+    fn is_complete(&self) -> bool {
+        SigningSession::is_complete(self)
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl Collector<DecryptionShare> for DecryptionSession {
+    fn add(&mut self, i: usize, share: DecryptionShare) -> Result<bool> {
+        self.add_share(i, share)
+    }
+
+    fn is_complete(&self) -> bool {
+        DecryptionSession::is_complete(self)
+    }
+}
+
+/// Polls all pending requests and their timeouts, adding shares to the session as they arrive.
+async fn collect<V, C: Collector<V>>(
+    session: &mut C,
+    mut pending: Vec<(usize, ShareFuture<'_, V>, Sleep)>,
+) -> BTreeMap<usize, Failure> {
+    let mut failures = BTreeMap::new();
+    poll_fn(|cx| {
+        let mut idx = 0;
+        while idx < pending.len() && !session.is_complete() {
+            let (i, request, sleep) = &mut pending[idx];
+            let i = *i;
+            if let Poll::Ready(result) = request.as_mut().poll(cx) {
+                drop(pending.swap_remove(idx));
+                if let Err(err) = result.and_then(|share| session.add(i, share)) {
+                    failures.insert(i, Failure::Rejected(err));
+                }
+                continue;
+            }
+            if sleep.as_mut().poll(cx).is_ready() {
+                drop(pending.swap_remove(idx));
+                failures.insert(i, Failure::TimedOut);
+                continue;
+            }
+            idx += 1;
+        }
+        if pending.is_empty() || session.is_complete() {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    })
+    .await;
+    failures
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::task::Wake;
+    use std::thread::Thread;
+
+    use crate::SecretKeySet;
+
+    /// Wakes a parked thread.
+    struct ThreadWaker(Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    /// Runs the future to completion on the current thread.
+    fn block_on<F: Future>(fut: F) -> F::Output {
+        let mut fut = Box::pin(fut);
+        let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+        let mut cx = Context::from_waker(&waker);
+        loop {
+            if let Poll::Ready(output) = fut.as_mut().poll(&mut cx) {
+                return output;
+            }
+            thread::park();
+        }
+    }
+
+    /// A participant that never responds.
+    struct Unresponsive(usize);
+
+    impl AsyncShareSigner for Unresponsive {
+        fn index(&self) -> usize {
+            self.0
+        }
+
+        fn sign_g2(&self, _hash: G2Affine) -> ShareFuture<'_, SignatureShare> {
+            Box::pin(std::future::pending())
+        }
+    }
+
+    /// A participant that is either an honest in-memory signer or unresponsive.
+    enum Participant {
+        Honest(InMemoryShare),
+        Silent(Unresponsive),
+    }
+
+    impl AsyncShareSigner for Participant {
+        fn index(&self) -> usize {
+            match self {
+                Participant::Honest(s) => AsyncShareSigner::index(s),
+                Participant::Silent(s) => s.index(),
+            }
+        }
+
+        fn sign_g2(&self, hash: G2Affine) -> ShareFuture<'_, SignatureShare> {
+            match self {
+                Participant::Honest(s) => AsyncShareSigner::sign_g2(s, hash),
+                Participant::Silent(s) => s.sign_g2(hash),
+            }
+        }
+    }
+
+    #[test]
+    fn test_async_sign_with_timeouts() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msg = "Asynchronously signed";
+        let participants: Vec<_> = (0..5)
+            .map(|i| {
+                if i < 2 {
+                    Participant::Silent(Unresponsive(i))
+                } else {
+                    Participant::Honest(InMemoryShare::new(i, sk_set.secret_key_share(i)))
+                }
+            })
+            .collect();
+
+        // The round completes as soon as three valid shares arrived, without waiting for the
+        // unresponsive participants to time out.
+        let mut session = SigningSession::new(pk_set.clone(), msg);
+        let timeout = Duration::from_secs(60);
+        let outcome = block_on(sign(&mut session, &participants, timeout, &ThreadTimer));
+        let sig = outcome.result.expect("three honest participants");
+        assert!(pk_set.public_key().verify(&sig, msg));
+        assert!(outcome.failures.is_empty());
+
+        // With only two honest participants, the round fails once the others time out.
+        let mut session = SigningSession::new(pk_set, msg);
+        let timeout = Duration::from_millis(10);
+        let outcome = block_on(sign(
+            &mut session,
+            &participants[..4],
+            timeout,
+            &ThreadTimer,
+        ));
+        assert_eq!(Err(Error::NotEnoughShares), outcome.result);
+        assert_eq!(Some(&Failure::TimedOut), outcome.failures.get(&0));
+        assert_eq!(Some(&Failure::TimedOut), outcome.failures.get(&1));
+        assert_eq!(2, outcome.failures.len());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_async_decrypt() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msg = b"Asynchronously decrypted";
+        let ct = pk_set.public_key().encrypt(msg);
+        let holders: Vec<_> = (0..3)
+            .map(|i| InMemoryShare::new(i, sk_set.secret_key_share(i)))
+            .collect();
+
+        let mut session = DecryptionSession::new(pk_set, ct).expect("valid ciphertext");
+        let timeout = Duration::from_secs(10);
+        let outcome = block_on(decrypt(&mut session, &holders, timeout, &ThreadTimer));
+        assert_eq!(msg[..], outcome.result.expect("enough shares")[..]);
+        assert!(outcome.failures.is_empty());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_async_decrypt_with_ad() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msg = b"Asynchronously decrypted";
+        let ct = pk_set.public_key().encrypt_with_ad(msg, "epoch 1");
+        let holders: Vec<_> = (0..3)
+            .map(|i| InMemoryShare::new(i, sk_set.secret_key_share(i)))
+            .collect();
+        let result = block_on(holders[0].decrypt_share_with_ad(&ct, b"epoch 2"));
+        assert_eq!(Err(Error::InvalidCiphertext), result);
+        assert!(block_on(holders[0].decrypt_share_with_ad(&ct, b"epoch 1")).is_ok());
+
+        let mut session =
+            DecryptionSession::new_with_ad(pk_set, ct, "epoch 1").expect("valid ciphertext");
+        let timeout = Duration::from_secs(10);
+        let outcome = block_on(decrypt(&mut session, &holders, timeout, &ThreadTimer));
+        assert_eq!(msg[..], outcome.result.expect("enough shares")[..]);
+        assert!(outcome.failures.is_empty());
+    }
+}
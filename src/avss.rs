@@ -0,0 +1,486 @@
+//! Asynchronous verifiable secret sharing.
+//!
+//! An [`Avss`] instance runs the protocol by Cachin, Kursawe, Lysyanskaya and Strobl among `n`
+//! nodes, `n > 3 * threshold`, in which a dealer shares a `SecretKey` so that every honest node
+//! eventually outputs the same `PublicKeySet` and its own `SecretKeyShare`, even if messages are
+//! delayed arbitrarily, and without relying on a reliable broadcast. Messages are sent point to
+//! point, and must be delivered over private, authenticated channels:
+//!
+//! 1. The dealer creates a random symmetric bivariate polynomial `f` with `f(0, 0)` the secret,
+//!    and sends each node `i` a [`Message::Send`] with the commitment to `f` and its row
+//!    `f(i, _)`.
+//! 2. Each node that receives a valid row sends each node `j` a [`Message::Echo`] with the value
+//!    `f(i, j)`.
+//! 3. Once a node received `⌈(n + threshold + 1) / 2⌉` valid echoes, or `threshold + 1` valid
+//!    readies, for the same commitment, it interpolates its row from them and sends each node `j`
+//!    a [`Message::Ready`] with the value `f(i, j)`.
+//! 4. Once a node received `2 * threshold + 1` valid readies, it outputs its share `f(i, 0)`.
+//!
+//! Node `i` receives share `i` of the dealt key set, and nodes must handle their own messages,
+//! too. If the dealer is faulty, either no honest node or all of them terminate.
+//!
+//! ```
+//! use threshold_crypto::avss::Avss;
+//! use threshold_crypto::SecretKey;
+//!
+//! let mut rng = rand::thread_rng();
+//! let mut nodes: Vec<Avss> = (0..4).map(|i| Avss::new(i, 0, 4, 1).unwrap()).collect();
+//! let secret = SecretKey::random();
+//! let mut queue: Vec<_> = nodes[0].deal(&secret, &mut rng).unwrap().into_iter().map(|(to, msg)| (0, to, msg)).collect();
+//! while let Some((from, to, msg)) = queue.pop() {
+//!     let step = nodes[to].handle_message(from, msg).unwrap();
+//!     assert_eq!(None, step.fault);
+//!     queue.extend(step.messages.into_iter().map(|(next, msg)| (to, next, msg)));
+//! }
+//!
+//! let (pk_set, share) = nodes[2].output().expect("terminated");
+//! assert_eq!(secret.public_key(), pk_set.public_key());
+//! assert_eq!(pk_set.public_key_share(2), share.public_key_share());
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use group::CurveAffine;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::poly::{BivarCommitment, BivarPoly, Poly};
+use crate::secret::clear_fr;
+use crate::{Fr, G1Affine, PublicKeySet, SecretKey, SecretKeyShare};
+
+/// A message of the protocol, sent to a single node.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub enum Message {
+    /// The dealer's commitment and the recipient's row.
+    Send {
+        /// The commitment to the dealer's polynomial.
+        commit: BivarCommitment,
+        /// The row `f(i, _)` of recipient `i`.
+        row: Poly,
+    },
+    /// A node's confirmation that it received a valid row.
+    Echo {
+        /// The commitment to the dealer's polynomial.
+        commit: BivarCommitment,
+        /// The value `f(i, j)` of sender `i`'s row at recipient `j`.
+        #[serde(with = "crate::serde_impl::field_element")]
+        value: Fr,
+    },
+    /// A node's confirmation that enough nodes received valid rows.
+    Ready {
+        /// The commitment to the dealer's polynomial.
+        commit: BivarCommitment,
+        /// The value `f(i, j)` of sender `i`'s row at recipient `j`.
+        #[serde(with = "crate::serde_impl::field_element")]
+        value: Fr,
+    },
+}
+
+impl Message {
+    /// Returns the commitment to the dealer's polynomial.
+    pub fn commitment(&self) -> &BivarCommitment {
+        match self {
+            Message::Send { commit, .. }
+            | Message::Echo { commit, .. }
+            | Message::Ready { commit, .. } => commit,
+        }
+    }
+}
+
+/// A debug statement where the rows and values have been redacted.
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Message::Send { .. } => "Send",
+            Message::Echo { .. } => "Echo",
+            Message::Ready { .. } => "Ready",
+        };
+        f.debug_struct(name)
+            .field("commit", self.commitment())
+            .finish()
+    }
+}
+
+/// The messages a node must send after handling a message, and the sender's fault, if any.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Step {
+    /// The messages to send, with the indices of their recipients.
+    pub messages: Vec<(usize, Message)>,
+    /// The reason why the handled message was invalid.
+    pub fault: Option<Fault>,
+}
+
+impl Step {
+    /// Returns a step without messages, in which the sender is faulty.
+    fn fault(fault: Fault) -> Self {
+        Step {
+            messages: Vec::new(),
+            fault: Some(fault),
+        }
+    }
+}
+
+/// A reason why a message is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// A `Send` message is not from the dealer.
+    NotDealer,
+    /// The sender has already sent a message of this kind.
+    DuplicateMessage,
+    /// The commitment's degree is not the threshold.
+    DegreeMismatch,
+    /// The row doesn't match the commitment.
+    InvalidRow,
+    /// The value doesn't match the commitment.
+    InvalidValue,
+}
+
+/// The valid echoes and readies received for a commitment.
+#[derive(Clone, Default)]
+struct Votes {
+    /// The echoed values `f(s, our_idx + 1)` at `s = sender + 1`.
+    echoes: BTreeMap<u64, Fr>,
+    /// The values `f(s, our_idx + 1)` at `s = sender + 1` sent in readies.
+    readies: BTreeMap<u64, Fr>,
+}
+
+impl Drop for Votes {
+    fn drop(&mut self) {
+        for value in self.echoes.values_mut().chain(self.readies.values_mut()) {
+            clear_fr(value);
+        }
+    }
+}
+
+/// A node's state in an asynchronous verifiable secret sharing.
+#[derive(Clone)]
+pub struct Avss {
+    our_idx: usize,
+    dealer: usize,
+    num_nodes: usize,
+    threshold: usize,
+    /// Whether this node has received the dealer's `Send` and echoed it.
+    echoed: bool,
+    /// Whether this node has sent its readies.
+    ready: bool,
+    /// The nodes whose echoes and readies were handled.
+    echo_senders: BTreeSet<usize>,
+    ready_senders: BTreeSet<usize>,
+    votes: BTreeMap<BivarCommitment, Votes>,
+    output: Option<(PublicKeySet, SecretKeyShare)>,
+}
+
+impl fmt::Debug for Avss {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Avss")
+            .field("our_idx", &self.our_idx)
+            .field("dealer", &self.dealer)
+            .field("num_nodes", &self.num_nodes)
+            .field("threshold", &self.threshold)
+            .field("echoed", &self.echoed)
+            .field("ready", &self.ready)
+            .field("terminated", &self.output.is_some())
+            .finish()
+    }
+}
+
+impl Avss {
+    /// Creates the state of node `our_idx` in a sharing by `dealer` among `num_nodes` nodes, any
+    /// `threshold + 1` of which can sign and decrypt with the dealt shares.
+    ///
+    /// Returns `Error::NotEnoughShares` if there are too few nodes to tolerate `threshold` faulty
+    /// ones, or `Error::UnknownParticipant` if one of the indices is not a node's.
+    pub fn new(our_idx: usize, dealer: usize, num_nodes: usize, threshold: usize) -> Result<Self> {
+        if num_nodes <= 3 * threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        if our_idx >= num_nodes || dealer >= num_nodes {
+            return Err(Error::UnknownParticipant);
+        }
+        Ok(Avss {
+            our_idx,
+            dealer,
+            num_nodes,
+            threshold,
+            echoed: false,
+            ready: false,
+            echo_senders: BTreeSet::new(),
+            ready_senders: BTreeSet::new(),
+            votes: BTreeMap::new(),
+            output: None,
+        })
+    }
+
+    /// Returns this node's index.
+    pub fn our_idx(&self) -> usize {
+        self.our_idx
+    }
+
+    /// Returns the dealer's index.
+    pub fn dealer(&self) -> usize {
+        self.dealer
+    }
+
+    /// Returns the threshold of the dealt key set.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Shares the secret key, and returns the `Send` messages for all nodes. Returns
+    /// `Error::UnknownParticipant` if this node is not the dealer.
+    pub fn deal<R: Rng>(&self, secret: &SecretKey, rng: &mut R) -> Result<Vec<(usize, Message)>> {
+        if self.our_idx != self.dealer {
+            return Err(Error::UnknownParticipant);
+        }
+        let poly = BivarPoly::with_secret(secret.0, self.threshold, rng);
+        let commit = poly.commitment();
+        let messages = (0..self.num_nodes)
+            .map(|idx| {
+                let row = poly.row(idx + 1);
+                let commit = commit.clone();
+                (idx, Message::Send { commit, row })
+            })
+            .collect();
+        Ok(messages)
+    }
+
+    /// Handles a message from node `sender`, and returns the messages to send in response.
+    /// Returns `Error::UnknownParticipant` if the sender is not one of the nodes.
+    pub fn handle_message(&mut self, sender: usize, msg: Message) -> Result<Step> {
+        if sender >= self.num_nodes {
+            return Err(Error::UnknownParticipant);
+        }
+        if msg.commitment().degree() != self.threshold {
+            return Ok(Step::fault(Fault::DegreeMismatch));
+        }
+        Ok(match msg {
+            Message::Send { commit, row } => self.handle_send(sender, commit, row),
+            Message::Echo { commit, value } => self.handle_echo(sender, commit, value),
+            Message::Ready { commit, value } => self.handle_ready(sender, commit, value),
+        })
+    }
+
+    /// Returns `true` if this node has output its share.
+    pub fn is_terminated(&self) -> bool {
+        self.output.is_some()
+    }
+
+    /// Returns the dealt public key set and this node's secret key share, once it has
+    /// terminated.
+    pub fn output(&self) -> Option<(PublicKeySet, SecretKeyShare)> {
+        self.output.clone()
+    }
+
+    /// Handles the dealer's commitment and our row, and echoes it.
+    fn handle_send(&mut self, sender: usize, commit: BivarCommitment, row: Poly) -> Step {
+        if sender != self.dealer {
+            return Step::fault(Fault::NotDealer);
+        }
+        if self.echoed {
+            return Step::fault(Fault::DuplicateMessage);
+        }
+        if row.coeff.len() != self.threshold + 1 || row.commitment() != commit.row(self.our_idx + 1)
+        {
+            return Step::fault(Fault::InvalidRow);
+        }
+        self.echoed = true;
+        let messages = self.send_values(&commit, &row, |commit, value| Message::Echo {
+            commit,
+            value,
+        });
+        Step {
+            messages,
+            fault: None,
+        }
+    }
+
+    /// Handles an echo, and sends our readies once enough nodes echoed the commitment.
+    fn handle_echo(&mut self, sender: usize, commit: BivarCommitment, value: Fr) -> Step {
+        if let Some(fault) = self.check_value(&self.echo_senders, sender, &commit, &value) {
+            return Step::fault(fault);
+        }
+        self.echo_senders.insert(sender);
+        let votes = self.votes.entry(commit.clone()).or_default();
+        votes.echoes.insert(sender as u64 + 1, value);
+        let count = votes.echoes.len();
+        let mut step = Step::default();
+        if !self.ready && count >= self.echo_threshold() {
+            step.messages = self.send_readies(&commit);
+        }
+        step
+    }
+
+    /// Handles a ready, and sends our readies or terminates once enough nodes sent theirs.
+    fn handle_ready(&mut self, sender: usize, commit: BivarCommitment, value: Fr) -> Step {
+        if let Some(fault) = self.check_value(&self.ready_senders, sender, &commit, &value) {
+            return Step::fault(fault);
+        }
+        self.ready_senders.insert(sender);
+        let votes = self.votes.entry(commit.clone()).or_default();
+        votes.readies.insert(sender as u64 + 1, value);
+        let count = votes.readies.len();
+        let mut step = Step::default();
+        if !self.ready && count > self.threshold {
+            step.messages = self.send_readies(&commit);
+        }
+        if self.output.is_none() && count > 2 * self.threshold {
+            let row = self.interpolate_row(&self.votes[&commit].readies);
+            let pk_set = PublicKeySet::from(commit.row(0));
+            let mut value = row.evaluate(0);
+            self.output = Some((pk_set, SecretKeyShare::from_mut(&mut value)));
+        }
+        step
+    }
+
+    /// Returns the fault of the sender's echo or ready, or `None` if it is valid.
+    fn check_value(
+        &self,
+        senders: &BTreeSet<usize>,
+        sender: usize,
+        commit: &BivarCommitment,
+        value: &Fr,
+    ) -> Option<Fault> {
+        if senders.contains(&sender) {
+            return Some(Fault::DuplicateMessage);
+        }
+        let expected = commit.evaluate(self.our_idx as u64 + 1, sender as u64 + 1);
+        if G1Affine::one().mul(*value) != expected {
+            return Some(Fault::InvalidValue);
+        }
+        None
+    }
+
+    /// Interpolates our row from the valid echoes or readies, and sends our readies.
+    fn send_readies(&mut self, commit: &BivarCommitment) -> Vec<(usize, Message)> {
+        self.ready = true;
+        let votes = &self.votes[commit];
+        let values = if votes.readies.len() > self.threshold {
+            &votes.readies
+        } else {
+            &votes.echoes
+        };
+        let row = self.interpolate_row(values);
+        self.send_values(commit, &row, |commit, value| Message::Ready {
+            commit,
+            value,
+        })
+    }
+
+    /// Returns the messages containing the row's value at each node.
+    fn send_values<F>(&self, commit: &BivarCommitment, row: &Poly, f: F) -> Vec<(usize, Message)>
+    where
+        F: Fn(BivarCommitment, Fr) -> Message,
+    {
+        (0..self.num_nodes)
+            .map(|idx| (idx, f(commit.clone(), row.evaluate(idx + 1))))
+            .collect()
+    }
+
+    /// Returns our row, interpolated from `threshold + 1` of the values.
+    fn interpolate_row(&self, values: &BTreeMap<u64, Fr>) -> Poly {
+        let samples = values.iter().take(self.threshold + 1);
+        Poly::interpolate(samples.map(|(x, value)| (*x, *value)))
+    }
+
+    /// Returns the number of echoes needed to send our readies: `⌈(n + threshold + 1) / 2⌉`.
+    fn echo_threshold(&self) -> usize {
+        (self.num_nodes + self.threshold + 2) / 2
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::simulator::{Network, Node, Outgoing, Target};
+
+    /// A node that deals or receives a share, for running the protocol in a simulated network.
+    #[derive(Debug)]
+    struct AvssNode {
+        avss: Avss,
+        secret: Option<SecretKey>,
+        faults: Vec<Fault>,
+    }
+
+    impl Node for AvssNode {
+        type Message = Message;
+        type Output = (PublicKeySet, SecretKeyShare);
+
+        fn start(&mut self) -> Vec<Outgoing<Message>> {
+            let secret = match &self.secret {
+                Some(secret) => secret,
+                None => return Vec::new(),
+            };
+            let messages = self.avss.deal(secret, &mut rand::thread_rng());
+            let messages = messages.expect("dealer");
+            messages
+                .into_iter()
+                .map(|(to, msg)| (Target::Node(to), msg))
+                .collect()
+        }
+
+        fn handle_message(&mut self, from: usize, msg: Message) -> Vec<Outgoing<Message>> {
+            let step = self.avss.handle_message(from, msg).expect("known sender");
+            self.faults.extend(step.fault);
+            step.messages
+                .into_iter()
+                .map(|(to, msg)| (Target::Node(to), msg))
+                .collect()
+        }
+
+        fn output(&self) -> Option<Self::Output> {
+            self.avss.output()
+        }
+    }
+
+    #[test]
+    fn test_avss() {
+        let secret = SecretKey::random();
+        let nodes = (0..7)
+            .map(|i| AvssNode {
+                avss: Avss::new(i, 3, 7, 2).expect("valid parameters"),
+                secret: Some(secret.clone()).filter(|_| i == 3),
+                faults: Vec::new(),
+            })
+            .collect();
+        let mut network = Network::new(nodes, 7).with_reordering();
+        // Two crashed nodes don't prevent the others from terminating.
+        network.crash(5);
+        network.crash(6);
+        network.run(10_000);
+        assert!(network.is_terminated());
+
+        let outputs = network.outputs();
+        let (pk_set, _) = outputs[0].clone().expect("terminated");
+        assert_eq!(secret.public_key(), pk_set.public_key());
+        for (i, output) in outputs.into_iter().enumerate().take(5) {
+            let (node_pk_set, share) = output.expect("terminated");
+            assert_eq!(pk_set, node_pk_set);
+            assert_eq!(pk_set.public_key_share(i), share.public_key_share());
+            assert!(network.nodes()[i].faults.is_empty());
+        }
+
+        // Invalid and repeated messages are faults.
+        let mut rng = rand::thread_rng();
+        let dealer = Avss::new(3, 3, 7, 2).expect("valid parameters");
+        let mut sends = dealer.deal(&secret, &mut rng).expect("dealer");
+        let mut avss = Avss::new(0, 3, 7, 2).expect("valid parameters");
+        let (_, send) = sends.swap_remove(1);
+        let step = avss.handle_message(3, send).expect("known sender");
+        assert_eq!(Some(Fault::InvalidRow), step.fault);
+        let (_, send) = sends.swap_remove(0);
+        let step = avss.handle_message(2, send.clone()).expect("known sender");
+        assert_eq!(Some(Fault::NotDealer), step.fault);
+        let mut step = avss.handle_message(3, send.clone()).expect("known sender");
+        assert_eq!((7, None), (step.messages.len(), step.fault));
+        let step_dup = avss.handle_message(3, send).expect("known sender");
+        assert_eq!(Some(Fault::DuplicateMessage), step_dup.fault);
+        let (_, echo) = step.messages.swap_remove(0);
+        assert_eq!(None, avss.handle_message(0, echo.clone()).unwrap().fault);
+        let step = avss.handle_message(1, echo).expect("known sender");
+        assert_eq!(Some(Fault::InvalidValue), step.fault);
+        assert_eq!(Some(Error::UnknownParticipant), Avss::new(0, 7, 7, 2).err());
+    }
+}
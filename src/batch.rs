@@ -0,0 +1,499 @@
+//! Batch verification of signatures, signature shares and decryption shares.
+//!
+//! Verifying a signature takes two pairings, so checking hundreds of them, e.g. all signatures
+//! in a block, is expensive. Instead, they can be checked together: with coefficients `r_i`
+//! derived by hashing the whole batch, `e(g1, sum(r_i * sig_i))` must equal the product of the
+//! `e(r_i * pk_i, hash(msg_i))`, which is a single multi-pairing. Public keys that signed the same
+//! message are combined first, so that each distinct message costs one Miller loop. If any of the
+//! signatures is invalid, the batch passes only with negligible probability.
+//!
+//! Signature shares for the same message are checked the same way, with two pairings in total.
+//! If the batch is invalid, `PublicKeySet::invalid_signature_shares` falls back to checking the
+//! shares individually, to identify the ones to discard and whose senders to penalize.
+//!
+//! Decryption shares for a block of ciphertexts are checked with one multi-pairing per side: The
+//! random linear combination of the shares of each ciphertext is paired with that ciphertext's
+//! hash, and each public key share with the combination of the ciphertexts it decrypted. That is
+//! the number of ciphertexts plus the number of key shares, instead of two pairings per share.
+//! The `_with_ad` variants check shares of ciphertexts that are bound to associated data.
+//!
+//! ```
+//! use threshold_crypto::{batch, SecretKey};
+//!
+//! let sec_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+//! let pub_keys: Vec<_> = sec_keys.iter().map(SecretKey::public_key).collect();
+//! let msgs = ["a", "b", "a"];
+//! let sigs: Vec<_> = sec_keys.iter().zip(&msgs).map(|(sk, msg)| sk.sign(msg)).collect();
+//! let items = pub_keys.iter().zip(&msgs).zip(&sigs).map(|((pk, msg), sig)| (pk, msg, sig));
+//! assert!(batch::verify_signatures(items));
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use pairing::{Engine, PairingCurveAffine};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+use crate::util::sha3_256;
+#[cfg(feature = "encryption")]
+use crate::{hash_g1_g2_ad, Ciphertext, DecryptionShare, PublicKeyShare};
+use crate::{
+    hash_g2, hash_g2_digest, Fr, G1Affine, PEngine, PublicKey, PublicKeySet, Signature,
+    SignatureShare, G1, G2,
+};
+
+/// The domain separator for the batch verification coefficients.
+const DST: &[u8] = b"threshold_crypto batch verification";
+
+impl PublicKey {
+    /// Returns `true` if each signature matches its public key and message.
+    ///
+    /// This is equivalent to `verify_signatures(items)`, and much faster than verifying the
+    /// signatures one by one. An empty batch is valid.
+    pub fn verify_batch<'a, M, I>(items: I) -> bool
+    where
+        M: AsRef<[u8]>,
+        I: IntoIterator<Item = (&'a PublicKey, M, &'a Signature)>,
+    {
+        verify_signatures(items)
+    }
+}
+
+impl PublicKeySet {
+    /// Returns the indices of the signature shares that don't match the message.
+    ///
+    /// The shares are verified together first, and only if that fails, one by one.
+    pub fn invalid_signature_shares<M: AsRef<[u8]>>(
+        &self,
+        shares: &BTreeMap<usize, SignatureShare>,
+        msg: M,
+    ) -> BTreeSet<usize> {
+        let msg = msg.as_ref();
+        if verify_signature_shares(self, shares, msg) {
+            return BTreeSet::new();
+        }
+        let hash = hash_g2(msg);
+        let is_invalid = |(i, share): &(&usize, &SignatureShare)| {
+            !self.public_key_share(**i).verify_g2(share, hash)
+        };
+        shares.iter().filter(is_invalid).map(|(i, _)| *i).collect()
+    }
+
+    /// Returns the positions of the ciphertexts and indices of the decryption shares that don't
+    /// match.
+    ///
+    /// Each item contains a ciphertext and its decryption shares by index. The shares are
+    /// verified together first, and only if that fails, one by one.
+    #[cfg(feature = "encryption")]
+    pub fn invalid_decryption_shares(
+        &self,
+        items: &[(&Ciphertext, &BTreeMap<usize, DecryptionShare>)],
+    ) -> BTreeSet<(usize, usize)> {
+        self.invalid_decryption_shares_with_ad(&without_ad(items))
+    }
+
+    /// Returns the positions of the ciphertexts and indices of the decryption shares that don't
+    /// match.
+    ///
+    /// Each item contains a ciphertext, its associated data and its decryption shares by index.
+    #[cfg(feature = "encryption")]
+    pub fn invalid_decryption_shares_with_ad(
+        &self,
+        items: &[(&Ciphertext, &[u8], &BTreeMap<usize, DecryptionShare>)],
+    ) -> BTreeSet<(usize, usize)> {
+        if verify_decryption_shares_with_ad(self, items) {
+            return BTreeSet::new();
+        }
+        let mut invalid = BTreeSet::new();
+        for (pos, (ct, ad, shares)) in items.iter().enumerate() {
+            for (i, share) in *shares {
+                let pk_share = self.public_key_share(*i);
+                if !pk_share.verify_decryption_share_with_ad(share, ct, ad) {
+                    invalid.insert((pos, *i));
+                }
+            }
+        }
+        invalid
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl PublicKeyShare {
+    /// Returns `true` if each decryption share matches its ciphertext.
+    ///
+    /// This is equivalent to calling `verify_decryption_share` for each of them, but takes only
+    /// one pairing per ciphertext, plus one.
+    pub fn verify_decryption_shares<'a, I>(&self, items: I) -> bool
+    where
+        I: IntoIterator<Item = (&'a DecryptionShare, &'a Ciphertext)>,
+    {
+        let items = items.into_iter().map(|(share, ct)| (share, ct, &[][..]));
+        self.verify_decryption_shares_with_ad(items)
+    }
+
+    /// Returns `true` if each decryption share matches its ciphertext with the associated data.
+    pub fn verify_decryption_shares_with_ad<'a, I>(&self, items: I) -> bool
+    where
+        I: IntoIterator<Item = (&'a DecryptionShare, &'a Ciphertext, &'a [u8])>,
+    {
+        let _span = span!("verify_decryption_shares");
+        let mut context = DST.to_vec();
+        context.extend(self.to_bytes().as_ref());
+        let mut entries = Vec::new();
+        for (pos, (share, ct, ad)) in items.into_iter().enumerate() {
+            context.extend(ct.to_bytes());
+            extend_ad(&mut context, ad);
+            context.extend(share.to_bytes().as_ref());
+            entries.push((0, (self.0).0, pos, ct, ad, share));
+        }
+        verify_decryption(&context, entries)
+    }
+}
+
+/// Returns `true` if each signature matches its public key and message.
+///
+/// If this returns `false`, at least one of the signatures is invalid, and they have to be
+/// verified individually to find out which.
+pub fn verify_signatures<'a, M, I>(items: I) -> bool
+where
+    M: AsRef<[u8]>,
+    I: IntoIterator<Item = (&'a PublicKey, M, &'a Signature)>,
+{
+    let _span = span!("verify_batch");
+    let mut context = DST.to_vec();
+    let mut triples = Vec::new();
+    for (pk, msg, sig) in items {
+        let digest = sha3_256(msg.as_ref());
+        context.extend(pk.to_bytes().as_ref());
+        context.extend(digest.as_ref());
+        context.extend(sig.to_bytes().as_ref());
+        triples.push((pk.0, digest, sig.0));
+    }
+    let mut rng = ChaChaRng::from_seed(sha3_256(&context));
+    // The combined signature, and the combined public keys by message.
+    count!(Exponentiation, 2 * triples.len());
+    let mut combined_sig = G2::zero();
+    let mut by_msg: BTreeMap<[u8; 32], G1> = BTreeMap::new();
+    for (mut pk, digest, mut sig) in triples {
+        let r = Fr::random(&mut rng);
+        pk.mul_assign(r);
+        sig.mul_assign(r);
+        combined_sig.add_assign(&sig);
+        by_msg
+            .entry(digest)
+            .or_insert_with(G1::zero)
+            .add_assign(&pk);
+    }
+    count!(Pairing, by_msg.len() + 1);
+    let pairs = by_msg
+        .iter()
+        .map(|(digest, pk)| (*pk, hash_g2_digest(digest)));
+    multi_pairing(pairs) == Some(PEngine::pairing(G1::one(), combined_sig))
+}
+
+/// Returns `true` if each signature share matches the message and the public key share with its
+/// index.
+///
+/// If this returns `false`, use `PublicKeySet::invalid_signature_shares` to find out which of the
+/// shares are invalid.
+pub fn verify_signature_shares<M: AsRef<[u8]>>(
+    pk_set: &PublicKeySet,
+    shares: &BTreeMap<usize, SignatureShare>,
+    msg: M,
+) -> bool {
+    let _span = span!("verify_shares_batch", shares = shares.len());
+    let digest = sha3_256(msg.as_ref());
+    let mut context = DST.to_vec();
+    context.extend(pk_set.to_bytes());
+    context.extend(digest.as_ref());
+    for (i, share) in shares {
+        context.extend(&(*i as u64).to_be_bytes());
+        context.extend(share.to_bytes().as_ref());
+    }
+    let mut rng = ChaChaRng::from_seed(sha3_256(&context));
+    count!(Exponentiation, 2 * shares.len());
+    let mut combined_pk = G1::zero();
+    let mut combined_sig = G2::zero();
+    for (i, share) in shares {
+        let r = Fr::random(&mut rng);
+        let mut pk = (pk_set.public_key_share(*i).0).0;
+        pk.mul_assign(r);
+        combined_pk.add_assign(&pk);
+        let mut sig = (share.0).0;
+        sig.mul_assign(r);
+        combined_sig.add_assign(&sig);
+    }
+    count!(Pairing, 2);
+    PEngine::pairing(combined_pk, hash_g2_digest(&digest))
+        == PEngine::pairing(G1Affine::one(), combined_sig)
+}
+
+/// Returns `true` if each decryption share matches its ciphertext and the public key share with
+/// its index.
+///
+/// Each item contains a ciphertext and its decryption shares by index. If this returns `false`,
+/// use `PublicKeySet::invalid_decryption_shares` to find out which of the shares are invalid.
+#[cfg(feature = "encryption")]
+pub fn verify_decryption_shares(
+    pk_set: &PublicKeySet,
+    items: &[(&Ciphertext, &BTreeMap<usize, DecryptionShare>)],
+) -> bool {
+    verify_decryption_shares_with_ad(pk_set, &without_ad(items))
+}
+
+/// Returns `true` if each decryption share matches its ciphertext with the associated data, and
+/// the public key share with its index.
+///
+/// Each item contains a ciphertext, its associated data and its decryption shares by index.
+#[cfg(feature = "encryption")]
+pub fn verify_decryption_shares_with_ad(
+    pk_set: &PublicKeySet,
+    items: &[(&Ciphertext, &[u8], &BTreeMap<usize, DecryptionShare>)],
+) -> bool {
+    let _span = span!("verify_decryption_shares_batch", ciphertexts = items.len());
+    let mut context = DST.to_vec();
+    context.extend(pk_set.to_bytes());
+    let mut pk_shares = BTreeMap::new();
+    let mut entries = Vec::new();
+    for (pos, (ct, ad, shares)) in items.iter().enumerate() {
+        context.extend(ct.to_bytes());
+        extend_ad(&mut context, ad);
+        for (i, share) in *shares {
+            context.extend(&(*i as u64).to_be_bytes());
+            context.extend(share.to_bytes().as_ref());
+            let pk = *pk_shares
+                .entry(*i)
+                .or_insert_with(|| (pk_set.public_key_share(*i).0).0);
+            entries.push((*i, pk, pos, *ct, *ad, share));
+        }
+    }
+    verify_decryption(&context, entries)
+}
+
+/// Returns the items with empty associated data.
+#[cfg(feature = "encryption")]
+fn without_ad<'a>(
+    items: &[(&'a Ciphertext, &'a BTreeMap<usize, DecryptionShare>)],
+) -> Vec<(
+    &'a Ciphertext,
+    &'a [u8],
+    &'a BTreeMap<usize, DecryptionShare>,
+)> {
+    items
+        .iter()
+        .map(|(ct, shares)| (*ct, &[][..], *shares))
+        .collect()
+}
+
+/// Appends the associated data to the context, if there is any, so that contexts without it are
+/// unchanged.
+#[cfg(feature = "encryption")]
+fn extend_ad(context: &mut Vec<u8>, ad: &[u8]) {
+    if !ad.is_empty() {
+        context.extend(&(ad.len() as u64).to_be_bytes());
+        context.extend(ad);
+    }
+}
+
+/// The index and value of a public key share, the position of a ciphertext, the ciphertext, its
+/// associated data and the decryption share.
+#[cfg(feature = "encryption")]
+type Entry<'a> = (
+    usize,
+    G1,
+    usize,
+    &'a Ciphertext,
+    &'a [u8],
+    &'a DecryptionShare,
+);
+
+/// Returns `true` if the random linear combination of the decryption shares is valid.
+///
+/// The coefficients are derived from the `context`, which must contain all entries.
+#[cfg(feature = "encryption")]
+fn verify_decryption(context: &[u8], entries: Vec<Entry<'_>>) -> bool {
+    let mut rng = ChaChaRng::from_seed(sha3_256(context));
+    count!(Exponentiation, 2 * entries.len());
+    // The hash and combined shares by ciphertext, and the combined `W`s by public key share.
+    let mut by_ct: BTreeMap<usize, (G2, G1)> = BTreeMap::new();
+    let mut by_pk: BTreeMap<usize, (G1, G2)> = BTreeMap::new();
+    for (i, pk, pos, ct, ad, share) in entries {
+        let Ciphertext(ref u, ref v, ref w) = *ct;
+        let r = Fr::random(&mut rng);
+        let mut share = share.0;
+        share.mul_assign(r);
+        let ct_entry = by_ct
+            .entry(pos)
+            .or_insert_with(|| (hash_g1_g2_ad(*u, v, ad), G1::zero()));
+        ct_entry.1.add_assign(&share);
+        let mut w = *w;
+        w.mul_assign(r);
+        by_pk.entry(i).or_insert((pk, G2::zero())).1.add_assign(&w);
+    }
+    count!(Pairing, by_ct.len() + by_pk.len());
+    let lhs = multi_pairing(by_ct.values().map(|(hash, share)| (*share, *hash)));
+    lhs == multi_pairing(by_pk.values().cloned())
+}
+
+/// Returns the product of the pairings, computed with a single final exponentiation.
+pub(crate) fn multi_pairing<I>(pairs: I) -> Option<<PEngine as Engine>::Fqk>
+where
+    I: IntoIterator<Item = (G1, G2)>,
+{
+    let prepared: Vec<_> = pairs
+        .into_iter()
+        .map(|(p, q)| (p.into_affine().prepare(), q.into_affine().prepare()))
+        .collect();
+    let refs: Vec<_> = prepared.iter().map(|(p, q)| (p, q)).collect();
+    PEngine::final_exponentiation(&PEngine::miller_loop(&refs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{SecretKey, SecretKeySet};
+
+    #[test]
+    fn test_verify_signatures() {
+        let sec_keys: Vec<SecretKey> = (0..4).map(|_| SecretKey::random()).collect();
+        let pub_keys: Vec<PublicKey> = sec_keys.iter().map(SecretKey::public_key).collect();
+        let msgs = ["foo", "bar", "foo", "baz"];
+        let mut sigs: Vec<Signature> = sec_keys
+            .iter()
+            .zip(&msgs)
+            .map(|(sk, msg)| sk.sign(msg))
+            .collect();
+        let items = |sigs: &[Signature]| -> Vec<(PublicKey, &str, Signature)> {
+            let triples = pub_keys.iter().zip(&msgs).zip(sigs);
+            triples
+                .map(|((pk, msg), sig)| (*pk, *msg, sig.clone()))
+                .collect()
+        };
+        let valid = items(&sigs);
+        assert!(PublicKey::verify_batch(
+            valid.iter().map(|(pk, msg, sig)| (pk, msg, sig))
+        ));
+        assert!(verify_signatures(
+            Vec::<(&PublicKey, &str, &Signature)>::new()
+        ));
+
+        // A signature for a different message is detected.
+        sigs[2] = sec_keys[2].sign("bar");
+        let invalid = items(&sigs);
+        assert!(!verify_signatures(
+            invalid.iter().map(|(pk, msg, sig)| (pk, msg, sig))
+        ));
+
+        // So are two invalid signatures that sum to a valid one.
+        let mut sigs: Vec<Signature> = valid.iter().map(|(_, _, sig)| sig.clone()).collect();
+        let mut delta = hash_g2("delta");
+        (sigs[0].0).add_assign(&delta);
+        delta.negate();
+        (sigs[1].0).add_assign(&delta);
+        let invalid = items(&sigs);
+        assert!(!verify_signatures(
+            invalid.iter().map(|(pk, msg, sig)| (pk, msg, sig))
+        ));
+    }
+
+    #[test]
+    fn test_verify_signature_shares() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let mut shares: BTreeMap<usize, SignatureShare> = (0..5)
+            .map(|i| (i, sk_set.secret_key_share(i).sign("msg")))
+            .collect();
+        assert!(verify_signature_shares(&pk_set, &shares, "msg"));
+        assert!(!verify_signature_shares(&pk_set, &shares, "other msg"));
+        assert!(pk_set.invalid_signature_shares(&shares, "msg").is_empty());
+
+        // A share for a different message, and a valid share with the wrong index.
+        shares.insert(1, sk_set.secret_key_share(1).sign("other msg"));
+        shares.insert(3, sk_set.secret_key_share(4).sign("msg"));
+        assert!(!verify_signature_shares(&pk_set, &shares, "msg"));
+        let invalid = pk_set.invalid_signature_shares(&shares, "msg");
+        assert_eq!(vec![1, 3], invalid.into_iter().collect::<Vec<_>>());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_verify_decryption_shares() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let cts: Vec<Ciphertext> = (0..3)
+            .map(|_| pk_set.public_key().encrypt_with_rng(&mut rng, b"secret"))
+            .collect();
+        let mut shares: Vec<BTreeMap<usize, DecryptionShare>> = cts
+            .iter()
+            .map(|ct| {
+                let share = |i| sk_set.secret_key_share(i).decrypt_share_no_verify(ct);
+                (0..3).map(|i| (i, share(i))).collect()
+            })
+            .collect();
+        let items: Vec<_> = cts.iter().zip(&shares).collect();
+        assert!(verify_decryption_shares(&pk_set, &items));
+        assert!(pk_set.invalid_decryption_shares(&items).is_empty());
+        let pk_share = pk_set.public_key_share(2);
+        let own: Vec<&DecryptionShare> = shares.iter().map(|s| &s[&2]).collect();
+        assert!(pk_share.verify_decryption_shares(own.iter().cloned().zip(&cts)));
+        // The shares are valid, but for the wrong ciphertexts.
+        assert!(!pk_share.verify_decryption_shares(own.iter().cloned().rev().zip(&cts)));
+
+        // A share for another ciphertext, and a share with the wrong index.
+        let wrong_ct = sk_set.secret_key_share(1).decrypt_share_no_verify(&cts[0]);
+        shares[2].insert(1, wrong_ct);
+        let wrong_index = sk_set.secret_key_share(0).decrypt_share_no_verify(&cts[1]);
+        shares[1].insert(2, wrong_index);
+        let items: Vec<_> = cts.iter().zip(&shares).collect();
+        assert!(!verify_decryption_shares(&pk_set, &items));
+        let invalid = pk_set.invalid_decryption_shares(&items);
+        assert_eq!(
+            vec![(1, 2), (2, 1)],
+            invalid.into_iter().collect::<Vec<_>>()
+        );
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_verify_decryption_shares_with_ad() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let ads: [&[u8]; 2] = [b"epoch 1", b""];
+        let cts: Vec<Ciphertext> = ads
+            .iter()
+            .map(|ad| {
+                pk_set
+                    .public_key()
+                    .encrypt_with_ad_and_rng(&mut rng, b"secret", ad)
+            })
+            .collect();
+        let shares: Vec<BTreeMap<usize, DecryptionShare>> = cts
+            .iter()
+            .map(|ct| {
+                let share = |i| sk_set.secret_key_share(i).decrypt_share_no_verify(ct);
+                (0..3).map(|i| (i, share(i))).collect()
+            })
+            .collect();
+        let items: Vec<_> = (0..2).map(|k| (&cts[k], ads[k], &shares[k])).collect();
+        assert!(verify_decryption_shares_with_ad(&pk_set, &items));
+        assert!(pk_set.invalid_decryption_shares_with_ad(&items).is_empty());
+        let pk_share = pk_set.public_key_share(1);
+        let own = (0..2).map(|k| (&shares[k][&1], &cts[k], ads[k]));
+        assert!(pk_share.verify_decryption_shares_with_ad(own));
+
+        // Without the associated data, the first ciphertext's shares don't match.
+        let items: Vec<_> = cts.iter().zip(&shares).collect();
+        assert!(!verify_decryption_shares(&pk_set, &items));
+        let invalid = pk_set.invalid_decryption_shares(&items);
+        assert_eq!(
+            vec![(0, 0), (0, 1), (0, 2)],
+            invalid.into_iter().collect::<Vec<_>>()
+        );
+    }
+}
@@ -0,0 +1,259 @@
+//! A threshold randomness beacon.
+//!
+//! In each round, the committee signs a message derived from the round number and the previous
+//! round's output. Since threshold signatures are unique, the combined signature, and therefore
+//! its hash, the round's output, is determined by the previous rounds, but can't be predicted
+//! until `threshold + 1` members have contributed their shares. The first round builds on an
+//! output derived from a genesis seed.
+//!
+//! A `Beacon` tracks the chain of rounds: Its `session` collects and verifies the shares for the
+//! next round in a [`SigningSession`](../session/struct.SigningSession.html) whose epoch is the
+//! round number, and `complete` appends the combined signature. Clients that only follow the
+//! beacon check each published round with `verify_round`, or a whole chain with `verify_chain`.
+//!
+//! ```
+//! use threshold_crypto::beacon::Beacon;
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let mut beacon = Beacon::new(sk_set.public_keys(), b"genesis");
+//! for _ in 0..3 {
+//!     let mut session = beacon.session();
+//!     for i in 0..2 {
+//!         session.add_share(i, beacon.sign_share(&sk_set.secret_key_share(i))).unwrap();
+//!     }
+//!     let round = beacon.complete(&session).unwrap();
+//!     let _random_bytes: [u8; 32] = round.randomness();
+//! }
+//! assert_eq!(3, beacon.latest().unwrap().number());
+//! ```
+
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::session::SigningSession;
+use crate::util::sha3_256;
+use crate::{PublicKey, PublicKeySet, SecretKeyShare, Signature, SignatureShare};
+
+/// The domain separator for the genesis output.
+const GENESIS_DST: &[u8] = b"threshold_crypto beacon genesis";
+
+/// The domain separator for round messages.
+const ROUND_DST: &[u8] = b"threshold_crypto beacon round";
+
+/// The domain separator for round outputs.
+const OUTPUT_DST: &[u8] = b"threshold_crypto beacon output";
+
+/// A completed round of the beacon.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Round {
+    number: u64,
+    signature: Signature,
+}
+
+impl Round {
+    /// Creates a round with the given number and signature, e.g. as received from the network.
+    ///
+    /// The round must be checked with `verify_round` before its output is used.
+    pub fn new(number: u64, signature: Signature) -> Self {
+        Round { number, signature }
+    }
+
+    /// Returns the round number. The first round is number 1.
+    pub fn number(&self) -> u64 {
+        self.number
+    }
+
+    /// Returns the committee's signature of the round's message.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Returns the round's output: 32 uniformly distributed bytes.
+    pub fn randomness(&self) -> [u8; 32] {
+        let mut bytes = OUTPUT_DST.to_vec();
+        bytes.extend(self.signature.to_bytes().as_ref());
+        sha3_256(&bytes)
+    }
+
+    /// Returns a random number generator seeded with the round's output, for deriving any amount
+    /// of uniform bytes, or uniform values in a range.
+    pub fn rng(&self) -> ChaChaRng {
+        ChaChaRng::from_seed(self.randomness())
+    }
+}
+
+/// The chain of rounds of a threshold randomness beacon.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Beacon {
+    pk_set: PublicKeySet,
+    genesis: [u8; 32],
+    latest: Option<Round>,
+}
+
+impl Beacon {
+    /// Creates a beacon for the committee with the given key set, starting from the genesis seed.
+    pub fn new(pk_set: PublicKeySet, genesis_seed: &[u8]) -> Self {
+        Beacon {
+            pk_set,
+            genesis: genesis_output(genesis_seed),
+            latest: None,
+        }
+    }
+
+    /// Returns the committee's public key set.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the latest completed round, or `None` before the first one.
+    pub fn latest(&self) -> Option<&Round> {
+        self.latest.as_ref()
+    }
+
+    /// Returns the number of the next round.
+    pub fn next_round(&self) -> u64 {
+        self.latest.as_ref().map_or(1, |round| round.number + 1)
+    }
+
+    /// Returns the output the next round builds on: the latest round's, or the genesis output.
+    pub fn previous_output(&self) -> [u8; 32] {
+        self.latest.as_ref().map_or(self.genesis, Round::randomness)
+    }
+
+    /// Returns the message the committee signs in the next round.
+    pub fn next_message(&self) -> Vec<u8> {
+        round_message(self.next_round(), &self.previous_output())
+    }
+
+    /// Signs the next round's message with a member's key share.
+    pub fn sign_share(&self, sk_share: &SecretKeyShare) -> SignatureShare {
+        sk_share.sign(self.next_message())
+    }
+
+    /// Returns a session collecting the shares for the next round, with the round number as its
+    /// epoch.
+    pub fn session(&self) -> SigningSession {
+        SigningSession::new(self.pk_set.clone(), self.next_message()).with_epoch(self.next_round())
+    }
+
+    /// Combines the session's shares and appends the round.
+    ///
+    /// Returns `Error::InvalidRound` if the session is not for the next round, and
+    /// `Error::NotEnoughShares` if it doesn't have enough shares yet.
+    pub fn complete(&mut self, session: &SigningSession) -> Result<&Round> {
+        if session.epoch() != self.next_round() || session.public_keys() != &self.pk_set {
+            return Err(Error::InvalidRound(session.epoch()));
+        }
+        self.advance(session.signature()?)
+    }
+
+    /// Appends the next round with the given signature, if it is valid.
+    ///
+    /// Returns `Error::InvalidRound` if the signature doesn't match the next round's message.
+    pub fn advance(&mut self, signature: Signature) -> Result<&Round> {
+        let round = Round::new(self.next_round(), signature);
+        let pk = self.pk_set.public_key();
+        if !verify_round(&pk, &self.previous_output(), &round) {
+            return Err(Error::InvalidRound(round.number));
+        }
+        Ok(self.latest.insert(round))
+    }
+}
+
+/// Returns the output the first round builds on, for the given genesis seed.
+pub fn genesis_output(genesis_seed: &[u8]) -> [u8; 32] {
+    let mut bytes = GENESIS_DST.to_vec();
+    bytes.extend(genesis_seed);
+    sha3_256(&bytes)
+}
+
+/// Returns the message signed in the given round, building on the previous round's output.
+pub fn round_message(number: u64, previous_output: &[u8; 32]) -> Vec<u8> {
+    let mut msg = ROUND_DST.to_vec();
+    msg.extend(&number.to_be_bytes());
+    msg.extend(previous_output);
+    msg
+}
+
+/// Returns `true` if the round's signature is valid, given the previous round's output.
+pub fn verify_round(pk: &PublicKey, previous_output: &[u8; 32], round: &Round) -> bool {
+    pk.verify(
+        &round.signature,
+        round_message(round.number, previous_output),
+    )
+}
+
+/// Returns `true` if the rounds are valid and consecutive, starting with round 1.
+pub fn verify_chain(pk: &PublicKey, genesis_seed: &[u8], rounds: &[Round]) -> bool {
+    let mut previous = genesis_output(genesis_seed);
+    for (number, round) in (1..).zip(rounds) {
+        if round.number != number || !verify_round(pk, &previous, round) {
+            return false;
+        }
+        previous = round.randomness();
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::RngCore;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_beacon() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk = sk_set.public_keys().public_key();
+        let mut beacon = Beacon::new(sk_set.public_keys(), b"genesis");
+        assert_eq!(1, beacon.next_round());
+        assert_eq!(genesis_output(b"genesis"), beacon.previous_output());
+
+        let mut rounds = Vec::new();
+        for number in 1..=3 {
+            let mut session = beacon.session();
+            assert_eq!(number, session.epoch());
+            for i in 0..2 {
+                let share = beacon.sign_share(&sk_set.secret_key_share(i));
+                session.add_share(i, share).expect("valid share");
+            }
+            assert_eq!(
+                Err(Error::NotEnoughShares),
+                beacon.complete(&session).map(drop)
+            );
+            let share = beacon.sign_share(&sk_set.secret_key_share(4));
+            session.add_share(4, share).expect("valid share");
+            let round = beacon.complete(&session).expect("enough shares").clone();
+            assert_eq!(number, round.number());
+            assert_eq!(Some(&round), beacon.latest());
+            rounds.push(round);
+        }
+        assert!(verify_chain(&pk, b"genesis", &rounds));
+        assert!(!verify_chain(&pk, b"other genesis", &rounds));
+        assert!(!verify_chain(&pk, b"genesis", &rounds[1..]));
+        assert_ne!(rounds[0].randomness(), rounds[1].randomness());
+        let (mut a, mut b) = ([0u8; 64], [0u8; 64]);
+        rounds[2].rng().fill_bytes(&mut a);
+        rounds[2].rng().fill_bytes(&mut b);
+        assert_eq!(a[..], b[..]);
+
+        // A stale session or a signature from an earlier round is rejected.
+        let stale = SigningSession::new(sk_set.public_keys(), "msg").with_epoch(2);
+        assert_eq!(
+            Err(Error::InvalidRound(2)),
+            beacon.complete(&stale).map(drop)
+        );
+        let old_sig = rounds[2].signature().clone();
+        assert_eq!(
+            Err(Error::InvalidRound(4)),
+            beacon.advance(old_sig).map(drop)
+        );
+        assert_eq!(4, beacon.next_round());
+    }
+}
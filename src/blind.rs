@@ -0,0 +1,233 @@
+//! Blind threshold signatures.
+//!
+//! A requester can obtain a signature of a message without revealing the message to the signers:
+//! It multiplies the message's hash by a random blinding factor `r`, the signers sign the blinded
+//! point with their key shares, and the requester combines the shares and multiplies the result by
+//! `r⁻¹`. The unblinded signature is the ordinary signature of the message, and the signers can't
+//! link it to the blinded point they signed, e.g. when it is later redeemed as a token.
+//!
+//! Each blind signature share can be verified against the signer's public key share and the
+//! blinded point, so invalid shares can be discarded before combining.
+//!
+//! ```
+//! use threshold_crypto::{blind, SecretKeySet};
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! let (blinded, factor) = blind::blind("token", &mut rng);
+//! let shares: Vec<_> = (0..2)
+//!     .map(|i| sk_set.secret_key_share(i).sign_blinded(&blinded))
+//!     .collect();
+//! let sig = pk_set
+//!     .combine_blind_signatures(&factor, shares.iter().enumerate())
+//!     .unwrap();
+//! assert!(pk_set.public_key().verify(&sig, "token"));
+//! ```
+
+use std::fmt;
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use hex_fmt::HexFmt;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::decode::decode;
+use crate::error::{DecodeResult, Result};
+use crate::secret::clear_fr;
+use crate::{
+    hash_g2, interpolate, DebugDots, Fr, G2Affine, IntoFr, PublicKeySet, PublicKeyShare, SecretKey,
+    SecretKeyShare, Signature, G2, SIG_SIZE,
+};
+
+/// A message hash in `G2`, multiplied by a blinding factor.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
+pub struct BlindedMessage(#[serde(with = "crate::serde_impl::projective")] G2);
+
+impl fmt::Debug for BlindedMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uncomp = self.0.into_affine().into_uncompressed();
+        write!(f, "BlindedMessage({:0.10})", HexFmt(uncomp))
+    }
+}
+
+impl BlindedMessage {
+    /// Returns the blinded message with the given representation, if valid.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "BlindedMessage", |r| {
+            Ok(BlindedMessage(r.point::<G2Affine>("blinded message")?))
+        })
+    }
+
+    /// Returns a byte string representation of the blinded message.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        let mut bytes = [0u8; SIG_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_compressed().as_ref());
+        bytes
+    }
+}
+
+/// The secret factor a message was blinded with, needed to unblind the signature.
+#[derive(Clone, PartialEq, Eq)]
+pub struct BlindingFactor(Fr);
+
+impl Zeroize for BlindingFactor {
+    fn zeroize(&mut self) {
+        clear_fr(&mut self.0)
+    }
+}
+
+impl Drop for BlindingFactor {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// A debug statement where the secret factor is redacted.
+impl fmt::Debug for BlindingFactor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("BlindingFactor").field(&DebugDots).finish()
+    }
+}
+
+impl BlindingFactor {
+    /// Returns the signature of the original message, given the signature of the blinded one.
+    pub fn unblind(&self, sig: &BlindSignature) -> Signature {
+        let mut inv = self.0.inverse().expect("blinding factors are nonzero");
+        count!(Exponentiation, 1);
+        let mut result = sig.0;
+        result.mul_assign(inv);
+        clear_fr(&mut inv);
+        Signature(result)
+    }
+}
+
+/// A signature of a blinded message.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BlindSignature(#[serde(with = "crate::serde_impl::projective")] G2);
+
+/// A signature share of a blinded message.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BlindSignatureShare(BlindSignature);
+
+impl BlindSignatureShare {
+    /// Returns the share with the given representation, if valid.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "BlindSignatureShare", |r| {
+            Ok(BlindSignatureShare(BlindSignature(
+                r.point::<G2Affine>("signature share")?,
+            )))
+        })
+    }
+
+    /// Returns a byte string representation of the share.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        let mut bytes = [0u8; SIG_SIZE];
+        bytes.copy_from_slice((self.0).0.into_affine().into_compressed().as_ref());
+        bytes
+    }
+}
+
+/// Returns the blinded hash of the message, and the factor to unblind its signature with.
+pub fn blind<M: AsRef<[u8]>, R: Rng>(msg: M, rng: &mut R) -> (BlindedMessage, BlindingFactor) {
+    let mut r = Fr::random(rng);
+    while r.is_zero() {
+        r = Fr::random(rng);
+    }
+    count!(Exponentiation, 1);
+    let mut point = hash_g2(msg);
+    point.mul_assign(r);
+    (BlindedMessage(point), BlindingFactor(r))
+}
+
+impl SecretKey {
+    /// Signs the blinded message.
+    pub fn sign_blinded(&self, blinded: &BlindedMessage) -> BlindSignature {
+        count!(Exponentiation, 1);
+        BlindSignature(blinded.0.into_affine().mul(self.0))
+    }
+}
+
+impl SecretKeyShare {
+    /// Signs the blinded message with the key share.
+    pub fn sign_blinded(&self, blinded: &BlindedMessage) -> BlindSignatureShare {
+        BlindSignatureShare(self.0.sign_blinded(blinded))
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns `true` if the share is a valid signature of the blinded message.
+    pub fn verify_blinded(&self, share: &BlindSignatureShare, blinded: &BlindedMessage) -> bool {
+        (self.0).verify_g2(&Signature((share.0).0), blinded.0)
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the shares of a blinded message and unblinds the result, returning the signature
+    /// of the original message.
+    ///
+    /// Like `combine_signatures`, this doesn't check the shares' validity.
+    pub fn combine_blind_signatures<'a, T, I>(
+        &self,
+        factor: &BlindingFactor,
+        shares: I,
+    ) -> Result<Signature>
+    where
+        I: IntoIterator<Item = (T, &'a BlindSignatureShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &(share.0).0));
+        let sig = BlindSignature(interpolate(self.threshold(), samples)?);
+        Ok(factor.unblind(&sig))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_blind_threshold_signature() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let (blinded, factor) = blind("token", &mut rng);
+        assert_ne!(BlindedMessage(hash_g2("token")), blinded);
+        assert_eq!(Ok(blinded), BlindedMessage::from_slice(&blinded.to_bytes()));
+
+        let (other_blinded, _) = blind("token", &mut rng);
+        let shares: Vec<BlindSignatureShare> = (0..4)
+            .map(|i| {
+                let share = sk_set.secret_key_share(i).sign_blinded(&blinded);
+                let pk_share = pk_set.public_key_share(i);
+                assert!(pk_share.verify_blinded(&share, &blinded));
+                assert!(!pk_share.verify_blinded(&share, &other_blinded));
+                assert_eq!(
+                    Ok(share),
+                    BlindSignatureShare::from_slice(&share.to_bytes())
+                );
+                share
+            })
+            .collect();
+
+        let sig = pk_set
+            .combine_blind_signatures(&factor, shares.iter().enumerate().skip(1))
+            .expect("enough shares");
+        assert_eq!(sk_set.secret_key().sign("token"), sig);
+        assert!(pk_set.public_key().verify(&sig, "token"));
+        assert!(pk_set
+            .combine_blind_signatures(&factor, shares.iter().enumerate().take(2))
+            .is_err());
+
+        // Unblinding with the wrong factor doesn't give a valid signature.
+        let (_, other_factor) = blind("token", &mut rng);
+        let wrong = pk_set
+            .combine_blind_signatures(&other_factor, shares.iter().enumerate())
+            .expect("enough shares");
+        assert!(!pk_set.public_key().verify(&wrong, "token"));
+    }
+}
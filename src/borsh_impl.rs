@@ -0,0 +1,239 @@
+//! Implementations of the [`borsh`](https://borsh.io) crate's `BorshSerialize` and
+//! `BorshDeserialize` for keys, signatures, shares, ciphertexts and key sets.
+//!
+//! The encodings wrap the canonical byte encodings: A public key, key share, signature, signature
+//! share or decryption share is a Borsh `[u8; N]` of its `to_bytes`, without a length prefix, and a
+//! secret key or key share a `[u8; SK_SIZE]` of its big-endian representation. A `Ciphertext` or
+//! `PublicKeySet` is a Borsh `Vec<u8>` of its `to_bytes`: a little-endian `u32` length followed by
+//! the bytes. Invalid encodings, e.g. of points that are not in the group, return an
+//! `io::ErrorKind::InvalidData` error wrapping the `DecodeError`.
+//!
+//! ```
+//! use threshold_crypto::{PublicKeySet, SecretKeySet};
+//!
+//! let pk_set = SecretKeySet::random(2, &mut rand::thread_rng()).public_keys();
+//! let bytes = borsh::to_vec(&pk_set).unwrap();
+//! assert_eq!(&bytes[..4], &(pk_set.to_bytes().len() as u32).to_le_bytes());
+//! assert_eq!(pk_set, borsh::from_slice::<PublicKeySet>(&bytes).unwrap());
+//! ```
+
+use std::convert::TryFrom;
+use std::io::{self, Read, Write};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use ff::{PrimeField, PrimeFieldRepr};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::DecodeError;
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{
+    PublicKey, PublicKeySet, PublicKeyShare, SecretKey, SecretKeyShare, Signature, SignatureShare,
+    PK_SIZE, SIG_SIZE, SK_SIZE,
+};
+
+/// Implements the traits for a type whose `to_bytes` and `from_slice` use a fixed-length array.
+macro_rules! impl_borsh_for_array {
+    ($type:ty, $size:expr) => {
+        impl BorshSerialize for $type {
+            fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                writer.write_all(&self.to_bytes())
+            }
+        }
+
+        impl BorshDeserialize for $type {
+            fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+                let mut bytes = [0u8; $size];
+                reader.read_exact(&mut bytes)?;
+                <$type>::from_slice(&bytes).map_err(invalid_data)
+            }
+        }
+    };
+}
+
+/// Implements the traits for a type whose `to_bytes` and `from_slice` use a `Vec<u8>`.
+macro_rules! impl_borsh_for_vec {
+    ($type:ty) => {
+        impl BorshSerialize for $type {
+            fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+                write_vec(writer, &self.to_bytes())
+            }
+        }
+
+        impl BorshDeserialize for $type {
+            fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+                <$type>::from_slice(&read_vec(reader)?).map_err(invalid_data)
+            }
+        }
+    };
+}
+
+impl_borsh_for_array!(PublicKey, PK_SIZE);
+impl_borsh_for_array!(PublicKeyShare, PK_SIZE);
+impl_borsh_for_array!(Signature, SIG_SIZE);
+impl_borsh_for_array!(SignatureShare, SIG_SIZE);
+#[cfg(feature = "encryption")]
+impl_borsh_for_array!(DecryptionShare, PK_SIZE);
+impl_borsh_for_vec!(PublicKeySet);
+#[cfg(feature = "encryption")]
+impl_borsh_for_vec!(Ciphertext);
+
+impl BorshSerialize for SecretKey {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+        let mut repr = self.0.into_repr();
+        repr.write_be(&mut *bytes)
+            .expect("writing to a vector can't fail");
+        repr.0.zeroize();
+        writer.write_all(&bytes)
+    }
+}
+
+impl BorshDeserialize for SecretKey {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        let mut bytes = Zeroizing::new([0u8; SK_SIZE]);
+        reader.read_exact(&mut *bytes)?;
+        SecretKey::from_slice(&*bytes).map_err(invalid_data)
+    }
+}
+
+impl BorshSerialize for SecretKeyShare {
+    fn serialize<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        self.0.serialize(writer)
+    }
+}
+
+impl BorshDeserialize for SecretKeyShare {
+    fn deserialize_reader<R: Read>(reader: &mut R) -> io::Result<Self> {
+        Ok(SecretKeyShare(SecretKey::deserialize_reader(reader)?))
+    }
+}
+
+/// Writes the bytes as a Borsh `Vec<u8>`, with a little-endian `u32` length prefix.
+fn write_vec<W: Write>(writer: &mut W, bytes: &[u8]) -> io::Result<()> {
+    let len = u32::try_from(bytes.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "longer than u32::MAX bytes"))?;
+    writer.write_all(&len.to_le_bytes())?;
+    writer.write_all(bytes)
+}
+
+/// Reads a Borsh `Vec<u8>`. The buffer only grows as the bytes arrive, so a forged length prefix
+/// can't make it allocate more than the input.
+fn read_vec<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut len = [0u8; 4];
+    reader.read_exact(&mut len)?;
+    let len = u64::from(u32::from_le_bytes(len));
+    let mut bytes = Vec::new();
+    reader.take(len).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 != len {
+        return Err(io::ErrorKind::UnexpectedEof.into());
+    }
+    Ok(bytes)
+}
+
+fn invalid_data(err: DecodeError) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use borsh::{from_slice, to_vec};
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_borsh_round_trips() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let sk = SecretKey::random();
+        let sk_share = sk_set.secret_key_share(2);
+        let pk = sk.public_key();
+        let pk_share = sk_share.public_key_share();
+        let sig = sk.sign(b"message");
+        let sig_share = sk_share.sign(b"message");
+
+        let bytes = to_vec(&pk).unwrap();
+        assert_eq!(&bytes[..], &pk.to_bytes()[..]);
+        assert_eq!(pk, from_slice(&bytes).unwrap());
+        let bytes = to_vec(&pk_share).unwrap();
+        assert_eq!(&bytes[..], &pk_share.to_bytes()[..]);
+        assert_eq!(pk_share, from_slice(&bytes).unwrap());
+        let bytes = to_vec(&sig).unwrap();
+        assert_eq!(&bytes[..], &sig.to_bytes()[..]);
+        assert_eq!(sig, from_slice(&bytes).unwrap());
+        let bytes = to_vec(&sig_share).unwrap();
+        assert_eq!(&bytes[..], &sig_share.to_bytes()[..]);
+        assert_eq!(sig_share, from_slice(&bytes).unwrap());
+        let bytes = to_vec(&sk).unwrap();
+        assert_eq!(SK_SIZE, bytes.len());
+        assert_eq!(sk, from_slice(&bytes).unwrap());
+        let bytes = to_vec(&sk_share).unwrap();
+        assert_eq!(sk_share, from_slice(&bytes).unwrap());
+
+        let pk_set_bytes = pk_set.to_bytes();
+        let bytes = to_vec(&pk_set).unwrap();
+        assert_eq!(&bytes[..4], &(pk_set_bytes.len() as u32).to_le_bytes());
+        assert_eq!(&bytes[4..], &pk_set_bytes[..]);
+        assert_eq!(pk_set, from_slice(&bytes).unwrap());
+
+        // The types can be fields of derived structs.
+        #[derive(BorshSerialize, BorshDeserialize, Debug, PartialEq)]
+        struct Committee {
+            epoch: u64,
+            pk_set: PublicKeySet,
+            sig: Signature,
+        }
+        let committee = Committee {
+            epoch: 7,
+            pk_set: pk_set.clone(),
+            sig,
+        };
+        let bytes = to_vec(&committee).unwrap();
+        assert_eq!(&bytes[..8], &7u64.to_le_bytes());
+        assert_eq!(
+            &bytes[8..(bytes.len() - SIG_SIZE)],
+            &to_vec(&pk_set).unwrap()[..]
+        );
+        assert_eq!(committee, from_slice(&bytes).unwrap());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_borsh_encryption_round_trips() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let ct = sk_set.public_keys().public_key().encrypt(b"message");
+        let dec_share = sk_set.secret_key_share(0).decrypt_share(&ct).unwrap();
+
+        let ct_bytes = ct.to_bytes();
+        let bytes = to_vec(&ct).unwrap();
+        assert_eq!(&bytes[..4], &(ct_bytes.len() as u32).to_le_bytes());
+        assert_eq!(&bytes[4..], &ct_bytes[..]);
+        assert_eq!(ct, from_slice(&bytes).unwrap());
+        let bytes = to_vec(&dec_share).unwrap();
+        assert_eq!(&bytes[..], &dec_share.to_bytes()[..]);
+        assert_eq!(dec_share, from_slice(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_borsh_rejects_invalid_input() {
+        let pk_set = SecretKeySet::random(1, &mut rand::thread_rng()).public_keys();
+        let bytes = to_vec(&pk_set).unwrap();
+
+        let err = from_slice::<PublicKeySet>(&bytes[..bytes.len() - 1]).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        let err = from_slice::<PublicKeySet>(&trailing).unwrap_err();
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+        let err = from_slice::<PublicKeySet>(&u32::MAX.to_le_bytes()).unwrap_err();
+        assert_eq!(io::ErrorKind::UnexpectedEof, err.kind());
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        let err = from_slice::<PublicKey>(&[0xff; PK_SIZE]).unwrap_err();
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        assert_eq!(io::ErrorKind::InvalidData, err.kind());
+    }
+}
@@ -0,0 +1,151 @@
+//! Non-hardened child key derivation.
+//!
+//! A child key is its parent key plus a tweak `t`, which is a hash of the parent's public key and
+//! the child's index: The child secret key is `sk + t`, and its public key is `pk + t * G`. So
+//! anyone with the parent's public key can derive the child public keys, e.g. a wallet watching
+//! per-purpose addresses, while only the holder of the secret key can derive the child secret
+//! keys.
+//!
+//! The same tweak can be added to the constant term of a key set's polynomial. This shifts every
+//! share by `t`, so each share holder derives its child share locally, from its own share and the
+//! master public key, and any `threshold + 1` child shares combine to signatures and decryptions
+//! for the child master key.
+//!
+//! Derivation is not hardened: A child secret key together with the parent public key reveals the
+//! parent secret key. Only hand out child secret keys to parties that may know the parent's. Use
+//! [`eth2::derive_child_sk`](../eth2/fn.derive_child_sk.html) for hardened derivation of plain
+//! secret keys.
+//!
+//! ```
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let pk_set = sk_set.public_keys();
+//! let child_pk_set = pk_set.derive_child(7);
+//! let shares: Vec<_> = (0..2)
+//!     .map(|i| {
+//!         let child_share = sk_set.secret_key_share(i).derive_child(&pk_set.public_key(), 7);
+//!         child_share.sign("msg")
+//!     })
+//!     .collect();
+//! let sig = child_pk_set.combine_signatures(shares.iter().enumerate()).unwrap();
+//! assert!(pk_set.public_key().derive_child(7).verify(&sig, "msg"));
+//! assert_eq!(sk_set.derive_child(7).public_keys(), child_pk_set);
+//! ```
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+use crate::poly::Poly;
+use crate::util::sha3_256;
+use crate::{
+    Fr, G1Affine, PublicKey, PublicKeySet, PublicKeyShare, SecretKey, SecretKeySet, SecretKeyShare,
+};
+
+/// The domain separator of the tweak.
+const TWEAK_DST: &[u8] = b"threshold_crypto child key tweak";
+
+impl SecretKey {
+    /// Returns the child key with the given index.
+    pub fn derive_child(&self, index: u32) -> SecretKey {
+        let mut fr = tweak(&self.public_key(), index);
+        fr.add_assign(&self.0);
+        SecretKey::from_mut(&mut fr)
+    }
+}
+
+impl PublicKey {
+    /// Returns the public key of the child key with the given index.
+    pub fn derive_child(&self, index: u32) -> PublicKey {
+        let mut point = G1Affine::one().mul(tweak(self, index));
+        point.add_assign(&self.0);
+        PublicKey(point)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns this share of the child key with the given index, where `master` is the master
+    /// public key of the share's key set.
+    pub fn derive_child(&self, master: &PublicKey, index: u32) -> SecretKeyShare {
+        let mut fr = tweak(master, index);
+        fr.add_assign(&(self.0).0);
+        SecretKeyShare(SecretKey::from_mut(&mut fr))
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns this public key share of the child key with the given index, where `master` is the
+    /// master public key of the share's key set.
+    pub fn derive_child(&self, master: &PublicKey, index: u32) -> PublicKeyShare {
+        let mut point = G1Affine::one().mul(tweak(master, index));
+        point.add_assign(&(self.0).0);
+        PublicKeyShare(PublicKey(point))
+    }
+}
+
+impl SecretKeySet {
+    /// Returns the key set of the child key with the given index. Its shares are the parent's
+    /// shares' `derive_child`.
+    pub fn derive_child(&self, index: u32) -> SecretKeySet {
+        let master = self.public_keys().public_key();
+        SecretKeySet::from(self.poly.clone() + tweak(&master, index))
+    }
+}
+
+impl PublicKeySet {
+    /// Returns the public key set of the child key with the given index. Its master key and key
+    /// shares are the parent's ones' `derive_child`.
+    pub fn derive_child(&self, index: u32) -> PublicKeySet {
+        let shift = Poly::constant(tweak(&self.public_key(), index)).commitment();
+        PublicKeySet::from(&self.commit + shift)
+    }
+}
+
+/// Returns the tweak of the child with the given index of the given parent key.
+fn tweak(parent: &PublicKey, index: u32) -> Fr {
+    let mut input = TWEAK_DST.to_vec();
+    input.extend(&parent.to_bytes());
+    input.extend(&index.to_be_bytes());
+    Fr::random(&mut ChaChaRng::from_seed(sha3_256(&input)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_derive_child() {
+        let sk = SecretKey::random();
+        let pk = sk.public_key();
+        let child = sk.derive_child(1);
+        assert_eq!(pk.derive_child(1), child.public_key());
+        assert_eq!(child, sk.derive_child(1));
+        assert!(pk.derive_child(1).verify(&child.sign("msg"), "msg"));
+
+        // Mock keys are too small for collisions to be unlikely.
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        {
+            assert_ne!(child, sk.derive_child(2));
+            assert_ne!(sk, child);
+        }
+    }
+
+    #[test]
+    fn test_derive_child_key_set() {
+        let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let pk_set = sk_set.public_keys();
+        let master = pk_set.public_key();
+        let child_set = sk_set.derive_child(5);
+        let child_pk_set = pk_set.derive_child(5);
+        assert_eq!(child_set.public_keys(), child_pk_set);
+        assert_eq!(master.derive_child(5), child_pk_set.public_key());
+        for i in 0..4 {
+            let share = sk_set.secret_key_share(i).derive_child(&master, 5);
+            assert_eq!(child_set.secret_key_share(i), share);
+            let pk_share = pk_set.public_key_share(i).derive_child(&master, 5);
+            assert_eq!(child_pk_set.public_key_share(i), pk_share);
+        }
+    }
+}
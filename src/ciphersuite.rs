@@ -0,0 +1,514 @@
+//! Ciphersuites with a configurable hash function.
+//!
+//! The crate's methods hash messages to `G2`, and derive the encryption masks, with SHA3-256.
+//! Deployments with compliance requirements, e.g. SHA-2 only, or performance goals can instead
+//! sign, verify, encrypt and decrypt through a [`Ciphersuite`], which uses any
+//! [`HashFunction`] with 32-byte outputs and prefixes every hash input with its domain
+//! separation tag. [`Sha3_256`], [`Keccak256`] and [`Sha256`] are built in, and `Blake3` is
+//! available with the `blake3` feature; other functions can be plugged in by implementing the
+//! trait.
+//!
+//! Under every suite, [`Ciphersuite::hash_g2`] seeds a ChaCha RNG with the suite's digest of
+//! the message and returns `G2::random` of it, like the crate's own `hash_g2`. The hash function
+//! only replaces the digest: This is not a hash-to-curve as in RFC 9380, so even the SHA-256
+//! suite's signatures don't verify in other BLS libraries. For those, use the `ietf` module.
+//!
+//! Keys and shares are the same for all ciphersuites, but signatures and ciphertexts are only
+//! valid under the suite that produced them. The suite `Ciphersuite::new(Sha3_256, b"")`, without
+//! a domain separation tag, is equivalent to the crate's own methods, including the `_with_ad`
+//! methods for ciphertexts bound to associated data.
+
+use group::CurveProjective;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use sha2::Digest;
+use tiny_keccak::{Hasher, Keccak, Sha3};
+
+#[cfg(feature = "encryption")]
+use ff::Field;
+#[cfg(feature = "encryption")]
+use group::CurveAffine;
+#[cfg(feature = "encryption")]
+use pairing::Engine;
+#[cfg(feature = "encryption")]
+use rand::distributions::Standard;
+#[cfg(feature = "encryption")]
+use rand::{Rng, RngCore};
+
+#[cfg(feature = "encryption")]
+use crate::error::Result;
+#[cfg(feature = "encryption")]
+use crate::into_fr::IntoFr;
+#[cfg(feature = "encryption")]
+use crate::{
+    interpolate, Ciphertext, DecryptionShare, Fr, G1Affine, PEngine, PublicKeySet, AD_DST, G1,
+};
+use crate::{PublicKey, PublicKeyShare, SecretKey, SecretKeyShare, Signature, SignatureShare, G2};
+
+/// The default domain separation tag of the SHA3-256 ciphersuite.
+pub const SHA3_256_DST: &[u8] = b"THRESHOLD_CRYPTO_BLS12381G2_SHA3-256_";
+
+/// The default domain separation tag of the Keccak-256 ciphersuite.
+pub const KECCAK_256_DST: &[u8] = b"THRESHOLD_CRYPTO_BLS12381G2_KECCAK-256_";
+
+/// The default domain separation tag of the SHA-256 ciphersuite.
+pub const SHA_256_DST: &[u8] = b"THRESHOLD_CRYPTO_BLS12381G2_SHA-256_";
+
+/// The default domain separation tag of the BLAKE3 ciphersuite.
+#[cfg(feature = "blake3")]
+pub const BLAKE3_DST: &[u8] = b"THRESHOLD_CRYPTO_BLS12381G2_BLAKE3_";
+
+/// A cryptographic hash function with 32-byte outputs.
+pub trait HashFunction {
+    /// Returns the digest of the data.
+    fn hash(&self, data: &[u8]) -> [u8; 32];
+}
+
+/// The SHA3-256 hash function, as used by the rest of the crate.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sha3_256;
+
+impl HashFunction for Sha3_256 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        let mut sha3 = Sha3::v256();
+        sha3.update(data);
+        let mut output = [0u8; 32];
+        sha3.finalize(&mut output);
+        output
+    }
+}
+
+/// The original Keccak-256 hash function, as used by Ethereum.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Keccak256;
+
+impl HashFunction for Keccak256 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        let mut keccak = Keccak::v256();
+        keccak.update(data);
+        let mut output = [0u8; 32];
+        keccak.finalize(&mut output);
+        output
+    }
+}
+
+/// The SHA-256 hash function of the SHA-2 family.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Sha256;
+
+impl HashFunction for Sha256 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        sha2::Sha256::digest(data).into()
+    }
+}
+
+/// The BLAKE3 hash function, with its default 32-byte output.
+#[cfg(feature = "blake3")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Blake3;
+
+#[cfg(feature = "blake3")]
+impl HashFunction for Blake3 {
+    fn hash(&self, data: &[u8]) -> [u8; 32] {
+        blake3::hash(data).into()
+    }
+}
+
+/// A hash function and domain separation tag, used for signing and encryption.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ciphersuite<H> {
+    hash: H,
+    dst: Vec<u8>,
+}
+
+impl Ciphersuite<Sha3_256> {
+    /// Returns the SHA3-256 ciphersuite with its default domain separation tag.
+    pub fn sha3_256() -> Self {
+        Ciphersuite::new(Sha3_256, SHA3_256_DST)
+    }
+}
+
+impl Ciphersuite<Keccak256> {
+    /// Returns the Keccak-256 ciphersuite with its default domain separation tag.
+    pub fn keccak_256() -> Self {
+        Ciphersuite::new(Keccak256, KECCAK_256_DST)
+    }
+}
+
+impl Ciphersuite<Sha256> {
+    /// Returns the SHA-256 ciphersuite with its default domain separation tag.
+    pub fn sha_256() -> Self {
+        Ciphersuite::new(Sha256, SHA_256_DST)
+    }
+}
+
+#[cfg(feature = "blake3")]
+impl Ciphersuite<Blake3> {
+    /// Returns the BLAKE3 ciphersuite with its default domain separation tag.
+    pub fn blake3() -> Self {
+        Ciphersuite::new(Blake3, BLAKE3_DST)
+    }
+}
+
+impl<H: HashFunction> Ciphersuite<H> {
+    /// Creates a ciphersuite with the given hash function and domain separation tag.
+    pub fn new(hash: H, dst: &[u8]) -> Self {
+        Ciphersuite {
+            hash,
+            dst: dst.to_vec(),
+        }
+    }
+
+    /// Returns the domain separation tag.
+    pub fn dst(&self) -> &[u8] {
+        &self.dst
+    }
+
+    /// Returns the hash of the domain separation tag followed by the data.
+    pub fn digest(&self, data: &[u8]) -> [u8; 32] {
+        let mut input = self.dst.clone();
+        input.extend(data);
+        self.hash.hash(&input)
+    }
+
+    /// Returns a hash of the message in `G2`.
+    ///
+    /// This is `G2::random` with a ChaCha RNG seeded by the suite's digest of the message, not a
+    /// hash-to-curve in the sense of RFC 9380.
+    pub fn hash_g2<M: AsRef<[u8]>>(&self, msg: M) -> G2 {
+        let _span = span!("ciphersuite_hash_g2", len = msg.as_ref().len());
+        count!(HashToCurve, 1);
+        G2::random(&mut ChaChaRng::from_seed(self.digest(msg.as_ref())))
+    }
+
+    /// Signs the message.
+    pub fn sign<M: AsRef<[u8]>>(&self, sk: &SecretKey, msg: M) -> Signature {
+        sk.sign_g2(self.hash_g2(msg))
+    }
+
+    /// Signs the message with a key share.
+    pub fn sign_share<M: AsRef<[u8]>>(&self, sk_share: &SecretKeyShare, msg: M) -> SignatureShare {
+        sk_share.sign_g2(self.hash_g2(msg))
+    }
+
+    /// Returns `true` if the signature matches the message.
+    pub fn verify<M: AsRef<[u8]>>(&self, pk: &PublicKey, sig: &Signature, msg: M) -> bool {
+        pk.verify_g2(sig, self.hash_g2(msg))
+    }
+
+    /// Returns `true` if the signature share matches the message.
+    pub fn verify_share<M: AsRef<[u8]>>(
+        &self,
+        pk_share: &PublicKeyShare,
+        sig: &SignatureShare,
+        msg: M,
+    ) -> bool {
+        pk_share.verify_g2(sig, self.hash_g2(msg))
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl<H: HashFunction> Ciphersuite<H> {
+    /// Encrypts the message to the public key.
+    pub fn encrypt_with_rng<R: RngCore, M: AsRef<[u8]>>(
+        &self,
+        pk: &PublicKey,
+        rng: &mut R,
+        msg: M,
+    ) -> Ciphertext {
+        self.encrypt_with_ad_and_rng(pk, rng, msg, [])
+    }
+
+    /// Encrypts the message to the public key, bound to the associated data.
+    pub fn encrypt_with_ad_and_rng<R, M, A>(
+        &self,
+        pk: &PublicKey,
+        rng: &mut R,
+        msg: M,
+        ad: A,
+    ) -> Ciphertext
+    where
+        R: RngCore,
+        M: AsRef<[u8]>,
+        A: AsRef<[u8]>,
+    {
+        let r: Fr = Fr::random(rng);
+        count!(Exponentiation, 3);
+        let u = G1Affine::one().mul(r);
+        let v = self.xor_with_hash(pk.0.into_affine().mul(r), msg.as_ref());
+        let w = self.hash_g1_g2_ad(u, &v, ad.as_ref()).into_affine().mul(r);
+        Ciphertext(u, v, w)
+    }
+
+    /// Returns `true` if this is a valid ciphertext under this ciphersuite.
+    pub fn verify_ciphertext(&self, ct: &Ciphertext) -> bool {
+        self.verify_ciphertext_with_ad(ct, [])
+    }
+
+    /// Returns `true` if this is a valid ciphertext under this ciphersuite, for the associated
+    /// data it was encrypted with.
+    pub fn verify_ciphertext_with_ad<A: AsRef<[u8]>>(&self, ct: &Ciphertext, ad: A) -> bool {
+        count!(Pairing, 2);
+        let Ciphertext(ref u, ref v, ref w) = *ct;
+        let hash = self.hash_g1_g2_ad(*u, v, ad.as_ref());
+        PEngine::pairing(G1Affine::one(), *w) == PEngine::pairing(*u, hash)
+    }
+
+    /// Returns the decrypted text, or `None` if the ciphertext isn't valid.
+    pub fn decrypt(&self, sk: &SecretKey, ct: &Ciphertext) -> Option<Vec<u8>> {
+        self.decrypt_with_ad(sk, ct, [])
+    }
+
+    /// Returns the decrypted text, or `None` if the ciphertext isn't valid with the associated
+    /// data.
+    pub fn decrypt_with_ad<A: AsRef<[u8]>>(
+        &self,
+        sk: &SecretKey,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> Option<Vec<u8>> {
+        if !self.verify_ciphertext_with_ad(ct, ad) {
+            return None;
+        }
+        count!(Exponentiation, 1);
+        Some(self.xor_with_hash(ct.0.into_affine().mul(sk.0), &ct.1))
+    }
+
+    /// Returns a decryption share, or `None` if the ciphertext isn't valid.
+    pub fn decrypt_share(
+        &self,
+        sk_share: &SecretKeyShare,
+        ct: &Ciphertext,
+    ) -> Option<DecryptionShare> {
+        self.decrypt_share_with_ad(sk_share, ct, [])
+    }
+
+    /// Returns a decryption share, or `None` if the ciphertext isn't valid with the associated
+    /// data.
+    pub fn decrypt_share_with_ad<A: AsRef<[u8]>>(
+        &self,
+        sk_share: &SecretKeyShare,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> Option<DecryptionShare> {
+        if !self.verify_ciphertext_with_ad(ct, ad) {
+            return None;
+        }
+        Some(sk_share.decrypt_share_no_verify(ct))
+    }
+
+    /// Returns `true` if the decryption share matches the ciphertext.
+    pub fn verify_decryption_share(
+        &self,
+        pk_share: &PublicKeyShare,
+        share: &DecryptionShare,
+        ct: &Ciphertext,
+    ) -> bool {
+        self.verify_decryption_share_with_ad(pk_share, share, ct, [])
+    }
+
+    /// Returns `true` if the decryption share matches the ciphertext with the associated data.
+    pub fn verify_decryption_share_with_ad<A: AsRef<[u8]>>(
+        &self,
+        pk_share: &PublicKeyShare,
+        share: &DecryptionShare,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> bool {
+        count!(Pairing, 2);
+        let Ciphertext(ref u, ref v, ref w) = *ct;
+        let hash = self.hash_g1_g2_ad(*u, v, ad.as_ref());
+        PEngine::pairing(share.0, hash) == PEngine::pairing((pk_share.0).0, *w)
+    }
+
+    /// Combines the decryption shares to decrypt the ciphertext.
+    pub fn decrypt_with_shares<'a, T, I>(
+        &self,
+        pk_set: &PublicKeySet,
+        shares: I,
+        ct: &Ciphertext,
+    ) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &share.0));
+        let g: G1 = interpolate(pk_set.threshold(), samples)?;
+        Ok(self.xor_with_hash(g, &ct.1))
+    }
+
+    /// Returns a hash of the group element, masked message and associated data, in `G2`, framed
+    /// like the crate's own. Without associated data, this is `hash_g1_g2(g1, v)`.
+    fn hash_g1_g2_ad(&self, g1: G1, v: &[u8], ad: &[u8]) -> G2 {
+        if ad.is_empty() {
+            return self.hash_g1_g2(g1, v);
+        }
+        let mut data = AD_DST.to_vec();
+        data.extend(&(ad.len() as u64).to_be_bytes());
+        data.extend(ad);
+        data.extend(&self.digest(v));
+        data.extend(g1.into_affine().into_compressed().as_ref());
+        self.hash_g2(&data)
+    }
+
+    /// Returns a hash of the group element and message, in `G2`.
+    fn hash_g1_g2(&self, g1: G1, msg: &[u8]) -> G2 {
+        // If the message is large, hash it, otherwise copy it.
+        let mut data = if msg.len() > 64 {
+            self.digest(msg).to_vec()
+        } else {
+            msg.to_vec()
+        };
+        data.extend(g1.into_affine().into_compressed().as_ref());
+        self.hash_g2(&data)
+    }
+
+    /// Returns the bitwise xor of `bytes` with a sequence of pseudorandom bytes determined by
+    /// `g1`.
+    fn xor_with_hash(&self, g1: G1, bytes: &[u8]) -> Vec<u8> {
+        let digest = self.digest(g1.into_affine().into_compressed().as_ref());
+        let rng = ChaChaRng::from_seed(digest);
+        let xor = |(a, b): (u8, &u8)| a ^ b;
+        rng.sample_iter(&Standard).zip(bytes).map(xor).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_signatures() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let suite = Ciphersuite::keccak_256();
+        let shares: Vec<SignatureShare> = (0..2)
+            .map(|i| suite.sign_share(&sk_set.secret_key_share(i), "msg"))
+            .collect();
+        assert!(suite.verify_share(&pk_set.public_key_share(0), &shares[0], "msg"));
+        let sig = pk_set
+            .combine_signatures(shares.iter().enumerate())
+            .unwrap();
+        assert!(suite.verify(&pk_set.public_key(), &sig, "msg"));
+        assert_eq!(suite.sign(&sk_set.secret_key(), "msg"), sig);
+
+        // Signatures are only valid under their own suite.
+        assert!(!pk_set.public_key().verify(&sig, "msg"));
+        assert!(!Ciphersuite::sha3_256().verify(&pk_set.public_key(), &sig, "msg"));
+        let plain = Ciphersuite::new(Sha3_256, b"");
+        assert_eq!(
+            sk_set.secret_key().sign("msg"),
+            plain.sign(&sk_set.secret_key(), "msg")
+        );
+    }
+
+    #[test]
+    fn test_hash_functions() {
+        // The digests of the empty string.
+        assert_eq!(
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+            hex_fmt::HexFmt(Sha256.hash(b"")).to_string()
+        );
+        #[cfg(feature = "blake3")]
+        assert_eq!(
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262",
+            hex_fmt::HexFmt(Blake3.hash(b"")).to_string()
+        );
+    }
+
+    #[test]
+    fn test_sha_256_and_blake3_suites() {
+        let sk = SecretKey::random();
+        let pk = sk.public_key();
+        let sha_256 = Ciphersuite::sha_256();
+        let sig = sha_256.sign(&sk, "msg");
+        assert!(sha_256.verify(&pk, &sig, "msg"));
+        assert!(!sha_256.verify(&pk, &sig, "other msg"));
+        assert!(!Ciphersuite::sha3_256().verify(&pk, &sig, "msg"));
+
+        #[cfg(feature = "blake3")]
+        {
+            let blake3 = Ciphersuite::blake3();
+            let sig = blake3.sign(&sk, "msg");
+            assert!(blake3.verify(&pk, &sig, "msg"));
+            assert!(!sha_256.verify(&pk, &sig, "msg"));
+        }
+
+        #[cfg(feature = "encryption")]
+        {
+            let ct = sha_256.encrypt_with_rng(&pk, &mut rand::thread_rng(), b"secret");
+            assert!(sha_256.verify_ciphertext(&ct));
+            assert!(!ct.verify());
+            assert_eq!(Some(b"secret".to_vec()), sha_256.decrypt(&sk, &ct));
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encryption() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let suite = Ciphersuite::keccak_256();
+        let ct = suite.encrypt_with_rng(&pk_set.public_key(), &mut rng, b"secret");
+        assert!(suite.verify_ciphertext(&ct));
+        assert!(!ct.verify());
+        assert_eq!(
+            Some(b"secret".to_vec()),
+            suite.decrypt(&sk_set.secret_key(), &ct)
+        );
+
+        let shares: Vec<DecryptionShare> = (0..2)
+            .map(|i| {
+                suite
+                    .decrypt_share(&sk_set.secret_key_share(i), &ct)
+                    .unwrap()
+            })
+            .collect();
+        let pk_share = pk_set.public_key_share(1);
+        assert!(suite.verify_decryption_share(&pk_share, &shares[1], &ct));
+        let plaintext = suite.decrypt_with_shares(&pk_set, shares.iter().enumerate(), &ct);
+        assert_eq!(Ok(b"secret".to_vec()), plaintext);
+
+        // Without a domain separation tag, SHA3-256 produces the crate's usual ciphertexts.
+        let plain = Ciphersuite::new(Sha3_256, b"");
+        let ct = plain.encrypt_with_rng(&pk_set.public_key(), &mut rng, b"secret");
+        assert_eq!(Some(b"secret".to_vec()), sk_set.secret_key().decrypt(&ct));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encryption_with_ad() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let suite = Ciphersuite::keccak_256();
+        let pk = pk_set.public_key();
+        let ct = suite.encrypt_with_ad_and_rng(&pk, &mut rng, b"secret", b"epoch 1");
+        assert!(suite.verify_ciphertext_with_ad(&ct, b"epoch 1"));
+        assert!(!suite.verify_ciphertext_with_ad(&ct, b"epoch 2"));
+        assert!(!suite.verify_ciphertext(&ct));
+        let sk = sk_set.secret_key();
+        assert_eq!(None, suite.decrypt(&sk, &ct));
+        let plaintext = suite.decrypt_with_ad(&sk, &ct, b"epoch 1");
+        assert_eq!(Some(b"secret".to_vec()), plaintext);
+
+        let sk_share = sk_set.secret_key_share(1);
+        assert_eq!(None, suite.decrypt_share(&sk_share, &ct));
+        let share = suite
+            .decrypt_share_with_ad(&sk_share, &ct, b"epoch 1")
+            .expect("valid ciphertext");
+        let pk_share = pk_set.public_key_share(1);
+        assert!(suite.verify_decryption_share_with_ad(&pk_share, &share, &ct, b"epoch 1"));
+        assert!(!suite.verify_decryption_share(&pk_share, &share, &ct));
+
+        // The plain SHA3-256 suite binds associated data like the crate's own methods.
+        let plain = Ciphersuite::new(Sha3_256, b"");
+        let ct = plain.encrypt_with_ad_and_rng(&pk, &mut rng, b"secret", b"epoch 1");
+        let plaintext = sk.decrypt_with_ad(&ct, b"epoch 1");
+        assert_eq!(Some(b"secret".to_vec()), plaintext);
+    }
+}
@@ -0,0 +1,261 @@
+//! Witness encodings for proving signature verification in SNARK circuits.
+//!
+//! Circuits over a different field, such as BN254's scalar field, represent BLS12-381 base field
+//! elements as _limbs_: little-endian digits of a fixed number of bits, each small enough to fit
+//! into a native field element. Non-native field gadgets, e.g. in arkworks and halo2, expect the
+//! coordinates of curve points in that form. A [`LimbLayout`] specifies the limb size and count,
+//! and converts public keys, signatures and hashes to `G2` into [`G1Witness`]es and
+//! [`G2Witness`]es with the affine coordinates split accordingly:
+//!
+//! * Limbs are ordered from least to most significant, and the most significant limbs are zero
+//!   if the layout has room for more than 381 bits.
+//! * An element `c0 + c1 * u` of the quadratic extension is given as `c0` and `c1`, in that
+//!   order. Note that the canonical byte encoding puts `c1` first.
+//! * The identity element has the coordinates `(0, 0)` and its `infinity` flag set.
+//!
+//! [`verification_witness`] collects everything a circuit checking `pk.verify(sig, msg)` needs:
+//! both pairings' inputs, and the SHA3-256 digest of the message that seeds the hash to `G2`.
+//! That hash samples the point with a ChaCha20 random number generator, which is impractical to
+//! prove in a circuit; circuits usually take the hash point as a public input, and reproduce it
+//! outside with `hash_g2`.
+//!
+//! This module is only available with the `circuit` feature.
+
+use group::{CurveAffine, CurveProjective};
+use pairing::bls12_381::{G1Affine, G2Affine};
+use serde::{Deserialize, Serialize};
+
+use crate::util::sha3_256;
+use crate::{hash_g2, PublicKey, Signature};
+
+/// The number of bits of a base field element.
+const FQ_BITS: usize = 381;
+
+/// The size of a base field element's big-endian representation in bytes.
+const FQ_SIZE: usize = 48;
+
+/// The flag bits in the first byte of an encoded point.
+const FLAGS_MASK: u8 = 0xe0;
+
+/// The flag marking the identity element in an encoded point.
+const INFINITY_FLAG: u8 = 0x40;
+
+/// The size and number of limbs that base field elements are split into.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LimbLayout {
+    limb_bits: usize,
+    num_limbs: usize,
+}
+
+impl LimbLayout {
+    /// Returns a layout with `num_limbs` limbs of `limb_bits` bits each.
+    ///
+    /// Returns `None` unless the limbs have between 1 and 128 bits and there is room for 381
+    /// bits in total.
+    pub fn new(limb_bits: usize, num_limbs: usize) -> Option<Self> {
+        if limb_bits == 0 || limb_bits > 128 || limb_bits * num_limbs < FQ_BITS {
+            return None;
+        }
+        Some(LimbLayout {
+            limb_bits,
+            num_limbs,
+        })
+    }
+
+    /// Returns the number of bits per limb.
+    pub fn limb_bits(&self) -> usize {
+        self.limb_bits
+    }
+
+    /// Returns the number of limbs per base field element.
+    pub fn num_limbs(&self) -> usize {
+        self.num_limbs
+    }
+
+    /// Returns the affine coordinates of a point in `G1`.
+    pub fn g1(&self, point: &G1Affine) -> G1Witness {
+        let bytes = point.into_uncompressed();
+        let (x, y, infinity) = self.coordinates(bytes.as_ref());
+        G1Witness { x, y, infinity }
+    }
+
+    /// Returns the affine coordinates of a point in `G2`.
+    pub fn g2(&self, point: &G2Affine) -> G2Witness {
+        // The encoding is `x.c1 | x.c0 | y.c1 | y.c0`.
+        let bytes = point.into_uncompressed();
+        let (x, y, infinity) = self.coordinates(bytes.as_ref());
+        let x = Fq2Limbs {
+            c0: x[self.num_limbs..].to_vec(),
+            c1: x[..self.num_limbs].to_vec(),
+        };
+        let y = Fq2Limbs {
+            c0: y[self.num_limbs..].to_vec(),
+            c1: y[..self.num_limbs].to_vec(),
+        };
+        G2Witness { x, y, infinity }
+    }
+
+    /// Splits an uncompressed point into the limbs of its two halves, and the infinity flag.
+    fn coordinates(&self, bytes: &[u8]) -> (Vec<u128>, Vec<u128>, bool) {
+        let mut bytes = bytes.to_vec();
+        let infinity = bytes[0] & INFINITY_FLAG != 0;
+        bytes[0] &= !FLAGS_MASK;
+        let (x, y) = bytes.split_at(bytes.len() / 2);
+        let limbs = |half: &[u8]| -> Vec<u128> {
+            half.chunks(FQ_SIZE).flat_map(|fq| self.limbs(fq)).collect()
+        };
+        (limbs(x), limbs(y), infinity)
+    }
+
+    /// Splits a base field element's big-endian representation into limbs.
+    fn limbs(&self, be_bytes: &[u8]) -> Vec<u128> {
+        let mut limbs = vec![0u128; self.num_limbs];
+        let bits = be_bytes
+            .iter()
+            .rev()
+            .flat_map(|byte| (0..8).map(move |j| (byte >> j) & 1));
+        for (i, bit) in bits.enumerate() {
+            // The value is less than `2^381`, so higher bits are zero and don't need a limb.
+            if bit == 1 {
+                limbs[i / self.limb_bits] |= 1 << (i % self.limb_bits);
+            }
+        }
+        limbs
+    }
+}
+
+/// The limbs of an element `c0 + c1 * u` of the quadratic extension of the base field.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fq2Limbs {
+    /// The constant part.
+    pub c0: Vec<u128>,
+    /// The coefficient of `u`.
+    pub c1: Vec<u128>,
+}
+
+/// The affine coordinates of a point in `G1`, as limbs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct G1Witness {
+    /// The `x` coordinate.
+    pub x: Vec<u128>,
+    /// The `y` coordinate.
+    pub y: Vec<u128>,
+    /// Whether this is the identity element.
+    pub infinity: bool,
+}
+
+/// The affine coordinates of a point in `G2`, as limbs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct G2Witness {
+    /// The `x` coordinate.
+    pub x: Fq2Limbs,
+    /// The `y` coordinate.
+    pub y: Fq2Limbs,
+    /// Whether this is the identity element.
+    pub infinity: bool,
+}
+
+/// The inputs of a circuit proving that a signature is valid.
+///
+/// The signature is valid if `e(g1, signature) == e(public_key, hash)`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VerificationWitness {
+    /// The layout of all limbs.
+    pub layout: LimbLayout,
+    /// The public key.
+    pub public_key: G1Witness,
+    /// The SHA3-256 digest of the message, which seeds the hash to `G2`.
+    pub message_digest: [u8; 32],
+    /// The message's hash to `G2`.
+    pub hash: G2Witness,
+    /// The signature.
+    pub signature: G2Witness,
+}
+
+/// Returns the witness for the verification of `sig` as a signature of `msg` under `pk`.
+///
+/// This doesn't check whether the signature is valid.
+pub fn verification_witness<M: AsRef<[u8]>>(
+    layout: LimbLayout,
+    pk: &PublicKey,
+    sig: &Signature,
+    msg: M,
+) -> VerificationWitness {
+    VerificationWitness {
+        layout,
+        public_key: layout.g1(&pk.0.into_affine()),
+        message_digest: sha3_256(msg.as_ref()),
+        hash: layout.g2(&hash_g2(msg).into_affine()),
+        signature: layout.g2(&sig.0.into_affine()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKey;
+
+    /// Returns the number represented by the limbs, in big-endian hexadecimal.
+    fn to_hex(layout: LimbLayout, limbs: &[u128]) -> String {
+        let mut bits: Vec<u8> = limbs
+            .iter()
+            .flat_map(|limb| (0..layout.limb_bits()).map(move |j| (limb >> j) as u8 & 1))
+            .collect();
+        bits.truncate(FQ_SIZE * 8);
+        let bytes: Vec<u8> = bits
+            .chunks(8)
+            .rev()
+            .map(|byte| byte.iter().rev().fold(0, |acc, bit| acc << 1 | bit))
+            .collect();
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    #[test]
+    fn test_layout() {
+        assert_eq!(None, LimbLayout::new(64, 5));
+        assert_eq!(None, LimbLayout::new(192, 2));
+        assert!(LimbLayout::new(64, 6).is_some());
+
+        // The generator of `G1`, from the BLS12-381 specification.
+        let x = "17f1d3a73197d7942695638c4fa9ac0fc3688c4f9774b905a14e3a3f171bac586c55e83ff97a1aeffb3af00adb22c6bb";
+        for (bits, num) in &[(64, 6), (55, 7), (104, 4), (128, 3)] {
+            let layout = LimbLayout::new(*bits, *num).expect("valid layout");
+            let witness = layout.g1(&G1Affine::one());
+            assert_eq!(*num, witness.x.len());
+            assert!(witness
+                .x
+                .iter()
+                .all(|limb| *bits == 128 || limb >> bits == 0));
+            assert_eq!(x, to_hex(layout, &witness.x));
+            assert!(!witness.infinity);
+        }
+
+        let layout = LimbLayout::new(64, 6).expect("valid layout");
+        let identity = layout.g2(&G2Affine::zero());
+        assert!(identity.infinity);
+        assert!(identity
+            .x
+            .c0
+            .iter()
+            .chain(&identity.y.c1)
+            .all(|limb| *limb == 0));
+    }
+
+    #[test]
+    fn test_verification_witness() {
+        let layout = LimbLayout::new(104, 4).expect("valid layout");
+        let sk = SecretKey::random();
+        let sig = sk.sign("Hello");
+        let witness = verification_witness(layout, &sk.public_key(), &sig, "Hello");
+        assert_eq!(layout.g2(&sig.0.into_affine()), witness.signature);
+        assert_eq!(layout.g2(&hash_g2("Hello").into_affine()), witness.hash);
+        assert_eq!(sha3_256(b"Hello"), witness.message_digest);
+
+        // `c1` comes first in the canonical encoding of `G2` points.
+        let bytes = sig.0.into_affine().into_uncompressed();
+        let x_c0 = &bytes.as_ref()[FQ_SIZE..2 * FQ_SIZE];
+        let x_c0_hex: String = x_c0.iter().map(|byte| format!("{:02x}", byte)).collect();
+        assert_eq!(x_c0_hex, to_hex(layout, &witness.signature.x.c0));
+    }
+}
@@ -20,10 +20,14 @@ macro_rules! impl_codec_for {
     };
 }
 
-use crate::{Ciphertext, DecryptionShare, PublicKey, PublicKeySet, Signature};
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{PublicKey, PublicKeySet, Signature};
 
 impl_codec_for!(PublicKey);
 impl_codec_for!(Signature);
-impl_codec_for!(DecryptionShare);
 impl_codec_for!(PublicKeySet);
+#[cfg(feature = "encryption")]
+impl_codec_for!(DecryptionShare);
+#[cfg(feature = "encryption")]
 impl_codec_for!(Ciphertext);
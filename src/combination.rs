@@ -0,0 +1,359 @@
+//! Proofs that a coordinator combined the right shares correctly.
+//!
+//! A coordinator that collects signature or decryption shares and publishes the result can also
+//! publish a [`CombinationProof`]: the `threshold + 1` shares it used, by index. Anyone with the
+//! public key set can then check that each of these shares is valid and that their Lagrange
+//! combination is the published signature or plaintext, and hold the share holders and the
+//! coordinator accountable for it.
+//!
+//! Verifying the shares one by one would take two pairings per share. Instead, they are checked
+//! together: with coefficients `r_i` derived by hashing the whole proof, the random linear
+//! combination `sum(r_i * share_i)` must be valid for `sum(r_i * pk_share_i)`, which takes two
+//! pairings in total. An invalid share passes this check only with negligible probability.
+//!
+//! If some share holders may send invalid shares, [`combine_signatures_robust`] verifies the
+//! shares before combining them, and reports the invalid ones so that their senders can be
+//! penalized. It only verifies as many shares as it needs: If the first `threshold + 1` are valid,
+//! that is a single batch verification.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use pairing::Engine;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::util::sha3_256;
+#[cfg(feature = "encryption")]
+use crate::{hash_g1_g2_ad, Ciphertext, DecryptionShare};
+use crate::{hash_g2, Fr, G1Affine, PEngine, PublicKeySet, Signature, SignatureShare, G1, G2};
+
+/// The domain separator for the batch verification coefficients.
+const DST: &[u8] = b"threshold_crypto combination proof";
+
+/// The outcome of a robust combination: the result, and the indices of the invalid shares.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Robust<T> {
+    /// The combined result and its proof, or `Error::NotEnoughShares` if there were too few valid
+    /// shares.
+    pub result: Result<T>,
+    /// The indices of the shares that were verified and turned out to be invalid.
+    pub invalid: BTreeSet<usize>,
+}
+
+/// The shares a result was combined from, by index.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CombinationProof<S> {
+    shares: BTreeMap<usize, S>,
+}
+
+impl<S> CombinationProof<S> {
+    /// Returns the indices of the shares that were combined.
+    pub fn indices(&self) -> impl Iterator<Item = usize> + '_ {
+        self.shares.keys().cloned()
+    }
+
+    /// Returns the shares that were combined, by index.
+    pub fn shares(&self) -> &BTreeMap<usize, S> {
+        &self.shares
+    }
+
+    /// Takes the first `threshold + 1` shares, or returns an error if there are not enough, or
+    /// duplicates among them.
+    fn collect<'a, I>(pk_set: &PublicKeySet, shares: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (usize, &'a S)>,
+        S: Clone + 'a,
+    {
+        let mut map = BTreeMap::new();
+        for (i, share) in shares.into_iter().take(pk_set.threshold() + 1) {
+            if map.insert(i, share.clone()).is_some() {
+                return Err(Error::DuplicateEntry);
+            }
+        }
+        if map.len() <= pk_set.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        Ok(CombinationProof { shares: map })
+    }
+
+    /// Returns the public key shares' random linear combination, and the coefficients.
+    fn coefficients(&self, pk_set: &PublicKeySet, context: &[u8]) -> (G1, Vec<Fr>) {
+        let mut rng = ChaChaRng::from_seed(sha3_256(context));
+        let coeffs: Vec<Fr> = self.shares.keys().map(|_| Fr::random(&mut rng)).collect();
+        count!(Exponentiation, coeffs.len());
+        let mut pk = G1::zero();
+        for (i, r) in self.indices().zip(&coeffs) {
+            let mut term = (pk_set.public_key_share(i).0).0;
+            term.mul_assign(*r);
+            pk.add_assign(&term);
+        }
+        (pk, coeffs)
+    }
+}
+
+impl CombinationProof<SignatureShare> {
+    /// Returns `true` if the proof contains `threshold + 1` valid shares for the message, and
+    /// they combine to the signature.
+    pub fn verify_signature<M: AsRef<[u8]>>(
+        &self,
+        pk_set: &PublicKeySet,
+        msg: M,
+        sig: &Signature,
+    ) -> bool {
+        if self.shares.len() != pk_set.threshold() + 1 {
+            return false;
+        }
+        let mut context = DST.to_vec();
+        context.extend(pk_set.to_bytes());
+        context.extend(sha3_256(msg.as_ref()).as_ref());
+        for (i, share) in &self.shares {
+            context.extend(&(*i as u64).to_be_bytes());
+            context.extend(share.to_bytes().as_ref());
+        }
+        let (pk, coeffs) = self.coefficients(pk_set, &context);
+        let mut combined = G2::zero();
+        for (share, r) in self.shares.values().zip(coeffs) {
+            let mut term = (share.0).0;
+            term.mul_assign(r);
+            combined.add_assign(&term);
+        }
+        count!(Pairing, 2);
+        let valid =
+            PEngine::pairing(pk, hash_g2(msg)) == PEngine::pairing(G1Affine::one(), combined);
+        valid && pk_set.combine_signatures(&self.shares).as_ref() == Ok(sig)
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl CombinationProof<DecryptionShare> {
+    /// Returns `true` if the proof contains `threshold + 1` valid shares for the ciphertext, and
+    /// they decrypt it to the plaintext.
+    pub fn verify_decryption(
+        &self,
+        pk_set: &PublicKeySet,
+        ct: &Ciphertext,
+        plaintext: &[u8],
+    ) -> bool {
+        self.verify_decryption_with_ad(pk_set, ct, &[], plaintext)
+    }
+
+    /// Returns `true` if the proof contains `threshold + 1` valid shares for the ciphertext with
+    /// the associated data, and they decrypt it to the plaintext.
+    pub fn verify_decryption_with_ad(
+        &self,
+        pk_set: &PublicKeySet,
+        ct: &Ciphertext,
+        ad: &[u8],
+        plaintext: &[u8],
+    ) -> bool {
+        if self.shares.len() != pk_set.threshold() + 1 {
+            return false;
+        }
+        let mut context = DST.to_vec();
+        context.extend(pk_set.to_bytes());
+        context.extend(ct.to_bytes());
+        // Without associated data, the context is unchanged.
+        if !ad.is_empty() {
+            context.extend(&(ad.len() as u64).to_be_bytes());
+            context.extend(ad);
+        }
+        for (i, share) in &self.shares {
+            context.extend(&(*i as u64).to_be_bytes());
+            context.extend(share.to_bytes().as_ref());
+        }
+        let (pk, coeffs) = self.coefficients(pk_set, &context);
+        let mut combined = G1::zero();
+        for (share, r) in self.shares.values().zip(coeffs) {
+            let mut term = share.0;
+            term.mul_assign(r);
+            combined.add_assign(&term);
+        }
+        let Ciphertext(ref u, ref v, ref w) = *ct;
+        count!(Pairing, 2);
+        if PEngine::pairing(combined, hash_g1_g2_ad(*u, v, ad)) != PEngine::pairing(pk, *w) {
+            return false;
+        }
+        pk_set.decrypt(&self.shares, ct).ok().as_deref() == Some(plaintext)
+    }
+}
+
+/// Combines the first `threshold + 1` shares into a signature, and returns it with the proof.
+///
+/// Like `PublicKeySet::combine_signatures`, this doesn't check the shares' validity.
+pub fn combine_signatures<'a, I>(
+    pk_set: &PublicKeySet,
+    shares: I,
+) -> Result<(Signature, CombinationProof<SignatureShare>)>
+where
+    I: IntoIterator<Item = (usize, &'a SignatureShare)>,
+{
+    let proof = CombinationProof::collect(pk_set, shares)?;
+    let sig = pk_set.combine_signatures(&proof.shares)?;
+    Ok((sig, proof))
+}
+
+/// Verifies the shares and combines `threshold + 1` valid ones into a signature, and returns it
+/// with the proof, together with the indices of any invalid shares.
+///
+/// The shares are verified in order of their indices, `threshold + 1` at a time in a batch,
+/// replacing invalid ones with the next shares until there are enough valid ones. So shares that
+/// weren't needed are not verified, and the invalid ones among them are not reported.
+pub fn combine_signatures_robust<M: AsRef<[u8]>>(
+    pk_set: &PublicKeySet,
+    shares: &BTreeMap<usize, SignatureShare>,
+    msg: M,
+) -> Robust<(Signature, CombinationProof<SignatureShare>)> {
+    let msg = msg.as_ref();
+    let mut valid = BTreeMap::new();
+    let mut invalid = BTreeSet::new();
+    let mut remaining = shares.iter();
+    while valid.len() <= pk_set.threshold() {
+        let needed = pk_set.threshold() + 1 - valid.len();
+        let batch: BTreeMap<usize, SignatureShare> = remaining
+            .by_ref()
+            .take(needed)
+            .map(|(i, share)| (*i, share.clone()))
+            .collect();
+        if batch.is_empty() {
+            let result = Err(Error::NotEnoughShares);
+            return Robust { result, invalid };
+        }
+        let bad = pk_set.invalid_signature_shares(&batch, msg);
+        valid.extend(batch.into_iter().filter(|(i, _)| !bad.contains(i)));
+        invalid.extend(bad);
+    }
+    let proof = CombinationProof { shares: valid };
+    let result = pk_set
+        .combine_signatures(&proof.shares)
+        .map(|sig| (sig, proof));
+    Robust { result, invalid }
+}
+
+/// Combines the first `threshold + 1` shares to decrypt the ciphertext, and returns the plaintext
+/// with the proof.
+///
+/// Like `PublicKeySet::decrypt`, this doesn't check the shares' validity.
+#[cfg(feature = "encryption")]
+pub fn decrypt<'a, I>(
+    pk_set: &PublicKeySet,
+    shares: I,
+    ct: &Ciphertext,
+) -> Result<(Vec<u8>, CombinationProof<DecryptionShare>)>
+where
+    I: IntoIterator<Item = (usize, &'a DecryptionShare)>,
+{
+    let proof = CombinationProof::collect(pk_set, shares)?;
+    let plaintext = pk_set.decrypt(&proof.shares, ct)?;
+    Ok((plaintext, proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_signature_proof() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let shares: BTreeMap<usize, SignatureShare> = (0..5)
+            .map(|i| (i, sk_set.secret_key_share(i).sign("msg")))
+            .collect();
+        let (sig, proof) = combine_signatures(&pk_set, shares.iter().skip(1).map(|(i, s)| (*i, s)))
+            .expect("enough shares");
+        assert_eq!(vec![1, 2, 3], proof.indices().collect::<Vec<_>>());
+        assert!(proof.verify_signature(&pk_set, "msg", &sig));
+        assert!(!proof.verify_signature(&pk_set, "other msg", &sig));
+
+        // Valid signature, but one of the claimed shares is invalid.
+        let mut forged = proof.clone();
+        forged
+            .shares
+            .insert(2, sk_set.secret_key_share(2).sign("other msg"));
+        assert!(!forged.verify_signature(&pk_set, "msg", &sig));
+        // Swapped shares are valid, but not for the claimed indices.
+        let mut swapped = proof.clone();
+        swapped.shares.insert(1, shares[&2].clone());
+        swapped.shares.insert(2, shares[&1].clone());
+        assert!(!swapped.verify_signature(&pk_set, "msg", &sig));
+
+        let too_few = combine_signatures(&pk_set, shares.iter().take(2).map(|(i, s)| (*i, s)));
+        assert_eq!(Some(Error::NotEnoughShares), too_few.err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_decryption_proof() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let ct = pk_set.public_key().encrypt_with_rng(&mut rng, b"secret");
+        let shares: Vec<DecryptionShare> = (0..3)
+            .map(|i| sk_set.secret_key_share(i).decrypt_share_no_verify(&ct))
+            .collect();
+        let (plaintext, proof) =
+            decrypt(&pk_set, shares.iter().enumerate(), &ct).expect("enough shares");
+        assert_eq!(b"secret".to_vec(), plaintext);
+        assert!(proof.verify_decryption(&pk_set, &ct, b"secret"));
+        assert!(!proof.verify_decryption(&pk_set, &ct, b"secreT"));
+
+        let mut forged = proof.clone();
+        forged.shares.insert(1, shares[2].clone());
+        assert!(!forged.verify_decryption(&pk_set, &ct, b"secret"));
+
+        // Shares of a ciphertext with associated data only verify with the same data.
+        let ct = pk_set
+            .public_key()
+            .encrypt_with_ad_and_rng(&mut rng, b"secret", b"epoch 1");
+        let shares: Vec<DecryptionShare> = (0..2)
+            .map(|i| sk_set.secret_key_share(i).decrypt_share_no_verify(&ct))
+            .collect();
+        let (_, proof) = decrypt(&pk_set, shares.iter().enumerate(), &ct).expect("enough shares");
+        assert!(proof.verify_decryption_with_ad(&pk_set, &ct, b"epoch 1", b"secret"));
+        assert!(!proof.verify_decryption_with_ad(&pk_set, &ct, b"epoch 2", b"secret"));
+        assert!(!proof.verify_decryption(&pk_set, &ct, b"secret"));
+    }
+
+    #[test]
+    fn test_robust_combination() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let mut shares: BTreeMap<usize, SignatureShare> = (0..6)
+            .map(|i| (i, sk_set.secret_key_share(i).sign("msg")))
+            .collect();
+        let robust = combine_signatures_robust(&pk_set, &shares, "msg");
+        assert!(robust.invalid.is_empty());
+        let (sig, proof) = robust.result.expect("valid shares");
+        assert!(pk_set.public_key().verify(&sig, "msg"));
+        assert_eq!(vec![0, 1, 2], proof.indices().collect::<Vec<_>>());
+
+        // Shares 1 and 3 are invalid, share 5 is not needed.
+        for i in &[1, 3, 5] {
+            shares.insert(*i, sk_set.secret_key_share(*i).sign("other msg"));
+        }
+        let robust = combine_signatures_robust(&pk_set, &shares, "msg");
+        assert_eq!(
+            vec![1, 3],
+            robust.invalid.iter().cloned().collect::<Vec<_>>()
+        );
+        let (robust_sig, proof) = robust.result.expect("enough valid shares");
+        assert_eq!(sig, robust_sig);
+        assert_eq!(vec![0, 2, 4], proof.indices().collect::<Vec<_>>());
+        assert!(proof.verify_signature(&pk_set, "msg", &sig));
+
+        shares.remove(&4);
+        let robust = combine_signatures_robust(&pk_set, &shares, "msg");
+        assert_eq!(Err(Error::NotEnoughShares), robust.result);
+        assert_eq!(
+            vec![1, 3, 5],
+            robust.invalid.iter().cloned().collect::<Vec<_>>()
+        );
+    }
+}
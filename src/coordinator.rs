@@ -0,0 +1,324 @@
+//! A reference threshold signing coordinator and participant.
+//!
+//! The [`Coordinator`] opens signing requests, collects the participants' signature shares in a
+//! [`SigningSession`](../session/struct.SigningSession.html) per request, and hands out the
+//! combined signature. A [`Participant`] answers share requests using any
+//! [`ShareSigner`](../signer/trait.ShareSigner.html) backend.
+//!
+//! Both roles only exchange the serde-serializable messages defined here, so they can be put
+//! behind any transport. `proto/coordinator.proto` describes the same protocol as a gRPC service,
+//! with the messages' group elements in their compressed `to_bytes` encoding. With the `grpc`
+//! feature, the `grpc` module serves both roles with `tonic`.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::session::{SigningSession, SigningSessionState};
+use crate::signer::{IndexedShare, ShareSigner};
+use crate::state::Resumable;
+use crate::{PublicKeySet, Signature, SignatureShare};
+
+/// A request for participants to sign a message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShareRequest {
+    /// The coordinator's ID for this signing request.
+    pub request_id: u64,
+    /// The message to be signed.
+    pub message: Vec<u8>,
+}
+
+/// A participant's signature share in response to a `ShareRequest`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ShareSubmission {
+    /// The ID of the request being answered.
+    pub request_id: u64,
+    /// The participant's share index.
+    pub index: usize,
+    /// The participant's signature share.
+    pub share: SignatureShare,
+}
+
+/// The state of a signing request.
+// The status is a short-lived response value, so boxing the signature isn't worth it.
+#[allow(clippy::large_enum_variant)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// More shares are needed.
+    Pending {
+        /// The number of valid shares collected so far.
+        shares: usize,
+        /// The number of shares needed, i.e. `threshold + 1`.
+        needed: usize,
+    },
+    /// The signature is complete.
+    Complete(Signature),
+}
+
+/// Collects signature shares for any number of concurrent signing requests.
+#[derive(Clone, Debug)]
+pub struct Coordinator {
+    pk_set: PublicKeySet,
+    next_id: u64,
+    requests: BTreeMap<u64, Request>,
+}
+
+/// An open signing request.
+#[derive(Clone, Debug)]
+struct Request {
+    session: SigningSession,
+    signature: Option<Signature>,
+}
+
+impl Coordinator {
+    /// Creates a coordinator for the committee with the given public key set.
+    pub fn new(pk_set: PublicKeySet) -> Self {
+        Coordinator {
+            pk_set,
+            next_id: 0,
+            requests: BTreeMap::new(),
+        }
+    }
+
+    /// Returns the public key set shares are verified against.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Opens a signing request for the message and returns the request to send to participants.
+    pub fn request_signature<M: AsRef<[u8]>>(&mut self, msg: M) -> ShareRequest {
+        let request_id = self.next_id;
+        self.next_id += 1;
+        let session = SigningSession::new(self.pk_set.clone(), &msg);
+        let request = Request {
+            session,
+            signature: None,
+        };
+        self.requests.insert(request_id, request);
+        ShareRequest {
+            request_id,
+            message: msg.as_ref().to_vec(),
+        }
+    }
+
+    /// Verifies and stores a participant's share, and returns the request's new state.
+    ///
+    /// Shares arriving after the signature is complete are ignored.
+    pub fn submit_share(&mut self, submission: ShareSubmission) -> Result<SignatureStatus> {
+        let request = self
+            .requests
+            .get_mut(&submission.request_id)
+            .ok_or(Error::UnknownRequest(submission.request_id))?;
+        if request.signature.is_none() {
+            request
+                .session
+                .add_share(submission.index, submission.share)?;
+            if request.session.is_complete() {
+                request.signature = Some(request.session.signature()?);
+            }
+        }
+        Ok(request.status())
+    }
+
+    /// Returns the state of the signing request, including the signature if it is complete.
+    pub fn fetch_signature(&self, request_id: u64) -> Result<SignatureStatus> {
+        self.requests
+            .get(&request_id)
+            .map(Request::status)
+            .ok_or(Error::UnknownRequest(request_id))
+    }
+
+    /// Closes the signing request, returning the signature if it was complete.
+    pub fn close(&mut self, request_id: u64) -> Result<Option<Signature>> {
+        self.requests
+            .remove(&request_id)
+            .map(|request| request.signature)
+            .ok_or(Error::UnknownRequest(request_id))
+    }
+}
+
+/// The saved state of a `Coordinator`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CoordinatorState {
+    /// The public key set shares are verified against.
+    pub public_key_set: PublicKeySet,
+    /// The ID of the next signing request.
+    pub next_id: u64,
+    /// The open signing requests' sessions, by ID.
+    pub requests: BTreeMap<u64, SigningSessionState>,
+}
+
+impl Resumable for Coordinator {
+    const PROTOCOL: &'static str = "coordinator";
+    type State = CoordinatorState;
+
+    fn to_state(&self) -> CoordinatorState {
+        let requests = self.requests.iter();
+        CoordinatorState {
+            public_key_set: self.pk_set.clone(),
+            next_id: self.next_id,
+            requests: requests
+                .map(|(id, r)| (*id, r.session.to_state()))
+                .collect(),
+        }
+    }
+
+    fn from_state(state: CoordinatorState) -> Result<Self> {
+        let mut requests = BTreeMap::new();
+        for (id, session_state) in state.requests {
+            if id >= state.next_id || session_state.public_key_set != state.public_key_set {
+                let msg = format!("request {} doesn't belong to the coordinator", id);
+                return Err(Error::InvalidState(msg));
+            }
+            let session = SigningSession::from_state(session_state)?;
+            let signature = if session.is_complete() {
+                Some(session.signature()?)
+            } else {
+                None
+            };
+            requests.insert(id, Request { session, signature });
+        }
+        Ok(Coordinator {
+            pk_set: state.public_key_set,
+            next_id: state.next_id,
+            requests,
+        })
+    }
+}
+
+impl Request {
+    fn status(&self) -> SignatureStatus {
+        match self.signature {
+            Some(ref sig) => SignatureStatus::Complete(sig.clone()),
+            None => SignatureStatus::Pending {
+                shares: self.session.shares().len(),
+                needed: self.session.public_keys().threshold() + 1,
+            },
+        }
+    }
+}
+
+/// A committee member answering the coordinator's share requests.
+#[derive(Clone, Debug)]
+pub struct Participant<S> {
+    signer: S,
+}
+
+impl<S: ShareSigner + IndexedShare> Participant<S> {
+    /// Creates a participant signing with the given share holder.
+    pub fn new(signer: S) -> Self {
+        Participant { signer }
+    }
+
+    /// Returns the share holder.
+    pub fn signer(&self) -> &S {
+        &self.signer
+    }
+
+    /// Signs the requested message with the participant's share.
+    pub fn handle_request(&self, request: &ShareRequest) -> Result<ShareSubmission> {
+        Ok(ShareSubmission {
+            request_id: request.request_id,
+            index: self.signer.index(),
+            share: self.signer.sign_share(&request.message)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::signer::InMemoryShare;
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_coordinator() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let participants: Vec<_> = (0..3)
+            .map(|i| Participant::new(InMemoryShare::new(i, sk_set.secret_key_share(i))))
+            .collect();
+        let mut coordinator = Coordinator::new(sk_set.public_keys());
+
+        let req0 = coordinator.request_signature("first");
+        let req1 = coordinator.request_signature("second");
+        assert_ne!(req0.request_id, req1.request_id);
+
+        // A share for the wrong request is rejected.
+        let mut misdirected = participants[0].handle_request(&req1).unwrap();
+        misdirected.request_id = req0.request_id;
+        assert_eq!(
+            Err(Error::InvalidShare(0)),
+            coordinator.submit_share(misdirected)
+        );
+
+        let pending = SignatureStatus::Pending {
+            shares: 1,
+            needed: 2,
+        };
+        let sub = participants[2].handle_request(&req0).unwrap();
+        assert_eq!(Ok(pending), coordinator.submit_share(sub));
+        let sub = participants[1].handle_request(&req0).unwrap();
+        let sig = match coordinator.submit_share(sub) {
+            Ok(SignatureStatus::Complete(sig)) => sig,
+            status => panic!("unexpected status {:?}", status),
+        };
+        assert!(sk_set.public_keys().public_key().verify(&sig, "first"));
+        assert_eq!(
+            Ok(SignatureStatus::Complete(sig.clone())),
+            coordinator.fetch_signature(req0.request_id)
+        );
+
+        // Late shares are ignored.
+        let sub = participants[0].handle_request(&req0).unwrap();
+        assert_eq!(
+            Ok(SignatureStatus::Complete(sig.clone())),
+            coordinator.submit_share(sub)
+        );
+
+        assert_eq!(Ok(Some(sig)), coordinator.close(req0.request_id));
+        assert_eq!(
+            Err(Error::UnknownRequest(req0.request_id)),
+            coordinator.fetch_signature(req0.request_id)
+        );
+    }
+
+    #[test]
+    fn test_resume_coordinator() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let participants: Vec<_> = (0..2)
+            .map(|i| Participant::new(InMemoryShare::new(i, sk_set.secret_key_share(i))))
+            .collect();
+        let mut coordinator = Coordinator::new(sk_set.public_keys());
+        let req0 = coordinator.request_signature("first");
+        let req1 = coordinator.request_signature("second");
+        for participant in &participants {
+            let sub = participant.handle_request(&req0).unwrap();
+            coordinator.submit_share(sub).unwrap();
+        }
+        let sub = participants[0].handle_request(&req1).unwrap();
+        coordinator.submit_share(sub).unwrap();
+
+        let bytes = bincode::serialize(&coordinator.save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&bytes).expect("deserialize state");
+        let mut restored = Coordinator::restore_state(saved).expect("valid state");
+        assert_eq!(
+            coordinator.fetch_signature(req0.request_id),
+            restored.fetch_signature(req0.request_id)
+        );
+        let sub = participants[1].handle_request(&req1).unwrap();
+        match restored.submit_share(sub) {
+            Ok(SignatureStatus::Complete(_)) => (),
+            status => panic!("unexpected status {:?}", status),
+        }
+        // New requests don't reuse IDs.
+        assert_eq!(2, restored.request_signature("third").request_id);
+
+        let mut saved = coordinator.save_state();
+        saved.state.next_id = 1;
+        assert!(Coordinator::restore_state(saved).is_err());
+    }
+}
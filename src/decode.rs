@@ -0,0 +1,193 @@
+//! Decoding of the canonical byte representations, with errors that locate the problem.
+
+use ff::{PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective, EncodedPoint, GroupDecodingError};
+use zeroize::Zeroize;
+
+use crate::error::{DecodeError, DecodeReason, DecodeResult};
+use crate::{Fr, FrRepr, SK_SIZE};
+
+/// Reads consecutive fields from a byte slice, keeping track of the offset.
+pub(crate) struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+    name: &'static str,
+}
+
+impl<'a> Reader<'a> {
+    /// Creates a reader for the encoding of a value of the named type.
+    pub(crate) fn new(bytes: &'a [u8], name: &'static str) -> Self {
+        Reader {
+            bytes,
+            offset: 0,
+            name,
+        }
+    }
+
+    /// Returns an error for the field starting at the current offset.
+    pub(crate) fn error(&self, field: &'static str, reason: DecodeReason) -> DecodeError {
+        DecodeError {
+            offset: self.offset,
+            field,
+            reason,
+        }
+    }
+
+    /// Returns the number of bytes left.
+    pub(crate) fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    /// Reads the next `len` bytes.
+    pub(crate) fn take(&mut self, len: usize, field: &'static str) -> DecodeResult<&'a [u8]> {
+        if self.remaining() < len {
+            let needed = len - self.remaining();
+            return Err(self.error(field, DecodeReason::UnexpectedEnd(needed)));
+        }
+        let bytes = &self.bytes[self.offset..(self.offset + len)];
+        self.offset += len;
+        Ok(bytes)
+    }
+
+    /// Reads all remaining bytes.
+    #[cfg(feature = "encryption")]
+    pub(crate) fn rest(&mut self) -> &'a [u8] {
+        let bytes = &self.bytes[self.offset..];
+        self.offset = self.bytes.len();
+        bytes
+    }
+
+    /// Reads a big-endian `u64`.
+    #[cfg(feature = "dkg")]
+    pub(crate) fn u64(&mut self, field: &'static str) -> DecodeResult<u64> {
+        let mut array = [0u8; 8];
+        array.copy_from_slice(self.take(8, field)?);
+        Ok(u64::from_be_bytes(array))
+    }
+
+    /// Reads a compressed curve point.
+    pub(crate) fn point<C: CurveAffine>(
+        &mut self,
+        field: &'static str,
+    ) -> DecodeResult<C::Projective> {
+        self.encoded_point::<C::Compressed>(field)
+    }
+
+    /// Reads an uncompressed curve point.
+    pub(crate) fn uncompressed_point<C: CurveAffine>(
+        &mut self,
+        field: &'static str,
+    ) -> DecodeResult<C::Projective> {
+        self.encoded_point::<C::Uncompressed>(field)
+    }
+
+    /// Reads a compressed curve point. With the `strict` feature, the identity is rejected.
+    pub(crate) fn strict_point<C: CurveAffine>(
+        &mut self,
+        field: &'static str,
+    ) -> DecodeResult<C::Projective> {
+        let start = self.offset;
+        let point = self.point::<C>(field)?;
+        check_strict(point, start, field)
+    }
+
+    /// Reads an uncompressed curve point. With the `strict` feature, the identity is rejected.
+    pub(crate) fn strict_uncompressed_point<C: CurveAffine>(
+        &mut self,
+        field: &'static str,
+    ) -> DecodeResult<C::Projective> {
+        let start = self.offset;
+        let point = self.uncompressed_point::<C>(field)?;
+        check_strict(point, start, field)
+    }
+
+    /// Reads a curve point in the given encoding.
+    fn encoded_point<E: EncodedPoint>(
+        &mut self,
+        field: &'static str,
+    ) -> DecodeResult<<E::Affine as CurveAffine>::Projective> {
+        let start = self.offset;
+        let mut encoded = E::empty();
+        let len = encoded.as_ref().len();
+        encoded.as_mut().copy_from_slice(self.take(len, field)?);
+        encoded
+            .into_affine()
+            .map(|affine| affine.into_projective())
+            .map_err(|err| DecodeError {
+                offset: start,
+                field,
+                reason: err.into(),
+            })
+    }
+
+    /// Reads a big-endian field element, zeroing the intermediate copies.
+    pub(crate) fn fr(&mut self, field: &'static str) -> DecodeResult<Fr> {
+        let start = self.offset;
+        let bytes = self.take(SK_SIZE, field)?;
+        let mut repr = FrRepr::default();
+        // Reading can't fail, since the slice has exactly the representation's size.
+        let read = repr.read_be(bytes);
+        let result = Fr::from_repr(repr);
+        repr.0.zeroize();
+        match (read, result) {
+            (Ok(()), Ok(fr)) => Ok(fr),
+            _ => Err(DecodeError {
+                offset: start,
+                field,
+                reason: DecodeReason::NotInField,
+            }),
+        }
+    }
+
+    /// Returns an error if there are bytes left.
+    pub(crate) fn finish(self) -> DecodeResult<()> {
+        match self.remaining() {
+            0 => Ok(()),
+            n => Err(self.error(self.name, DecodeReason::TrailingBytes(n))),
+        }
+    }
+}
+
+impl From<GroupDecodingError> for DecodeReason {
+    fn from(err: GroupDecodingError) -> DecodeReason {
+        match err {
+            GroupDecodingError::NotOnCurve => DecodeReason::NotOnCurve,
+            GroupDecodingError::NotInSubgroup => DecodeReason::NotInSubgroup,
+            GroupDecodingError::CoordinateDecodingError(..) => DecodeReason::InvalidCoordinate,
+            GroupDecodingError::UnexpectedCompressionMode
+            | GroupDecodingError::UnexpectedInformation => DecodeReason::InvalidFlags,
+        }
+    }
+}
+
+/// Returns the point, or with the `strict` feature, an error if it is the identity.
+fn check_strict<C: CurveProjective>(
+    point: C,
+    offset: usize,
+    field: &'static str,
+) -> DecodeResult<C> {
+    if cfg!(feature = "strict") && point.is_zero() {
+        return Err(DecodeError {
+            offset,
+            field,
+            reason: DecodeReason::Identity,
+        });
+    }
+    Ok(point)
+}
+
+/// Decodes a value of the named type with `f`, and returns an error if there are bytes left.
+pub(crate) fn decode<'a, T, F>(bytes: &'a [u8], name: &'static str, f: F) -> DecodeResult<T>
+where
+    F: FnOnce(&mut Reader<'a>) -> DecodeResult<T>,
+{
+    let mut reader = Reader::new(bytes, name);
+    let value = f(&mut reader)?;
+    reader.finish()?;
+    Ok(value)
+}
+
+/// Appends the point's compressed encoding.
+pub(crate) fn write_point<C: CurveProjective>(bytes: &mut Vec<u8>, point: &C) {
+    bytes.extend_from_slice(point.into_affine().into_compressed().as_ref());
+}
@@ -0,0 +1,1485 @@
+//! Dealerless distributed key generation.
+//!
+//! A [`KeyGen`] runs a Joint-Feldman protocol with symmetric bivariate polynomials among a fixed
+//! set of nodes, each identified by an ID and a `PublicKey` for encrypting messages to it. It
+//! assumes that all nodes receive all messages in the same order, e.g. by committing them to a
+//! blockchain or passing them through a consensus protocol:
+//!
+//! 1. Every node creates a random bivariate polynomial of degree `threshold` and broadcasts a
+//!    [`Part`]: a commitment to the polynomial, and each node's row, encrypted to that node.
+//! 2. Every node that receives a valid `Part` broadcasts an [`Ack`]: the values of its row at each
+//!    node, again encrypted to that node.
+//! 3. A `Part` is complete once `2 * threshold + 1` nodes acknowledged it. Once more than
+//!    `threshold` parts are complete, every node can call `generate` to obtain the same
+//!    `PublicKeySet` and its own `SecretKeyShare`: the sum of the complete parts. Nodes can
+//!    broadcast a [`Complete`] message to confirm that they agree on the result.
+//!
+//! Nodes must handle their own messages, too. Invalid messages are returned as faults, so that
+//! the caller can penalize the sender. With the `sessions` feature, `KeyGen::with_limits` bounds
+//! the size of the parts and acks a node handles, and how many of them each node may send. If more than `threshold` nodes are faulty, the key can
+//! neither be kept secret nor guaranteed to be generated.
+//!
+//! In Joint-Feldman, the parts' commitments reveal each proposer's contribution to the public key
+//! right away, so a faulty proposer that sees the other parts first can bias the key by choosing
+//! its own part, or by withholding its acks, accordingly. [`KeyGenMode::Pedersen`] selects the
+//! protocol by Gennaro et al. instead: the parts only contain hiding Pedersen commitments, the
+//! _qualified_ parts are fixed as soon as more than `threshold` of them are complete, and only
+//! then do their proposers send a [`Reveal`] with their contribution to the public key. If a
+//! reveal doesn't match a node's row, the nodes send a [`Disclosure`] of their rows, and the
+//! contribution is reconstructed from them instead.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::dkg::{AckOutcome, KeyGen, PartOutcome};
+//! use threshold_crypto::SecretKey;
+//!
+//! let mut rng = rand::thread_rng();
+//! let sec_keys: Vec<SecretKey> = (0..4).map(|_| rand::random()).collect();
+//! let pub_keys: BTreeMap<usize, _> = sec_keys.iter().map(SecretKey::public_key).enumerate().collect();
+//!
+//! let mut nodes = Vec::new();
+//! let mut parts = Vec::new();
+//! for (id, sk) in sec_keys.into_iter().enumerate() {
+//!     let (node, part) = KeyGen::new(id, sk, pub_keys.clone(), 1, &mut rng).unwrap();
+//!     nodes.push(node);
+//!     parts.push((id, part.expect("a member sends a part")));
+//! }
+//!
+//! let mut acks = Vec::new();
+//! for (sender, part) in parts {
+//!     for (id, node) in nodes.iter_mut().enumerate() {
+//!         match node.handle_part(&sender, part.clone(), &mut rng).unwrap() {
+//!             PartOutcome::Valid(Some(ack)) => acks.push((id, ack)),
+//!             outcome => panic!("unexpected outcome {:?}", outcome),
+//!         }
+//!     }
+//! }
+//! for (sender, ack) in acks {
+//!     for node in &mut nodes {
+//!         assert_eq!(AckOutcome::Valid, node.handle_ack(&sender, ack.clone()).unwrap());
+//!     }
+//! }
+//!
+//! let (pk_set, share) = nodes[0].generate().unwrap();
+//! let sig_share = share.expect("a member has a share").sign("msg");
+//! assert!(pk_set.public_key_share(0).verify(&sig_share, "msg"));
+//! ```
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective};
+use rand::rngs::OsRng;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::decode::decode;
+use crate::error::{DecodeResult, Error, Result};
+use crate::poly::{BivarCommitment, BivarPoly, Commitment, Poly};
+use crate::secret::clear_fr;
+use crate::serde_impl::SerdeSecret;
+#[cfg(feature = "sessions")]
+use crate::session::SessionLimits;
+use crate::state::Resumable;
+use crate::util::sha3_256;
+use crate::{Ciphertext, Fr, G1Affine, PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+use crate::{DebugDots, G1, SK_SIZE};
+#[cfg(feature = "sessions")]
+use crate::{PK_SIZE, SIG_SIZE};
+
+/// The domain separator of the second generator of Pedersen commitments.
+const PEDERSEN_DST: &[u8] = b"threshold_crypto dkg pedersen generator";
+
+/// Returns the second generator of Pedersen commitments, whose discrete logarithm is unknown.
+fn pedersen_base() -> G1 {
+    G1::random(&mut ChaChaRng::from_seed(sha3_256(PEDERSEN_DST)))
+}
+
+/// The variant of the key generation protocol. All nodes must use the same one.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum KeyGenMode {
+    /// Joint-Feldman: The key set is the sum of the parts that are complete when `generate` is
+    /// called. Faulty nodes can bias the public key.
+    #[default]
+    Feldman,
+    /// Gennaro et al.: The parts are hidden by Pedersen commitments until the qualified ones are
+    /// fixed, so that the public key can't be biased. This needs an additional round of
+    /// [`Reveal`] messages.
+    Pedersen,
+}
+
+/// A node's proposal: a commitment to its bivariate polynomial, and the rows for each node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Part {
+    commit: BivarCommitment,
+    rows: Vec<Ciphertext>,
+}
+
+impl Part {
+    /// Creates a part from its commitment and encrypted rows, e.g. when decoding it from another
+    /// format. `KeyGen::handle_part` validates it.
+    pub fn new(commit: BivarCommitment, rows: Vec<Ciphertext>) -> Self {
+        Part { commit, rows }
+    }
+
+    /// Returns the commitment to the proposer's polynomial. In Pedersen mode, it is a Pedersen
+    /// commitment.
+    pub fn commitment(&self) -> &BivarCommitment {
+        &self.commit
+    }
+
+    /// Returns the rows for each node, encrypted to the nodes' public keys.
+    pub fn rows(&self) -> &[Ciphertext] {
+        &self.rows
+    }
+
+    /// Returns the length of the commitment's and the rows' `to_bytes`.
+    #[cfg(feature = "sessions")]
+    fn encoded_len(&self) -> usize {
+        let degree = self.commit.degree();
+        let coeffs = (degree + 1).saturating_mul(degree + 2) / 2;
+        let commit_len = coeffs.saturating_mul(PK_SIZE).saturating_add(8);
+        commit_len.saturating_add(ciphertexts_len(&self.rows))
+    }
+}
+
+/// A node's confirmation that a proposer's part is valid, with the values for each node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ack {
+    proposer: u64,
+    values: Vec<Ciphertext>,
+}
+
+impl Ack {
+    /// Creates an acknowledgment from the proposer's index and the encrypted values, e.g. when
+    /// decoding it from another format. `KeyGen::handle_ack` validates it.
+    pub fn new(proposer: u64, values: Vec<Ciphertext>) -> Self {
+        Ack { proposer, values }
+    }
+
+    /// Returns the index of the node whose part is acknowledged.
+    pub fn proposer(&self) -> u64 {
+        self.proposer
+    }
+
+    /// Returns the values for each node, encrypted to the nodes' public keys.
+    pub fn values(&self) -> &[Ciphertext] {
+        &self.values
+    }
+
+    /// Returns the length of the proposer's index, as a `u64`, and the values' `to_bytes`.
+    #[cfg(feature = "sessions")]
+    fn encoded_len(&self) -> usize {
+        8 + ciphertexts_len(&self.values)
+    }
+}
+
+/// Returns the total length of the ciphertexts' `to_bytes`.
+#[cfg(feature = "sessions")]
+fn ciphertexts_len(cts: &[Ciphertext]) -> usize {
+    cts.iter()
+        .map(|ct| PK_SIZE + SIG_SIZE + ct.1.len())
+        .fold(0, usize::saturating_add)
+}
+
+/// A qualified proposer's contribution to the public key set, in Pedersen mode.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Reveal {
+    commit: Commitment,
+}
+
+impl Reveal {
+    /// Creates a reveal from its commitment, e.g. when decoding it from another format.
+    pub fn new(commit: Commitment) -> Self {
+        Reveal { commit }
+    }
+
+    /// Returns the commitment to row `0` of the proposer's polynomial.
+    pub fn commitment(&self) -> &Commitment {
+        &self.commit
+    }
+}
+
+/// A node's row of a qualified proposer's polynomials, disclosed in Pedersen mode because the
+/// proposer's reveal is missing or wrong.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Disclosure {
+    proposer: u64,
+    row: Poly,
+    blinding: Poly,
+}
+
+impl Disclosure {
+    /// Creates a disclosure from the proposer's index and the disclosed row and blinding
+    /// polynomials, e.g. when decoding it from another format.
+    pub fn new(proposer: u64, row: Poly, blinding: Poly) -> Self {
+        Disclosure {
+            proposer,
+            row,
+            blinding,
+        }
+    }
+
+    /// Returns the index of the node whose rows are disclosed.
+    pub fn proposer(&self) -> u64 {
+        self.proposer
+    }
+
+    /// Returns the disclosed row of the proposer's polynomial.
+    pub fn row(&self) -> &Poly {
+        &self.row
+    }
+
+    /// Returns the disclosed row of the proposer's blinding polynomial.
+    pub fn blinding(&self) -> &Poly {
+        &self.blinding
+    }
+}
+
+/// A node's statement of the complete parts it used, and the resulting public key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Complete {
+    proposers: BTreeSet<u64>,
+    public_key: PublicKey,
+}
+
+impl Complete {
+    /// Creates a completion statement, e.g. when decoding it from another format.
+    pub fn new(proposers: BTreeSet<u64>, public_key: PublicKey) -> Self {
+        Complete {
+            proposers,
+            public_key,
+        }
+    }
+
+    /// Returns the indices of the nodes whose parts were used.
+    pub fn proposers(&self) -> &BTreeSet<u64> {
+        &self.proposers
+    }
+
+    /// Returns the generated master public key.
+    pub fn public_key(&self) -> PublicKey {
+        self.public_key
+    }
+}
+
+/// The result of handling a `Part`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PartOutcome {
+    /// The part was valid. If this node is a member, it must broadcast the `Ack`.
+    Valid(Option<Ack>),
+    /// The part was invalid, and the proposer is faulty.
+    Invalid(PartFault),
+}
+
+/// A reason why a `Part` is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartFault {
+    /// The proposer has already sent a different part.
+    MultipleParts,
+    /// The commitment's degree is not the threshold.
+    DegreeMismatch,
+    /// The number of rows is not the number of nodes.
+    RowCount,
+    /// This node's row could not be decrypted or decoded.
+    InvalidRow,
+    /// This node's row doesn't match the commitment.
+    RowCommitment,
+    /// In a share refresh, the commitment's secret is not zero.
+    NonzeroSecret,
+}
+
+/// The result of handling an `Ack`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AckOutcome {
+    /// The ack was valid.
+    Valid,
+    /// The ack was invalid, and its sender is faulty.
+    Invalid(AckFault),
+}
+
+/// A reason why an `Ack` is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AckFault {
+    /// There is no part by the proposer.
+    MissingPart,
+    /// The sender has already acknowledged the part.
+    DuplicateAck,
+    /// The number of values is not the number of nodes.
+    ValueCount,
+    /// This node's value could not be decrypted or decoded.
+    InvalidValue,
+    /// This node's value doesn't match the commitment.
+    ValueCommitment,
+}
+
+/// The result of handling a `Reveal`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RevealOutcome {
+    /// The reveal was valid.
+    Valid,
+    /// The reveal doesn't match this node's row, so the proposer is faulty. This node must
+    /// broadcast the `Disclosure`, so that the others can verify that and reconstruct the
+    /// proposer's contribution.
+    Disputed(Disclosure),
+    /// The reveal was invalid, and its sender is faulty.
+    Invalid(RevealFault),
+}
+
+/// A reason why a `Reveal` is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RevealFault {
+    /// The sender's part is not qualified, or the protocol is not in Pedersen mode.
+    NotQualified,
+    /// The sender has already revealed its contribution.
+    MultipleReveals,
+    /// The commitment's degree is not the threshold.
+    DegreeMismatch,
+}
+
+/// The result of handling a `Disclosure`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DisclosureOutcome {
+    /// The disclosure was valid. If this node must disclose its own row now, too, it must
+    /// broadcast the returned `Disclosure`.
+    Valid(Option<Disclosure>),
+    /// The disclosure was invalid, and its sender is faulty.
+    Invalid(DisclosureFault),
+}
+
+/// A reason why a `Disclosure` is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DisclosureFault {
+    /// The proposer's part is not qualified, or the protocol is not in Pedersen mode.
+    NotQualified,
+    /// The sender has already disclosed its row.
+    DuplicateDisclosure,
+    /// The rows' degree is higher than the threshold.
+    DegreeMismatch,
+    /// The rows don't match the proposer's commitment.
+    RowCommitment,
+    /// The proposer's reveal matches the row, so there is nothing to dispute.
+    Unjustified,
+}
+
+/// A node's row of a proposer's polynomial, and in Pedersen mode of its blinding polynomial.
+#[derive(Clone)]
+struct Row {
+    values: Poly,
+    blinding: Option<Poly>,
+}
+
+/// The received part of a proposer, and the acknowledged values of this node's row.
+#[derive(Clone)]
+struct ProposalState {
+    commit: BivarCommitment,
+    /// The values `f(s, our_idx + 1)` at `s = sender + 1`, received in the senders' acks.
+    values: BTreeMap<u64, Fr>,
+    /// The indices of the nodes that acknowledged the part.
+    acks: BTreeSet<u64>,
+    /// This node's row, in Pedersen mode, in case it has to be disclosed.
+    row: Option<Row>,
+    /// The proposer's revealed contribution, in Pedersen mode.
+    reveal: Option<Commitment>,
+    /// The disclosed rows `f(s, _)` at `s = sender + 1`, in Pedersen mode.
+    disclosures: BTreeMap<u64, Poly>,
+    /// Whether the contribution has to be reconstructed from the disclosed rows.
+    contested: bool,
+    /// Whether this node has returned the disclosure of its own row.
+    disclosed: bool,
+}
+
+impl ProposalState {
+    /// Returns `true` if enough nodes acknowledged the part.
+    fn is_complete(&self, threshold: usize) -> bool {
+        self.acks.len() > 2 * threshold
+    }
+
+    /// Returns the commitment to row `0`, i.e. the contribution to the public key set, or `None`
+    /// if it is not known yet.
+    fn contribution(&self, mode: KeyGenMode, threshold: usize) -> Option<Commitment> {
+        if mode == KeyGenMode::Feldman {
+            return Some(self.commit.row(0));
+        }
+        if !self.contested {
+            return self.reveal.clone();
+        }
+        if self.disclosures.len() <= threshold {
+            return None;
+        }
+        // Row `0` is interpolated coefficient-wise from the disclosed rows.
+        let rows: Vec<(&u64, &Poly)> = self.disclosures.iter().take(threshold + 1).collect();
+        let coeff: Vec<Fr> = (0..=threshold)
+            .map(|k| {
+                let samples = rows.iter().map(|(x, row)| (**x, coeff_at(row, k)));
+                Poly::interpolate(samples).evaluate(0)
+            })
+            .collect();
+        Some(Poly::from(coeff).commitment())
+    }
+}
+
+impl Drop for ProposalState {
+    fn drop(&mut self) {
+        for value in self.values.values_mut() {
+            clear_fr(value);
+        }
+    }
+}
+
+/// A node's state in the distributed key generation.
+#[derive(Clone)]
+pub struct KeyGen<N> {
+    our_id: N,
+    our_idx: Option<u64>,
+    sec_key: SecretKey,
+    pub_keys: BTreeMap<N, PublicKey>,
+    threshold: usize,
+    mode: KeyGenMode,
+    /// Whether all parts must share the value zero, to refresh existing shares.
+    zero_secret: bool,
+    /// This node's contribution to the public key set, to be revealed in Pedersen mode.
+    our_reveal: Option<Commitment>,
+    parts: BTreeMap<u64, ProposalState>,
+    /// The qualified parts, once they are fixed in Pedersen mode.
+    qualified: Option<BTreeSet<u64>>,
+    /// The valid messages handled so far, for saving the state.
+    log: Vec<(N, Message)>,
+    #[cfg(feature = "sessions")]
+    limits: SessionLimits,
+    /// The number of parts and acks handled, by sender.
+    #[cfg(feature = "sessions")]
+    submissions: BTreeMap<u64, usize>,
+}
+
+impl<N: fmt::Debug> fmt::Debug for KeyGen<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyGen")
+            .field("our_id", &self.our_id)
+            .field("our_idx", &self.our_idx)
+            .field("sec_key", &DebugDots)
+            .field("threshold", &self.threshold)
+            .field("mode", &self.mode)
+            .field("parts", &self.parts.keys().collect::<Vec<_>>())
+            .field("qualified", &self.qualified)
+            .finish()
+    }
+}
+
+impl<N: Ord + Clone> KeyGen<N> {
+    /// Creates the state of node `our_id` among the nodes with the given public keys, and returns
+    /// the `Part` it must broadcast, or `None` if it is only an observer.
+    ///
+    /// Any `threshold + 1` of the generated shares can sign and decrypt. Returns
+    /// `Error::NotEnoughShares` if there are too few nodes to tolerate `threshold` faulty ones.
+    pub fn new<R: Rng>(
+        our_id: N,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<N, PublicKey>,
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<(Self, Option<Part>)> {
+        KeyGen::with_mode(
+            our_id,
+            sec_key,
+            pub_keys,
+            threshold,
+            KeyGenMode::Feldman,
+            rng,
+        )
+    }
+
+    /// Creates the state of node `our_id` like `new`, running the given variant of the protocol.
+    pub fn with_mode<R: Rng>(
+        our_id: N,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<N, PublicKey>,
+        threshold: usize,
+        mode: KeyGenMode,
+        rng: &mut R,
+    ) -> Result<(Self, Option<Part>)> {
+        KeyGen::create(our_id, sec_key, pub_keys, threshold, mode, false, rng)
+    }
+
+    /// Creates the state of node `our_id` like `new`, but all parts, including this node's, must
+    /// share the value zero. The result is added to existing shares to refresh them.
+    pub(crate) fn with_zero_secret<R: Rng>(
+        our_id: N,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<N, PublicKey>,
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<(Self, Option<Part>)> {
+        let mode = KeyGenMode::Feldman;
+        KeyGen::create(our_id, sec_key, pub_keys, threshold, mode, true, rng)
+    }
+
+    /// Creates the state of node `our_id`, and its `Part` if it is a member.
+    fn create<R: Rng>(
+        our_id: N,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<N, PublicKey>,
+        threshold: usize,
+        mode: KeyGenMode,
+        zero_secret: bool,
+        rng: &mut R,
+    ) -> Result<(Self, Option<Part>)> {
+        if pub_keys.len() <= 2 * threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        let our_idx = pub_keys
+            .keys()
+            .position(|id| *id == our_id)
+            .map(|idx| idx as u64);
+        let (part, our_reveal) = match our_idx {
+            Some(_) => {
+                let (part, reveal) =
+                    Self::create_part(&pub_keys, threshold, mode, zero_secret, rng)?;
+                (Some(part), reveal)
+            }
+            None => (None, None),
+        };
+        let key_gen = KeyGen {
+            our_id,
+            our_idx,
+            sec_key,
+            pub_keys,
+            threshold,
+            mode,
+            zero_secret,
+            our_reveal,
+            parts: BTreeMap::new(),
+            qualified: None,
+            log: Vec::new(),
+            #[cfg(feature = "sessions")]
+            limits: SessionLimits::default(),
+            #[cfg(feature = "sessions")]
+            submissions: BTreeMap::new(),
+        };
+        Ok((key_gen, part))
+    }
+
+    /// Sets the limits on the parts and acks the node handles: Those larger than
+    /// `max_message_size`, in their canonical encoding, i.e. the commitment's and ciphertexts'
+    /// `to_bytes`, and those by a sender that has already sent `max_submissions` of them, are
+    /// rejected with `Error::LimitExceeded` before they are verified. Since all messages are
+    /// verified as they arrive, the other limits don't apply.
+    #[cfg(feature = "sessions")]
+    pub fn with_limits(mut self, limits: SessionLimits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Returns the limits on the parts and acks the node handles.
+    #[cfg(feature = "sessions")]
+    pub fn limits(&self) -> &SessionLimits {
+        &self.limits
+    }
+
+    /// Returns the ID of this node.
+    pub fn our_id(&self) -> &N {
+        &self.our_id
+    }
+
+    /// Returns the participating nodes' public keys.
+    pub fn public_keys(&self) -> &BTreeMap<N, PublicKey> {
+        &self.pub_keys
+    }
+
+    /// Returns the threshold of the generated key set.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Returns the variant of the protocol.
+    pub fn mode(&self) -> KeyGenMode {
+        self.mode
+    }
+
+    /// Handles a `Part` by the node `sender_id`, and returns the `Ack` to broadcast if it is
+    /// valid. Returns `Error::UnknownParticipant` if the sender is not one of the nodes.
+    pub fn handle_part<R: Rng>(
+        &mut self,
+        sender_id: &N,
+        part: Part,
+        rng: &mut R,
+    ) -> Result<PartOutcome> {
+        let sender_idx = self.node_index(sender_id)?;
+        #[cfg(feature = "sessions")]
+        self.check_limits(sender_idx, part.encoded_len())?;
+        let row = match self.check_part(sender_idx, &part) {
+            Ok(row) => row,
+            Err(fault) => return Ok(PartOutcome::Invalid(fault)),
+        };
+        let ack = row
+            .as_ref()
+            .map(|row| self.create_ack(sender_idx, row, rng));
+        if let Entry::Vacant(entry) = self.parts.entry(sender_idx) {
+            let mode = self.mode;
+            entry.insert(ProposalState {
+                commit: part.commit.clone(),
+                values: BTreeMap::new(),
+                acks: BTreeSet::new(),
+                row: row.filter(|_| mode == KeyGenMode::Pedersen),
+                reveal: None,
+                disclosures: BTreeMap::new(),
+                contested: false,
+                disclosed: false,
+            });
+            self.log.push((sender_id.clone(), Message::Part(part)));
+        }
+        Ok(PartOutcome::Valid(ack))
+    }
+
+    /// Handles an `Ack` by the node `sender_id`. Returns `Error::UnknownParticipant` if the
+    /// sender is not one of the nodes.
+    pub fn handle_ack(&mut self, sender_id: &N, ack: Ack) -> Result<AckOutcome> {
+        let sender_idx = self.node_index(sender_id)?;
+        #[cfg(feature = "sessions")]
+        self.check_limits(sender_idx, ack.encoded_len())?;
+        let value = match self.check_ack(sender_idx, &ack) {
+            Ok(value) => value,
+            Err(fault) => return Ok(AckOutcome::Invalid(fault)),
+        };
+        let state = self
+            .parts
+            .get_mut(&ack.proposer)
+            .expect("the part was checked");
+        state.acks.insert(sender_idx);
+        if let Some(value) = value {
+            state.values.insert(sender_idx + 1, value);
+        }
+        self.log.push((sender_id.clone(), Message::Ack(ack)));
+        if self.mode == KeyGenMode::Pedersen
+            && self.qualified.is_none()
+            && self.count_complete() > self.threshold
+        {
+            let qualified = self.complete_parts().map(|(idx, _)| *idx).collect();
+            self.qualified = Some(qualified);
+        }
+        Ok(AckOutcome::Valid)
+    }
+
+    /// Returns the qualified parts in Pedersen mode, or `None` if they are not fixed yet.
+    pub fn qualified(&self) -> Option<&BTreeSet<u64>> {
+        self.qualified.as_ref()
+    }
+
+    /// Returns the `Reveal` this node must broadcast in Pedersen mode, once its part is
+    /// qualified, and until it has handled it.
+    pub fn reveal(&self) -> Option<Reveal> {
+        let our_idx = self.our_idx?;
+        if !self.qualified.as_ref()?.contains(&our_idx) {
+            return None;
+        }
+        if self.parts.get(&our_idx)?.reveal.is_some() {
+            return None;
+        }
+        let commit = self.our_reveal.clone()?;
+        Some(Reveal { commit })
+    }
+
+    /// Handles a `Reveal` by the node `sender_id`, and returns the `Disclosure` to broadcast if
+    /// it doesn't match this node's row. Returns `Error::UnknownParticipant` if the sender is not
+    /// one of the nodes.
+    pub fn handle_reveal(&mut self, sender_id: &N, reveal: Reveal) -> Result<RevealOutcome> {
+        let sender_idx = self.node_index(sender_id)?;
+        let (our_idx, threshold) = (self.our_idx, self.threshold);
+        let state = match self.qualified_part_mut(sender_idx) {
+            Some(state) => state,
+            None => return Ok(RevealOutcome::Invalid(RevealFault::NotQualified)),
+        };
+        if state.reveal.is_some() {
+            return Ok(RevealOutcome::Invalid(RevealFault::MultipleReveals));
+        }
+        if reveal.commit.coeff.len() != threshold + 1 {
+            return Ok(RevealOutcome::Invalid(RevealFault::DegreeMismatch));
+        }
+        state.reveal = Some(reveal.commit.clone());
+        let mut outcome = RevealOutcome::Valid;
+        if let (Some(our_idx), Some(row)) = (our_idx, &state.row) {
+            let matches = matches_reveal(&reveal.commit, our_idx + 1, &row.values);
+            if !matches && !state.contested && !state.disclosed {
+                state.disclosed = true;
+                outcome = RevealOutcome::Disputed(disclosure(sender_idx, row));
+            }
+        }
+        state.update_contested(threshold);
+        self.log.push((sender_id.clone(), Message::Reveal(reveal)));
+        Ok(outcome)
+    }
+
+    /// Returns the `Disclosure` of this node's row of the given qualified proposer, in Pedersen
+    /// mode. Nodes should broadcast it if the proposer hasn't sent its `Reveal` in time: Once
+    /// more than `threshold` nodes disclosed their rows, the contribution is reconstructed from
+    /// them instead.
+    pub fn disclose(&mut self, proposer_id: &N) -> Option<Disclosure> {
+        let proposer = self.node_index(proposer_id).ok()?;
+        let state = self.qualified_part_mut(proposer)?;
+        if state.reveal.is_some() || state.disclosed {
+            return None;
+        }
+        let result = disclosure(proposer, state.row.as_ref()?);
+        state.disclosed = true;
+        Some(result)
+    }
+
+    /// Handles a `Disclosure` by the node `sender_id`. Returns `Error::UnknownParticipant` if
+    /// the sender is not one of the nodes.
+    pub fn handle_disclosure(
+        &mut self,
+        sender_id: &N,
+        msg: Disclosure,
+    ) -> Result<DisclosureOutcome> {
+        let sender_idx = self.node_index(sender_id)?;
+        let (our_idx, threshold) = (self.our_idx, self.threshold);
+        let x = sender_idx + 1;
+        let row = Row {
+            values: msg.row.clone(),
+            blinding: Some(msg.blinding.clone()),
+        };
+        let row_commit = self.row_commitment(&row);
+        let fault = |fault| Ok(DisclosureOutcome::Invalid(fault));
+        let state = match self.qualified_part_mut(msg.proposer) {
+            Some(state) => state,
+            None => return fault(DisclosureFault::NotQualified),
+        };
+        if state.disclosures.contains_key(&x) {
+            return fault(DisclosureFault::DuplicateDisclosure);
+        }
+        if msg.row.coeff.len() > threshold + 1 || msg.blinding.coeff.len() > threshold + 1 {
+            return fault(DisclosureFault::DegreeMismatch);
+        }
+        if row_commit != state.commit.row(x) {
+            return fault(DisclosureFault::RowCommitment);
+        }
+        if let Some(reveal) = &state.reveal {
+            if !state.contested && matches_reveal(reveal, x, &msg.row) {
+                return fault(DisclosureFault::Unjustified);
+            }
+        }
+        state.disclosures.insert(x, msg.row.clone());
+        let was_contested = state.contested;
+        state.update_contested(threshold);
+        if our_idx == Some(sender_idx) {
+            state.disclosed = true;
+        }
+        let mut response = None;
+        if state.contested && !was_contested && !state.disclosed {
+            if let Some(row) = &state.row {
+                state.disclosed = true;
+                response = Some(disclosure(msg.proposer, row));
+            }
+        }
+        self.log.push((sender_id.clone(), Message::Disclosure(msg)));
+        Ok(DisclosureOutcome::Valid(response))
+    }
+
+    /// Returns the number of complete parts.
+    pub fn count_complete(&self) -> usize {
+        self.complete_parts().count()
+    }
+
+    /// Returns `true` if the part of the given node is complete.
+    pub fn is_node_ready(&self, proposer_id: &N) -> bool {
+        self.node_index(proposer_id)
+            .ok()
+            .and_then(|idx| self.parts.get(&idx))
+            .is_some_and(|state| state.is_complete(self.threshold))
+    }
+
+    /// Returns `true` if enough parts are complete to generate the key set. In Pedersen mode, the
+    /// contributions of all qualified parts must be known, too.
+    pub fn is_ready(&self) -> bool {
+        match self.mode {
+            KeyGenMode::Feldman => self.count_complete() > self.threshold,
+            KeyGenMode::Pedersen => self.contributions().is_some(),
+        }
+    }
+
+    /// Returns the `Complete` message to broadcast, or `None` if not enough parts are complete.
+    pub fn complete(&self) -> Option<Complete> {
+        if !self.is_ready() {
+            return None;
+        }
+        Some(Complete {
+            proposers: self.contributors().collect(),
+            public_key: self.public_key_set()?.public_key(),
+        })
+    }
+
+    /// Returns `true` if the `Complete` message agrees with this node's result.
+    pub fn verify_complete(&self, complete: &Complete) -> bool {
+        self.complete().as_ref() == Some(complete)
+    }
+
+    /// Returns the generated public key set and, if this node is a member, its secret key share.
+    ///
+    /// Returns `Error::NotEnoughShares` if not enough parts are complete yet. All nodes that call
+    /// this after handling the same messages get the same key set.
+    pub fn generate(&self) -> Result<(PublicKeySet, Option<SecretKeyShare>)> {
+        let pk_set = self.public_key_set().ok_or(Error::NotEnoughShares)?;
+        let share = match self.our_idx {
+            None => None,
+            Some(_) => {
+                let mut sk_val = Fr::zero();
+                for idx in self.contributors() {
+                    let samples = self.parts[&idx].values.iter().take(self.threshold + 1);
+                    let row = Poly::interpolate(samples.map(|(x, value)| (*x, *value)));
+                    let mut value = row.evaluate(0);
+                    sk_val.add_assign(&value);
+                    clear_fr(&mut value);
+                }
+                Some(SecretKeyShare::from_mut(&mut sk_val))
+            }
+        };
+        Ok((pk_set, share))
+    }
+
+    /// Returns the sum of the contributing parts' public commitments, or `None` if not ready.
+    fn public_key_set(&self) -> Option<PublicKeySet> {
+        if !self.is_ready() {
+            return None;
+        }
+        let mut commit = Poly::zero().commitment();
+        for contribution in self.contributions()? {
+            commit += contribution;
+        }
+        Some(PublicKeySet::from(commit))
+    }
+
+    /// Returns the contributions of all contributing parts, or `None` if one is still unknown.
+    fn contributions(&self) -> Option<Vec<Commitment>> {
+        if self.mode == KeyGenMode::Pedersen {
+            self.qualified.as_ref()?;
+        }
+        self.contributors()
+            .map(|idx| self.parts[&idx].contribution(self.mode, self.threshold))
+            .collect()
+    }
+
+    /// Returns the indices of the parts the key set is made of: the complete ones in Feldman
+    /// mode, and the qualified ones in Pedersen mode.
+    fn contributors(&self) -> Box<dyn Iterator<Item = u64> + '_> {
+        match self.mode {
+            KeyGenMode::Feldman => Box::new(self.complete_parts().map(|(idx, _)| *idx)),
+            KeyGenMode::Pedersen => Box::new(self.qualified.iter().flatten().cloned()),
+        }
+    }
+
+    /// Returns the complete parts.
+    fn complete_parts(&self) -> impl Iterator<Item = (&u64, &ProposalState)> {
+        let threshold = self.threshold;
+        self.parts
+            .iter()
+            .filter(move |(_, state)| state.is_complete(threshold))
+    }
+
+    /// Returns the qualified part of the given proposer, if in Pedersen mode.
+    fn qualified_part_mut(&mut self, proposer: u64) -> Option<&mut ProposalState> {
+        if !self.qualified.as_ref()?.contains(&proposer) {
+            return None;
+        }
+        self.parts.get_mut(&proposer)
+    }
+
+    /// Checks a message's size and counts it as a submission by the sender, or returns
+    /// `Error::LimitExceeded`.
+    #[cfg(feature = "sessions")]
+    fn check_limits(&mut self, sender_idx: u64, len: usize) -> Result<()> {
+        if len > self.limits.max_message_size {
+            return Err(Error::LimitExceeded("message size".to_string()));
+        }
+        let submissions = self.submissions.entry(sender_idx).or_insert(0);
+        if *submissions >= self.limits.max_submissions {
+            let limit = format!("submissions by participant {}", sender_idx);
+            return Err(Error::LimitExceeded(limit));
+        }
+        *submissions += 1;
+        Ok(())
+    }
+
+    /// Returns the node's index, or `Error::UnknownParticipant`.
+    fn node_index(&self, id: &N) -> Result<u64> {
+        self.pub_keys
+            .keys()
+            .position(|key| key == id)
+            .map(|idx| idx as u64)
+            .ok_or(Error::UnknownParticipant)
+    }
+
+    /// Creates random bivariate polynomials, and the `Part` with their commitment and rows. In
+    /// Pedersen mode, also returns the contribution to reveal later.
+    fn create_part<R: Rng>(
+        pub_keys: &BTreeMap<N, PublicKey>,
+        threshold: usize,
+        mode: KeyGenMode,
+        zero_secret: bool,
+        rng: &mut R,
+    ) -> Result<(Part, Option<Commitment>)> {
+        let poly = if zero_secret {
+            BivarPoly::with_secret(0, threshold, rng)
+        } else {
+            BivarPoly::try_random(threshold, rng)?
+        };
+        let blinding = match mode {
+            KeyGenMode::Feldman => None,
+            KeyGenMode::Pedersen => Some(BivarPoly::try_random(threshold, rng)?),
+        };
+        let rows = pub_keys
+            .values()
+            .enumerate()
+            .map(|(idx, pk)| {
+                let mut bytes = Zeroizing::new(Vec::with_capacity(2 * (threshold + 1) * SK_SIZE));
+                for row in Some(&poly).into_iter().chain(&blinding) {
+                    for coeff in &row.row(idx + 1).coeff {
+                        bytes.extend(fr_to_bytes(coeff).iter());
+                    }
+                }
+                pk.encrypt_with_rng(rng, &*bytes)
+            })
+            .collect();
+        let (commit, reveal) = match &blinding {
+            None => (poly.commitment(), None),
+            Some(blinding) => {
+                let commit = poly.pedersen_commitment(blinding, pedersen_base());
+                (commit, Some(poly.row(0).commitment()))
+            }
+        };
+        Ok((Part { commit, rows }, reveal))
+    }
+
+    /// Creates this node's `Ack` of the proposer's part, given its row.
+    fn create_ack<R: Rng>(&self, proposer: u64, row: &Row, rng: &mut R) -> Ack {
+        let values = self
+            .pub_keys
+            .values()
+            .enumerate()
+            .map(|(idx, pk)| {
+                let mut bytes = Zeroizing::new(Vec::with_capacity(2 * SK_SIZE));
+                for poly in Some(&row.values).into_iter().chain(&row.blinding) {
+                    bytes.extend(fr_to_bytes(&poly.evaluate(idx + 1)).iter());
+                }
+                pk.encrypt_with_rng(rng, &*bytes)
+            })
+            .collect();
+        Ack { proposer, values }
+    }
+
+    /// Verifies the part, and returns this node's row if it is a member.
+    fn check_part(
+        &self,
+        sender_idx: u64,
+        part: &Part,
+    ) -> ::std::result::Result<Option<Row>, PartFault> {
+        if let Some(state) = self.parts.get(&sender_idx) {
+            if state.commit != part.commit {
+                return Err(PartFault::MultipleParts);
+            }
+        }
+        if part.commit.degree() != self.threshold {
+            return Err(PartFault::DegreeMismatch);
+        }
+        if self.zero_secret && !part.commit.evaluate(0, 0).is_zero() {
+            return Err(PartFault::NonzeroSecret);
+        }
+        if part.rows.len() != self.pub_keys.len() {
+            return Err(PartFault::RowCount);
+        }
+        let our_idx = match self.our_idx {
+            Some(our_idx) => our_idx,
+            None => return Ok(None),
+        };
+        let row = self
+            .decrypt_row(&part.rows[our_idx as usize])
+            .ok_or(PartFault::InvalidRow)?;
+        if self.row_commitment(&row) != part.commit.row(our_idx + 1) {
+            return Err(PartFault::RowCommitment);
+        }
+        Ok(Some(row))
+    }
+
+    /// Decrypts and decodes this node's row.
+    fn decrypt_row(&self, ct: &Ciphertext) -> Option<Row> {
+        let bytes = Zeroizing::new(self.sec_key.decrypt(ct)?);
+        let mut coeff = decode(&bytes, "Row", |r| {
+            (0..self.width() * (self.threshold + 1))
+                .map(|_| r.fr("coefficient"))
+                .collect::<DecodeResult<Vec<Fr>>>()
+        })
+        .ok()?;
+        let blinding = match self.mode {
+            KeyGenMode::Feldman => None,
+            KeyGenMode::Pedersen => Some(Poly::from(coeff.split_off(self.threshold + 1))),
+        };
+        let values = Poly::from(coeff);
+        Some(Row { values, blinding })
+    }
+
+    /// Returns the commitment to the row: a Pedersen commitment if it has a blinding row.
+    fn row_commitment(&self, row: &Row) -> Commitment {
+        let h = row.blinding.as_ref().map(|_| pedersen_base());
+        let coeff = (0..=self.threshold)
+            .map(|k| {
+                let mut result = G1Affine::one().mul(coeff_at(&row.values, k));
+                if let (Some(mut h), Some(blinding)) = (h, &row.blinding) {
+                    h.mul_assign(coeff_at(blinding, k));
+                    result.add_assign(&h);
+                }
+                result
+            })
+            .collect();
+        Commitment { coeff }
+    }
+
+    /// Verifies the ack, and returns this node's value if it is a member.
+    fn check_ack(&self, sender_idx: u64, ack: &Ack) -> ::std::result::Result<Option<Fr>, AckFault> {
+        let state = self.parts.get(&ack.proposer).ok_or(AckFault::MissingPart)?;
+        if state.acks.contains(&sender_idx) {
+            return Err(AckFault::DuplicateAck);
+        }
+        if ack.values.len() != self.pub_keys.len() {
+            return Err(AckFault::ValueCount);
+        }
+        let our_idx = match self.our_idx {
+            Some(our_idx) => our_idx,
+            None => return Ok(None),
+        };
+        let bytes = self
+            .sec_key
+            .decrypt(&ack.values[our_idx as usize])
+            .map(Zeroizing::new)
+            .ok_or(AckFault::InvalidValue)?;
+        let mut values = decode(&bytes, "Value", |r| {
+            (0..self.width())
+                .map(|_| r.fr("value"))
+                .collect::<DecodeResult<Vec<Fr>>>()
+        })
+        .map_err(|_| AckFault::InvalidValue)?;
+        let mut expected = G1Affine::one().mul(values[0]);
+        if let Some(blinding) = values.get(1) {
+            let mut h = pedersen_base();
+            h.mul_assign(*blinding);
+            expected.add_assign(&h);
+        }
+        let value = values.swap_remove(0);
+        values.iter_mut().for_each(clear_fr);
+        if state.commit.evaluate(our_idx + 1, sender_idx + 1) != expected {
+            return Err(AckFault::ValueCommitment);
+        }
+        Ok(Some(value))
+    }
+
+    /// Returns the number of polynomials in a part: two in Pedersen mode, including the blinding
+    /// one.
+    fn width(&self) -> usize {
+        match self.mode {
+            KeyGenMode::Feldman => 1,
+            KeyGenMode::Pedersen => 2,
+        }
+    }
+}
+
+impl ProposalState {
+    /// Marks the contribution as contested if a disclosed row doesn't match the reveal, or if
+    /// more than `threshold` nodes disclosed their rows before there was a reveal.
+    fn update_contested(&mut self, threshold: usize) {
+        self.contested |= match &self.reveal {
+            None => self.disclosures.len() > threshold,
+            Some(reveal) => self
+                .disclosures
+                .iter()
+                .any(|(x, row)| !matches_reveal(reveal, *x, row)),
+        };
+    }
+}
+
+/// Returns `true` if the value of `row` at `0` matches the revealed contribution at `x`.
+fn matches_reveal(reveal: &Commitment, x: u64, row: &Poly) -> bool {
+    G1Affine::one().mul(row.evaluate(0)) == reveal.evaluate(x)
+}
+
+/// Returns the disclosure of the row of the given proposer's polynomials.
+fn disclosure(proposer: u64, row: &Row) -> Disclosure {
+    Disclosure {
+        proposer,
+        row: row.values.clone(),
+        blinding: row.blinding.clone().unwrap_or_else(Poly::zero),
+    }
+}
+
+/// Returns the `k`-th coefficient of the polynomial.
+fn coeff_at(poly: &Poly, k: usize) -> Fr {
+    poly.coeff.get(k).cloned().unwrap_or_else(Fr::zero)
+}
+
+/// A valid message handled by a `KeyGen`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// A proposer's part.
+    Part(Part),
+    /// An acknowledgment of a part.
+    Ack(Ack),
+    /// A qualified proposer's contribution, in Pedersen mode.
+    Reveal(Reveal),
+    /// A node's row of a qualified proposer's polynomials, in Pedersen mode.
+    Disclosure(Disclosure),
+}
+
+/// The saved state of a `KeyGen`: its keys and the valid messages it handled.
+///
+/// Contains the node's secret key, so it must be stored securely.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(bound(serialize = "N: Serialize", deserialize = "N: DeserializeOwned + Ord"))]
+pub struct KeyGenState<N> {
+    /// This node's ID.
+    pub our_id: N,
+    /// This node's secret key, for decrypting its rows and values.
+    pub sec_key: SerdeSecret<SecretKey>,
+    /// The participating nodes' public keys.
+    pub pub_keys: BTreeMap<N, PublicKey>,
+    /// The threshold of the generated key set.
+    pub threshold: usize,
+    /// The variant of the protocol.
+    #[serde(default)]
+    pub mode: KeyGenMode,
+    /// This node's contribution to reveal, in Pedersen mode.
+    #[serde(default)]
+    pub reveal: Option<Commitment>,
+    /// The valid messages handled so far, with their senders, in order.
+    pub messages: Vec<(N, Message)>,
+}
+
+impl<N> Resumable for KeyGen<N>
+where
+    N: Ord + Clone + Serialize + DeserializeOwned,
+{
+    const PROTOCOL: &'static str = "sync_key_gen";
+    type State = KeyGenState<N>;
+
+    fn to_state(&self) -> KeyGenState<N> {
+        KeyGenState {
+            our_id: self.our_id.clone(),
+            sec_key: SerdeSecret(self.sec_key.clone()),
+            pub_keys: self.pub_keys.clone(),
+            threshold: self.threshold,
+            mode: self.mode,
+            reveal: self.our_reveal.clone(),
+            messages: self.log.clone(),
+        }
+    }
+
+    /// Restores the state by handling the saved messages again, so that they are verified.
+    fn from_state(state: KeyGenState<N>) -> Result<Self> {
+        let KeyGenState {
+            our_id,
+            sec_key,
+            pub_keys,
+            threshold,
+            mode,
+            reveal,
+            messages,
+        } = state;
+        let invalid = |msg: &str| Error::InvalidState(msg.to_string());
+        // The restored node doesn't broadcast its part again, so a fresh one is discarded.
+        let (mut key_gen, _) = KeyGen::with_mode(
+            our_id,
+            sec_key.into_inner(),
+            pub_keys,
+            threshold,
+            mode,
+            &mut OsRng,
+        )?;
+        if mode == KeyGenMode::Pedersen && key_gen.our_idx.is_some() {
+            key_gen.our_reveal = Some(reveal.ok_or_else(|| invalid("missing contribution"))?);
+        }
+        key_gen.replay(messages)?;
+        Ok(key_gen)
+    }
+}
+
+impl<N: Ord + Clone> KeyGen<N> {
+    /// Handles the saved messages again, and returns `Error::InvalidState` if one is invalid.
+    pub(crate) fn replay(&mut self, messages: Vec<(N, Message)>) -> Result<()> {
+        let invalid = |msg: &str| Error::InvalidState(msg.to_string());
+        for (sender_id, msg) in messages {
+            let valid = match msg {
+                Message::Part(part) => {
+                    let outcome = self.handle_part(&sender_id, part, &mut OsRng)?;
+                    matches!(outcome, PartOutcome::Valid(_))
+                }
+                Message::Ack(ack) => self.handle_ack(&sender_id, ack)? == AckOutcome::Valid,
+                Message::Reveal(reveal) => {
+                    let outcome = self.handle_reveal(&sender_id, reveal)?;
+                    !matches!(outcome, RevealOutcome::Invalid(_))
+                }
+                Message::Disclosure(msg) => {
+                    let outcome = self.handle_disclosure(&sender_id, msg)?;
+                    matches!(outcome, DisclosureOutcome::Valid(_))
+                }
+            };
+            if !valid {
+                return Err(invalid("invalid key generation message"));
+            }
+        }
+        // If our own part was handled, the contribution must match it.
+        if let (Some(our_idx), Some(reveal)) = (self.our_idx, &self.our_reveal) {
+            let row = self
+                .parts
+                .get(&our_idx)
+                .and_then(|state| state.row.as_ref());
+            if row.is_some_and(|row| !matches_reveal(reveal, our_idx + 1, &row.values)) {
+                return Err(invalid("contribution doesn't match the part"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Returns the field element's big-endian representation.
+pub(crate) fn fr_to_bytes(fr: &Fr) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+    fr.into_repr()
+        .write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SignatureShare;
+
+    /// Creates `n` nodes and has them broadcast their parts.
+    fn setup(n: usize, threshold: usize) -> (Vec<KeyGen<usize>>, Vec<(usize, Part)>) {
+        setup_with_mode(n, threshold, KeyGenMode::Feldman)
+    }
+
+    /// Creates `n` nodes running the given variant, and has them broadcast their parts.
+    fn setup_with_mode(
+        n: usize,
+        threshold: usize,
+        mode: KeyGenMode,
+    ) -> (Vec<KeyGen<usize>>, Vec<(usize, Part)>) {
+        let mut rng = rand::thread_rng();
+        let sec_keys: Vec<SecretKey> = (0..n).map(|_| rng.gen()).collect();
+        let pub_keys: BTreeMap<usize, PublicKey> = sec_keys
+            .iter()
+            .map(SecretKey::public_key)
+            .enumerate()
+            .collect();
+        let mut nodes = Vec::new();
+        let mut parts = Vec::new();
+        for (id, sk) in sec_keys.into_iter().enumerate() {
+            let (node, part) =
+                KeyGen::with_mode(id, sk, pub_keys.clone(), threshold, mode, &mut rng)
+                    .expect("new node");
+            nodes.push(node);
+            parts.push((id, part.expect("member")));
+        }
+        (nodes, parts)
+    }
+
+    #[test]
+    fn test_key_gen() {
+        let mut rng = rand::thread_rng();
+        let (mut nodes, parts) = setup(4, 1);
+
+        // Node 3's part is only handled after the keys were generated.
+        let mut acks = Vec::new();
+        for (sender, part) in &parts[..3] {
+            for node in &mut nodes {
+                let outcome = node.handle_part(sender, part.clone(), &mut rng);
+                match outcome.expect("known sender") {
+                    PartOutcome::Valid(Some(ack)) => acks.push((*node.our_id(), ack)),
+                    outcome => panic!("unexpected outcome {:?}", outcome),
+                }
+            }
+        }
+        for (sender, ack) in &acks {
+            for node in &mut nodes {
+                let outcome = node.handle_ack(sender, ack.clone());
+                assert_eq!(Ok(AckOutcome::Valid), outcome);
+            }
+        }
+        assert!(nodes[0].is_ready());
+        assert!(nodes[0].is_node_ready(&2));
+        assert!(!nodes[0].is_node_ready(&3));
+
+        // All nodes agree on the key set, and their shares match it.
+        let complete = nodes[0].complete().expect("ready");
+        assert!(nodes.iter().all(|node| node.verify_complete(&complete)));
+        let (pk_set, _) = nodes[0].generate().expect("ready");
+        let sig_shares: BTreeMap<usize, SignatureShare> = nodes
+            .iter()
+            .map(|node| {
+                let (node_pk_set, share) = node.generate().expect("ready");
+                assert_eq!(pk_set, node_pk_set);
+                let share = share.expect("member");
+                assert_eq!(
+                    pk_set.public_key_share(node.our_id()),
+                    share.public_key_share()
+                );
+                (*node.our_id(), share.sign("msg"))
+            })
+            .collect();
+        let sig = pk_set.combine_signatures(sig_shares.iter().take(2));
+        assert!(pk_set.public_key().verify(&sig.unwrap(), "msg"));
+
+        // Saving and restoring the state replays the messages.
+        let saved = bincode::serialize(&nodes[1].save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&saved).expect("deserialize state");
+        let restored = KeyGen::<usize>::restore_state(saved).expect("valid state");
+        assert_eq!(nodes[1].generate(), restored.generate());
+    }
+
+    #[test]
+    fn test_faults() {
+        let mut rng = rand::thread_rng();
+        let (mut nodes, parts) = setup(3, 1);
+        let (sender, part) = parts[0].clone();
+        let ack = match nodes[1].handle_part(&sender, part.clone(), &mut rng) {
+            Ok(PartOutcome::Valid(Some(ack))) => ack,
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        };
+        assert_eq!(Ok(AckOutcome::Valid), nodes[1].handle_ack(&1, ack.clone()));
+        assert_eq!(
+            Ok(AckOutcome::Invalid(AckFault::DuplicateAck)),
+            nodes[1].handle_ack(&1, ack.clone())
+        );
+        assert_eq!(
+            Ok(AckOutcome::Invalid(AckFault::MissingPart)),
+            nodes[2].handle_ack(&1, ack.clone())
+        );
+        let result = nodes[1].handle_ack(&7, ack);
+        assert_eq!(Some(Error::UnknownParticipant), result.err());
+
+        // A part can't be replaced, and each node must be able to decrypt its row.
+        let other = parts[1].1.clone();
+        let result = nodes[1].handle_part(&0, other.clone(), &mut rng);
+        assert_eq!(Ok(PartOutcome::Invalid(PartFault::MultipleParts)), result);
+        let mut swapped = other.clone();
+        swapped.rows.swap(0, 2);
+        let result = nodes[0].handle_part(&1, swapped, &mut rng);
+        assert!(matches!(result, Ok(PartOutcome::Invalid(_))));
+        let mut short = other;
+        short.rows.pop();
+        let result = nodes[0].handle_part(&1, short, &mut rng);
+        assert_eq!(Ok(PartOutcome::Invalid(PartFault::RowCount)), result);
+        assert_eq!(Err(Error::NotEnoughShares), nodes[0].generate().map(|_| ()));
+    }
+
+    #[cfg(feature = "sessions")]
+    #[test]
+    fn test_limits() {
+        let mut rng = rand::thread_rng();
+        let (mut nodes, parts) = setup(3, 1);
+        let (sender, part) = parts[0].clone();
+        let rows_len: usize = part.rows.iter().map(|row| row.to_bytes().len()).sum();
+        let part_len = part.commit.to_bytes().len() + rows_len;
+        assert_eq!(part_len, part.encoded_len());
+        let limits = SessionLimits {
+            max_submissions: 1,
+            max_message_size: part_len,
+            ..SessionLimits::default()
+        };
+        let mut node = nodes.remove(1).with_limits(limits);
+        let mut large = part.clone();
+        large.rows[0].1.push(0);
+        let limit = Error::LimitExceeded("message size".to_string());
+        let result = node.handle_part(&sender, large, &mut rng);
+        assert_eq!(Some(limit), result.err());
+        let ack = match node.handle_part(&sender, part.clone(), &mut rng) {
+            Ok(PartOutcome::Valid(Some(ack))) => ack,
+            outcome => panic!("unexpected outcome {:?}", outcome),
+        };
+        let limit = Error::LimitExceeded("submissions by participant 0".to_string());
+        let result = node.handle_part(&sender, part, &mut rng);
+        assert_eq!(Some(limit), result.err());
+        assert_eq!(Ok(AckOutcome::Valid), node.handle_ack(&1, ack));
+    }
+
+    #[test]
+    fn test_pedersen() {
+        let mut rng = rand::thread_rng();
+        let (mut nodes, parts) = setup_with_mode(4, 1, KeyGenMode::Pedersen);
+        let mut acks = Vec::new();
+        for (sender, part) in &parts {
+            for node in &mut nodes {
+                match node.handle_part(sender, part.clone(), &mut rng) {
+                    Ok(PartOutcome::Valid(Some(ack))) => acks.push((*node.our_id(), ack)),
+                    outcome => panic!("unexpected outcome {:?}", outcome),
+                }
+            }
+        }
+        for (sender, ack) in &acks {
+            for node in &mut nodes {
+                assert_eq!(Ok(AckOutcome::Valid), node.handle_ack(sender, ack.clone()));
+            }
+        }
+
+        // The first two complete parts are qualified, but their contributions are still unknown.
+        let qualified: BTreeSet<u64> = vec![0, 1].into_iter().collect();
+        assert_eq!(Some(&qualified), nodes[2].qualified());
+        assert!(!nodes[2].is_ready());
+        assert_eq!(None, nodes[2].reveal());
+
+        // Node 1 reveals its contribution, but node 0 reveals a wrong one.
+        let honest = nodes[1].reveal().expect("qualified");
+        let wrong = Reveal {
+            commit: Poly::random(1, &mut rng).commitment(),
+        };
+        let mut disclosures = Vec::new();
+        for node in &mut nodes {
+            assert_eq!(
+                Ok(RevealOutcome::Valid),
+                node.handle_reveal(&1, honest.clone())
+            );
+            match node.handle_reveal(&0, wrong.clone()) {
+                Ok(RevealOutcome::Disputed(msg)) => disclosures.push((*node.our_id(), msg)),
+                outcome => panic!("unexpected outcome {:?}", outcome),
+            }
+        }
+        let result = nodes[3].handle_reveal(&3, wrong);
+        assert_eq!(
+            Ok(RevealOutcome::Invalid(RevealFault::NotQualified)),
+            result
+        );
+
+        // The nodes' disclosures reveal node 0's actual contribution.
+        let unjustified = disclosure(1, nodes[0].parts[&1].row.as_ref().expect("member"));
+        let result = nodes[2].handle_disclosure(&0, unjustified);
+        assert_eq!(
+            Ok(DisclosureOutcome::Invalid(DisclosureFault::Unjustified)),
+            result
+        );
+        for (sender, msg) in &disclosures {
+            for node in &mut nodes {
+                let outcome = node.handle_disclosure(sender, msg.clone());
+                assert_eq!(Ok(DisclosureOutcome::Valid(None)), outcome);
+            }
+        }
+        let (pk_set, _) = nodes[0].generate().expect("ready");
+        for node in &nodes {
+            let (node_pk_set, share) = node.generate().expect("ready");
+            assert_eq!(pk_set, node_pk_set);
+            let share = share.expect("member");
+            assert_eq!(
+                pk_set.public_key_share(node.our_id()),
+                share.public_key_share()
+            );
+        }
+
+        let saved = bincode::serialize(&nodes[0].save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&saved).expect("deserialize state");
+        let restored = KeyGen::<usize>::restore_state(saved).expect("valid state");
+        assert_eq!(nodes[0].generate(), restored.generate());
+    }
+}
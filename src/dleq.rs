@@ -0,0 +1,183 @@
+//! Decryption shares with proofs of correctness.
+//!
+//! `PublicKeyShare::verify_decryption_share` checks a share with two pairings against the
+//! ciphertext's `G2` component. A `ProvenDecryptionShare` instead carries a Chaum-Pedersen proof
+//! that the share `D = x * U` and the public key share `P = x * G` have the same discrete logarithm
+//! `x`, where `U` is the ciphertext's `G1` component and `G` the generator of `G1`. Any third party
+//! can check it with four multiplications in `G1` and no pairings, e.g. to audit a decryption after
+//! the fact, without learning `x` or the ciphertext's randomness.
+//!
+//! ```
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! let ct = pk_set.public_key().encrypt("secret");
+//! let share = sk_set.secret_key_share(0).decrypt_share_with_proof(&ct, &mut rng).unwrap();
+//! assert!(share.verify(&pk_set.public_key_share(0), &ct));
+//! ```
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+
+use crate::secret::clear_fr;
+use crate::util::sha3_256;
+use crate::{Ciphertext, DecryptionShare, Fr, G1Affine, PublicKeyShare, SecretKeyShare, G1};
+
+/// The domain separator for the proofs' challenges.
+const DST: &[u8] = b"threshold_crypto decryption share DLEQ";
+
+/// A decryption share with a proof that it matches the sender's public key share.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProvenDecryptionShare {
+    share: DecryptionShare,
+    #[serde(with = "crate::serde_impl::field_element")]
+    challenge: Fr,
+    #[serde(with = "crate::serde_impl::field_element")]
+    response: Fr,
+}
+
+impl ProvenDecryptionShare {
+    /// Returns the decryption share.
+    pub fn share(&self) -> &DecryptionShare {
+        &self.share
+    }
+
+    /// Returns the decryption share, discarding the proof.
+    pub fn into_share(self) -> DecryptionShare {
+        self.share
+    }
+
+    /// Returns `true` if the proof shows that the share was created with the secret key belonging
+    /// to the public key share, for this ciphertext.
+    ///
+    /// This doesn't check the ciphertext itself: Use `Ciphertext::verify` for that.
+    pub fn verify(&self, pk_share: &PublicKeyShare, ct: &Ciphertext) -> bool {
+        self.verify_base(pk_share, &ct.0)
+    }
+
+    /// Returns `true` if the proof shows that the share is the public key share's discrete
+    /// logarithm times `u`.
+    pub(crate) fn verify_base(&self, pk_share: &PublicKeyShare, u: &G1) -> bool {
+        let (p, d) = ((pk_share.0).0, self.share.0);
+        count!(Exponentiation, 4);
+        let a = commitment(G1Affine::one(), &p, self.challenge, self.response);
+        let b = commitment(u.into_affine(), &d, self.challenge, self.response);
+        self.challenge == challenge(&p, u, &d, &a, &b)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns a decryption share with a proof of its correctness, or `None` if the ciphertext
+    /// isn't valid.
+    pub fn decrypt_share_with_proof<R: Rng>(
+        &self,
+        ct: &Ciphertext,
+        rng: &mut R,
+    ) -> Option<ProvenDecryptionShare> {
+        if !ct.verify() {
+            return None;
+        }
+        Some(self.decrypt_share_with_proof_no_verify(ct, rng))
+    }
+
+    /// Returns a decryption share with a proof of its correctness, without validating the
+    /// ciphertext.
+    pub fn decrypt_share_with_proof_no_verify<R: Rng>(
+        &self,
+        ct: &Ciphertext,
+        rng: &mut R,
+    ) -> ProvenDecryptionShare {
+        self.prove_decryption_share(&ct.0, rng)
+    }
+
+    /// Returns the decryption share `x * u` with a proof of its correctness.
+    pub(crate) fn prove_decryption_share<R: Rng>(
+        &self,
+        u: &G1,
+        rng: &mut R,
+    ) -> ProvenDecryptionShare {
+        let x = (self.0).0;
+        count!(Exponentiation, 4);
+        let share = DecryptionShare(u.into_affine().mul(x));
+        let mut w = Fr::random(rng);
+        let a = G1Affine::one().mul(w);
+        let b = u.into_affine().mul(w);
+        let p = G1Affine::one().mul(x);
+        let challenge = challenge(&p, u, &share.0, &a, &b);
+        let mut response = x;
+        response.mul_assign(&challenge);
+        response.add_assign(&w);
+        clear_fr(&mut w);
+        ProvenDecryptionShare {
+            share,
+            challenge,
+            response,
+        }
+    }
+}
+
+/// Returns `z * base - c * point`, the commitment an honest prover must have used.
+fn commitment(base: G1Affine, point: &G1, c: Fr, z: Fr) -> G1 {
+    let mut result = base.mul(z);
+    let mut cp = point.into_affine().mul(c);
+    cp.negate();
+    result.add_assign(&cp);
+    result
+}
+
+/// Hashes the statement and commitments to a challenge.
+fn challenge(p: &G1, u: &G1, d: &G1, a: &G1, b: &G1) -> Fr {
+    let mut data = DST.to_vec();
+    for point in &[p, u, d, a, b] {
+        data.extend(point.into_affine().into_compressed().as_ref());
+    }
+    Fr::random(&mut ChaChaRng::from_seed(sha3_256(&data)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_proven_decryption_shares() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let ct = pk_set.public_key().encrypt("secret");
+        let other_ct = pk_set.public_key().encrypt("secret");
+        let shares: Vec<ProvenDecryptionShare> = (0..3)
+            .map(|i| {
+                let sk_share = sk_set.secret_key_share(i);
+                let share = sk_share
+                    .decrypt_share_with_proof(&ct, &mut rng)
+                    .expect("valid ciphertext");
+                let pk_share = pk_set.public_key_share(i);
+                assert!(share.verify(&pk_share, &ct));
+                assert!(!share.verify(&pk_share, &other_ct));
+                assert!(!share.verify(&pk_set.public_key_share(i + 1), &ct));
+                assert!(pk_share.verify_decryption_share(share.share(), &ct));
+                assert_eq!(sk_share.decrypt_share(&ct).as_ref(), Some(share.share()));
+                share
+            })
+            .collect();
+        let plain = pk_set
+            .decrypt(
+                shares.iter().map(ProvenDecryptionShare::share).enumerate(),
+                &ct,
+            )
+            .expect("enough shares");
+        assert_eq!(b"secret".to_vec(), plain);
+
+        // A wrong share with the original proof is rejected.
+        let mut forged = shares[0].clone();
+        forged.share = rng.gen();
+        assert!(!forged.verify(&pk_set.public_key_share(0), &ct));
+    }
+}
@@ -0,0 +1,372 @@
+//! A drand client and contributor.
+//!
+//! drand is a distributed randomness beacon: Every `period` seconds, its nodes create a threshold
+//! BLS signature of the next round, and the signature's SHA-256 digest is the round's randomness.
+//! Its default schemes have public keys in `G1` and signatures in `G2`, hashed as in the IETF
+//! basic scheme, so the crate's types can verify the rounds and sign them as a contributor.
+//!
+//! In the chained scheme, each round signs the previous round's signature together with the round
+//! number; in the unchained scheme, only the round number. `ChainInfo` parses the chain info that
+//! drand nodes and relays serve as JSON, `Group` the group file of a node, and `Beacon` a round.
+//!
+//! ```
+//! use threshold_crypto::drand::{Beacon, ChainInfo};
+//!
+//! let info = ChainInfo::from_json(
+//!     r#"{
+//!         "public_key": "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31",
+//!         "period": 30,
+//!         "genesis_time": 1595431050,
+//!         "hash": "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce",
+//!         "groupHash": "176f93498eac9ca337150b46d21dd58673ea4e3581185f869672e59fa4cb390a",
+//!         "schemeID": "pedersen-bls-chained",
+//!         "metadata": {"beaconID": "default"}
+//!     }"#,
+//! )
+//! .unwrap();
+//! assert_eq!(1, info.round_at(1595431050));
+//! assert!(info.public_key().is_ok());
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::ietf::{hash_to_g2, BASIC_DST};
+use crate::util::digest::sha256;
+use crate::util::from_hex;
+use crate::{Commitment, PublicKey, PublicKeySet, SecretKeyShare, Signature, SignatureShare, G2};
+
+/// The identifier of the chained scheme.
+pub const CHAINED_SCHEME_ID: &str = "pedersen-bls-chained";
+
+/// The identifier of the unchained scheme.
+pub const UNCHAINED_SCHEME_ID: &str = "pedersen-bls-unchained";
+
+/// A drand signature scheme.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// Each round signs the previous round's signature and the round number.
+    Chained,
+    /// Each round signs only the round number.
+    Unchained,
+}
+
+impl Scheme {
+    /// Returns the scheme with the given identifier.
+    ///
+    /// Schemes with signatures in `G1`, like `bls-unchained-on-g1`, are not supported.
+    pub fn from_id(id: &str) -> Result<Scheme> {
+        match id {
+            CHAINED_SCHEME_ID => Ok(Scheme::Chained),
+            UNCHAINED_SCHEME_ID => Ok(Scheme::Unchained),
+            _ => Err(invalid(format!("unsupported scheme {:?}", id))),
+        }
+    }
+
+    /// Returns the scheme's identifier.
+    pub fn id(self) -> &'static str {
+        match self {
+            Scheme::Chained => CHAINED_SCHEME_ID,
+            Scheme::Unchained => UNCHAINED_SCHEME_ID,
+        }
+    }
+
+    /// Returns the digest that is signed in the given round.
+    ///
+    /// The previous signature is ignored in the unchained scheme.
+    pub fn message(self, round: u64, previous_signature: &[u8]) -> [u8; 32] {
+        match self {
+            Scheme::Chained => sha256(&[previous_signature, &round.to_be_bytes()]),
+            Scheme::Unchained => sha256(&[&round.to_be_bytes()]),
+        }
+    }
+
+    /// Returns the hash in `G2` that is signed in the given round.
+    pub fn hash(self, round: u64, previous_signature: &[u8]) -> G2 {
+        hash_to_g2(self.message(round, previous_signature), BASIC_DST)
+    }
+
+    /// Returns `true` if the signature is valid for the given round.
+    pub fn verify(
+        self,
+        pk: &PublicKey,
+        round: u64,
+        previous_signature: &[u8],
+        sig: &Signature,
+    ) -> bool {
+        pk.verify_g2(sig, self.hash(round, previous_signature))
+    }
+
+    /// Signs the given round with a node's key share, as a contributor.
+    pub fn sign_share(
+        self,
+        sk_share: &SecretKeyShare,
+        round: u64,
+        previous_signature: &[u8],
+    ) -> SignatureShare {
+        sk_share.sign_g2(self.hash(round, previous_signature))
+    }
+}
+
+/// The information about a chain that drand serves at `/info`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChainInfo {
+    /// The hexadecimal group public key.
+    pub public_key: String,
+    /// The time between rounds, in seconds.
+    pub period: u64,
+    /// The Unix time of the first round.
+    pub genesis_time: u64,
+    /// The hexadecimal chain hash.
+    pub hash: String,
+    /// The hexadecimal hash of the group file.
+    #[serde(rename = "groupHash", default)]
+    pub group_hash: String,
+    /// The scheme identifier. Older nodes don't send it and only support the chained scheme.
+    #[serde(rename = "schemeID", default = "default_scheme_id")]
+    pub scheme_id: String,
+}
+
+impl ChainInfo {
+    /// Parses the chain info from JSON.
+    pub fn from_json(json: &str) -> Result<ChainInfo> {
+        serde_json::from_str(json).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Returns the group public key.
+    pub fn public_key(&self) -> Result<PublicKey> {
+        decode_hex("public key", &self.public_key, PublicKey::from_slice)
+    }
+
+    /// Returns the chain's signature scheme.
+    pub fn scheme(&self) -> Result<Scheme> {
+        Scheme::from_id(&self.scheme_id)
+    }
+
+    /// Returns the latest round at the given Unix time, or `0` before the genesis.
+    pub fn round_at(&self, time: u64) -> u64 {
+        if time < self.genesis_time || self.period == 0 {
+            return 0;
+        }
+        (time - self.genesis_time) / self.period + 1
+    }
+
+    /// Returns `true` if the beacon is a valid round of this chain.
+    ///
+    /// Returns `false` if the public key or scheme is invalid.
+    pub fn verify(&self, beacon: &Beacon) -> bool {
+        match (self.public_key(), self.scheme()) {
+            (Ok(pk), Ok(scheme)) => beacon.verify(&pk, scheme),
+            _ => false,
+        }
+    }
+}
+
+/// A round, as served by drand at `/public/{round}`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Beacon {
+    /// The round number.
+    pub round: u64,
+    /// The hexadecimal randomness, i.e. the SHA-256 digest of the signature.
+    pub randomness: String,
+    /// The hexadecimal signature.
+    pub signature: String,
+    /// The hexadecimal signature of the previous round, in the chained scheme.
+    #[serde(default)]
+    pub previous_signature: String,
+}
+
+impl Beacon {
+    /// Parses the round from JSON.
+    pub fn from_json(json: &str) -> Result<Beacon> {
+        serde_json::from_str(json).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Returns `true` if the signature is valid for the round and key, and the randomness is its
+    /// digest.
+    pub fn verify(&self, pk: &PublicKey, scheme: Scheme) -> bool {
+        let sig_bytes = match from_hex(&self.signature) {
+            Some(bytes) => bytes,
+            None => return false,
+        };
+        let (sig, prev) = match (
+            Signature::from_slice(&sig_bytes),
+            from_hex(&self.previous_signature),
+        ) {
+            (Ok(sig), Some(prev)) => (sig, prev),
+            _ => return false,
+        };
+        from_hex(&self.randomness).as_deref() == Some(&sha256(&[&sig_bytes])[..])
+            && scheme.verify(pk, self.round, &prev, &sig)
+    }
+}
+
+/// A drand group file, in TOML.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Group {
+    /// The number of nodes that must sign a round.
+    pub threshold: usize,
+    /// The time between rounds, e.g. `"30s"`.
+    pub period: String,
+    /// The Unix time of the first round.
+    pub genesis_time: u64,
+    /// The scheme identifier. Older group files don't have it and use the chained scheme.
+    #[serde(rename = "SchemeID", default = "default_scheme_id")]
+    pub scheme_id: String,
+    /// The nodes of the group.
+    #[serde(default)]
+    pub nodes: Vec<Node>,
+    /// The commitments to the distributed key, once it has been generated.
+    pub public_key: Option<DistPublic>,
+}
+
+impl Group {
+    /// Parses the group file.
+    pub fn from_toml(toml: &str) -> Result<Group> {
+        toml::from_str(toml).map_err(|err| invalid(err.to_string()))
+    }
+
+    /// Returns the chain's signature scheme.
+    pub fn scheme(&self) -> Result<Scheme> {
+        Scheme::from_id(&self.scheme_id)
+    }
+
+    /// Returns the public key set of the distributed key.
+    ///
+    /// The node with drand `Index` `i` has the key share `public_key_share(i)`. drand's threshold
+    /// is the number of shares needed, one more than the key set's.
+    pub fn public_key_set(&self) -> Result<PublicKeySet> {
+        let dist = self
+            .public_key
+            .as_ref()
+            .ok_or_else(|| invalid("the group has no distributed key".to_string()))?;
+        if dist.coefficients.len() != self.threshold || self.threshold == 0 {
+            let msg = format!(
+                "{} coefficients for threshold {}",
+                dist.coefficients.len(),
+                self.threshold
+            );
+            return Err(invalid(msg));
+        }
+        let coeff = dist
+            .coefficients
+            .iter()
+            .map(|c| decode_hex("coefficient", c, PublicKey::from_slice).map(|pk| pk.0))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(PublicKeySet::from(Commitment { coeff }))
+    }
+}
+
+/// A node of a drand group.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Node {
+    /// The node's address.
+    pub address: String,
+    /// The node's hexadecimal long-term public key.
+    pub key: String,
+    /// The node's index in the distributed key.
+    pub index: u32,
+}
+
+/// The commitments to the coefficients of a drand group's distributed key.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DistPublic {
+    /// The hexadecimal commitments, starting with the group public key.
+    pub coefficients: Vec<String>,
+}
+
+fn default_scheme_id() -> String {
+    CHAINED_SCHEME_ID.to_string()
+}
+
+fn invalid(msg: String) -> Error {
+    Error::InvalidChainInfo(msg)
+}
+
+/// Decodes the hexadecimal field with the given decoding function.
+fn decode_hex<T, E, F>(field: &str, hex: &str, decode: F) -> Result<T>
+where
+    F: FnOnce(&[u8]) -> std::result::Result<T, E>,
+{
+    from_hex(hex)
+        .and_then(|bytes| decode(&bytes).ok())
+        .ok_or_else(|| invalid(format!("invalid {}", field)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use hex_fmt::HexFmt;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_mainnet_round() {
+        let info = ChainInfo {
+            public_key: "868f005eb8e6e4ca0a47c8a77ceaa5309a47978a7c71bc5cce96366b5d7a569937c529eeda66c7293784a9402801af31".to_string(),
+            period: 30,
+            genesis_time: 1_595_431_050,
+            hash: "8990e7a9aaed2ffed73dbd7092123d6f289930540d7651336225dc172e51b2ce".to_string(),
+            group_hash: String::new(),
+            scheme_id: default_scheme_id(),
+        };
+        let signature = "82f5d3d2de4db19d40a6980e8aa37842a0e55d1df06bd68bddc8d60002e8e959eb9cfa368b3c1b77d18f02a54fe047b80f0989315f83b12a74fd8679c4f12aae86eaf6ab5690b34f1fddd50ee3cc6f6cdf59e95526d5a5d82aaa84fa6f181e42";
+        let mut beacon = Beacon {
+            round: 72_785,
+            randomness: HexFmt(sha256(&[&from_hex(signature).unwrap()])).to_string(),
+            signature: signature.to_string(),
+            previous_signature: "a609e19a03c2fcc559e8dae14900aaefe517cb55c840f6e69bc8e4f66c8d18e8a609685d9917efbfb0c37f058c2de88f13d297c7e19e0ab24813079efe57a182554ff054c7638153f9b26a60e7111f71a0ff63d9571704905d3ca6df0b031747".to_string(),
+        };
+        assert!(info.verify(&beacon));
+        beacon.round += 1;
+        assert!(!info.verify(&beacon));
+    }
+
+    #[test]
+    fn test_group_contributors() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let coefficients: Vec<String> = pk_set
+            .commit
+            .coeff
+            .iter()
+            .map(|c| format!("\"{}\"", HexFmt(PublicKey(*c).to_bytes())))
+            .collect();
+        let toml = format!(
+            "Threshold = 2\nPeriod = \"3s\"\nGenesisTime = 1600000000\n\
+             SchemeID = \"pedersen-bls-unchained\"\n\n\
+             [[Nodes]]\nAddress = \"a:1\"\nKey = \"00\"\nTLS = true\nIndex = 0\n\n\
+             [PublicKey]\nCoefficients = [{}]\n",
+            coefficients.join(", ")
+        );
+        let group = Group::from_toml(&toml).expect("valid group file");
+        assert_eq!(Ok(Scheme::Unchained), group.scheme());
+        assert_eq!(0, group.nodes[0].index);
+        assert_eq!(Ok(pk_set.clone()), group.public_key_set());
+
+        for scheme in &[Scheme::Chained, Scheme::Unchained] {
+            let prev = [7u8; 96];
+            let shares: Vec<SignatureShare> = (0..3)
+                .map(|i| scheme.sign_share(&sk_set.secret_key_share(i), 5, &prev))
+                .collect();
+            let sig = pk_set
+                .combine_signatures(shares.iter().enumerate().skip(1))
+                .expect("enough shares");
+            let pk = pk_set.public_key();
+            assert!(scheme.verify(&pk, 5, &prev, &sig));
+            assert!(!scheme.verify(&pk, 6, &prev, &sig));
+            let other_prev_valid = *scheme == Scheme::Unchained;
+            assert_eq!(other_prev_valid, scheme.verify(&pk, 5, &[0; 96], &sig));
+        }
+
+        let mut group = group;
+        group.threshold = 3;
+        assert!(group.public_key_set().is_err());
+        assert!(Scheme::from_id("bls-unchained-on-g1").is_err());
+    }
+}
@@ -0,0 +1,332 @@
+//! Re-randomizable ElGamal encryption of group elements.
+//!
+//! A `Ciphertext` can't be re-randomized: its message is masked with a hash of `r * PK`, and its
+//! `G2` component `W = r * H(U, V)` makes it non-malleable, so nobody without the secret key can
+//! turn it into a different encryption of the same message. Mix networks need exactly that: each
+//! mix server re-randomizes and shuffles a batch of ciphertexts, so that the outputs can't be
+//! linked to the inputs.
+//!
+//! An `ElGamalCiphertext` encrypts a point `M` in `G1` as `(r * G, M + r * PK)`. Anyone with the
+//! public key can add a fresh encryption of zero to it, which yields an unlinkable ciphertext of
+//! the same point. Decryption shares are `x_i * C1`, exactly as for a `Ciphertext`, and can be
+//! proven correct with a [`ProvenDecryptionShare`](../dleq/struct.ProvenDecryptionShare.html).
+//! The ciphertexts are malleable by design, so that a message should be a random point, e.g. one
+//! that a symmetric key is derived from, not application data.
+//!
+//! In the exponential mode, `encrypt_value` encrypts a number `m` as the point `m * G`. Adding two
+//! such ciphertexts gives an encryption of the sum of their numbers, so that e.g. encrypted votes
+//! can be tallied before a single threshold decryption. Recovering the number from the point is a
+//! discrete logarithm, which `decrypt_value` computes in about `sqrt(bound)` steps, for values up
+//! to a given bound.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::elgamal::ElGamalCiphertext;
+//! use threshold_crypto::{SecretKeySet, G1};
+//! use threshold_crypto::group::CurveProjective;
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! let point = G1::random(&mut rng);
+//! let ct = pk_set.public_key().encrypt_elgamal(point);
+//! let mixed = ct.rerandomize(&pk_set.public_key(), &mut rng);
+//! assert_ne!(ct, mixed);
+//! let shares: BTreeMap<_, _> = (0..2)
+//!     .map(|i| (i, sk_set.secret_key_share(i).decrypt_elgamal_share(&mixed)))
+//!     .collect();
+//! assert_eq!(point, pk_set.decrypt_elgamal(&shares, &mixed).unwrap());
+//!
+//! let votes = [1, 0, 1, 1];
+//! let tally: ElGamalCiphertext = votes.iter().map(|v| pk_set.public_key().encrypt_value(*v)).sum();
+//! let shares: BTreeMap<_, _> = (0..2)
+//!     .map(|i| (i, sk_set.secret_key_share(i).decrypt_elgamal_share(&tally)))
+//!     .collect();
+//! assert_eq!(3, pk_set.decrypt_value(&shares, &tally, votes.len() as u64).unwrap());
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::HashMap;
+use std::{iter, ops};
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::dleq::ProvenDecryptionShare;
+use crate::error::{Error, Result};
+use crate::secret::clear_fr;
+use crate::{
+    interpolate, DecryptionShare, Fr, G1Affine, IntoFr, PublicKey, PublicKeySet, PublicKeyShare,
+    SecretKey, SecretKeyShare, G1,
+};
+
+/// An ElGamal encryption of a point in `G1`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ElGamalCiphertext {
+    #[serde(with = "crate::serde_impl::projective")]
+    c1: G1,
+    #[serde(with = "crate::serde_impl::projective")]
+    c2: G1,
+}
+
+impl ElGamalCiphertext {
+    /// Returns a new encryption of the same point, that can't be linked to this one without the
+    /// secret key.
+    pub fn rerandomize<R: Rng>(&self, pk: &PublicKey, rng: &mut R) -> Self {
+        *self + pk.encrypt_elgamal_with_rng(rng, G1::zero())
+    }
+
+    /// Returns the components `(r * G, M + r * PK)`.
+    pub fn to_points(&self) -> (G1, G1) {
+        (self.c1, self.c2)
+    }
+
+    /// Returns the ciphertext with the given components.
+    pub(crate) fn from_points(c1: G1, c2: G1) -> Self {
+        ElGamalCiphertext { c1, c2 }
+    }
+
+    /// Returns the trivial encryption of zero, i.e. of the point at infinity.
+    pub fn zero() -> Self {
+        ElGamalCiphertext::from_points(G1::zero(), G1::zero())
+    }
+}
+
+/// Adds the encrypted points, or in the exponential mode, the encrypted numbers.
+impl<B: Borrow<ElGamalCiphertext>> ops::AddAssign<B> for ElGamalCiphertext {
+    fn add_assign(&mut self, rhs: B) {
+        self.c1.add_assign(&rhs.borrow().c1);
+        self.c2.add_assign(&rhs.borrow().c2);
+    }
+}
+
+impl<B: Borrow<ElGamalCiphertext>> ops::Add<B> for ElGamalCiphertext {
+    type Output = ElGamalCiphertext;
+
+    fn add(mut self, rhs: B) -> ElGamalCiphertext {
+        self += rhs;
+        self
+    }
+}
+
+impl<B: Borrow<ElGamalCiphertext>> iter::Sum<B> for ElGamalCiphertext {
+    fn sum<I: Iterator<Item = B>>(iter: I) -> Self {
+        iter.fold(ElGamalCiphertext::zero(), |sum, ct| sum + ct)
+    }
+}
+
+impl PublicKey {
+    /// Encrypts the point.
+    pub fn encrypt_elgamal(&self, point: G1) -> ElGamalCiphertext {
+        self.encrypt_elgamal_with_rng(&mut OsRng, point)
+    }
+
+    /// Encrypts the point, using the given random number generator.
+    pub fn encrypt_elgamal_with_rng<R: Rng>(&self, rng: &mut R, point: G1) -> ElGamalCiphertext {
+        let mut r = Fr::random(rng);
+        count!(Exponentiation, 2);
+        let c1 = G1Affine::one().mul(r);
+        let mut c2 = self.0.into_affine().mul(r);
+        c2.add_assign(&point);
+        clear_fr(&mut r);
+        ElGamalCiphertext { c1, c2 }
+    }
+
+    /// Encrypts the number in the exponential mode, as the point `value * G`.
+    pub fn encrypt_value(&self, value: u64) -> ElGamalCiphertext {
+        self.encrypt_value_with_rng(&mut OsRng, value)
+    }
+
+    /// Encrypts the number in the exponential mode, using the given random number generator.
+    pub fn encrypt_value_with_rng<R: Rng>(&self, rng: &mut R, value: u64) -> ElGamalCiphertext {
+        count!(Exponentiation, 1);
+        let point = G1Affine::one().mul(value.into_fr());
+        self.encrypt_elgamal_with_rng(rng, point)
+    }
+}
+
+impl SecretKey {
+    /// Returns the decrypted point.
+    pub fn decrypt_elgamal(&self, ct: &ElGamalCiphertext) -> G1 {
+        count!(Exponentiation, 1);
+        let mut point = ct.c2;
+        point.sub_assign(&ct.c1.into_affine().mul(self.0));
+        point
+    }
+
+    /// Returns the number encrypted in the exponential mode.
+    ///
+    /// Returns `Error::ValueOutOfRange` if the number is greater than `bound`.
+    pub fn decrypt_value(&self, ct: &ElGamalCiphertext, bound: u64) -> Result<u64> {
+        discrete_log(&self.decrypt_elgamal(ct), bound)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns a decryption share.
+    pub fn decrypt_elgamal_share(&self, ct: &ElGamalCiphertext) -> DecryptionShare {
+        count!(Exponentiation, 1);
+        DecryptionShare(ct.c1.into_affine().mul((self.0).0))
+    }
+
+    /// Returns a decryption share with a proof of its correctness.
+    pub fn decrypt_elgamal_share_with_proof<R: Rng>(
+        &self,
+        ct: &ElGamalCiphertext,
+        rng: &mut R,
+    ) -> ProvenDecryptionShare {
+        self.prove_decryption_share(&ct.c1, rng)
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns `true` if the proof shows that the share was created with the secret key belonging
+    /// to this public key share, for this ciphertext.
+    ///
+    /// Since both components are in `G1`, there is no pairing check for unproven shares.
+    pub fn verify_elgamal_share(
+        &self,
+        share: &ProvenDecryptionShare,
+        ct: &ElGamalCiphertext,
+    ) -> bool {
+        share.verify_base(self, &ct.c1)
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the decryption shares to decrypt the point.
+    ///
+    /// Like `decrypt`, this doesn't verify the shares: Combining invalid ones yields a wrong point.
+    pub fn decrypt_elgamal<'a, T, I>(&self, shares: I, ct: &ElGamalCiphertext) -> Result<G1>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &share.0));
+        let mask = interpolate(self.commit.degree(), samples)?;
+        let mut point = ct.c2;
+        point.sub_assign(&mask);
+        Ok(point)
+    }
+
+    /// Combines the decryption shares to decrypt the number encrypted in the exponential mode.
+    ///
+    /// Returns `Error::ValueOutOfRange` if the number is greater than `bound`, e.g. because one of
+    /// the shares was invalid.
+    pub fn decrypt_value<'a, T, I>(
+        &self,
+        shares: I,
+        ct: &ElGamalCiphertext,
+        bound: u64,
+    ) -> Result<u64>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        discrete_log(&self.decrypt_elgamal(shares, ct)?, bound)
+    }
+}
+
+/// Returns the number `m <= bound` with `m * G == point`, using the baby-step giant-step
+/// algorithm.
+fn discrete_log(point: &G1, bound: u64) -> Result<u64> {
+    // The smallest step with `step * step > bound`.
+    let step = (bound as f64).sqrt() as u64 + 1;
+    let key = |p: &G1| p.into_affine().into_compressed().as_ref().to_vec();
+    let mut baby_steps = HashMap::with_capacity(step as usize);
+    let mut p = G1::zero();
+    for j in 0..step {
+        baby_steps.entry(key(&p)).or_insert(j);
+        p.add_assign_mixed(&G1Affine::one());
+    }
+    // `p` is now `step * G`.
+    let mut giant = *point;
+    for i in 0..=(bound / step) {
+        if let Some(j) = baby_steps.get(&key(&giant)) {
+            let value = i * step + j;
+            if value <= bound {
+                return Ok(value);
+            }
+        }
+        giant.sub_assign(&p);
+    }
+    Err(Error::ValueOutOfRange(bound))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_rerandomize() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let pk = pk_set.public_key();
+        let point = G1::random(&mut rng);
+        let ct = pk.encrypt_elgamal_with_rng(&mut rng, point);
+        assert_eq!(point, sk_set.secret_key().decrypt_elgamal(&ct));
+
+        // Re-randomizing changes both components, but not the plaintext.
+        let mixed = ct.rerandomize(&pk, &mut rng).rerandomize(&pk, &mut rng);
+        assert_ne!(ct.c1, mixed.c1);
+        assert_ne!(ct.c2, mixed.c2);
+        assert_eq!(point, sk_set.secret_key().decrypt_elgamal(&mixed));
+
+        let shares: BTreeMap<usize, ProvenDecryptionShare> = (0..4)
+            .map(|i| {
+                let sk_share = sk_set.secret_key_share(i);
+                let share = sk_share.decrypt_elgamal_share_with_proof(&mixed, &mut rng);
+                let pk_share = pk_set.public_key_share(i);
+                assert!(pk_share.verify_elgamal_share(&share, &mixed));
+                assert!(!pk_share.verify_elgamal_share(&share, &ct));
+                assert_eq!(&sk_share.decrypt_elgamal_share(&mixed), share.share());
+                (i, share)
+            })
+            .collect();
+        let plain = pk_set
+            .decrypt_elgamal(shares.iter().map(|(i, s)| (*i, s.share())), &mixed)
+            .expect("enough shares");
+        assert_eq!(point, plain);
+        assert!(pk_set
+            .decrypt_elgamal(shares.iter().map(|(i, s)| (*i, s.share())).take(2), &mixed)
+            .is_err());
+    }
+
+    #[test]
+    fn test_additive_homomorphism() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let pk = pk_set.public_key();
+        let values = [3u64, 0, 250, 1_000];
+        let cts: Vec<ElGamalCiphertext> = values
+            .iter()
+            .map(|v| pk.encrypt_value_with_rng(&mut rng, *v))
+            .collect();
+        let sk = sk_set.secret_key();
+        assert_eq!(Ok(250), sk.decrypt_value(&cts[2], 250));
+        assert_eq!(Ok(0), sk.decrypt_value(&cts[1], 0));
+        assert_eq!(
+            Err(Error::ValueOutOfRange(249)),
+            sk.decrypt_value(&cts[2], 249)
+        );
+
+        let sum: ElGamalCiphertext = cts.iter().sum();
+        assert_eq!(sum, cts[0] + cts[1] + cts[2] + cts[3]);
+        let sum = sum.rerandomize(&pk, &mut rng);
+        let shares: BTreeMap<usize, DecryptionShare> = (0..2)
+            .map(|i| (i, sk_set.secret_key_share(i).decrypt_elgamal_share(&sum)))
+            .collect();
+        assert_eq!(Ok(1_253), pk_set.decrypt_value(&shares, &sum, 10_000));
+        assert_eq!(Ok(0), sk.decrypt_value(&ElGamalCiphertext::zero(), 10));
+    }
+}
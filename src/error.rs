@@ -11,9 +11,99 @@ pub enum Error {
     /// Signature shares contain a duplicated index.
     #[error("Signature shares contain a duplicated index")]
     DuplicateEntry,
-    /// The degree is too high for the coefficients to be indexed by `usize`.
-    #[error("The degree is too high for the coefficients to be indexed by usize.")]
+    /// The degree is too high for the coefficients to be indexed by `usize` or allocated.
+    #[error("The degree is too high for the coefficients to be indexed by usize or allocated.")]
     DegreeTooHigh,
+    /// A signature or decryption share doesn't match the sender's public key share.
+    #[error("Share {0} is invalid")]
+    InvalidShare(usize),
+    /// The ciphertext isn't valid.
+    #[error("Invalid ciphertext")]
+    InvalidCiphertext,
+    /// A share-holder backend failed to perform an operation.
+    #[error("Share-holder backend error: {0}")]
+    Backend(String),
+    /// The coordinator has no signing request with the given ID.
+    #[error("Unknown request {0}")]
+    UnknownRequest(u64),
+    /// The vault has no entry with the given label.
+    #[error("No vault entry labelled {0:?}")]
+    VaultEntryNotFound(String),
+    /// The vault entry with the given label is not a valid secret key share.
+    #[error("Invalid vault entry {0:?}")]
+    InvalidVaultEntry(String),
+    /// A vault entry was wrapped with a key that is not registered with the vault.
+    #[error("Unknown wrapping key {0:?}")]
+    UnknownWrappingKey(String),
+    /// A metrics recorder has already been installed.
+    #[error("A metrics recorder has already been set")]
+    RecorderAlreadySet,
+    /// An archived protocol transcript is malformed or doesn't verify.
+    #[error("Invalid transcript: {0}")]
+    InvalidTranscript(String),
+    /// The signer is not among those that committed to a nonce in the signing package.
+    #[error("Signer {0} has no nonce commitment in the signing package")]
+    UnknownSigner(usize),
+    /// The signer's nonces don't match its commitment in the signing package.
+    #[error("Signer {0}'s nonces don't match its commitment in the signing package")]
+    NonceMismatch(usize),
+    /// The access structure's parameters are inconsistent.
+    #[error("Invalid access structure: {0}")]
+    InvalidAccessStructure(String),
+    /// The shares' indices don't determine a unique secret.
+    #[error("The shares' indices don't determine the secret")]
+    InterpolationFailed,
+    /// A saved protocol state can't be restored.
+    #[error("Invalid saved state: {0}")]
+    InvalidState(String),
+    /// The outcome of a key rotation is inconsistent with the current epoch.
+    #[error("Invalid key rotation: {0}")]
+    InvalidRotation(String),
+    /// A secret reconstructed from shares doesn't match its digest.
+    #[error("The reconstructed secret failed its integrity check")]
+    IntegrityCheckFailed,
+    /// A committee transition is out of order or not signed by the previous committee.
+    #[error("Committee transition to epoch {0} is invalid")]
+    InvalidTransition(u64),
+    /// A key transparency log entry doesn't extend the log or is not signed by its committee.
+    #[error("Key transparency log entry {0} is invalid")]
+    InvalidLogEntry(u64),
+    /// The participant has been banned from the session.
+    #[error("Participant {0} is banned")]
+    Banned(usize),
+    /// A share was made for a different epoch than the session's.
+    #[error("Share is for epoch {0}, not the session's epoch")]
+    WrongEpoch(u64),
+    /// A key share was requested at the point zero, i.e. the master key.
+    #[error("Evaluation point is zero")]
+    ZeroEvaluationPoint,
+    /// The public key doesn't belong to any participant of the group.
+    #[error("Unknown participant")]
+    UnknownParticipant,
+    /// A session limit was exceeded.
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+    /// A key can't be derived from the given seed or path.
+    #[error("Invalid key derivation: {0}")]
+    InvalidKeyDerivation(String),
+    /// A drand chain info or group file is malformed or uses an unsupported scheme.
+    #[error("Invalid drand chain info: {0}")]
+    InvalidChainInfo(String),
+    /// A beacon round's signature doesn't match its message, or the round is out of order.
+    #[error("Beacon round {0} is invalid")]
+    InvalidRound(u64),
+    /// A number decrypted in the exponential mode is greater than the given bound.
+    #[error("The decrypted value is greater than {0}")]
+    ValueOutOfRange(u64),
+    /// A keystore is malformed or uses unsupported functions or parameters.
+    #[error("Invalid keystore: {0}")]
+    InvalidKeystore(String),
+    /// A keystore's checksum doesn't match the password.
+    #[error("Wrong keystore password")]
+    WrongPassword,
+    /// A mnemonic phrase is malformed or doesn't encode a key.
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
 }
 
 /// A crypto result.
@@ -42,3 +132,131 @@ pub enum FromBytesError {
 
 /// The result of attempting to read a structure from an array of bytes.
 pub type FromBytesResult<T> = ::std::result::Result<T, FromBytesError>;
+
+impl From<DecodeError> for FromBytesError {
+    fn from(_: DecodeError) -> Self {
+        FromBytesError::Invalid
+    }
+}
+
+/// The reason a field of an encoding is invalid.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Error)]
+pub enum DecodeReason {
+    /// The input ended in the middle of the field; the given number of bytes are missing.
+    #[error("unexpected end of input, {0} more bytes needed")]
+    UnexpectedEnd(usize),
+    /// The input has the given number of bytes after the end of the value.
+    #[error("{0} trailing bytes")]
+    TrailingBytes(usize),
+    /// The coordinates don't lie on the curve.
+    #[error("not on the curve")]
+    NotOnCurve,
+    /// The point is on the curve, but not in the prime-order subgroup.
+    #[error("not in the prime-order subgroup")]
+    NotInSubgroup,
+    /// A coordinate is not a reduced element of the base field.
+    #[error("coordinate exceeds the field modulus")]
+    InvalidCoordinate,
+    /// The compression or infinity flags are invalid.
+    #[error("invalid compression or infinity flags")]
+    InvalidFlags,
+    /// The integer is not a reduced element of the scalar field.
+    #[error("not a reduced field element")]
+    NotInField,
+    /// A length or degree field is inconsistent with the rest of the encoding.
+    #[error("inconsistent length")]
+    InvalidLength,
+    /// The point is the identity element, which the `strict` feature rejects.
+    #[error("the identity element")]
+    Identity,
+}
+
+/// An error decoding a value from a byte slice, identifying the invalid field and its offset.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Error)]
+#[error("Invalid {field} at byte {offset}: {reason}")]
+pub struct DecodeError {
+    /// The position in the input where the invalid field starts.
+    pub offset: usize,
+    /// The name of the invalid field.
+    pub field: &'static str,
+    /// What is wrong with the field.
+    pub reason: DecodeReason,
+}
+
+/// The result of decoding a value from a byte slice.
+pub type DecodeResult<T> = ::std::result::Result<T, DecodeError>;
+
+/// An error reading a value from its PEM or DER encoding.
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub enum PemError {
+    /// The input has no `BEGIN` and matching `END` line.
+    #[error("Missing or malformed PEM armor")]
+    Armor,
+    /// The PEM block has a different label than expected.
+    #[error("Expected a PEM block labelled {expected:?}, found {found:?}")]
+    Label {
+        /// The label for the requested type.
+        expected: &'static str,
+        /// The input's label.
+        found: String,
+    },
+    /// The PEM block's contents are not valid base64.
+    #[error("Invalid base64 in PEM block")]
+    Base64,
+    /// The DER structure is malformed; the field is named.
+    #[error("Invalid DER encoding of the {0}")]
+    Der(&'static str),
+    /// The algorithm identifier is not this crate's.
+    #[error("Unknown algorithm identifier")]
+    UnknownAlgorithm,
+    /// The encoded key or signature is invalid.
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+}
+
+/// The result of reading a value from its PEM or DER encoding.
+pub type PemResult<T> = ::std::result::Result<T, PemError>;
+
+/// An error reading a value from its versioned wire encoding.
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub enum WireError {
+    /// The input doesn't start with the wire format's magic bytes.
+    #[error("Missing wire format magic bytes")]
+    Magic,
+    /// The input's format version is unknown to this version of the crate.
+    #[error("Unsupported wire format version {0}")]
+    UnsupportedVersion(u8),
+    /// The input encodes a different type than expected.
+    #[error("Expected a wire encoding of a {expected}, found type tag {found}")]
+    Type {
+        /// The name of the requested type.
+        expected: &'static str,
+        /// The input's type tag.
+        found: u8,
+    },
+    /// The payload is invalid.
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+}
+
+/// The result of reading a value from its versioned wire encoding.
+pub type WireResult<T> = ::std::result::Result<T, WireError>;
+
+#[cfg(feature = "proto")]
+/// An error converting a protocol buffer message to the corresponding type.
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub enum ProtoError {
+    /// A required field is not set.
+    #[error("Missing field {0}")]
+    MissingField(&'static str),
+    /// A number doesn't fit in a `usize`.
+    #[error("{0} is out of range")]
+    OutOfRange(u64),
+    /// A field holds an invalid encoding.
+    #[error(transparent)]
+    Decode(#[from] DecodeError),
+}
+
+#[cfg(feature = "proto")]
+/// The result of converting a protocol buffer message to the corresponding type.
+pub type ProtoResult<T> = ::std::result::Result<T, ProtoError>;
@@ -0,0 +1,296 @@
+//! Verifiable escrow of key shares to an auditor.
+//!
+//! In regulated custody setups a supervisor must be able to recover the key under legal process,
+//! without being trusted to hold it day to day. Each participant publishes an [`EscrowedShare`]:
+//! their `SecretKeyShare` encrypted to the escrow agent's `PublicKey`, with a proof that it is
+//! the secret key of their public key share. Anyone with the `PublicKeySet` can verify it, and
+//! the escrow agent can decrypt it with their `SecretKey`.
+//!
+//! The share is encrypted bit by bit with ElGamal "in the exponent": for each bit `b` and random
+//! `r`, the ciphertext is `(r * G, b * G + r * E)`, where `G` is the generator of `G1` and `E` the
+//! escrow public key. A zero-knowledge OR-proof shows that each ciphertext encrypts `0` or `1`,
+//! and a proof of equality of discrete logarithms shows that the bits, weighted by powers of two,
+//! add up to the public key share. Since every plaintext is a bit, decryption only has to
+//! compare `b * G` with zero. An escrowed share takes about 60 kB.
+
+use std::borrow::Borrow;
+
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::secret::clear_fr;
+use crate::util::sha3_256;
+use crate::{Fr, G1Affine, PublicKey, PublicKeySet, SecretKey, SecretKeyShare, G1};
+
+/// The domain separator for the proofs' challenges.
+const DST: &[u8] = b"threshold_crypto escrow";
+
+/// The encryption of one bit of a key share, with the proof that it is a bit.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct BitCiphertext {
+    #[serde(with = "crate::serde_impl::projective")]
+    r: G1,
+    #[serde(with = "crate::serde_impl::projective")]
+    c: G1,
+    /// The challenges and responses for the statements `b = 0` and `b = 1`.
+    #[serde(with = "crate::serde_impl::field_vec")]
+    proof: Vec<Fr>,
+}
+
+/// A key share, verifiably encrypted to an escrow agent.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EscrowedShare {
+    index: usize,
+    bits: Vec<BitCiphertext>,
+    #[serde(with = "crate::serde_impl::field_element")]
+    challenge: Fr,
+    #[serde(with = "crate::serde_impl::field_element")]
+    response: Fr,
+}
+
+impl EscrowedShare {
+    /// Encrypts the key share with the given index to the escrow public key.
+    pub fn new<R: Rng>(
+        sk_share: &SecretKeyShare,
+        index: usize,
+        escrow: &PublicKey,
+        rng: &mut R,
+    ) -> Self {
+        let _span = span!("escrow", index = index);
+        let context = context(&(sk_share.public_key_share().0).0, index, escrow);
+        let g = G1Affine::one().into_projective();
+        let e = escrow.0;
+
+        let mut repr = ((sk_share.0).0).into_repr();
+        let mut rand_sum = Fr::zero();
+        let mut power = Fr::one();
+        let mut bits = Vec::with_capacity(Fr::NUM_BITS as usize);
+        for j in 0..Fr::NUM_BITS as usize {
+            let bit = repr.is_odd();
+            repr.div2();
+            let mut r_j = Fr::random(rng);
+            let r = mul(g, r_j);
+            let mut c = mul(e, r_j);
+            if bit {
+                c.add_assign(&g);
+            }
+
+            // Prove the true statement, and simulate the proof of the false one.
+            let mut w = Fr::random(rng);
+            let fake_c = Fr::random(rng);
+            let fake_z = Fr::random(rng);
+            let mut commitments = [(G1::zero(), G1::zero()), (G1::zero(), G1::zero())];
+            commitments[bit as usize] = (mul(g, w), mul(e, w));
+            commitments[!bit as usize] = simulate(r, statement(c, !bit), e, fake_c, fake_z);
+            let total = bit_challenge(&context, j, &r, &c, &commitments);
+            let mut real_c = total;
+            real_c.sub_assign(&fake_c);
+            let mut real_z = r_j;
+            real_z.mul_assign(&real_c);
+            real_z.add_assign(&w);
+            let proof = if bit {
+                vec![fake_c, real_c, fake_z, real_z]
+            } else {
+                vec![real_c, fake_c, real_z, fake_z]
+            };
+            bits.push(BitCiphertext { r, c, proof });
+
+            r_j.mul_assign(&power);
+            rand_sum.add_assign(&r_j);
+            power.double();
+            clear_fr(&mut r_j);
+            clear_fr(&mut w);
+        }
+
+        let mut w = Fr::random(rng);
+        let commitment = (mul(g, w), mul(e, w));
+        let challenge = sum_challenge(&context, &bits, &commitment);
+        let mut response = rand_sum;
+        response.mul_assign(&challenge);
+        response.add_assign(&w);
+        clear_fr(&mut rand_sum);
+        clear_fr(&mut w);
+        EscrowedShare {
+            index,
+            bits,
+            challenge,
+            response,
+        }
+    }
+
+    /// Returns the index of the escrowed key share.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `true` if this is an encryption of the key share `index` of the key set, to the
+    /// escrow public key.
+    pub fn verify(&self, pk_set: &PublicKeySet, escrow: &PublicKey) -> bool {
+        let _span = span!("verify_escrow", index = self.index);
+        if self.bits.len() != Fr::NUM_BITS as usize {
+            return false;
+        }
+        let pk_share = (pk_set.public_key_share(self.index).0).0;
+        let context = context(&pk_share, self.index, escrow);
+        let e = escrow.0;
+        let bits_valid = self.bits.iter().enumerate().all(|(j, bit)| {
+            if bit.proof.len() != 4 {
+                return false;
+            }
+            let commitments = [
+                simulate(bit.r, bit.c, e, bit.proof[0], bit.proof[2]),
+                simulate(bit.r, statement(bit.c, true), e, bit.proof[1], bit.proof[3]),
+            ];
+            let mut total = bit.proof[0];
+            total.add_assign(&bit.proof[1]);
+            total == bit_challenge(&context, j, &bit.r, &bit.c, &commitments)
+        });
+        if !bits_valid {
+            return false;
+        }
+        let (sum_r, mut sum_c) = weighted_sums(&self.bits);
+        sum_c.sub_assign(&pk_share);
+        let commitment = simulate(sum_r, sum_c, e, self.challenge, self.response);
+        self.challenge == sum_challenge(&context, &self.bits, &commitment)
+    }
+
+    /// Decrypts the key share with the escrow secret key.
+    ///
+    /// Returns `Error::InvalidShare` with the share index if the escrowed share doesn't decrypt to
+    /// the key share of `pk_set`, e.g. because it was encrypted to a different key.
+    pub fn decrypt<B: Borrow<SecretKey>>(
+        &self,
+        escrow_sk: B,
+        pk_set: &PublicKeySet,
+    ) -> Result<SecretKeyShare> {
+        let g = G1Affine::one().into_projective();
+        let mut value = Fr::zero();
+        let mut power = Fr::one();
+        for bit in &self.bits {
+            let mut plain = bit.c;
+            plain.sub_assign(&mul(bit.r, escrow_sk.borrow().0));
+            if plain == g {
+                value.add_assign(&power);
+            } else if !plain.is_zero() {
+                clear_fr(&mut value);
+                return Err(Error::InvalidShare(self.index));
+            }
+            power.double();
+        }
+        let sk_share = SecretKeyShare::from_mut(&mut value);
+        if sk_share.public_key_share() != pk_set.public_key_share(self.index) {
+            return Err(Error::InvalidShare(self.index));
+        }
+        Ok(sk_share)
+    }
+}
+
+/// Returns `p * s`.
+fn mul(mut p: G1, s: Fr) -> G1 {
+    p.mul_assign(s);
+    p
+}
+
+/// Returns the element that must be `r * E` if the ciphertext `c` encrypts `bit`.
+fn statement(mut c: G1, bit: bool) -> G1 {
+    if bit {
+        c.sub_assign(&G1Affine::one().into_projective());
+    }
+    c
+}
+
+/// Returns the commitments `(z * G - ch * a, z * e - ch * b)` of a proof that `a` and `b` have
+/// the same discrete logarithm to the bases `G` and `e`, given the challenge and response.
+fn simulate(a: G1, b: G1, e: G1, ch: Fr, z: Fr) -> (G1, G1) {
+    let mut lhs = mul(G1Affine::one().into_projective(), z);
+    lhs.sub_assign(&mul(a, ch));
+    let mut rhs = mul(e, z);
+    rhs.sub_assign(&mul(b, ch));
+    (lhs, rhs)
+}
+
+/// Returns the sums of the ciphertexts' components, weighted by powers of two.
+fn weighted_sums(bits: &[BitCiphertext]) -> (G1, G1) {
+    let mut sum_r = G1::zero();
+    let mut sum_c = G1::zero();
+    for bit in bits.iter().rev() {
+        sum_r.double();
+        sum_r.add_assign(&bit.r);
+        sum_c.double();
+        sum_c.add_assign(&bit.c);
+    }
+    (sum_r, sum_c)
+}
+
+/// Returns the context all challenges are bound to.
+fn context(pk_share: &G1, index: usize, escrow: &PublicKey) -> Vec<u8> {
+    let mut context = DST.to_vec();
+    context.extend(&(index as u64).to_be_bytes());
+    context.extend(PublicKey(*pk_share).to_bytes().as_ref());
+    context.extend(escrow.to_bytes().as_ref());
+    context
+}
+
+/// Hashes the context and group elements to a challenge.
+fn challenge<'a, I: IntoIterator<Item = &'a G1>>(mut data: Vec<u8>, points: I) -> Fr {
+    for p in points {
+        data.extend(p.into_affine().into_compressed().as_ref());
+    }
+    Fr::random(&mut ChaChaRng::from_seed(sha3_256(&data)))
+}
+
+/// Returns the challenge for the proof that bit `j`'s ciphertext encrypts `0` or `1`.
+fn bit_challenge(context: &[u8], j: usize, r: &G1, c: &G1, commitments: &[(G1, G1); 2]) -> Fr {
+    let mut data = context.to_vec();
+    data.extend(&(j as u64).to_be_bytes());
+    let [(a0, b0), (a1, b1)] = commitments;
+    challenge(data, vec![r, c, a0, b0, a1, b1])
+}
+
+/// Returns the challenge for the proof that the bits add up to the key share.
+fn sum_challenge(context: &[u8], bits: &[BitCiphertext], commitment: &(G1, G1)) -> Fr {
+    let points = bits.iter().flat_map(|bit| vec![&bit.r, &bit.c]);
+    let data = context.to_vec();
+    challenge(data, points.chain(vec![&commitment.0, &commitment.1]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_escrow() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let escrow_sk = SecretKey::random();
+        let escrow_pk = escrow_sk.public_key();
+
+        let escrowed = EscrowedShare::new(&sk_set.secret_key_share(2), 2, &escrow_pk, &mut rng);
+        assert!(escrowed.verify(&pk_set, &escrow_pk));
+        let sk_share = escrowed.decrypt(&escrow_sk, &pk_set).expect("valid escrow");
+        assert_eq!(sk_set.secret_key_share(2), sk_share);
+
+        // The proof is bound to the index and the escrow key.
+        let other_pk = SecretKey::random().public_key();
+        assert!(!escrowed.verify(&pk_set, &other_pk));
+        let mut moved = escrowed.clone();
+        moved.index = 1;
+        assert!(!moved.verify(&pk_set, &escrow_pk));
+        let result = moved.decrypt(&escrow_sk, &pk_set);
+        assert_eq!(Some(Error::InvalidShare(1)), result.err());
+
+        // Flipping a bit invalidates the proof.
+        let mut flipped = escrowed;
+        flipped.bits[0]
+            .c
+            .add_assign(&G1Affine::one().into_projective());
+        assert!(!flipped.verify(&pk_set, &escrow_pk));
+    }
+}
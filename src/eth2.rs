@@ -0,0 +1,276 @@
+//! Ethereum 2.0 validator keys and signatures.
+//!
+//! Ethereum's consensus layer uses the IETF proof of possession scheme, see
+//! [`ietf`](../ietf/index.html), with the compressed point encodings that `PublicKey::to_bytes`
+//! and `Signature::to_bytes` already produce, and 32-byte big-endian secret keys. Validator keys
+//! are derived from a seed as in EIP-2333, along EIP-2334 paths such as `m/12381/3600/0/0/0`.
+//!
+//! A threshold validator shares its key with `SecretKeySet::from_secret_key`: The combined
+//! signature of `threshold + 1` shares of `eth2::hash(msg)` is the validator's signature.
+//!
+//! ```
+//! use threshold_crypto::{eth2, SecretKeySet};
+//!
+//! let seed = [7u8; 32];
+//! let sk = eth2::derive_path(&seed, "m/12381/3600/0/0/0").unwrap();
+//! let sk_set = SecretKeySet::from_secret_key(&sk, 1, &mut rand::thread_rng()).unwrap();
+//! let pk_set = sk_set.public_keys();
+//! let shares: Vec<_> = (0..2)
+//!     .map(|i| sk_set.secret_key_share(i).sign_g2(eth2::hash("block")))
+//!     .collect();
+//! let sig = pk_set.combine_signatures(shares.iter().enumerate()).unwrap();
+//! assert!(eth2::verify(&sk.public_key(), "block", &sig));
+//! ```
+
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use group::CurveProjective;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, Result};
+use crate::ietf::{hash_to_g2, POP_DST};
+use crate::util::digest::sha256;
+use crate::{Fr, FrRepr, PublicKey, SecretKey, Signature, G2, SK_SIZE};
+
+/// The domain separation tag of Ethereum 2.0 signatures.
+pub const DST: &[u8] = POP_DST;
+
+/// The minimum length of a seed for key derivation.
+pub const MIN_SEED_LEN: usize = 32;
+
+/// The initial salt of `HKDF_mod_r` in EIP-2333.
+const KEYGEN_SALT: &[u8] = b"BLS-SIG-KEYGEN-SALT-";
+
+/// The number of 32-byte chunks of a Lamport secret key in EIP-2333.
+const LAMPORT_CHUNKS: usize = 255;
+
+/// Returns the hash of the message in `G2`, which is signed to sign the message.
+pub fn hash<M: AsRef<[u8]>>(msg: M) -> G2 {
+    hash_to_g2(msg, DST)
+}
+
+/// Signs the message.
+pub fn sign<M: AsRef<[u8]>>(sk: &SecretKey, msg: M) -> Signature {
+    sk.sign_g2(hash(msg))
+}
+
+/// Returns `true` if the public key is valid, i.e. not the identity.
+///
+/// Decoding already checks that the key is in the right subgroup.
+pub fn key_validate(pk: &PublicKey) -> bool {
+    !pk.0.is_zero()
+}
+
+/// Returns `true` if the public key is valid and the signature matches the message.
+pub fn verify<M: AsRef<[u8]>>(pk: &PublicKey, msg: M, sig: &Signature) -> bool {
+    key_validate(pk) && pk.verify_g2(sig, hash(msg))
+}
+
+/// Returns `true` if the signature is the aggregate of signatures of the message by all of the
+/// public keys, as in `FastAggregateVerify`.
+///
+/// The keys' proofs of possession must have been checked beforehand, e.g. on deposit.
+pub fn fast_aggregate_verify<M: AsRef<[u8]>>(
+    pub_keys: &[PublicKey],
+    msg: M,
+    sig: &Signature,
+) -> bool {
+    !pub_keys.is_empty()
+        && pub_keys.iter().all(key_validate)
+        && PublicKey::aggregate(pub_keys).verify_g2(sig, hash(msg))
+}
+
+/// Returns the 32-byte big-endian representation of the secret key.
+pub fn secret_key_to_bytes(sk: &SecretKey) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+    sk.0.into_repr()
+        .write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    bytes
+}
+
+/// Returns the secret key with the given 32-byte big-endian representation, or `None` if it is
+/// not a valid nonzero key.
+pub fn secret_key_from_bytes(bytes: &[u8]) -> Option<SecretKey> {
+    SecretKey::from_slice(bytes)
+        .ok()
+        .filter(|sk| !sk.0.is_zero())
+}
+
+/// Derives the master secret key from the seed, as in EIP-2333.
+///
+/// Returns `Error::InvalidKeyDerivation` if the seed is shorter than `MIN_SEED_LEN`.
+pub fn derive_master_sk(seed: &[u8]) -> Result<SecretKey> {
+    if seed.len() < MIN_SEED_LEN {
+        let msg = format!("the seed must have at least {} bytes", MIN_SEED_LEN);
+        return Err(Error::InvalidKeyDerivation(msg));
+    }
+    Ok(hkdf_mod_r(seed, &[]))
+}
+
+/// Derives the child secret key with the given index, as in EIP-2333.
+pub fn derive_child_sk(parent: &SecretKey, index: u32) -> SecretKey {
+    let salt = index.to_be_bytes();
+    let ikm = secret_key_to_bytes(parent);
+    let mut not_ikm = ikm.clone();
+    for byte in not_ikm.iter_mut() {
+        *byte = !*byte;
+    }
+    let mut lamport_pk = Zeroizing::new(Vec::with_capacity(2 * LAMPORT_CHUNKS * 32));
+    for ikm in &[ikm, not_ikm] {
+        let lamport_sk = hkdf_expand(&*hkdf_extract(&salt, &[ikm]), &[], LAMPORT_CHUNKS * 32);
+        for chunk in lamport_sk.chunks(32) {
+            lamport_pk.extend(&sha256(&[chunk]));
+        }
+    }
+    hkdf_mod_r(&sha256(&[&lamport_pk]), &[])
+}
+
+/// Derives the secret key at the path, e.g. `m/12381/3600/0/0/0`, as in EIP-2334.
+///
+/// Returns `Error::InvalidKeyDerivation` if the seed is too short, or the path doesn't start with
+/// `m` or contains an index that is not a `u32`.
+pub fn derive_path(seed: &[u8], path: &str) -> Result<SecretKey> {
+    let mut parts = path.split('/');
+    if parts.next() != Some("m") {
+        let msg = format!("path {:?} doesn't start with \"m\"", path);
+        return Err(Error::InvalidKeyDerivation(msg));
+    }
+    let mut sk = derive_master_sk(seed)?;
+    for part in parts {
+        let index = part.parse().map_err(|_| {
+            Error::InvalidKeyDerivation(format!("invalid index {:?} in path", part))
+        })?;
+        sk = derive_child_sk(&sk, index);
+    }
+    Ok(sk)
+}
+
+/// Returns the secret key `HKDF_mod_r(ikm)` of EIP-2333, which is `KeyGen(ikm, key_info)` of the
+/// IETF BLS signature draft. EIP-2333 uses an empty `key_info`.
+pub(crate) fn hkdf_mod_r(ikm: &[u8], key_info: &[u8]) -> SecretKey {
+    let mut salt = sha256(&[KEYGEN_SALT]);
+    loop {
+        let prk = hkdf_extract(&salt, &[ikm, &[0]]);
+        let okm = hkdf_expand(&*prk, &[key_info, &[0, 48]].concat(), 48);
+        let mut fr = fr_from_okm(&okm);
+        if !fr.is_zero() {
+            return SecretKey::from_mut(&mut fr);
+        }
+        salt = sha256(&[&salt]);
+    }
+}
+
+/// Returns the 48-byte big-endian integer modulo the group order.
+fn fr_from_okm(okm: &[u8]) -> Fr {
+    // Each 16-byte chunk is less than the modulus.
+    let mut two_128 = FrRepr::default();
+    two_128.as_mut()[2] = 1;
+    let two_128 = Fr::from_repr(two_128).expect("less than the modulus");
+    let mut result = Fr::zero();
+    for chunk in okm.chunks(16) {
+        let mut bytes = [0u8; SK_SIZE];
+        bytes[16..].copy_from_slice(chunk);
+        let mut repr = FrRepr::default();
+        repr.read_be(&bytes[..]).expect("32 bytes");
+        bytes.zeroize();
+        result.mul_assign(&two_128);
+        result.add_assign(&Fr::from_repr(repr).expect("less than the modulus"));
+        repr.0.zeroize();
+    }
+    result
+}
+
+/// Returns `HKDF-Extract` with SHA-256 of the concatenated inputs.
+fn hkdf_extract(salt: &[u8], ikm: &[&[u8]]) -> Zeroizing<[u8; 32]> {
+    hmac_sha256(salt, ikm)
+}
+
+/// Returns `HKDF-Expand` with SHA-256, with `len` bytes of output.
+fn hkdf_expand(prk: &[u8], info: &[u8], len: usize) -> Zeroizing<Vec<u8>> {
+    let mut okm = Zeroizing::new(Vec::with_capacity(len + 32));
+    let mut block = Zeroizing::new([0u8; 32]);
+    for i in 1..=len.div_ceil(32) as u8 {
+        let prev: &[u8] = if i == 1 { &[] } else { &*block };
+        block = hmac_sha256(prk, &[prev, info, &[i]]);
+        okm.extend(&*block);
+    }
+    okm.truncate(len);
+    okm
+}
+
+/// Returns the HMAC-SHA256 of the concatenated inputs.
+fn hmac_sha256(key: &[u8], data: &[&[u8]]) -> Zeroizing<[u8; 32]> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    for input in data {
+        mac.update(input);
+    }
+    Zeroizing::new(mac.finalize().into_bytes().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        let digit = |i| u8::from_str_radix(&hex[i..i + 2], 16).expect("hexadecimal");
+        (0..hex.len()).step_by(2).map(digit).collect()
+    }
+
+    #[test]
+    fn test_eip2333() {
+        // The first two test cases of EIP-2333.
+        let vectors = [
+            (
+                "c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04",
+                "0d7359d57963ab8fbbde1852dcf553fedbc31f464d80ee7d40ae683122b45070",
+                0,
+                "2d18bd6c14e6d15bf8b5085c9b74f3daae3b03cc2014770a599d8c1539e50f8e",
+            ),
+            (
+                "3141592653589793238462643383279502884197169399375105820974944592",
+                "41c9e07822b092a93fd6797396338c3ada4170cc81829fdfce6b5d34bd5e7ec7",
+                3_141_592_653,
+                "384843fad5f3d777ea39de3e47a8f999ae91f89e42bffa993d91d9782d152a0f",
+            ),
+        ];
+        for (seed, master, index, child) in &vectors {
+            let master_sk = derive_master_sk(&from_hex(seed)).expect("long enough seed");
+            assert_eq!(*master, hex(&secret_key_to_bytes(&master_sk)));
+            let child_sk = derive_child_sk(&master_sk, *index);
+            assert_eq!(*child, hex(&secret_key_to_bytes(&child_sk)));
+            let path = format!("m/{}", index);
+            let derived = derive_path(&from_hex(seed), &path).expect("valid path");
+            assert_eq!(child_sk, derived);
+        }
+        assert!(derive_master_sk(&[0; 31]).is_err());
+        assert!(derive_path(&[0; 32], "x/0").is_err());
+        assert!(derive_path(&[0; 32], "m/-1").is_err());
+    }
+
+    #[test]
+    fn test_eth2_signatures() {
+        let sk = derive_path(&[1; 32], "m/12381/3600/0/0/0").expect("valid path");
+        let bytes = secret_key_to_bytes(&sk);
+        assert_eq!(Some(sk.clone()), secret_key_from_bytes(&bytes));
+        assert_eq!(None, secret_key_from_bytes(&[0; 32]));
+        assert_eq!(None, secret_key_from_bytes(&[0xff; 32]));
+
+        let sig = sign(&sk, "msg");
+        assert!(verify(&sk.public_key(), "msg", &sig));
+        assert!(!verify(&sk.public_key(), "other msg", &sig));
+        assert!(!sk.public_key().verify(&sig, "msg"));
+
+        let sks: Vec<SecretKey> = (0..3).map(|i| derive_child_sk(&sk, i)).collect();
+        let pub_keys: Vec<PublicKey> = sks.iter().map(SecretKey::public_key).collect();
+        let sig = Signature::aggregate(sks.iter().map(|sk| sign(sk, "msg")));
+        assert!(fast_aggregate_verify(&pub_keys, "msg", &sig));
+        assert!(!fast_aggregate_verify(&pub_keys[1..], "msg", &sig));
+        assert!(!fast_aggregate_verify(&[], "msg", &sig));
+    }
+}
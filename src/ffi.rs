@@ -0,0 +1,487 @@
+//! A C interface for key generation, share signing, share verification and combination.
+//!
+//! Key sets, key shares and public key sets are passed as opaque handles, which are created by
+//! the `tc_*_random`, `tc_*_share` and `tc_*_from_bytes` functions and must be released with the
+//! matching `tc_*_free` function. Signatures and signature shares are `TC_SIG_SIZE`-byte buffers
+//! in their canonical encoding. Variable-length outputs are returned in a `TcBuffer` that must be
+//! released with `tc_buffer_free`.
+//!
+//! Functions that can fail return `TC_OK` on success and a negative error code otherwise, and
+//! functions that check a signature return `1` if it is valid and `0` if not. A panic never
+//! unwinds into the caller: The function returns `TC_ERR_PANIC`, null or a null buffer instead.
+//! `include/threshold_crypto.h` declares the interface. To build a library that C, C++ or Go can
+//! link against, run:
+//!
+//! ```text
+//! cargo rustc --release --features ffi --crate-type staticlib
+//! ```
+//!
+//! This module is only available with the `ffi` feature.
+
+use std::panic::{self, AssertUnwindSafe};
+use std::{ptr, slice};
+
+use ff::{PrimeField, PrimeFieldRepr};
+use rand::rngs::OsRng;
+use zeroize::Zeroize;
+
+use crate::{
+    PublicKeySet, SecretKeySet, SecretKeyShare, Signature, SignatureShare, SIG_SIZE, SK_SIZE,
+};
+
+/// The size of signatures and signature shares.
+pub const TC_SIG_SIZE: usize = SIG_SIZE;
+
+/// The function succeeded.
+pub const TC_OK: i32 = 0;
+/// A pointer argument was null.
+pub const TC_ERR_NULL: i32 = -1;
+/// A byte buffer argument is not a valid encoding.
+pub const TC_ERR_INVALID: i32 = -2;
+/// Too few or duplicate signature shares were passed to `tc_public_key_set_combine_signatures`.
+pub const TC_ERR_COMBINE: i32 = -3;
+/// The library panicked.
+pub const TC_ERR_PANIC: i32 = -4;
+
+/// A byte buffer allocated by this library.
+#[repr(C)]
+#[derive(Debug)]
+pub struct TcBuffer {
+    /// The buffer's contents, or null if the function failed.
+    pub data: *mut u8,
+    /// The number of bytes.
+    pub len: usize,
+}
+
+impl TcBuffer {
+    fn new(bytes: Vec<u8>) -> Self {
+        let bytes = bytes.into_boxed_slice();
+        let len = bytes.len();
+        TcBuffer {
+            data: Box::into_raw(bytes) as *mut u8,
+            len,
+        }
+    }
+
+    fn null() -> Self {
+        TcBuffer {
+            data: ptr::null_mut(),
+            len: 0,
+        }
+    }
+}
+
+/// Zeroes and releases a buffer returned by this library.
+///
+/// # Safety
+///
+/// The buffer must have been returned by this library and not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tc_buffer_free(buf: TcBuffer) {
+    catch((), || {
+        if !buf.data.is_null() {
+            let mut bytes = Box::from_raw(ptr::slice_from_raw_parts_mut(buf.data, buf.len));
+            bytes.zeroize();
+        }
+    })
+}
+
+/// Generates a random key set where any `threshold + 1` shares can sign, or returns null if the
+/// threshold is too large to allocate the key set.
+#[no_mangle]
+pub extern "C" fn tc_secret_key_set_random(threshold: usize) -> *mut SecretKeySet {
+    catch(ptr::null_mut(), || {
+        match SecretKeySet::try_random(threshold, &mut OsRng) {
+            Ok(sk_set) => Box::into_raw(Box::new(sk_set)),
+            Err(_) => ptr::null_mut(),
+        }
+    })
+}
+
+/// Releases a key set.
+///
+/// # Safety
+///
+/// `sk_set` must be null or a key set handle that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_set_free(sk_set: *mut SecretKeySet) {
+    catch((), || {
+        if !sk_set.is_null() {
+            drop(Box::from_raw(sk_set));
+        }
+    })
+}
+
+/// Returns the key share with index `i`, or null if `sk_set` is null.
+///
+/// # Safety
+///
+/// `sk_set` must be null or a valid key set handle.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_set_share(
+    sk_set: *const SecretKeySet,
+    i: u64,
+) -> *mut SecretKeyShare {
+    catch(ptr::null_mut(), || match sk_set.as_ref() {
+        Some(sk_set) => Box::into_raw(Box::new(sk_set.secret_key_share(i))),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Returns the key set's public keys, or null if `sk_set` is null.
+///
+/// # Safety
+///
+/// `sk_set` must be null or a valid key set handle.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_set_public_keys(
+    sk_set: *const SecretKeySet,
+) -> *mut PublicKeySet {
+    catch(ptr::null_mut(), || match sk_set.as_ref() {
+        Some(sk_set) => Box::into_raw(Box::new(sk_set.public_keys())),
+        None => ptr::null_mut(),
+    })
+}
+
+/// Decodes a key share from its 32-byte big-endian representation, or returns null if it is
+/// invalid.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_share_from_bytes(
+    bytes: *const u8,
+    len: usize,
+) -> *mut SecretKeyShare {
+    catch(ptr::null_mut(), || {
+        match input(bytes, len).map(SecretKeyShare::from_slice) {
+            Some(Ok(share)) => Box::into_raw(Box::new(share)),
+            _ => ptr::null_mut(),
+        }
+    })
+}
+
+/// Returns the key share's 32-byte big-endian representation, or a null buffer if `share` is
+/// null. The buffer contains secret key material, and `tc_buffer_free` zeroes it.
+///
+/// # Safety
+///
+/// `share` must be null or a valid key share handle.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_share_to_bytes(share: *const SecretKeyShare) -> TcBuffer {
+    catch(TcBuffer::null(), || match share.as_ref() {
+        Some(share) => {
+            let mut bytes = Vec::with_capacity(SK_SIZE);
+            let mut repr = ((share.0).0).into_repr();
+            repr.write_be(&mut bytes)
+                .expect("writing to a vector can't fail");
+            repr.0.zeroize();
+            TcBuffer::new(bytes)
+        }
+        None => TcBuffer::null(),
+    })
+}
+
+/// Releases a key share.
+///
+/// # Safety
+///
+/// `share` must be null or a key share handle that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_share_free(share: *mut SecretKeyShare) {
+    catch((), || {
+        if !share.is_null() {
+            drop(Box::from_raw(share));
+        }
+    })
+}
+
+/// Signs the message with the key share, and writes the signature share to `sig_out`.
+///
+/// # Safety
+///
+/// `share` must be a valid key share handle, `msg` must point to `msg_len` readable bytes and
+/// `sig_out` to `TC_SIG_SIZE` writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tc_secret_key_share_sign(
+    share: *const SecretKeyShare,
+    msg: *const u8,
+    msg_len: usize,
+    sig_out: *mut u8,
+) -> i32 {
+    catch(TC_ERR_PANIC, || {
+        match (share.as_ref(), input(msg, msg_len)) {
+            (Some(share), Some(msg)) if !sig_out.is_null() => {
+                output(&share.sign(msg).to_bytes(), sig_out);
+                TC_OK
+            }
+            _ => TC_ERR_NULL,
+        }
+    })
+}
+
+/// Decodes a public key set from its canonical encoding, or returns null if it is invalid.
+///
+/// # Safety
+///
+/// `bytes` must point to `len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tc_public_key_set_from_bytes(
+    bytes: *const u8,
+    len: usize,
+) -> *mut PublicKeySet {
+    catch(ptr::null_mut(), || {
+        match input(bytes, len).map(PublicKeySet::from_slice) {
+            Some(Ok(pk_set)) => Box::into_raw(Box::new(pk_set)),
+            _ => ptr::null_mut(),
+        }
+    })
+}
+
+/// Returns the public key set's canonical encoding, or a null buffer if `pk_set` is null.
+///
+/// # Safety
+///
+/// `pk_set` must be null or a valid public key set handle.
+#[no_mangle]
+pub unsafe extern "C" fn tc_public_key_set_to_bytes(pk_set: *const PublicKeySet) -> TcBuffer {
+    catch(TcBuffer::null(), || match pk_set.as_ref() {
+        Some(pk_set) => TcBuffer::new(pk_set.to_bytes()),
+        None => TcBuffer::null(),
+    })
+}
+
+/// Releases a public key set.
+///
+/// # Safety
+///
+/// `pk_set` must be null or a public key set handle that has not been released yet.
+#[no_mangle]
+pub unsafe extern "C" fn tc_public_key_set_free(pk_set: *mut PublicKeySet) {
+    catch((), || {
+        if !pk_set.is_null() {
+            drop(Box::from_raw(pk_set));
+        }
+    })
+}
+
+/// Returns `1` if the signature share is valid for share `i` and the message, and `0` if not.
+///
+/// # Safety
+///
+/// `pk_set` must be a valid public key set handle, `sig` must point to `TC_SIG_SIZE` readable
+/// bytes and `msg` to `msg_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tc_public_key_set_verify_share(
+    pk_set: *const PublicKeySet,
+    i: u64,
+    sig: *const u8,
+    msg: *const u8,
+    msg_len: usize,
+) -> i32 {
+    catch(TC_ERR_PANIC, || {
+        let (pk_set, sig, msg) = match (
+            pk_set.as_ref(),
+            input(sig, TC_SIG_SIZE),
+            input(msg, msg_len),
+        ) {
+            (Some(pk_set), Some(sig), Some(msg)) => (pk_set, sig, msg),
+            _ => return TC_ERR_NULL,
+        };
+        match SignatureShare::from_slice(sig) {
+            Ok(sig) => pk_set.public_key_share(i).verify(&sig, msg) as i32,
+            Err(_) => TC_ERR_INVALID,
+        }
+    })
+}
+
+/// Combines `count` signature shares into a signature, and writes it to `sig_out`.
+///
+/// `indices` contains the shares' indices, and `shares` the concatenated shares. The shares are
+/// not verified: Use `tc_public_key_set_verify_share` first if they could be invalid. Returns
+/// `TC_ERR_INVALID` if `count * TC_SIG_SIZE` doesn't fit in a slice.
+///
+/// # Safety
+///
+/// `pk_set` must be a valid public key set handle, `indices` must point to `count` readable
+/// indices, `shares` to `count * TC_SIG_SIZE` readable bytes and `sig_out` to `TC_SIG_SIZE`
+/// writable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tc_public_key_set_combine_signatures(
+    pk_set: *const PublicKeySet,
+    indices: *const u64,
+    shares: *const u8,
+    count: usize,
+    sig_out: *mut u8,
+) -> i32 {
+    catch(TC_ERR_PANIC, || {
+        let pk_set = match pk_set.as_ref() {
+            Some(pk_set) => pk_set,
+            None => return TC_ERR_NULL,
+        };
+        let shares_len = match count.checked_mul(TC_SIG_SIZE) {
+            Some(len) if len <= isize::MAX as usize => len,
+            _ => return TC_ERR_INVALID,
+        };
+        let (indices, shares) = match (input(indices, count), input(shares, shares_len)) {
+            (Some(indices), Some(shares)) if !sig_out.is_null() => (indices, shares),
+            _ => return TC_ERR_NULL,
+        };
+        let shares = match shares
+            .chunks(TC_SIG_SIZE)
+            .map(SignatureShare::from_slice)
+            .collect::<Result<Vec<_>, _>>()
+        {
+            Ok(shares) => shares,
+            Err(_) => return TC_ERR_INVALID,
+        };
+        match pk_set.combine_signatures(indices.iter().copied().zip(&shares)) {
+            Ok(sig) => {
+                output(&sig.to_bytes(), sig_out);
+                TC_OK
+            }
+            Err(_) => TC_ERR_COMBINE,
+        }
+    })
+}
+
+/// Returns `1` if the signature is valid for the master public key and the message, and `0` if
+/// not.
+///
+/// # Safety
+///
+/// `pk_set` must be a valid public key set handle, `sig` must point to `TC_SIG_SIZE` readable
+/// bytes and `msg` to `msg_len` readable bytes.
+#[no_mangle]
+pub unsafe extern "C" fn tc_public_key_set_verify(
+    pk_set: *const PublicKeySet,
+    sig: *const u8,
+    msg: *const u8,
+    msg_len: usize,
+) -> i32 {
+    catch(TC_ERR_PANIC, || {
+        let (pk_set, sig, msg) = match (
+            pk_set.as_ref(),
+            input(sig, TC_SIG_SIZE),
+            input(msg, msg_len),
+        ) {
+            (Some(pk_set), Some(sig), Some(msg)) => (pk_set, sig, msg),
+            _ => return TC_ERR_NULL,
+        };
+        match Signature::from_slice(sig) {
+            Ok(sig) => pk_set.public_key().verify(&sig, msg) as i32,
+            Err(_) => TC_ERR_INVALID,
+        }
+    })
+}
+
+/// Returns the slice of `len` values at `data`, or `None` if it is null. An empty slice may be
+/// null.
+unsafe fn input<'a, T>(data: *const T, len: usize) -> Option<&'a [T]> {
+    if len == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(data, len))
+    }
+}
+
+/// Runs `f`, and returns `default` if it panics, so that the panic doesn't unwind into the caller.
+fn catch<T, F: FnOnce() -> T>(default: T, f: F) -> T {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(default)
+}
+
+/// Copies the bytes to `out`.
+unsafe fn output(bytes: &[u8], out: *mut u8) {
+    ptr::copy_nonoverlapping(bytes.as_ptr(), out, bytes.len());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ffi() {
+        unsafe {
+            let sk_set = tc_secret_key_set_random(1);
+            let original = tc_secret_key_set_public_keys(sk_set);
+            let buf = tc_public_key_set_to_bytes(original);
+            let pk_set = tc_public_key_set_from_bytes(buf.data, buf.len);
+            assert_eq!(*original, *pk_set);
+            tc_buffer_free(buf);
+            tc_public_key_set_free(original);
+            assert!(tc_secret_key_set_random(usize::MAX).is_null());
+            assert!(tc_secret_key_set_random(usize::MAX / 2).is_null());
+
+            let msg = b"msg";
+            let mut shares = [0u8; 2 * TC_SIG_SIZE];
+            for (i, share_out) in shares.chunks_mut(TC_SIG_SIZE).enumerate() {
+                let share = tc_secret_key_set_share(sk_set, i as u64 + 3);
+                let buf = tc_secret_key_share_to_bytes(share);
+                assert_eq!(SK_SIZE, buf.len);
+                let decoded = tc_secret_key_share_from_bytes(buf.data, buf.len);
+                assert_eq!(*share, *decoded);
+                tc_buffer_free(buf);
+                tc_secret_key_share_free(decoded);
+                let result =
+                    tc_secret_key_share_sign(share, msg.as_ptr(), 3, share_out.as_mut_ptr());
+                assert_eq!(TC_OK, result);
+                tc_secret_key_share_free(share);
+            }
+            let sig_ptr = shares.as_ptr();
+            assert_eq!(
+                1,
+                tc_public_key_set_verify_share(pk_set, 3, sig_ptr, msg.as_ptr(), 3)
+            );
+            assert_eq!(
+                0,
+                tc_public_key_set_verify_share(pk_set, 4, sig_ptr, msg.as_ptr(), 3)
+            );
+
+            let mut sig = [0u8; TC_SIG_SIZE];
+            let indices = [3u64, 4];
+            let result = tc_public_key_set_combine_signatures(
+                pk_set,
+                indices.as_ptr(),
+                sig_ptr,
+                2,
+                sig.as_mut_ptr(),
+            );
+            assert_eq!(TC_OK, result);
+            assert_eq!(
+                1,
+                tc_public_key_set_verify(pk_set, sig.as_ptr(), msg.as_ptr(), 3)
+            );
+            assert_eq!(
+                0,
+                tc_public_key_set_verify(pk_set, sig.as_ptr(), msg.as_ptr(), 2)
+            );
+            let result = tc_public_key_set_combine_signatures(
+                pk_set,
+                indices.as_ptr(),
+                sig_ptr,
+                1,
+                sig.as_mut_ptr(),
+            );
+            assert_eq!(TC_ERR_COMBINE, result);
+            let result = tc_public_key_set_combine_signatures(
+                pk_set,
+                indices.as_ptr(),
+                sig_ptr,
+                usize::MAX / TC_SIG_SIZE + 1,
+                sig.as_mut_ptr(),
+            );
+            assert_eq!(TC_ERR_INVALID, result);
+            assert_eq!(
+                TC_ERR_NULL,
+                tc_public_key_set_verify(ptr::null(), sig.as_ptr(), msg.as_ptr(), 3)
+            );
+            assert!(tc_public_key_set_from_bytes(msg.as_ptr(), 3).is_null());
+
+            tc_public_key_set_free(pk_set);
+            tc_secret_key_set_free(sk_set);
+        }
+    }
+}
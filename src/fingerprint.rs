@@ -0,0 +1,135 @@
+//! Short, stable identifiers of public keys, key shares and key sets.
+//!
+//! A [`Fingerprint`] is the first 16 bytes of the SHA3-256 hash of a value's canonical `to_bytes`
+//! encoding, under a domain separator for its type. It is stable across nodes, platforms and
+//! versions of the crate, and the same point has different fingerprints as a `PublicKey` and as a
+//! `PublicKeyShare`. At 128 bits, finding two keys with the same fingerprint takes about `2^64`
+//! hashes.
+//!
+//! ```
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let pk_set = sk_set.public_keys();
+//! let fingerprint = pk_set.fingerprint();
+//! assert_eq!(32, fingerprint.to_string().len());
+//! assert_ne!(pk_set.public_key().fingerprint(), fingerprint);
+//! ```
+
+use std::fmt;
+
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+
+use crate::util::sha3_256;
+use crate::{PublicKey, PublicKeySet, PublicKeyShare};
+
+/// The length of a fingerprint in bytes.
+pub const FINGERPRINT_SIZE: usize = 16;
+
+/// The domain separator of public key fingerprints.
+const PUBLIC_KEY_DST: &[u8] = b"threshold_crypto fingerprint PublicKey";
+/// The domain separator of public key share fingerprints.
+const PUBLIC_KEY_SHARE_DST: &[u8] = b"threshold_crypto fingerprint PublicKeyShare";
+/// The domain separator of public key set fingerprints.
+const PUBLIC_KEY_SET_DST: &[u8] = b"threshold_crypto fingerprint PublicKeySet";
+
+/// A short hash that identifies a public key, key share or key set.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint([u8; FINGERPRINT_SIZE]);
+
+impl fmt::Debug for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Fingerprint({})", HexFmt(&self.0))
+    }
+}
+
+/// Formats the fingerprint as lowercase hexadecimal digits.
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", HexFmt(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for Fingerprint {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Fingerprint {
+    /// Returns the fingerprint with the given bytes.
+    pub fn from_bytes(bytes: [u8; FINGERPRINT_SIZE]) -> Self {
+        Fingerprint(bytes)
+    }
+
+    /// Returns the fingerprint's bytes.
+    pub fn to_bytes(&self) -> [u8; FINGERPRINT_SIZE] {
+        self.0
+    }
+}
+
+impl PublicKey {
+    /// Returns the key's fingerprint.
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint(PUBLIC_KEY_DST, &self.to_bytes())
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns the key share's fingerprint.
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint(PUBLIC_KEY_SHARE_DST, &self.to_bytes())
+    }
+}
+
+impl PublicKeySet {
+    /// Returns the key set's fingerprint. It depends on all coefficients, not only the master key.
+    pub fn fingerprint(&self) -> Fingerprint {
+        fingerprint(PUBLIC_KEY_SET_DST, &self.to_bytes())
+    }
+}
+
+/// Returns the truncated hash of the domain separator and encoding.
+fn fingerprint(dst: &[u8], bytes: &[u8]) -> Fingerprint {
+    let mut input = dst.to_vec();
+    input.extend(bytes);
+    let mut result = [0u8; FINGERPRINT_SIZE];
+    result.copy_from_slice(&sha3_256(&input)[..FINGERPRINT_SIZE]);
+    Fingerprint(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_fingerprints() {
+        let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let pk_set = sk_set.public_keys();
+        let pk_share = pk_set.public_key_share(0);
+        assert_eq!(pk_set.fingerprint(), pk_set.clone().fingerprint());
+        assert_ne!(pk_share.fingerprint(), pk_share.0.fingerprint());
+
+        // Mock keys are too small for collisions to be unlikely.
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        {
+            let mut rng = rand::thread_rng();
+            let other = SecretKeySet::from_secret_key(&sk_set.secret_key(), 2, &mut rng)
+                .expect("valid threshold");
+            assert_eq!(other.public_keys().public_key(), pk_set.public_key());
+            assert_ne!(other.public_keys().fingerprint(), pk_set.fingerprint());
+
+            // The identity's fingerprint is fixed.
+            let pk = crate::SecretKey::default().public_key();
+            let expected = "f385e09adfe7e0cf1b89495a71e2b048";
+            assert_eq!(expected, pk.fingerprint().to_string());
+        }
+
+        let pk = pk_set.public_key();
+        let fingerprint = Fingerprint::from_bytes(pk.fingerprint().to_bytes());
+        assert_eq!(pk.fingerprint(), fingerprint);
+    }
+}
@@ -0,0 +1,390 @@
+//! FROST threshold Schnorr signatures, using the same key sets as the BLS scheme.
+//!
+//! The participants' `SecretKeyShare`s and the committee's `PublicKeySet`, whether they were
+//! created by a dealer or in a distributed key generation, can also produce Schnorr signatures in
+//! `G1`, following the two-round FROST protocol:
+//!
+//! 1. Each signer calls [`commit`] and publishes the [`NonceCommitment`], keeping the
+//!    [`SigningNonces`] secret.
+//! 2. Once at least `threshold + 1` signers committed, the coordinator sends a
+//!    [`SigningPackage`] with the message and all commitments to them. Each of them answers with
+//!    a [`SignatureShare`] from [`sign`], which consumes the nonces, so they can't be reused,
+//!    and checks that the package contains their commitment.
+//! 3. The coordinator [combines](aggregate) the shares into a [`Signature`] that is valid for the
+//!    main public key.
+//!
+//! Unlike BLS signatures, these signatures are randomized and depend on the set of signers. They
+//! can be verified without pairings.
+//!
+//! Hashes to `Fr` use SHA3-256 with a domain separator per purpose, so the signatures are not
+//! compatible with other FROST implementations' ciphersuites.
+//!
+//! This module is only available with the `frost` feature.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::error::{Error, Result};
+use crate::secret::clear_fr;
+use crate::util::sha3_256;
+use crate::{
+    into_fr_plus_1, lagrange_coefficients, DebugDots, Fr, G1Affine, PublicKey, PublicKeySet,
+    SecretKeyShare, G1,
+};
+
+/// The domain separator for binding factors.
+const DST_BINDING: &[u8] = b"threshold_crypto FROST binding";
+/// The domain separator for challenges.
+const DST_CHALLENGE: &[u8] = b"threshold_crypto FROST challenge";
+
+/// A signer's secret nonces for one signing round.
+///
+/// The nonces must only be used once: `sign` consumes them, and they are zeroed when dropped.
+/// They also keep their public commitment, so `sign` can check that the package commits to them.
+pub struct SigningNonces {
+    hiding: Fr,
+    binding: Fr,
+    commitment: NonceCommitment,
+}
+
+impl SigningNonces {
+    /// Returns the public commitment to the nonces.
+    pub fn commitment(&self) -> NonceCommitment {
+        self.commitment
+    }
+}
+
+impl Zeroize for SigningNonces {
+    fn zeroize(&mut self) {
+        clear_fr(&mut self.hiding);
+        clear_fr(&mut self.binding);
+    }
+}
+
+impl Drop for SigningNonces {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl fmt::Debug for SigningNonces {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SigningNonces").field(&DebugDots).finish()
+    }
+}
+
+/// A signer's public commitment to its nonces.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NonceCommitment {
+    #[serde(with = "crate::serde_impl::projective")]
+    hiding: G1,
+    #[serde(with = "crate::serde_impl::projective")]
+    binding: G1,
+}
+
+/// Returns fresh nonces and the commitment to publish in the first round.
+pub fn commit<R: Rng>(rng: &mut R) -> (SigningNonces, NonceCommitment) {
+    let hiding = Fr::random(rng);
+    let binding = Fr::random(rng);
+    let commitment = NonceCommitment {
+        hiding: G1Affine::one().mul(hiding),
+        binding: G1Affine::one().mul(binding),
+    };
+    let nonces = SigningNonces {
+        hiding,
+        binding,
+        commitment,
+    };
+    (nonces, commitment)
+}
+
+/// The message and the participating signers' nonce commitments.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SigningPackage {
+    msg: Vec<u8>,
+    commitments: BTreeMap<usize, NonceCommitment>,
+}
+
+impl SigningPackage {
+    /// Creates a package for signing the message with the given signers' commitments, by share
+    /// index.
+    pub fn new<M: AsRef<[u8]>>(msg: M, commitments: BTreeMap<usize, NonceCommitment>) -> Self {
+        SigningPackage {
+            msg: msg.as_ref().to_vec(),
+            commitments,
+        }
+    }
+
+    /// Returns the message to be signed.
+    pub fn message(&self) -> &[u8] {
+        &self.msg
+    }
+
+    /// Returns the signers' nonce commitments, by share index.
+    pub fn commitments(&self) -> &BTreeMap<usize, NonceCommitment> {
+        &self.commitments
+    }
+
+    /// Returns an error if signer `index` didn't commit, or too few signers did.
+    fn check(&self, pk_set: &PublicKeySet, index: usize) -> Result<()> {
+        if !self.commitments.contains_key(&index) {
+            return Err(Error::UnknownSigner(index));
+        }
+        if self.commitments.len() <= pk_set.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        Ok(())
+    }
+
+    /// Returns the binding factors of all signers, and the group commitment `R`.
+    fn binding_factors(&self) -> (BTreeMap<usize, Fr>, G1) {
+        let mut encoded = DST_BINDING.to_vec();
+        encoded.extend(&(self.msg.len() as u64).to_be_bytes());
+        encoded.extend(&self.msg);
+        for (i, comm) in &self.commitments {
+            encoded.extend(&(*i as u64).to_be_bytes());
+            encoded.extend(comm.hiding.into_affine().into_compressed().as_ref());
+            encoded.extend(comm.binding.into_affine().into_compressed().as_ref());
+        }
+        let mut group_commitment = G1::zero();
+        let factors = self
+            .commitments
+            .iter()
+            .map(|(i, comm)| {
+                let mut input = encoded.clone();
+                input.extend(&(*i as u64).to_be_bytes());
+                let rho = hash_fr(&input);
+                let mut term = comm.binding;
+                term.mul_assign(rho);
+                term.add_assign(&comm.hiding);
+                group_commitment.add_assign(&term);
+                (*i, rho)
+            })
+            .collect();
+        (factors, group_commitment)
+    }
+
+    /// Returns the Lagrange coefficient of signer `i` for interpolating at `0`, or
+    /// `Error::DuplicateEntry` if two signers' indices are the same field element.
+    fn lagrange_coefficient(&self, i: usize) -> Result<Fr> {
+        let xs: Vec<Fr> = self
+            .commitments
+            .keys()
+            .map(|j| into_fr_plus_1(*j as u64))
+            .collect();
+        let pos = self.commitments.keys().position(|j| *j == i);
+        let pos = pos.ok_or(Error::UnknownSigner(i))?;
+        Ok(lagrange_coefficients(&xs)?[pos])
+    }
+}
+
+/// A signer's response in the second round.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct SignatureShare(#[serde(with = "crate::serde_impl::field_element")] Fr);
+
+impl fmt::Debug for SignatureShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SignatureShare").field(&self.0).finish()
+    }
+}
+
+/// A Schnorr signature `(R, z)`, valid if `z * g == R + c * pk`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Signature {
+    #[serde(with = "crate::serde_impl::projective")]
+    r: G1,
+    #[serde(with = "crate::serde_impl::field_element")]
+    z: Fr,
+}
+
+impl Signature {
+    /// Returns `true` if the signature is valid for the message under the public key.
+    pub fn verify<M: AsRef<[u8]>>(&self, pk: &PublicKey, msg: M) -> bool {
+        let c = challenge(&self.r, &pk.0, msg.as_ref());
+        let mut rhs = pk.0;
+        rhs.mul_assign(c);
+        rhs.add_assign(&self.r);
+        G1Affine::one().mul(self.z) == rhs
+    }
+}
+
+/// Computes signer `index`'s signature share in the second round.
+///
+/// Returns `Error::UnknownSigner` if the package contains no commitment by `index`,
+/// `Error::NonceMismatch` if its commitment by `index` is not the one to `nonces`, and
+/// `Error::NotEnoughShares` if it contains fewer than `threshold + 1`.
+pub fn sign(
+    package: &SigningPackage,
+    pk_set: &PublicKeySet,
+    index: usize,
+    sk_share: &SecretKeyShare,
+    nonces: SigningNonces,
+) -> Result<SignatureShare> {
+    package.check(pk_set, index)?;
+    if package.commitments[&index] != nonces.commitment {
+        return Err(Error::NonceMismatch(index));
+    }
+    let lambda = package.lagrange_coefficient(index)?;
+    let (factors, group_commitment) = package.binding_factors();
+    let c = challenge(&group_commitment, &pk_set.public_key().0, &package.msg);
+    // z = hiding + binding * rho + lambda * s * c
+    let mut z = (sk_share.0).0;
+    z.mul_assign(&lambda);
+    z.mul_assign(&c);
+    let mut binding = nonces.binding;
+    binding.mul_assign(&factors[&index]);
+    z.add_assign(&binding);
+    z.add_assign(&nonces.hiding);
+    clear_fr(&mut binding);
+    Ok(SignatureShare(z))
+}
+
+/// Returns `true` if signer `index`'s share is valid for the package.
+pub fn verify_share(
+    package: &SigningPackage,
+    pk_set: &PublicKeySet,
+    index: usize,
+    share: &SignatureShare,
+) -> bool {
+    let (comm, mut lambda_c) = match (
+        package.commitments.get(&index),
+        package.lagrange_coefficient(index),
+    ) {
+        (Some(comm), Ok(lambda)) => (comm, lambda),
+        _ => return false,
+    };
+    let (factors, group_commitment) = package.binding_factors();
+    let c = challenge(&group_commitment, &pk_set.public_key().0, &package.msg);
+    // z * g == hiding + rho * binding + lambda * c * pk_share
+    lambda_c.mul_assign(&c);
+    let mut rhs = (pk_set.public_key_share(index).0).0;
+    rhs.mul_assign(lambda_c);
+    let mut binding = comm.binding;
+    binding.mul_assign(factors[&index]);
+    rhs.add_assign(&binding);
+    rhs.add_assign(&comm.hiding);
+    G1Affine::one().mul(share.0) == rhs
+}
+
+/// Verifies the signature shares and combines them into a signature.
+///
+/// There must be a valid share by every signer in the package, otherwise this returns
+/// `Error::InvalidShare` with the index of the first missing or invalid one.
+pub fn aggregate(
+    package: &SigningPackage,
+    pk_set: &PublicKeySet,
+    shares: &BTreeMap<usize, SignatureShare>,
+) -> Result<Signature> {
+    if package.commitments.len() <= pk_set.threshold() {
+        return Err(Error::NotEnoughShares);
+    }
+    let mut z = Fr::zero();
+    for i in package.commitments.keys() {
+        match shares.get(i) {
+            Some(share) if verify_share(package, pk_set, *i, share) => z.add_assign(&share.0),
+            _ => return Err(Error::InvalidShare(*i)),
+        }
+    }
+    let (_, r) = package.binding_factors();
+    Ok(Signature { r, z })
+}
+
+/// Returns the challenge `c` for the group commitment, public key and message.
+fn challenge(r: &G1, pk: &G1, msg: &[u8]) -> Fr {
+    let mut input = DST_CHALLENGE.to_vec();
+    input.extend(r.into_affine().into_compressed().as_ref());
+    input.extend(pk.into_affine().into_compressed().as_ref());
+    input.extend(msg);
+    hash_fr(&input)
+}
+
+/// Returns a hash of the input in `Fr`.
+fn hash_fr(input: &[u8]) -> Fr {
+    Fr::random(&mut ChaChaRng::from_seed(sha3_256(input)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_frost() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let signers = [0, 2, 3];
+
+        let mut nonces = BTreeMap::new();
+        let mut commitments = BTreeMap::new();
+        for i in &signers {
+            let (nonce, commitment) = commit(&mut rng);
+            nonces.insert(*i, nonce);
+            commitments.insert(*i, commitment);
+        }
+        let package = SigningPackage::new("Hello", commitments);
+
+        let mut shares = BTreeMap::new();
+        for (i, nonce) in nonces {
+            let sk_share = sk_set.secret_key_share(i);
+            let share = sign(&package, &pk_set, i, &sk_share, nonce).expect("sign share");
+            assert!(verify_share(&package, &pk_set, i, &share));
+            shares.insert(i, share);
+        }
+        let sig = aggregate(&package, &pk_set, &shares).expect("aggregate shares");
+        assert!(sig.verify(&pk_set.public_key(), "Hello"));
+        assert!(!sig.verify(&pk_set.public_key(), "Goodbye"));
+
+        // A share computed for a different signer is detected.
+        let mut wrong = shares.clone();
+        wrong.insert(2, shares[&3]);
+        assert_eq!(
+            Err(Error::InvalidShare(2)),
+            aggregate(&package, &pk_set, &wrong)
+        );
+        wrong.remove(&2);
+        assert_eq!(
+            Err(Error::InvalidShare(2)),
+            aggregate(&package, &pk_set, &wrong)
+        );
+    }
+
+    #[test]
+    fn test_sign_errors() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let sk_share = sk_set.secret_key_share(0);
+        let (_, commitment) = commit(&mut rng);
+        let package = SigningPackage::new("Hello", Some((0, commitment)).into_iter().collect());
+        let (nonces, _) = commit(&mut rng);
+        let result = sign(&package, &pk_set, 1, &sk_share, nonces);
+        assert_eq!(Some(Error::UnknownSigner(1)), result.err());
+        let (nonces, _) = commit(&mut rng);
+        let result = sign(&package, &pk_set, 0, &sk_share, nonces);
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+
+        // Nonces that don't match the package's commitment are rejected.
+        let (nonces0, commitment0) = commit(&mut rng);
+        let (nonces1, commitment1) = commit(&mut rng);
+        assert_eq!(commitment0, nonces0.commitment());
+        let commitments = vec![(0, commitment0), (1, commitment1)]
+            .into_iter()
+            .collect();
+        let package = SigningPackage::new("Hello", commitments);
+        let result = sign(&package, &pk_set, 0, &sk_share, nonces1);
+        // Mock keys are too small for collisions to be unlikely.
+        if commitment0 != commitment1 {
+            assert_eq!(Some(Error::NonceMismatch(0)), result.err());
+        }
+        assert!(sign(&package, &pk_set, 0, &sk_share, nonces0).is_ok());
+    }
+}
@@ -0,0 +1,377 @@
+//! A gRPC service and client for the threshold signing coordinator, generated by `tonic` from
+//! `proto/coordinator.proto`.
+//!
+//! [`CoordinatorService`] serves the `Coordinator` service with a
+//! [`coordinator::Coordinator`](../coordinator/struct.Coordinator.html), and [`ParticipantService`]
+//! serves the `Participant` service with a
+//! [`coordinator::Participant`](../coordinator/struct.Participant.html) and its share backend. The
+//! generated [`CoordinatorClient`] and [`ParticipantClient`] call them. A signing round is:
+//!
+//! 1. `RequestSignature` on the coordinator, which returns a `ShareRequest`.
+//! 2. `RequestShare` with it on participants, until enough of them answered; each returns a
+//!    `ShareSubmission`.
+//! 3. `SubmitShare` with each submission on the coordinator, which returns the `SignatureStatus`.
+//!    Once it is complete, `FetchSignature` returns it, too.
+//!
+//! The messages convert from and to the ones in [`coordinator`](../coordinator/index.html) with
+//! `From` and `TryFrom`. Errors are returned as a `tonic::Status`: `NOT_FOUND` for an unknown
+//! request, `INVALID_ARGUMENT` for an invalid share or encoding, `PERMISSION_DENIED` for a banned
+//! participant, `RESOURCE_EXHAUSTED` for an exceeded limit and `INTERNAL` for a backend failure.
+//! The generated code is checked in, so building the crate doesn't need `protoc`.
+//!
+//! ```no_run
+//! use threshold_crypto::coordinator::Coordinator;
+//! use threshold_crypto::grpc::{CoordinatorServer, CoordinatorService};
+//! use threshold_crypto::SecretKeySet;
+//!
+//! # async fn serve() -> Result<(), Box<dyn std::error::Error>> {
+//! let pk_set = SecretKeySet::random(1, &mut rand::thread_rng()).public_keys();
+//! let service = CoordinatorService::new(Coordinator::new(pk_set));
+//! tonic::transport::Server::builder()
+//!     .add_service(CoordinatorServer::new(service))
+//!     .serve("127.0.0.1:50051".parse()?)
+//!     .await?;
+//! # Ok(())
+//! # }
+//! ```
+
+use std::convert::TryFrom;
+use std::sync::{Mutex, MutexGuard};
+
+use tonic::{Request, Response, Status};
+
+use crate::coordinator::{self, Coordinator, Participant};
+use crate::error::{Error, ProtoError, ProtoResult};
+use crate::signer::{IndexedShare, ShareSigner};
+use crate::{Signature, SignatureShare};
+
+#[allow(missing_docs)]
+#[rustfmt::skip]
+mod generated {
+    include!("grpc/threshold_crypto.coordinator.rs");
+}
+
+pub use self::generated::coordinator_client::{self, CoordinatorClient};
+pub use self::generated::coordinator_server::{self, CoordinatorServer};
+pub use self::generated::participant_client::{self, ParticipantClient};
+pub use self::generated::participant_server::{self, ParticipantServer};
+pub use self::generated::{
+    FetchSignatureRequest, ShareRequest, ShareSubmission, SignatureRequest, SignatureStatus,
+};
+
+/// Serves the `Coordinator` gRPC service with a `Coordinator`.
+#[derive(Debug)]
+pub struct CoordinatorService {
+    coordinator: Mutex<Coordinator>,
+}
+
+impl CoordinatorService {
+    /// Creates a service collecting shares with the given coordinator.
+    pub fn new(coordinator: Coordinator) -> Self {
+        CoordinatorService {
+            coordinator: Mutex::new(coordinator),
+        }
+    }
+
+    /// Returns the coordinator, e.g. to save its state or close requests.
+    pub fn coordinator(&self) -> MutexGuard<'_, Coordinator> {
+        self.coordinator.lock().expect("coordinator lock poisoned")
+    }
+
+    /// Returns the coordinator, consuming the service.
+    pub fn into_inner(self) -> Coordinator {
+        self.coordinator
+            .into_inner()
+            .expect("coordinator lock poisoned")
+    }
+}
+
+#[tonic::async_trait]
+impl coordinator_server::Coordinator for CoordinatorService {
+    async fn request_signature(
+        &self,
+        request: Request<SignatureRequest>,
+    ) -> Result<Response<ShareRequest>, Status> {
+        let msg = request.into_inner().message;
+        let share_request = self.coordinator().request_signature(msg);
+        Ok(Response::new(share_request.into()))
+    }
+
+    async fn submit_share(
+        &self,
+        request: Request<ShareSubmission>,
+    ) -> Result<Response<SignatureStatus>, Status> {
+        let submission = coordinator::ShareSubmission::try_from(request.into_inner())
+            .map_err(|err| Status::invalid_argument(err.to_string()))?;
+        let request_id = submission.request_id;
+        let status = self
+            .coordinator()
+            .submit_share(submission)
+            .map_err(status_from_error)?;
+        Ok(Response::new((request_id, status).into()))
+    }
+
+    async fn fetch_signature(
+        &self,
+        request: Request<FetchSignatureRequest>,
+    ) -> Result<Response<SignatureStatus>, Status> {
+        let request_id = request.into_inner().request_id;
+        let status = self
+            .coordinator()
+            .fetch_signature(request_id)
+            .map_err(status_from_error)?;
+        Ok(Response::new((request_id, status).into()))
+    }
+}
+
+/// Serves the `Participant` gRPC service with a `Participant`.
+#[derive(Debug)]
+pub struct ParticipantService<S> {
+    participant: Participant<S>,
+}
+
+impl<S: ShareSigner + IndexedShare> ParticipantService<S> {
+    /// Creates a service answering share requests with the given participant.
+    pub fn new(participant: Participant<S>) -> Self {
+        ParticipantService { participant }
+    }
+
+    /// Returns the participant.
+    pub fn participant(&self) -> &Participant<S> {
+        &self.participant
+    }
+}
+
+#[tonic::async_trait]
+impl<S> participant_server::Participant for ParticipantService<S>
+where
+    S: ShareSigner + IndexedShare + Send + Sync + 'static,
+{
+    async fn request_share(
+        &self,
+        request: Request<ShareRequest>,
+    ) -> Result<Response<ShareSubmission>, Status> {
+        let request = coordinator::ShareRequest::from(request.into_inner());
+        let submission = self
+            .participant
+            .handle_request(&request)
+            .map_err(status_from_error)?;
+        Ok(Response::new(submission.into()))
+    }
+}
+
+/// Returns the gRPC status for the error.
+fn status_from_error(err: Error) -> Status {
+    let msg = err.to_string();
+    match err {
+        Error::UnknownRequest(_) => Status::not_found(msg),
+        Error::Banned(_) => Status::permission_denied(msg),
+        Error::LimitExceeded(_) => Status::resource_exhausted(msg),
+        Error::Backend(_) => Status::internal(msg),
+        _ => Status::invalid_argument(msg),
+    }
+}
+
+impl From<coordinator::ShareRequest> for ShareRequest {
+    fn from(request: coordinator::ShareRequest) -> Self {
+        ShareRequest {
+            request_id: request.request_id,
+            message: request.message,
+        }
+    }
+}
+
+impl From<ShareRequest> for coordinator::ShareRequest {
+    fn from(request: ShareRequest) -> Self {
+        coordinator::ShareRequest {
+            request_id: request.request_id,
+            message: request.message,
+        }
+    }
+}
+
+impl From<coordinator::ShareSubmission> for ShareSubmission {
+    fn from(submission: coordinator::ShareSubmission) -> Self {
+        ShareSubmission {
+            request_id: submission.request_id,
+            index: submission.index as u64,
+            share: submission.share.to_bytes().to_vec(),
+        }
+    }
+}
+
+impl TryFrom<ShareSubmission> for coordinator::ShareSubmission {
+    type Error = ProtoError;
+
+    fn try_from(submission: ShareSubmission) -> ProtoResult<Self> {
+        Ok(coordinator::ShareSubmission {
+            request_id: submission.request_id,
+            index: usize_from(submission.index)?,
+            share: SignatureShare::from_slice(&submission.share)?,
+        })
+    }
+}
+
+/// Converts the status of the request with the given ID.
+impl From<(u64, coordinator::SignatureStatus)> for SignatureStatus {
+    fn from((request_id, status): (u64, coordinator::SignatureStatus)) -> Self {
+        match status {
+            coordinator::SignatureStatus::Pending { shares, needed } => SignatureStatus {
+                request_id,
+                shares: shares as u64,
+                needed: needed as u64,
+                signature: Vec::new(),
+            },
+            coordinator::SignatureStatus::Complete(sig) => SignatureStatus {
+                request_id,
+                shares: 0,
+                needed: 0,
+                signature: sig.to_bytes().to_vec(),
+            },
+        }
+    }
+}
+
+/// Converts the status, which is complete if it has a signature, and returns it with its request's
+/// ID.
+impl TryFrom<SignatureStatus> for (u64, coordinator::SignatureStatus) {
+    type Error = ProtoError;
+
+    fn try_from(status: SignatureStatus) -> ProtoResult<Self> {
+        let converted = if status.signature.is_empty() {
+            coordinator::SignatureStatus::Pending {
+                shares: usize_from(status.shares)?,
+                needed: usize_from(status.needed)?,
+            }
+        } else {
+            coordinator::SignatureStatus::Complete(Signature::from_slice(&status.signature)?)
+        };
+        Ok((status.request_id, converted))
+    }
+}
+
+/// Returns the number as a `usize`, or `ProtoError::OutOfRange` if it doesn't fit.
+fn usize_from(n: u64) -> ProtoResult<usize> {
+    usize::try_from(n).map_err(|_| ProtoError::OutOfRange(n))
+}
+
+#[cfg(test)]
+mod tests {
+    use tokio::net::TcpListener;
+    use tokio_stream::wrappers::TcpListenerStream;
+    use tonic::transport::{Channel, Server};
+    use tonic::Code;
+
+    use super::*;
+    use crate::signer::InMemoryShare;
+    use crate::SecretKeySet;
+
+    /// Serves the service on a local port and returns a channel to it.
+    async fn serve<S>(service: S) -> Channel
+    where
+        S: tonic::codegen::Service<
+                tonic::codegen::http::Request<tonic::body::BoxBody>,
+                Response = tonic::codegen::http::Response<tonic::body::BoxBody>,
+                Error = std::convert::Infallible,
+            > + tonic::server::NamedService
+            + Clone
+            + Send
+            + 'static,
+        S::Future: Send + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").await.expect("bind");
+        let addr = listener.local_addr().expect("local address");
+        tokio::spawn(
+            Server::builder()
+                .add_service(service)
+                .serve_with_incoming(TcpListenerStream::new(listener)),
+        );
+        Channel::from_shared(format!("http://{}", addr))
+            .expect("valid URI")
+            .connect()
+            .await
+            .expect("connect")
+    }
+
+    #[tokio::test]
+    async fn test_grpc_signing() {
+        let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+        let pk_set = sk_set.public_keys();
+        let service = CoordinatorService::new(Coordinator::new(pk_set.clone()));
+        let mut coordinator = CoordinatorClient::new(serve(CoordinatorServer::new(service)).await);
+        let mut participants = Vec::new();
+        for i in 0..3 {
+            let share = InMemoryShare::new(i, sk_set.secret_key_share(i));
+            let service = ParticipantService::new(Participant::new(share));
+            let channel = serve(ParticipantServer::new(service)).await;
+            participants.push(ParticipantClient::new(channel));
+        }
+
+        let msg = b"Signed over gRPC".to_vec();
+        let request = coordinator
+            .request_signature(SignatureRequest {
+                message: msg.clone(),
+            })
+            .await
+            .expect("request signature")
+            .into_inner();
+        assert_eq!(msg, request.message);
+
+        let mut submission = participants[0]
+            .request_share(request.clone())
+            .await
+            .expect("request share")
+            .into_inner();
+        let status = coordinator
+            .submit_share(submission.clone())
+            .await
+            .expect("submit share")
+            .into_inner();
+        assert_eq!((1, 2), (status.shares, status.needed));
+        assert!(status.signature.is_empty());
+
+        // Invalid shares and unknown requests are reported with the matching status code.
+        submission.index = 1;
+        let err = coordinator.submit_share(submission.clone()).await;
+        assert_eq!(
+            Code::InvalidArgument,
+            err.expect_err("invalid share").code()
+        );
+        submission.share = vec![0; 3];
+        let err = coordinator.submit_share(submission).await;
+        assert_eq!(
+            Code::InvalidArgument,
+            err.expect_err("invalid encoding").code()
+        );
+        let unknown = FetchSignatureRequest { request_id: 7 };
+        let err = coordinator.fetch_signature(unknown).await;
+        assert_eq!(Code::NotFound, err.expect_err("unknown request").code());
+
+        let submission = participants[2]
+            .request_share(request.clone())
+            .await
+            .expect("request share")
+            .into_inner();
+        let status = coordinator
+            .submit_share(submission)
+            .await
+            .expect("submit share")
+            .into_inner();
+        let fetched = coordinator
+            .fetch_signature(FetchSignatureRequest {
+                request_id: request.request_id,
+            })
+            .await
+            .expect("fetch signature")
+            .into_inner();
+        assert_eq!(status, fetched);
+        let (request_id, status) =
+            <(u64, coordinator::SignatureStatus)>::try_from(fetched).expect("valid status");
+        assert_eq!(request.request_id, request_id);
+        let sig = match status {
+            coordinator::SignatureStatus::Complete(sig) => sig,
+            status => panic!("incomplete signature: {:?}", status),
+        };
+        assert!(pk_set.public_key().verify(&sig, &msg));
+    }
+}
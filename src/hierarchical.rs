@@ -0,0 +1,349 @@
+//! Hierarchical threshold secret sharing, based on Birkhoff interpolation.
+//!
+//! In Shamir's scheme all participants are equal: any `threshold + 1` of them can reconstruct the
+//! secret. In a [`Hierarchy`], participants are assigned to _levels_, each with a cumulative
+//! threshold: a set of shares is authorized if, for every level `l`, it contains at least
+//! `thresholds[l]` shares of level `l` or above (where level `0` is the highest). E.g. with
+//! thresholds `[1, 4]`, at least one director and four people in total are needed: two directors
+//! and two managers, or one director and three managers, but no number of managers on their own.
+//!
+//! This is Tassa's scheme: The dealer picks a random polynomial `f` of degree
+//! `thresholds[last] - 1` with the secret as its constant term. A participant at level `0` gets
+//! `f(x)`, like in Shamir's scheme, and a participant at level `l > 0` gets the
+//! `thresholds[l - 1]`-th derivative of `f` at `x`. That derivative has too low a degree to
+//! reveal the secret, so lower levels can only contribute to the higher levels' shares.
+//!
+//! As in the rest of the crate, share `index` is evaluated at `x = index + 1`. Reconstruction
+//! solves a linear system which can be singular for some choices of indices. For committees of
+//! practical size, it is guaranteed not to be if the indices are assigned in order of level, i.e.
+//! each level's indices are lower than those of the levels below it.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::error::{Error, Result};
+use crate::into_fr::IntoFr;
+use crate::linalg;
+use crate::poly::{Commitment, Poly};
+use crate::secret::clear_fr;
+use crate::{into_fr_plus_1, DebugDots, Fr, G1Affine, PublicKey, SecretKey, G1};
+
+/// A hierarchical access structure: the levels' cumulative thresholds.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Hierarchy {
+    thresholds: Vec<usize>,
+}
+
+impl Hierarchy {
+    /// Creates a hierarchy with the given cumulative thresholds, from the highest level to the
+    /// lowest.
+    ///
+    /// Returns `Error::InvalidAccessStructure` unless there is at least one level, and the
+    /// thresholds are positive and strictly increasing.
+    pub fn new(thresholds: Vec<usize>) -> Result<Self> {
+        if thresholds.is_empty() || thresholds[0] == 0 {
+            let msg = "the first threshold must be positive".to_string();
+            return Err(Error::InvalidAccessStructure(msg));
+        }
+        if thresholds.windows(2).any(|w| w[0] >= w[1]) {
+            let msg = "the thresholds must be strictly increasing".to_string();
+            return Err(Error::InvalidAccessStructure(msg));
+        }
+        Ok(Hierarchy { thresholds })
+    }
+
+    /// Returns the number of levels.
+    pub fn levels(&self) -> usize {
+        self.thresholds.len()
+    }
+
+    /// Returns the minimum number of shares of the given level or above in an authorized set.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level doesn't exist.
+    pub fn threshold(&self, level: usize) -> usize {
+        self.thresholds[level]
+    }
+
+    /// Returns `true` if shares at the given levels are enough to reconstruct the secret.
+    ///
+    /// Levels that don't exist are ignored.
+    pub fn is_authorized<I: IntoIterator<Item = usize>>(&self, levels: I) -> bool {
+        let mut counts = vec![0; self.levels()];
+        for level in levels {
+            if let Some(count) = counts.get_mut(level) {
+                *count += 1;
+            }
+        }
+        let mut total = 0;
+        counts
+            .iter()
+            .zip(&self.thresholds)
+            .all(|(count, threshold)| {
+                total += count;
+                total >= *threshold
+            })
+    }
+
+    /// Reconstructs the secret key from the shares.
+    ///
+    /// Returns `Error::NotEnoughShares` if the shares are not authorized,
+    /// `Error::InvalidShare` if a share's level doesn't exist, `Error::DuplicateEntry` if two
+    /// shares have the same index, and `Error::InterpolationFailed` if the shares' indices don't
+    /// determine the secret. Invalid share values are not detected, and result in a wrong key:
+    /// Use [`HierarchicalShare::verify`] to check them first.
+    pub fn combine<B, I>(&self, shares: I) -> Result<SecretKey>
+    where
+        B: Borrow<HierarchicalShare>,
+        I: IntoIterator<Item = B>,
+    {
+        let shares: Vec<B> = shares.into_iter().collect();
+        let mut indices = BTreeSet::new();
+        for share in &shares {
+            let share = share.borrow();
+            if share.level >= self.levels() {
+                return Err(Error::InvalidShare(share.index));
+            }
+            if !indices.insert(share.index) {
+                return Err(Error::DuplicateEntry);
+            }
+        }
+        if !self.is_authorized(shares.iter().map(|share| share.borrow().level)) {
+            return Err(Error::NotEnoughShares);
+        }
+        let matrix = shares
+            .iter()
+            .map(|share| self.row(share.borrow().level, share.borrow().index))
+            .collect();
+        let values = shares.iter().map(|share| share.borrow().value).collect();
+        let mut coeffs = linalg::solve(matrix, values).ok_or(Error::InterpolationFailed)?;
+        let sk = SecretKey::from_mut(&mut coeffs[0]);
+        coeffs.iter_mut().for_each(clear_fr);
+        Ok(sk)
+    }
+
+    /// Returns the degree of the dealer's polynomial.
+    fn degree(&self) -> usize {
+        self.thresholds[self.levels() - 1] - 1
+    }
+
+    /// Returns the order of the derivative that the shares of the given level are values of.
+    fn order(&self, level: usize) -> usize {
+        match level {
+            0 => 0,
+            _ => self.thresholds[level - 1],
+        }
+    }
+
+    /// Returns the factors by which the polynomial's coefficients contribute to the share value.
+    ///
+    /// The `order`-th derivative of `X^t` is `t! / (t - order)! * X^(t - order)`.
+    fn row(&self, level: usize, index: usize) -> Vec<Fr> {
+        let order = self.order(level);
+        let x = into_fr_plus_1(index as u64);
+        let mut x_pow = Fr::one();
+        (0..=self.degree())
+            .map(|t| {
+                if t < order {
+                    return Fr::zero();
+                }
+                let mut factor = x_pow;
+                for j in 0..order {
+                    factor.mul_assign(&((t - j) as u64).into_fr());
+                }
+                x_pow.mul_assign(&x);
+                factor
+            })
+            .collect()
+    }
+}
+
+/// A dealer's secret for a hierarchical access structure.
+#[derive(Clone, PartialEq, Eq)]
+pub struct HierarchicalKeySet {
+    hierarchy: Hierarchy,
+    poly: Poly,
+}
+
+impl fmt::Debug for HierarchicalKeySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HierarchicalKeySet")
+            .field("hierarchy", &self.hierarchy)
+            .field("poly", &DebugDots)
+            .finish()
+    }
+}
+
+impl HierarchicalKeySet {
+    /// Creates a random secret for the hierarchy.
+    pub fn random<R: Rng>(hierarchy: Hierarchy, rng: &mut R) -> Self {
+        let poly = Poly::random(hierarchy.degree(), rng);
+        HierarchicalKeySet { hierarchy, poly }
+    }
+
+    /// Returns the access structure.
+    pub fn hierarchy(&self) -> &Hierarchy {
+        &self.hierarchy
+    }
+
+    /// Returns the shared secret key.
+    pub fn secret_key(&self) -> SecretKey {
+        let mut fr = self.poly.evaluate(0);
+        SecretKey::from_mut(&mut fr)
+    }
+
+    /// Returns the public key corresponding to the shared secret key.
+    pub fn public_key(&self) -> PublicKey {
+        self.secret_key().public_key()
+    }
+
+    /// Returns the public commitment, for verifying the shares.
+    pub fn commitment(&self) -> Commitment {
+        self.poly.commitment()
+    }
+
+    /// Returns the share with the given index for a participant at the given level.
+    ///
+    /// Returns `Error::InvalidAccessStructure` if the level doesn't exist.
+    pub fn share(&self, level: usize, index: usize) -> Result<HierarchicalShare> {
+        if level >= self.hierarchy.levels() {
+            let msg = format!("level {} doesn't exist", level);
+            return Err(Error::InvalidAccessStructure(msg));
+        }
+        let mut value = Fr::zero();
+        for (c, f) in self.poly.coeff.iter().zip(self.hierarchy.row(level, index)) {
+            let mut term = *c;
+            term.mul_assign(&f);
+            value.add_assign(&term);
+            clear_fr(&mut term);
+        }
+        Ok(HierarchicalShare {
+            level,
+            index,
+            value,
+        })
+    }
+}
+
+/// A participant's share of a hierarchical secret.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct HierarchicalShare {
+    level: usize,
+    index: usize,
+    #[serde(with = "crate::serde_impl::field_element")]
+    value: Fr,
+}
+
+impl Zeroize for HierarchicalShare {
+    fn zeroize(&mut self) {
+        clear_fr(&mut self.value);
+    }
+}
+
+impl Drop for HierarchicalShare {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl fmt::Debug for HierarchicalShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HierarchicalShare")
+            .field("level", &self.level)
+            .field("index", &self.index)
+            .field("value", &DebugDots)
+            .finish()
+    }
+}
+
+impl HierarchicalShare {
+    /// Returns the participant's level.
+    pub fn level(&self) -> usize {
+        self.level
+    }
+
+    /// Returns the share index.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `true` if the share matches the dealer's commitment.
+    pub fn verify(&self, hierarchy: &Hierarchy, commitment: &Commitment) -> bool {
+        if self.level >= hierarchy.levels() || commitment.degree() != hierarchy.degree() {
+            return false;
+        }
+        let mut expected = G1::zero();
+        for (c, f) in commitment
+            .coeff
+            .iter()
+            .zip(hierarchy.row(self.level, self.index))
+        {
+            let mut term = *c;
+            term.mul_assign(f);
+            expected.add_assign(&term);
+        }
+        G1Affine::one().mul(self.value) == expected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hierarchy() {
+        assert!(Hierarchy::new(vec![]).is_err());
+        assert!(Hierarchy::new(vec![0, 2]).is_err());
+        assert!(Hierarchy::new(vec![2, 2]).is_err());
+        let hierarchy = Hierarchy::new(vec![1, 4]).expect("valid hierarchy");
+        assert!(hierarchy.is_authorized(vec![0, 1, 1, 1]));
+        assert!(hierarchy.is_authorized(vec![0, 0, 0, 0]));
+        assert!(!hierarchy.is_authorized(vec![1, 1, 1, 1, 1]));
+        assert!(!hierarchy.is_authorized(vec![0, 0, 1]));
+    }
+
+    #[test]
+    fn test_combine() {
+        let mut rng = rand::thread_rng();
+        let hierarchy = Hierarchy::new(vec![1, 4]).expect("valid hierarchy");
+        let key_set = HierarchicalKeySet::random(hierarchy.clone(), &mut rng);
+        let commitment = key_set.commitment();
+        // Two directors with indices 0 and 1, and four managers with indices 2 to 5.
+        let director = |i| key_set.share(0, i).expect("level 0 exists");
+        let manager = |i| key_set.share(1, i).expect("level 1 exists");
+        let shares = [
+            director(0),
+            director(1),
+            manager(2),
+            manager(3),
+            manager(4),
+            manager(5),
+        ];
+        assert!(shares.iter().all(|s| s.verify(&hierarchy, &commitment)));
+
+        let expected = key_set.secret_key();
+        assert_eq!(key_set.public_key(), expected.public_key());
+        for subset in &[[0, 2, 3, 4], [1, 3, 4, 5], [0, 1, 2, 5], [0, 1, 2, 3]] {
+            let sk = hierarchy.combine(subset.iter().map(|i| &shares[*i]));
+            assert_eq!(Ok(&expected), sk.as_ref());
+        }
+        let managers = hierarchy.combine(&shares[2..]);
+        assert_eq!(Err(Error::NotEnoughShares), managers.map(|_| ()));
+        let duplicate = hierarchy.combine([&shares[0], &shares[0], &shares[2], &shares[3]]);
+        assert_eq!(Err(Error::DuplicateEntry), duplicate.map(|_| ()));
+
+        // A share claiming the wrong level doesn't verify.
+        let mut forged = manager(2);
+        forged.level = 0;
+        assert!(!forged.verify(&hierarchy, &commitment));
+        assert!(key_set.share(2, 0).is_err());
+    }
+}
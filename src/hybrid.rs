@@ -0,0 +1,164 @@
+//! Hybrid signatures, pairing a threshold signature with a post-quantum one.
+//!
+//! BLS signatures will not withstand a quantum computer. Attestations that must remain
+//! meaningful for decades can be made _hybrid_: a [`HybridSignature`] contains the committee's
+//! threshold signature and a post-quantum signature over the same message, and is only valid if
+//! both are. Until quantum computers exist, it is as strong as the threshold signature; after
+//! that, it still proves that the holder of the post-quantum key endorsed the message.
+//!
+//! The post-quantum scheme is pluggable: implement [`PostQuantumSigner`] and
+//! [`PostQuantumVerifier`] for e.g. a hash-based or lattice-based scheme. It signs
+//! [`pq_message`], the message prefixed with a domain separator, so that the post-quantum
+//! signature can't be used for a different protocol with the same key.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{PublicKey, Signature};
+
+/// The domain separator prefixed to messages before signing them with the post-quantum scheme.
+const DST: &[u8] = b"threshold_crypto hybrid signature";
+
+/// The signing half of a post-quantum signature scheme.
+pub trait PostQuantumSigner {
+    /// The signature type.
+    type Signature;
+
+    /// Signs the message.
+    fn sign(&self, msg: &[u8]) -> Self::Signature;
+}
+
+/// The verifying half of a post-quantum signature scheme, i.e. its public key.
+pub trait PostQuantumVerifier {
+    /// The signature type.
+    type Signature;
+
+    /// Returns `true` if the signature is valid for the message.
+    fn verify(&self, msg: &[u8], sig: &Self::Signature) -> bool;
+}
+
+/// A threshold signature and a post-quantum signature of the same message.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HybridSignature<S> {
+    bls: Signature,
+    pq: S,
+}
+
+impl<S> HybridSignature<S> {
+    /// Adds a post-quantum signature of the message to the threshold signature.
+    ///
+    /// The threshold signature is not verified.
+    pub fn new<P, M>(bls: Signature, signer: &P, msg: M) -> Self
+    where
+        P: PostQuantumSigner<Signature = S>,
+        M: AsRef<[u8]>,
+    {
+        let pq = signer.sign(&pq_message(msg));
+        HybridSignature { bls, pq }
+    }
+
+    /// Returns the threshold signature.
+    pub fn bls(&self) -> &Signature {
+        &self.bls
+    }
+
+    /// Returns the post-quantum signature.
+    pub fn pq(&self) -> &S {
+        &self.pq
+    }
+
+    /// Returns `true` if both signatures are valid for the message.
+    pub fn verify<V, M>(&self, pk: &PublicKey, pq_key: &V, msg: M) -> bool
+    where
+        V: PostQuantumVerifier<Signature = S>,
+        M: AsRef<[u8]>,
+    {
+        pk.verify(&self.bls, msg.as_ref()) && pq_key.verify(&pq_message(msg), &self.pq)
+    }
+}
+
+/// Returns the bytes the post-quantum scheme signs for the message.
+pub fn pq_message<M: AsRef<[u8]>>(msg: M) -> Vec<u8> {
+    let mut bytes = DST.to_vec();
+    bytes.extend(msg.as_ref());
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::Rng;
+
+    use crate::util::sha3_256;
+    use crate::SecretKeySet;
+
+    /// A Lamport one-time signature key, signing the message's SHA3-256 digest.
+    struct Lamport {
+        secret: Vec<[[u8; 32]; 2]>,
+    }
+
+    /// A Lamport public key: the hashes of the secret values.
+    struct LamportPublicKey(Vec<[[u8; 32]; 2]>);
+
+    impl Lamport {
+        fn public_key(&self) -> LamportPublicKey {
+            let hash = |pair: &[[u8; 32]; 2]| [sha3_256(&pair[0]), sha3_256(&pair[1])];
+            LamportPublicKey(self.secret.iter().map(hash).collect())
+        }
+    }
+
+    fn digest_bits(msg: &[u8]) -> impl Iterator<Item = usize> {
+        let digest = sha3_256(msg);
+        (0..256).map(move |i| ((digest[i / 8] >> (i % 8)) & 1) as usize)
+    }
+
+    impl PostQuantumSigner for Lamport {
+        type Signature = Vec<[u8; 32]>;
+
+        fn sign(&self, msg: &[u8]) -> Vec<[u8; 32]> {
+            let bits = digest_bits(msg);
+            self.secret
+                .iter()
+                .zip(bits)
+                .map(|(pair, b)| pair[b])
+                .collect()
+        }
+    }
+
+    impl PostQuantumVerifier for LamportPublicKey {
+        type Signature = Vec<[u8; 32]>;
+
+        fn verify(&self, msg: &[u8], sig: &Vec<[u8; 32]>) -> bool {
+            sig.len() == self.0.len()
+                && (self.0.iter().zip(sig).zip(digest_bits(msg)))
+                    .all(|((pair, value), b)| sha3_256(value) == pair[b])
+        }
+    }
+
+    #[test]
+    fn test_hybrid_signature() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk = sk_set.public_keys().public_key();
+        let lamport = Lamport {
+            secret: (0..256).map(|_| [rng.gen(), rng.gen()]).collect(),
+        };
+        let pq_key = lamport.public_key();
+
+        let msg = "attestation";
+        let bls = sk_set.secret_key().sign(msg);
+        let sig = HybridSignature::new(bls.clone(), &lamport, msg);
+        assert!(sig.verify(&pk, &pq_key, msg));
+        assert!(!sig.verify(&pk, &pq_key, "other attestation"));
+
+        // Both signatures are required.
+        let other_bls = sk_set.secret_key().sign("other attestation");
+        let mixed = HybridSignature::new(other_bls, &lamport, msg);
+        assert!(!mixed.verify(&pk, &pq_key, msg));
+        let undomained = HybridSignature {
+            bls,
+            pq: lamport.sign(msg.as_bytes()),
+        };
+        assert!(!undomained.verify(&pk, &pq_key, msg));
+    }
+}
@@ -0,0 +1,569 @@
+//! BLS signatures compatible with the IETF ciphersuites.
+//!
+//! The crate's own `hash_g2` seeds a random number generator with the message's SHA3-256 digest,
+//! so its signatures only verify with this crate. This module instead implements the
+//! `BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_` ciphersuites of draft-irtf-cfrg-bls-signature: Messages
+//! are hashed to `G2` as specified in RFC 9380, with `expand_message_xmd` using SHA-256, the
+//! simplified SWU map to a 3-isogenous curve, and cofactor clearing by `h_eff`.
+//!
+//! Keys and signatures are otherwise the same: Public keys are in `G1`, signatures in `G2`, and
+//! their `to_bytes` representations are the compressed encodings the draft uses. Threshold
+//! signatures work unchanged: Shares sign the [`Scheme::hash`] of the message, and combine to a
+//! standard signature for the master key.
+//!
+//! `SecretKey::from_seed` and `SecretKeySet::from_seed` derive keys from a seed with the draft's
+//! `KeyGen`.
+//!
+//! ```
+//! use threshold_crypto::ietf::Scheme;
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! let hash = Scheme::ProofOfPossession.hash(&pk_set.public_key(), "msg");
+//! let shares: Vec<_> = (0..2).map(|i| sk_set.secret_key_share(i).sign_g2(hash)).collect();
+//! let sig = pk_set.combine_signatures(shares.iter().enumerate()).unwrap();
+//! assert!(Scheme::ProofOfPossession.verify(&pk_set.public_key(), &sig, "msg"));
+//! ```
+
+use ff::{Field, PrimeField, PrimeFieldRepr, SqrtField};
+use group::{CurveAffine, CurveProjective, EncodedPoint};
+use pairing::bls12_381::{Fq, Fq2, FqRepr, G2Uncompressed};
+
+use crate::error::{Error, Result};
+use crate::eth2::{hkdf_mod_r, MIN_SEED_LEN};
+use crate::poly::Poly;
+use crate::util::digest::sha256;
+use crate::{PublicKey, SecretKey, SecretKeySet, Signature, G2};
+
+/// The domain separation tag of the basic scheme.
+pub const BASIC_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// The domain separation tag of the message augmentation scheme.
+pub const AUG_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_AUG_";
+
+/// The domain separation tag of signatures in the proof of possession scheme.
+pub const POP_DST: &[u8] = b"BLS_SIG_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The domain separation tag of proofs of possession.
+pub const POP_PROVE_DST: &[u8] = b"BLS_POP_BLS12381G2_XMD:SHA-256_SSWU_RO_POP_";
+
+/// The `key_info` prefix of a seeded key set's coefficients.
+const KEY_SET_INFO: &[u8] = b"threshold_crypto key set";
+
+/// A signature scheme of the IETF ciphersuites, which differ in how they prevent rogue-key
+/// attacks on aggregate signatures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Scheme {
+    /// Aggregates are only valid for distinct messages.
+    Basic,
+    /// Each message is prefixed with the signer's public key.
+    MessageAugmentation,
+    /// Aggregation requires a proof of possession of each key, see `pop_prove`.
+    ProofOfPossession,
+}
+
+impl Scheme {
+    /// Returns the scheme's domain separation tag.
+    pub fn dst(self) -> &'static [u8] {
+        match self {
+            Scheme::Basic => BASIC_DST,
+            Scheme::MessageAugmentation => AUG_DST,
+            Scheme::ProofOfPossession => POP_DST,
+        }
+    }
+
+    /// Returns the hash in `G2` that is signed to sign the message for the given public key.
+    ///
+    /// The key is only used by the message augmentation scheme. For threshold signatures, it is
+    /// the key set's master public key.
+    pub fn hash<M: AsRef<[u8]>>(self, pk: &PublicKey, msg: M) -> G2 {
+        match self {
+            Scheme::MessageAugmentation => {
+                let mut input = pk.to_bytes().to_vec();
+                input.extend(msg.as_ref());
+                hash_to_g2(input, AUG_DST)
+            }
+            _ => hash_to_g2(msg, self.dst()),
+        }
+    }
+
+    /// Signs the message.
+    pub fn sign<M: AsRef<[u8]>>(self, sk: &SecretKey, msg: M) -> Signature {
+        sk.sign_g2(self.hash(&sk.public_key(), msg))
+    }
+
+    /// Returns `true` if the signature matches the message.
+    pub fn verify<M: AsRef<[u8]>>(self, pk: &PublicKey, sig: &Signature, msg: M) -> bool {
+        pk.verify_g2(sig, self.hash(pk, msg))
+    }
+}
+
+/// Returns a proof of possession of the secret key, as in the proof of possession scheme.
+pub fn pop_prove(sk: &SecretKey) -> Signature {
+    sk.sign_g2(hash_to_g2(sk.public_key().to_bytes(), POP_PROVE_DST))
+}
+
+/// Returns `true` if the signature is a proof of possession of the public key's secret key.
+pub fn pop_verify(pk: &PublicKey, pop: &Signature) -> bool {
+    pk.verify_g2(pop, hash_to_g2(pk.to_bytes(), POP_PROVE_DST))
+}
+
+impl SecretKey {
+    /// Derives the key from the seed with the draft's `KeyGen`, which hashes the seed to a
+    /// nonzero scalar with HKDF-SHA256, and an empty `key_info`. This is the same key as
+    /// `eth2::derive_master_sk` and other compliant libraries derive from the seed.
+    ///
+    /// Returns `Error::InvalidKeyDerivation` if the seed is shorter than `eth2::MIN_SEED_LEN`.
+    pub fn from_seed(seed: &[u8]) -> Result<Self> {
+        check_seed_len(seed)?;
+        Ok(hkdf_mod_r(seed, &[]))
+    }
+}
+
+impl SecretKeySet {
+    /// Derives a key set with the given threshold from the seed. The master key is
+    /// `SecretKey::from_seed(seed)`, and coefficient `i > 0` is `KeyGen` of the seed with the
+    /// `key_info` `"threshold_crypto key set"`, followed by the threshold and `i` as big-endian
+    /// `u64`s. So key sets with different thresholds share only their master key.
+    ///
+    /// Returns `Error::InvalidKeyDerivation` if the seed is shorter than `eth2::MIN_SEED_LEN`, and
+    /// `Error::DegreeTooHigh` if the threshold is `usize::MAX`.
+    pub fn from_seed(threshold: usize, seed: &[u8]) -> Result<Self> {
+        check_seed_len(seed)?;
+        if threshold == usize::MAX {
+            return Err(Error::DegreeTooHigh);
+        }
+        let mut coeff = vec![hkdf_mod_r(seed, &[]).0];
+        for i in 1..=threshold as u64 {
+            let mut key_info = KEY_SET_INFO.to_vec();
+            key_info.extend(&(threshold as u64).to_be_bytes());
+            key_info.extend(&i.to_be_bytes());
+            coeff.push(hkdf_mod_r(seed, &key_info).0);
+        }
+        Ok(SecretKeySet::from(Poly::from(coeff)))
+    }
+}
+
+/// Returns an error if the seed is too short for `KeyGen`.
+fn check_seed_len(seed: &[u8]) -> Result<()> {
+    if seed.len() < MIN_SEED_LEN {
+        let msg = format!("the seed must have at least {} bytes", MIN_SEED_LEN);
+        return Err(Error::InvalidKeyDerivation(msg));
+    }
+    Ok(())
+}
+
+/// Returns the hash of the message in `G2`, with the `BLS12381G2_XMD:SHA-256_SSWU_RO_` suite of
+/// RFC 9380 and the given domain separation tag.
+///
+/// The tag must be at most 255 bytes long.
+pub fn hash_to_g2<M: AsRef<[u8]>>(msg: M, dst: &[u8]) -> G2 {
+    let _span = span!("hash_to_g2", len = msg.as_ref().len());
+    count!(HashToCurve, 1);
+    let bytes = expand_message_xmd(msg.as_ref(), dst);
+    let u0 = fq2_from_okm(&bytes[..128]);
+    let u1 = fq2_from_okm(&bytes[128..]);
+    let mut q = map_to_curve(u0);
+    q.add_assign(&map_to_curve(u1));
+    clear_cofactor(&q)
+}
+
+/// The length of the output of `expand_message_xmd`: two field elements in `Fq2`, with 64 bytes
+/// for each of their coordinates.
+const OKM_LEN: usize = 256;
+
+/// Expands the message to `OKM_LEN` uniform bytes, as in RFC 9380, section 5.3.1.
+fn expand_message_xmd(msg: &[u8], dst: &[u8]) -> Vec<u8> {
+    assert!(dst.len() <= 255, "domain separation tag too long");
+    let dst_len = [dst.len() as u8];
+    let b0 = sha256(&[
+        &[0; 64],
+        msg,
+        &(OKM_LEN as u16).to_be_bytes(),
+        &[0],
+        dst,
+        &dst_len,
+    ]);
+    let mut okm = Vec::with_capacity(OKM_LEN);
+    let mut b = sha256(&[&b0, &[1], dst, &dst_len]);
+    okm.extend(&b);
+    for i in 2..=(OKM_LEN / 32) as u8 {
+        let mut xor = b0;
+        for (x, y) in xor.iter_mut().zip(&b) {
+            *x ^= y;
+        }
+        b = sha256(&[&xor, &[i], dst, &dst_len]);
+        okm.extend(&b);
+    }
+    okm
+}
+
+/// Returns the element of `Fq2` whose coordinates are the two 64-byte big-endian integers,
+/// modulo `q`.
+fn fq2_from_okm(bytes: &[u8]) -> Fq2 {
+    Fq2 {
+        c0: fq_from_okm(&bytes[..64]),
+        c1: fq_from_okm(&bytes[64..]),
+    }
+}
+
+/// Returns the 64-byte big-endian integer modulo `q`.
+fn fq_from_okm(bytes: &[u8]) -> Fq {
+    // Each half is less than 2^256, and thus than `q`.
+    let half = |bytes: &[u8]| {
+        let mut repr = FqRepr::default();
+        let mut padded = [0u8; 48];
+        padded[16..].copy_from_slice(bytes);
+        repr.read_be(&padded[..]).expect("48 bytes");
+        Fq::from_repr(repr).expect("less than the modulus")
+    };
+    let mut two_256 = FqRepr::default();
+    two_256.as_mut()[4] = 1;
+    let mut result = half(&bytes[..32]);
+    result.mul_assign(&Fq::from_repr(two_256).expect("less than the modulus"));
+    result.add_assign(&half(&bytes[32..]));
+    result
+}
+
+/// Returns the field element with the given hexadecimal big-endian representation.
+fn fq(hex: &str) -> Fq {
+    let mut repr = FqRepr::default();
+    for digit in hex.chars() {
+        repr.shl(4);
+        repr.as_mut()[0] |= u64::from(digit.to_digit(16).expect("hexadecimal constant"));
+    }
+    Fq::from_repr(repr).expect("constant less than the modulus")
+}
+
+/// Returns `c0 + c1 * i` with the given hexadecimal coordinates.
+fn fq2(c0: &str, c1: &str) -> Fq2 {
+    Fq2 {
+        c0: fq(c0),
+        c1: fq(c1),
+    }
+}
+
+/// Returns `-q` for the element `q` of `Fq2`.
+fn neg(mut q: Fq2) -> Fq2 {
+    q.negate();
+    q
+}
+
+/// Returns `true` if the element's sign is negative, as defined by `sgn0` in RFC 9380.
+fn sgn0(x: &Fq2) -> bool {
+    let c0 = x.c0.into_repr();
+    c0.is_odd() || (c0.is_zero() && x.c1.into_repr().is_odd())
+}
+
+/// Returns `x^3 + a * x + b` for the isogenous curve `y^2 = x^3 + 240i * x + 1012(1 + i)`.
+fn iso_curve_rhs(x: &Fq2) -> Fq2 {
+    let mut rhs = *x;
+    rhs.square();
+    rhs.add_assign(&fq2("00", "f0"));
+    rhs.mul_assign(x);
+    rhs.add_assign(&fq2("03f4", "03f4"));
+    rhs
+}
+
+/// Maps the field element to a point in `G2`'s curve, not necessarily in the subgroup.
+///
+/// This is the simplified SWU map to the isogenous curve, RFC 9380, section 6.6.2, followed by
+/// the 3-isogeny of appendix E.3.
+fn map_to_curve(u: Fq2) -> G2 {
+    let z = neg(fq2("02", "01"));
+    let (a, b) = (fq2("00", "f0"), fq2("03f4", "03f4"));
+    let mut u2 = u;
+    u2.square();
+    let mut z_u2 = z;
+    z_u2.mul_assign(&u2);
+    // tv1 = 1 / (z^2 * u^4 + z * u^2), or 0 if that is 0.
+    let mut tv1 = z_u2;
+    tv1.square();
+    tv1.add_assign(&z_u2);
+    let tv1 = tv1.inverse();
+    let mut x1 = b;
+    match tv1 {
+        Some(tv1) => {
+            // x1 = (-b / a) * (1 + tv1)
+            let mut one_plus = tv1;
+            one_plus.add_assign(&Fq2::one());
+            x1.negate();
+            x1.mul_assign(&a.inverse().expect("nonzero"));
+            x1.mul_assign(&one_plus);
+        }
+        None => {
+            // x1 = b / (z * a)
+            let mut z_a = z;
+            z_a.mul_assign(&a);
+            x1.mul_assign(&z_a.inverse().expect("nonzero"));
+        }
+    }
+    let (x, mut y) = match iso_curve_rhs(&x1).sqrt() {
+        Some(y) => (x1, y),
+        None => {
+            let mut x2 = z_u2;
+            x2.mul_assign(&x1);
+            let y = iso_curve_rhs(&x2)
+                .sqrt()
+                .expect("one of gx1, gx2 is square");
+            (x2, y)
+        }
+    };
+    if sgn0(&u) != sgn0(&y) {
+        y.negate();
+    }
+    iso_map(&x, &y)
+}
+
+/// The coefficients of the 3-isogeny's `x` numerator, RFC 9380, appendix E.3.
+const X_NUM: [(&str, &str); 4] = [
+    (
+        "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6",
+        "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97d6",
+    ),
+    (
+        "00",
+        "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71a",
+    ),
+    (
+        "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71e",
+        "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38d",
+    ),
+    (
+        "171d6541fa38ccfaed6dea691f5fb614cb14b4e7f4e810aa22d6108f142b85757098e38d0f671c7188e2aaaaaaaa5ed1",
+        "00",
+    ),
+];
+
+/// The coefficients of the 3-isogeny's `x` denominator, except the leading one.
+const X_DEN: [(&str, &str); 2] = [
+    (
+        "00",
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa63",
+    ),
+    (
+        "0c",
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa9f",
+    ),
+];
+
+/// The coefficients of the 3-isogeny's `y` numerator.
+const Y_NUM: [(&str, &str); 4] = [
+    (
+        "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706",
+        "1530477c7ab4113b59a4c18b076d11930f7da5d4a07f649bf54439d87d27e500fc8c25ebf8c92f6812cfc71c71c6d706",
+    ),
+    (
+        "00",
+        "5c759507e8e333ebb5b7a9a47d7ed8532c52d39fd3a042a88b58423c50ae15d5c2638e343d9c71c6238aaaaaaaa97be",
+    ),
+    (
+        "11560bf17baa99bc32126fced787c88f984f87adf7ae0c7f9a208c6b4f20a4181472aaa9cb8d555526a9ffffffffc71c",
+        "8ab05f8bdd54cde190937e76bc3e447cc27c3d6fbd7063fcd104635a790520c0a395554e5c6aaaa9354ffffffffe38f",
+    ),
+    (
+        "124c9ad43b6cf79bfbf7043de3811ad0761b0f37a1e26286b0e977c69aa274524e79097a56dc4bd9e1b371c71c718b10",
+        "00",
+    ),
+];
+
+/// The coefficients of the 3-isogeny's `y` denominator, except the leading one.
+const Y_DEN: [(&str, &str); 3] = [
+    (
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb",
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa8fb",
+    ),
+    (
+        "00",
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffa9d3",
+    ),
+    (
+        "12",
+        "1a0111ea397fe69a4b1ba7b6434bacd764774b84f38512bf6730d2a0f6b0f6241eabfffeb153ffffb9feffffffffaa99",
+    ),
+];
+
+/// Evaluates the polynomial with the given coefficients, in ascending order, and a leading one if
+/// `monic`, at `x`.
+fn evaluate(coeffs: &[(&str, &str)], monic: bool, x: &Fq2) -> Fq2 {
+    let mut result = if monic { Fq2::one() } else { Fq2::zero() };
+    for (c0, c1) in coeffs.iter().rev() {
+        result.mul_assign(x);
+        result.add_assign(&fq2(c0, c1));
+    }
+    result
+}
+
+/// Maps a point of the isogenous curve to `G2`'s curve.
+fn iso_map(x: &Fq2, y: &Fq2) -> G2 {
+    let x_den = evaluate(&X_DEN, true, x);
+    let y_den = evaluate(&Y_DEN, true, x);
+    let (x_den, y_den) = match (x_den.inverse(), y_den.inverse()) {
+        (Some(x_den), Some(y_den)) => (x_den, y_den),
+        // The kernel of the isogeny maps to the point at infinity.
+        _ => return G2::zero(),
+    };
+    let mut x_out = evaluate(&X_NUM, false, x);
+    x_out.mul_assign(&x_den);
+    let mut y_out = evaluate(&Y_NUM, false, x);
+    y_out.mul_assign(&y_den);
+    y_out.mul_assign(y);
+    let mut encoded = G2Uncompressed::empty();
+    {
+        let mut writer = encoded.as_mut();
+        for coord in &[x_out.c1, x_out.c0, y_out.c1, y_out.c0] {
+            coord.into_repr().write_be(&mut writer).expect("192 bytes");
+        }
+    }
+    encoded
+        .into_affine_unchecked()
+        .expect("isogeny maps to the curve")
+        .into_projective()
+}
+
+/// The effective cofactor `h_eff` of `G2`, RFC 9380, section 8.8.2, in big-endian 64-bit limbs.
+const H_EFF: [u64; 10] = [
+    0x0bc6_9f08_f2ee_75b3,
+    0x584c_6a0e_a91b_3528,
+    0x88e2_a8e9_145a_d768,
+    0x9986_ff03_1508_ffe1,
+    0x329c_2f17_8731_db95,
+    0x6d82_bf01_5d12_12b0,
+    0x2ec0_ec69_d747_7c1a,
+    0xe954_cbc0_6689_f6a3,
+    0x5989_4c0a_debb_f6b4,
+    0xe802_0005_aaa9_5551,
+];
+
+/// Multiplies the point by `h_eff`, which maps every point of the curve to the subgroup `G2`.
+fn clear_cofactor(p: &G2) -> G2 {
+    // `mul_assign` takes a scalar modulo the group order, so it can't be used for points outside
+    // the subgroup.
+    let mut result = G2::zero();
+    for limb in &H_EFF {
+        for bit in (0..64).rev() {
+            result.double();
+            if (limb >> bit) & 1 == 1 {
+                result.add_assign(p);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The domain separation tag of the RFC 9380 test vectors.
+    const TEST_DST: &[u8] = b"QUUX-V01-CS02-with-BLS12381G2_XMD:SHA-256_SSWU_RO_";
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn test_sha256() {
+        assert_eq!(
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+            hex(&sha256(&[b"a", b"bc"]))
+        );
+        let data: Vec<u8> = (0..1024).map(|i| i as u8).collect();
+        assert_eq!(
+            "785b0751fc2c53dc14a4ce3d800e69ef9ce1009eb327ccf458afe09c242c26c9",
+            hex(&sha256(&[&data]))
+        );
+    }
+
+    #[test]
+    fn test_hash_to_g2() {
+        // RFC 9380, appendix J.10.1.
+        let bytes = expand_message_xmd(b"", TEST_DST);
+        let u0 = fq2_from_okm(&bytes[..128]);
+        assert_eq!(
+            fq2(
+                "03dbc2cce174e91ba93cbb08f26b917f98194a2ea08d1cce75b2b9cc9f21689d80bd79b594a613d0a68eb807dfdc1cf8",
+                "05a2acec64114845711a54199ea339abd125ba38253b70a92c876df10598bd1986b739cad67961eb94f7076511b3b39a",
+            ),
+            u0
+        );
+        let vectors: [(&[u8], &str, &str, &str, &str); 2] = [
+            (
+                b"",
+                "0141ebfbdca40eb85b87142e130ab689c673cf60f1a3e98d69335266f30d9b8d4ac44c1038e9dcdd5393faf5c41fb78a",
+                "05cb8437535e20ecffaef7752baddf98034139c38452458baeefab379ba13dff5bf5dd71b72418717047f5b0f37da03d",
+                "0503921d7f6a12805e72940b963c0cf3471c7b2a524950ca195d11062ee75ec076daf2d4bc358c4b190c0c98064fdd92",
+                "12424ac32561493f3fe3c260708a12b7c620e7be00099a974e259ddc7d1f6395c3c811cdd19f1e8dbf3e9ecfdcbab8d6",
+            ),
+            (
+                b"abc",
+                "02c2d18e033b960562aae3cab37a27ce00d80ccd5ba4b7fe0e7a210245129dbec7780ccc7954725f4168aff2787776e6",
+                "139cddbccdc5e91b9623efd38c49f81a6f83f175e80b06fc374de9eb4b41dfe4ca3a230ed250fbe3a2acf73a41177fd8",
+                "1787327b68159716a37440985269cf584bcb1e621d3a7202be6ea05c4cfe244aeb197642555a0645fb87bf7466b2ba48",
+                "00aa65dae3c8d732d10ecd2c50f8a1baf3001578f71c694e03866e9f3d49ac1e1ce70dd94a733534f106d4cec0eddd16",
+            ),
+        ];
+        for (msg, x0, x1, y0, y1) in &vectors {
+            // The uncompressed encoding contains `x.c1`, `x.c0`, `y.c1` and `y.c0`.
+            let uncompressed = hash_to_g2(msg, TEST_DST).into_affine().into_uncompressed();
+            assert_eq!([*x1, x0, y1, y0].concat(), hex(uncompressed.as_ref()));
+        }
+    }
+
+    #[test]
+    fn test_schemes() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let pk = pk_set.public_key();
+        let schemes = [
+            Scheme::Basic,
+            Scheme::MessageAugmentation,
+            Scheme::ProofOfPossession,
+        ];
+        for scheme in &schemes {
+            let hash = scheme.hash(&pk, "msg");
+            let shares: Vec<_> = (0..3)
+                .map(|i| sk_set.secret_key_share(i).sign_g2(hash))
+                .collect();
+            let sig = pk_set
+                .combine_signatures(shares.iter().enumerate())
+                .expect("enough shares");
+            assert!(scheme.verify(&pk, &sig, "msg"));
+            assert!(!scheme.verify(&pk, &sig, "other msg"));
+            assert!(!pk.verify(&sig, "msg"));
+            for other in schemes.iter().filter(|other| *other != scheme) {
+                assert!(!other.verify(&pk, &sig, "msg"));
+            }
+        }
+
+        let sk = SecretKey::random();
+        let sig = Scheme::MessageAugmentation.sign(&sk, "msg");
+        assert!(Scheme::MessageAugmentation.verify(&sk.public_key(), &sig, "msg"));
+        let pop = pop_prove(&sk);
+        assert!(pop_verify(&sk.public_key(), &pop));
+        assert!(!pop_verify(&pk, &pop));
+        assert!(!pop_verify(
+            &sk.public_key(),
+            &Scheme::ProofOfPossession.sign(&sk, "msg")
+        ));
+    }
+
+    #[test]
+    fn test_from_seed() {
+        let seed = [3u8; MIN_SEED_LEN];
+        let sk = SecretKey::from_seed(&seed).expect("long enough seed");
+        assert_eq!(Ok(sk.clone()), crate::eth2::derive_master_sk(&seed));
+        let sk_set = SecretKeySet::from_seed(2, &seed).expect("long enough seed");
+        assert_eq!(2, sk_set.threshold());
+        assert_eq!(sk, sk_set.secret_key());
+        assert_eq!(Ok(sk_set.clone()), SecretKeySet::from_seed(2, &seed));
+        let other = SecretKeySet::from_seed(3, &seed).expect("long enough seed");
+        assert_eq!(sk, other.secret_key());
+        assert_ne!(sk_set.secret_key_share(0), other.secret_key_share(0));
+        assert!(SecretKey::from_seed(&seed[1..]).is_err());
+        assert!(SecretKeySet::from_seed(2, &seed[1..]).is_err());
+    }
+}
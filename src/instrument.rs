@@ -0,0 +1,169 @@
+//! Tracing spans and operation counters around expensive operations.
+//!
+//! With the `instrument` feature, operations are wrapped in `info`-level
+//! [`tracing`](https://docs.rs/tracing) spans named after them, whose fields hold public metadata
+//! such as the threshold and the number of shares. Subscribers can time them, e.g. with
+//! `tracing_subscriber`'s `FmtSpan::CLOSE`. Secret keys, shares and plaintexts are never recorded.
+//! Without the feature, spans are empty and their fields are never evaluated.
+//!
+//! With the `metrics` feature, `count!` reports operations to the
+//! [`metrics`](../metrics/index.html) recorder. Without it, the count is never evaluated.
+
+/// Enters an `info` span with the given name and `key = value` fields, e.g.
+/// `span!("combine_many", threshold = t)`. The span is exited when the guard is dropped.
+macro_rules! span {
+    ($name:literal $(, $key:ident = $value:expr)* $(,)?) => {{
+        #[cfg(feature = "instrument")]
+        let guard = tracing::info_span!($name $(, $key = $value)*).entered();
+        #[cfg(not(feature = "instrument"))]
+        let guard = $crate::instrument::NoSpan;
+        guard
+    }};
+}
+
+/// Records the number of shares in the current span, if it has a `shares` field.
+macro_rules! record_shares {
+    ($n:expr) => {
+        #[cfg(feature = "instrument")]
+        tracing::Span::current().record("shares", $n);
+    };
+}
+
+/// Reports that the [`Operation`](../metrics/enum.Operation.html) has been performed `n` times.
+macro_rules! count {
+    ($op:ident, $n:expr) => {
+        #[cfg(feature = "metrics")]
+        $crate::metrics::record($crate::metrics::Operation::$op, ($n) as u64);
+    };
+}
+
+/// The guard of a span without the `instrument` feature, which does nothing.
+#[cfg(not(feature = "instrument"))]
+pub(crate) struct NoSpan;
+
+#[cfg(all(test, feature = "instrument"))]
+mod tests {
+    use std::fmt::{Debug, Write};
+    use std::sync::{Arc, Mutex};
+
+    use tracing::field::{Field, Visit};
+    use tracing::span::{Attributes, Id, Record};
+    use tracing::{Event, Metadata, Subscriber};
+    use tracing_core::span::Current;
+
+    use crate::SecretKeySet;
+
+    /// A subscriber that records the names and fields of all spans.
+    #[derive(Clone, Default)]
+    struct Recorder {
+        spans: Arc<Mutex<Vec<(&'static Metadata<'static>, String)>>>,
+        stack: Arc<Mutex<Vec<Id>>>,
+    }
+
+    impl Recorder {
+        fn spans(&self) -> Vec<String> {
+            let spans = self.spans.lock().unwrap();
+            let entries = spans
+                .iter()
+                .map(|(meta, fields)| meta.name().to_owned() + fields);
+            entries.collect()
+        }
+    }
+
+    struct Fields<'a>(&'a mut String);
+
+    impl Visit for Fields<'_> {
+        fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+            write!(self.0, " {}={:?}", field.name(), value).unwrap();
+        }
+    }
+
+    impl Subscriber for Recorder {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &Attributes<'_>) -> Id {
+            let mut fields = String::new();
+            span.record(&mut Fields(&mut fields));
+            let mut spans = self.spans.lock().unwrap();
+            spans.push((span.metadata(), fields));
+            Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, span: &Id, values: &Record<'_>) {
+            let mut spans = self.spans.lock().unwrap();
+            values.record(&mut Fields(&mut spans[span.into_u64() as usize - 1].1));
+        }
+
+        fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+        fn event(&self, _: &Event<'_>) {}
+
+        fn enter(&self, span: &Id) {
+            self.stack.lock().unwrap().push(span.clone());
+        }
+
+        fn exit(&self, _: &Id) {
+            self.stack.lock().unwrap().pop();
+        }
+
+        fn current_span(&self) -> Current {
+            match self.stack.lock().unwrap().last() {
+                Some(id) => {
+                    let meta = self.spans.lock().unwrap()[id.into_u64() as usize - 1].0;
+                    Current::new(id.clone(), meta)
+                }
+                None => Current::none(),
+            }
+        }
+    }
+
+    #[test]
+    fn test_span() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut rng = rand::thread_rng();
+            let sk_set = SecretKeySet::random(1, &mut rng);
+            let pk_set = sk_set.public_keys();
+            let shares: Vec<_> = (0..3)
+                .map(|i| sk_set.secret_key_share(i).sign("Hello"))
+                .collect();
+            let sig = pk_set
+                .combine_signatures(shares.iter().enumerate())
+                .expect("enough shares");
+            assert!(pk_set.public_key().verify(&sig, "Hello"));
+        });
+        let spans = recorder.spans();
+        assert!(spans.contains(&"hash_g2 len=5".to_string()));
+        assert!(spans.contains(&"combine_signatures threshold=1 shares=2".to_string()));
+        assert!(spans.contains(&"interpolate threshold=1 shares=2".to_string()));
+        assert!(spans.contains(&"verify".to_string()));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_secret_values_not_recorded() {
+        let recorder = Recorder::default();
+        tracing::subscriber::with_default(recorder.clone(), || {
+            let mut rng = rand::thread_rng();
+            let sk_set = SecretKeySet::random(1, &mut rng);
+            let pk_set = sk_set.public_keys();
+            let ct = pk_set
+                .public_key()
+                .encrypt_with_rng(&mut rng, b"secret message");
+            let shares: Vec<_> = (0..2)
+                .map(|i| {
+                    let share = sk_set.secret_key_share(i).decrypt_share(&ct);
+                    share.expect("valid ciphertext")
+                })
+                .collect();
+            let msg = pk_set.decrypt(shares.iter().enumerate(), &ct);
+            assert_eq!(b"secret message", &msg.expect("enough shares")[..]);
+        });
+        let spans = recorder.spans();
+        assert!(spans.contains(&"decrypt_share".to_string()));
+        assert!(spans.contains(&"combine_decryption_shares threshold=1 shares=2".to_string()));
+        assert!(spans.iter().all(|span| !span.contains("secret")));
+    }
+}
@@ -1,4 +1,5 @@
 use super::Fr;
+use crate::error::{Error, Result};
 use ff::{Field, PrimeField};
 
 /// A conversion into an element of the field `Fr`.
@@ -49,8 +50,42 @@ impl IntoFr for i64 {
     }
 }
 
-impl<'a, T: IntoFr> IntoFr for &'a T {
+impl<T: IntoFr> IntoFr for &T {
     fn into_fr(self) -> Fr {
         (*self).into_fr()
     }
 }
+
+/// A nonzero field element at which a key share is evaluated.
+///
+/// The methods taking a share index `i` evaluate the key set's polynomial at `i + 1`, so that the
+/// first share has index `0` and the master key, at `0`, can't be requested. An
+/// `EvaluationPoint` specifies the point `x` itself instead, e.g. one derived from a hash or a
+/// large participant ID. It converts to the index `x - 1`, so it can be passed to
+/// `secret_key_share`, `public_key_share`, `combine_signatures` and all other methods taking an
+/// index.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvaluationPoint(Fr);
+
+impl EvaluationPoint {
+    /// Returns the point `x`, or `Error::ZeroEvaluationPoint` if it is zero.
+    pub fn new(x: Fr) -> Result<Self> {
+        if x.is_zero() {
+            return Err(Error::ZeroEvaluationPoint);
+        }
+        Ok(EvaluationPoint(x))
+    }
+
+    /// Returns the point `x`.
+    pub fn value(&self) -> Fr {
+        self.0
+    }
+}
+
+impl IntoFr for EvaluationPoint {
+    fn into_fr(self) -> Fr {
+        let mut index = self.0;
+        index.sub_assign(&Fr::one());
+        index
+    }
+}
@@ -0,0 +1,243 @@
+//! Hybrid encryption of messages of any size.
+//!
+//! `PublicKey::encrypt` masks the whole message with a hash of a group element, and every
+//! decryption share is combined and applied to it, which is fine for keys and short secrets, but
+//! not for documents. A `HybridCiphertext` only encrypts a random 32-byte key with the threshold
+//! scheme, the _key encapsulation_, and the message itself with a symmetric authenticated cipher,
+//! the _data encapsulation_: ChaCha20 with a SHA3-256 MAC over the encapsulated key and the
+//! encrypted message. Decryption shares are created and verified for the encapsulated key exactly
+//! as for an ordinary `Ciphertext`.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let pk_set = sk_set.public_keys();
+//! let msg = vec![42u8; 100_000];
+//! let ct = pk_set.public_key().encrypt_hybrid(&msg);
+//! let shares: BTreeMap<_, _> = (0..2)
+//!     .map(|i| (i, sk_set.secret_key_share(i).decrypt_hybrid_share(&ct).unwrap()))
+//!     .collect();
+//! assert_eq!(msg, pk_set.decrypt_hybrid(&shares, &ct).unwrap());
+//! ```
+
+use std::convert::TryInto;
+
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+use crate::util::sha3_256;
+use crate::{
+    Ciphertext, DecryptionShare, IntoFr, PublicKey, PublicKeySet, SecretKey, SecretKeyShare,
+};
+
+/// The domain separator for deriving the cipher and MAC keys from the encapsulated key.
+const KEY_DST: &[u8] = b"threshold_crypto hybrid encryption key";
+
+/// The domain separator for the MAC.
+const MAC_DST: &[u8] = b"threshold_crypto hybrid encryption MAC";
+
+/// The size of the encapsulated key and of the MAC tag, in bytes.
+pub(crate) const KEY_SIZE: usize = 32;
+
+/// A message encrypted with a symmetric key, and the key encrypted to a threshold public key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct HybridCiphertext {
+    kem: Ciphertext,
+    body: Vec<u8>,
+    tag: [u8; KEY_SIZE],
+}
+
+impl HybridCiphertext {
+    /// Returns the encapsulated key, for creating and verifying decryption shares.
+    pub fn kem(&self) -> &Ciphertext {
+        &self.kem
+    }
+
+    /// Returns `true` if the encapsulated key is valid.
+    ///
+    /// The symmetric part is only authenticated when decrypting.
+    pub fn verify(&self) -> bool {
+        self.kem.verify()
+    }
+
+    /// Returns the plaintext, or `None` if the key or MAC tag is wrong.
+    fn open(&self, key: &[u8]) -> Option<Vec<u8>> {
+        let key: &[u8; KEY_SIZE] = key.try_into().ok()?;
+        let keys = DemKeys::new(key);
+        let expected = keys.tag(&[&self.kem.to_bytes(), &self.body]);
+        if !ct_eq(&expected, &self.tag) {
+            return None;
+        }
+        let mut plaintext = self.body.clone();
+        keys.apply_keystream(0, &mut plaintext);
+        Some(plaintext)
+    }
+}
+
+impl PublicKey {
+    /// Encrypts a message of any size.
+    pub fn encrypt_hybrid<M: AsRef<[u8]>>(&self, msg: M) -> HybridCiphertext {
+        self.encrypt_hybrid_with_rng(&mut OsRng, msg)
+    }
+
+    /// Encrypts a message of any size, using the given random number generator.
+    pub fn encrypt_hybrid_with_rng<R: RngCore, M: AsRef<[u8]>>(
+        &self,
+        rng: &mut R,
+        msg: M,
+    ) -> HybridCiphertext {
+        let (kem, keys) = encapsulate(self, rng);
+        let mut body = msg.as_ref().to_vec();
+        keys.apply_keystream(0, &mut body);
+        let tag = keys.tag(&[&kem.to_bytes(), &body]);
+        HybridCiphertext { kem, body, tag }
+    }
+}
+
+impl SecretKey {
+    /// Returns the decrypted message, or `None` if the ciphertext isn't valid.
+    pub fn decrypt_hybrid(&self, ct: &HybridCiphertext) -> Option<Vec<u8>> {
+        let key = Zeroizing::new(self.decrypt(&ct.kem)?);
+        ct.open(&key)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns a decryption share of the encapsulated key, or `None` if it isn't valid.
+    pub fn decrypt_hybrid_share(&self, ct: &HybridCiphertext) -> Option<DecryptionShare> {
+        self.decrypt_share(&ct.kem)
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the decryption shares of the encapsulated key, and decrypts the message.
+    ///
+    /// Like `decrypt`, this doesn't verify the shares. Returns `Error::InvalidCiphertext` if the
+    /// combined key doesn't authenticate the message, e.g. because one of the shares was invalid.
+    pub fn decrypt_hybrid<'a, T, I>(&self, shares: I, ct: &HybridCiphertext) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        let key = Zeroizing::new(self.decrypt(shares, &ct.kem)?);
+        ct.open(&key).ok_or(Error::InvalidCiphertext)
+    }
+}
+
+/// Returns a fresh random key, encrypted to the public key, and the keys derived from it.
+pub(crate) fn encapsulate<R: RngCore>(pk: &PublicKey, rng: &mut R) -> (Ciphertext, DemKeys) {
+    let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+    rng.fill_bytes(&mut *key);
+    let kem = pk.encrypt_with_rng(rng, &key[..]);
+    (kem, DemKeys::new(&key))
+}
+
+/// The cipher and MAC keys derived from an encapsulated key.
+pub(crate) struct DemKeys {
+    enc: Zeroizing<[u8; KEY_SIZE]>,
+    mac: Zeroizing<[u8; KEY_SIZE]>,
+}
+
+impl DemKeys {
+    /// Derives the cipher and MAC keys.
+    pub(crate) fn new(key: &[u8; KEY_SIZE]) -> Self {
+        let derive = |label: u8| {
+            let mut input = Zeroizing::new(KEY_DST.to_vec());
+            input.push(label);
+            input.extend(key);
+            Zeroizing::new(sha3_256(&input))
+        };
+        DemKeys {
+            enc: derive(0),
+            mac: derive(1),
+        }
+    }
+
+    /// XORs the data with the ChaCha20 keystream with the given stream number.
+    pub(crate) fn apply_keystream(&self, stream: u64, data: &mut [u8]) {
+        let mut rng = ChaChaRng::from_seed(*self.enc);
+        rng.set_stream(stream);
+        let mut block = Zeroizing::new([0u8; 64]);
+        for chunk in data.chunks_mut(64) {
+            rng.fill_bytes(&mut block[..chunk.len()]);
+            for (byte, key) in chunk.iter_mut().zip(block.iter()) {
+                *byte ^= key;
+            }
+        }
+    }
+
+    /// Returns the MAC tag of the length-prefixed parts.
+    pub(crate) fn tag(&self, parts: &[&[u8]]) -> [u8; KEY_SIZE] {
+        let mut input = MAC_DST.to_vec();
+        input.extend(&*self.mac);
+        for part in parts {
+            input.extend(&(part.len() as u64).to_be_bytes());
+            input.extend(*part);
+        }
+        sha3_256(&input)
+    }
+}
+
+/// Compares the tags without branching on their contents.
+pub(crate) fn ct_eq(a: &[u8; KEY_SIZE], b: &[u8; KEY_SIZE]) -> bool {
+    a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use rand::Rng;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_hybrid_encryption() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msg: Vec<u8> = (0..10_000).map(|i| i as u8).collect();
+        let ct = pk_set.public_key().encrypt_hybrid_with_rng(&mut rng, &msg);
+        assert!(ct.verify());
+        assert_ne!(msg, ct.body);
+        assert_eq!(Some(msg.clone()), sk_set.secret_key().decrypt_hybrid(&ct));
+        assert_eq!(None, SecretKey::random().decrypt_hybrid(&ct));
+
+        let shares: BTreeMap<usize, DecryptionShare> = (0..4)
+            .map(|i| {
+                let share = sk_set
+                    .secret_key_share(i)
+                    .decrypt_hybrid_share(&ct)
+                    .expect("valid ciphertext");
+                assert!(pk_set
+                    .public_key_share(i)
+                    .verify_decryption_share(&share, ct.kem()));
+                (i, share)
+            })
+            .collect();
+        assert_eq!(Ok(msg.clone()), pk_set.decrypt_hybrid(&shares, &ct));
+        let mut bad_shares = shares.clone();
+        bad_shares.insert(0, rng.gen());
+        assert_eq!(
+            Err(Error::InvalidCiphertext),
+            pk_set.decrypt_hybrid(bad_shares.iter().take(3), &ct)
+        );
+
+        // Any change to the encrypted message is detected.
+        let mut tampered = ct.clone();
+        tampered.body[5_000] ^= 1;
+        assert_eq!(None, sk_set.secret_key().decrypt_hybrid(&tampered));
+        let mut truncated = ct;
+        truncated.body.pop();
+        assert_eq!(None, sk_set.secret_key().decrypt_hybrid(&truncated));
+    }
+}
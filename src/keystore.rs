@@ -0,0 +1,641 @@
+//! Passphrase-encrypted keystores for secret keys and secret key shares.
+//!
+//! A [`Keystore`] is an [EIP-2335](https://eips.ethereum.org/EIPS/eip-2335) JSON document: The
+//! secret key's 32-byte big-endian representation is encrypted with AES-128-CTR under a key
+//! derived from the password with scrypt or PBKDF2-HMAC-SHA256, together with a SHA-256 checksum
+//! that detects a wrong password, and the matching public key in the clear. Keystores written by
+//! Ethereum validator clients can be loaded, and vice versa.
+//!
+//! That interoperability is why the format is EIP-2335 and not, e.g., Argon2id with AES-GCM:
+//! EIP-2335 only defines scrypt and PBKDF2 with AES-128-CTR, and its checksum over the second half
+//! of the derived key and the ciphertext takes the place of an authentication tag, rejecting a
+//! wrong password or a modified ciphertext before anything is decrypted. The primitives are the
+//! RustCrypto `scrypt`, `pbkdf2`, `aes`, `ctr` and `sha2` implementations.
+//!
+//! New keystores use scrypt with EIP-2335's parameters, `n = 2^18`, `r = 8` and `p = 1`, which take
+//! 256 MiB of memory. As in EIP-2335, control characters are removed from the password. Unlike
+//! EIP-2335, the password is not normalized to Unicode NFKD: Callers with non-ASCII passwords
+//! should normalize them first to read keystores written by other implementations.
+//!
+//! ```no_run
+//! use threshold_crypto::SecretKey;
+//!
+//! let sk = SecretKey::random();
+//! sk.save_to_keystore("validator.json", "correct horse battery staple")?;
+//! let loaded = SecretKey::load_from_keystore("validator.json", "correct horse battery staple")?;
+//! assert_eq!(sk, loaded);
+//! # Ok::<(), threshold_crypto::error::Error>(())
+//! ```
+
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use aes::Aes128;
+use ctr::cipher::{KeyIvInit, StreamCipher};
+use ctr::Ctr128BE;
+use ff::{PrimeField, PrimeFieldRepr};
+use pbkdf2::pbkdf2_hmac;
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, Result};
+use crate::util::digest::sha256;
+use crate::util::{from_hex, to_hex};
+use crate::{PublicKey, SecretKey, SecretKeyShare, SK_SIZE};
+
+/// The keystore format version of EIP-2335.
+const VERSION: u32 = 4;
+
+/// The length of the derived key.
+const DKLEN: usize = 32;
+
+/// The length of the random salt.
+const SALT_SIZE: usize = 32;
+
+/// The most memory, in bytes, that the scrypt parameters of a keystore being decrypted may need,
+/// for both the `n` entries of `128 * r` bytes and the `p` blocks of that size.
+const MAX_MEMORY: u128 = 1 << 30;
+
+/// The largest `r * n * p`, proportional to the number of Salsa20/8 rounds, that the scrypt
+/// parameters of a keystore being decrypted may have: 4 times EIP-2335's.
+const MAX_SCRYPT_WORK: u128 = 1 << 23;
+
+/// The most PBKDF2 iterations a keystore being decrypted may have: 16 times EIP-2335's.
+const MAX_PBKDF2_ITERATIONS: u32 = 1 << 22;
+
+/// A key derivation function and its cost parameters.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kdf {
+    /// scrypt, with CPU and memory cost `2^log_n`, block size `r` and parallelization `p`.
+    Scrypt {
+        /// The base-2 logarithm of the CPU and memory cost `n`.
+        log_n: u8,
+        /// The block size.
+        r: u32,
+        /// The parallelization parameter.
+        p: u32,
+    },
+    /// PBKDF2-HMAC-SHA256 with `c` iterations.
+    Pbkdf2 {
+        /// The number of iterations.
+        c: u32,
+    },
+}
+
+impl Default for Kdf {
+    /// Returns scrypt with EIP-2335's parameters, `n = 2^18`, `r = 8` and `p = 1`.
+    fn default() -> Self {
+        Kdf::Scrypt {
+            log_n: 18,
+            r: 8,
+            p: 1,
+        }
+    }
+}
+
+/// A passphrase-encrypted secret key in the EIP-2335 JSON format.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Keystore {
+    crypto: KeystoreCrypto,
+    /// A description of the key, for the operator's benefit.
+    #[serde(default)]
+    pub description: String,
+    /// The hex-encoded compressed public key.
+    pub pubkey: String,
+    /// The key's EIP-2334 derivation path, if any.
+    pub path: String,
+    /// A random UUID identifying the keystore.
+    pub uuid: String,
+    /// The format version.
+    pub version: u32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct KeystoreCrypto {
+    kdf: Module<KdfParams>,
+    checksum: Module<NoParams>,
+    cipher: Module<CipherParams>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct Module<P> {
+    function: String,
+    params: P,
+    message: String,
+}
+
+/// The parameters of either key derivation function; `n`, `r` and `p` are scrypt's, `c` and `prf`
+/// PBKDF2's.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct KdfParams {
+    dklen: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    n: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    r: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    p: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    c: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    prf: Option<String>,
+    salt: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct CipherParams {
+    iv: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+struct NoParams {}
+
+impl Keystore {
+    /// Encrypts the secret key with the password.
+    ///
+    /// Returns `Error::InvalidKeystore` if the key derivation parameters would be rejected by
+    /// `decrypt`.
+    pub fn encrypt(sk: &SecretKey, password: &str, kdf: Kdf) -> Result<Self> {
+        Self::encrypt_with_rng(&mut OsRng, sk, password, kdf)
+    }
+
+    /// Encrypts the secret key with the password, using the given random number generator for
+    /// the salt, IV and UUID.
+    ///
+    /// Returns `Error::InvalidKeystore` if the key derivation parameters would be rejected by
+    /// `decrypt`.
+    pub fn encrypt_with_rng<R: Rng>(
+        rng: &mut R,
+        sk: &SecretKey,
+        password: &str,
+        kdf: Kdf,
+    ) -> Result<Self> {
+        kdf.check()?;
+        let salt: [u8; SALT_SIZE] = rng.gen();
+        let iv: [u8; 16] = rng.gen();
+        let key = derive_key(password, &salt, kdf)?;
+        let mut message = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+        let mut repr = sk.0.into_repr();
+        repr.write_be(&mut *message)
+            .expect("writing to a vector can't fail");
+        repr.0.zeroize();
+        aes_128_ctr(&key[..16], &iv, &mut message);
+        let params = match kdf {
+            Kdf::Scrypt { log_n, r, p } => KdfParams {
+                n: Some(1u64 << log_n),
+                r: Some(r),
+                p: Some(p),
+                ..KdfParams::new(salt)
+            },
+            Kdf::Pbkdf2 { c } => KdfParams {
+                c: Some(c),
+                prf: Some("hmac-sha256".to_string()),
+                ..KdfParams::new(salt)
+            },
+        };
+        Ok(Keystore {
+            crypto: KeystoreCrypto {
+                kdf: Module {
+                    function: kdf.function().to_string(),
+                    params,
+                    message: String::new(),
+                },
+                checksum: Module {
+                    function: "sha256".to_string(),
+                    params: NoParams {},
+                    message: to_hex(sha256(&[&key[16..], &message])),
+                },
+                cipher: Module {
+                    function: "aes-128-ctr".to_string(),
+                    params: CipherParams { iv: to_hex(iv) },
+                    message: to_hex(&*message),
+                },
+            },
+            description: String::new(),
+            pubkey: to_hex(sk.public_key().to_bytes()),
+            path: String::new(),
+            uuid: random_uuid(rng),
+            version: VERSION,
+        })
+    }
+
+    /// Decrypts the secret key with the password.
+    ///
+    /// Returns `Error::WrongPassword` if the checksum doesn't match, and `Error::InvalidKeystore`
+    /// if the keystore is malformed, uses unsupported functions, has scrypt parameters that need
+    /// more than 1 GiB of memory or 4 times EIP-2335's work, has more than `2^22` PBKDF2
+    /// iterations, or the key doesn't match `pubkey`.
+    pub fn decrypt(&self, password: &str) -> Result<SecretKey> {
+        let invalid = |what: &str| Error::InvalidKeystore(what.to_string());
+        let crypto = &self.crypto;
+        if self.version != VERSION {
+            return Err(invalid("unsupported version"));
+        }
+        if crypto.checksum.function != "sha256" || crypto.cipher.function != "aes-128-ctr" {
+            return Err(invalid("unsupported function"));
+        }
+        let kdf = self.kdf()?;
+        let salt = from_hex(&crypto.kdf.params.salt).ok_or_else(|| invalid("salt"))?;
+        let checksum = from_hex(&crypto.checksum.message).ok_or_else(|| invalid("checksum"))?;
+        let iv = from_hex(&crypto.cipher.params.iv)
+            .filter(|iv| iv.len() == 16)
+            .ok_or_else(|| invalid("IV"))?;
+        let mut message =
+            Zeroizing::new(from_hex(&crypto.cipher.message).ok_or_else(|| invalid("cipher"))?);
+        let pk_bytes = from_hex(&self.pubkey).ok_or_else(|| invalid("pubkey"))?;
+        let pk = PublicKey::from_slice(&pk_bytes).map_err(|_| invalid("pubkey"))?;
+
+        let key = derive_key(password, &salt, kdf)?;
+        let expected = sha256(&[&key[16..], &message]);
+        let diff = checksum
+            .iter()
+            .zip(&expected)
+            .fold(0, |acc, (x, y)| acc | (x ^ y));
+        if checksum.len() != expected.len() || diff != 0 {
+            return Err(Error::WrongPassword);
+        }
+        aes_128_ctr(&key[..16], &iv, &mut message);
+        let sk = SecretKey::from_slice(&message).map_err(|_| invalid("secret key"))?;
+        if sk.public_key() != pk {
+            return Err(invalid("the secret key doesn't match the public key"));
+        }
+        Ok(sk)
+    }
+
+    /// Returns the keystore as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("keystores are serializable")
+    }
+
+    /// Parses a keystore from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|err| Error::InvalidKeystore(err.to_string()))
+    }
+
+    /// Writes the keystore to the file, which on Unix is only readable by its owner.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path)
+            .map_err(|err| Error::Backend(err.to_string()))?;
+        file.write_all(self.to_json().as_bytes())
+            .and_then(|()| file.sync_all())
+            .map_err(|err| Error::Backend(err.to_string()))
+    }
+
+    /// Reads a keystore from the file.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let json = fs::read_to_string(path).map_err(|err| Error::Backend(err.to_string()))?;
+        Keystore::from_json(&json)
+    }
+
+    /// Returns the key derivation function, if it is supported and within the memory and CPU limits.
+    fn kdf(&self) -> Result<Kdf> {
+        let params = &self.crypto.kdf.params;
+        if params.dklen != DKLEN as u32 {
+            return Err(Kdf::unsupported());
+        }
+        let kdf = match (&*self.crypto.kdf.function, params.n, params.r, params.p) {
+            ("scrypt", Some(n), Some(r), Some(p)) if n.is_power_of_two() => Kdf::Scrypt {
+                log_n: n.trailing_zeros() as u8,
+                r,
+                p,
+            },
+            ("pbkdf2", ..) => match (params.c, params.prf.as_deref()) {
+                (Some(c), Some("hmac-sha256")) => Kdf::Pbkdf2 { c },
+                _ => return Err(Kdf::unsupported()),
+            },
+            _ => return Err(Kdf::unsupported()),
+        };
+        kdf.check()?;
+        Ok(kdf)
+    }
+}
+
+impl KdfParams {
+    /// Returns parameters with the given salt and no function-specific entries.
+    fn new(salt: [u8; SALT_SIZE]) -> Self {
+        KdfParams {
+            dklen: DKLEN as u32,
+            n: None,
+            r: None,
+            p: None,
+            c: None,
+            prf: None,
+            salt: to_hex(salt),
+        }
+    }
+}
+
+impl Kdf {
+    /// Returns `Error::InvalidKeystore` unless the parameters are within the memory and CPU
+    /// limits, and valid: `n > 1`, `r > 0`, `p > 0` and `c > 0`.
+    fn check(&self) -> Result<()> {
+        let valid = match *self {
+            Kdf::Scrypt { log_n, r, p } => match 1u64.checked_shl(u32::from(log_n)) {
+                Some(n) => {
+                    let (n, r, p) = (u128::from(n), u128::from(r), u128::from(p));
+                    n > 1
+                        && r > 0
+                        && p > 0
+                        && 128 * r * n <= MAX_MEMORY
+                        && 128 * r * p <= MAX_MEMORY
+                        && r * n * p <= MAX_SCRYPT_WORK
+                }
+                None => false,
+            },
+            Kdf::Pbkdf2 { c } => c > 0 && c <= MAX_PBKDF2_ITERATIONS,
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Kdf::unsupported())
+        }
+    }
+
+    /// Returns the error for unsupported key derivation parameters.
+    fn unsupported() -> Error {
+        Error::InvalidKeystore("unsupported key derivation".to_string())
+    }
+
+    /// Returns the function's name in EIP-2335.
+    fn function(&self) -> &'static str {
+        match self {
+            Kdf::Scrypt { .. } => "scrypt",
+            Kdf::Pbkdf2 { .. } => "pbkdf2",
+        }
+    }
+}
+
+impl SecretKey {
+    /// Encrypts the key with the password, using the default `Kdf`, and writes the keystore to
+    /// the file.
+    pub fn save_to_keystore<P: AsRef<Path>>(&self, path: P, password: &str) -> Result<()> {
+        Keystore::encrypt(self, password, Kdf::default())?.save(path)
+    }
+
+    /// Reads the keystore from the file and decrypts the key with the password.
+    pub fn load_from_keystore<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        Keystore::load(path)?.decrypt(password)
+    }
+}
+
+impl SecretKeyShare {
+    /// Encrypts the key share with the password, using the default `Kdf`, and writes the keystore
+    /// to the file. The keystore's `pubkey` is the public key share.
+    pub fn save_to_keystore<P: AsRef<Path>>(&self, path: P, password: &str) -> Result<()> {
+        self.0.save_to_keystore(path, password)
+    }
+
+    /// Reads the keystore from the file and decrypts the key share with the password.
+    pub fn load_from_keystore<P: AsRef<Path>>(path: P, password: &str) -> Result<Self> {
+        Ok(SecretKeyShare(SecretKey::load_from_keystore(
+            path, password,
+        )?))
+    }
+}
+
+/// Derives the decryption key from the password, without its control characters.
+fn derive_key(password: &str, salt: &[u8], kdf: Kdf) -> Result<Zeroizing<[u8; DKLEN]>> {
+    let password: Zeroizing<String> =
+        Zeroizing::new(password.chars().filter(|c| !c.is_control()).collect());
+    let mut key = Zeroizing::new([0u8; DKLEN]);
+    match kdf {
+        Kdf::Scrypt { log_n, r, p } => {
+            let params = scrypt::Params::new(log_n, r, p, DKLEN).map_err(|_| Kdf::unsupported())?;
+            scrypt::scrypt(password.as_bytes(), salt, &params, &mut *key)
+                .map_err(|_| Kdf::unsupported())?;
+        }
+        Kdf::Pbkdf2 { c } => pbkdf2_hmac::<Sha256>(password.as_bytes(), salt, c, &mut *key),
+    }
+    Ok(key)
+}
+
+/// Returns a random version 4 UUID.
+fn random_uuid<R: Rng>(rng: &mut R) -> String {
+    let mut bytes: [u8; 16] = rng.gen();
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+    let hex = to_hex(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/// XORs the data with the AES-128-CTR keystream, with a 128-bit big-endian counter starting at
+/// the IV.
+fn aes_128_ctr(key: &[u8], iv: &[u8], data: &mut [u8]) {
+    let mut cipher = Ctr128BE::<Aes128>::new(key.into(), iv.into());
+    cipher.apply_keystream(data);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    /// Parameters that keep the tests fast.
+    const TEST_KDF: Kdf = Kdf::Scrypt {
+        log_n: 4,
+        r: 2,
+        p: 2,
+    };
+
+    #[test]
+    fn test_keystore() {
+        let mut rng = rand::thread_rng();
+        let sk: SecretKey = rng.gen();
+        let keystore = Keystore::encrypt_with_rng(&mut rng, &sk, "pass\u{7}word", TEST_KDF)
+            .expect("valid parameters");
+        assert_eq!(to_hex(sk.public_key().to_bytes()), keystore.pubkey);
+        assert_eq!(36, keystore.uuid.len());
+        let parsed = Keystore::from_json(&keystore.to_json()).expect("valid JSON");
+        assert_eq!(keystore, parsed);
+        // Control characters are ignored.
+        assert_eq!(Ok(sk.clone()), parsed.decrypt("password"));
+        assert_eq!(Err(Error::WrongPassword), parsed.decrypt("passw0rd"));
+        let pbkdf2 =
+            Keystore::encrypt(&sk, "password", Kdf::Pbkdf2 { c: 16 }).expect("valid parameters");
+        assert_eq!(Ok(sk), pbkdf2.decrypt("password"));
+
+        let mut tampered = keystore.clone();
+        tampered.pubkey = to_hex(rng.gen::<SecretKey>().public_key().to_bytes());
+        assert!(matches!(
+            tampered.decrypt("password"),
+            Err(Error::InvalidKeystore(_))
+        ));
+        let mut tampered = keystore;
+        for n in &[3, 1 << 40] {
+            tampered.crypto.kdf.params.n = Some(*n);
+            assert!(matches!(
+                tampered.decrypt("password"),
+                Err(Error::InvalidKeystore(_))
+            ));
+        }
+        // Parameters that would need too much memory or CPU time are rejected before deriving.
+        let start = std::time::Instant::now();
+        for (n, r, p) in &[(2, 8, 1 << 26), (2, 1, 1 << 23), (1 << 12, 1, 1 << 12)] {
+            tampered.crypto.kdf.params.n = Some(*n);
+            tampered.crypto.kdf.params.r = Some(*r);
+            tampered.crypto.kdf.params.p = Some(*p);
+            assert!(matches!(
+                tampered.decrypt("password"),
+                Err(Error::InvalidKeystore(_))
+            ));
+        }
+        let mut tampered = pbkdf2;
+        tampered.crypto.kdf.params.c = Some(u32::MAX);
+        assert!(matches!(
+            tampered.decrypt("password"),
+            Err(Error::InvalidKeystore(_))
+        ));
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+
+        let dir = std::env::temp_dir().join(format!("tc-keystore-{}", rng.gen::<u64>()));
+        fs::create_dir_all(&dir).expect("create directory");
+        let path = dir.join("share.json");
+        let share = SecretKeySet::random(1, &mut rng).secret_key_share(0);
+        Keystore::encrypt(&share.0, "pw", TEST_KDF)
+            .expect("valid parameters")
+            .save(&path)
+            .expect("save keystore");
+        assert_eq!(Ok(share), SecretKeyShare::load_from_keystore(&path, "pw"));
+        fs::remove_dir_all(dir).expect("remove directory");
+    }
+
+    #[test]
+    fn test_encrypt_rejects_invalid_kdf() {
+        let sk: SecretKey = rand::thread_rng().gen();
+        let invalid = [
+            Kdf::Pbkdf2 { c: 0 },
+            Kdf::Pbkdf2 { c: u32::MAX },
+            Kdf::Scrypt {
+                log_n: 0,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                log_n: 21,
+                r: 8,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 0,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 1,
+                p: 0,
+            },
+            Kdf::Scrypt {
+                log_n: 64,
+                r: 1,
+                p: 1,
+            },
+            Kdf::Scrypt {
+                log_n: 255,
+                r: 1,
+                p: 1,
+            },
+        ];
+        for kdf in &invalid {
+            assert!(
+                matches!(
+                    Keystore::encrypt(&sk, "password", *kdf),
+                    Err(Error::InvalidKeystore(_))
+                ),
+                "{:?}",
+                kdf
+            );
+        }
+        assert_eq!(Ok(()), Kdf::default().check());
+    }
+
+    #[test]
+    fn test_primitives() {
+        // The first half of the scrypt test vector from RFC 7914, section 12.
+        let key = derive_key(
+            "",
+            b"",
+            Kdf::Scrypt {
+                log_n: 4,
+                r: 1,
+                p: 1,
+            },
+        )
+        .expect("valid");
+        assert_eq!(
+            "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442",
+            to_hex(&key[..])
+        );
+
+        // The AES-128 example from FIPS 197, appendix C.1, as the first keystream block.
+        let key = from_hex("000102030405060708090a0b0c0d0e0f").expect("valid hex");
+        let iv = from_hex("00112233445566778899aabbccddeeff").expect("valid hex");
+        let mut data = [0u8; 16];
+        aes_128_ctr(&key, &iv, &mut data);
+        assert_eq!("69c4e0d86a7b0430d8cdb78070b4c55a", to_hex(data));
+    }
+
+    #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+    #[test]
+    fn test_eip2335_vector() {
+        // The PBKDF2 test vector from EIP-2335, whose password is "testpassword🔑" after NFKD.
+        let json = r#"{
+            "crypto": {
+                "kdf": {
+                    "function": "pbkdf2",
+                    "params": {
+                        "dklen": 32,
+                        "c": 262144,
+                        "prf": "hmac-sha256",
+                        "salt": "d4e56740f876aef8c010b86a40d5f56745a118d0906a34e69aec8c0db1cb8fa3"
+                    },
+                    "message": ""
+                },
+                "checksum": {
+                    "function": "sha256",
+                    "params": {},
+                    "message": "8a9f5d9912ed7e75ea794bc5a89bca5f193721d30868ade6f73043c6ea6febf1"
+                },
+                "cipher": {
+                    "function": "aes-128-ctr",
+                    "params": {
+                        "iv": "264daa3f303d7259501c93d997d84fe6"
+                    },
+                    "message": "cee03fde2af33149775b7223e7845e4fb2c8ae1792e5f99fe9ecf474cc8c16ad"
+                }
+            },
+            "description": "This is a test keystore that uses PBKDF2 to secure the secret.",
+            "pubkey": "9612d7a727c9d0a22e185a1c768478dfe919cada9266988cb32359c11f2b7b27f4ae4040902382ae2910c15e2b420d07",
+            "path": "m/12381/60/0/0",
+            "uuid": "64625def-3331-4eea-ab6f-782f3ed16a83",
+            "version": 4
+        }"#;
+        let keystore = Keystore::from_json(json).expect("valid keystore");
+        let sk = keystore
+            .decrypt("testpassword🔑")
+            .expect("correct password");
+        let mut bytes = Vec::new();
+        sk.0.into_repr().write_be(&mut bytes).expect("write");
+        assert_eq!(
+            "000000000019d6689c085ae165831e934ff763ae46a2a6c172b3f1b60a8ce26f",
+            to_hex(bytes)
+        );
+    }
+}
@@ -0,0 +1,400 @@
+//! Encryption of secret key shares at rest with a key management service.
+//!
+//! A [`KeyWrapper`] encrypts and decrypts small blobs with a key that never leaves the KMS, e.g. an
+//! AWS KMS or Google Cloud KMS key. A [`WrappedVault`] puts a wrapper in front of any
+//! [`ShareVault`](../vault/trait.ShareVault.html), so that e.g. a
+//! [`DirectoryVault`](../vault/struct.DirectoryVault.html) only ever stores wrapped shares.
+//!
+//! Each entry is stored as an [`Envelope`] that records the ID of the key it was wrapped with.
+//! That way the wrapping key can be rotated: Entries wrapped with an older key remain readable as
+//! long as that key is registered with the vault, and [`WrappedVault::rewrap`] moves them to the
+//! current key.
+
+use std::convert::TryFrom;
+use std::fmt;
+#[cfg(unix)]
+use std::process::Command;
+
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, FromBytesError, FromBytesResult, Result};
+#[cfg(unix)]
+use crate::vault::run;
+use crate::vault::ShareVault;
+
+/// The first bytes of an encoded `Envelope`.
+const ENVELOPE_MAGIC: &[u8; 4] = b"TCKW";
+/// The current version of the `Envelope` encoding.
+const ENVELOPE_VERSION: u8 = 1;
+
+/// A key in a key management service that can encrypt and decrypt small blobs.
+pub trait KeyWrapper {
+    /// Returns an identifier of the wrapping key, e.g. its ARN or resource name.
+    fn key_id(&self) -> &str;
+
+    /// Encrypts the plaintext.
+    fn wrap(&self, plaintext: &[u8]) -> Result<Vec<u8>>;
+
+    /// Decrypts a blob returned by `wrap`.
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>>;
+}
+
+/// A wrapped secret, together with the ID of the key it was wrapped with.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Envelope {
+    /// The ID of the wrapping key.
+    pub key_id: String,
+    /// The wrapped secret.
+    pub wrapped: Vec<u8>,
+}
+
+impl Envelope {
+    /// Returns the envelope's byte representation: a magic number and version, the key ID with a
+    /// two-byte big-endian length prefix, and the wrapped secret.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key ID is longer than `u16::MAX` bytes.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let id_len = u16::try_from(self.key_id.len()).expect("key ID too long");
+        let mut bytes = Vec::with_capacity(7 + self.key_id.len() + self.wrapped.len());
+        bytes.extend_from_slice(ENVELOPE_MAGIC);
+        bytes.push(ENVELOPE_VERSION);
+        bytes.extend_from_slice(&id_len.to_be_bytes());
+        bytes.extend_from_slice(self.key_id.as_bytes());
+        bytes.extend_from_slice(&self.wrapped);
+        bytes
+    }
+
+    /// Returns the envelope with the given representation, if valid.
+    pub fn from_bytes(bytes: &[u8]) -> FromBytesResult<Self> {
+        if bytes.len() < 7 || bytes[..4] != ENVELOPE_MAGIC[..] || bytes[4] != ENVELOPE_VERSION {
+            return Err(FromBytesError::Invalid);
+        }
+        let id_len = u16::from_be_bytes([bytes[5], bytes[6]]) as usize;
+        if bytes.len() < 7 + id_len {
+            return Err(FromBytesError::Invalid);
+        }
+        let (id_bytes, wrapped) = bytes[7..].split_at(id_len);
+        let key_id = String::from_utf8(id_bytes.to_vec()).map_err(|_| FromBytesError::Invalid)?;
+        Ok(Envelope {
+            key_id,
+            wrapped: wrapped.to_vec(),
+        })
+    }
+}
+
+/// A vault that wraps all secrets with a KMS key before sealing them in the inner vault.
+pub struct WrappedVault<V> {
+    inner: V,
+    current: Box<dyn KeyWrapper>,
+    previous: Vec<Box<dyn KeyWrapper>>,
+}
+
+impl<V: fmt::Debug> fmt::Debug for WrappedVault<V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let previous: Vec<_> = self.previous.iter().map(|w| w.key_id()).collect();
+        f.debug_struct("WrappedVault")
+            .field("inner", &self.inner)
+            .field("current", &self.current.key_id())
+            .field("previous", &previous)
+            .finish()
+    }
+}
+
+impl<V: ShareVault> WrappedVault<V> {
+    /// Creates a vault that wraps secrets with the given key and seals them in `inner`.
+    pub fn new<W: KeyWrapper + 'static>(inner: V, wrapper: W) -> Self {
+        WrappedVault {
+            inner,
+            current: Box::new(wrapper),
+            previous: Vec::new(),
+        }
+    }
+
+    /// Returns the inner vault.
+    pub fn inner(&self) -> &V {
+        &self.inner
+    }
+
+    /// Returns the inner vault, consuming this one.
+    pub fn into_inner(self) -> V {
+        self.inner
+    }
+
+    /// Returns the ID of the key new secrets are wrapped with.
+    pub fn key_id(&self) -> &str {
+        self.current.key_id()
+    }
+
+    /// Registers an older key, so that entries wrapped with it can still be unsealed.
+    pub fn add_previous_key<W: KeyWrapper + 'static>(&mut self, wrapper: W) {
+        self.previous.push(Box::new(wrapper));
+    }
+
+    /// Makes the given key the one new secrets are wrapped with, and keeps the current one for
+    /// unsealing existing entries.
+    pub fn rotate_key<W: KeyWrapper + 'static>(&mut self, wrapper: W) {
+        let old = std::mem::replace(&mut self.current, Box::new(wrapper));
+        self.previous.push(old);
+    }
+
+    /// Re-wraps the entry with the given label with the current key.
+    ///
+    /// Returns `Ok(false)` if it was already wrapped with the current key.
+    pub fn rewrap(&mut self, label: &str) -> Result<bool> {
+        let envelope = self.envelope(label)?;
+        if envelope.key_id == self.current.key_id() {
+            return Ok(false);
+        }
+        let secret = self.open(label, &envelope)?;
+        self.seal(label, &secret)?;
+        Ok(true)
+    }
+
+    /// Returns the parsed envelope stored under the given label.
+    fn envelope(&self, label: &str) -> Result<Envelope> {
+        let bytes = self.inner.unseal(label)?;
+        Envelope::from_bytes(&bytes).map_err(|_| Error::InvalidVaultEntry(label.to_string()))
+    }
+
+    /// Unwraps the envelope with the key it was wrapped with.
+    fn open(&self, label: &str, envelope: &Envelope) -> Result<Zeroizing<Vec<u8>>> {
+        let wrapper = std::iter::once(&self.current)
+            .chain(&self.previous)
+            .find(|wrapper| wrapper.key_id() == envelope.key_id)
+            .ok_or_else(|| Error::UnknownWrappingKey(envelope.key_id.clone()))?;
+        wrapper.unwrap(&envelope.wrapped).map_err(|err| match err {
+            Error::Backend(_) => err,
+            _ => Error::InvalidVaultEntry(label.to_string()),
+        })
+    }
+}
+
+impl<V: ShareVault> ShareVault for WrappedVault<V> {
+    fn seal(&mut self, label: &str, secret: &[u8]) -> Result<()> {
+        let key_id = self.current.key_id().to_string();
+        if u16::try_from(key_id.len()).is_err() {
+            return Err(Error::Backend("wrapping key ID too long".to_string()));
+        }
+        let wrapped = self.current.wrap(secret)?;
+        self.inner
+            .seal(label, &Envelope { key_id, wrapped }.to_bytes())
+    }
+
+    fn unseal(&self, label: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let envelope = self.envelope(label)?;
+        self.open(label, &envelope)
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        self.inner.remove(label)
+    }
+}
+
+/// A key in AWS KMS, used via the `aws` command line client.
+///
+/// The client's usual configuration and credentials apply. Secrets are passed to it on standard
+/// input.
+#[cfg(unix)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AwsKmsWrapper {
+    key_id: String,
+}
+
+#[cfg(unix)]
+impl AwsKmsWrapper {
+    /// Creates a wrapper using the key with the given ID, ARN or alias.
+    pub fn new<S: Into<String>>(key_id: S) -> Self {
+        AwsKmsWrapper {
+            key_id: key_id.into(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl KeyWrapper for AwsKmsWrapper {
+    fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    fn wrap(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut cmd = Command::new("aws");
+        cmd.args(["kms", "encrypt", "--key-id", &self.key_id])
+            .args(["--plaintext", "fileb:///dev/stdin"])
+            .args(["--output", "text", "--query", "CiphertextBlob"]);
+        let output = run(&mut cmd, Some(plaintext))?;
+        let wrapped = decode_base64(&output)
+            .ok_or_else(|| Error::Backend("invalid AWS KMS response".to_string()))?;
+        Ok(wrapped.to_vec())
+    }
+
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let mut cmd = Command::new("aws");
+        cmd.args(["kms", "decrypt", "--key-id", &self.key_id])
+            .args(["--ciphertext-blob", "fileb:///dev/stdin"])
+            .args(["--output", "text", "--query", "Plaintext"]);
+        let output = run(&mut cmd, Some(wrapped))?;
+        decode_base64(&output).ok_or_else(|| Error::Backend("invalid AWS KMS response".to_string()))
+    }
+}
+
+/// A key in Google Cloud KMS, used via the `gcloud` command line client.
+///
+/// The client's usual configuration and credentials apply. Secrets are passed to it on standard
+/// input.
+#[cfg(unix)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GcpKmsWrapper {
+    key_name: String,
+}
+
+#[cfg(unix)]
+impl GcpKmsWrapper {
+    /// Creates a wrapper using the key with the given resource name, i.e.
+    /// `projects/*/locations/*/keyRings/*/cryptoKeys/*`.
+    pub fn new<S: Into<String>>(key_name: S) -> Self {
+        GcpKmsWrapper {
+            key_name: key_name.into(),
+        }
+    }
+}
+
+#[cfg(unix)]
+impl KeyWrapper for GcpKmsWrapper {
+    fn key_id(&self) -> &str {
+        &self.key_name
+    }
+
+    fn wrap(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        let mut cmd = Command::new("gcloud");
+        cmd.args(["kms", "encrypt", "--key", &self.key_name]).args([
+            "--plaintext-file",
+            "-",
+            "--ciphertext-file",
+            "-",
+        ]);
+        Ok(run(&mut cmd, Some(plaintext))?.to_vec())
+    }
+
+    fn unwrap(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+        let mut cmd = Command::new("gcloud");
+        cmd.args(["kms", "decrypt", "--key", &self.key_name]).args([
+            "--ciphertext-file",
+            "-",
+            "--plaintext-file",
+            "-",
+        ]);
+        run(&mut cmd, Some(wrapped))
+    }
+}
+
+/// Decodes standard base64 with padding, ignoring whitespace.
+#[cfg(any(unix, test))]
+fn decode_base64(text: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    let value = |c: u8| match c {
+        b'A'..=b'Z' => Some(c - b'A'),
+        b'a'..=b'z' => Some(c - b'a' + 26),
+        b'0'..=b'9' => Some(c - b'0' + 52),
+        b'+' => Some(62),
+        b'/' => Some(63),
+        _ => None,
+    };
+    let mut bytes = Zeroizing::new(Vec::with_capacity(text.len() * 3 / 4));
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut padding = 0;
+    for &c in text.iter().filter(|c| !c.is_ascii_whitespace()) {
+        if c == b'=' {
+            padding += 1;
+            continue;
+        }
+        if padding > 0 {
+            return None;
+        }
+        acc = acc << 6 | u32::from(value(c)?);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            bytes.push((acc >> bits) as u8);
+        }
+    }
+    acc.zeroize();
+    if bits >= 6 || padding > 2 {
+        return None;
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::vault::{load_share, store_share, MemoryVault};
+
+    /// A toy wrapper for testing, which xors with the key.
+    struct XorWrapper(&'static str, u8);
+
+    impl KeyWrapper for XorWrapper {
+        fn key_id(&self) -> &str {
+            self.0
+        }
+
+        fn wrap(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+            Ok(plaintext.iter().map(|b| b ^ self.1).collect())
+        }
+
+        fn unwrap(&self, wrapped: &[u8]) -> Result<Zeroizing<Vec<u8>>> {
+            Ok(Zeroizing::new(self.wrap(wrapped)?))
+        }
+    }
+
+    #[test]
+    fn test_wrapped_vault_rotation() {
+        let share = rand::random();
+        let mut vault = WrappedVault::new(MemoryVault::new(), XorWrapper("old", 0x55));
+        store_share(&mut vault, "share", &share).expect("store share");
+        let stored = vault.inner().unseal("share").expect("envelope");
+        let envelope = Envelope::from_bytes(&stored).expect("valid envelope");
+        assert_eq!("old", envelope.key_id);
+
+        vault.rotate_key(XorWrapper("new", 0xaa));
+        assert_eq!(Ok(share.clone()), load_share(&vault, "share"));
+        assert_eq!(Ok(true), vault.rewrap("share"));
+        assert_eq!(Ok(false), vault.rewrap("share"));
+
+        // After re-wrapping, the old key is no longer needed.
+        let mut vault = WrappedVault::new(vault.into_inner(), XorWrapper("new", 0xaa));
+        assert_eq!(Ok(share), load_share(&vault, "share"));
+
+        vault.rotate_key(XorWrapper("newer", 0x0f));
+        let mut vault = WrappedVault::new(vault.into_inner(), XorWrapper("newer", 0x0f));
+        let err = Error::UnknownWrappingKey("new".to_string());
+        assert_eq!(Err(err), load_share(&vault, "share"));
+        vault.add_previous_key(XorWrapper("new", 0xaa));
+        assert!(load_share(&vault, "share").is_ok());
+    }
+
+    #[test]
+    fn test_envelope_bytes() {
+        let envelope = Envelope {
+            key_id: "arn:aws:kms:key".to_string(),
+            wrapped: vec![1, 2, 3],
+        };
+        let bytes = envelope.to_bytes();
+        assert_eq!(Ok(envelope), Envelope::from_bytes(&bytes));
+        assert!(Envelope::from_bytes(&bytes[..10]).is_err());
+        assert!(Envelope::from_bytes(b"TCKW\x02\x00\x00").is_err());
+    }
+
+    #[test]
+    fn test_decode_base64() {
+        let decode = |s: &str| decode_base64(s.as_bytes()).map(|b| b.to_vec());
+        assert_eq!(Some(b"".to_vec()), decode(""));
+        assert_eq!(Some(b"f".to_vec()), decode("Zg=="));
+        assert_eq!(Some(b"foob".to_vec()), decode("Zm9v\nYg==\n"));
+        assert_eq!(Some(b"foobar".to_vec()), decode("Zm9vYmFy"));
+        assert_eq!(None, decode("Zg=a"));
+        assert_eq!(None, decode("Z"));
+    }
+}
@@ -2,7 +2,7 @@
 
 // Clippy warns that it's dangerous to derive `PartialEq` and explicitly implement `Hash`, but the
 // `pairing::bls12_381` types don't implement `Hash`, so we can't derive it.
-#![allow(clippy::derive_hash_xor_eq)]
+#![allow(clippy::derived_hash_with_manual_eq)]
 // When using the mocktography, the resulting field elements become wrapped `u32`s, suddenly
 // triggering pass-by-reference warnings. They are conditionally disabled for this reason:
 #![cfg_attr(
@@ -15,45 +15,180 @@ pub use ff;
 pub use group;
 pub use pairing;
 
+#[cfg(feature = "mobile")]
+uniffi::setup_scaffolding!();
+
 mod cmp_pairing;
+mod decode;
+#[macro_use]
+mod instrument;
 mod into_fr;
+mod linalg;
 mod secret;
 
+#[cfg(feature = "borsh")]
+mod borsh_impl;
 #[cfg(feature = "codec-support")]
 #[macro_use]
 mod codec_impl;
-
+#[cfg(feature = "signature")]
+mod signature_impl;
+#[cfg(all(feature = "ssz", not(feature = "use-insecure-test-only-mock-crypto")))]
+mod ssz_impl;
+
+#[cfg(feature = "sessions")]
+pub mod async_session;
+#[cfg(feature = "dkg")]
+pub mod avss;
+pub mod batch;
+#[cfg(feature = "sessions")]
+pub mod beacon;
+pub mod blind;
+pub mod child;
+pub mod ciphersuite;
+#[cfg(all(
+    feature = "circuit",
+    not(feature = "use-insecure-test-only-mock-crypto")
+))]
+pub mod circuit;
+pub mod combination;
+#[cfg(feature = "sessions")]
+pub mod coordinator;
+#[cfg(all(feature = "dkg", feature = "encryption"))]
+pub mod dkg;
+#[cfg(feature = "encryption")]
+pub mod dleq;
+#[cfg(all(feature = "drand", not(feature = "use-insecure-test-only-mock-crypto")))]
+pub mod drand;
+#[cfg(feature = "encryption")]
+pub mod elgamal;
 pub mod error;
+pub mod escrow;
+#[cfg(all(feature = "ietf", not(feature = "use-insecure-test-only-mock-crypto")))]
+pub mod eth2;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod fingerprint;
+#[cfg(feature = "frost")]
+pub mod frost;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod hierarchical;
+pub mod hybrid;
+#[cfg(all(feature = "ietf", not(feature = "use-insecure-test-only-mock-crypto")))]
+pub mod ietf;
+#[cfg(feature = "encryption")]
+pub mod kem;
+#[cfg(feature = "keystore")]
+pub mod keystore;
+#[cfg(feature = "vault")]
+pub mod kms;
+pub mod light_client;
+pub mod lsss;
+pub mod merkle;
+#[cfg(feature = "metrics")]
+pub mod metrics;
+pub mod min_sig;
+#[cfg(all(
+    feature = "mnemonic",
+    not(feature = "use-insecure-test-only-mock-crypto")
+))]
+pub mod mnemonic;
+#[cfg(feature = "mobile")]
+pub mod mobile;
+#[cfg(feature = "encryption")]
+pub mod multi_recipient;
+pub mod multi_threshold;
+pub mod multisig;
+pub mod nested;
+pub mod pem;
+#[cfg(feature = "encryption")]
+pub mod policy_encryption;
 pub mod poly;
+#[cfg(feature = "encryption")]
+pub mod pre;
+#[cfg(feature = "proto")]
+pub mod proto;
+pub mod pvss;
+#[cfg(feature = "encryption")]
+pub mod recovery;
+#[cfg(all(feature = "dkg", feature = "encryption"))]
+pub mod refresh;
+#[cfg(all(feature = "dkg", feature = "encryption"))]
+pub mod reshare;
+pub mod roster;
+#[cfg(feature = "sessions")]
+pub mod rotation;
 pub mod serde_impl;
+#[cfg(feature = "sessions")]
+pub mod session;
+pub mod shamir;
+#[cfg(feature = "sessions")]
+pub mod signer;
+#[cfg(any(test, feature = "testing"))]
+pub mod simulator;
+pub mod state;
+#[cfg(feature = "encryption")]
+pub mod stream;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+#[cfg(feature = "test-vectors")]
+pub mod transcript;
+pub mod transparency;
+#[cfg(feature = "vault")]
+pub mod vault;
+#[cfg(feature = "test-vectors")]
+pub mod vectors;
+pub mod verify_cache;
+#[cfg(all(
+    feature = "verify-only",
+    not(feature = "use-insecure-test-only-mock-crypto")
+))]
+pub mod verify_only;
+pub mod vrf;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+pub mod weighted;
+pub mod wire;
 
+use core::ops::{Add, AddAssign};
 use std::borrow::Borrow;
 use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::convert::TryFrom;
 use std::fmt;
 use std::hash::{Hash, Hasher};
+use std::io::{self, Read};
 use std::vec::Vec;
-use core::ops::{Add, AddAssign};
 
 use ff::Field;
-use group::{CurveAffine, CurveProjective, EncodedPoint};
+use group::{CurveAffine, CurveProjective};
 use hex_fmt::HexFmt;
 use log::debug;
 use pairing::Engine;
 use rand::distributions::{Distribution, Standard};
-use rand::{rngs::OsRng, Rng, RngCore, SeedableRng};
+use rand::rngs::OsRng;
+#[cfg(feature = "encryption")]
+use rand::RngCore;
+use rand::{Rng, SeedableRng};
 use rand_chacha::ChaChaRng;
 use serde::{Deserialize, Serialize};
-use zeroize::Zeroize;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
 
 use crate::cmp_pairing::cmp_projective;
-use crate::error::{Error, FromBytesError, FromBytesResult, Result};
+use crate::decode::decode;
+#[cfg(feature = "encryption")]
+use crate::decode::write_point;
+use crate::error::{DecodeError, DecodeResult, Error, FromBytesResult, Result};
 use crate::poly::{Commitment, Poly};
-use crate::secret::clear_fr;
+#[cfg(feature = "ct")]
+use crate::secret::ct_eq_fr;
+use crate::secret::{clear_fr, fr_bytes, mul_secret};
 
-pub use crate::into_fr::IntoFr;
+pub use crate::into_fr::{EvaluationPoint, IntoFr};
 
 mod util;
-use util::sha3_256;
+use util::{sha3_256, sha3_256_reader};
 
 #[cfg(feature = "use-insecure-test-only-mock-crypto")]
 mod mock;
@@ -61,7 +196,8 @@ mod mock;
 #[cfg(feature = "use-insecure-test-only-mock-crypto")]
 pub use crate::mock::{
     Mersenne8 as Fr, Mersenne8 as FrRepr, Mocktography as PEngine, Ms8Affine as G1Affine,
-    Ms8Affine as G2Affine, Ms8Projective as G1, Ms8Projective as G2, PK_SIZE, SIG_SIZE,
+    Ms8Affine as G2Affine, Ms8Projective as G1, Ms8Projective as G2, PK_SIZE, PK_UNCOMPRESSED_SIZE,
+    SIG_SIZE, SIG_UNCOMPRESSED_SIZE, SK_SIZE,
 };
 
 #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
@@ -75,9 +211,25 @@ pub const PK_SIZE: usize = 48;
 #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
 pub const SIG_SIZE: usize = 96;
 
+/// The size of a secret key's representation in bytes.
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+pub const SK_SIZE: usize = 32;
+
+/// The size of a key's uncompressed representation in bytes.
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+pub const PK_UNCOMPRESSED_SIZE: usize = 96;
+
+/// The size of a signature's uncompressed representation in bytes.
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+pub const SIG_UNCOMPRESSED_SIZE: usize = 192;
+
+/// The domain separator for the hash of a ciphertext's masked message and associated data.
+#[cfg(feature = "encryption")]
+const AD_DST: &[u8] = b"threshold_crypto associated data";
+
 /// A public key.
 #[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
-pub struct PublicKey(#[serde(with = "serde_impl::projective")] G1);
+pub struct PublicKey(#[serde(with = "serde_impl::strict_projective")] G1);
 
 impl Hash for PublicKey {
     fn hash<H: Hasher>(&self, state: &mut H) {
@@ -94,7 +246,7 @@ impl fmt::Debug for PublicKey {
 
 impl PartialOrd for PublicKey {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -106,7 +258,9 @@ impl Ord for PublicKey {
 
 impl PublicKey {
     /// Returns `true` if the signature matches the element of `G2`.
+    #[cfg_attr(feature = "instrument", tracing::instrument(name = "verify", skip_all))]
     pub fn verify_g2<H: Into<G2Affine>>(&self, sig: &Signature, hash: H) -> bool {
+        count!(Pairing, 2);
         PEngine::pairing(self.0, hash) == PEngine::pairing(G1Affine::one(), sig.0)
     }
 
@@ -117,6 +271,12 @@ impl PublicKey {
         self.verify_g2(sig, hash_g2(msg))
     }
 
+    /// Returns `true` if the signature matches the reader's contents, which are hashed in chunks.
+    pub fn verify_reader<R: Read>(&self, sig: &Signature, reader: R) -> io::Result<bool> {
+        Ok(self.verify_g2(sig, hash_g2_reader(reader)?))
+    }
+
+    #[cfg(feature = "encryption")]
     /// Encrypts the message using the OS random number generator.
     ///
     /// Uses the `OsRng` by default. To pass in a custom random number generator, use
@@ -125,25 +285,54 @@ impl PublicKey {
         self.encrypt_with_rng(&mut OsRng, msg)
     }
 
-    /// Encrypts the message.
+    #[cfg(feature = "encryption")]
+    /// Encrypts the message, using the given random number generator.
+    ///
+    /// With a seeded generator, the ciphertext is deterministic, e.g. for reproducible tests. The
+    /// generator must be cryptographically secure: Anyone who can predict its output can decrypt.
     pub fn encrypt_with_rng<R: RngCore, M: AsRef<[u8]>>(&self, rng: &mut R, msg: M) -> Ciphertext {
+        self.encrypt_with_ad_and_rng(rng, msg, [])
+    }
+
+    #[cfg(feature = "encryption")]
+    /// Encrypts the message, bound to the associated data, e.g. an epoch, round or transaction
+    /// hash, using the OS random number generator.
+    ///
+    /// The ciphertext only verifies, and decryption shares are only created and accepted, with the
+    /// same associated data. Empty associated data is the same as none.
+    pub fn encrypt_with_ad<M: AsRef<[u8]>, A: AsRef<[u8]>>(&self, msg: M, ad: A) -> Ciphertext {
+        self.encrypt_with_ad_and_rng(&mut OsRng, msg, ad)
+    }
+
+    #[cfg(feature = "encryption")]
+    /// Encrypts the message, bound to the associated data.
+    pub fn encrypt_with_ad_and_rng<R, M, A>(&self, rng: &mut R, msg: M, ad: A) -> Ciphertext
+    where
+        R: RngCore,
+        M: AsRef<[u8]>,
+        A: AsRef<[u8]>,
+    {
         let r: Fr = Fr::random(rng);
+        count!(Exponentiation, 3);
         let u = G1Affine::one().mul(r);
         let v: Vec<u8> = {
             let g = self.0.into_affine().mul(r);
             xor_with_hash(g, msg.as_ref())
         };
-        let w = hash_g1_g2(u, &v).into_affine().mul(r);
+        let w = hash_g1_g2_ad(u, &v, ad.as_ref()).into_affine().mul(r);
         Ciphertext(u, v, w)
     }
 
     /// Returns the key with the given representation, if valid.
     pub fn from_bytes<B: Borrow<[u8; PK_SIZE]>>(bytes: B) -> FromBytesResult<Self> {
-        let mut compressed: <G1Affine as CurveAffine>::Compressed = EncodedPoint::empty();
-        compressed.as_mut().copy_from_slice(bytes.borrow());
-        let opt_affine = compressed.into_affine().ok();
-        let projective = opt_affine.ok_or(FromBytesError::Invalid)?.into_projective();
-        Ok(PublicKey(projective))
+        Ok(PublicKey::from_slice(bytes.borrow())?)
+    }
+
+    /// Decodes the key from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "PublicKey", |r| {
+            Ok(PublicKey(r.strict_point::<G1Affine>("public key")?))
+        })
     }
 
     /// Returns a byte string representation of the public key.
@@ -153,6 +342,22 @@ impl PublicKey {
         bytes
     }
 
+    /// Returns the key's uncompressed representation, which is twice as large as `to_bytes`, but
+    /// faster to decode.
+    pub fn to_uncompressed(&self) -> [u8; PK_UNCOMPRESSED_SIZE] {
+        let mut bytes = [0u8; PK_UNCOMPRESSED_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_uncompressed().as_ref());
+        bytes
+    }
+
+    /// Decodes the key from its uncompressed representation, as returned by `to_uncompressed`.
+    pub fn from_uncompressed(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "PublicKey", |r| {
+            let point = r.strict_uncompressed_point::<G1Affine>("public key")?;
+            Ok(PublicKey(point))
+        })
+    }
+
     /// Generates a non-redacted debug string.
     pub fn reveal(&self) -> String {
         format!("PublicKey({:?})", self.0)
@@ -184,10 +389,32 @@ impl PublicKeyShare {
         self.verify_g2(sig, hash_g2(msg))
     }
 
+    /// Returns `true` if the signature matches the reader's contents, which are hashed in chunks.
+    pub fn verify_reader<R: Read>(&self, sig: &SignatureShare, reader: R) -> io::Result<bool> {
+        Ok(self.verify_g2(sig, hash_g2_reader(reader)?))
+    }
+
+    #[cfg(feature = "encryption")]
     /// Returns `true` if the decryption share matches the ciphertext.
     pub fn verify_decryption_share(&self, share: &DecryptionShare, ct: &Ciphertext) -> bool {
+        self.verify_decryption_share_with_ad(share, ct, [])
+    }
+
+    #[cfg(feature = "encryption")]
+    /// Returns `true` if the decryption share matches the ciphertext with the associated data.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(name = "verify_decryption_share", skip_all)
+    )]
+    pub fn verify_decryption_share_with_ad<A: AsRef<[u8]>>(
+        &self,
+        share: &DecryptionShare,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> bool {
+        count!(Pairing, 2);
         let Ciphertext(ref u, ref v, ref w) = *ct;
-        let hash = hash_g1_g2(*u, v);
+        let hash = hash_g1_g2_ad(*u, v, ad.as_ref());
         PEngine::pairing(share.0, hash) == PEngine::pairing((self.0).0, *w)
     }
 
@@ -196,28 +423,49 @@ impl PublicKeyShare {
         Ok(PublicKeyShare(PublicKey::from_bytes(bytes)?))
     }
 
+    /// Decodes the key share from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "PublicKeyShare", |r| {
+            let pk = r.strict_point::<G1Affine>("public key")?;
+            Ok(PublicKeyShare(PublicKey(pk)))
+        })
+    }
+
     /// Returns a byte string representation of the public key share.
     pub fn to_bytes(&self) -> [u8; PK_SIZE] {
         self.0.to_bytes()
     }
 
+    /// Returns the key share's uncompressed representation.
+    pub fn to_uncompressed(&self) -> [u8; PK_UNCOMPRESSED_SIZE] {
+        self.0.to_uncompressed()
+    }
+
+    /// Decodes the key share from its uncompressed representation, as returned by
+    /// `to_uncompressed`.
+    pub fn from_uncompressed(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "PublicKeyShare", |r| {
+            let pk = r.strict_uncompressed_point::<G1Affine>("public key")?;
+            Ok(PublicKeyShare(PublicKey(pk)))
+        })
+    }
+
     /// Combines two public key shares to one (basically adds the two commitments)
     pub fn combine(&self, other: PublicKeyShare) -> PublicKeyShare {
-        let mut commit = self.0.clone().0;
-        commit.add_assign(&other.0.clone().0);
+        let mut commit = (self.0).0;
+        commit.add_assign(&(other.0).0);
         PublicKeyShare(PublicKey(commit))
     }
-
 }
 
 /// A signature.
 // Note: Random signatures can be generated for testing.
 #[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
-pub struct Signature(#[serde(with = "serde_impl::projective")] G2);
+pub struct Signature(#[serde(with = "serde_impl::strict_projective")] G2);
 
 impl PartialOrd for Signature {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -246,23 +494,41 @@ impl Hash for Signature {
     }
 }
 
+/// Decodes the signature with `from_slice`.
+impl TryFrom<&[u8]> for Signature {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> DecodeResult<Self> {
+        Signature::from_slice(bytes)
+    }
+}
+
+impl From<Signature> for [u8; SIG_SIZE] {
+    fn from(sig: Signature) -> Self {
+        sig.to_bytes()
+    }
+}
+
 impl Signature {
     /// Returns `true` if the signature contains an odd number of ones.
     pub fn parity(&self) -> bool {
         let uncomp = self.0.into_affine().into_uncompressed();
         let xor_bytes: u8 = uncomp.as_ref().iter().fold(0, |result, byte| result ^ byte);
-        let parity = 0 != xor_bytes.count_ones() % 2;
+        let parity = !xor_bytes.count_ones().is_multiple_of(2);
         debug!("Signature: {:0.10}, parity: {}", HexFmt(uncomp), parity);
         parity
     }
 
     /// Returns the signature with the given representation, if valid.
     pub fn from_bytes<B: Borrow<[u8; SIG_SIZE]>>(bytes: B) -> FromBytesResult<Self> {
-        let mut compressed: <G2Affine as CurveAffine>::Compressed = EncodedPoint::empty();
-        compressed.as_mut().copy_from_slice(bytes.borrow());
-        let opt_affine = compressed.into_affine().ok();
-        let projective = opt_affine.ok_or(FromBytesError::Invalid)?.into_projective();
-        Ok(Signature(projective))
+        Ok(Signature::from_slice(bytes.borrow())?)
+    }
+
+    /// Decodes the signature from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "Signature", |r| {
+            Ok(Signature(r.strict_point::<G2Affine>("signature")?))
+        })
     }
 
     /// Returns a byte string representation of the signature.
@@ -271,6 +537,23 @@ impl Signature {
         bytes.copy_from_slice(self.0.into_affine().into_compressed().as_ref());
         bytes
     }
+
+    /// Returns the signature's uncompressed representation, which is twice as large as
+    /// `to_bytes`, but faster to decode.
+    pub fn to_uncompressed(&self) -> [u8; SIG_UNCOMPRESSED_SIZE] {
+        let mut bytes = [0u8; SIG_UNCOMPRESSED_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_uncompressed().as_ref());
+        bytes
+    }
+
+    /// Decodes the signature from its uncompressed representation, as returned by
+    /// `to_uncompressed`.
+    pub fn from_uncompressed(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "Signature", |r| {
+            let point = r.strict_uncompressed_point::<G2Affine>("signature")?;
+            Ok(Signature(point))
+        })
+    }
 }
 
 /// A signature share.
@@ -285,6 +568,21 @@ impl Distribution<SignatureShare> for Standard {
     }
 }
 
+/// Decodes the signature share with `from_slice`.
+impl TryFrom<&[u8]> for SignatureShare {
+    type Error = DecodeError;
+
+    fn try_from(bytes: &[u8]) -> DecodeResult<Self> {
+        SignatureShare::from_slice(bytes)
+    }
+}
+
+impl From<SignatureShare> for [u8; SIG_SIZE] {
+    fn from(sig: SignatureShare) -> Self {
+        sig.to_bytes()
+    }
+}
+
 impl fmt::Debug for SignatureShare {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         let uncomp = (self.0).0.into_affine().into_uncompressed();
@@ -298,10 +596,32 @@ impl SignatureShare {
         Ok(SignatureShare(Signature::from_bytes(bytes)?))
     }
 
+    /// Decodes the signature share from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "SignatureShare", |r| {
+            let point = r.strict_point::<G2Affine>("signature")?;
+            Ok(SignatureShare(Signature(point)))
+        })
+    }
+
     /// Returns a byte string representation of the signature share.
     pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
         self.0.to_bytes()
     }
+
+    /// Returns the signature share's uncompressed representation.
+    pub fn to_uncompressed(&self) -> [u8; SIG_UNCOMPRESSED_SIZE] {
+        self.0.to_uncompressed()
+    }
+
+    /// Decodes the signature share from its uncompressed representation, as returned by
+    /// `to_uncompressed`.
+    pub fn from_uncompressed(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "SignatureShare", |r| {
+            let sig = r.strict_uncompressed_point::<G2Affine>("signature")?;
+            Ok(SignatureShare(Signature(sig)))
+        })
+    }
 }
 
 /// A secret key; wraps a single prime field element. The field element is
@@ -312,9 +632,21 @@ impl SignatureShare {
 /// `SecretKey` implements `Deserialize` but not `Serialize` to avoid accidental
 /// serialization in insecure contexts. To enable both use the `::serde_impl::SerdeSecret`
 /// wrapper which implements both `Deserialize` and `Serialize`.
-#[derive(PartialEq, Eq, Clone)]
+#[derive(Clone)]
+#[cfg_attr(not(feature = "ct"), derive(PartialEq, Eq))]
 pub struct SecretKey(Fr);
 
+/// With the `ct` feature, keys are compared in constant time.
+#[cfg(feature = "ct")]
+impl PartialEq for SecretKey {
+    fn eq(&self, other: &Self) -> bool {
+        ct_eq_fr(&self.0, &other.0).into()
+    }
+}
+
+#[cfg(feature = "ct")]
+impl Eq for SecretKey {}
+
 impl Zeroize for SecretKey {
     fn zeroize(&mut self) {
         clear_fr(&mut self.0)
@@ -327,6 +659,8 @@ impl Drop for SecretKey {
     }
 }
 
+impl ZeroizeOnDrop for SecretKey {}
+
 /// Creates a `SecretKey` containing the zero prime field element.
 impl Default for SecretKey {
     fn default() -> Self {
@@ -377,14 +711,26 @@ impl SecretKey {
         rand::random()
     }
 
+    /// Creates a new random `SecretKey`, taking the randomness directly from the operating
+    /// system's random number generator, `OsRng`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the operating system's random number generator fails.
+    pub fn generate() -> Self {
+        OsRng.gen()
+    }
+
     /// Returns the matching public key.
     pub fn public_key(&self) -> PublicKey {
-        PublicKey(G1Affine::one().mul(self.0))
+        count!(Exponentiation, 1);
+        PublicKey(mul_secret(G1Affine::one(), &self.0))
     }
 
     /// Signs the given element of `G2`.
     pub fn sign_g2<H: Into<G2Affine>>(&self, hash: H) -> Signature {
-        Signature(hash.into().mul(self.0))
+        count!(Exponentiation, 1);
+        Signature(mul_secret(hash.into(), &self.0))
     }
 
     /// Signs the given message.
@@ -394,16 +740,49 @@ impl SecretKey {
         self.sign_g2(hash_g2(msg))
     }
 
+    /// Signs the reader's contents, hashing them in chunks instead of keeping them in memory.
+    ///
+    /// The signature is the same as `sign` of the contents.
+    pub fn sign_reader<R: Read>(&self, reader: R) -> io::Result<Signature> {
+        Ok(self.sign_g2(hash_g2_reader(reader)?))
+    }
+
+    #[cfg(feature = "encryption")]
     /// Returns the decrypted text, or `None`, if the ciphertext isn't valid.
     pub fn decrypt(&self, ct: &Ciphertext) -> Option<Vec<u8>> {
-        if !ct.verify() {
+        self.decrypt_with_ad(ct, [])
+    }
+
+    #[cfg(feature = "encryption")]
+    /// Returns the decrypted text, or `None`, if the ciphertext isn't valid with the associated
+    /// data.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(name = "decrypt", skip_all)
+    )]
+    pub fn decrypt_with_ad<A: AsRef<[u8]>>(&self, ct: &Ciphertext, ad: A) -> Option<Vec<u8>> {
+        if !ct.verify_with_ad(ad) {
             return None;
         }
         let Ciphertext(ref u, ref v, _) = *ct;
-        let g = u.into_affine().mul(self.0);
+        count!(Exponentiation, 1);
+        let g = mul_secret(u.into_affine(), &self.0);
         Some(xor_with_hash(g, v))
     }
 
+    /// Decodes the key from its `SK_SIZE`-byte big-endian representation.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        let mut fr = decode(bytes, "SecretKey", |r| r.fr("secret key"))?;
+        Ok(SecretKey::from_mut(&mut fr))
+    }
+
+    /// Returns the key's `SK_SIZE`-byte big-endian representation, which `from_slice` decodes.
+    ///
+    /// *WARNING* the bytes reveal the key. They are overwritten with zeros when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        fr_bytes(&self.0)
+    }
+
     /// Generates a non-redacted debug string. This method differs from
     /// the `Debug` implementation in that it *does* leak the secret prime
     /// field element.
@@ -421,6 +800,15 @@ impl SecretKey {
 #[derive(Clone, PartialEq, Eq, Default)]
 pub struct SecretKeyShare(SecretKey);
 
+impl Zeroize for SecretKeyShare {
+    fn zeroize(&mut self) {
+        self.0.zeroize()
+    }
+}
+
+/// The inner `SecretKey` is zeroized on drop.
+impl ZeroizeOnDrop for SecretKeyShare {}
+
 /// Can be used to create a new random instance of `SecretKeyShare`. This is only useful for testing
 /// purposes as such a key has not been derived from a `SecretKeySet`.
 impl Distribution<SecretKeyShare> for Standard {
@@ -436,13 +824,13 @@ impl fmt::Debug for SecretKeyShare {
 }
 
 /// Allow combining two `SecretKeyShare` together
-impl<'a, 'b> Add<&'b SecretKeyShare> for &'a SecretKeyShare {
+impl<'b> Add<&'b SecretKeyShare> for &SecretKeyShare {
     type Output = SecretKeyShare;
     fn add(self, other: &'b SecretKeyShare) -> SecretKeyShare {
         let priv_key_1 = self.clone().0;
         let priv_key_2 = &other.0;
         let mut priv_key = priv_key_1.0;
-        priv_key.add_assign(&(priv_key_2.0));
+        Field::add_assign(&mut priv_key, &(priv_key_2.0));
         SecretKeyShare(SecretKey(priv_key))
     }
 }
@@ -459,6 +847,20 @@ impl SecretKeyShare {
         SecretKeyShare(SecretKey::from_mut(fr))
     }
 
+    /// Decodes the key share from its `SK_SIZE`-byte big-endian representation.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        let mut fr = decode(bytes, "SecretKeyShare", |r| r.fr("secret key"))?;
+        Ok(SecretKeyShare::from_mut(&mut fr))
+    }
+
+    /// Returns the key share's `SK_SIZE`-byte big-endian representation, which `from_slice`
+    /// decodes.
+    ///
+    /// *WARNING* the bytes reveal the key share. They are overwritten with zeros when dropped.
+    pub fn to_bytes(&self) -> Zeroizing<Vec<u8>> {
+        self.0.to_bytes()
+    }
+
     /// Returns the matching public key share.
     pub fn public_key_share(&self) -> PublicKeyShare {
         PublicKeyShare(self.0.public_key())
@@ -474,17 +876,40 @@ impl SecretKeyShare {
         SignatureShare(self.0.sign(msg))
     }
 
+    /// Signs the reader's contents, hashing them in chunks instead of keeping them in memory.
+    pub fn sign_reader<R: Read>(&self, reader: R) -> io::Result<SignatureShare> {
+        Ok(SignatureShare(self.0.sign_reader(reader)?))
+    }
+
+    #[cfg(feature = "encryption")]
     /// Returns a decryption share, or `None`, if the ciphertext isn't valid.
     pub fn decrypt_share(&self, ct: &Ciphertext) -> Option<DecryptionShare> {
-        if !ct.verify() {
+        self.decrypt_share_with_ad(ct, [])
+    }
+
+    #[cfg(feature = "encryption")]
+    /// Returns a decryption share, or `None`, if the ciphertext isn't valid with the associated
+    /// data.
+    pub fn decrypt_share_with_ad<A: AsRef<[u8]>>(
+        &self,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> Option<DecryptionShare> {
+        if !ct.verify_with_ad(ad) {
             return None;
         }
         Some(self.decrypt_share_no_verify(ct))
     }
 
+    #[cfg(feature = "encryption")]
     /// Returns a decryption share, without validating the ciphertext.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(name = "decrypt_share", skip_all)
+    )]
     pub fn decrypt_share_no_verify(&self, ct: &Ciphertext) -> DecryptionShare {
-        DecryptionShare(ct.0.into_affine().mul((self.0).0))
+        count!(Exponentiation, 1);
+        DecryptionShare(mul_secret(ct.0.into_affine(), &(self.0).0))
     }
 
     /// Generates a non-redacted debug string. This method differs from
@@ -495,14 +920,16 @@ impl SecretKeyShare {
     }
 }
 
+#[cfg(feature = "encryption")]
 /// An encrypted message.
 #[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
 pub struct Ciphertext(
-    #[serde(with = "serde_impl::projective")] G1,
+    #[serde(with = "serde_impl::strict_projective")] G1,
     Vec<u8>,
-    #[serde(with = "serde_impl::projective")] G2,
+    #[serde(with = "serde_impl::strict_projective")] G2,
 );
 
+#[cfg(feature = "encryption")]
 impl Hash for Ciphertext {
     fn hash<H: Hasher>(&self, state: &mut H) {
         let Ciphertext(ref u, ref v, ref w) = *self;
@@ -512,12 +939,14 @@ impl Hash for Ciphertext {
     }
 }
 
+#[cfg(feature = "encryption")]
 impl PartialOrd for Ciphertext {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
+#[cfg(feature = "encryption")]
 impl Ord for Ciphertext {
     fn cmp(&self, other: &Self) -> Ordering {
         let Ciphertext(ref u0, ref v0, ref w0) = self;
@@ -528,32 +957,108 @@ impl Ord for Ciphertext {
     }
 }
 
+#[cfg(feature = "encryption")]
 impl Ciphertext {
     /// Returns `true` if this is a valid ciphertext. This check is necessary to prevent
     /// chosen-ciphertext attacks.
     pub fn verify(&self) -> bool {
+        self.verify_with_ad([])
+    }
+
+    /// Returns `true` if this is a valid ciphertext for the associated data it was encrypted with.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(name = "verify_ciphertext", skip_all)
+    )]
+    pub fn verify_with_ad<A: AsRef<[u8]>>(&self, ad: A) -> bool {
+        count!(Pairing, 2);
         let Ciphertext(ref u, ref v, ref w) = *self;
-        let hash = hash_g1_g2(*u, v);
+        let hash = hash_g1_g2_ad(*u, v, ad.as_ref());
         PEngine::pairing(G1Affine::one(), *w) == PEngine::pairing(*u, hash)
     }
+
+    /// Returns the ciphertext's representation: `U` and `W` in their compressed encoding,
+    /// followed by the masked message `V`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let Ciphertext(ref u, ref v, ref w) = *self;
+        let mut bytes = Vec::with_capacity(PK_SIZE + SIG_SIZE + v.len());
+        write_point(&mut bytes, u);
+        write_point(&mut bytes, w);
+        bytes.extend_from_slice(v);
+        bytes
+    }
+
+    /// Decodes the ciphertext from its representation, as returned by `to_bytes`.
+    ///
+    /// This doesn't check whether the ciphertext is valid; use `verify` for that.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "Ciphertext", |r| {
+            let u = r.strict_point::<G1Affine>("U")?;
+            let w = r.strict_point::<G2Affine>("W")?;
+            Ok(Ciphertext(u, r.rest().to_vec(), w))
+        })
+    }
 }
 
+#[cfg(feature = "encryption")]
 /// A decryption share. A threshold of decryption shares can be used to decrypt a message.
 #[derive(Clone, Deserialize, Serialize, PartialEq, Eq)]
-pub struct DecryptionShare(#[serde(with = "serde_impl::projective")] G1);
+pub struct DecryptionShare(#[serde(with = "serde_impl::strict_projective")] G1);
 
+#[cfg(feature = "encryption")]
 impl Distribution<DecryptionShare> for Standard {
     fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> DecryptionShare {
         DecryptionShare(G1::random(rng))
     }
 }
 
+#[cfg(feature = "encryption")]
 impl Hash for DecryptionShare {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.0.into_affine().into_compressed().as_ref().hash(state);
     }
 }
 
+#[cfg(feature = "encryption")]
+impl DecryptionShare {
+    /// Returns the decryption share's compressed representation.
+    pub fn to_bytes(&self) -> [u8; PK_SIZE] {
+        let mut bytes = [0u8; PK_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_compressed().as_ref());
+        bytes
+    }
+
+    /// Returns the decryption share with the given representation, if valid.
+    pub fn from_bytes<B: Borrow<[u8; PK_SIZE]>>(bytes: B) -> FromBytesResult<Self> {
+        Ok(DecryptionShare::from_slice(bytes.borrow())?)
+    }
+
+    /// Decodes the decryption share from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "DecryptionShare", |r| {
+            let point = r.strict_point::<G1Affine>("decryption share")?;
+            Ok(DecryptionShare(point))
+        })
+    }
+
+    /// Returns the decryption share's uncompressed representation.
+    pub fn to_uncompressed(&self) -> [u8; PK_UNCOMPRESSED_SIZE] {
+        let mut bytes = [0u8; PK_UNCOMPRESSED_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_uncompressed().as_ref());
+        bytes
+    }
+
+    /// Decodes the decryption share from its uncompressed representation, as returned by
+    /// `to_uncompressed`.
+    pub fn from_uncompressed(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "DecryptionShare", |r| {
+            let share = r.strict_uncompressed_point::<G1Affine>("decryption share")?;
+            Ok(DecryptionShare(share))
+        })
+    }
+}
+
+#[cfg(feature = "encryption")]
 impl fmt::Debug for DecryptionShare {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_tuple("DecryptionShare").field(&DebugDots).finish()
@@ -592,12 +1097,43 @@ impl PublicKeySet {
         PublicKey(self.commit.coeff[0])
     }
 
-    /// Returns the `i`-th public key share.
+    /// Returns the `i`-th public key share. To use an arbitrary nonzero evaluation point instead
+    /// of an index, pass an `EvaluationPoint`.
     pub fn public_key_share<T: IntoFr>(&self, i: T) -> PublicKeyShare {
         let value = self.commit.evaluate(into_fr_plus_1(i));
         PublicKeyShare(PublicKey(value))
     }
 
+    /// Interpolates the key set from `threshold + 1` public key shares `(i, share)`, e.g. if a
+    /// verifier only knows the signers' public key shares and not the dealer's output.
+    ///
+    /// The threshold is the number of shares minus one, and the result only matches the original
+    /// key set if the shares are correct. Returns `Error::NotEnoughShares` if there are no shares,
+    /// and `Error::DuplicateEntry` if an index is repeated.
+    pub fn from_public_key_shares<T, B, I>(shares: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (T, B)>,
+        T: IntoFr,
+        B: Borrow<PublicKeyShare>,
+    {
+        let samples: Vec<(Fr, G1)> = shares
+            .into_iter()
+            .map(|(i, share)| (into_fr_plus_1(i), (share.borrow().0).0))
+            .collect();
+        let _span = span!("interpolate_public_key_set", shares = samples.len());
+        Commitment::interpolate(&samples).map(PublicKeySet::from)
+    }
+
+    /// Returns the representation of the key set's commitment, as in `Commitment::to_bytes`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.commit.to_bytes()
+    }
+
+    /// Decodes the key set from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        Commitment::from_slice(bytes).map(PublicKeySet::from)
+    }
+
     /// Combines the shares into a signature that can be verified with the main public key.
     ///
     /// The validity of the shares is not checked: If one of them is invalid, the resulting
@@ -631,6 +1167,13 @@ impl PublicKeySet {
     /// // Validate the main signature. If the shares were valid, this can't fail.
     /// assert!(pk_set.public_key().verify(&sig, msg));
     /// ```
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(
+            skip_all,
+            fields(threshold = self.threshold(), shares = tracing::field::Empty)
+        )
+    )]
     pub fn combine_signatures<'a, T, I>(&self, shares: I) -> Result<Signature>
     where
         I: IntoIterator<Item = (T, &'a SignatureShare)>,
@@ -640,7 +1183,57 @@ impl PublicKeySet {
         Ok(Signature(interpolate(self.commit.degree(), samples)?))
     }
 
+    /// Combines the shares of the same `threshold + 1` signers for several messages, into one
+    /// signature per message.
+    ///
+    /// Each signer's shares must be in the order of the messages, and all signers must have a
+    /// share for every message, otherwise `Error::NotEnoughShares` is returned. The Lagrange
+    /// coefficients are computed only once, so this is faster than calling `combine_signatures`
+    /// for each message.
+    pub fn combine_many<T, B, I>(&self, shares: I) -> Result<Vec<Signature>>
+    where
+        I: IntoIterator<Item = (T, B)>,
+        T: IntoFr,
+        B: AsRef<[SignatureShare]>,
+    {
+        let t = self.threshold();
+        let (xs, signers): (Vec<_>, Vec<_>) = shares
+            .into_iter()
+            .take(t + 1)
+            .map(|(i, sig_shares)| (into_fr_plus_1(i), sig_shares))
+            .unzip();
+        if signers.len() <= t {
+            return Err(Error::NotEnoughShares);
+        }
+        let count = signers[0].as_ref().len();
+        if signers
+            .iter()
+            .any(|sig_shares| sig_shares.as_ref().len() != count)
+        {
+            return Err(Error::NotEnoughShares);
+        }
+        let _span = span!("combine_many", threshold = t, messages = count);
+        count!(Exponentiation, signers.len() * count);
+        let coeffs = lagrange_coefficients(&xs)?;
+        let mut sigs = vec![G2::zero(); count];
+        for (l0, sig_shares) in coeffs.into_iter().zip(&signers) {
+            for (sig, share) in sigs.iter_mut().zip(sig_shares.as_ref()) {
+                sig.add_assign(&((share.0).0).into_affine().mul(l0));
+            }
+        }
+        Ok(sigs.into_iter().map(Signature).collect())
+    }
+
+    #[cfg(feature = "encryption")]
     /// Combines the shares to decrypt the ciphertext.
+    #[cfg_attr(
+        feature = "instrument",
+        tracing::instrument(
+            name = "combine_decryption_shares",
+            skip_all,
+            fields(threshold = self.threshold(), shares = tracing::field::Empty)
+        )
+    )]
     pub fn decrypt<'a, T, I>(&self, shares: I, ct: &Ciphertext) -> Result<Vec<u8>>
     where
         I: IntoIterator<Item = (T, &'a DecryptionShare)>,
@@ -655,9 +1248,8 @@ impl PublicKeySet {
     pub fn combine(&self, other: PublicKeySet) -> PublicKeySet {
         let mut commit = self.commit.clone();
         commit.add_assign(&other.commit);
-        PublicKeySet{ commit }
+        PublicKeySet { commit }
     }
-
 }
 
 /// A secret key and an associated set of secret key shares.
@@ -668,6 +1260,24 @@ pub struct SecretKeySet {
     poly: Poly,
 }
 
+impl Zeroize for SecretKeySet {
+    fn zeroize(&mut self) {
+        self.poly.zeroize()
+    }
+}
+
+/// The inner `Poly` is zeroized on drop.
+impl ZeroizeOnDrop for SecretKeySet {}
+
+impl fmt::Debug for SecretKeySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecretKeySet")
+            .field("threshold", &self.threshold())
+            .field("poly", &DebugDots)
+            .finish()
+    }
+}
+
 impl From<Poly> for SecretKeySet {
     fn from(poly: Poly) -> SecretKeySet {
         SecretKeySet { poly }
@@ -694,13 +1304,69 @@ impl SecretKeySet {
         Poly::try_random(threshold, rng).map(SecretKeySet::from)
     }
 
+    /// Creates a set of secret key shares, where any `threshold + 1` of them can collaboratively
+    /// sign and decrypt, taking the randomness directly from the operating system's random number
+    /// generator, `OsRng`.
+    ///
+    /// # Panic
+    ///
+    /// Panics if the `threshold` is too large for the coefficients to fit into a `Vec`, or if the
+    /// operating system's random number generator fails.
+    pub fn generate(threshold: usize) -> Self {
+        SecretKeySet::random(threshold, &mut OsRng)
+    }
+
+    /// Creates a set of secret key shares of the given master key, where any `threshold + 1` of
+    /// them can collaboratively sign and decrypt. This lets a trusted dealer upgrade an existing
+    /// `SecretKey` to threshold operation, keeping its `PublicKey`.
+    pub fn from_secret_key<R: Rng>(sk: &SecretKey, threshold: usize, rng: &mut R) -> Result<Self> {
+        let mut poly = Poly::try_random(threshold, rng)?;
+        poly.coeff[0] = sk.0;
+        Ok(SecretKeySet::from(poly))
+    }
+
+    /// Interpolates the master key from `threshold + 1` of the given shares, e.g. to restore an
+    /// escrowed key.
+    ///
+    /// Returns `Error::NotEnoughShares` if there are no more than `threshold` shares, and
+    /// `Error::InvalidShare(i)` if share `i` doesn't match the key set.
+    pub fn recover(
+        shares: &BTreeMap<usize, SecretKeyShare>,
+        pk_set: &PublicKeySet,
+    ) -> Result<SecretKey> {
+        let threshold = pk_set.threshold();
+        if shares.len() <= threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        let shares: Vec<(&usize, &SecretKeyShare)> = shares.iter().take(threshold + 1).collect();
+        for (i, share) in &shares {
+            if share.public_key_share() != pk_set.public_key_share(**i) {
+                return Err(Error::InvalidShare(**i));
+            }
+        }
+        let xs: Vec<Fr> = shares.iter().map(|(i, _)| into_fr_plus_1(**i)).collect();
+        let mut fr = Fr::zero();
+        for (l0, (_, share)) in lagrange_coefficients(&xs)?.into_iter().zip(&shares) {
+            let mut term = (share.0).0;
+            term.mul_assign(&l0);
+            Field::add_assign(&mut fr, &term);
+            clear_fr(&mut term);
+        }
+        let sk = SecretKey::from_mut(&mut fr);
+        if sk.public_key() != pk_set.public_key() {
+            return Err(Error::InterpolationFailed);
+        }
+        Ok(sk)
+    }
+
     /// Returns the threshold `t`: any set of `t + 1` signature shares can be combined into a full
     /// signature.
     pub fn threshold(&self) -> usize {
         self.poly.degree()
     }
 
-    /// Returns the `i`-th secret key share.
+    /// Returns the `i`-th secret key share. To use an arbitrary nonzero evaluation point instead
+    /// of an index, pass an `EvaluationPoint`.
     pub fn secret_key_share<T: IntoFr>(&self, i: T) -> SecretKeyShare {
         let mut fr = self.poly.evaluate(into_fr_plus_1(i));
         SecretKeyShare::from_mut(&mut fr)
@@ -723,10 +1389,27 @@ impl SecretKeySet {
 
 /// Returns a hash of the given message in `G2`.
 pub fn hash_g2<M: AsRef<[u8]>>(msg: M) -> G2 {
-    let digest = sha3_256(msg.as_ref());
-    G2::random(&mut ChaChaRng::from_seed(digest))
+    let _span = span!("hash_g2", len = msg.as_ref().len());
+    hash_g2_digest(&sha3_256(msg.as_ref()))
 }
 
+/// Returns a hash of the message with the given SHA3-256 digest, in `G2`.
+///
+/// This is equivalent to `hash_g2(msg)`, but can be used if the digest has already been computed.
+pub fn hash_g2_digest(digest: &[u8; 32]) -> G2 {
+    count!(HashToCurve, 1);
+    G2::random(&mut ChaChaRng::from_seed(*digest))
+}
+
+/// Returns a hash of the reader's contents, in `G2`, without keeping all of it in memory.
+///
+/// This is equivalent to `hash_g2` of the contents.
+pub fn hash_g2_reader<R: Read>(reader: R) -> io::Result<G2> {
+    let _span = span!("hash_g2_reader");
+    Ok(hash_g2_digest(&sha3_256_reader(reader)?))
+}
+
+#[cfg(feature = "encryption")]
 /// Returns a hash of the group element and message, in the second group.
 fn hash_g1_g2<M: AsRef<[u8]>>(g1: G1, msg: M) -> G2 {
     // If the message is large, hash it, otherwise copy it.
@@ -740,6 +1423,26 @@ fn hash_g1_g2<M: AsRef<[u8]>>(g1: G1, msg: M) -> G2 {
     hash_g2(&msg)
 }
 
+#[cfg(feature = "encryption")]
+/// Returns a hash of the group element, masked message and associated data, in the second group.
+///
+/// Without associated data, this is `hash_g1_g2(g1, v)`, so that plain ciphertexts are unchanged.
+/// Otherwise the hash input is `AD_DST`, the length-prefixed associated data, the digest of `v` and
+/// the group element: at least 121 bytes, while `hash_g1_g2`'s input is at most 112, so that no
+/// ciphertext with associated data is also valid as one without.
+fn hash_g1_g2_ad(g1: G1, v: &[u8], ad: &[u8]) -> G2 {
+    if ad.is_empty() {
+        return hash_g1_g2(g1, v);
+    }
+    let mut data = AD_DST.to_vec();
+    data.extend(&(ad.len() as u64).to_be_bytes());
+    data.extend(ad);
+    data.extend(&sha3_256(v));
+    data.extend(g1.into_affine().into_compressed().as_ref());
+    hash_g2(&data)
+}
+
+#[cfg(feature = "encryption")]
 /// Returns the bitwise xor of `bytes` with a sequence of pseudorandom bytes determined by `g1`.
 fn xor_with_hash(g1: G1, bytes: &[u8]) -> Vec<u8> {
     let digest = sha3_256(g1.into_affine().into_compressed().as_ref());
@@ -757,52 +1460,65 @@ where
     T: IntoFr,
     B: Borrow<C>,
 {
-    let samples: Vec<_> = items
+    let (xs, samples): (Vec<_>, Vec<_>) = items
         .into_iter()
         .take(t + 1)
         .map(|(i, sample)| (into_fr_plus_1(i), sample))
-        .collect();
+        .unzip();
     if samples.len() <= t {
         return Err(Error::NotEnoughShares);
     }
+    record_shares!(samples.len());
+    let _span = span!("interpolate", threshold = t, shares = samples.len());
+    count!(Exponentiation, samples.len());
 
     if t == 0 {
-        return Ok(*samples[0].1.borrow());
+        return Ok(*samples[0].borrow());
     }
 
+    let mut result = C::zero();
+    for (l0, sample) in lagrange_coefficients(&xs)?.into_iter().zip(&samples) {
+        result.add_assign(&sample.borrow().into_affine().mul(l0));
+    }
+    Ok(result)
+}
+
+/// Returns the values at `0` of the Lagrange polynomials that are `1` at one of the given distinct
+/// points and `0` at the others.
+fn lagrange_coefficients(xs: &[Fr]) -> Result<Vec<Fr>> {
     // Compute the products `x_prod[i]` of all but the `i`-th entry.
-    let mut x_prod: Vec<C::Scalar> = Vec::with_capacity(t);
-    let mut tmp = C::Scalar::one();
+    let mut x_prod: Vec<Fr> = Vec::with_capacity(xs.len());
+    let mut tmp = Fr::one();
     x_prod.push(tmp);
-    for (x, _) in samples.iter().take(t) {
+    for x in xs.iter().take(xs.len().saturating_sub(1)) {
         tmp.mul_assign(x);
         x_prod.push(tmp);
     }
-    tmp = C::Scalar::one();
-    for (i, (x, _)) in samples[1..].iter().enumerate().rev() {
+    tmp = Fr::one();
+    for (i, x) in xs.iter().enumerate().skip(1).rev() {
         tmp.mul_assign(x);
-        x_prod[i].mul_assign(&tmp);
+        x_prod[i - 1].mul_assign(&tmp);
     }
 
-    let mut result = C::zero();
-    for (mut l0, (x, sample)) in x_prod.into_iter().zip(&samples) {
+    let mut coeffs = Vec::with_capacity(xs.len());
+    for (mut l0, x) in x_prod.into_iter().zip(xs) {
         // Compute the value at 0 of the Lagrange polynomial that is `0` at the other data
         // points but `1` at `x`.
-        let mut denom = C::Scalar::one();
-        for (x0, _) in samples.iter().filter(|(x0, _)| x0 != x) {
+        let mut denom = Fr::one();
+        for x0 in xs.iter().filter(|x0| *x0 != x) {
             let mut diff = *x0;
             diff.sub_assign(x);
             denom.mul_assign(&diff);
         }
         l0.mul_assign(&denom.inverse().ok_or(Error::DuplicateEntry)?);
-        result.add_assign(&sample.borrow().into_affine().mul(l0));
+        coeffs.push(l0);
     }
-    Ok(result)
+    Ok(coeffs)
 }
 
 fn into_fr_plus_1<I: IntoFr>(x: I) -> Fr {
     let mut result = Fr::one();
-    result.add_assign(&x.into_fr());
+    Field::add_assign(&mut result, &x.into_fr());
     result
 }
 
@@ -822,7 +1538,8 @@ mod tests {
 
     use std::collections::BTreeMap;
 
-    use rand::{self, distributions::Standard, random, Rng};
+    use group::EncodedPoint;
+    use rand::{self, distributions::Standard, random, Rng, RngCore};
 
     #[test]
     fn test_interpolate() {
@@ -841,6 +1558,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_from_secret_key() {
+        let mut rng = rand::thread_rng();
+        let sk = SecretKey::random();
+        let sk_set = SecretKeySet::from_secret_key(&sk, 2, &mut rng).expect("valid threshold");
+        assert_eq!(2, sk_set.threshold());
+        assert_eq!(sk, sk_set.secret_key());
+        assert_eq!(sk.public_key(), sk_set.public_keys().public_key());
+        assert!(SecretKeySet::from_secret_key(&sk, usize::MAX, &mut rng).is_err());
+    }
+
+    #[test]
+    fn test_recover() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let mut shares: BTreeMap<usize, SecretKeyShare> = vec![1, 4, 6]
+            .into_iter()
+            .map(|i| (i, sk_set.secret_key_share(i)))
+            .collect();
+        let sk = SecretKeySet::recover(&shares, &pk_set).expect("valid shares");
+        assert_eq!(sk_set.secret_key(), sk);
+
+        shares.insert(4, sk_set.secret_key_share(5));
+        let result = SecretKeySet::recover(&shares, &pk_set);
+        assert_eq!(Some(Error::InvalidShare(4)), result.err());
+        shares.remove(&4);
+        let result = SecretKeySet::recover(&shares, &pk_set);
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+    }
+
+    #[test]
+    fn test_from_public_key_shares() {
+        let mut rng = rand::thread_rng();
+        let pk_set = SecretKeySet::random(2, &mut rng).public_keys();
+        let shares = |indices: &[usize]| -> Vec<(usize, PublicKeyShare)> {
+            indices
+                .iter()
+                .map(|i| (*i, pk_set.public_key_share(*i)))
+                .collect()
+        };
+        let result = PublicKeySet::from_public_key_shares(shares(&[5, 0, 3]));
+        assert_eq!(Ok(pk_set.clone()), result);
+        // More shares of the same key set result in the same commitment.
+        let result = PublicKeySet::from_public_key_shares(shares(&[1, 2, 4, 7]));
+        assert_eq!(Ok(pk_set.clone()), result);
+        let result = PublicKeySet::from_public_key_shares(shares(&[1, 2]));
+        assert_eq!(1, result.expect("valid shares").threshold());
+
+        let result = PublicKeySet::from_public_key_shares(shares(&[1, 2, 1]));
+        assert_eq!(Some(Error::DuplicateEntry), result.err());
+        let result = PublicKeySet::from_public_key_shares(shares(&[]));
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+    }
+
     #[test]
     fn test_simple_sig() {
         let sk0 = SecretKey::random();
@@ -906,6 +1678,87 @@ mod tests {
         assert_eq!(sig, sig2);
     }
 
+    #[test]
+    fn test_combine_many() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msgs = ["vote 1", "vote 2", "vote 3"];
+        let shares: BTreeMap<usize, Vec<SignatureShare>> = [3, 1, 4]
+            .iter()
+            .map(|&i| {
+                let sk_share = sk_set.secret_key_share(i);
+                (i, msgs.iter().map(|msg| sk_share.sign(msg)).collect())
+            })
+            .collect();
+        let sigs = pk_set.combine_many(&shares).expect("enough shares");
+        assert_eq!(msgs.len(), sigs.len());
+        for (msg, sig) in msgs.iter().zip(&sigs) {
+            assert_eq!(sk_set.secret_key().sign(msg), *sig);
+        }
+
+        let mut missing = shares.clone();
+        missing.get_mut(&1).expect("signer 1").pop();
+        assert_eq!(Err(Error::NotEnoughShares), pk_set.combine_many(&missing));
+        let too_few = shares.iter().skip(1);
+        assert_eq!(Err(Error::NotEnoughShares), pk_set.combine_many(too_few));
+    }
+
+    #[test]
+    fn test_evaluation_points() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        assert_eq!(
+            Err(Error::ZeroEvaluationPoint),
+            EvaluationPoint::new(Fr::zero())
+        );
+
+        // Points derived from hashes work like any other share index.
+        let points: Vec<EvaluationPoint> = (0..3u8)
+            .map(|i| {
+                let x = Fr::random(&mut ChaChaRng::from_seed(sha3_256(&[i])));
+                EvaluationPoint::new(x).expect("nonzero point")
+            })
+            .collect();
+        let msg = "msg";
+        let shares: Vec<_> = points
+            .iter()
+            .map(|x| (*x, sk_set.secret_key_share(x).sign(msg)))
+            .collect();
+        for (x, share) in &shares {
+            assert!(pk_set.public_key_share(x).verify(share, msg));
+        }
+        let sig = pk_set.combine_signatures(shares.iter().map(|(x, share)| (x, share)));
+        let sig = sig.expect("enough shares");
+        assert!(pk_set.public_key().verify(&sig, msg));
+
+        // The point `x` is the share index `x - 1`.
+        let one = EvaluationPoint::new(Fr::one()).expect("nonzero point");
+        assert_eq!(sk_set.secret_key_share(0), sk_set.secret_key_share(one));
+    }
+
+    #[test]
+    fn test_sign_reader() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let msg: Vec<u8> = (0..200_000).map(|i| i as u8).collect();
+        let sig = sk_set.secret_key().sign_reader(&msg[..]).expect("read");
+        assert_eq!(sk_set.secret_key().sign(&msg), sig);
+        let pk = sk_set.public_keys().public_key();
+        assert!(pk.verify_reader(&sig, &msg[..]).expect("read"));
+        assert!(!pk.verify_reader(&sig, &msg[1..]).expect("read"));
+        assert_eq!(hash_g2(&msg), hash_g2_digest(&sha3_256(&msg)));
+
+        let share = sk_set
+            .secret_key_share(1)
+            .sign_reader(&msg[..])
+            .expect("read");
+        let pk_share = sk_set.public_keys().public_key_share(1);
+        assert!(pk_share.verify_reader(&share, &msg[..]).expect("read"));
+    }
+
+    #[cfg(feature = "encryption")]
     #[test]
     fn test_simple_enc() {
         let sk_bob: SecretKey = random();
@@ -931,14 +1784,134 @@ mod tests {
         assert_eq!(None, sk_bob.decrypt(&fake_ciphertext));
     }
 
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn test_encrypt_with_ad() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let ct = pk_set.public_key().encrypt_with_ad("msg", "epoch 1");
+        assert!(ct.verify_with_ad("epoch 1"));
+        assert!(!ct.verify_with_ad("epoch 2"));
+        assert!(!ct.verify());
+        let sk = sk_set.secret_key();
+        assert_eq!(Some(b"msg".to_vec()), sk.decrypt_with_ad(&ct, "epoch 1"));
+        assert_eq!(None, sk.decrypt_with_ad(&ct, "epoch 2"));
+
+        // Shares are only created and accepted for the ciphertext's context.
+        let sk_share = sk_set.secret_key_share(0);
+        let pk_share = pk_set.public_key_share(0);
+        assert_eq!(None, sk_share.decrypt_share_with_ad(&ct, "epoch 2"));
+        let share = sk_share
+            .decrypt_share_with_ad(&ct, "epoch 1")
+            .expect("valid ciphertext");
+        assert!(pk_share.verify_decryption_share_with_ad(&share, &ct, "epoch 1"));
+        assert!(!pk_share.verify_decryption_share_with_ad(&share, &ct, "epoch 2"));
+        assert!(!pk_share.verify_decryption_share(&share, &ct));
+
+        // Empty associated data is the same as none.
+        let plain = pk_set.public_key().encrypt_with_ad("msg", []);
+        assert!(plain.verify());
+        assert_eq!(Some(b"msg".to_vec()), sk.decrypt(&plain));
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn test_rewrapped_ad_ciphertext() {
+        let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+        let ct = sk_set
+            .public_keys()
+            .public_key()
+            .encrypt_with_ad("msg", "epoch 1");
+
+        // Moving the associated data into the masked message doesn't yield a plain ciphertext.
+        let Ciphertext(u, v, w) = ct;
+        let mut framed_ad = AD_DST.to_vec();
+        framed_ad.extend(&7u64.to_be_bytes());
+        framed_ad.extend(b"epoch 1");
+        for suffix in &[v.clone(), sha3_256(&v).to_vec()] {
+            let rewrapped_v = [&framed_ad[..], suffix].concat();
+            let rewrapped = Ciphertext(u, rewrapped_v, w);
+            assert!(!rewrapped.verify());
+            assert_eq!(None, sk_set.secret_key_share(0).decrypt_share(&rewrapped));
+        }
+    }
+
+    #[test]
+    #[cfg(all(
+        feature = "encryption",
+        not(feature = "use-insecure-test-only-mock-crypto")
+    ))]
+    fn test_legacy_ciphertext() {
+        // A ciphertext of "legacy ciphertext" to the secret key `42`, and a decryption share by
+        // the key share `43`, serialized with `bincode` by version 0.4.0.
+        let ct_bytes = util::from_hex(
+            "a9c5d047deae1a51a075d998b50d0acdf2df52f2532bc78fbab419a42765122fc47208a577293a7b35\
+             667a2e1b684ea611000000000000003cdc569d79558112381faf1312d8ddbb4e90674b1d51d5a557ff\
+             6fcd9a247b8ceb217ac5e091112ac8ed71448a05faf5016a4e17a5166c67fe54f8302ab114953e04cc\
+             221a6661a9dcbfa00acbe911fc81e88c4819924fb87a89b3b7c2489afcde3a13d900befd79d7312e01\
+             ae96ac28f9",
+        )
+        .expect("valid hex");
+        let share_bytes = util::from_hex(
+            "8a23b12ab30599dbdd8c3bb000b42b4fcf5c68e62b95f698365ef3c59b11df7d811f9ab45e790f745911\
+             cb7a3d159042",
+        )
+        .expect("valid hex");
+        let ct: Ciphertext = bincode::deserialize(&ct_bytes).expect("valid ciphertext");
+        let share: DecryptionShare = bincode::deserialize(&share_bytes).expect("valid share");
+
+        let sk = SecretKey::from_mut(&mut 42u64.into_fr());
+        let sk_share = SecretKeyShare::from_mut(&mut 43u64.into_fr());
+        assert!(ct.verify());
+        assert_eq!(Some(b"legacy ciphertext".to_vec()), sk.decrypt(&ct));
+        assert!(sk_share
+            .public_key_share()
+            .verify_decryption_share(&share, &ct));
+        assert_eq!(Some(share), sk_share.decrypt_share(&ct));
+    }
+
+    #[test]
+    #[cfg(feature = "encryption")]
+    fn test_encrypt_with_seeded_rng() {
+        let sk = SecretKey::random();
+        let pk = sk.public_key();
+        let ct = pk.encrypt_with_rng(&mut ChaChaRng::from_seed([7; 32]), "msg");
+        assert_eq!(
+            ct,
+            pk.encrypt_with_rng(&mut ChaChaRng::from_seed([7; 32]), "msg")
+        );
+        assert_ne!(
+            ct,
+            pk.encrypt_with_rng(&mut ChaChaRng::from_seed([8; 32]), "msg")
+        );
+        assert_eq!(Some(b"msg".to_vec()), sk.decrypt(&ct));
+    }
+
     #[test]
     fn test_random_extreme_thresholds() {
         let mut rng = rand::thread_rng();
         let sks = SecretKeySet::random(0, &mut rng);
         assert_eq!(0, sks.threshold());
-        assert!(SecretKeySet::try_random(usize::max_value(), &mut rng).is_err());
+        assert!(SecretKeySet::try_random(usize::MAX, &mut rng).is_err());
     }
 
+    #[test]
+    fn test_generate() {
+        let sk = SecretKey::generate();
+        assert!(sk.public_key().verify(&sk.sign("msg"), "msg"));
+        let sk_set = SecretKeySet::generate(2);
+        assert_eq!(2, sk_set.threshold());
+
+        // Mock keys are too small for collisions to be unlikely.
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        {
+            assert_ne!(sk, SecretKey::generate());
+            assert_ne!(sk_set.secret_key(), SecretKeySet::generate(2).secret_key());
+        }
+    }
+
+    #[cfg(feature = "encryption")]
     #[test]
     fn test_threshold_enc() {
         let mut rng = rand::thread_rng();
@@ -986,6 +1959,7 @@ mod tests {
         assert_ne!(hash_g2(&msg_end0), hash_g2(&msg_end1));
     }
 
+    #[cfg(feature = "encryption")]
     /// Some basic sanity checks for the `hash_g1_g2` function.
     #[test]
     fn test_hash_g1_g2() {
@@ -1002,6 +1976,7 @@ mod tests {
         assert_ne!(hash_g1_g2(g0, &msg), hash_g1_g2(g1, &msg));
     }
 
+    #[cfg(feature = "encryption")]
     /// Some basic sanity checks for the `hash_bytes` function.
     #[test]
     fn test_xor_with_hash() {
@@ -1025,6 +2000,132 @@ mod tests {
         assert_eq!(pk, pk2);
         let sig2 = Signature::from_bytes(sig.to_bytes()).expect("invalid sig representation");
         assert_eq!(sig, sig2);
+        assert_eq!(SK_SIZE, sk.to_bytes().len());
+        let sk2 = SecretKey::from_slice(&sk.to_bytes()).expect("invalid sk representation");
+        assert_eq!(sk, sk2);
+        let sk_share = SecretKeySet::random(1, &mut rand::thread_rng()).secret_key_share(1);
+        let decoded = SecretKeyShare::from_slice(&sk_share.to_bytes());
+        assert_eq!(Ok(sk_share), decoded);
+    }
+
+    #[test]
+    fn test_uncompressed() {
+        let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+        let pk = sk_set.public_keys().public_key();
+        let pk_share = sk_set.public_keys().public_key_share(1);
+        let sig_share = sk_set.secret_key_share(1).sign("msg");
+        let sig = sk_set.secret_key().sign("msg");
+        assert_eq!(Ok(pk), PublicKey::from_uncompressed(&pk.to_uncompressed()));
+        let decoded = PublicKeyShare::from_uncompressed(&pk_share.to_uncompressed());
+        assert_eq!(Ok(pk_share), decoded);
+        let decoded = SignatureShare::from_uncompressed(&sig_share.to_uncompressed());
+        assert_eq!(Ok(sig_share), decoded);
+        assert_eq!(
+            Ok(sig.clone()),
+            Signature::from_uncompressed(&sig.to_uncompressed())
+        );
+
+        // The encodings are not interchangeable.
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        {
+            assert!(Signature::from_uncompressed(&sig.to_bytes()).is_err());
+            assert!(PublicKey::from_slice(&pk.to_uncompressed()).is_err());
+        }
+
+        #[cfg(feature = "encryption")]
+        {
+            let ct = pk.encrypt("msg");
+            let share = sk_set.secret_key_share(1).decrypt_share_no_verify(&ct);
+            let decoded = DecryptionShare::from_uncompressed(&share.to_uncompressed());
+            assert_eq!(Ok(share), decoded);
+        }
+    }
+
+    #[test]
+    fn test_from_slice() {
+        use crate::error::{DecodeError, DecodeReason};
+        use ff::{PrimeField, PrimeFieldRepr};
+
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let pk_share = pk_set.public_key_share(1);
+        let sig_share = sk_set.secret_key_share(1).sign("msg");
+        let decoded_pk_set = PublicKeySet::from_slice(&pk_set.to_bytes());
+        assert_eq!(Ok(pk_set.clone()), decoded_pk_set);
+        let decoded_pk_share = PublicKeyShare::from_slice(&pk_share.to_bytes());
+        assert_eq!(Ok(pk_share), decoded_pk_share);
+        let decoded_sig_share = SignatureShare::from_slice(&sig_share.to_bytes());
+        assert_eq!(Ok(sig_share.clone()), decoded_sig_share);
+        let sig_bytes: [u8; SIG_SIZE] = sig_share.clone().into();
+        let decoded_sig_share = SignatureShare::try_from(&sig_bytes[..]);
+        assert_eq!(Ok(sig_share.clone()), decoded_sig_share);
+        let sig = sk_set.secret_key().sign("msg");
+        let sig_bytes: [u8; SIG_SIZE] = sig.clone().into();
+        assert_eq!(Ok(sig), Signature::try_from(&sig_bytes[..]));
+
+        let mut sk_bytes = Vec::new();
+        let repr = sk_set.secret_key().0.into_repr();
+        repr.write_be(&mut sk_bytes).expect("write secret key");
+        assert_eq!(Ok(sk_set.secret_key()), SecretKey::from_slice(&sk_bytes));
+        let not_in_field = DecodeError {
+            offset: 0,
+            field: "secret key",
+            reason: DecodeReason::NotInField,
+        };
+        let result = SecretKeyShare::from_slice(&[0xff; SK_SIZE]);
+        assert_eq!(Some(not_in_field), result.err());
+
+        #[cfg(feature = "encryption")]
+        {
+            let ct = pk_set.public_key().encrypt_with_rng(&mut rng, "secret");
+            assert_eq!(Ok(ct.clone()), Ciphertext::from_slice(&ct.to_bytes()));
+            let dec_share = sk_set.secret_key_share(1).decrypt_share_no_verify(&ct);
+            let decoded = DecryptionShare::from_slice(&dec_share.to_bytes());
+            assert_eq!(Ok(dec_share.clone()), decoded);
+            let decoded = DecryptionShare::from_bytes(dec_share.to_bytes());
+            assert_eq!(Ok(dec_share), decoded);
+        }
+
+        #[cfg(feature = "dkg")]
+        {
+            let commit = poly::BivarPoly::random(2, &mut rng).commitment();
+            let decoded = poly::BivarCommitment::from_slice(&commit.to_bytes());
+            assert_eq!(Ok(commit), decoded);
+        }
+
+        // Errors name the field and where it starts.
+        let bytes = pk_set.to_bytes();
+        let truncated = DecodeError {
+            offset: 2 * PK_SIZE,
+            field: "coefficient",
+            reason: DecodeReason::UnexpectedEnd(1),
+        };
+        let result = PublicKeySet::from_slice(&bytes[..(bytes.len() - 1)]);
+        assert_eq!(Err(truncated), result);
+        let empty = DecodeError {
+            offset: 0,
+            field: "coefficient",
+            reason: DecodeReason::UnexpectedEnd(PK_SIZE),
+        };
+        assert_eq!(Err(empty), Commitment::from_slice(&[]));
+        let mut bytes = sig_share.to_bytes().to_vec();
+        bytes.push(0);
+        let trailing = DecodeError {
+            offset: SIG_SIZE,
+            field: "SignatureShare",
+            reason: DecodeReason::TrailingBytes(1),
+        };
+        assert_eq!(Err(trailing), SignatureShare::from_slice(&bytes));
+
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        {
+            // The compression flag is missing.
+            let reason = PublicKey::from_slice(&[0; PK_SIZE])
+                .err()
+                .map(|err| err.reason);
+            assert_eq!(Some(DecodeReason::InvalidFlags), reason);
+        }
     }
 
     #[test]
@@ -1042,6 +2143,29 @@ mod tests {
         assert_eq!(sig, deser_sig);
     }
 
+    #[cfg(feature = "strict")]
+    #[test]
+    fn test_strict() {
+        use crate::error::DecodeReason;
+
+        let identity = SecretKey::default().public_key();
+        let reason = PublicKey::from_slice(&identity.to_bytes())
+            .err()
+            .map(|err| err.reason);
+        assert_eq!(Some(DecodeReason::Identity), reason);
+        let reason = PublicKey::from_uncompressed(&identity.to_uncompressed())
+            .err()
+            .map(|err| err.reason);
+        assert_eq!(Some(DecodeReason::Identity), reason);
+        let ser_pk = bincode::serialize(&identity).expect("serialize public key");
+        assert!(bincode::deserialize::<PublicKey>(&ser_pk).is_err());
+
+        // The master key of a public key set may still be the identity.
+        let pk_set = SecretKeySet::from(Poly::monomial(1)).public_keys();
+        let decoded = PublicKeySet::from_slice(&pk_set.to_bytes());
+        assert_eq!(Ok(pk_set), decoded);
+    }
+
     #[cfg(feature = "codec-support")]
     #[test]
     fn test_codec() {
@@ -1070,11 +2194,14 @@ mod tests {
         let sig_share = SignatureShare(sig);
         assert_codec!(sig_share, SignatureShare);
 
-        let cipher_text = pk.encrypt(b"cipher text");
-        assert_codec!(cipher_text, Ciphertext);
+        #[cfg(feature = "encryption")]
+        {
+            let cipher_text = pk.encrypt(b"cipher text");
+            assert_codec!(cipher_text, Ciphertext);
 
-        let dec_share: DecryptionShare = Standard.sample(&mut thread_rng());
-        assert_codec!(dec_share, DecryptionShare);
+            let dec_share: DecryptionShare = Standard.sample(&mut thread_rng());
+            assert_codec!(dec_share, DecryptionShare);
+        }
 
         let sk_set = SecretKeySet::random(3, &mut thread_rng());
         let pk_set = sk_set.public_keys();
@@ -1087,6 +2214,18 @@ mod tests {
         assert_eq!(<G2Affine as CurveAffine>::Compressed::size(), SIG_SIZE);
     }
 
+    #[test]
+    fn test_debug_is_redacted() {
+        let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let sk_share = sk_set.secret_key_share(0);
+        assert_eq!("SecretKey(...)", format!("{:?}", sk_set.secret_key()));
+        assert_eq!("SecretKeyShare(...)", format!("{:?}", sk_share));
+        let debug = format!("{:?}", sk_set);
+        assert_eq!("SecretKeySet { threshold: 2, poly: ... }", debug);
+        let debug = format!("{:?}", Poly::monomial(3));
+        assert_eq!("Poly { degree: 3, coeff: ... }", debug);
+    }
+
     #[test]
     fn test_zeroize() {
         let zero_sk = SecretKey::from_mut(&mut Fr::zero());
@@ -1096,6 +2235,13 @@ mod tests {
 
         sk.zeroize();
         assert_eq!(zero_sk, sk);
+
+        let mut sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let mut sk_share = sk_set.secret_key_share(0);
+        sk_share.zeroize();
+        assert_eq!(SecretKeyShare::default(), sk_share);
+        sk_set.zeroize();
+        assert_eq!(zero_sk, sk_set.secret_key());
     }
 
     #[test]
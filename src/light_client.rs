@@ -0,0 +1,189 @@
+//! Verification of committee handovers, for light clients.
+//!
+//! When a committee's keys are reshared or replaced, e.g. by a
+//! [`KeyRotationManager`](../rotation/struct.KeyRotationManager.html), the outgoing committee
+//! signs a [`Transition`] record: the new epoch number and the incoming committee's
+//! `PublicKeySet`. A client that only trusts the genesis `PublicKey` can then follow the chain of
+//! transitions with a [`LightClient`], checking each one against the key it already trusts, and
+//! end up with the current committee's key set, without having observed any of the protocols.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::{PublicKey, PublicKeySet, Signature};
+
+/// The domain separator of the signed transition messages.
+const DST: &[u8] = b"threshold_crypto committee transition";
+
+/// A handover to a new committee, signed by the previous one.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Transition {
+    epoch: u64,
+    pk_set: PublicKeySet,
+    signature: Signature,
+}
+
+impl Transition {
+    /// Creates a transition to the key set in the given epoch, with the previous committee's
+    /// signature of `Transition::message(epoch, &pk_set)`.
+    pub fn new(epoch: u64, pk_set: PublicKeySet, signature: Signature) -> Self {
+        Transition {
+            epoch,
+            pk_set,
+            signature,
+        }
+    }
+
+    /// Returns the message the previous committee has to sign for the transition.
+    pub fn message(epoch: u64, pk_set: &PublicKeySet) -> Vec<u8> {
+        let mut msg = DST.to_vec();
+        msg.extend(&epoch.to_be_bytes());
+        msg.extend(pk_set.to_bytes());
+        msg
+    }
+
+    /// Returns the epoch the new committee starts.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the new committee's key set.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the previous committee's signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Returns `true` if the transition was signed by the given key.
+    pub fn verify(&self, pk: &PublicKey) -> bool {
+        pk.verify(
+            &self.signature,
+            Transition::message(self.epoch, &self.pk_set),
+        )
+    }
+}
+
+/// A client following a committee's transitions from a trusted genesis key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LightClient {
+    epoch: u64,
+    genesis: PublicKey,
+    pk_set: Option<PublicKeySet>,
+}
+
+impl LightClient {
+    /// Creates a client that trusts the genesis key in epoch `0`.
+    pub fn new(genesis: PublicKey) -> Self {
+        LightClient {
+            epoch: 0,
+            genesis,
+            pk_set: None,
+        }
+    }
+
+    /// Returns the current epoch.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the currently trusted public key: the genesis key in epoch `0`, and the master key
+    /// of the current committee after that.
+    pub fn public_key(&self) -> PublicKey {
+        match self.pk_set {
+            Some(ref pk_set) => pk_set.public_key(),
+            None => self.genesis,
+        }
+    }
+
+    /// Returns the current committee's key set, or `None` before the first transition.
+    pub fn public_keys(&self) -> Option<&PublicKeySet> {
+        self.pk_set.as_ref()
+    }
+
+    /// Verifies the transition and moves to the next epoch.
+    ///
+    /// Returns `Error::InvalidTransition` with the transition's epoch if it is not for the next
+    /// epoch, or not signed by the current key.
+    pub fn apply(&mut self, transition: &Transition) -> Result<()> {
+        if Some(transition.epoch) != self.epoch.checked_add(1)
+            || !transition.verify(&self.public_key())
+        {
+            return Err(Error::InvalidTransition(transition.epoch));
+        }
+        self.epoch = transition.epoch;
+        self.pk_set = Some(transition.pk_set.clone());
+        Ok(())
+    }
+
+    /// Verifies a chain of transitions from the genesis key, and returns the client at its end.
+    pub fn verify_chain<'a, I>(genesis: PublicKey, transitions: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = &'a Transition>,
+    {
+        let mut client = LightClient::new(genesis);
+        for transition in transitions {
+            client.apply(transition)?;
+        }
+        Ok(client)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{SecretKey, SecretKeySet};
+
+    #[test]
+    fn test_chain() {
+        let mut rng = rand::thread_rng();
+        let genesis = SecretKey::random();
+        let sk_sets: Vec<SecretKeySet> =
+            (0..3).map(|_| SecretKeySet::random(1, &mut rng)).collect();
+        let mut transitions = Vec::new();
+        for (i, sk_set) in sk_sets.iter().enumerate() {
+            let epoch = i as u64 + 1;
+            let pk_set = sk_set.public_keys();
+            let msg = Transition::message(epoch, &pk_set);
+            let sig = match i {
+                0 => genesis.sign(&msg),
+                _ => {
+                    let prev = &sk_sets[i - 1];
+                    let shares: Vec<_> = (0..2)
+                        .map(|j| prev.secret_key_share(j).sign(&msg))
+                        .collect();
+                    prev.public_keys()
+                        .combine_signatures(shares.iter().enumerate())
+                        .unwrap()
+                }
+            };
+            transitions.push(Transition::new(epoch, pk_set, sig));
+        }
+
+        let client = LightClient::verify_chain(genesis.public_key(), &transitions).unwrap();
+        assert_eq!(3, client.epoch());
+        assert_eq!(Some(&sk_sets[2].public_keys()), client.public_keys());
+
+        // Transitions can't be skipped or replayed.
+        let skipped = [&transitions[0], &transitions[2]];
+        let result = LightClient::verify_chain(genesis.public_key(), skipped.iter().cloned());
+        assert_eq!(Some(Error::InvalidTransition(3)), result.err());
+        let mut client = LightClient::new(genesis.public_key());
+        client.apply(&transitions[0]).unwrap();
+        assert_eq!(
+            Err(Error::InvalidTransition(1)),
+            client.apply(&transitions[0])
+        );
+
+        // A transition must be signed by the previous committee.
+        let forged_sig = sk_sets[2]
+            .secret_key()
+            .sign(Transition::message(2, &sk_sets[2].public_keys()));
+        let forged = Transition::new(2, sk_sets[2].public_keys(), forged_sig);
+        assert_eq!(Err(Error::InvalidTransition(2)), client.apply(&forged));
+        assert_eq!(1, client.epoch());
+    }
+}
@@ -0,0 +1,107 @@
+//! Linear algebra over `Fr`, for reconstruction in secret sharing schemes beyond Shamir's.
+
+use ff::Field;
+
+use crate::secret::clear_fr;
+use crate::Fr;
+
+/// Solves `matrix * x == rhs` for `x`, by Gaussian elimination.
+///
+/// The matrix has one row per equation, all of length `n`, and at least `n` rows. Returns `None`
+/// unless the matrix has rank `n`, so that the solution is unique. If there are more equations
+/// than unknowns, the surplus ones are not checked.
+pub(crate) fn solve(mut matrix: Vec<Vec<Fr>>, mut rhs: Vec<Fr>) -> Option<Vec<Fr>> {
+    let n = matrix.first().map_or(0, Vec::len);
+    if eliminate(&mut matrix, &mut rhs).len() < n {
+        return None;
+    }
+    // The right hand side may contain secrets; the surplus equations' values are not returned.
+    for value in &mut rhs[n..] {
+        clear_fr(value);
+    }
+    rhs.truncate(n);
+    Some(rhs)
+}
+
+/// Returns some solution `x` of `matrix * x == rhs` with `n` unknowns, or `None` if there is none.
+///
+/// Unlike `solve`, this accepts any number of equations and doesn't require the solution to be
+/// unique.
+pub(crate) fn solve_any(mut matrix: Vec<Vec<Fr>>, mut rhs: Vec<Fr>, n: usize) -> Option<Vec<Fr>> {
+    let pivots = eliminate(&mut matrix, &mut rhs);
+    if rhs[pivots.len()..].iter().any(|value| !value.is_zero()) {
+        return None;
+    }
+    let mut x = vec![Fr::zero(); n];
+    for (row, col) in pivots.into_iter().enumerate() {
+        x[col] = rhs[row];
+    }
+    Some(x)
+}
+
+/// Transforms the system into reduced row echelon form, and returns the pivot column of each
+/// nonzero row. These come first, in order of their pivots.
+fn eliminate(matrix: &mut [Vec<Fr>], rhs: &mut [Fr]) -> Vec<usize> {
+    let n = matrix.first().map_or(0, Vec::len);
+    let mut pivots = Vec::new();
+    for col in 0..n {
+        let rank = pivots.len();
+        let pivot = match (rank..matrix.len()).find(|row| !matrix[*row][col].is_zero()) {
+            Some(pivot) => pivot,
+            None => continue,
+        };
+        matrix.swap(rank, pivot);
+        rhs.swap(rank, pivot);
+        let inv = matrix[rank][col].inverse().expect("pivot is nonzero");
+        for c in &mut matrix[rank][col..] {
+            c.mul_assign(&inv);
+        }
+        rhs[rank].mul_assign(&inv);
+        let (pivot_row, pivot_value) = (matrix[rank].clone(), rhs[rank]);
+        let rows = matrix.iter_mut().zip(rhs.iter_mut()).enumerate();
+        for (_, (row, value)) in rows.filter(|(i, (row, _))| *i != rank && !row[col].is_zero()) {
+            let factor = row[col];
+            for (c, p) in row[col..].iter_mut().zip(&pivot_row[col..]) {
+                let mut term = *p;
+                term.mul_assign(&factor);
+                c.sub_assign(&term);
+            }
+            let mut term = pivot_value;
+            term.mul_assign(&factor);
+            value.sub_assign(&term);
+        }
+        pivots.push(col);
+    }
+    pivots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::into_fr::IntoFr;
+
+    fn fr(x: u64) -> Fr {
+        x.into_fr()
+    }
+
+    #[test]
+    fn test_solve() {
+        // x + 2y == 5, 3x + y == 5, x + y == 3
+        let matrix = vec![vec![fr(1), fr(2)], vec![fr(3), fr(1)], vec![fr(1), fr(1)]];
+        let solution = solve(matrix, vec![fr(5), fr(5), fr(3)]);
+        assert_eq!(Some(vec![fr(1), fr(2)]), solution);
+
+        // The second equation is a multiple of the first.
+        let matrix = vec![vec![fr(1), fr(2)], vec![fr(2), fr(4)]];
+        assert_eq!(None, solve(matrix.clone(), vec![fr(5), fr(10)]));
+
+        // It has infinitely many solutions, unless the right hand side is inconsistent.
+        let x = solve_any(matrix.clone(), vec![fr(5), fr(10)], 2).expect("solvable");
+        let mut lhs = x[1];
+        lhs.double();
+        lhs.add_assign(&x[0]);
+        assert_eq!(fr(5), lhs);
+        assert_eq!(None, solve_any(matrix, vec![fr(5), fr(11)], 2));
+    }
+}
@@ -0,0 +1,451 @@
+//! Linear secret sharing for arbitrary monotone access structures.
+//!
+//! A [`Policy`] describes which sets of parties are authorized, as a tree of threshold gates over
+//! party IDs. It compiles into a [`SpanProgram`]: a matrix `M` whose rows are each owned by one
+//! party, such that a set of parties is authorized if and only if the target vector
+//! `(1, 0, ..., 0)` is a linear combination of their rows.
+//!
+//! To share a secret `s`, the dealer picks a random vector `r` with `r[0] == s`, and gives the
+//! owner of row `i` the share `M[i] * r`. An authorized set finds coefficients `c` with
+//! `sum(c[i] * M[i]) == (1, 0, ..., 0)`, and recovers `s` as `sum(c[i] * share[i])`. The
+//! commitment `r[j] * g` lets everyone verify their shares, like a polynomial commitment in
+//! Shamir's scheme.
+//!
+//! Threshold gates are compiled by the standard insertion method: The children of a `k`-of-`n`
+//! gate with vector `v` get the vectors `v | (x, x^2, ..., x^(k - 1))` for `x = 1, ..., n`, in
+//! `k - 1` new columns. A set satisfying `Policy::threshold(k, parties)` gets the same shares as
+//! in Shamir's scheme, up to indexing.
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+use std::fmt;
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::error::{Error, Result};
+use crate::into_fr::IntoFr;
+use crate::linalg;
+use crate::secret::clear_fr;
+use crate::{DebugDots, Fr, G1Affine, PublicKey, SecretKey, G1};
+
+/// A monotone access structure, as a tree of threshold gates.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Policy {
+    /// The party with the given ID.
+    Party(usize),
+    /// At least the given number of the subpolicies.
+    Threshold(usize, Vec<Policy>),
+}
+
+impl Policy {
+    /// Returns a policy satisfied by that party.
+    pub fn party(id: usize) -> Self {
+        Policy::Party(id)
+    }
+
+    /// Returns a policy satisfied if all of the subpolicies are.
+    pub fn and(policies: Vec<Policy>) -> Self {
+        Policy::Threshold(policies.len(), policies)
+    }
+
+    /// Returns a policy satisfied if any of the subpolicies is.
+    pub fn or(policies: Vec<Policy>) -> Self {
+        Policy::Threshold(1, policies)
+    }
+
+    /// Returns a policy satisfied if at least `k` of the subpolicies are.
+    pub fn threshold(k: usize, policies: Vec<Policy>) -> Self {
+        Policy::Threshold(k, policies)
+    }
+
+    /// Returns `true` if the given set of parties satisfies the policy.
+    pub fn is_satisfied(&self, parties: &BTreeSet<usize>) -> bool {
+        match self {
+            Policy::Party(id) => parties.contains(id),
+            Policy::Threshold(k, policies) => {
+                policies.iter().filter(|p| p.is_satisfied(parties)).count() >= *k
+            }
+        }
+    }
+
+    /// Compiles the policy into a span program.
+    ///
+    /// Returns `Error::InvalidAccessStructure` if a gate has no subpolicies, or its threshold is
+    /// zero or greater than their number.
+    pub fn compile(&self) -> Result<SpanProgram> {
+        let mut rows = Vec::new();
+        let mut cols = 1;
+        self.insert(vec![Fr::one()], &mut cols, &mut rows)?;
+        for (row, _) in &mut rows {
+            row.resize(cols, Fr::zero());
+        }
+        let (rows, owners) = rows.into_iter().unzip();
+        Ok(SpanProgram { rows, owners })
+    }
+
+    /// Adds the rows for this subpolicy, which is labeled with `vector`.
+    fn insert(
+        &self,
+        vector: Vec<Fr>,
+        cols: &mut usize,
+        rows: &mut Vec<(Vec<Fr>, usize)>,
+    ) -> Result<()> {
+        let (k, policies) = match self {
+            Policy::Party(id) => {
+                rows.push((vector, *id));
+                return Ok(());
+            }
+            Policy::Threshold(k, policies) => (*k, policies),
+        };
+        if k == 0 || k > policies.len() {
+            let msg = format!("a {}-of-{} gate", k, policies.len());
+            return Err(Error::InvalidAccessStructure(msg));
+        }
+        let (start, end) = (*cols, *cols + k - 1);
+        *cols = end;
+        for (j, policy) in policies.iter().enumerate() {
+            let x = (j as u64 + 1).into_fr();
+            let mut child = vector.clone();
+            child.resize(end, Fr::zero());
+            let mut x_pow = x;
+            for c in &mut child[start..] {
+                *c = x_pow;
+                x_pow.mul_assign(&x);
+            }
+            policy.insert(child, cols, rows)?;
+        }
+        Ok(())
+    }
+}
+
+/// A monotone span program: a share distribution matrix with an owner for each row.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SpanProgram {
+    #[serde(with = "crate::serde_impl::field_matrix")]
+    rows: Vec<Vec<Fr>>,
+    owners: Vec<usize>,
+}
+
+impl SpanProgram {
+    /// Creates a span program from a matrix and the owners of its rows.
+    ///
+    /// Returns `Error::InvalidAccessStructure` if the matrix is empty, the rows have different
+    /// lengths, or the number of owners doesn't match.
+    pub fn new(rows: Vec<Vec<Fr>>, owners: Vec<usize>) -> Result<Self> {
+        let cols = rows.first().map_or(0, Vec::len);
+        if cols == 0 || rows.iter().any(|row| row.len() != cols) || rows.len() != owners.len() {
+            let msg = "the matrix must be nonempty and rectangular, with one owner per row";
+            return Err(Error::InvalidAccessStructure(msg.to_string()));
+        }
+        Ok(SpanProgram { rows, owners })
+    }
+
+    /// Returns the number of rows, i.e. of shares.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Returns `true` if the program has no rows.
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Returns the number of columns, i.e. the length of the dealer's random vector.
+    pub fn columns(&self) -> usize {
+        self.rows[0].len()
+    }
+
+    /// Returns the party that owns the given row, or `None` if it doesn't exist.
+    pub fn owner(&self, row: usize) -> Option<usize> {
+        self.owners.get(row).cloned()
+    }
+
+    /// Returns `true` if the given set of parties is authorized.
+    pub fn is_authorized(&self, parties: &BTreeSet<usize>) -> bool {
+        let rows = (0..self.len()).filter(|i| parties.contains(&self.owners[*i]));
+        self.coefficients(&rows.collect::<Vec<_>>()).is_some()
+    }
+
+    /// Reconstructs the secret key from the shares.
+    ///
+    /// Returns `Error::InvalidShare` with the row of a share that doesn't belong to the program,
+    /// `Error::DuplicateEntry` if two shares are for the same row, and `Error::NotEnoughShares`
+    /// if their owners are not authorized. Invalid share values are not detected, and result in
+    /// a wrong key: Use [`Share::verify`] to check them first.
+    pub fn combine<B, I>(&self, shares: I) -> Result<SecretKey>
+    where
+        B: Borrow<Share>,
+        I: IntoIterator<Item = B>,
+    {
+        let shares: Vec<B> = shares.into_iter().collect();
+        let mut rows = BTreeSet::new();
+        for share in &shares {
+            let share = share.borrow();
+            if self.owner(share.row) != Some(share.party) {
+                return Err(Error::InvalidShare(share.row));
+            }
+            if !rows.insert(share.row) {
+                return Err(Error::DuplicateEntry);
+            }
+        }
+        let rows: Vec<usize> = shares.iter().map(|share| share.borrow().row).collect();
+        let coeffs = self.coefficients(&rows).ok_or(Error::NotEnoughShares)?;
+        let mut secret = Fr::zero();
+        for (share, coeff) in shares.iter().zip(coeffs) {
+            let mut term = share.borrow().value;
+            term.mul_assign(&coeff);
+            secret.add_assign(&term);
+            clear_fr(&mut term);
+        }
+        Ok(SecretKey::from_mut(&mut secret))
+    }
+
+    /// Returns coefficients that combine the given rows into the target vector `(1, 0, ..., 0)`,
+    /// or `None` if there are none.
+    fn coefficients(&self, rows: &[usize]) -> Option<Vec<Fr>> {
+        // The columns of the submatrix are the unknowns' coefficients in the equations.
+        let transposed = (0..self.columns())
+            .map(|col| rows.iter().map(|row| self.rows[*row][col]).collect())
+            .collect();
+        let mut target = vec![Fr::zero(); self.columns()];
+        target[0] = Fr::one();
+        linalg::solve_any(transposed, target, rows.len())
+    }
+
+    /// Returns the given row's scalar product with the vector.
+    fn dot<T: Copy, F: Fn(&mut T, Fr)>(&self, row: usize, vector: &[T], mul: F) -> Vec<T> {
+        self.rows[row]
+            .iter()
+            .zip(vector)
+            .map(|(m, v)| {
+                let mut term = *v;
+                mul(&mut term, *m);
+                term
+            })
+            .collect()
+    }
+}
+
+/// A dealer's secret for a span program: the random vector whose first entry is the secret.
+#[derive(Clone, PartialEq, Eq)]
+pub struct LsssKeySet {
+    program: SpanProgram,
+    vector: Vec<Fr>,
+}
+
+impl Zeroize for LsssKeySet {
+    fn zeroize(&mut self) {
+        self.vector.iter_mut().for_each(clear_fr);
+    }
+}
+
+impl Drop for LsssKeySet {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl fmt::Debug for LsssKeySet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LsssKeySet")
+            .field("program", &self.program)
+            .field("vector", &DebugDots)
+            .finish()
+    }
+}
+
+impl LsssKeySet {
+    /// Creates a random secret for the span program.
+    pub fn random<R: Rng>(program: SpanProgram, rng: &mut R) -> Self {
+        let vector = (0..program.columns()).map(|_| Fr::random(rng)).collect();
+        LsssKeySet { program, vector }
+    }
+
+    /// Returns the span program.
+    pub fn program(&self) -> &SpanProgram {
+        &self.program
+    }
+
+    /// Returns the shared secret key.
+    pub fn secret_key(&self) -> SecretKey {
+        let mut fr = self.vector[0];
+        SecretKey::from_mut(&mut fr)
+    }
+
+    /// Returns the public key corresponding to the shared secret key.
+    pub fn public_key(&self) -> PublicKey {
+        self.secret_key().public_key()
+    }
+
+    /// Returns the public commitment, for verifying the shares.
+    pub fn commitment(&self) -> Commitment {
+        let to_g1 = |c: &Fr| G1Affine::one().mul(*c);
+        Commitment {
+            coeff: self.vector.iter().map(to_g1).collect(),
+        }
+    }
+
+    /// Returns the shares of all rows owned by the given party.
+    pub fn shares(&self, party: usize) -> Vec<Share> {
+        let rows = (0..self.program.len()).filter(|i| self.program.owners[*i] == party);
+        rows.map(|row| {
+            let mut terms = self.program.dot(row, &self.vector, |t, m| t.mul_assign(&m));
+            let mut value = Fr::zero();
+            terms.iter().for_each(|term| value.add_assign(term));
+            terms.iter_mut().for_each(clear_fr);
+            Share { row, party, value }
+        })
+        .collect()
+    }
+}
+
+/// A commitment to the dealer's random vector.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Commitment {
+    #[serde(with = "crate::serde_impl::projective_vec")]
+    coeff: Vec<G1>,
+}
+
+impl Commitment {
+    /// Returns the public key corresponding to the shared secret key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.coeff[0])
+    }
+}
+
+/// A party's share for one row of a span program.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Share {
+    pub(super) row: usize,
+    pub(super) party: usize,
+    #[serde(with = "crate::serde_impl::field_element")]
+    pub(super) value: Fr,
+}
+
+impl Zeroize for Share {
+    fn zeroize(&mut self) {
+        clear_fr(&mut self.value);
+    }
+}
+
+impl Drop for Share {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl fmt::Debug for Share {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Share")
+            .field("row", &self.row)
+            .field("party", &self.party)
+            .field("value", &DebugDots)
+            .finish()
+    }
+}
+
+impl Share {
+    /// Returns the row of the span program.
+    pub fn row(&self) -> usize {
+        self.row
+    }
+
+    /// Returns the party that owns the row.
+    pub fn party(&self) -> usize {
+        self.party
+    }
+
+    /// Returns `true` if the share matches the dealer's commitment.
+    pub fn verify(&self, program: &SpanProgram, commitment: &Commitment) -> bool {
+        if program.owner(self.row) != Some(self.party)
+            || commitment.coeff.len() != program.columns()
+        {
+            return false;
+        }
+        let terms = program.dot(self.row, &commitment.coeff, |t, m| t.mul_assign(m));
+        let mut expected = G1::zero();
+        terms.iter().for_each(|term| expected.add_assign(term));
+        G1Affine::one().mul(self.value) == expected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn set(parties: &[usize]) -> BTreeSet<usize> {
+        parties.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_policy() {
+        // Party 0 and one of 1 and 2, or two of 3, 4 and 5.
+        let p = Policy::party;
+        let policy = Policy::or(vec![
+            Policy::and(vec![p(0), Policy::or(vec![p(1), p(2)])]),
+            Policy::threshold(2, vec![p(3), p(4), p(5)]),
+        ]);
+        let program = policy.compile().expect("valid policy");
+        assert_eq!(6, program.len());
+        for parties in &[
+            &[0, 1][..],
+            &[0, 2],
+            &[3, 5],
+            &[1, 2, 4],
+            &[0],
+            &[1, 2, 3],
+            &[],
+        ] {
+            let parties = set(parties);
+            assert_eq!(
+                policy.is_satisfied(&parties),
+                program.is_authorized(&parties)
+            );
+        }
+        assert!(program.is_authorized(&set(&[0, 2])));
+        assert!(!program.is_authorized(&set(&[1, 2, 3])));
+
+        assert!(Policy::threshold(3, vec![p(0), p(1)]).compile().is_err());
+        assert!(Policy::or(vec![]).compile().is_err());
+    }
+
+    #[test]
+    fn test_combine() {
+        let mut rng = rand::thread_rng();
+        let p = Policy::party;
+        let policy = Policy::or(vec![
+            Policy::and(vec![p(0), p(1)]),
+            Policy::threshold(2, vec![p(1), p(2), p(3)]),
+        ]);
+        let key_set = LsssKeySet::random(policy.compile().expect("valid policy"), &mut rng);
+        let program = key_set.program();
+        let commitment = key_set.commitment();
+        let expected = key_set.secret_key();
+        assert_eq!(commitment.public_key(), key_set.public_key());
+
+        let shares: Vec<Vec<Share>> = (0..4).map(|party| key_set.shares(party)).collect();
+        // Party 1 appears in both branches, so it owns two rows.
+        assert_eq!(2, shares[1].len());
+        assert!(shares
+            .iter()
+            .flatten()
+            .all(|s| s.verify(program, &commitment)));
+
+        for parties in &[[0, 1], [1, 2], [2, 3]] {
+            let subset = parties.iter().flat_map(|i| &shares[*i]);
+            assert_eq!(Ok(&expected), program.combine(subset).as_ref());
+        }
+        let unauthorized = program.combine(shares[0].iter().chain(&shares[2]));
+        assert_eq!(Err(Error::NotEnoughShares), unauthorized.map(|_| ()));
+        let duplicate = program.combine(shares[1].iter().chain(&shares[1]));
+        assert_eq!(Err(Error::DuplicateEntry), duplicate.map(|_| ()));
+
+        let mut forged = shares[2][0].clone();
+        forged.value.add_assign(&Fr::one());
+        assert!(!forged.verify(program, &commitment));
+    }
+}
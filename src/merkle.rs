@@ -0,0 +1,188 @@
+//! Merkle commitments to a committee's public key shares.
+//!
+//! Verifying a single participant's `PublicKeyShare` normally requires the whole `PublicKeySet`,
+//! whose size grows with the threshold. A [`MerkleTree`] over the first `n` public key shares
+//! has a 32-byte root instead: a verifier that knows the root can check a participant's share
+//! with a [`MerkleProof`] of `log2(n)` hashes.
+//!
+//! Leaves and inner nodes are SHA3-256 hashes with distinct prefixes, so that one can't be
+//! passed off as the other, and the root also commits to the number of leaves. An inner node
+//! with only one child is the child itself.
+
+use serde::{Deserialize, Serialize};
+
+use crate::util::sha3_256;
+use crate::{PublicKeySet, PublicKeyShare};
+
+/// The prefix of leaf hashes.
+const LEAF: u8 = 0;
+/// The prefix of inner node hashes.
+const NODE: u8 = 1;
+/// The prefix of the root hash.
+const ROOT: u8 = 2;
+
+/// A Merkle tree over the public key shares `0` to `n - 1`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MerkleTree {
+    /// The hashes of each level, starting with the leaves.
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    /// Creates a tree over the first `n` public key shares of the key set. `n` must be positive.
+    pub fn new(pk_set: &PublicKeySet, n: usize) -> Self {
+        assert!(n > 0, "a Merkle tree needs at least one leaf");
+        let leaves: Vec<[u8; 32]> = (0..n)
+            .map(|i| leaf_hash(i, &pk_set.public_key_share(i)))
+            .collect();
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let level = &levels[levels.len() - 1];
+            let next = level
+                .chunks(2)
+                .map(|pair| match pair {
+                    [left, right] => node_hash(left, right),
+                    [single] => *single,
+                    _ => unreachable!("chunks have one or two elements"),
+                })
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    /// Returns the number of leaves.
+    pub fn len(&self) -> usize {
+        self.levels[0].len()
+    }
+
+    /// Returns `false`: a tree always has at least one leaf.
+    pub fn is_empty(&self) -> bool {
+        self.levels[0].is_empty()
+    }
+
+    /// Returns the root hash, committing to the shares and their number.
+    pub fn root(&self) -> [u8; 32] {
+        root_hash(self.len(), &self.levels[self.levels.len() - 1][0])
+    }
+
+    /// Returns the proof that public key share `index` is in the tree, or `None` if `index` is
+    /// out of range.
+    pub fn proof(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.len() {
+            return None;
+        }
+        let mut pos = index;
+        let mut siblings = Vec::new();
+        for level in &self.levels[..self.levels.len() - 1] {
+            if let Some(sibling) = level.get(pos ^ 1) {
+                siblings.push(*sibling);
+            }
+            pos /= 2;
+        }
+        Some(MerkleProof {
+            index,
+            len: self.len(),
+            siblings,
+        })
+    }
+}
+
+/// A proof that a public key share is a leaf of a Merkle tree.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MerkleProof {
+    index: usize,
+    len: usize,
+    siblings: Vec<[u8; 32]>,
+}
+
+impl MerkleProof {
+    /// Returns the index of the share.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns `true` if `pk_share` is the public key share `index` in the tree with the given
+    /// root.
+    pub fn verify(&self, root: &[u8; 32], pk_share: &PublicKeyShare) -> bool {
+        if self.index >= self.len {
+            return false;
+        }
+        let mut hash = leaf_hash(self.index, pk_share);
+        let mut siblings = self.siblings.iter();
+        let (mut pos, mut width) = (self.index, self.len);
+        while width > 1 {
+            if pos ^ 1 < width {
+                let sibling = match siblings.next() {
+                    Some(sibling) => sibling,
+                    None => return false,
+                };
+                hash = if pos % 2 == 0 {
+                    node_hash(&hash, sibling)
+                } else {
+                    node_hash(sibling, &hash)
+                };
+            }
+            pos /= 2;
+            width = width.div_ceil(2);
+        }
+        siblings.next().is_none() && root_hash(self.len, &hash) == *root
+    }
+}
+
+/// Returns the hash of leaf `index`.
+fn leaf_hash(index: usize, pk_share: &PublicKeyShare) -> [u8; 32] {
+    let mut bytes = vec![LEAF];
+    bytes.extend(&(index as u64).to_be_bytes());
+    bytes.extend(pk_share.to_bytes().as_ref());
+    sha3_256(&bytes)
+}
+
+/// Returns the hash of an inner node.
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = vec![NODE];
+    bytes.extend(left);
+    bytes.extend(right);
+    sha3_256(&bytes)
+}
+
+/// Returns the root hash, given the number of leaves and the top node.
+fn root_hash(len: usize, top: &[u8; 32]) -> [u8; 32] {
+    let mut bytes = vec![ROOT];
+    bytes.extend(&(len as u64).to_be_bytes());
+    bytes.extend(top);
+    sha3_256(&bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_merkle_proofs() {
+        let mut rng = rand::thread_rng();
+        let pk_set = SecretKeySet::random(2, &mut rng).public_keys();
+        for n in 1..8 {
+            let tree = MerkleTree::new(&pk_set, n);
+            let root = tree.root();
+            for i in 0..n {
+                let proof = tree.proof(i).expect("index in range");
+                assert!(proof.verify(&root, &pk_set.public_key_share(i)));
+                assert!(!proof.verify(&root, &pk_set.public_key_share(i + 1)));
+            }
+            assert_eq!(None, tree.proof(n));
+        }
+
+        // The root commits to the number of leaves, and proofs to the index.
+        let tree = MerkleTree::new(&pk_set, 5);
+        let mut proof = tree.proof(4).unwrap();
+        assert_ne!(MerkleTree::new(&pk_set, 4).root(), tree.root());
+        proof.index = 3;
+        assert!(!proof.verify(&tree.root(), &pk_set.public_key_share(4)));
+        let mut proof = tree.proof(0).unwrap();
+        proof.siblings.push([0; 32]);
+        assert!(!proof.verify(&tree.root(), &pk_set.public_key_share(0)));
+    }
+}
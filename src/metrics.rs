@@ -0,0 +1,151 @@
+//! Counters of expensive operations, for cost models and performance regression tests.
+//!
+//! Once a [`Recorder`] is installed with [`set_recorder`], the crate reports every pairing, group
+//! exponentiation, i.e. multiplication of a curve point by a scalar, and hash to `G2`. The
+//! [`Counters`] recorder simply adds them up:
+//!
+//! ```
+//! use threshold_crypto::metrics::{self, Counters};
+//! use threshold_crypto::SecretKey;
+//!
+//! static COUNTERS: Counters = Counters::new();
+//! metrics::set_recorder(&COUNTERS).expect("no other recorder");
+//!
+//! let sk = SecretKey::random();
+//! let sig = sk.sign("Hello");
+//! let before = COUNTERS.snapshot();
+//! assert!(sk.public_key().verify(&sig, "Hello"));
+//! let cost = COUNTERS.snapshot().since(&before);
+//! assert_eq!((cost.pairings, cost.hashes_to_curve), (2, 1));
+//! ```
+//!
+//! This module is only available with the `metrics` feature. Without it, nothing is counted.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use crate::error::{Error, Result};
+
+/// An operation reported to the recorder.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Operation {
+    /// A pairing, including each pair in a multi-Miller loop.
+    Pairing,
+    /// A multiplication of an element of `G1` or `G2` by a scalar.
+    Exponentiation,
+    /// A hash of a message to `G2`.
+    HashToCurve,
+}
+
+/// Something that is notified of the operations the crate performs.
+pub trait Recorder: Send + Sync {
+    /// Records that the operation has been performed `count` times.
+    fn record(&self, op: Operation, count: u64);
+}
+
+static RECORDER: OnceLock<&'static dyn Recorder> = OnceLock::new();
+
+/// Installs the global recorder, or returns `Error::RecorderAlreadySet` if there already is one.
+pub fn set_recorder(recorder: &'static dyn Recorder) -> Result<()> {
+    RECORDER
+        .set(recorder)
+        .map_err(|_| Error::RecorderAlreadySet)
+}
+
+/// Reports the operation to the global recorder, if there is one.
+pub(crate) fn record(op: Operation, count: u64) {
+    if let Some(recorder) = RECORDER.get() {
+        recorder.record(op, count);
+    }
+}
+
+/// A recorder that counts the operations of each kind.
+#[derive(Debug, Default)]
+pub struct Counters {
+    pairings: AtomicU64,
+    exponentiations: AtomicU64,
+    hashes_to_curve: AtomicU64,
+}
+
+impl Counters {
+    /// Creates counters starting at zero.
+    pub const fn new() -> Self {
+        Counters {
+            pairings: AtomicU64::new(0),
+            exponentiations: AtomicU64::new(0),
+            hashes_to_curve: AtomicU64::new(0),
+        }
+    }
+
+    /// Returns the current counts.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            pairings: self.pairings.load(Ordering::Relaxed),
+            exponentiations: self.exponentiations.load(Ordering::Relaxed),
+            hashes_to_curve: self.hashes_to_curve.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Resets all counts to zero.
+    pub fn reset(&self) {
+        self.pairings.store(0, Ordering::Relaxed);
+        self.exponentiations.store(0, Ordering::Relaxed);
+        self.hashes_to_curve.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Recorder for Counters {
+    fn record(&self, op: Operation, count: u64) {
+        let counter = match op {
+            Operation::Pairing => &self.pairings,
+            Operation::Exponentiation => &self.exponentiations,
+            Operation::HashToCurve => &self.hashes_to_curve,
+        };
+        counter.fetch_add(count, Ordering::Relaxed);
+    }
+}
+
+/// The counts of a `Counters` recorder at some point in time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Snapshot {
+    /// The number of pairings.
+    pub pairings: u64,
+    /// The number of scalar multiplications in `G1` and `G2`.
+    pub exponentiations: u64,
+    /// The number of hashes to `G2`.
+    pub hashes_to_curve: u64,
+}
+
+impl Snapshot {
+    /// Returns the operations counted since the `earlier` snapshot.
+    pub fn since(&self, earlier: &Snapshot) -> Snapshot {
+        Snapshot {
+            pairings: self.pairings - earlier.pairings,
+            exponentiations: self.exponentiations - earlier.exponentiations,
+            hashes_to_curve: self.hashes_to_curve - earlier.hashes_to_curve,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_counters() {
+        let counters = Counters::new();
+        counters.record(Operation::Pairing, 2);
+        counters.record(Operation::Exponentiation, 3);
+        let before = counters.snapshot();
+        counters.record(Operation::Pairing, 1);
+        counters.record(Operation::HashToCurve, 1);
+        let expected = Snapshot {
+            pairings: 1,
+            exponentiations: 0,
+            hashes_to_curve: 1,
+        };
+        assert_eq!(expected, counters.snapshot().since(&before));
+        counters.reset();
+        assert_eq!(Snapshot::default(), counters.snapshot());
+    }
+}
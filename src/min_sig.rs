@@ -0,0 +1,270 @@
+//! The minimal-signature-size variant of BLS signatures.
+//!
+//! The crate's [`PublicKey`](../struct.PublicKey.html) is in `G1` and its
+//! [`Signature`](../struct.Signature.html) in `G2`: the minimal-public-key-size variant, with
+//! 48-byte keys and 96-byte signatures, as used e.g. by Ethereum 2.0 and Chia. This module has the
+//! opposite placement, with 96-byte public keys in `G2` and 48-byte signatures in `G1`, for
+//! ecosystems that store or send many signatures but few keys.
+//!
+//! The same `SecretKey`, `SecretKeyShare` and `SecretKeySet` sign in both variants; only the
+//! public keys differ. Messages are hashed to `G1` from their SHA3-256 digest, like `hash_g2`.
+//!
+//! ```
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let pk_set = sk_set.min_sig_public_keys();
+//! let shares: Vec<_> = (0..2)
+//!     .map(|i| sk_set.secret_key_share(i).sign_min_sig("msg"))
+//!     .collect();
+//! let sig = pk_set.combine_signatures(shares.iter().enumerate()).unwrap();
+//! assert!(pk_set.public_key().verify(&sig, "msg"));
+//! # #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+//! assert_eq!(48, sig.to_bytes().len());
+//! ```
+
+use std::fmt;
+
+use group::{CurveAffine, CurveProjective};
+use hex_fmt::HexFmt;
+use pairing::Engine;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+
+use crate::decode::decode;
+use crate::error::{DecodeResult, Result};
+use crate::util::sha3_256;
+use crate::{
+    interpolate, into_fr_plus_1, serde_impl, G1Affine, G2Affine, IntoFr, PEngine, SecretKey,
+    SecretKeySet, SecretKeyShare, G1, G2,
+};
+
+/// The size of a public key's representation in bytes.
+pub const PK_SIZE: usize = crate::SIG_SIZE;
+
+/// The size of a signature's representation in bytes.
+pub const SIG_SIZE: usize = crate::PK_SIZE;
+
+/// A public key in `G2`.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq)]
+pub struct PublicKey(#[serde(with = "serde_impl::projective")] G2);
+
+impl fmt::Debug for PublicKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uncomp = self.0.into_affine().into_uncompressed();
+        write!(f, "PublicKey({:0.10})", HexFmt(uncomp))
+    }
+}
+
+impl PublicKey {
+    /// Returns `true` if the signature matches the element of `G1`.
+    pub fn verify_g1<H: Into<G1Affine>>(&self, sig: &Signature, hash: H) -> bool {
+        count!(Pairing, 2);
+        PEngine::pairing(hash, self.0) == PEngine::pairing(sig.0, G2Affine::one())
+    }
+
+    /// Returns `true` if the signature matches the message.
+    ///
+    /// This is equivalent to `verify_g1(sig, hash_g1(msg))`.
+    pub fn verify<M: AsRef<[u8]>>(&self, sig: &Signature, msg: M) -> bool {
+        self.verify_g1(sig, hash_g1(msg))
+    }
+
+    /// Returns the key with the given representation, if valid.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "PublicKey", |r| {
+            Ok(PublicKey(r.point::<G2Affine>("public key")?))
+        })
+    }
+
+    /// Returns a byte string representation of the public key.
+    pub fn to_bytes(&self) -> [u8; PK_SIZE] {
+        let mut bytes = [0u8; PK_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_compressed().as_ref());
+        bytes
+    }
+}
+
+/// A public key share in `G2`.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Debug)]
+pub struct PublicKeyShare(PublicKey);
+
+impl PublicKeyShare {
+    /// Returns `true` if the signature share matches the message.
+    pub fn verify<M: AsRef<[u8]>>(&self, sig: &SignatureShare, msg: M) -> bool {
+        self.0.verify(&sig.0, msg)
+    }
+
+    /// Returns a byte string representation of the public key share.
+    pub fn to_bytes(&self) -> [u8; PK_SIZE] {
+        self.0.to_bytes()
+    }
+}
+
+/// A signature in `G1`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
+pub struct Signature(#[serde(with = "serde_impl::projective")] G1);
+
+impl fmt::Debug for Signature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let uncomp = self.0.into_affine().into_uncompressed();
+        write!(f, "Signature({:0.10})", HexFmt(uncomp))
+    }
+}
+
+impl Signature {
+    /// Returns the signature with the given representation, if valid.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "Signature", |r| {
+            Ok(Signature(r.point::<G1Affine>("signature")?))
+        })
+    }
+
+    /// Returns a byte string representation of the signature.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        let mut bytes = [0u8; SIG_SIZE];
+        bytes.copy_from_slice(self.0.into_affine().into_compressed().as_ref());
+        bytes
+    }
+}
+
+/// A signature share in `G1`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct SignatureShare(Signature);
+
+impl SignatureShare {
+    /// Returns a byte string representation of the signature share.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        self.0.to_bytes()
+    }
+}
+
+/// A public key and an associated set of public key shares, in `G2`.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct PublicKeySet {
+    /// The commitments to the secret polynomial's coefficients.
+    #[serde(with = "serde_impl::projective_vec")]
+    commit: Vec<G2>,
+}
+
+impl PublicKeySet {
+    /// Returns the threshold `t`: any set of `t + 1` signature shares can be combined into a full
+    /// signature.
+    pub fn threshold(&self) -> usize {
+        self.commit.len() - 1
+    }
+
+    /// Returns the public key.
+    pub fn public_key(&self) -> PublicKey {
+        PublicKey(self.commit[0])
+    }
+
+    /// Returns the `i`-th public key share.
+    pub fn public_key_share<T: IntoFr>(&self, i: T) -> PublicKeyShare {
+        let x = into_fr_plus_1(i);
+        let mut value = G2::zero();
+        for coeff in self.commit.iter().rev() {
+            value.mul_assign(x);
+            value.add_assign(coeff);
+        }
+        PublicKeyShare(PublicKey(value))
+    }
+
+    /// Combines the shares into a signature that can be verified with the main public key.
+    ///
+    /// Like `threshold_crypto::PublicKeySet::combine_signatures`, this doesn't check the shares'
+    /// validity, and only returns an error if there is a duplicate index or too few shares.
+    pub fn combine_signatures<'a, T, I>(&self, shares: I) -> Result<Signature>
+    where
+        I: IntoIterator<Item = (T, &'a SignatureShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &(share.0).0));
+        Ok(Signature(interpolate(self.threshold(), samples)?))
+    }
+}
+
+impl SecretKey {
+    /// Returns the public key in `G2`, for the minimal-signature-size variant.
+    pub fn min_sig_public_key(&self) -> PublicKey {
+        count!(Exponentiation, 1);
+        PublicKey(G2Affine::one().mul(self.0))
+    }
+
+    /// Signs the element of `G1`, in the minimal-signature-size variant.
+    pub fn sign_min_sig_g1<H: Into<G1Affine>>(&self, hash: H) -> Signature {
+        count!(Exponentiation, 1);
+        Signature(hash.into().mul(self.0))
+    }
+
+    /// Signs the message, in the minimal-signature-size variant.
+    pub fn sign_min_sig<M: AsRef<[u8]>>(&self, msg: M) -> Signature {
+        self.sign_min_sig_g1(hash_g1(msg))
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the public key share in `G2`, for the minimal-signature-size variant.
+    pub fn min_sig_public_key_share(&self) -> PublicKeyShare {
+        PublicKeyShare(self.0.min_sig_public_key())
+    }
+
+    /// Signs the message, in the minimal-signature-size variant.
+    pub fn sign_min_sig<M: AsRef<[u8]>>(&self, msg: M) -> SignatureShare {
+        SignatureShare(self.0.sign_min_sig(msg))
+    }
+}
+
+impl SecretKeySet {
+    /// Returns the public key set in `G2`, for the minimal-signature-size variant.
+    pub fn min_sig_public_keys(&self) -> PublicKeySet {
+        count!(Exponentiation, self.poly.coeff.len());
+        let commit = self.poly.coeff.iter().map(|c| G2Affine::one().mul(*c));
+        PublicKeySet {
+            commit: commit.collect(),
+        }
+    }
+}
+
+/// Returns a hash of the given message in `G1`.
+pub fn hash_g1<M: AsRef<[u8]>>(msg: M) -> G1 {
+    count!(HashToCurve, 1);
+    G1::random(&mut ChaChaRng::from_seed(sha3_256(msg.as_ref())))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_min_sig() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.min_sig_public_keys();
+        assert_eq!(2, pk_set.threshold());
+        let master = pk_set.public_key();
+        assert_eq!(sk_set.secret_key().min_sig_public_key(), master);
+        let shares: Vec<SignatureShare> = (0..4)
+            .map(|i| {
+                let sk_share = sk_set.secret_key_share(i);
+                let pk_share = pk_set.public_key_share(i);
+                assert_eq!(sk_share.min_sig_public_key_share(), pk_share);
+                let share = sk_share.sign_min_sig("msg");
+                assert!(pk_share.verify(&share, "msg"));
+                assert!(!pk_share.verify(&share, "other msg"));
+                share
+            })
+            .collect();
+        let sig = pk_set
+            .combine_signatures(shares.iter().enumerate().skip(1))
+            .expect("enough shares");
+        assert_eq!(sk_set.secret_key().sign_min_sig("msg"), sig);
+        assert!(master.verify(&sig, "msg"));
+        assert!(!master.verify(&sig, "other msg"));
+
+        let bytes = sig.to_bytes();
+        assert_eq!(Ok(sig), Signature::from_slice(&bytes));
+        assert_eq!(Ok(master), PublicKey::from_slice(&master.to_bytes()));
+    }
+}
@@ -0,0 +1,171 @@
+//! BIP39 mnemonic phrases for secret keys and key shares.
+//!
+//! `to_mnemonic` encodes a key's 32-byte big-endian representation as the entropy of a 24-word
+//! phrase from the BIP39 English wordlist, whose last word includes an 8-bit SHA-256 checksum, and
+//! `from_mnemonic` decodes it. The phrase is the key itself: Unlike a wallet's, it is not stretched
+//! into a seed with PBKDF2, so there is no passphrase, and the same key always has the same
+//! phrase.
+//!
+//! Keys are less than the group order, which is about `0.45 * 2^256`, so not every 24-word phrase
+//! encodes a key. In particular, `from_mnemonic` rejects most phrases generated by wallets. Use
+//! [`eth2::derive_master_sk`](../eth2/fn.derive_master_sk.html) to derive a key from such a seed
+//! instead.
+//!
+//! This module is only available with the `mnemonic` feature.
+//!
+//! ```
+//! use threshold_crypto::SecretKeySet;
+//! use threshold_crypto::SecretKeyShare;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let share = sk_set.secret_key_share(0);
+//! let phrase = share.to_mnemonic();
+//! assert_eq!(24, phrase.split(' ').count());
+//! assert_eq!(Ok(share), SecretKeyShare::from_mnemonic(&phrase));
+//! ```
+
+use ff::{PrimeField, PrimeFieldRepr};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, Result};
+use crate::util::digest::sha256;
+use crate::{SecretKey, SecretKeyShare, SK_SIZE};
+
+/// The BIP39 English wordlist, in order.
+const WORDLIST: &str = include_str!("mnemonic/english.txt");
+
+/// The number of words in a key's phrase: 256 bits of entropy and 8 bits of checksum, in 11-bit
+/// words.
+const WORDS: usize = 24;
+
+impl SecretKey {
+    /// Returns the key's 24-word BIP39 mnemonic phrase, with the words separated by spaces.
+    pub fn to_mnemonic(&self) -> Zeroizing<String> {
+        let mut bytes = Zeroizing::new([0u8; SK_SIZE]);
+        let mut repr = self.0.into_repr();
+        repr.write_be(&mut bytes[..])
+            .expect("the key has SK_SIZE bytes");
+        repr.0.zeroize();
+        encode(&bytes)
+    }
+
+    /// Decodes the key from its 24-word BIP39 mnemonic phrase.
+    ///
+    /// Returns `Error::InvalidMnemonic` if a word isn't in the wordlist, the checksum doesn't
+    /// match or the phrase doesn't encode a key.
+    pub fn from_mnemonic(phrase: &str) -> Result<Self> {
+        let bytes = decode(phrase)?;
+        SecretKey::from_slice(&bytes[..])
+            .map_err(|_| Error::InvalidMnemonic("the phrase doesn't encode a key".to_string()))
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the key share's 24-word BIP39 mnemonic phrase, with the words separated by
+    /// spaces.
+    pub fn to_mnemonic(&self) -> Zeroizing<String> {
+        self.0.to_mnemonic()
+    }
+
+    /// Decodes the key share from its 24-word BIP39 mnemonic phrase.
+    pub fn from_mnemonic(phrase: &str) -> Result<Self> {
+        SecretKey::from_mnemonic(phrase).map(SecretKeyShare)
+    }
+}
+
+/// Returns the phrase with the given entropy.
+fn encode(entropy: &[u8; SK_SIZE]) -> Zeroizing<String> {
+    let words: Vec<&str> = WORDLIST.lines().collect();
+    let checksum = sha256(&[&entropy[..]])[0];
+    let mut phrase = Zeroizing::new(String::new());
+    let mut acc = 0u32;
+    let mut bits = 0;
+    for byte in entropy.iter().chain(Some(&checksum)) {
+        acc = (acc << 8) | u32::from(*byte);
+        bits += 8;
+        if bits >= 11 {
+            bits -= 11;
+            if !phrase.is_empty() {
+                phrase.push(' ');
+            }
+            phrase.push_str(words[(acc >> bits) as usize & 0x7ff]);
+        }
+    }
+    acc.zeroize();
+    phrase
+}
+
+/// Returns the entropy of the phrase, or an error if it is malformed.
+fn decode(phrase: &str) -> Result<Zeroizing<[u8; SK_SIZE]>> {
+    let invalid = |msg: &str| Error::InvalidMnemonic(msg.to_string());
+    let words: Vec<&str> = WORDLIST.lines().collect();
+    if phrase.split_whitespace().count() != WORDS {
+        return Err(invalid("a key's phrase has 24 words"));
+    }
+    // The entropy, followed by the checksum.
+    let mut bytes = Zeroizing::new([0u8; SK_SIZE + 1]);
+    let mut acc = 0u32;
+    let mut bits = 0;
+    let mut pos = 0;
+    for word in phrase.split_whitespace() {
+        let index = words
+            .binary_search(&word)
+            .map_err(|_| invalid("unknown word"))?;
+        acc = (acc << 11) | index as u32;
+        bits += 11;
+        while bits >= 8 {
+            bits -= 8;
+            bytes[pos] = (acc >> bits) as u8;
+            pos += 1;
+        }
+    }
+    acc.zeroize();
+    let mut entropy = Zeroizing::new([0u8; SK_SIZE]);
+    entropy.copy_from_slice(&bytes[..SK_SIZE]);
+    if sha256(&[&entropy[..]])[0] != bytes[SK_SIZE] {
+        return Err(invalid("wrong checksum"));
+    }
+    Ok(entropy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mnemonic_vectors() {
+        // BIP39 test vectors with 256 bits of entropy.
+        let zero = ["abandon"; 23].join(" ") + " art";
+        assert_eq!(zero, *SecretKey::default().to_mnemonic());
+        assert_eq!(Ok(SecretKey::default()), SecretKey::from_mnemonic(&zero));
+        let phrase = "hamster diagram private dutch cause delay private meat slide toddler razor \
+                      book happy fancy gospel tennis maple dilemma loan word shrug inflict delay \
+                      length";
+        let sk = SecretKey::from_mnemonic(phrase).expect("valid phrase");
+        let entropy = "68a79eaca2324873eacc50cb9c6eca8cc68ea5d936f98787c60c7ebc74e6ce7c";
+        let bytes = crate::util::from_hex(entropy).expect("valid hex");
+        assert_eq!(Ok(sk.clone()), SecretKey::from_slice(&bytes));
+        assert_eq!(phrase, *sk.to_mnemonic());
+
+        // This phrase is valid, but its entropy is not less than the group order.
+        let phrase = "legal winner thank year wave sausage worth useful legal winner thank year \
+                      wave sausage worth useful legal winner thank year wave sausage worth title";
+        let err = Error::InvalidMnemonic("the phrase doesn't encode a key".to_string());
+        assert_eq!(Err(err), SecretKey::from_mnemonic(phrase));
+    }
+
+    #[test]
+    fn test_invalid_mnemonic() {
+        let share = SecretKeyShare::from_mnemonic(&SecretKey::random().to_mnemonic());
+        assert!(share.is_ok());
+        let invalid = |phrase: &str| {
+            matches!(
+                SecretKey::from_mnemonic(phrase),
+                Err(Error::InvalidMnemonic(_))
+            )
+        };
+        assert!(invalid(&["abandon"; 24].join(" ")));
+        assert!(invalid(&["abandon"; 12].join(" ")));
+        assert!(invalid(&(["abandon"; 23].join(" ") + " zzz")));
+    }
+}
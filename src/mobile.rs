@@ -0,0 +1,249 @@
+//! An API surface for UniFFI bindings, for share-based signing and threshold decryption in
+//! Kotlin and Swift.
+//!
+//! The types are exported with UniFFI's proc macros: [`KeyShare`] and [`Committee`] are objects
+//! with fallible constructors, [`IndexedShare`] is a record, and [`MobileError`] is an error enum.
+//! All other arguments and return values are byte vectors, integers and booleans. Values use
+//! their canonical `to_bytes` encodings.
+//!
+//! The crate sets up the UniFFI scaffolding itself, so the bindings are generated in library
+//! mode from the crate built as a `cdylib`:
+//!
+//! ```text
+//! cargo rustc --lib --release --features mobile --crate-type cdylib
+//! cargo run -p uniffi-bindgen -- generate --library target/release/libthreshold_crypto.so \
+//!     --language kotlin --language swift --out-dir target/uniffi
+//! ```
+//!
+//! This module is only available with the `mobile` feature.
+//!
+//! ```
+//! use threshold_crypto::mobile::{Committee, IndexedShare, KeyShare};
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let committee = Committee::new(sk_set.public_keys().to_bytes()).unwrap();
+//! let ciphertext = committee.encrypt(b"secret".to_vec());
+//! let mut shares = Vec::new();
+//! for index in 0..2 {
+//!     let share = sk_set.secret_key_share(index);
+//!     let key_share = KeyShare::from_secret_key_share(share);
+//!     let share = key_share.decrypt_share(ciphertext.clone()).unwrap();
+//!     shares.push(IndexedShare { index, share });
+//! }
+//! let plaintext = committee.decrypt(shares, ciphertext).unwrap();
+//! assert_eq!(b"secret".to_vec(), plaintext);
+//! ```
+
+use std::sync::Arc;
+
+use thiserror::Error;
+
+use crate::{Ciphertext, DecryptionShare, PublicKeySet, SecretKeyShare, Signature, SignatureShare};
+
+/// An error returned to the binding's caller.
+#[derive(Clone, Eq, PartialEq, Debug, Error, uniffi::Error)]
+pub enum MobileError {
+    /// An argument is not a valid encoding.
+    #[error("Invalid encoding: {message}")]
+    InvalidEncoding {
+        /// A description of the invalid value.
+        message: String,
+    },
+    /// The ciphertext is invalid.
+    #[error("Invalid ciphertext")]
+    InvalidCiphertext,
+    /// The shares could not be combined.
+    #[error("Failed to combine shares: {message}")]
+    Combine {
+        /// The reason.
+        message: String,
+    },
+}
+
+/// A signature or decryption share, together with the index of the key share that created it.
+#[derive(Clone, Eq, PartialEq, Debug, uniffi::Record)]
+pub struct IndexedShare {
+    /// The key share's index.
+    pub index: u64,
+    /// The encoded signature or decryption share.
+    pub share: Vec<u8>,
+}
+
+/// The device's secret key share.
+#[derive(Debug, uniffi::Object)]
+pub struct KeyShare(SecretKeyShare);
+
+impl KeyShare {
+    /// Wraps the key share.
+    pub fn from_secret_key_share(share: SecretKeyShare) -> Self {
+        KeyShare(share)
+    }
+}
+
+#[uniffi::export]
+impl KeyShare {
+    /// Decodes the key share from its 32-byte big-endian representation.
+    #[uniffi::constructor]
+    pub fn new(share: Vec<u8>) -> Result<Arc<Self>, MobileError> {
+        let share = SecretKeyShare::from_slice(&share).map_err(invalid)?;
+        Ok(Arc::new(KeyShare(share)))
+    }
+
+    /// Returns the encoded public key share.
+    pub fn public_key_share(&self) -> Vec<u8> {
+        self.0.public_key_share().to_bytes().to_vec()
+    }
+
+    /// Signs the message, and returns the encoded signature share.
+    pub fn sign(&self, msg: Vec<u8>) -> Vec<u8> {
+        self.0.sign(msg).to_bytes().to_vec()
+    }
+
+    /// Returns the encoded decryption share for the encoded ciphertext.
+    pub fn decrypt_share(&self, ciphertext: Vec<u8>) -> Result<Vec<u8>, MobileError> {
+        let ct = Ciphertext::from_slice(&ciphertext).map_err(invalid)?;
+        match self.0.decrypt_share(&ct) {
+            Some(share) => Ok(share.to_bytes().to_vec()),
+            None => Err(MobileError::InvalidCiphertext),
+        }
+    }
+}
+
+/// A committee's public key set.
+#[derive(Debug, uniffi::Object)]
+pub struct Committee(PublicKeySet);
+
+#[uniffi::export]
+impl Committee {
+    /// Decodes the committee's public key set.
+    #[uniffi::constructor]
+    pub fn new(pk_set: Vec<u8>) -> Result<Arc<Self>, MobileError> {
+        let pk_set = PublicKeySet::from_slice(&pk_set).map_err(invalid)?;
+        Ok(Arc::new(Committee(pk_set)))
+    }
+
+    /// Returns the encoded master public key.
+    pub fn public_key(&self) -> Vec<u8> {
+        self.0.public_key().to_bytes().to_vec()
+    }
+
+    /// Returns whether the signature share is valid for the key share's index and message.
+    pub fn verify_signature_share(
+        &self,
+        share: IndexedShare,
+        msg: Vec<u8>,
+    ) -> Result<bool, MobileError> {
+        let sig = SignatureShare::from_slice(&share.share).map_err(invalid)?;
+        Ok(self.0.public_key_share(share.index).verify(&sig, msg))
+    }
+
+    /// Combines the signature shares into a signature, and returns it encoded.
+    ///
+    /// The shares are not verified: Use `verify_signature_share` first if they could be invalid.
+    pub fn combine_signatures(&self, shares: Vec<IndexedShare>) -> Result<Vec<u8>, MobileError> {
+        let shares = decode_shares(&shares, SignatureShare::from_slice)?;
+        let indexed = shares.iter().map(|(i, share)| (*i, share));
+        let sig = self.0.combine_signatures(indexed).map_err(combine)?;
+        Ok(sig.to_bytes().to_vec())
+    }
+
+    /// Returns whether the signature is valid for the master public key and the message.
+    pub fn verify(&self, sig: Vec<u8>, msg: Vec<u8>) -> Result<bool, MobileError> {
+        let sig = Signature::from_slice(&sig).map_err(invalid)?;
+        Ok(self.0.public_key().verify(&sig, msg))
+    }
+
+    /// Encrypts the message to the committee, and returns the encoded ciphertext.
+    pub fn encrypt(&self, msg: Vec<u8>) -> Vec<u8> {
+        self.0.public_key().encrypt(msg).to_bytes()
+    }
+
+    /// Verifies the decryption shares and combines them into the plaintext.
+    pub fn decrypt(
+        &self,
+        shares: Vec<IndexedShare>,
+        ciphertext: Vec<u8>,
+    ) -> Result<Vec<u8>, MobileError> {
+        let ct = Ciphertext::from_slice(&ciphertext).map_err(invalid)?;
+        if !ct.verify() {
+            return Err(MobileError::InvalidCiphertext);
+        }
+        let shares = decode_shares(&shares, DecryptionShare::from_slice)?;
+        for (i, share) in &shares {
+            if !self
+                .0
+                .public_key_share(*i)
+                .verify_decryption_share(share, &ct)
+            {
+                return Err(MobileError::Combine {
+                    message: format!("invalid decryption share {}", i),
+                });
+            }
+        }
+        let indexed = shares.iter().map(|(i, share)| (*i, share));
+        self.0.decrypt(indexed, &ct).map_err(combine)
+    }
+}
+
+/// Decodes the shares, keeping their indices.
+fn decode_shares<T, E, F>(shares: &[IndexedShare], decode: F) -> Result<Vec<(u64, T)>, MobileError>
+where
+    F: Fn(&[u8]) -> Result<T, E>,
+    E: ToString,
+{
+    shares
+        .iter()
+        .map(|share| Ok((share.index, decode(&share.share).map_err(invalid)?)))
+        .collect()
+}
+
+/// Returns an `InvalidEncoding` error with the error's message.
+fn invalid<E: ToString>(err: E) -> MobileError {
+    MobileError::InvalidEncoding {
+        message: err.to_string(),
+    }
+}
+
+/// Returns a `Combine` error with the error's message.
+fn combine<E: ToString>(err: E) -> MobileError {
+    MobileError::Combine {
+        message: err.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_mobile_signing() {
+        let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+        let committee = Committee::new(sk_set.public_keys().to_bytes()).expect("valid key set");
+        let shares: Vec<_> = (3..5)
+            .map(|index| {
+                let key_share = KeyShare::from_secret_key_share(sk_set.secret_key_share(index));
+                let share = key_share.sign(b"msg".to_vec());
+                IndexedShare { index, share }
+            })
+            .collect();
+        assert_eq!(
+            Ok(true),
+            committee.verify_signature_share(shares[0].clone(), b"msg".to_vec())
+        );
+        let sig = committee
+            .combine_signatures(shares.clone())
+            .expect("combine");
+        assert_eq!(Ok(true), committee.verify(sig, b"msg".to_vec()));
+        assert!(matches!(
+            committee.combine_signatures(shares[..1].to_vec()),
+            Err(MobileError::Combine { .. })
+        ));
+        assert!(matches!(
+            KeyShare::new(vec![0xff; 32]),
+            Err(MobileError::InvalidEncoding { .. })
+        ));
+    }
+}
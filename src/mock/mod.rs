@@ -25,6 +25,12 @@ pub use self::ms8::Mersenne8;
 pub const PK_SIZE: usize = 4;
 /// The size of a signature's representation in bytes.
 pub const SIG_SIZE: usize = 4;
+/// The size of a secret key's representation in bytes.
+pub const SK_SIZE: usize = 4;
+/// The size of a key's uncompressed representation in bytes.
+pub const PK_UNCOMPRESSED_SIZE: usize = 4;
+/// The size of a signature's uncompressed representation in bytes.
+pub const SIG_UNCOMPRESSED_SIZE: usize = 4;
 
 /// A `pairing` Engine based on `Mersenne8` prime fields.
 #[derive(Clone, Debug)]
@@ -99,7 +105,7 @@ impl Engine for Mocktography {
         p.into().0 * q.into().0
     }
 
-    fn miller_loop<'a, I>(_i: I) -> Self::Fqk
+    fn miller_loop<'a, I>(i: I) -> Self::Fqk
     where
         I: IntoIterator<
             Item = &'a (
@@ -108,13 +114,14 @@ impl Engine for Mocktography {
             ),
         >,
     {
-        // Unused?
-        unimplemented!()
+        // The "pairing" is bilinear already, so the Miller loop just multiplies the pairings. The
+        // target group is written multiplicatively, which in `Mersenne8` is addition.
+        i.into_iter()
+            .fold(Mersenne8::zero(), |acc, (p, q)| acc + p.0 * q.0)
     }
 
-    fn final_exponentiation(_: &Self::Fqk) -> Option<Self::Fqk> {
-        // Unused?
-        unimplemented!()
+    fn final_exponentiation(f: &Self::Fqk) -> Option<Self::Fqk> {
+        Some(*f)
     }
 }
 
@@ -317,6 +324,15 @@ mod test {
         }
     }
 
+    #[test]
+    fn miller_loop() {
+        let (p0, q0) = (Ms8Affine(3u32.into()), Ms8Affine(5u32.into()));
+        let (p1, q1) = (Ms8Affine(7u32.into()), Ms8Affine(11u32.into()));
+        let product = Mocktography::miller_loop(&[(&p0, &q0), (&p1, &q1)]);
+        let expected = Mocktography::pairing(p0, q0) + Mocktography::pairing(p1, q1);
+        assert_eq!(Some(expected), Mocktography::final_exponentiation(&product));
+    }
+
     #[test]
     fn size() {
         assert_eq!(<Ms8Affine as EncodedPoint>::size(), PK_SIZE);
@@ -269,11 +269,14 @@ impl PrimeField for Mersenne8 {
     const NUM_BITS: u32 = 32;
     const CAPACITY: u32 = 30;
 
-    // Not actually used.
-    const S: u32 = 0;
+    // `MS8 - 1 = 2 * (2^30 - 1)`, with `2^30 - 1` odd.
+    const S: u32 = 1;
 
     #[inline]
     fn from_repr(v: Self::Repr) -> Result<Self, PrimeFieldDecodingError> {
+        if v.0 >= MS8 {
+            return Err(PrimeFieldDecodingError::NotInField(format!("{}", v.0)));
+        }
         Ok(v)
     }
 
@@ -300,8 +303,8 @@ impl PrimeField for Mersenne8 {
 
     #[inline]
     fn root_of_unity() -> Self {
-        // Still unclear what's supposed to be implemented here, missing at least an `n`?.
-        unimplemented!()
+        // The `2^S`-th root of unity, i.e. `-1`.
+        Mersenne8::new(MS8 - 1)
     }
 }
 
@@ -319,7 +322,13 @@ impl SqrtField for Mersenne8 {
     }
 
     fn sqrt(&self) -> Option<Self> {
-        unimplemented!() // FIXME, could use Tonelli-Shanks algorithm
+        // Since `MS8 % 4 == 3`, a square root of a quadratic residue `a` is `a^((MS8 + 1) / 4)`.
+        let root = (*self).pow((MS8 + 1) / 4);
+        if root * root == *self {
+            Some(root)
+        } else {
+            None
+        }
     }
 }
 
@@ -431,8 +440,8 @@ mod tests {
     // Some test functions contain long lists of assertions. Clippy thinks they are too complex.
     #![allow(clippy::cognitive_complexity)]
 
-    use super::{ext_euclid, modular_pow, Mersenne8};
-    use ff::Field;
+    use super::{ext_euclid, modular_pow, Mersenne8, MS8};
+    use ff::{Field, LegendreSymbol, PrimeField, SqrtField};
 
     #[test]
     fn ext_euclid_simple() {
@@ -811,4 +820,33 @@ mod tests {
         assert_eq!(g_fm, g);
         assert_eq!(h_fm, h);
     }
+    #[test]
+    fn sqrt() {
+        for &v in &[0u32, 1, 2, 4, 9, 123456789, MS8 - 1] {
+            let a = Mersenne8::new(v);
+            match a.sqrt() {
+                Some(root) => assert_eq!(root * root, a),
+                None => assert_eq!(a.legendre(), LegendreSymbol::QuadraticNonResidue),
+            }
+        }
+        // `-1` is not a square, since `MS8 % 4 == 3`.
+        assert_eq!(None, Mersenne8::new(MS8 - 1).sqrt());
+    }
+
+    #[test]
+    fn root_of_unity() {
+        let mut root = Mersenne8::root_of_unity();
+        assert_ne!(root, Mersenne8::one());
+        root.square();
+        assert_eq!(root, Mersenne8::one());
+    }
+
+    #[test]
+    fn from_repr() {
+        assert_eq!(
+            Mersenne8::new(5),
+            Mersenne8::from_repr(Mersenne8(5)).unwrap()
+        );
+        assert!(Mersenne8::from_repr(Mersenne8(MS8)).is_err());
+    }
 }
@@ -0,0 +1,259 @@
+//! Encryption of one message to several recipients.
+//!
+//! Encrypting a message to `n` public keys separately repeats the message and the ciphertext's two
+//! group elements `n` times. A `MultiCiphertext` instead shares the randomness `r` between all
+//! recipients: It contains `U = r * G` and `W = r * H(U, ...)` once, the message once, encrypted
+//! with a random symmetric key, and for each recipient `j` the key masked with a hash of
+//! `r * PK_j`. It is `PK_SIZE + SIG_SIZE + 32 * n` bytes longer than the message.
+//!
+//! A recipient can be a `PublicKey` or the master key of a `PublicKeySet`. The decryption shares
+//! `x_i * U` are the same for all recipients, and are verified against `W` as for a `Ciphertext`.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::multi_recipient::MultiCiphertext;
+//! use threshold_crypto::{SecretKey, SecretKeySet};
+//!
+//! let sk = SecretKey::random();
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let pk_set = sk_set.public_keys();
+//! let ct = MultiCiphertext::encrypt(&[sk.public_key(), pk_set.public_key()], "hello");
+//! assert_eq!(Some(b"hello".to_vec()), sk.decrypt_multi(&ct, 0));
+//! let shares: BTreeMap<_, _> = (0..2)
+//!     .map(|i| (i, sk_set.secret_key_share(i).decrypt_multi_share(&ct).unwrap()))
+//!     .collect();
+//! assert_eq!(b"hello".to_vec(), pk_set.decrypt_multi(&shares, &ct, 1).unwrap());
+//! ```
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use pairing::Engine;
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::error::{Error, Result};
+use crate::kem::{DemKeys, KEY_SIZE};
+use crate::secret::clear_fr;
+use crate::util::sha3_256;
+use crate::{
+    hash_g1_g2, interpolate, DecryptionShare, Fr, G1Affine, IntoFr, PEngine, PublicKey,
+    PublicKeySet, PublicKeyShare, SecretKey, SecretKeyShare, G1, G2,
+};
+
+/// The domain separator for the recipients' key masks.
+const MASK_DST: &[u8] = b"threshold_crypto multi-recipient key mask";
+
+/// The domain separator for the hash that `W` is a multiple of.
+const HASH_DST: &[u8] = b"threshold_crypto multi-recipient ciphertext";
+
+/// A message encrypted to several public keys.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MultiCiphertext {
+    #[serde(with = "crate::serde_impl::projective")]
+    u: G1,
+    keys: Vec<[u8; KEY_SIZE]>,
+    body: Vec<u8>,
+    #[serde(with = "crate::serde_impl::projective")]
+    w: G2,
+}
+
+impl MultiCiphertext {
+    /// Encrypts the message to the recipients, in the given order.
+    pub fn encrypt<M: AsRef<[u8]>>(recipients: &[PublicKey], msg: M) -> Self {
+        Self::encrypt_with_rng(&mut OsRng, recipients, msg)
+    }
+
+    /// Encrypts the message to the recipients, using the given random number generator.
+    pub fn encrypt_with_rng<R: Rng, M: AsRef<[u8]>>(
+        rng: &mut R,
+        recipients: &[PublicKey],
+        msg: M,
+    ) -> Self {
+        let mut key = Zeroizing::new([0u8; KEY_SIZE]);
+        rng.fill_bytes(&mut *key);
+        let mut r = Fr::random(rng);
+        count!(Exponentiation, recipients.len() + 2);
+        let u = G1Affine::one().mul(r);
+        let keys = recipients
+            .iter()
+            .enumerate()
+            .map(|(j, pk)| mask_key(&key, j, pk.0.into_affine().mul(r)))
+            .collect();
+        let mut body = msg.as_ref().to_vec();
+        DemKeys::new(&key).apply_keystream(0, &mut body);
+        let mut ct = MultiCiphertext {
+            u,
+            keys,
+            body,
+            w: G2::zero(),
+        };
+        ct.w = ct.hash().into_affine().mul(r);
+        clear_fr(&mut r);
+        ct
+    }
+
+    /// Returns the number of recipients.
+    pub fn recipients(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns `true` if this is a valid ciphertext. This check is necessary to prevent
+    /// chosen-ciphertext attacks.
+    pub fn verify(&self) -> bool {
+        count!(Pairing, 2);
+        PEngine::pairing(G1Affine::one(), self.w) == PEngine::pairing(self.u, self.hash())
+    }
+
+    /// Returns the hash of everything but `W`, in `G2`.
+    fn hash(&self) -> G2 {
+        let mut data = HASH_DST.to_vec();
+        data.extend(&(self.keys.len() as u64).to_be_bytes());
+        for key in &self.keys {
+            data.extend(key);
+        }
+        data.extend(&self.body);
+        hash_g1_g2(self.u, data)
+    }
+
+    /// Returns the plaintext for the given recipient, given `r * PK`, or `None` if there is no
+    /// such recipient.
+    fn open(&self, recipient: usize, shared: G1) -> Option<Vec<u8>> {
+        let masked = self.keys.get(recipient)?;
+        let key = Zeroizing::new(mask_key(masked, recipient, shared));
+        let mut plaintext = self.body.clone();
+        DemKeys::new(&key).apply_keystream(0, &mut plaintext);
+        Some(plaintext)
+    }
+}
+
+impl SecretKey {
+    /// Returns the decrypted message for the recipient with the given position, or `None` if the
+    /// ciphertext isn't valid or has no such recipient.
+    ///
+    /// Decrypting with another recipient's position yields garbage.
+    pub fn decrypt_multi(&self, ct: &MultiCiphertext, recipient: usize) -> Option<Vec<u8>> {
+        if !ct.verify() {
+            return None;
+        }
+        count!(Exponentiation, 1);
+        ct.open(recipient, ct.u.into_affine().mul(self.0))
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns a decryption share, or `None` if the ciphertext isn't valid.
+    pub fn decrypt_multi_share(&self, ct: &MultiCiphertext) -> Option<DecryptionShare> {
+        if !ct.verify() {
+            return None;
+        }
+        count!(Exponentiation, 1);
+        Some(DecryptionShare(ct.u.into_affine().mul((self.0).0)))
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns `true` if the decryption share matches the ciphertext.
+    pub fn verify_multi_decryption_share(
+        &self,
+        share: &DecryptionShare,
+        ct: &MultiCiphertext,
+    ) -> bool {
+        count!(Pairing, 2);
+        PEngine::pairing(share.0, ct.hash()) == PEngine::pairing((self.0).0, ct.w)
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the decryption shares to decrypt the message for the recipient with the given
+    /// position, which must be this key set's master key.
+    ///
+    /// Like `decrypt`, this doesn't verify the shares. Returns `Error::InvalidCiphertext` if there
+    /// is no such recipient.
+    pub fn decrypt_multi<'a, T, I>(
+        &self,
+        shares: I,
+        ct: &MultiCiphertext,
+        recipient: usize,
+    ) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        let samples = shares.into_iter().map(|(i, share)| (i, &share.0));
+        let shared = interpolate(self.commit.degree(), samples)?;
+        ct.open(recipient, shared).ok_or(Error::InvalidCiphertext)
+    }
+}
+
+/// Returns the key XORed with the recipient's mask, derived from its position and `r * PK`.
+fn mask_key(key: &[u8; KEY_SIZE], recipient: usize, shared: G1) -> [u8; KEY_SIZE] {
+    let mut data = MASK_DST.to_vec();
+    data.extend(&(recipient as u64).to_be_bytes());
+    data.extend(shared.into_affine().into_compressed().as_ref());
+    let mut masked = sha3_256(&data);
+    for (byte, key) in masked.iter_mut().zip(key) {
+        *byte ^= key;
+    }
+    masked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_multi_recipient_encryption() {
+        let mut rng = rand::thread_rng();
+        let sks: Vec<SecretKey> = (0..3).map(|_| rng.gen()).collect();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let mut recipients: Vec<PublicKey> = sks.iter().map(SecretKey::public_key).collect();
+        recipients.push(pk_set.public_key());
+        let msg = vec![7u8; 1_000];
+        let ct = MultiCiphertext::encrypt_with_rng(&mut rng, &recipients, &msg);
+        assert!(ct.verify());
+        assert_eq!(4, ct.recipients());
+
+        for (j, sk) in sks.iter().enumerate() {
+            assert_eq!(Some(msg.clone()), sk.decrypt_multi(&ct, j));
+            assert_ne!(Some(msg.clone()), sk.decrypt_multi(&ct, (j + 1) % 3));
+        }
+        assert_eq!(None, sks[0].decrypt_multi(&ct, 4));
+
+        let shares: BTreeMap<usize, DecryptionShare> = (0..3)
+            .map(|i| {
+                let share = sk_set
+                    .secret_key_share(i)
+                    .decrypt_multi_share(&ct)
+                    .expect("valid ciphertext");
+                let pk_share = pk_set.public_key_share(i);
+                assert!(pk_share.verify_multi_decryption_share(&share, &ct));
+                assert!(!pk_set
+                    .public_key_share(i + 1)
+                    .verify_multi_decryption_share(&share, &ct));
+                (i, share)
+            })
+            .collect();
+        assert_eq!(Ok(msg), pk_set.decrypt_multi(&shares, &ct, 3));
+
+        // Any change to the ciphertext is detected.
+        let mut tampered = ct.clone();
+        tampered.body[0] ^= 1;
+        assert!(!tampered.verify());
+        assert_eq!(None, sks[0].decrypt_multi(&tampered, 0));
+        let mut tampered = ct;
+        tampered.keys.swap(0, 1);
+        assert!(!tampered.verify());
+        assert_eq!(
+            None,
+            sk_set.secret_key_share(0).decrypt_multi_share(&tampered)
+        );
+    }
+}
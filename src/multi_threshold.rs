@@ -0,0 +1,294 @@
+//! Key sets with different thresholds for different operations.
+//!
+//! Some deployments want a low threshold for routine operations and a higher one for sensitive
+//! ones, e.g. 2-of-5 for signing and 4-of-5 for approving a key export. Instead of running
+//! separate key generations, [`OperationKeySet::derive`] reshares the master secret of one key
+//! set with the operation's threshold. All derived key sets have the same master public key.
+//!
+//! Each operation is a type implementing [`Operation`], and all keys, shares and signatures are
+//! tagged with it, so that shares of one operation can't be combined under another one by
+//! mistake. To rule out mixing shares at the protocol level, too, messages are always signed
+//! together with the operation's name: a signature for operation `O` is a plain signature of
+//! `operation_message::<O>(msg)` by the master key.
+//!
+//! ```
+//! use threshold_crypto::multi_threshold::{Operation, OperationKeySet};
+//! use threshold_crypto::SecretKeySet;
+//!
+//! #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+//! struct KeyExport;
+//!
+//! impl Operation for KeyExport {
+//!     const NAME: &'static str = "key export";
+//! }
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let export_set = OperationKeySet::<KeyExport>::derive(&sk_set, 3, &mut rng);
+//! let pk_set = export_set.public_keys();
+//! assert_eq!(sk_set.public_keys().public_key(), pk_set.public_key());
+//!
+//! let msg = "export key 42";
+//! let shares: Vec<_> = (0..4).map(|i| export_set.secret_key_share(i).sign(msg)).collect();
+//! let sig = pk_set.combine_signatures(shares.iter().enumerate()).unwrap();
+//! assert!(pk_set.verify(&sig, msg));
+//! ```
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::Result;
+use crate::into_fr::IntoFr;
+use crate::poly::Poly;
+use crate::{
+    PublicKey, PublicKeySet, PublicKeyShare, SecretKeySet, SecretKeyShare, Signature,
+    SignatureShare,
+};
+
+/// An operation with its own threshold.
+pub trait Operation: Copy + fmt::Debug + Eq {
+    /// The operation's name. It is signed together with every message, so it must be unique.
+    const NAME: &'static str;
+}
+
+/// Returns the message that is actually signed for the operation `O`.
+pub fn operation_message<O: Operation, M: AsRef<[u8]>>(msg: M) -> Vec<u8> {
+    let mut data = b"threshold_crypto operation ".to_vec();
+    data.extend(&(O::NAME.len() as u64).to_be_bytes());
+    data.extend(O::NAME.as_bytes());
+    data.extend(msg.as_ref());
+    data
+}
+
+/// A secret key set for the operation `O`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct OperationKeySet<O> {
+    sk_set: SecretKeySet,
+    _op: PhantomData<O>,
+}
+
+impl<O: Operation> OperationKeySet<O> {
+    /// Reshares the master secret of `sk_set` with the given threshold: Any `threshold + 1` of
+    /// the returned shares can sign for the operation.
+    pub fn derive<R: Rng>(sk_set: &SecretKeySet, threshold: usize, rng: &mut R) -> Self {
+        let mut poly = Poly::random(threshold, rng);
+        poly.coeff[0] = sk_set.poly.coeff[0];
+        OperationKeySet {
+            sk_set: SecretKeySet::from(poly),
+            _op: PhantomData,
+        }
+    }
+
+    /// Returns the operation's threshold.
+    pub fn threshold(&self) -> usize {
+        self.sk_set.threshold()
+    }
+
+    /// Returns the `i`-th secret key share.
+    pub fn secret_key_share<T: IntoFr>(&self, i: T) -> OperationKeyShare<O> {
+        OperationKeyShare {
+            share: self.sk_set.secret_key_share(i),
+            _op: PhantomData,
+        }
+    }
+
+    /// Returns the corresponding public key set.
+    pub fn public_keys(&self) -> OperationPublicKeys<O> {
+        OperationPublicKeys {
+            pk_set: self.sk_set.public_keys(),
+            _op: PhantomData,
+        }
+    }
+}
+
+/// A secret key share for the operation `O`.
+#[derive(Clone, PartialEq, Eq)]
+pub struct OperationKeyShare<O> {
+    share: SecretKeyShare,
+    _op: PhantomData<O>,
+}
+
+impl<O> fmt::Debug for OperationKeyShare<O> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("OperationKeyShare")
+            .field(&self.share)
+            .finish()
+    }
+}
+
+impl<O: Operation> OperationKeyShare<O> {
+    /// Wraps a share of a key set derived for the operation, e.g. after it was stored.
+    pub fn new(share: SecretKeyShare) -> Self {
+        OperationKeyShare {
+            share,
+            _op: PhantomData,
+        }
+    }
+
+    /// Returns the untagged secret key share.
+    pub fn secret_key_share(&self) -> &SecretKeyShare {
+        &self.share
+    }
+
+    /// Returns the public key share.
+    pub fn public_key_share(&self) -> PublicKeyShare {
+        self.share.public_key_share()
+    }
+
+    /// Signs the message for the operation.
+    pub fn sign<M: AsRef<[u8]>>(&self, msg: M) -> OperationSignatureShare<O> {
+        OperationSignatureShare {
+            share: self.share.sign(operation_message::<O, _>(msg)),
+            _op: PhantomData,
+        }
+    }
+}
+
+/// A signature share for the operation `O`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound = "")]
+pub struct OperationSignatureShare<O> {
+    share: SignatureShare,
+    #[serde(skip)]
+    _op: PhantomData<O>,
+}
+
+impl<O> OperationSignatureShare<O> {
+    /// Returns the untagged signature share.
+    pub fn signature_share(&self) -> &SignatureShare {
+        &self.share
+    }
+}
+
+/// A public key set for the operation `O`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(bound = "")]
+pub struct OperationPublicKeys<O> {
+    pk_set: PublicKeySet,
+    #[serde(skip)]
+    _op: PhantomData<O>,
+}
+
+impl<O: Operation> OperationPublicKeys<O> {
+    /// Wraps the public key set of a key set derived for the operation.
+    pub fn new(pk_set: PublicKeySet) -> Self {
+        OperationPublicKeys {
+            pk_set,
+            _op: PhantomData,
+        }
+    }
+
+    /// Returns the untagged public key set.
+    pub fn public_key_set(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the operation's threshold.
+    pub fn threshold(&self) -> usize {
+        self.pk_set.threshold()
+    }
+
+    /// Returns the master public key, which is the same for all operations.
+    pub fn public_key(&self) -> PublicKey {
+        self.pk_set.public_key()
+    }
+
+    /// Returns the `i`-th public key share.
+    pub fn public_key_share<T: IntoFr>(&self, i: T) -> PublicKeyShare {
+        self.pk_set.public_key_share(i)
+    }
+
+    /// Returns `true` if the signature share is valid for share `i` and the message.
+    pub fn verify_share<T: IntoFr, M: AsRef<[u8]>>(
+        &self,
+        i: T,
+        share: &OperationSignatureShare<O>,
+        msg: M,
+    ) -> bool {
+        self.public_key_share(i)
+            .verify(&share.share, operation_message::<O, _>(msg))
+    }
+
+    /// Combines the signature shares of at least `threshold + 1` participants.
+    pub fn combine_signatures<'a, T, I>(&self, shares: I) -> Result<Signature>
+    where
+        I: IntoIterator<Item = (T, &'a OperationSignatureShare<O>)>,
+        T: IntoFr,
+        O: 'a,
+    {
+        let shares = shares.into_iter().map(|(i, share)| (i, &share.share));
+        self.pk_set.combine_signatures(shares)
+    }
+
+    /// Returns `true` if the signature was produced for the operation and the message.
+    pub fn verify<M: AsRef<[u8]>>(&self, sig: &Signature, msg: M) -> bool {
+        self.public_key()
+            .verify(sig, operation_message::<O, _>(msg))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::error::Error;
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Routine;
+
+    impl Operation for Routine {
+        const NAME: &'static str = "routine";
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    struct Export;
+
+    impl Operation for Export {
+        const NAME: &'static str = "export";
+    }
+
+    #[test]
+    fn test_operations() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let routine = OperationKeySet::<Routine>::derive(&sk_set, 1, &mut rng);
+        let export = OperationKeySet::<Export>::derive(&sk_set, 3, &mut rng);
+        let (routine_pks, export_pks) = (routine.public_keys(), export.public_keys());
+        assert_eq!(routine_pks.public_key(), export_pks.public_key());
+        assert_eq!(3, export_pks.threshold());
+
+        let msg = "msg";
+        let routine_shares: Vec<_> = (0..4)
+            .map(|i| routine.secret_key_share(i).sign(msg))
+            .collect();
+        let export_shares: Vec<_> = (0..4)
+            .map(|i| export.secret_key_share(i).sign(msg))
+            .collect();
+        assert!(routine_pks.verify_share(0, &routine_shares[0], msg));
+        let sig = routine_pks
+            .combine_signatures(routine_shares[..2].iter().enumerate())
+            .unwrap();
+        assert!(routine_pks.verify(&sig, msg));
+        assert!(!export_pks.verify(&sig, msg));
+        assert!(!sk_set.public_keys().public_key().verify(&sig, msg));
+
+        // The export key set needs four shares.
+        let sig = export_pks.combine_signatures(export_shares[..3].iter().enumerate());
+        assert_eq!(Err(Error::NotEnoughShares), sig);
+        let sig = export_pks
+            .combine_signatures(export_shares.iter().enumerate())
+            .unwrap();
+        assert!(export_pks.verify(&sig, msg));
+
+        // Untagged shares of different operations don't combine to a valid signature.
+        let untagged = export_shares[0].signature_share().clone();
+        let mixed = OperationSignatureShare::<Routine> {
+            share: untagged,
+            _op: PhantomData,
+        };
+        assert!(!routine_pks.verify_share(0, &mixed, msg));
+    }
+}
@@ -0,0 +1,242 @@
+//! Plain BLS multisignatures.
+//!
+//! Besides threshold signatures, BLS supports aggregation: The sum of signatures by several keys
+//! on the same message is a valid signature for the sum of the keys. That is an `n`-of-`n`
+//! multisignature of the same size as a single one, that can be verified with two pairings.
+//!
+//! Aggregating public keys is only safe if each of them is known to belong to a signer that has
+//! its secret key: Otherwise, an attacker can choose its key depending on the honest signers'
+//! keys, so that the aggregate is a key the attacker controls alone. A proof of possession,
+//! created with `SecretKey::sign_pop` and checked with `PublicKey::verify_pop`, shows that: It is
+//! a signature of the public key itself, under a hash that is independent of message signatures,
+//! so that a signature of any message can't be used as a proof. `aggregate_with_pop` and
+//! `verify_multisig_with_pop` only accept keys with valid proofs.
+//!
+//! Signatures of distinct messages can be aggregated, too, without that restriction. The
+//! aggregate is verified with one pairing per message, plus one, in a single product of pairings.
+//!
+//! ```
+//! use threshold_crypto::{multisig, PublicKey, SecretKey, Signature};
+//!
+//! let sec_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+//! let pub_keys: Vec<PublicKey> = sec_keys.iter().map(SecretKey::public_key).collect();
+//! let sigs: Vec<Signature> = sec_keys.iter().map(|sk| sk.sign("msg")).collect();
+//! let sig = Signature::aggregate(&sigs);
+//! assert!(multisig::verify_multisig(&pub_keys, "msg", &sig));
+//!
+//! let keys: Vec<_> = sec_keys.iter().map(|sk| (sk.public_key(), sk.sign_pop())).collect();
+//! assert!(multisig::verify_multisig_with_pop(&keys, "msg", &sig));
+//! ```
+
+use std::borrow::Borrow;
+use std::collections::BTreeSet;
+
+use group::CurveProjective;
+use pairing::Engine;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+use crate::batch::multi_pairing;
+use crate::util::sha3_256;
+use crate::{hash_g2_digest, PEngine, PublicKey, SecretKey, Signature, G1, G2};
+
+/// The domain separator for proofs of possession.
+const POP_DST: &[u8] = b"threshold_crypto proof of possession";
+
+/// The ChaCha stream used to hash public keys to `G2` for proofs of possession. Messages are
+/// hashed with stream 0, so no message hashes to the same point as a public key.
+const POP_STREAM: u64 = 1;
+
+impl SecretKey {
+    /// Returns a proof of possession: a signature of the public key, showing that its owner
+    /// knows the secret key.
+    pub fn sign_pop(&self) -> Signature {
+        self.sign_g2(hash_pop(&self.public_key()))
+    }
+}
+
+impl Signature {
+    /// Returns the sum of the signatures.
+    ///
+    /// If they are all signatures of the same message, the result is a signature of it for the
+    /// aggregate of the public keys.
+    pub fn aggregate<B, I>(sigs: I) -> Signature
+    where
+        B: Borrow<Signature>,
+        I: IntoIterator<Item = B>,
+    {
+        let mut sum = G2::zero();
+        for sig in sigs {
+            sum.add_assign(&sig.borrow().0);
+        }
+        Signature(sum)
+    }
+}
+
+impl PublicKey {
+    /// Returns the sum of the public keys.
+    ///
+    /// The keys must be known to belong to their owners, e.g. by a proof of possession.
+    pub fn aggregate<B, I>(pub_keys: I) -> PublicKey
+    where
+        B: Borrow<PublicKey>,
+        I: IntoIterator<Item = B>,
+    {
+        let mut sum = G1::zero();
+        for pk in pub_keys {
+            sum.add_assign(&pk.borrow().0);
+        }
+        PublicKey(sum)
+    }
+
+    /// Returns `true` if the signature is a proof of possession of the secret key.
+    pub fn verify_pop(&self, pop: &Signature) -> bool {
+        self.verify_g2(pop, hash_pop(self))
+    }
+}
+
+/// Returns the aggregate of the public keys, or `None` if any of the proofs of possession is
+/// invalid, or there are no keys.
+pub fn aggregate_with_pop(keys: &[(PublicKey, Signature)]) -> Option<PublicKey> {
+    if keys.is_empty() || !keys.iter().all(|(pk, pop)| pk.verify_pop(pop)) {
+        return None;
+    }
+    Some(PublicKey::aggregate(keys.iter().map(|(pk, _)| pk)))
+}
+
+/// Returns `true` if each public key has a valid proof of possession, and the signature is the
+/// aggregate of signatures of the message by all of them.
+pub fn verify_multisig_with_pop<M: AsRef<[u8]>>(
+    keys: &[(PublicKey, Signature)],
+    msg: M,
+    sig: &Signature,
+) -> bool {
+    aggregate_with_pop(keys).is_some_and(|pk| pk.verify(sig, msg))
+}
+
+/// Returns `true` if the signature is the aggregate of signatures of the message by all of the
+/// given public keys.
+///
+/// Returns `false` if there are no public keys. The keys must be known to belong to their owners,
+/// e.g. by a proof of possession.
+pub fn verify_multisig<M: AsRef<[u8]>>(pub_keys: &[PublicKey], msg: M, sig: &Signature) -> bool {
+    !pub_keys.is_empty() && PublicKey::aggregate(pub_keys).verify(sig, msg)
+}
+
+/// Returns `true` if the signature is the aggregate of signatures of each message by its public
+/// key.
+///
+/// Returns `false` if there are no messages, or if any of them are equal: Aggregates of
+/// signatures of the same message must be verified with `verify_multisig` instead.
+pub fn verify_aggregate<M: AsRef<[u8]>>(items: &[(PublicKey, M)], sig: &Signature) -> bool {
+    let _span = span!("verify_aggregate", messages = items.len());
+    let mut digests = BTreeSet::new();
+    let mut pairs = Vec::with_capacity(items.len());
+    for (pk, msg) in items {
+        let digest = sha3_256(msg.as_ref());
+        if !digests.insert(digest) {
+            return false;
+        }
+        pairs.push((pk.0, hash_g2_digest(&digest)));
+    }
+    count!(Pairing, items.len() + 1);
+    !pairs.is_empty() && multi_pairing(pairs) == Some(PEngine::pairing(G1::one(), sig.0))
+}
+
+/// Returns the hash of the public key in `G2`, for proofs of possession.
+fn hash_pop(pk: &PublicKey) -> G2 {
+    let mut input = POP_DST.to_vec();
+    input.extend(pk.to_bytes().as_ref());
+    let mut rng = ChaChaRng::from_seed(sha3_256(&input));
+    rng.set_stream(POP_STREAM);
+    count!(HashToCurve, 1);
+    G2::random(&mut rng)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::hash_g2;
+
+    #[test]
+    fn test_multisig() {
+        let sec_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+        let pub_keys: Vec<PublicKey> = sec_keys.iter().map(SecretKey::public_key).collect();
+        let sigs: Vec<Signature> = sec_keys.iter().map(|sk| sk.sign("msg")).collect();
+        let sig = Signature::aggregate(&sigs);
+        assert!(verify_multisig(&pub_keys, "msg", &sig));
+        assert!(!verify_multisig(&pub_keys, "other msg", &sig));
+        assert!(!verify_multisig(&pub_keys[..2], "msg", &sig));
+        let empty = Signature::aggregate(Vec::<Signature>::new());
+        assert!(!verify_multisig(&[], "msg", &empty));
+        let partial = Signature::aggregate(&sigs[1..]);
+        assert!(verify_multisig(&pub_keys[1..], "msg", &partial));
+        assert_eq!(pub_keys[0], PublicKey::aggregate(&pub_keys[..1]));
+    }
+
+    #[test]
+    fn test_verify_aggregate() {
+        let sec_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+        let msgs = ["foo", "bar", "baz"];
+        let items: Vec<(PublicKey, &str)> = sec_keys
+            .iter()
+            .zip(&msgs)
+            .map(|(sk, msg)| (sk.public_key(), *msg))
+            .collect();
+        let sigs: Vec<Signature> = sec_keys
+            .iter()
+            .zip(&msgs)
+            .map(|(sk, msg)| sk.sign(msg))
+            .collect();
+        let sig = Signature::aggregate(&sigs);
+        assert!(verify_aggregate(&items, &sig));
+        assert!(!verify_aggregate(&items[1..], &sig));
+        assert!(!verify_aggregate(&[(items[0].0, "qux")], &sigs[0]));
+        assert!(!verify_aggregate::<&str>(
+            &[],
+            &Signature::aggregate(&sigs[..0])
+        ));
+
+        // The same message is rejected, even if the signature is valid.
+        let sigs = [sec_keys[0].sign("foo"), sec_keys[1].sign("foo")];
+        let same = [(items[0].0, "foo"), (items[1].0, "foo")];
+        assert!(!verify_aggregate(&same, &Signature::aggregate(&sigs)));
+    }
+
+    #[test]
+    fn test_pop() {
+        let sec_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+        let mut keys: Vec<(PublicKey, Signature)> = sec_keys
+            .iter()
+            .map(|sk| (sk.public_key(), sk.sign_pop()))
+            .collect();
+        assert!(keys.iter().all(|(pk, pop)| pk.verify_pop(pop)));
+        assert!(!keys[0].0.verify_pop(&keys[1].1));
+        let sigs: Vec<Signature> = sec_keys.iter().map(|sk| sk.sign("msg")).collect();
+        let sig = Signature::aggregate(&sigs);
+        assert!(verify_multisig_with_pop(&keys, "msg", &sig));
+        assert_eq!(None, aggregate_with_pop(&[]));
+
+        // A plain signature of the key is not a proof of possession.
+        let pk = keys[0].0;
+        assert!(!pk.verify_pop(&sec_keys[0].sign(pk.to_bytes())));
+        let mut input = POP_DST.to_vec();
+        input.extend(pk.to_bytes().as_ref());
+        assert!(hash_pop(&pk) != hash_g2(&input));
+
+        // A rogue key that cancels the others has no valid proof.
+        let rogue_sk = SecretKey::random();
+        let mut rogue = rogue_sk.public_key().0;
+        for (pk, _) in &keys[1..] {
+            let mut neg = pk.0;
+            neg.negate();
+            rogue.add_assign(&neg);
+        }
+        keys[0] = (PublicKey(rogue), rogue_sk.sign_pop());
+        let forged = rogue_sk.sign("msg");
+        let pub_keys: Vec<PublicKey> = keys.iter().map(|(pk, _)| *pk).collect();
+        assert!(verify_multisig(&pub_keys, "msg", &forged));
+        assert!(!verify_multisig_with_pop(&keys, "msg", &forged));
+    }
+}
@@ -0,0 +1,281 @@
+//! Committees of committees: top-level key shares that are themselves held threshold-wise.
+//!
+//! In a two-level scheme, each share of the top-level key set belongs to a _subcommittee_
+//! instead of a single participant. The share's holder, or a dealer, splits it with
+//! [`split_share`] into a new key set whose master key is the share itself, and distributes the
+//! sub-shares. To sign, `sub_threshold + 1` subcommittee members produce signature shares with
+//! their sub-shares; combining them yields the subcommittee's signature share for the top-level
+//! key set, which is then combined with the other subcommittees' as usual.
+//!
+//! A [`NestedCommittee`] holds the top-level public key set and each subcommittee's. It checks
+//! that a subcommittee's master public key is the top-level public key share it stands for, and
+//! verifies both the sub-shares and the resulting signature share.
+//!
+//! A dealer can also generate the whole structure at once with [`random_nested`], e.g. for
+//! "2-of-3 departments, each 3-of-5 members", and `NestedCommittee::combine_signatures` combines
+//! the members' shares within each department and then across departments.
+//!
+//! ```
+//! use threshold_crypto::nested::{random_nested, NestedCommittee};
+//!
+//! let mut rng = rand::thread_rng();
+//! let (sk_set, departments) = random_nested(1, &[2, 2, 2], &mut rng).unwrap();
+//! let sub_pk_sets = departments.iter().map(|d| d.public_keys()).collect();
+//! let committee = NestedCommittee::with_subcommittees(sk_set.public_keys(), sub_pk_sets).unwrap();
+//! // Members 0 to 2 of departments 0 and 2 sign.
+//! let shares: Vec<Vec<_>> = [0, 2]
+//!     .iter()
+//!     .map(|d| (0..3).map(|j| departments[*d].secret_key_share(j).sign("msg")).collect())
+//!     .collect();
+//! let groups = vec![(0, shares[0].iter().enumerate()), (2, shares[1].iter().enumerate())];
+//! let sig = committee.combine_signatures(groups, "msg").unwrap();
+//! assert!(committee.public_keys().public_key().verify(&sig, "msg"));
+//! ```
+
+use std::collections::BTreeMap;
+
+use rand::Rng;
+
+use crate::error::{Error, Result};
+use crate::poly::Poly;
+use crate::{PublicKeySet, SecretKeySet, SecretKeyShare, Signature, SignatureShare};
+
+/// Splits a key share among a subcommittee: Any `threshold + 1` of the returned key set's shares
+/// can sign on behalf of `share`.
+pub fn split_share<R: Rng>(share: &SecretKeyShare, threshold: usize, rng: &mut R) -> SecretKeySet {
+    let mut poly = Poly::random(threshold, rng);
+    poly.coeff[0] = (share.0).0;
+    SecretKeySet::from(poly)
+}
+
+/// Creates a top-level key set with the given threshold, and splits its shares `0` to
+/// `sub_thresholds.len() - 1` among subcommittees with the given thresholds.
+///
+/// Returns the top-level key set and the subcommittees' key sets, where subcommittee `i` holds
+/// top-level share `i`. Only the subcommittees' shares need to be distributed; the dealer should
+/// discard the top-level key set after publishing its public keys. Returns
+/// `Error::InvalidAccessStructure` if there are no more subcommittees than the threshold.
+pub fn random_nested<R: Rng>(
+    threshold: usize,
+    sub_thresholds: &[usize],
+    rng: &mut R,
+) -> Result<(SecretKeySet, Vec<SecretKeySet>)> {
+    if sub_thresholds.len() <= threshold {
+        let msg = "there must be more subcommittees than the threshold".to_string();
+        return Err(Error::InvalidAccessStructure(msg));
+    }
+    let sk_set = SecretKeySet::try_random(threshold, rng)?;
+    let subs = sub_thresholds
+        .iter()
+        .enumerate()
+        .map(|(i, sub_threshold)| {
+            let mut poly = Poly::try_random(*sub_threshold, rng)?;
+            poly.coeff[0] = (sk_set.secret_key_share(i).0).0;
+            Ok(SecretKeySet::from(poly))
+        })
+        .collect::<Result<_>>()?;
+    Ok((sk_set, subs))
+}
+
+/// A top-level key set and the subcommittees holding its shares.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NestedCommittee {
+    pk_set: PublicKeySet,
+    subcommittees: BTreeMap<usize, PublicKeySet>,
+}
+
+impl NestedCommittee {
+    /// Creates a nested committee for the top-level key set, without any subcommittees yet.
+    pub fn new(pk_set: PublicKeySet) -> Self {
+        NestedCommittee {
+            pk_set,
+            subcommittees: BTreeMap::new(),
+        }
+    }
+
+    /// Creates a nested committee where subcommittee `i` holds top-level share `i`.
+    ///
+    /// Returns `Error::InvalidShare(i)` if subcommittee `i`'s master public key is not the
+    /// top-level public key share `i`.
+    pub fn with_subcommittees(pk_set: PublicKeySet, subs: Vec<PublicKeySet>) -> Result<Self> {
+        let mut committee = NestedCommittee::new(pk_set);
+        for (i, sub_pk_set) in subs.into_iter().enumerate() {
+            committee.add_subcommittee(i, sub_pk_set)?;
+        }
+        Ok(committee)
+    }
+
+    /// Returns the top-level public key set.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the public key set of the subcommittee holding top-level share `index`.
+    pub fn subcommittee(&self, index: usize) -> Option<&PublicKeySet> {
+        self.subcommittees.get(&index)
+    }
+
+    /// Registers the subcommittee holding top-level share `index`.
+    ///
+    /// Returns `Error::InvalidShare(index)` if the subcommittee's master public key is not the
+    /// top-level public key share `index`.
+    pub fn add_subcommittee(&mut self, index: usize, sub_pk_set: PublicKeySet) -> Result<()> {
+        if sub_pk_set.public_key() != self.pk_set.public_key_share(index).0 {
+            return Err(Error::InvalidShare(index));
+        }
+        self.subcommittees.insert(index, sub_pk_set);
+        Ok(())
+    }
+
+    /// Verifies the subcommittee members' signature shares and combines them into the
+    /// subcommittee's top-level signature share for the message.
+    ///
+    /// Returns `Error::UnknownSigner(index)` if there is no such subcommittee,
+    /// `Error::InvalidShare` with the member's index if a sub-share is invalid, and
+    /// `Error::NotEnoughShares` if there are too few.
+    pub fn combine_sub_shares<'a, I, M>(
+        &self,
+        index: usize,
+        sub_shares: I,
+        msg: M,
+    ) -> Result<SignatureShare>
+    where
+        I: IntoIterator<Item = (usize, &'a SignatureShare)>,
+        M: AsRef<[u8]>,
+    {
+        let sub_pk_set = self
+            .subcommittees
+            .get(&index)
+            .ok_or(Error::UnknownSigner(index))?;
+        let mut shares = BTreeMap::new();
+        for (j, share) in sub_shares {
+            if !sub_pk_set.public_key_share(j).verify(share, msg.as_ref()) {
+                return Err(Error::InvalidShare(j));
+            }
+            shares.insert(j, share);
+        }
+        let sig = sub_pk_set.combine_signatures(shares)?;
+        Ok(SignatureShare(sig))
+    }
+
+    /// Returns `true` if the signature share is valid for top-level share `index`.
+    pub fn verify_share<M: AsRef<[u8]>>(
+        &self,
+        index: usize,
+        share: &SignatureShare,
+        msg: M,
+    ) -> bool {
+        self.pk_set.public_key_share(index).verify(share, msg)
+    }
+
+    /// Combines the members' signature shares within each subcommittee, and the resulting
+    /// signature shares into a signature under the top-level master key.
+    ///
+    /// `groups` yields each subcommittee's top-level index together with its members' indices
+    /// and signature shares. Returns the error of `combine_sub_shares` for the first
+    /// subcommittee that fails, and `Error::NotEnoughShares` if there are not more subcommittees
+    /// than the top-level threshold.
+    pub fn combine_signatures<'a, I, J, M>(&self, groups: I, msg: M) -> Result<Signature>
+    where
+        I: IntoIterator<Item = (usize, J)>,
+        J: IntoIterator<Item = (usize, &'a SignatureShare)>,
+        M: AsRef<[u8]>,
+    {
+        let mut shares = BTreeMap::new();
+        for (index, sub_shares) in groups {
+            let share = self.combine_sub_shares(index, sub_shares, msg.as_ref())?;
+            shares.insert(index, share);
+        }
+        self.pk_set.combine_signatures(&shares)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_signing() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let mut committee = NestedCommittee::new(sk_set.public_keys());
+        // Top-level shares 0 and 2 are held by 2-of-3 and 3-of-4 subcommittees.
+        let subs: Vec<(usize, SecretKeySet)> = vec![
+            (0, split_share(&sk_set.secret_key_share(0), 1, &mut rng)),
+            (2, split_share(&sk_set.secret_key_share(2), 2, &mut rng)),
+        ];
+        for (i, sub) in &subs {
+            committee
+                .add_subcommittee(*i, sub.public_keys())
+                .expect("matching key");
+        }
+        let wrong = split_share(&sk_set.secret_key_share(1), 1, &mut rng);
+        let result = committee.add_subcommittee(2, wrong.public_keys());
+        assert_eq!(Err(Error::InvalidShare(2)), result);
+
+        let msg = "nested";
+        let mut top_shares = BTreeMap::new();
+        for (i, sub) in &subs {
+            let sub_shares: BTreeMap<usize, SignatureShare> = (0..=sub.threshold())
+                .map(|j| (j + 1, sub.secret_key_share(j + 1).sign(msg)))
+                .collect();
+            let share = committee
+                .combine_sub_shares(*i, sub_shares.iter().map(|(j, s)| (*j, s)), msg)
+                .expect("valid sub-shares");
+            assert!(committee.verify_share(*i, &share, msg));
+            top_shares.insert(*i, share);
+        }
+        let sig = sk_set
+            .public_keys()
+            .combine_signatures(&top_shares)
+            .unwrap();
+        assert!(sk_set.public_keys().public_key().verify(&sig, msg));
+
+        // An invalid sub-share is attributed to the subcommittee member.
+        let (_, sub) = &subs[0];
+        let bad = sub.secret_key_share(1).sign("other");
+        let good = sub.secret_key_share(0).sign(msg);
+        let result = committee.combine_sub_shares(0, vec![(0, &good), (1, &bad)], msg);
+        assert_eq!(Some(Error::InvalidShare(1)), result.err());
+        let result = committee.combine_sub_shares(0, vec![(0, &good)], msg);
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+        let result = committee.combine_sub_shares(1, vec![(0, &good)], msg);
+        assert_eq!(Some(Error::UnknownSigner(1)), result.err());
+    }
+
+    #[test]
+    fn test_random_nested() {
+        let mut rng = rand::thread_rng();
+        // Any two of three departments, each needing two, three or two of its members.
+        let (sk_set, subs) = random_nested(1, &[1, 2, 1], &mut rng).expect("valid structure");
+        let sub_pk_sets = subs.iter().map(SecretKeySet::public_keys).collect();
+        let committee =
+            NestedCommittee::with_subcommittees(sk_set.public_keys(), sub_pk_sets).expect("valid");
+        let msg = "departments";
+        let sign = |i: usize, members: &[usize]| -> Vec<(usize, SignatureShare)> {
+            let sub = &subs[i];
+            let sign_one = |j: &usize| (*j, sub.secret_key_share(*j).sign(msg));
+            members.iter().map(sign_one).collect()
+        };
+        let shares1 = sign(1, &[0, 3, 4]);
+        let shares2 = sign(2, &[1, 3]);
+        let groups = vec![
+            (1, shares1.iter().map(|(j, s)| (*j, s)).collect::<Vec<_>>()),
+            (2, shares2.iter().map(|(j, s)| (*j, s)).collect()),
+        ];
+        let sig = committee
+            .combine_signatures(groups.clone(), msg)
+            .expect("valid shares");
+        assert!(sk_set.public_keys().public_key().verify(&sig, msg));
+
+        // One department isn't enough, nor are too few members of another.
+        let result = committee.combine_signatures(groups[..1].to_vec(), msg);
+        assert_eq!(Err(Error::NotEnoughShares), result);
+        let short = vec![groups[0].clone(), (2, groups[1].1[..1].to_vec())];
+        let result = committee.combine_signatures(short, msg);
+        assert_eq!(Err(Error::NotEnoughShares), result);
+
+        let result = random_nested(2, &[1, 1], &mut rng);
+        assert!(matches!(result, Err(Error::InvalidAccessStructure(_))));
+    }
+}
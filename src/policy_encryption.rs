@@ -0,0 +1,309 @@
+//! Encryption under an access policy over several keys and committees.
+//!
+//! Some secrets should only be decrypted with the approval of e.g. "the security team's
+//! committee, and either the CFO or two of three directors". [`encrypt_under_policy`] encrypts
+//! a message under an [`lsss::Policy`](../lsss/enum.Policy.html) whose parties are
+//! [`Recipient`]s: single `PublicKey`s, or `PublicKeySet`s of committees that decrypt
+//! threshold-wise.
+//!
+//! The message is encrypted with a random content key, which is split with the policy's span
+//! program. Each row's share is encrypted to the public key of the row's owner. To decrypt, each
+//! party of an authorized set recovers its [`PartyShares`], with its `SecretKey` or a quorum of
+//! its committee's decryption shares, and [`PolicyCiphertext::decrypt`] combines them. Whether a
+//! set of parties is authorized can be checked beforehand with
+//! [`PolicyCiphertext::is_authorized`].
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use ff::{PrimeField, PrimeFieldRepr};
+use rand::distributions::Standard;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::decode::decode;
+use crate::error::{Error, Result};
+use crate::lsss::{LsssKeySet, Policy, Share, SpanProgram};
+use crate::util::sha3_256;
+use crate::{
+    Ciphertext, DecryptionShare, Fr, PublicKey, PublicKeySet, SecretKey, SecretKeyShare, SK_SIZE,
+};
+
+/// The domain separator of the message mask.
+const MASK_DST: &[u8] = b"threshold_crypto policy encryption mask";
+
+/// The domain separator of the integrity tag.
+const TAG_DST: &[u8] = b"threshold_crypto policy encryption tag";
+
+/// A party of a policy: a single key, or a committee.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Recipient {
+    /// A single key holder.
+    Key(PublicKey),
+    /// A committee, decrypting with `threshold + 1` decryption shares.
+    Committee(PublicKeySet),
+}
+
+impl Recipient {
+    /// Returns the public key the recipient's shares are encrypted to.
+    fn public_key(&self) -> PublicKey {
+        match self {
+            Recipient::Key(pk) => *pk,
+            Recipient::Committee(pk_set) => pk_set.public_key(),
+        }
+    }
+}
+
+/// A message encrypted under a policy.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PolicyCiphertext {
+    program: SpanProgram,
+    /// The encrypted shares of the content key, one per row.
+    shares: Vec<Ciphertext>,
+    payload: Vec<u8>,
+    tag: [u8; 32],
+}
+
+/// Encrypts the message so that only sets of recipients satisfying the policy can decrypt it.
+///
+/// Party `i` of the policy is `recipients[i]`. Returns `Error::InvalidAccessStructure` if the
+/// policy is invalid, or refers to a party without a recipient.
+pub fn encrypt_under_policy<R: Rng>(
+    policy: &Policy,
+    recipients: &[Recipient],
+    msg: &[u8],
+    rng: &mut R,
+) -> Result<PolicyCiphertext> {
+    let program = policy.compile()?;
+    let key_set = LsssKeySet::random(program.clone(), rng);
+    let mut shares: Vec<Option<Ciphertext>> = vec![None; program.len()];
+    let parties: BTreeSet<usize> = (0..program.len())
+        .filter_map(|row| program.owner(row))
+        .collect();
+    for party in parties {
+        let recipient = recipients.get(party).ok_or_else(|| {
+            Error::InvalidAccessStructure(format!("party {} has no recipient", party))
+        })?;
+        let pk = recipient.public_key();
+        for share in key_set.shares(party) {
+            let bytes = fr_to_bytes(&share.value);
+            shares[share.row] = Some(pk.encrypt_with_rng(rng, &bytes[..]));
+        }
+    }
+    let key = key_set.secret_key();
+    Ok(PolicyCiphertext {
+        program,
+        shares: shares
+            .into_iter()
+            .map(|ct| ct.expect("every row has an owner"))
+            .collect(),
+        payload: mask(&key.0, msg),
+        tag: tag(&key.0, msg),
+    })
+}
+
+impl PolicyCiphertext {
+    /// Returns the span program the content key was shared with.
+    pub fn program(&self) -> &SpanProgram {
+        &self.program
+    }
+
+    /// Returns `true` if the given set of parties can decrypt the message.
+    pub fn is_authorized(&self, parties: &BTreeSet<usize>) -> bool {
+        self.program.is_authorized(parties)
+    }
+
+    /// Returns the encrypted shares of the given party, by row.
+    pub fn ciphertexts(&self, party: usize) -> impl Iterator<Item = (usize, &Ciphertext)> {
+        let program = &self.program;
+        let owned = move |(row, _): &(usize, &Ciphertext)| program.owner(*row) == Some(party);
+        self.shares.iter().enumerate().filter(owned)
+    }
+
+    /// Decrypts the shares of a party that is a single key holder.
+    ///
+    /// Returns `Error::InvalidCiphertext` if one of them can't be decrypted with the key.
+    pub fn decrypt_shares(&self, party: usize, sk: &SecretKey) -> Result<PartyShares> {
+        let shares = self
+            .ciphertexts(party)
+            .map(|(row, ct)| {
+                let bytes = Zeroizing::new(sk.decrypt(ct).ok_or(Error::InvalidCiphertext)?);
+                Ok(Share {
+                    row,
+                    party,
+                    value: fr_from_bytes(&bytes)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(PartyShares { shares })
+    }
+
+    /// Returns a committee member's decryption shares for the given party's encrypted shares.
+    ///
+    /// Returns `Error::InvalidCiphertext` if one of them is invalid.
+    pub fn decryption_shares(
+        &self,
+        party: usize,
+        sk_share: &SecretKeyShare,
+    ) -> Result<Vec<DecryptionShare>> {
+        self.ciphertexts(party)
+            .map(|(_, ct)| sk_share.decrypt_share(ct).ok_or(Error::InvalidCiphertext))
+            .collect()
+    }
+
+    /// Combines the decryption shares of `threshold + 1` members of a committee into the
+    /// committee's shares.
+    ///
+    /// Returns `Error::InvalidShare` with the member's index if any of its decryption shares is
+    /// invalid, and `Error::NotEnoughShares` if there are too few members.
+    pub fn combine_decryption_shares(
+        &self,
+        party: usize,
+        pk_set: &PublicKeySet,
+        dec_shares: &BTreeMap<usize, Vec<DecryptionShare>>,
+    ) -> Result<PartyShares> {
+        let ciphertexts: Vec<(usize, &Ciphertext)> = self.ciphertexts(party).collect();
+        for (i, member_shares) in dec_shares {
+            let pk_share = pk_set.public_key_share(*i);
+            let valid = member_shares.len() == ciphertexts.len()
+                && (ciphertexts.iter().zip(member_shares))
+                    .all(|((_, ct), share)| pk_share.verify_decryption_share(share, ct));
+            if !valid {
+                return Err(Error::InvalidShare(*i));
+            }
+        }
+        let shares = (ciphertexts.iter().enumerate())
+            .map(|(k, (row, ct))| {
+                let row_shares = dec_shares.iter().map(|(i, shares)| (*i, &shares[k]));
+                let bytes = Zeroizing::new(pk_set.decrypt(row_shares, ct)?);
+                Ok(Share {
+                    row: *row,
+                    party,
+                    value: fr_from_bytes(&bytes)?,
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(PartyShares { shares })
+    }
+
+    /// Decrypts the message with the shares of an authorized set of parties.
+    ///
+    /// Returns `Error::NotEnoughShares` if the parties are not authorized, and
+    /// `Error::IntegrityCheckFailed` if a share was wrong.
+    pub fn decrypt<'a, I>(&self, party_shares: I) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = &'a PartyShares>,
+    {
+        let shares = party_shares.into_iter().flat_map(|p| &p.shares);
+        let key = self.program.combine(shares)?;
+        let msg = mask(&key.0, &self.payload);
+        if tag(&key.0, &msg) != self.tag {
+            return Err(Error::IntegrityCheckFailed);
+        }
+        Ok(msg)
+    }
+}
+
+/// A party's decrypted shares of a content key.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PartyShares {
+    shares: Vec<Share>,
+}
+
+/// Returns the bitwise xor of `bytes` with a pseudorandom stream derived from the content key.
+fn mask(key: &Fr, bytes: &[u8]) -> Vec<u8> {
+    let mut seed = MASK_DST.to_vec();
+    seed.extend(fr_to_bytes(key).iter());
+    let rng = ChaChaRng::from_seed(sha3_256(&seed));
+    seed.zeroize();
+    let xor = |(a, b): (u8, &u8)| a ^ b;
+    rng.sample_iter(&Standard).zip(bytes).map(xor).collect()
+}
+
+/// Returns the tag that binds the message to the content key.
+fn tag(key: &Fr, msg: &[u8]) -> [u8; 32] {
+    let mut data = TAG_DST.to_vec();
+    data.extend(fr_to_bytes(key).iter());
+    data.extend(msg);
+    let tag = sha3_256(&data);
+    data.zeroize();
+    tag
+}
+
+/// Returns the field element's big-endian representation.
+fn fr_to_bytes(fr: &Fr) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+    fr.into_repr()
+        .write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    bytes
+}
+
+/// Decodes a big-endian field element, or returns `Error::InvalidCiphertext`.
+fn fr_from_bytes(bytes: &[u8]) -> Result<Fr> {
+    decode(bytes, "Share", |r| r.fr("value")).map_err(|_| Error::InvalidCiphertext)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    fn set(parties: &[usize]) -> BTreeSet<usize> {
+        parties.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_policy_encryption() {
+        let mut rng = rand::thread_rng();
+        let committee = SecretKeySet::random(1, &mut rng);
+        let keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+        let mut recipients = vec![Recipient::Committee(committee.public_keys())];
+        recipients.extend(keys.iter().map(|sk| Recipient::Key(sk.public_key())));
+        // The committee, and either party 1 or both parties 2 and 3.
+        let policy = Policy::and(vec![
+            Policy::party(0),
+            Policy::or(vec![
+                Policy::party(1),
+                Policy::and(vec![Policy::party(2), Policy::party(3)]),
+            ]),
+        ]);
+        let ct = encrypt_under_policy(&policy, &recipients, b"launch codes", &mut rng).unwrap();
+        assert!(ct.is_authorized(&set(&[0, 1])));
+        assert!(ct.is_authorized(&set(&[0, 2, 3])));
+        assert!(!ct.is_authorized(&set(&[1, 2, 3])));
+
+        let dec_shares: BTreeMap<usize, Vec<DecryptionShare>> = [0, 2]
+            .iter()
+            .map(|&i| {
+                let sk_share = committee.secret_key_share(i);
+                (i, ct.decryption_shares(0, &sk_share).unwrap())
+            })
+            .collect();
+        let committee_shares = ct
+            .combine_decryption_shares(0, &committee.public_keys(), &dec_shares)
+            .unwrap();
+        let shares_2 = ct.decrypt_shares(2, &keys[1]).unwrap();
+        let shares_3 = ct.decrypt_shares(3, &keys[2]).unwrap();
+        let msg = ct.decrypt(vec![&committee_shares, &shares_2, &shares_3]);
+        assert_eq!(Ok(b"launch codes".to_vec()), msg);
+
+        let result = ct.decrypt(vec![&committee_shares, &shares_2]);
+        assert_eq!(Err(Error::NotEnoughShares), result);
+        // Shares decrypted with the wrong key don't decrypt the message.
+        let wrong = ct.decrypt_shares(1, &keys[1]);
+        assert!(wrong
+            .and_then(|s| ct.decrypt(vec![&committee_shares, &s]))
+            .is_err());
+
+        let mut bad = dec_shares.clone();
+        bad.insert(1, dec_shares[&0].clone());
+        let result = ct.combine_decryption_shares(0, &committee.public_keys(), &bad);
+        assert_eq!(Some(Error::InvalidShare(1)), result.err());
+
+        let too_few = encrypt_under_policy(&policy, &recipients[..3], b"", &mut rng);
+        assert!(too_few.is_err());
+    }
+}
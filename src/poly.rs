@@ -18,6 +18,8 @@
 
 use std::borrow::Borrow;
 use std::cmp::Ordering;
+#[cfg(feature = "dkg")]
+use std::convert::TryFrom;
 use std::fmt::{self, Debug, Formatter};
 use std::hash::{Hash, Hasher};
 use std::iter::repeat_with;
@@ -27,17 +29,25 @@ use ff::Field;
 use group::{CurveAffine, CurveProjective};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use zeroize::Zeroize;
+#[cfg(feature = "ct")]
+use subtle::Choice;
+use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use crate::cmp_pairing::cmp_projective;
-use crate::error::{Error, Result};
+use crate::decode::{decode, write_point};
+#[cfg(feature = "dkg")]
+use crate::error::DecodeReason;
+use crate::error::{DecodeResult, Error, Result};
 use crate::into_fr::IntoFr;
 use crate::secret::clear_fr;
-use crate::PublicKey;
+#[cfg(feature = "ct")]
+use crate::secret::ct_eq_fr;
+use crate::{DebugDots, PublicKey};
 use crate::{Fr, G1Affine, G1};
 
 /// A univariate polynomial in the prime field.
-#[derive(Serialize, Deserialize, PartialEq, Eq, Clone)]
+#[derive(Serialize, Deserialize, Clone)]
+#[cfg_attr(not(feature = "ct"), derive(PartialEq, Eq))]
 pub struct Poly {
     /// The coefficients of a polynomial.
     #[serde(with = "super::serde_impl::field_vec")]
@@ -58,21 +68,41 @@ impl Drop for Poly {
     }
 }
 
+impl ZeroizeOnDrop for Poly {}
+
+/// With the `ct` feature, polynomials of the same degree are compared in constant time.
+#[cfg(feature = "ct")]
+impl PartialEq for Poly {
+    fn eq(&self, other: &Self) -> bool {
+        let eq_coeff = |(a, b): (&Fr, &Fr)| ct_eq_fr(a, b);
+        self.coeff.len() == other.coeff.len()
+            && self
+                .coeff
+                .iter()
+                .zip(&other.coeff)
+                .map(eq_coeff)
+                .fold(Choice::from(1), |acc, eq| acc & eq)
+                .into()
+    }
+}
+
+#[cfg(feature = "ct")]
+impl Eq for Poly {}
+
 /// A debug statement where the `coeff` vector of prime field elements has been redacted.
 impl Debug for Poly {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("Poly").field("coeff", &"...").finish()
+        f.debug_struct("Poly")
+            .field("degree", &self.degree())
+            .field("coeff", &DebugDots)
+            .finish()
     }
 }
 
 #[allow(clippy::suspicious_op_assign_impl)]
 impl<B: Borrow<Poly>> ops::AddAssign<B> for Poly {
     fn add_assign(&mut self, rhs: B) {
-        let len = self.coeff.len();
-        let rhs_len = rhs.borrow().coeff.len();
-        if rhs_len > len {
-            self.coeff.resize(rhs_len, Fr::zero());
-        }
+        grow_zeroizing(&mut self.coeff, rhs.borrow().coeff.len());
         for (self_c, rhs_c) in self.coeff.iter_mut().zip(&rhs.borrow().coeff) {
             Field::add_assign(self_c, rhs_c);
         }
@@ -80,7 +110,7 @@ impl<B: Borrow<Poly>> ops::AddAssign<B> for Poly {
     }
 }
 
-impl<'a, B: Borrow<Poly>> ops::Add<B> for &'a Poly {
+impl<B: Borrow<Poly>> ops::Add<B> for &Poly {
     type Output = Poly;
 
     fn add(self, rhs: B) -> Poly {
@@ -97,7 +127,7 @@ impl<B: Borrow<Poly>> ops::Add<B> for Poly {
     }
 }
 
-impl<'a> ops::Add<Fr> for Poly {
+impl ops::Add<Fr> for Poly {
     type Output = Poly;
 
     fn add(mut self, rhs: Fr) -> Self::Output {
@@ -111,7 +141,7 @@ impl<'a> ops::Add<Fr> for Poly {
     }
 }
 
-impl<'a> ops::Add<u64> for Poly {
+impl ops::Add<u64> for Poly {
     type Output = Poly;
 
     fn add(self, rhs: u64) -> Self::Output {
@@ -121,11 +151,7 @@ impl<'a> ops::Add<u64> for Poly {
 
 impl<B: Borrow<Poly>> ops::SubAssign<B> for Poly {
     fn sub_assign(&mut self, rhs: B) {
-        let len = self.coeff.len();
-        let rhs_len = rhs.borrow().coeff.len();
-        if rhs_len > len {
-            self.coeff.resize(rhs_len, Fr::zero());
-        }
+        grow_zeroizing(&mut self.coeff, rhs.borrow().coeff.len());
         for (self_c, rhs_c) in self.coeff.iter_mut().zip(&rhs.borrow().coeff) {
             Field::sub_assign(self_c, rhs_c);
         }
@@ -133,7 +159,7 @@ impl<B: Borrow<Poly>> ops::SubAssign<B> for Poly {
     }
 }
 
-impl<'a, B: Borrow<Poly>> ops::Sub<B> for &'a Poly {
+impl<B: Borrow<Poly>> ops::Sub<B> for &Poly {
     type Output = Poly;
 
     fn sub(self, rhs: B) -> Poly {
@@ -152,7 +178,7 @@ impl<B: Borrow<Poly>> ops::Sub<B> for Poly {
 
 // Clippy thinks using `+` in a `Sub` implementation is suspicious.
 #[allow(clippy::suspicious_arithmetic_impl)]
-impl<'a> ops::Sub<Fr> for Poly {
+impl ops::Sub<Fr> for Poly {
     type Output = Poly;
 
     fn sub(self, mut rhs: Fr) -> Self::Output {
@@ -161,7 +187,7 @@ impl<'a> ops::Sub<Fr> for Poly {
     }
 }
 
-impl<'a> ops::Sub<u64> for Poly {
+impl ops::Sub<u64> for Poly {
     type Output = Poly;
 
     fn sub(self, rhs: u64) -> Self::Output {
@@ -171,7 +197,7 @@ impl<'a> ops::Sub<u64> for Poly {
 
 // Clippy thinks using any `+` and `-` in a `Mul` implementation is suspicious.
 #[allow(clippy::suspicious_arithmetic_impl)]
-impl<'a, B: Borrow<Poly>> ops::Mul<B> for &'a Poly {
+impl<B: Borrow<Poly>> ops::Mul<B> for &Poly {
     type Output = Poly;
 
     fn mul(self, rhs: B) -> Self::Output {
@@ -221,7 +247,7 @@ impl ops::MulAssign<Fr> for Poly {
     }
 }
 
-impl<'a> ops::Mul<&'a Fr> for Poly {
+impl ops::Mul<&Fr> for Poly {
     type Output = Poly;
 
     fn mul(mut self, rhs: &Fr) -> Self::Output {
@@ -252,7 +278,7 @@ impl<'a> ops::Mul<&'a Fr> for &'a Poly {
     }
 }
 
-impl<'a> ops::Mul<Fr> for &'a Poly {
+impl ops::Mul<Fr> for &Poly {
     type Output = Poly;
 
     fn mul(self, rhs: Fr) -> Self::Output {
@@ -281,7 +307,8 @@ impl Poly {
     ///
     /// # Panics
     ///
-    /// Panics if the `degree` is too large for the coefficients to fit into a `Vec`.
+    /// Panics if the `degree` is too large for the coefficients to fit into a `Vec`, or if they
+    /// can't be allocated.
     pub fn random<R: Rng>(degree: usize, rng: &mut R) -> Self {
         Poly::try_random(degree, rng)
             .unwrap_or_else(|e| panic!("Failed to create random `Poly`: {}", e))
@@ -291,10 +318,14 @@ impl Poly {
     /// constructor in every way except that this constructor will return an `Err` where
     /// `try_random` would return an error.
     pub fn try_random<R: Rng>(degree: usize, rng: &mut R) -> Result<Self> {
-        if degree == usize::max_value() {
+        if degree == usize::MAX {
             return Err(Error::DegreeTooHigh);
         }
-        let coeff: Vec<Fr> = repeat_with(|| Fr::random(rng)).take(degree + 1).collect();
+        let mut coeff: Vec<Fr> = Vec::new();
+        coeff
+            .try_reserve_exact(degree + 1)
+            .map_err(|_| Error::DegreeTooHigh)?;
+        coeff.extend(repeat_with(|| Fr::random(rng)).take(degree + 1));
         Ok(Poly::from(coeff))
     }
 
@@ -330,8 +361,7 @@ impl Poly {
 
     /// Returns the (monic) monomial: `x.pow(degree)`.
     pub fn monomial(degree: usize) -> Self {
-        let coeff: Vec<Fr> = iter::repeat(Fr::zero())
-            .take(degree)
+        let coeff: Vec<Fr> = iter::repeat_n(Fr::zero(), degree)
             .chain(iter::once(Fr::one()))
             .collect();
         Poly::from(coeff)
@@ -347,17 +377,20 @@ impl Poly {
     {
         let convert = |(x, y): (T, U)| (x.into_fr(), y.into_fr());
         let samples: Vec<(Fr, Fr)> = samples_repr.into_iter().map(convert).collect();
-        Poly::compute_interpolation(&samples)
+        Poly::interpolate_from_fr(samples)
     }
 
     /// Returns the unique polynomial `f` of degree `samples.len() - 1` with the given values
     /// `(x, f(x))`. Expects samples to be a vector of two tuple Field representation elements.
-    pub fn interpolate_from_fr(samples: Vec<(Fr, Fr)>) -> Self
-    {
-        Poly::compute_interpolation(&samples)
+    pub fn interpolate_from_fr(mut samples: Vec<(Fr, Fr)>) -> Self {
+        let poly = Poly::compute_interpolation(&samples);
+        for (x, y) in &mut samples {
+            clear_fr(x);
+            clear_fr(y);
+        }
+        poly
     }
 
-
     /// Returns the degree.
     pub fn degree(&self) -> usize {
         self.coeff.len().saturating_sub(1)
@@ -379,6 +412,8 @@ impl Poly {
 
     /// Returns the corresponding commitment.
     pub fn commitment(&self) -> Commitment {
+        let _span = span!("commitment", degree = self.degree());
+        count!(Exponentiation, self.coeff.len());
         let to_g1 = |c: &Fr| G1Affine::one().mul(*c);
         Commitment {
             coeff: self.coeff.iter().map(to_g1).collect(),
@@ -394,34 +429,45 @@ impl Poly {
 
     /// Returns the unique polynomial `f` of degree `samples.len() - 1` with the given values
     /// `(x, f(x))`.
+    ///
+    /// Only the sample points `x` are inverted or branched on, so secret values `f(x)` don't
+    /// affect the control flow until the final removal of zero leading coefficients.
     fn compute_interpolation(samples: &[(Fr, Fr)]) -> Self {
         if samples.is_empty() {
             return Poly::zero();
         }
         // Interpolates on the first `i` samples.
-        let mut poly = Poly::constant(samples[0].1);
+        let mut poly = Poly::from(vec![samples[0].1]);
         let mut minus_s0 = samples[0].0;
         minus_s0.negate();
-        // Is zero on the first `i` samples.
+        // Is zero on the first `i` samples. It only depends on the sample points.
         let mut base = Poly::from(vec![minus_s0, Fr::one()]);
 
         // We update `base` so that it is always zero on all previous samples, and `poly` so that
         // it has the correct values on the previous samples.
         for (ref x, ref y) in &samples[1..] {
-            // Scale `base` so that its value at `x` is the difference between `y` and `poly`'s
-            // current value at `x`: Adding it to `poly` will then make it correct for `x`.
+            // Add `base`, scaled so that its value at `x` is the difference between `y` and
+            // `poly`'s current value at `x`: That makes `poly` correct for `x`.
             let mut diff = *y;
             diff.sub_assign(&poly.evaluate(x));
             let base_val = base.evaluate(x);
             diff.mul_assign(&base_val.inverse().expect("sample points must be distinct"));
-            base *= diff;
-            poly += &base;
+            grow_zeroizing(&mut poly.coeff, base.coeff.len());
+            let mut term = Fr::zero();
+            for (poly_c, base_c) in poly.coeff.iter_mut().zip(&base.coeff) {
+                term = *base_c;
+                term.mul_assign(&diff);
+                poly_c.add_assign(&term);
+            }
+            clear_fr(&mut term);
+            clear_fr(&mut diff);
 
             // Finally, multiply `base` by X - x, so that it is zero at `x`, too, now.
             let mut minus_x = *x;
             minus_x.negate();
             base *= Poly::from(vec![minus_x, Fr::one()]);
         }
+        poly.remove_zeros();
         poly
     }
 
@@ -443,7 +489,7 @@ pub struct Commitment {
 
 impl PartialOrd for Commitment {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
@@ -479,7 +525,7 @@ impl<B: Borrow<Commitment>> ops::AddAssign<B> for Commitment {
     }
 }
 
-impl<'a, B: Borrow<Commitment>> ops::Add<B> for &'a Commitment {
+impl<B: Borrow<Commitment>> ops::Add<B> for &Commitment {
     type Output = Commitment;
 
     fn add(self, rhs: B) -> Commitment {
@@ -502,6 +548,28 @@ impl Commitment {
         self.coeff.len() - 1
     }
 
+    /// Returns the commitment's representation: the compressed coefficients, constant term
+    /// first.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        for c in &self.coeff {
+            write_point(&mut bytes, c);
+        }
+        bytes
+    }
+
+    /// Decodes the commitment from its representation, as returned by `to_bytes`. It must have at
+    /// least one coefficient.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "Commitment", |r| {
+            let mut coeff = vec![r.point::<G1Affine>("coefficient")?];
+            while r.remaining() > 0 {
+                coeff.push(r.point::<G1Affine>("coefficient")?);
+            }
+            Ok(Commitment { coeff })
+        })
+    }
+
     /// Returns the `i`-th public key share.
     pub fn evaluate<T: IntoFr>(&self, i: T) -> G1 {
         let mut result = match self.coeff.last() {
@@ -509,6 +577,7 @@ impl Commitment {
             Some(c) => *c,
         };
         let x = i.into_fr();
+        count!(Exponentiation, self.coeff.len() - 1);
         for c in self.coeff.iter().rev().skip(1) {
             result.mul_assign(x);
             result.add_assign(c);
@@ -516,6 +585,45 @@ impl Commitment {
         result
     }
 
+    /// Returns the commitment to the unique polynomial `f` of degree at most `samples.len() - 1`
+    /// whose values are committed to in the samples `(x, f(x) * g)`: interpolation in the
+    /// exponent.
+    ///
+    /// Returns `Error::NotEnoughShares` if there are no samples, and `Error::DuplicateEntry` if
+    /// two of them have the same `x`.
+    pub(crate) fn interpolate(samples: &[(Fr, G1)]) -> Result<Self> {
+        if samples.is_empty() {
+            return Err(Error::NotEnoughShares);
+        }
+        let xs: Vec<Fr> = samples.iter().map(|(x, _)| *x).collect();
+        if xs.iter().enumerate().any(|(i, x)| xs[..i].contains(x)) {
+            return Err(Error::DuplicateEntry);
+        }
+        count!(Exponentiation, samples.len() * samples.len());
+        let mut coeff = vec![G1::zero(); samples.len()];
+        for (x_i, value) in samples {
+            // The Lagrange polynomial that is `1` at the `i`-th point and `0` at the others.
+            let mut basis = Poly::constant(Fr::one());
+            let mut denom = Fr::one();
+            for x_j in xs.iter().filter(|x_j| *x_j != x_i) {
+                let mut minus_x_j = *x_j;
+                minus_x_j.negate();
+                basis *= Poly::from(vec![minus_x_j, Fr::one()]);
+                let mut diff = *x_i;
+                diff.sub_assign(x_j);
+                denom.mul_assign(&diff);
+            }
+            basis *= denom.inverse().expect("sample points are distinct");
+            let value = value.into_affine();
+            for (c, b) in coeff.iter_mut().zip(&basis.coeff) {
+                c.add_assign(&value.mul(*b));
+            }
+        }
+        let zeros = coeff.iter().rev().take_while(|c| c.is_zero()).count();
+        coeff.truncate(cmp::max(1, coeff.len() - zeros));
+        Ok(Commitment { coeff })
+    }
+
     /// Removes all trailing zero coefficients.
     fn remove_zeros(&mut self) {
         let zeros = self.coeff.iter().rev().take_while(|c| c.is_zero()).count();
@@ -531,7 +639,7 @@ impl Commitment {
     /// Generates a public key from a commitment
     pub fn public_key(&self) -> PublicKey {
         let mut pub_key = self.coeff[0];
-        let length = self.coeff.len() as usize;
+        let length = self.coeff.len();
         for i in 1..length {
             pub_key.add_assign(&self.coeff[i]);
         }
@@ -539,6 +647,22 @@ impl Commitment {
     }
 }
 
+/// Extends the coefficients with zeros to at least `len` elements. If that needs a new
+/// allocation, the old one is overwritten with zeros before it is freed.
+fn grow_zeroizing(coeff: &mut Vec<Fr>, len: usize) {
+    if len <= coeff.len() {
+        return;
+    }
+    if len > coeff.capacity() {
+        let mut grown = Vec::with_capacity(len);
+        grown.extend_from_slice(coeff);
+        coeff.iter_mut().for_each(clear_fr);
+        *coeff = grown;
+    }
+    coeff.resize(len, Fr::zero());
+}
+
+#[cfg(feature = "dkg")]
 /// A symmetric bivariate polynomial in the prime field.
 ///
 /// This can be used for Verifiable Secret Sharing and Distributed Key Generation. See the module
@@ -553,6 +677,7 @@ pub struct BivarPoly {
     coeff: Vec<Fr>,
 }
 
+#[cfg(feature = "dkg")]
 impl Zeroize for BivarPoly {
     fn zeroize(&mut self) {
         for fr in self.coeff.iter_mut() {
@@ -562,22 +687,28 @@ impl Zeroize for BivarPoly {
     }
 }
 
+#[cfg(feature = "dkg")]
 impl Drop for BivarPoly {
     fn drop(&mut self) {
         self.zeroize();
     }
 }
 
+#[cfg(feature = "dkg")]
+impl ZeroizeOnDrop for BivarPoly {}
+
+#[cfg(feature = "dkg")]
 /// A debug statement where the `coeff` vector has been redacted.
 impl Debug for BivarPoly {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         f.debug_struct("BivarPoly")
             .field("degree", &self.degree)
-            .field("coeff", &"...")
+            .field("coeff", &DebugDots)
             .finish()
     }
 }
 
+#[cfg(feature = "dkg")]
 impl BivarPoly {
     /// Creates a random polynomial.
     ///
@@ -596,9 +727,9 @@ impl BivarPoly {
     /// Creates a polynomial where the 0th coeff is set to `secret`.
     pub fn with_secret<T: IntoFr, R: Rng>(secret: T, degree: usize, rng: &mut R) -> Self {
         let mut bipoly: BivarPoly = BivarPoly::random(degree, rng);
-        let mut coeff = bipoly.coeff.clone();
-        coeff[0] = secret.into_fr();
-        bipoly.coeff = coeff;
+        let mut fr = secret.into_fr();
+        bipoly.coeff[0] = fr;
+        clear_fr(&mut fr);
         bipoly
     }
 
@@ -632,6 +763,7 @@ impl BivarPoly {
                 summand.mul_assign(&x_pow_i);
                 summand.mul_assign(y_pow_j);
                 result.add_assign(&summand);
+                clear_fr(&mut summand);
             }
         }
         result
@@ -639,16 +771,17 @@ impl BivarPoly {
 
     /// Returns the `x`-th row, as a univariate polynomial.
     pub fn row<T: IntoFr>(&self, x: T) -> Poly {
+        let _span = span!("dkg_row", degree = self.degree);
         let x_pow = self.powers(x);
         let coeff: Vec<Fr> = (0..=self.degree)
             .map(|i| {
-                // TODO: clear these secrets from the stack.
                 let mut result = Fr::zero();
                 for (j, x_pow_j) in x_pow.iter().enumerate() {
                     let index = coeff_pos(i, j).expect("polynomial degree too high");
                     let mut summand = self.coeff[index];
                     summand.mul_assign(x_pow_j);
                     result.add_assign(&summand);
+                    clear_fr(&mut summand);
                 }
                 result
             })
@@ -658,6 +791,8 @@ impl BivarPoly {
 
     /// Returns the corresponding commitment. That information can be shared publicly.
     pub fn commitment(&self) -> BivarCommitment {
+        let _span = span!("dkg_commitment", degree = self.degree);
+        count!(Exponentiation, self.coeff.len());
         let to_pub = |c: &Fr| G1Affine::one().mul(*c);
         BivarCommitment {
             degree: self.degree,
@@ -665,6 +800,26 @@ impl BivarPoly {
         }
     }
 
+    /// Returns the Pedersen commitment to the polynomial, hidden by a `blinding` polynomial of the
+    /// same degree: Coefficient `a` with blinding coefficient `b` is committed to as
+    /// `a * G + b * h`.
+    pub(crate) fn pedersen_commitment(&self, blinding: &BivarPoly, h: G1) -> BivarCommitment {
+        let _span = span!("dkg_pedersen_commitment", degree = self.degree);
+        assert_eq!(self.degree, blinding.degree, "blinding degree mismatch");
+        count!(Exponentiation, 2 * self.coeff.len());
+        let to_pub = |(a, b): (&Fr, &Fr)| {
+            let mut result = G1Affine::one().mul(*a);
+            let mut blind = h;
+            blind.mul_assign(*b);
+            result.add_assign(&blind);
+            result
+        };
+        BivarCommitment {
+            degree: self.degree,
+            coeff: self.coeff.iter().zip(&blinding.coeff).map(to_pub).collect(),
+        }
+    }
+
     /// Returns the `0`-th to `degree`-th power of `x`.
     fn powers<T: IntoFr>(&self, x: T) -> Vec<Fr> {
         powers(x, self.degree)
@@ -681,6 +836,7 @@ impl BivarPoly {
     }
 }
 
+#[cfg(feature = "dkg")]
 /// A commitment to a symmetric bivariate polynomial.
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct BivarCommitment {
@@ -690,6 +846,7 @@ pub struct BivarCommitment {
     pub(crate) coeff: Vec<G1>,
 }
 
+#[cfg(feature = "dkg")]
 impl Hash for BivarCommitment {
     fn hash<H: Hasher>(&self, state: &mut H) {
         self.degree.hash(state);
@@ -699,12 +856,14 @@ impl Hash for BivarCommitment {
     }
 }
 
+#[cfg(feature = "dkg")]
 impl PartialOrd for BivarCommitment {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(&other))
+        Some(self.cmp(other))
     }
 }
 
+#[cfg(feature = "dkg")]
 impl Ord for BivarCommitment {
     fn cmp(&self, other: &Self) -> Ordering {
         self.degree.cmp(&other.degree).then_with(|| {
@@ -717,17 +876,49 @@ impl Ord for BivarCommitment {
     }
 }
 
+#[cfg(feature = "dkg")]
 impl BivarCommitment {
     /// Returns the polynomial's degree: It is the same in both variables.
     pub fn degree(&self) -> usize {
         self.degree
     }
 
+    /// Returns the commitment's representation: the degree as a big-endian `u64`, followed by the
+    /// compressed coefficients.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = (self.degree as u64).to_be_bytes().to_vec();
+        for c in &self.coeff {
+            write_point(&mut bytes, c);
+        }
+        bytes
+    }
+
+    /// Decodes the commitment from its representation, as returned by `to_bytes`.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        decode(bytes, "BivarCommitment", |r| {
+            let invalid_degree = r.error("degree", DecodeReason::InvalidLength);
+            let degree = r.u64("degree")?;
+            let len = usize::try_from(degree)
+                .ok()
+                .and_then(|degree| coeff_pos(degree, degree))
+                .and_then(|pos| pos.checked_add(1))
+                .ok_or(invalid_degree)?;
+            let coeff = (0..len)
+                .map(|_| r.point::<G1Affine>("coefficient"))
+                .collect::<DecodeResult<_>>()?;
+            Ok(BivarCommitment {
+                degree: degree as usize,
+                coeff,
+            })
+        })
+    }
+
     /// Returns the commitment's value at the point `(x, y)`.
     pub fn evaluate<T: IntoFr>(&self, x: T, y: T) -> G1 {
         let x_pow = self.powers(x);
         let y_pow = self.powers(y);
         // TODO: Can we save a few multiplication steps here due to the symmetry?
+        count!(Exponentiation, 2 * (self.degree + 1) * (self.degree + 1));
         let mut result = G1::zero();
         for (i, x_pow_i) in x_pow.into_iter().enumerate() {
             for (j, y_pow_j) in y_pow.iter().enumerate() {
@@ -743,6 +934,8 @@ impl BivarCommitment {
 
     /// Returns the `x`-th row, as a commitment to a univariate polynomial.
     pub fn row<T: IntoFr>(&self, x: T) -> Commitment {
+        let _span = span!("dkg_commitment_row", degree = self.degree);
+        count!(Exponentiation, (self.degree + 1) * (self.degree + 1));
         let x_pow = self.powers(x);
         let coeff: Vec<G1> = (0..=self.degree)
             .map(|i| {
@@ -775,6 +968,7 @@ impl BivarCommitment {
     }
 }
 
+#[cfg(feature = "dkg")]
 /// Returns the `0`-th to `degree`-th power of `x`.
 fn powers<T: IntoFr>(into_x: T, degree: usize) -> Vec<Fr> {
     let x = into_x.into_fr();
@@ -787,6 +981,7 @@ fn powers<T: IntoFr>(into_x: T, degree: usize) -> Vec<Fr> {
         .collect()
 }
 
+#[cfg(feature = "dkg")]
 /// Returns the position of coefficient `(i, j)` in the vector describing a symmetric bivariate
 /// polynomial. If `i` or `j` are too large to represent the position as a `usize`, `None` is
 /// returned.
@@ -798,14 +993,19 @@ pub(crate) fn coeff_pos(i: usize, j: usize) -> Option<usize> {
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "dkg")]
     use std::collections::BTreeMap;
 
-    use super::{coeff_pos, BivarPoly, IntoFr, Poly};
-    use super::{Fr, G1Affine, G1};
+    #[cfg(feature = "dkg")]
+    use super::{coeff_pos, BivarPoly, Fr, G1Affine, G1};
+    use super::{IntoFr, Poly};
+    #[cfg(feature = "dkg")]
     use ff::Field;
+    #[cfg(feature = "dkg")]
     use group::{CurveAffine, CurveProjective};
     use zeroize::Zeroize;
 
+    #[cfg(feature = "dkg")]
     #[test]
     fn test_coeff_pos() {
         let mut i = 0;
@@ -838,8 +1038,14 @@ mod tests {
         }
         let interp = Poly::interpolate(samples);
         assert_eq!(interp, poly);
+
+        // Zero values and vanishing leading coefficients.
+        let samples = vec![(0, 0), (1, 1), (2, 2)];
+        assert_eq!(Poly::monomial(1), Poly::interpolate(samples));
+        assert_eq!(Poly::zero(), Poly::interpolate(vec![(4, 0), (7, 0)]));
     }
 
+    #[cfg(feature = "dkg")]
     #[test]
     fn bipoly_with_secret() {
         let mut rng = rand::thread_rng();
@@ -854,7 +1060,11 @@ mod tests {
         let mut poly = Poly::monomial(3) + Poly::monomial(2) - 1;
         poly.zeroize();
         assert!(poly.is_zero());
+    }
 
+    #[cfg(feature = "dkg")]
+    #[test]
+    fn test_zeroize_bivar() {
         let mut bi_poly = BivarPoly::random(3, &mut rand::thread_rng());
         let random_commitment = bi_poly.commitment();
 
@@ -868,6 +1078,7 @@ mod tests {
         assert_eq!(zero_commitment.evaluate(x, y), G1::zero());
     }
 
+    #[cfg(feature = "dkg")]
     #[test]
     fn distributed_key_generation() {
         let mut rng = rand::thread_rng();
@@ -0,0 +1,357 @@
+//! Proxy re-encryption, for handing stored data over to a new key or committee.
+//!
+//! A `ReencryptionKey` from a source key `a` to a target key `b` is the difference `b - a`. A
+//! proxy holding it turns an [`ElGamalCiphertext`](../elgamal/struct.ElGamalCiphertext.html)
+//! `(r * G, M + r * a * G)` for the source into `(r * G, M + r * b * G)` for the target, without
+//! being able to decrypt either. Since the key is linear in the secrets, key shares work the same
+//! way: the holders of the source and target shares at index `i` create a `ReencryptionKeyShare`
+//! that anyone can check against the two public key shares, and the proxy combines enough of them
+//! into the key for the two `PublicKeySet`s.
+//!
+//! `Ciphertext`s are non-malleable and can't be re-encrypted. A `ReencryptableCiphertext`
+//! encrypts a message of any size with a symmetric key derived from a random point, and only the
+//! point with ElGamal, so that re-encrypting it only transforms the point.
+//!
+//! The proxy is trusted not to collude: Together with the holder of either key, it can compute the
+//! other one.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::pre::{ReencryptionKey, ReencryptionKeyShare};
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let mut rng = rand::thread_rng();
+//! let old_set = SecretKeySet::random(1, &mut rng);
+//! let new_set = SecretKeySet::random(1, &mut rng);
+//! let (old_pks, new_pks) = (old_set.public_keys(), new_set.public_keys());
+//! let ct = old_pks.public_key().encrypt_reencryptable("stored data");
+//!
+//! let rk_shares: BTreeMap<usize, ReencryptionKeyShare> = (0..2)
+//!     .map(|i| {
+//!         let (old, new) = (old_set.secret_key_share(i), new_set.secret_key_share(i));
+//!         (i, old.reencryption_key_share(&new))
+//!     })
+//!     .collect();
+//! let rk = ReencryptionKey::combine(&old_pks, &new_pks, &rk_shares).unwrap();
+//! let new_ct = ct.reencrypt(&rk);
+//!
+//! let shares: BTreeMap<_, _> = (0..2)
+//!     .map(|i| (i, new_set.secret_key_share(i).decrypt_reencryptable_share(&new_ct)))
+//!     .collect();
+//! assert_eq!(b"stored data".to_vec(), new_pks.decrypt_reencryptable(&shares, &new_ct).unwrap());
+//! ```
+
+use std::fmt;
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroize;
+
+use crate::elgamal::ElGamalCiphertext;
+use crate::error::{Error, Result};
+use crate::kem::{ct_eq, DemKeys, KEY_SIZE};
+use crate::secret::clear_fr;
+use crate::util::sha3_256;
+use crate::{
+    into_fr_plus_1, lagrange_coefficients, DebugDots, DecryptionShare, Fr, G1Affine, IntoFr,
+    PublicKey, PublicKeySet, PublicKeyShare, SecretKey, SecretKeyShare, G1,
+};
+
+/// The domain separator for deriving the symmetric key from the encrypted point.
+const KEY_DST: &[u8] = b"threshold_crypto re-encryptable ciphertext key";
+
+/// A key that lets a proxy re-encrypt ciphertexts from a source key to a target key.
+///
+/// It must only be sent to the proxy over a confidential channel.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ReencryptionKey(#[serde(with = "crate::serde_impl::field_element")] Fr);
+
+impl Zeroize for ReencryptionKey {
+    fn zeroize(&mut self) {
+        clear_fr(&mut self.0)
+    }
+}
+
+impl Drop for ReencryptionKey {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+/// A debug statement where the key is redacted.
+impl fmt::Debug for ReencryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("ReencryptionKey").field(&DebugDots).finish()
+    }
+}
+
+impl ReencryptionKey {
+    /// Returns `true` if this is the re-encryption key from the source to the target.
+    pub fn verify(&self, source: &PublicKey, target: &PublicKey) -> bool {
+        difference_matches(self.0, &source.0, &target.0)
+    }
+
+    /// Combines the shares into the re-encryption key from the source to the target key set.
+    ///
+    /// This needs more shares than the larger of the two thresholds. Returns
+    /// `Error::InterpolationFailed` if the result doesn't match the two master public keys, e.g.
+    /// because one of the shares was invalid: Use `ReencryptionKeyShare::verify` to find it.
+    pub fn combine<'a, T, I>(
+        source: &PublicKeySet,
+        target: &PublicKeySet,
+        shares: I,
+    ) -> Result<Self>
+    where
+        I: IntoIterator<Item = (T, &'a ReencryptionKeyShare)>,
+        T: IntoFr,
+    {
+        let degree = source.threshold().max(target.threshold());
+        let (xs, shares): (Vec<Fr>, Vec<&ReencryptionKeyShare>) = shares
+            .into_iter()
+            .take(degree + 1)
+            .map(|(i, share)| (into_fr_plus_1(i), share))
+            .unzip();
+        if shares.len() <= degree {
+            return Err(Error::NotEnoughShares);
+        }
+        let mut rk = ReencryptionKey(Fr::zero());
+        for (l0, share) in lagrange_coefficients(&xs)?.into_iter().zip(shares) {
+            let mut term = (share.0).0;
+            term.mul_assign(&l0);
+            rk.0.add_assign(&term);
+            clear_fr(&mut term);
+        }
+        if !rk.verify(&source.public_key(), &target.public_key()) {
+            return Err(Error::InterpolationFailed);
+        }
+        Ok(rk)
+    }
+}
+
+/// A share of a re-encryption key between two key sets.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct ReencryptionKeyShare(ReencryptionKey);
+
+impl ReencryptionKeyShare {
+    /// Returns `true` if this is the share from the source to the target public key share.
+    pub fn verify(&self, source: &PublicKeyShare, target: &PublicKeyShare) -> bool {
+        (self.0).verify(&source.0, &target.0)
+    }
+}
+
+impl SecretKey {
+    /// Returns the key that re-encrypts ciphertexts from this key to the target key.
+    pub fn reencryption_key(&self, target: &SecretKey) -> ReencryptionKey {
+        let mut rk = target.0;
+        rk.sub_assign(&self.0);
+        ReencryptionKey(rk)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the share of the re-encryption key from this share's key set to the target share's
+    /// key set. Both shares must have the same index.
+    pub fn reencryption_key_share(&self, target: &SecretKeyShare) -> ReencryptionKeyShare {
+        ReencryptionKeyShare(self.0.reencryption_key(&target.0))
+    }
+
+    /// Returns a decryption share of the ciphertext's encrypted point.
+    pub fn decrypt_reencryptable_share(&self, ct: &ReencryptableCiphertext) -> DecryptionShare {
+        self.decrypt_elgamal_share(&ct.kem)
+    }
+}
+
+impl ElGamalCiphertext {
+    /// Returns the ciphertext re-encrypted from the re-encryption key's source to its target.
+    pub fn reencrypt(&self, rk: &ReencryptionKey) -> Self {
+        let (c1, mut c2) = self.to_points();
+        count!(Exponentiation, 1);
+        c2.add_assign(&c1.into_affine().mul(rk.0));
+        ElGamalCiphertext::from_points(c1, c2)
+    }
+}
+
+/// A message of any size, encrypted so that a proxy can re-encrypt it to a different key.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReencryptableCiphertext {
+    kem: ElGamalCiphertext,
+    body: Vec<u8>,
+    tag: [u8; KEY_SIZE],
+}
+
+impl ReencryptableCiphertext {
+    /// Returns the encrypted point the symmetric key is derived from.
+    pub fn kem(&self) -> &ElGamalCiphertext {
+        &self.kem
+    }
+
+    /// Returns the ciphertext re-encrypted from the re-encryption key's source to its target.
+    pub fn reencrypt(&self, rk: &ReencryptionKey) -> Self {
+        ReencryptableCiphertext {
+            kem: self.kem.reencrypt(rk),
+            body: self.body.clone(),
+            tag: self.tag,
+        }
+    }
+
+    /// Returns the plaintext, or `None` if the point or MAC tag is wrong.
+    fn open(&self, point: &G1) -> Option<Vec<u8>> {
+        let keys = dem_keys(point);
+        if !ct_eq(&keys.tag(&[&self.body]), &self.tag) {
+            return None;
+        }
+        let mut plaintext = self.body.clone();
+        keys.apply_keystream(0, &mut plaintext);
+        Some(plaintext)
+    }
+}
+
+impl PublicKey {
+    /// Encrypts a message of any size, so that it can be re-encrypted.
+    pub fn encrypt_reencryptable<M: AsRef<[u8]>>(&self, msg: M) -> ReencryptableCiphertext {
+        self.encrypt_reencryptable_with_rng(&mut OsRng, msg)
+    }
+
+    /// Encrypts a message of any size so that it can be re-encrypted, using the given random
+    /// number generator.
+    pub fn encrypt_reencryptable_with_rng<R: Rng, M: AsRef<[u8]>>(
+        &self,
+        rng: &mut R,
+        msg: M,
+    ) -> ReencryptableCiphertext {
+        let point = G1::random(rng);
+        let keys = dem_keys(&point);
+        let kem = self.encrypt_elgamal_with_rng(rng, point);
+        let mut body = msg.as_ref().to_vec();
+        keys.apply_keystream(0, &mut body);
+        let tag = keys.tag(&[&body]);
+        ReencryptableCiphertext { kem, body, tag }
+    }
+}
+
+impl SecretKey {
+    /// Returns the decrypted message, or `None` if the ciphertext isn't valid.
+    pub fn decrypt_reencryptable(&self, ct: &ReencryptableCiphertext) -> Option<Vec<u8>> {
+        ct.open(&self.decrypt_elgamal(&ct.kem))
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the decryption shares of the encrypted point, and decrypts the message.
+    ///
+    /// Like `decrypt`, this doesn't verify the shares. Returns `Error::InvalidCiphertext` if the
+    /// combined point doesn't authenticate the message, e.g. because one of the shares was invalid.
+    pub fn decrypt_reencryptable<'a, T, I>(
+        &self,
+        shares: I,
+        ct: &ReencryptableCiphertext,
+    ) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        let point = self.decrypt_elgamal(shares, &ct.kem)?;
+        ct.open(&point).ok_or(Error::InvalidCiphertext)
+    }
+}
+
+/// Returns `true` if `diff * G == target - source`.
+fn difference_matches(diff: Fr, source: &G1, target: &G1) -> bool {
+    count!(Exponentiation, 1);
+    let mut expected = *target;
+    expected.sub_assign(source);
+    G1Affine::one().mul(diff) == expected
+}
+
+/// Derives the symmetric keys from the encrypted point.
+fn dem_keys(point: &G1) -> DemKeys {
+    let mut input = KEY_DST.to_vec();
+    input.extend(point.into_affine().into_compressed().as_ref());
+    let mut key = sha3_256(&input);
+    let keys = DemKeys::new(&key);
+    key.zeroize();
+    keys
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_reencryption() {
+        let mut rng = rand::thread_rng();
+        let (alice, bob) = (SecretKey::random(), SecretKey::random());
+        let rk = alice.reencryption_key(&bob);
+        assert!(rk.verify(&alice.public_key(), &bob.public_key()));
+        assert!(!rk.verify(&bob.public_key(), &alice.public_key()));
+
+        let ct = alice
+            .public_key()
+            .encrypt_reencryptable_with_rng(&mut rng, "hello");
+        let bob_ct = ct.reencrypt(&rk);
+        assert_eq!(Some(b"hello".to_vec()), alice.decrypt_reencryptable(&ct));
+        assert_eq!(None, bob.decrypt_reencryptable(&ct));
+        assert_eq!(Some(b"hello".to_vec()), bob.decrypt_reencryptable(&bob_ct));
+        assert_eq!(None, alice.decrypt_reencryptable(&bob_ct));
+
+        let mut tampered = bob_ct;
+        tampered.body[0] ^= 1;
+        assert_eq!(None, bob.decrypt_reencryptable(&tampered));
+    }
+
+    #[test]
+    fn test_threshold_reencryption() {
+        let mut rng = rand::thread_rng();
+        let old_set = SecretKeySet::random(1, &mut rng);
+        let new_set = SecretKeySet::random(2, &mut rng);
+        let (old_pks, new_pks) = (old_set.public_keys(), new_set.public_keys());
+        let point = G1::random(&mut rng);
+        let ct = old_pks
+            .public_key()
+            .encrypt_elgamal_with_rng(&mut rng, point);
+
+        let rk_shares: BTreeMap<usize, ReencryptionKeyShare> = (0..4)
+            .map(|i| {
+                let old = old_set.secret_key_share(i);
+                let share = old.reencryption_key_share(&new_set.secret_key_share(i));
+                let old_pk = old_pks.public_key_share(i);
+                assert!(share.verify(&old_pk, &new_pks.public_key_share(i)));
+                assert!(!share.verify(&old_pk, &new_pks.public_key_share(i + 1)));
+                (i, share)
+            })
+            .collect();
+
+        // The larger threshold determines how many shares are needed.
+        assert_eq!(
+            Err(Error::NotEnoughShares),
+            ReencryptionKey::combine(&old_pks, &new_pks, rk_shares.iter().take(2))
+        );
+        let rk = ReencryptionKey::combine(&old_pks, &new_pks, &rk_shares).expect("enough shares");
+        let expected = old_set.secret_key().reencryption_key(&new_set.secret_key());
+        assert_eq!(expected, rk);
+        assert_eq!(
+            point,
+            new_set.secret_key().decrypt_elgamal(&ct.reencrypt(&rk))
+        );
+
+        let mut bad_shares = rk_shares.clone();
+        bad_shares.insert(
+            1,
+            old_set
+                .secret_key_share(1)
+                .reencryption_key_share(&old_set.secret_key_share(1)),
+        );
+        assert_eq!(
+            Err(Error::InterpolationFailed),
+            ReencryptionKey::combine(&old_pks, &new_pks, &bad_shares)
+        );
+    }
+}
@@ -0,0 +1,453 @@
+//! Protocol buffer messages for keys, shares, signatures, commitments, ciphertexts and DKG
+//! messages, generated by `prost` from the schemas in `proto/`.
+//!
+//! The generated code is checked in, so building the crate doesn't need `protoc`.
+//!
+//! Every `bytes` field holds the canonical `to_bytes` encoding of the corresponding type. The
+//! messages convert from the crate's types with `From`, and back with `TryFrom`, which decodes and
+//! validates the encodings. Shares are converted together with their index, as a
+//! `(u64, SignatureShare)`, `(u64, DecryptionShare)` or `(u64, PublicKeyShare)` pair.
+//!
+//! ```
+//! use std::convert::TryFrom;
+//!
+//! use prost::Message;
+//! use threshold_crypto::{proto, PublicKeySet, SecretKeySet};
+//!
+//! let pk_set = SecretKeySet::random(2, &mut rand::thread_rng()).public_keys();
+//! let bytes = proto::PublicKeySet::from(pk_set.clone()).encode_to_vec();
+//! let msg = proto::PublicKeySet::decode(&bytes[..]).unwrap();
+//! assert_eq!(Ok(pk_set), PublicKeySet::try_from(msg));
+//! ```
+
+#[allow(missing_docs)]
+#[rustfmt::skip]
+mod types {
+    include!("proto/threshold_crypto.rs");
+}
+
+pub use self::types::*;
+
+#[cfg(all(feature = "dkg", feature = "encryption"))]
+/// The messages of the distributed key generation protocol in [`dkg`](../dkg/index.html).
+#[allow(missing_docs)]
+#[rustfmt::skip]
+pub mod dkg {
+    include!("proto/threshold_crypto.dkg.rs");
+}
+
+mod types_impl {
+    use std::convert::TryFrom;
+
+    use super::types;
+    use crate::error::{ProtoError, ProtoResult};
+    use crate::poly::Commitment;
+    #[cfg(feature = "encryption")]
+    use crate::{Ciphertext, DecryptionShare};
+    use crate::{PublicKey, PublicKeySet, PublicKeyShare, Signature, SignatureShare};
+
+    impl From<PublicKey> for types::PublicKey {
+        fn from(pk: PublicKey) -> Self {
+            types::PublicKey {
+                key: pk.to_bytes().to_vec(),
+            }
+        }
+    }
+
+    impl TryFrom<types::PublicKey> for PublicKey {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::PublicKey) -> ProtoResult<Self> {
+            Ok(PublicKey::from_slice(&msg.key)?)
+        }
+    }
+
+    impl From<(u64, PublicKeyShare)> for types::PublicKeyShare {
+        fn from((index, pk_share): (u64, PublicKeyShare)) -> Self {
+            types::PublicKeyShare {
+                key: pk_share.to_bytes().to_vec(),
+                index,
+            }
+        }
+    }
+
+    impl TryFrom<types::PublicKeyShare> for (u64, PublicKeyShare) {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::PublicKeyShare) -> ProtoResult<Self> {
+            Ok((msg.index, PublicKeyShare::from_slice(&msg.key)?))
+        }
+    }
+
+    impl From<Signature> for types::Signature {
+        fn from(sig: Signature) -> Self {
+            types::Signature {
+                signature: sig.to_bytes().to_vec(),
+            }
+        }
+    }
+
+    impl TryFrom<types::Signature> for Signature {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::Signature) -> ProtoResult<Self> {
+            Ok(Signature::from_slice(&msg.signature)?)
+        }
+    }
+
+    impl From<(u64, SignatureShare)> for types::SignatureShare {
+        fn from((index, share): (u64, SignatureShare)) -> Self {
+            types::SignatureShare {
+                share: share.to_bytes().to_vec(),
+                index,
+            }
+        }
+    }
+
+    impl TryFrom<types::SignatureShare> for (u64, SignatureShare) {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::SignatureShare) -> ProtoResult<Self> {
+            Ok((msg.index, SignatureShare::from_slice(&msg.share)?))
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    impl From<(u64, DecryptionShare)> for types::DecryptionShare {
+        fn from((index, share): (u64, DecryptionShare)) -> Self {
+            types::DecryptionShare {
+                share: share.to_bytes().to_vec(),
+                index,
+            }
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    impl TryFrom<types::DecryptionShare> for (u64, DecryptionShare) {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::DecryptionShare) -> ProtoResult<Self> {
+            Ok((msg.index, DecryptionShare::from_slice(&msg.share)?))
+        }
+    }
+
+    impl From<Commitment> for types::Commitment {
+        fn from(commit: Commitment) -> Self {
+            types::Commitment {
+                commitment: commit.to_bytes(),
+            }
+        }
+    }
+
+    impl TryFrom<types::Commitment> for Commitment {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::Commitment) -> ProtoResult<Self> {
+            Ok(Commitment::from_slice(&msg.commitment)?)
+        }
+    }
+
+    impl From<PublicKeySet> for types::PublicKeySet {
+        fn from(pk_set: PublicKeySet) -> Self {
+            types::PublicKeySet {
+                commitment: pk_set.to_bytes(),
+            }
+        }
+    }
+
+    impl TryFrom<types::PublicKeySet> for PublicKeySet {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::PublicKeySet) -> ProtoResult<Self> {
+            Ok(PublicKeySet::from_slice(&msg.commitment)?)
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    impl From<Ciphertext> for types::Ciphertext {
+        fn from(ct: Ciphertext) -> Self {
+            types::Ciphertext {
+                ciphertext: ct.to_bytes(),
+            }
+        }
+    }
+
+    #[cfg(feature = "encryption")]
+    impl TryFrom<types::Ciphertext> for Ciphertext {
+        type Error = ProtoError;
+
+        fn try_from(msg: types::Ciphertext) -> ProtoResult<Self> {
+            Ok(Ciphertext::from_slice(&msg.ciphertext)?)
+        }
+    }
+}
+
+#[cfg(all(feature = "dkg", feature = "encryption"))]
+mod dkg_impl {
+    use std::convert::TryFrom;
+
+    use super::dkg::{self, message};
+    use crate::decode::decode;
+    use crate::dkg::{fr_to_bytes, Ack, Complete, Disclosure, Message, Part, Reveal};
+    use crate::error::{ProtoError, ProtoResult};
+    use crate::poly::{BivarCommitment, Commitment, Poly};
+    use crate::{Ciphertext, Fr, PublicKey};
+
+    impl From<Part> for dkg::Part {
+        fn from(part: Part) -> Self {
+            dkg::Part {
+                commitment: part.commitment().to_bytes(),
+                rows: part.rows().iter().map(Ciphertext::to_bytes).collect(),
+            }
+        }
+    }
+
+    impl TryFrom<dkg::Part> for Part {
+        type Error = ProtoError;
+
+        fn try_from(msg: dkg::Part) -> ProtoResult<Self> {
+            let commit = BivarCommitment::from_slice(&msg.commitment)?;
+            Ok(Part::new(commit, ciphertexts(&msg.rows)?))
+        }
+    }
+
+    impl From<Ack> for dkg::Ack {
+        fn from(ack: Ack) -> Self {
+            dkg::Ack {
+                proposer: ack.proposer(),
+                values: ack.values().iter().map(Ciphertext::to_bytes).collect(),
+            }
+        }
+    }
+
+    impl TryFrom<dkg::Ack> for Ack {
+        type Error = ProtoError;
+
+        fn try_from(msg: dkg::Ack) -> ProtoResult<Self> {
+            Ok(Ack::new(msg.proposer, ciphertexts(&msg.values)?))
+        }
+    }
+
+    impl From<Reveal> for dkg::Reveal {
+        fn from(reveal: Reveal) -> Self {
+            dkg::Reveal {
+                commitment: reveal.commitment().to_bytes(),
+            }
+        }
+    }
+
+    impl TryFrom<dkg::Reveal> for Reveal {
+        type Error = ProtoError;
+
+        fn try_from(msg: dkg::Reveal) -> ProtoResult<Self> {
+            Ok(Reveal::new(Commitment::from_slice(&msg.commitment)?))
+        }
+    }
+
+    impl From<Disclosure> for dkg::Disclosure {
+        fn from(disclosure: Disclosure) -> Self {
+            dkg::Disclosure {
+                proposer: disclosure.proposer(),
+                row: coefficients(disclosure.row()),
+                blinding: coefficients(disclosure.blinding()),
+            }
+        }
+    }
+
+    impl TryFrom<dkg::Disclosure> for Disclosure {
+        type Error = ProtoError;
+
+        fn try_from(msg: dkg::Disclosure) -> ProtoResult<Self> {
+            let row = poly(&msg.row)?;
+            let blinding = poly(&msg.blinding)?;
+            Ok(Disclosure::new(msg.proposer, row, blinding))
+        }
+    }
+
+    impl From<Complete> for dkg::Complete {
+        fn from(complete: Complete) -> Self {
+            dkg::Complete {
+                proposers: complete.proposers().iter().cloned().collect(),
+                public_key: complete.public_key().to_bytes().to_vec(),
+            }
+        }
+    }
+
+    impl TryFrom<dkg::Complete> for Complete {
+        type Error = ProtoError;
+
+        fn try_from(msg: dkg::Complete) -> ProtoResult<Self> {
+            let public_key = PublicKey::from_slice(&msg.public_key)?;
+            Ok(Complete::new(
+                msg.proposers.into_iter().collect(),
+                public_key,
+            ))
+        }
+    }
+
+    impl From<Message> for dkg::Message {
+        fn from(msg: Message) -> Self {
+            let msg = match msg {
+                Message::Part(part) => message::Message::Part(part.into()),
+                Message::Ack(ack) => message::Message::Ack(ack.into()),
+                Message::Reveal(reveal) => message::Message::Reveal(reveal.into()),
+                Message::Disclosure(disclosure) => message::Message::Disclosure(disclosure.into()),
+            };
+            dkg::Message { message: Some(msg) }
+        }
+    }
+
+    impl TryFrom<dkg::Message> for Message {
+        type Error = ProtoError;
+
+        /// Returns `ProtoError::MissingField` if the `oneof` is not set.
+        fn try_from(msg: dkg::Message) -> ProtoResult<Self> {
+            match msg.message.ok_or(ProtoError::MissingField("message"))? {
+                message::Message::Part(part) => Ok(Message::Part(Part::try_from(part)?)),
+                message::Message::Ack(ack) => Ok(Message::Ack(Ack::try_from(ack)?)),
+                message::Message::Reveal(reveal) => Ok(Message::Reveal(Reveal::try_from(reveal)?)),
+                message::Message::Disclosure(disclosure) => {
+                    Ok(Message::Disclosure(Disclosure::try_from(disclosure)?))
+                }
+            }
+        }
+    }
+
+    /// Decodes each of the ciphertexts.
+    fn ciphertexts(encoded: &[Vec<u8>]) -> ProtoResult<Vec<Ciphertext>> {
+        let decoded = encoded.iter().map(|bytes| Ciphertext::from_slice(bytes));
+        Ok(decoded.collect::<Result<_, _>>()?)
+    }
+
+    /// Returns the polynomial's coefficients as big-endian field elements, constant term first.
+    fn coefficients(poly: &Poly) -> Vec<Vec<u8>> {
+        poly.coeff.iter().map(|c| fr_to_bytes(c).to_vec()).collect()
+    }
+
+    /// Decodes a polynomial from its big-endian coefficients, constant term first.
+    fn poly(encoded: &[Vec<u8>]) -> ProtoResult<Poly> {
+        let decoded = encoded
+            .iter()
+            .map(|bytes| decode(bytes, "Fr", |r| r.fr("coefficient")));
+        let coeff: Vec<Fr> = decoded.collect::<Result<_, _>>()?;
+        Ok(Poly::from(coeff))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use prost::Message;
+
+    use crate::error::{ProtoError, ProtoResult};
+    use crate::{proto, SecretKeySet, Signature};
+
+    /// Encodes and decodes the message, and converts it back.
+    fn round_trip<T, M>(value: T) -> ProtoResult<T>
+    where
+        M: Message + Default + From<T>,
+        T: TryFrom<M, Error = ProtoError>,
+    {
+        let bytes = M::from(value).encode_to_vec();
+        T::try_from(M::decode(&bytes[..]).expect("valid protobuf"))
+    }
+
+    #[test]
+    fn test_types() {
+        let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let pk_set = sk_set.public_keys();
+        let sk_share = sk_set.secret_key_share(3);
+        let pk = pk_set.public_key();
+        assert_eq!(Ok(pk), round_trip::<_, proto::PublicKey>(pk));
+        let pk_share = (3, pk_set.public_key_share(3));
+        assert_eq!(
+            Ok(pk_share),
+            round_trip::<_, proto::PublicKeyShare>(pk_share)
+        );
+        let sig = sk_set.secret_key().sign("msg");
+        assert_eq!(Ok(sig.clone()), round_trip::<_, proto::Signature>(sig));
+        let share = (3, sk_share.sign("msg"));
+        assert_eq!(
+            Ok(share.clone()),
+            round_trip::<_, proto::SignatureShare>(share)
+        );
+        assert_eq!(
+            Ok(pk_set.clone()),
+            round_trip::<_, proto::PublicKeySet>(pk_set.clone())
+        );
+        let commit = pk_set.commit.clone();
+        assert_eq!(
+            Ok(commit.clone()),
+            round_trip::<_, proto::Commitment>(commit)
+        );
+
+        // Invalid encodings are rejected.
+        let msg = proto::Signature {
+            signature: vec![0; 3],
+        };
+        assert!(matches!(
+            Signature::try_from(msg),
+            Err(ProtoError::Decode(_))
+        ));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_encryption_types() {
+        let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let ct = sk_set.public_keys().public_key().encrypt("msg");
+        let share = (1, sk_set.secret_key_share(1).decrypt_share_no_verify(&ct));
+        assert_eq!(Ok(ct.clone()), round_trip::<_, proto::Ciphertext>(ct));
+        assert_eq!(
+            Ok(share.clone()),
+            round_trip::<_, proto::DecryptionShare>(share)
+        );
+    }
+
+    #[cfg(all(feature = "dkg", feature = "encryption"))]
+    #[test]
+    fn test_dkg_messages() {
+        use crate::dkg::{Ack, Complete, Disclosure, Message as DkgMessage, Part, Reveal};
+        use crate::poly::{BivarPoly, Poly};
+        use crate::SecretKey;
+
+        let mut rng = rand::thread_rng();
+        let pk = SecretKey::random().public_key();
+        let bi_poly = BivarPoly::random(1, &mut rng);
+        let row = bi_poly.row(1);
+        let part = Part::new(bi_poly.commitment(), vec![pk.encrypt("row")]);
+        let ack = Ack::new(2, vec![pk.encrypt("value"), pk.encrypt("value")]);
+        let reveal = Reveal::new(bi_poly.row(0).commitment());
+        let disclosure = Disclosure::new(2, row.clone(), Poly::random(1, &mut rng));
+        let complete = Complete::new(vec![0, 2].into_iter().collect(), pk);
+
+        assert_eq!(
+            Ok(complete.clone()),
+            round_trip::<_, proto::dkg::Complete>(complete)
+        );
+        for msg in [
+            DkgMessage::Part(part),
+            DkgMessage::Ack(ack),
+            DkgMessage::Reveal(reveal),
+            DkgMessage::Disclosure(disclosure),
+        ] {
+            assert_eq!(Ok(msg.clone()), round_trip::<_, proto::dkg::Message>(msg));
+        }
+        assert_eq!(
+            Err(ProtoError::MissingField("message")),
+            DkgMessage::try_from(proto::dkg::Message::default())
+        );
+
+        // A coefficient that isn't a reduced field element is rejected.
+        let msg = proto::dkg::Disclosure {
+            proposer: 2,
+            row: vec![vec![0xff; 32]],
+            blinding: vec![],
+        };
+        assert!(matches!(
+            Disclosure::try_from(msg),
+            Err(ProtoError::Decode(_))
+        ));
+    }
+}
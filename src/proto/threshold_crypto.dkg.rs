@@ -0,0 +1,66 @@
+// This file is @generated by prost-build.
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Part {
+    /// A `BivarCommitment`: the degree as a big-endian `u64`, followed by the compressed
+    /// coefficients.
+    #[prost(bytes = "vec", tag = "1")]
+    pub commitment: ::prost::alloc::vec::Vec<u8>,
+    /// One encrypted row per node, as `Ciphertext` encodings, in node order.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub rows: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Ack {
+    /// The index of the node whose part is acknowledged.
+    #[prost(uint64, tag = "1")]
+    pub proposer: u64,
+    /// One encrypted value per node, as `Ciphertext` encodings, in node order.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub values: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Reveal {
+    /// A `Commitment` to row 0 of the proposer's polynomial.
+    #[prost(bytes = "vec", tag = "1")]
+    pub commitment: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Disclosure {
+    /// The index of the node whose rows are disclosed.
+    #[prost(uint64, tag = "1")]
+    pub proposer: u64,
+    /// The coefficients of the row and blinding polynomials, as 32-byte big-endian field
+    /// elements, constant term first.
+    #[prost(bytes = "vec", repeated, tag = "2")]
+    pub row: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+    #[prost(bytes = "vec", repeated, tag = "3")]
+    pub blinding: ::prost::alloc::vec::Vec<::prost::alloc::vec::Vec<u8>>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Complete {
+    /// The indices of the nodes whose parts were used, in ascending order.
+    #[prost(uint64, repeated, tag = "1")]
+    pub proposers: ::prost::alloc::vec::Vec<u64>,
+    /// 48 bytes: the compressed generated master public key.
+    #[prost(bytes = "vec", tag = "2")]
+    pub public_key: ::prost::alloc::vec::Vec<u8>,
+}
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct Message {
+    #[prost(oneof = "message::Message", tags = "1, 2, 3, 4")]
+    pub message: ::core::option::Option<message::Message>,
+}
+/// Nested message and enum types in `Message`.
+pub mod message {
+    #[derive(Clone, PartialEq, ::prost::Oneof)]
+    pub enum Message {
+        #[prost(message, tag = "1")]
+        Part(super::Part),
+        #[prost(message, tag = "2")]
+        Ack(super::Ack),
+        #[prost(message, tag = "3")]
+        Reveal(super::Reveal),
+        #[prost(message, tag = "4")]
+        Disclosure(super::Disclosure),
+    }
+}
@@ -0,0 +1,167 @@
+//! Publicly verifiable secret sharing.
+//!
+//! A dealer that shares a key among participants usually sends each of them their share in
+//! private, so only the participants themselves can check it. A [`Dealing`] instead publishes
+//! every participant's `SecretKeyShare` as an [`EscrowedShare`], encrypted to the participant's
+//! `PublicKey` with a zero-knowledge proof that it is the key share of the dealt `PublicKeySet`.
+//! Anyone can verify the whole dealing, e.g. a smart contract or the other nodes of a randomness
+//! beacon, and each participant decrypts its share with its `SecretKey`.
+//!
+//! Like escrowed shares, a dealing is large: about 60 kB per participant.
+//!
+//! ```
+//! use threshold_crypto::pvss::Dealing;
+//! use threshold_crypto::SecretKey;
+//!
+//! let mut rng = rand::thread_rng();
+//! let sec_keys: Vec<SecretKey> = (0..3).map(|_| SecretKey::random()).collect();
+//! let pub_keys: Vec<_> = sec_keys.iter().map(SecretKey::public_key).collect();
+//! let dealing = Dealing::new(&pub_keys, 1, &mut rng).unwrap();
+//! assert!(dealing.verify(&pub_keys, 1));
+//!
+//! let share = dealing.decrypt_share(2, &sec_keys[2]).unwrap();
+//! assert_eq!(dealing.public_keys().public_key_share(2), share.public_key_share());
+//! ```
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::escrow::EscrowedShare;
+use crate::poly::Poly;
+use crate::{PublicKey, PublicKeySet, SecretKey, SecretKeySet, SecretKeyShare};
+
+/// A key set with each participant's share encrypted to it, verifiable by anyone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Dealing {
+    pk_set: PublicKeySet,
+    shares: Vec<EscrowedShare>,
+}
+
+impl Dealing {
+    /// Deals a random key to the participants with the given public keys: Participant `i`
+    /// receives share `i`, and any `threshold + 1` of them can sign and decrypt.
+    ///
+    /// Returns `Error::NotEnoughShares` if there are no more than `threshold` participants.
+    pub fn new<R: Rng>(pub_keys: &[PublicKey], threshold: usize, rng: &mut R) -> Result<Self> {
+        let poly = Poly::try_random(threshold, rng)?;
+        Dealing::from_key_set(&SecretKeySet::from(poly), pub_keys, rng)
+    }
+
+    /// Deals the given secret key to the participants, like `new`.
+    pub fn with_secret<R: Rng>(
+        secret: &SecretKey,
+        pub_keys: &[PublicKey],
+        threshold: usize,
+        rng: &mut R,
+    ) -> Result<Self> {
+        let sk_set = SecretKeySet::from_secret_key(secret, threshold, rng)?;
+        Dealing::from_key_set(&sk_set, pub_keys, rng)
+    }
+
+    /// Encrypts each participant's share of the key set.
+    fn from_key_set<R: Rng>(
+        sk_set: &SecretKeySet,
+        pub_keys: &[PublicKey],
+        rng: &mut R,
+    ) -> Result<Self> {
+        let _span = span!("pvss_deal", participants = pub_keys.len());
+        if pub_keys.len() <= sk_set.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        let shares = pub_keys
+            .iter()
+            .enumerate()
+            .map(|(i, pk)| EscrowedShare::new(&sk_set.secret_key_share(i), i, pk, rng))
+            .collect();
+        Ok(Dealing {
+            pk_set: sk_set.public_keys(),
+            shares,
+        })
+    }
+
+    /// Returns the dealt public key set.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the encrypted shares, in the order of the participants.
+    pub fn shares(&self) -> &[EscrowedShare] {
+        &self.shares
+    }
+
+    /// Returns `true` if the dealing has the given threshold, and contains a valid share for
+    /// each of the participants with the given public keys, in order.
+    pub fn verify(&self, pub_keys: &[PublicKey], threshold: usize) -> bool {
+        let _span = span!("pvss_verify", participants = pub_keys.len());
+        self.pk_set.threshold() == threshold
+            && self.shares.len() == pub_keys.len()
+            && pub_keys
+                .iter()
+                .enumerate()
+                .all(|(i, pk)| self.verify_share(i, pk))
+    }
+
+    /// Returns `true` if the dealing contains a valid share for participant `i`, with the given
+    /// public key.
+    pub fn verify_share(&self, i: usize, pk: &PublicKey) -> bool {
+        self.shares
+            .get(i)
+            .is_some_and(|share| share.index() == i && share.verify(&self.pk_set, pk))
+    }
+
+    /// Decrypts the share of participant `i` with its secret key.
+    ///
+    /// Returns `Error::InvalidShare(i)` if there is no such share, or it doesn't decrypt to the
+    /// key share of the dealt key set.
+    pub fn decrypt_share(&self, i: usize, sk: &SecretKey) -> Result<SecretKeyShare> {
+        let share = self.shares.get(i).ok_or(Error::InvalidShare(i))?;
+        if share.index() != i {
+            return Err(Error::InvalidShare(i));
+        }
+        share.decrypt(sk, &self.pk_set)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_pvss() {
+        let mut rng = rand::thread_rng();
+        let sec_keys: Vec<SecretKey> = (0..2).map(|_| SecretKey::random()).collect();
+        let pub_keys: Vec<PublicKey> = sec_keys.iter().map(SecretKey::public_key).collect();
+        let secret = SecretKey::random();
+        let dealing = Dealing::with_secret(&secret, &pub_keys, 1, &mut rng).expect("dealing");
+        assert!(dealing.verify(&pub_keys, 1));
+        assert!(!dealing.verify(&pub_keys, 2));
+        assert!(!dealing.verify(&pub_keys[..1], 1));
+        assert_eq!(secret.public_key(), dealing.public_keys().public_key());
+
+        let sig_shares: BTreeMap<usize, _> = sec_keys
+            .iter()
+            .enumerate()
+            .map(|(i, sk)| {
+                let share = dealing.decrypt_share(i, sk).expect("valid share");
+                (i, share.sign("msg"))
+            })
+            .collect();
+        let pk_set = dealing.public_keys();
+        let sig = pk_set
+            .combine_signatures(&sig_shares)
+            .expect("enough shares");
+        assert_eq!(secret.sign("msg"), sig);
+        let result = dealing.decrypt_share(0, &sec_keys[1]);
+        assert_eq!(Some(Error::InvalidShare(0)), result.err());
+
+        // Swapping two participants' shares is detected.
+        let mut swapped = dealing;
+        swapped.shares.swap(0, 1);
+        assert!(!swapped.verify(&pub_keys, 1));
+        let result = Dealing::new(&pub_keys[..1], 1, &mut rng);
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+    }
+}
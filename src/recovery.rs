@@ -0,0 +1,579 @@
+//! Recovery of a lost key share.
+//!
+//! A node that lost its `SecretKeyShare`, e.g. with its disk, can get it back from `threshold + 1`
+//! other nodes without anyone learning the master key or another node's share. The lost share
+//! `s_r` is the Lagrange interpolation of the helpers' shares at the lost index: the sum of the
+//! _contributions_ `λ_i * s_i`. Every helper splits its contribution into random pieces, one for
+//! each helper, and broadcasts a [`Blinding`] with the pieces encrypted to their helpers and
+//! commitments to all of them. Each helper then sends the sum of the pieces it received to the
+//! lost node as a [`Contribution`], and the lost node adds them up. A single contribution is a
+//! uniformly random value, so that only the sum reveals anything.
+//!
+//! The commitments let everyone check that a helper's pieces add up to its contribution,
+//! according to the `PublicKeySet`, and let the lost node check each helper's sum. Recovery needs
+//! all selected helpers to be honest: If one of them is faulty, the lost node must start again
+//! with a different set of helpers.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::recovery::{Helper, Outcome, Recipient};
+//! use threshold_crypto::{SecretKey, SecretKeySet};
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! // Nodes 0 and 2 help node 1 recover its share.
+//! let node_keys: BTreeMap<usize, SecretKey> = vec![0, 2].into_iter().map(|i| (i, rand::random())).collect();
+//! let helper_keys = node_keys.iter().map(|(i, sk)| (*i, sk.public_key())).collect();
+//! let (mut recipient, request) = Recipient::new(1, pk_set.clone(), helper_keys, &mut rng).unwrap();
+//!
+//! let mut helpers = Vec::new();
+//! let mut blindings = Vec::new();
+//! for (i, sk) in node_keys {
+//!     let share = sk_set.secret_key_share(i);
+//!     let (helper, blinding) = Helper::new(i, sk, share, pk_set.clone(), request.clone(), &mut rng).unwrap();
+//!     helpers.push(helper);
+//!     blindings.push((i, blinding));
+//! }
+//! for (sender, blinding) in blindings {
+//!     for helper in &mut helpers {
+//!         assert_eq!(Outcome::Valid, helper.handle_blinding(sender, &blinding).unwrap());
+//!     }
+//!     assert_eq!(Outcome::Valid, recipient.handle_blinding(sender, &blinding).unwrap());
+//! }
+//! for helper in &helpers {
+//!     let contribution = helper.contribution(&mut rng).expect("all blindings");
+//!     let outcome = recipient.handle_contribution(helper.our_index(), contribution);
+//!     assert_eq!(Outcome::Valid, outcome.unwrap());
+//! }
+//! let share = recipient.share().unwrap();
+//! assert_eq!(pk_set.public_key_share(1), share.public_key_share());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::decode::decode;
+use crate::error::{Error, Result};
+use crate::lagrange_coefficients;
+use crate::secret::clear_fr;
+use crate::{Ciphertext, Fr, G1Affine, PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+use crate::{DebugDots, IntoFr, G1, SK_SIZE};
+
+/// A lost node's request for help: its index, the helpers, and the key to encrypt its
+/// contributions to.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Request {
+    lost: usize,
+    key: PublicKey,
+    helpers: BTreeMap<usize, PublicKey>,
+}
+
+impl Request {
+    /// Returns the index of the lost share.
+    pub fn lost(&self) -> usize {
+        self.lost
+    }
+
+    /// Returns the helpers' share indices and public keys.
+    pub fn helpers(&self) -> &BTreeMap<usize, PublicKey> {
+        &self.helpers
+    }
+}
+
+/// A helper's contribution, split into pieces for each helper.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Blinding {
+    /// The commitments to the pieces, in the order of the helpers' indices.
+    #[serde(with = "crate::serde_impl::projective_vec")]
+    commits: Vec<G1>,
+    /// The pieces, encrypted to their helpers.
+    values: Vec<Ciphertext>,
+}
+
+/// A helper's sum of the pieces it received, encrypted to the lost node.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Contribution {
+    value: Ciphertext,
+}
+
+/// The result of handling a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The message was valid.
+    Valid,
+    /// The message was invalid, and its sender is faulty.
+    Invalid(Fault),
+}
+
+/// A reason why a message is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Fault {
+    /// The sender has already sent this kind of message.
+    DuplicateMessage,
+    /// The number of commitments or values is not the number of helpers.
+    EntryCount,
+    /// The pieces don't add up to the sender's contribution, according to the key set.
+    ContributionCommitment,
+    /// This node's value could not be decrypted or decoded.
+    InvalidValue,
+    /// This node's value doesn't match the commitment.
+    ValueCommitment,
+}
+
+/// The parameters of a recovery, shared by the helpers and the lost node.
+#[derive(Clone, Debug)]
+struct Session {
+    pk_set: PublicKeySet,
+    request: Request,
+    /// The Lagrange coefficients of the helpers' shares at the lost index.
+    lambdas: BTreeMap<usize, Fr>,
+}
+
+impl Session {
+    /// Returns the session, or an error if the helpers can't interpolate the lost share.
+    fn new(pk_set: PublicKeySet, request: Request) -> Result<Self> {
+        if request.helpers.len() <= pk_set.threshold() {
+            return Err(Error::NotEnoughShares);
+        }
+        if request.helpers.contains_key(&request.lost) {
+            return Err(Error::DuplicateEntry);
+        }
+        // Shifting all points by the lost index moves the interpolation to `0`.
+        let xs: Vec<Fr> = request
+            .helpers
+            .keys()
+            .map(|i| {
+                let mut x = i.into_fr();
+                x.sub_assign(&request.lost.into_fr());
+                x
+            })
+            .collect();
+        let lambdas = request
+            .helpers
+            .keys()
+            .cloned()
+            .zip(lagrange_coefficients(&xs)?)
+            .collect();
+        Ok(Session {
+            pk_set,
+            request,
+            lambdas,
+        })
+    }
+
+    /// Returns the sender's position among the helpers, or `Error::UnknownParticipant`.
+    fn position(&self, sender: usize) -> Result<usize> {
+        let helpers = &self.request.helpers;
+        helpers
+            .keys()
+            .position(|i| *i == sender)
+            .ok_or(Error::UnknownParticipant)
+    }
+
+    /// Verifies the public parts of the sender's blinding.
+    fn check_blinding(
+        &self,
+        sender: usize,
+        blinding: &Blinding,
+    ) -> ::std::result::Result<(), Fault> {
+        let count = self.request.helpers.len();
+        if blinding.commits.len() != count || blinding.values.len() != count {
+            return Err(Fault::EntryCount);
+        }
+        let mut sum = G1::zero();
+        for commit in &blinding.commits {
+            sum.add_assign(commit);
+        }
+        let mut expected = (self.pk_set.public_key_share(sender).0).0;
+        expected.mul_assign(self.lambdas[&sender]);
+        if sum != expected {
+            return Err(Fault::ContributionCommitment);
+        }
+        Ok(())
+    }
+}
+
+/// A node's state while it helps a lost node recover its share.
+#[derive(Clone)]
+pub struct Helper {
+    our_idx: usize,
+    sec_key: SecretKey,
+    session: Session,
+    /// The pieces received from each helper.
+    values: BTreeMap<usize, Fr>,
+}
+
+impl fmt::Debug for Helper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Helper")
+            .field("our_idx", &self.our_idx)
+            .field("sec_key", &DebugDots)
+            .field("session", &self.session)
+            .field("values", &self.values.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl Drop for Helper {
+    fn drop(&mut self) {
+        for value in self.values.values_mut() {
+            clear_fr(value);
+        }
+    }
+}
+
+impl Helper {
+    /// Creates the state of helper `our_idx` with the given secret key and key share, and
+    /// returns the `Blinding` it must send to the other helpers and the lost node.
+    ///
+    /// Returns `Error::UnknownParticipant` if it is not one of the helpers,
+    /// `Error::InvalidShare(our_idx)` if the share doesn't match the key set, and
+    /// `Error::NotEnoughShares` if there are no more than `threshold` helpers.
+    pub fn new<R: Rng>(
+        our_idx: usize,
+        sec_key: SecretKey,
+        share: SecretKeyShare,
+        pk_set: PublicKeySet,
+        request: Request,
+        rng: &mut R,
+    ) -> Result<(Self, Blinding)> {
+        let session = Session::new(pk_set, request)?;
+        session.position(our_idx)?;
+        if share.public_key_share() != session.pk_set.public_key_share(our_idx) {
+            return Err(Error::InvalidShare(our_idx));
+        }
+        let mut contribution = (share.0).0;
+        contribution.mul_assign(&session.lambdas[&our_idx]);
+        // The last piece is the contribution minus all the random ones.
+        let count = session.request.helpers.len();
+        let mut pieces: Vec<Fr> = (1..count).map(|_| Fr::random(rng)).collect();
+        for piece in &pieces {
+            contribution.sub_assign(piece);
+        }
+        pieces.push(contribution);
+        clear_fr(&mut contribution);
+        let commits = pieces.iter().map(|p| G1Affine::one().mul(*p)).collect();
+        let values = session
+            .request
+            .helpers
+            .values()
+            .zip(&pieces)
+            .map(|(pk, piece)| pk.encrypt_with_rng(rng, &*fr_to_bytes(piece)))
+            .collect();
+        pieces.iter_mut().for_each(clear_fr);
+        let helper = Helper {
+            our_idx,
+            sec_key,
+            session,
+            values: BTreeMap::new(),
+        };
+        Ok((helper, Blinding { commits, values }))
+    }
+
+    /// Returns this helper's share index.
+    pub fn our_index(&self) -> usize {
+        self.our_idx
+    }
+
+    /// Handles the `Blinding` by helper `sender`, which may be this node. Returns
+    /// `Error::UnknownParticipant` if the sender is not one of the helpers.
+    pub fn handle_blinding(&mut self, sender: usize, blinding: &Blinding) -> Result<Outcome> {
+        self.session.position(sender)?;
+        if self.values.contains_key(&sender) {
+            return Ok(Outcome::Invalid(Fault::DuplicateMessage));
+        }
+        if let Err(fault) = self.session.check_blinding(sender, blinding) {
+            return Ok(Outcome::Invalid(fault));
+        }
+        let pos = self.session.position(self.our_idx)?;
+        let value = match self.sec_key.decrypt(&blinding.values[pos]) {
+            Some(bytes) => fr_from_bytes(&Zeroizing::new(bytes)),
+            None => None,
+        };
+        let value = match value {
+            Some(value) => value,
+            None => return Ok(Outcome::Invalid(Fault::InvalidValue)),
+        };
+        if G1Affine::one().mul(value) != blinding.commits[pos] {
+            return Ok(Outcome::Invalid(Fault::ValueCommitment));
+        }
+        self.values.insert(sender, value);
+        Ok(Outcome::Valid)
+    }
+
+    /// Returns the `Contribution` to send to the lost node, or `None` if some blindings are
+    /// still missing.
+    pub fn contribution<R: Rng>(&self, rng: &mut R) -> Option<Contribution> {
+        if self.values.len() < self.session.request.helpers.len() {
+            return None;
+        }
+        let mut sum = Fr::zero();
+        for value in self.values.values() {
+            sum.add_assign(value);
+        }
+        let value = self
+            .session
+            .request
+            .key
+            .encrypt_with_rng(rng, &*fr_to_bytes(&sum));
+        clear_fr(&mut sum);
+        Some(Contribution { value })
+    }
+}
+
+/// The state of the node that recovers its lost share.
+#[derive(Clone)]
+pub struct Recipient {
+    sec_key: SecretKey,
+    session: Session,
+    /// The helpers' commitments to their pieces.
+    commits: BTreeMap<usize, Vec<G1>>,
+    /// The helpers' sums of their pieces.
+    contributions: BTreeMap<usize, Fr>,
+}
+
+impl fmt::Debug for Recipient {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Recipient")
+            .field("sec_key", &DebugDots)
+            .field("session", &self.session)
+            .field("commits", &self.commits.keys().collect::<Vec<_>>())
+            .field(
+                "contributions",
+                &self.contributions.keys().collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+impl Drop for Recipient {
+    fn drop(&mut self) {
+        for value in self.contributions.values_mut() {
+            clear_fr(value);
+        }
+    }
+}
+
+impl Recipient {
+    /// Creates the state of the node with the lost share `lost` of the key set, and returns the
+    /// `Request` to send to the given helpers, i.e. their indices and public keys.
+    ///
+    /// Returns `Error::NotEnoughShares` if there are no more than `threshold` helpers, and
+    /// `Error::DuplicateEntry` if the lost node is one of them.
+    pub fn new<R: Rng>(
+        lost: usize,
+        pk_set: PublicKeySet,
+        helpers: BTreeMap<usize, PublicKey>,
+        rng: &mut R,
+    ) -> Result<(Self, Request)> {
+        let sec_key: SecretKey = rng.gen();
+        let request = Request {
+            lost,
+            key: sec_key.public_key(),
+            helpers,
+        };
+        let recipient = Recipient {
+            sec_key,
+            session: Session::new(pk_set, request.clone())?,
+            commits: BTreeMap::new(),
+            contributions: BTreeMap::new(),
+        };
+        Ok((recipient, request))
+    }
+
+    /// Handles the `Blinding` by helper `sender`. Returns `Error::UnknownParticipant` if the
+    /// sender is not one of the helpers.
+    pub fn handle_blinding(&mut self, sender: usize, blinding: &Blinding) -> Result<Outcome> {
+        self.session.position(sender)?;
+        if self.commits.contains_key(&sender) {
+            return Ok(Outcome::Invalid(Fault::DuplicateMessage));
+        }
+        if let Err(fault) = self.session.check_blinding(sender, blinding) {
+            return Ok(Outcome::Invalid(fault));
+        }
+        self.commits.insert(sender, blinding.commits.clone());
+        Ok(Outcome::Valid)
+    }
+
+    /// Handles the `Contribution` by helper `sender`. It is verified by `share`, once all
+    /// blindings are known. Returns `Error::UnknownParticipant` if the sender is not one of the
+    /// helpers.
+    pub fn handle_contribution(
+        &mut self,
+        sender: usize,
+        contribution: Contribution,
+    ) -> Result<Outcome> {
+        self.session.position(sender)?;
+        if self.contributions.contains_key(&sender) {
+            return Ok(Outcome::Invalid(Fault::DuplicateMessage));
+        }
+        let value = match self.sec_key.decrypt(&contribution.value) {
+            Some(bytes) => fr_from_bytes(&Zeroizing::new(bytes)),
+            None => None,
+        };
+        match value {
+            Some(value) => {
+                self.contributions.insert(sender, value);
+                Ok(Outcome::Valid)
+            }
+            None => Ok(Outcome::Invalid(Fault::InvalidValue)),
+        }
+    }
+
+    /// Returns the recovered share.
+    ///
+    /// Returns `Error::NotEnoughShares` if a helper's blinding or contribution is missing, and
+    /// `Error::InvalidShare(i)` if helper `i`'s contribution doesn't match the blindings.
+    pub fn share(&self) -> Result<SecretKeyShare> {
+        let helpers = &self.session.request.helpers;
+        if self.commits.len() < helpers.len() || self.contributions.len() < helpers.len() {
+            return Err(Error::NotEnoughShares);
+        }
+        let mut sk_val = Fr::zero();
+        for (pos, (i, value)) in self.contributions.iter().enumerate() {
+            let mut expected = G1::zero();
+            for commits in self.commits.values() {
+                expected.add_assign(&commits[pos]);
+            }
+            if G1Affine::one().mul(*value) != expected {
+                clear_fr(&mut sk_val);
+                return Err(Error::InvalidShare(*i));
+            }
+            sk_val.add_assign(value);
+        }
+        let lost = self.session.request.lost;
+        let share = SecretKeyShare::from_mut(&mut sk_val);
+        if share.public_key_share() != self.session.pk_set.public_key_share(lost) {
+            return Err(Error::InvalidShare(lost));
+        }
+        Ok(share)
+    }
+}
+
+/// Returns the field element's big-endian representation.
+fn fr_to_bytes(fr: &Fr) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+    fr.into_repr()
+        .write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    bytes
+}
+
+/// Decodes a big-endian field element.
+fn fr_from_bytes(bytes: &[u8]) -> Option<Fr> {
+    decode(bytes, "Piece", |r| r.fr("value")).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_recovery() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let node_keys: BTreeMap<usize, SecretKey> = vec![0, 1, 4]
+            .into_iter()
+            .map(|i| (i, rand::random()))
+            .collect();
+        let helper_keys: BTreeMap<usize, PublicKey> = node_keys
+            .iter()
+            .map(|(i, sk)| (*i, sk.public_key()))
+            .collect();
+        let result = Recipient::new(1, pk_set.clone(), helper_keys.clone(), &mut rng);
+        assert_eq!(Some(Error::DuplicateEntry), result.err());
+        let mut too_few = helper_keys.clone();
+        too_few.remove(&0);
+        let result = Recipient::new(3, pk_set.clone(), too_few, &mut rng);
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+        let (mut recipient, request) =
+            Recipient::new(3, pk_set.clone(), helper_keys, &mut rng).expect("recipient");
+
+        let share = sk_set.secret_key_share(1);
+        let result = Helper::new(
+            0,
+            rand::random(),
+            share,
+            pk_set.clone(),
+            request.clone(),
+            &mut rng,
+        );
+        assert_eq!(Some(Error::InvalidShare(0)), result.err());
+        let (mut helpers, blindings): (Vec<Helper>, Vec<Blinding>) = node_keys
+            .iter()
+            .map(|(i, sk)| {
+                let share = sk_set.secret_key_share(*i);
+                Helper::new(
+                    *i,
+                    sk.clone(),
+                    share,
+                    pk_set.clone(),
+                    request.clone(),
+                    &mut rng,
+                )
+                .expect("helper")
+            })
+            .unzip();
+
+        // A helper that swaps its pieces' commitments is detected.
+        let mut swapped = blindings[0].clone();
+        swapped.commits.swap(0, 1);
+        assert_eq!(
+            Ok(Outcome::Invalid(Fault::ValueCommitment)),
+            helpers[1].handle_blinding(0, &swapped)
+        );
+        let mut other = blindings[1].clone();
+        other.commits[0] = G1Affine::one().into_projective();
+        assert_eq!(
+            Ok(Outcome::Invalid(Fault::ContributionCommitment)),
+            recipient.handle_blinding(1, &other)
+        );
+
+        for (sender, blinding) in node_keys.keys().zip(&blindings) {
+            for helper in &mut helpers {
+                assert_eq!(
+                    Ok(Outcome::Valid),
+                    helper.handle_blinding(*sender, blinding)
+                );
+            }
+            assert_eq!(
+                Ok(Outcome::Valid),
+                recipient.handle_blinding(*sender, blinding)
+            );
+        }
+        let result = recipient.handle_blinding(4, &blindings[2]);
+        assert_eq!(Ok(Outcome::Invalid(Fault::DuplicateMessage)), result);
+        assert_eq!(Some(Error::NotEnoughShares), recipient.share().err());
+
+        // A contribution that is not the sum of the helper's pieces is detected.
+        let mut wrong = recipient.clone();
+        let value = request.key.encrypt(&*fr_to_bytes(&Fr::one()));
+        for helper in &helpers[1..] {
+            let contribution = helper.contribution(&mut rng).expect("all blindings");
+            let outcome = wrong.handle_contribution(helper.our_index(), contribution);
+            assert_eq!(Ok(Outcome::Valid), outcome);
+        }
+        let outcome = wrong.handle_contribution(0, Contribution { value });
+        assert_eq!(Ok(Outcome::Valid), outcome);
+        assert_eq!(Some(Error::InvalidShare(0)), wrong.share().err());
+
+        for helper in &helpers {
+            let contribution = helper.contribution(&mut rng).expect("all blindings");
+            let outcome = recipient.handle_contribution(helper.our_index(), contribution);
+            assert_eq!(Ok(Outcome::Valid), outcome);
+        }
+        let share = recipient.share().expect("recovered share");
+        assert_eq!(sk_set.secret_key_share(3), share);
+    }
+}
@@ -0,0 +1,341 @@
+//! Proactive refresh of a committee's key shares.
+//!
+//! An attacker who gradually compromises nodes only learns the key once it has more than
+//! `threshold` shares. A [`Refresh`] periodically replaces all shares with new ones of the same
+//! master key, so that shares stolen in different periods can't be combined. It runs the
+//! [key generation](../dkg/index.html) protocol among the members of the committee, with the
+//! same messages and the same assumption that all nodes receive them in the same order, except
+//! that every [`Part`] must share the value zero: its commitment's constant term must be the
+//! identity, otherwise it is rejected with `PartFault::NonzeroSecret`. Adding the result to the
+//! current key set keeps the master public key and threshold, but changes all public key shares.
+//!
+//! ```
+//! use std::collections::BTreeMap;
+//!
+//! use threshold_crypto::dkg::{AckOutcome, PartOutcome};
+//! use threshold_crypto::refresh::Refresh;
+//! use threshold_crypto::{SecretKey, SecretKeySet};
+//!
+//! let mut rng = rand::thread_rng();
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! let sec_keys: Vec<SecretKey> = (0..4).map(|_| rand::random()).collect();
+//! let pub_keys: BTreeMap<usize, _> = sec_keys.iter().map(SecretKey::public_key).enumerate().collect();
+//!
+//! let mut nodes = Vec::new();
+//! let mut parts = Vec::new();
+//! for (id, sk) in sec_keys.into_iter().enumerate() {
+//!     let share = Some(sk_set.secret_key_share(id));
+//!     let (node, part) = Refresh::new(id, sk, pub_keys.clone(), pk_set.clone(), share, &mut rng)
+//!         .unwrap();
+//!     nodes.push(node);
+//!     parts.push((id, part.expect("a member sends a part")));
+//! }
+//!
+//! let mut acks = Vec::new();
+//! for (sender, part) in parts {
+//!     for (id, node) in nodes.iter_mut().enumerate() {
+//!         match node.handle_part(&sender, part.clone(), &mut rng).unwrap() {
+//!             PartOutcome::Valid(Some(ack)) => acks.push((id, ack)),
+//!             outcome => panic!("unexpected outcome {:?}", outcome),
+//!         }
+//!     }
+//! }
+//! for (sender, ack) in acks {
+//!     for node in &mut nodes {
+//!         assert_eq!(AckOutcome::Valid, node.handle_ack(&sender, ack.clone()).unwrap());
+//!     }
+//! }
+//!
+//! let (new_pk_set, share) = nodes[0].generate().unwrap();
+//! assert_eq!(pk_set.public_key(), new_pk_set.public_key());
+//! let sig_share = share.expect("a member has a share").sign("msg");
+//! assert!(new_pk_set.public_key_share(0).verify(&sig_share, "msg"));
+//! assert!(!pk_set.public_key_share(0).verify(&sig_share, "msg"));
+//! ```
+
+use std::collections::BTreeMap;
+
+use ff::Field;
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::dkg::{Ack, AckOutcome, KeyGen, KeyGenMode, KeyGenState, Part, PartOutcome};
+use crate::error::{Error, Result};
+use crate::serde_impl::SerdeSecret;
+use crate::state::Resumable;
+use crate::{PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+
+/// A node's state in the refresh of its committee's key shares.
+#[derive(Clone, Debug)]
+pub struct Refresh<N> {
+    key_gen: KeyGen<N>,
+    pk_set: PublicKeySet,
+    share: Option<SecretKeyShare>,
+}
+
+impl<N: Ord + Clone> Refresh<N> {
+    /// Creates the state of node `our_id` in the committee with the given public keys, which
+    /// holds the key set `pk_set`, and returns the `Part` it must broadcast, or `None` if it is
+    /// only an observer. As in the key generation, node `i` in the order of the IDs holds share
+    /// `i`.
+    ///
+    /// `share` must be this node's current share if it is a member, and is ignored otherwise.
+    /// Returns `Error::InvalidShare(i)` if it doesn't match share `i` of the key set, and
+    /// `Error::NotEnoughShares` if there are too few nodes to tolerate `threshold` faulty ones.
+    pub fn new<R: Rng>(
+        our_id: N,
+        sec_key: SecretKey,
+        pub_keys: BTreeMap<N, PublicKey>,
+        pk_set: PublicKeySet,
+        share: Option<SecretKeyShare>,
+        rng: &mut R,
+    ) -> Result<(Self, Option<Part>)> {
+        let our_idx = pub_keys.keys().position(|id| *id == our_id);
+        let share = match our_idx {
+            None => None,
+            Some(idx) => match share {
+                Some(share) if share.public_key_share() == pk_set.public_key_share(idx) => {
+                    Some(share)
+                }
+                _ => return Err(Error::InvalidShare(idx)),
+            },
+        };
+        let threshold = pk_set.threshold();
+        let (key_gen, part) = KeyGen::with_zero_secret(our_id, sec_key, pub_keys, threshold, rng)?;
+        let refresh = Refresh {
+            key_gen,
+            pk_set,
+            share,
+        };
+        Ok((refresh, part))
+    }
+
+    /// Returns the ID of this node.
+    pub fn our_id(&self) -> &N {
+        self.key_gen.our_id()
+    }
+
+    /// Returns the committee members' public keys.
+    pub fn public_keys(&self) -> &BTreeMap<N, PublicKey> {
+        self.key_gen.public_keys()
+    }
+
+    /// Returns the key set before the refresh.
+    pub fn public_key_set(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Handles a `Part` by the node `sender_id`, and returns the `Ack` to broadcast if it is
+    /// valid. Returns `Error::UnknownParticipant` if the sender is not one of the nodes.
+    pub fn handle_part<R: Rng>(
+        &mut self,
+        sender_id: &N,
+        part: Part,
+        rng: &mut R,
+    ) -> Result<PartOutcome> {
+        self.key_gen.handle_part(sender_id, part, rng)
+    }
+
+    /// Handles an `Ack` by the node `sender_id`. Returns `Error::UnknownParticipant` if the
+    /// sender is not one of the nodes.
+    pub fn handle_ack(&mut self, sender_id: &N, ack: Ack) -> Result<AckOutcome> {
+        self.key_gen.handle_ack(sender_id, ack)
+    }
+
+    /// Returns the number of complete parts.
+    pub fn count_complete(&self) -> usize {
+        self.key_gen.count_complete()
+    }
+
+    /// Returns `true` if enough parts are complete to refresh the shares.
+    pub fn is_ready(&self) -> bool {
+        self.key_gen.is_ready()
+    }
+
+    /// Returns the refreshed public key set and, if this node is a member, its new secret key
+    /// share. The old share must be deleted once the new one is in use.
+    ///
+    /// Returns `Error::NotEnoughShares` if not enough parts are complete yet. All nodes that call
+    /// this after handling the same messages get the same key set.
+    pub fn generate(&self) -> Result<(PublicKeySet, Option<SecretKeyShare>)> {
+        let (delta_set, delta_share) = self.key_gen.generate()?;
+        let pk_set = PublicKeySet::from(self.pk_set.commit.clone() + delta_set.commit);
+        let share = match (&self.share, delta_share) {
+            (Some(share), Some(delta)) => {
+                let mut sk_val = (share.0).0;
+                sk_val.add_assign(&(delta.0).0);
+                Some(SecretKeyShare::from_mut(&mut sk_val))
+            }
+            _ => None,
+        };
+        Ok((pk_set, share))
+    }
+}
+
+/// The saved state of a `Refresh`.
+///
+/// Contains the node's secret key and share, so it must be stored securely.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(bound(serialize = "N: Serialize", deserialize = "N: DeserializeOwned + Ord"))]
+pub struct RefreshState<N> {
+    /// The state of the key generation of the zero-sharing parts.
+    pub key_gen: KeyGenState<N>,
+    /// The key set before the refresh.
+    pub pk_set: PublicKeySet,
+    /// This node's share before the refresh, if it is a member.
+    pub share: Option<SerdeSecret<SecretKeyShare>>,
+}
+
+impl<N> Resumable for Refresh<N>
+where
+    N: Ord + Clone + Serialize + DeserializeOwned,
+{
+    const PROTOCOL: &'static str = "share_refresh";
+    type State = RefreshState<N>;
+
+    fn to_state(&self) -> RefreshState<N> {
+        RefreshState {
+            key_gen: self.key_gen.to_state(),
+            pk_set: self.pk_set.clone(),
+            share: self.share.clone().map(SerdeSecret),
+        }
+    }
+
+    /// Restores the state by handling the saved messages again, so that they are verified.
+    fn from_state(state: RefreshState<N>) -> Result<Self> {
+        let RefreshState {
+            key_gen,
+            pk_set,
+            share,
+        } = state;
+        let invalid = |msg: &str| Error::InvalidState(msg.to_string());
+        if key_gen.mode != KeyGenMode::Feldman || key_gen.threshold != pk_set.threshold() {
+            return Err(invalid("parameters don't match the key set"));
+        }
+        // The restored node doesn't broadcast its part again, so a fresh one is discarded.
+        let (mut refresh, _) = Refresh::new(
+            key_gen.our_id,
+            key_gen.sec_key.into_inner(),
+            key_gen.pub_keys,
+            pk_set,
+            share.map(SerdeSecret::into_inner),
+            &mut OsRng,
+        )?;
+        refresh.key_gen.replay(key_gen.messages)?;
+        Ok(refresh)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::dkg::PartFault;
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_refresh() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let sec_keys: Vec<SecretKey> = (0..4).map(|_| rand::random()).collect();
+        let pub_keys: BTreeMap<usize, PublicKey> = sec_keys
+            .iter()
+            .map(SecretKey::public_key)
+            .enumerate()
+            .collect();
+
+        let wrong_share = Some(sk_set.secret_key_share(1));
+        let result = Refresh::new(
+            0,
+            sec_keys[0].clone(),
+            pub_keys.clone(),
+            pk_set.clone(),
+            wrong_share,
+            &mut rng,
+        );
+        assert_eq!(Some(Error::InvalidShare(0)), result.err());
+
+        let mut nodes = Vec::new();
+        let mut parts = Vec::new();
+        for (id, sk) in sec_keys.iter().enumerate() {
+            let share = Some(sk_set.secret_key_share(id));
+            let (node, part) = Refresh::new(
+                id,
+                sk.clone(),
+                pub_keys.clone(),
+                pk_set.clone(),
+                share,
+                &mut rng,
+            )
+            .expect("valid share");
+            nodes.push(node);
+            parts.push((id, part.expect("part")));
+        }
+
+        // A key generation part with a nonzero secret is rejected.
+        let (_, part) = KeyGen::new(3, sec_keys[3].clone(), pub_keys.clone(), 1, &mut rng)
+            .expect("key generation");
+        let result = nodes[0].handle_part(&3, part.expect("part"), &mut rng);
+        assert_eq!(
+            Some(PartOutcome::Invalid(PartFault::NonzeroSecret)),
+            result.ok()
+        );
+
+        // Nodes 1 and 2 complete the refresh, and node 3's part is never acknowledged.
+        let mut acks = Vec::new();
+        for (sender, part) in parts.into_iter().take(3) {
+            for (id, node) in nodes.iter_mut().enumerate() {
+                match node.handle_part(&sender, part.clone(), &mut rng) {
+                    Ok(PartOutcome::Valid(Some(ack))) => acks.push((id, ack)),
+                    outcome => panic!("unexpected outcome {:?}", outcome),
+                }
+            }
+        }
+        let saved = bincode::serialize(&nodes[0].save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&saved).expect("deserialize state");
+        nodes[0] = Refresh::restore_state(saved).expect("valid state");
+        for (sender, ack) in acks {
+            for node in &mut nodes {
+                assert_eq!(Ok(AckOutcome::Valid), node.handle_ack(&sender, ack.clone()));
+            }
+        }
+        assert!(nodes[0].is_ready());
+
+        let (new_pk_set, _) = nodes[0].generate().expect("ready");
+        assert_eq!(pk_set.public_key(), new_pk_set.public_key());
+        assert_eq!(pk_set.threshold(), new_pk_set.threshold());
+        let shares: Vec<SecretKeyShare> = nodes
+            .iter()
+            .map(|node| node.generate().expect("ready"))
+            .map(|(node_pk_set, share)| {
+                assert_eq!(new_pk_set, node_pk_set);
+                share.expect("member")
+            })
+            .collect();
+        for (i, share) in shares.iter().enumerate() {
+            assert_eq!(new_pk_set.public_key_share(i), share.public_key_share());
+            assert_ne!(pk_set.public_key_share(i), share.public_key_share());
+        }
+
+        // New shares can be combined, but not mixed with old ones.
+        let sig_shares: BTreeMap<usize, _> = shares
+            .iter()
+            .map(|share| share.sign("msg"))
+            .enumerate()
+            .collect();
+        let sig = new_pk_set.combine_signatures(sig_shares.iter().take(2));
+        assert!(pk_set
+            .public_key()
+            .verify(&sig.expect("enough shares"), "msg"));
+        let old_share = sk_set.secret_key_share(0).sign("msg");
+        let mixed = vec![(0, &old_share), (1, &sig_shares[&1])];
+        let sig = new_pk_set.combine_signatures(mixed);
+        assert!(!pk_set
+            .public_key()
+            .verify(&sig.expect("enough shares"), "msg"));
+    }
+}
@@ -0,0 +1,704 @@
+//! Resharing a key to a new committee and threshold.
+//!
+//! Resharing hands the master key of committee A, with threshold `t`, to committee B, with a
+//! possibly different size and threshold `t'`, keeping the master public key. The committees may
+//! overlap. Like the [key generation](../dkg/index.html), it assumes that all nodes receive all
+//! messages in the same order:
+//!
+//! 1. Members of A call [`SecretKeyShare::reshare_commit`] with their shares and broadcast the
+//!    returned [`Deal`]: a commitment to a random bivariate polynomial of degree `t'` whose value
+//!    at `(0, 0)` is their share, and each member of B's row of it, encrypted to that member.
+//! 2. Every member of B that receives a valid `Deal` broadcasts an [`Ack`] with the values of its
+//!    row at each member of B.
+//! 3. A `Deal` is complete once `2 * t' + 1` members of B acknowledged it. The first `t + 1`
+//!    complete deals are _qualified_, and once they are fixed, every node can call `generate` to
+//!    obtain B's `PublicKeySet` and its own `SecretKeyShare`, interpolated from the qualified
+//!    deals.
+//!
+//! A [`Reshare`] verifies that each deal commits to the dealer's share of A's key set, and each
+//! row and value to the deal, so that no faulty dealer can change the key. If more than `t`
+//! members of A or `t'` members of B are faulty, the key can neither be kept secret nor
+//! guaranteed to be reshared.
+//!
+//! ```
+//! use std::collections::{BTreeMap, BTreeSet};
+//!
+//! use threshold_crypto::reshare::{AckOutcome, DealOutcome, Reshare};
+//! use threshold_crypto::{SecretKey, SecretKeySet};
+//!
+//! let mut rng = rand::thread_rng();
+//! // Committee A has the nodes 0, 1 and 2, and threshold 1.
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let pk_set = sk_set.public_keys();
+//! let old_ids: BTreeSet<usize> = (0..3).collect();
+//! // Committee B has the nodes 3 to 7, and threshold 2.
+//! let sec_keys: BTreeMap<usize, SecretKey> = (3..8).map(|id| (id, rand::random())).collect();
+//! let pub_keys: BTreeMap<usize, _> = sec_keys.iter().map(|(id, sk)| (*id, sk.public_key())).collect();
+//!
+//! let mut nodes = Vec::new();
+//! for (id, sk) in sec_keys {
+//!     nodes.push(Reshare::new(id, sk, pk_set.clone(), old_ids.clone(), pub_keys.clone(), 2).unwrap());
+//! }
+//!
+//! let mut acks = Vec::new();
+//! for dealer in old_ids {
+//!     let share = sk_set.secret_key_share(dealer);
+//!     let deal = share.reshare_commit(2, pub_keys.values(), &mut rng).unwrap();
+//!     for node in &mut nodes {
+//!         match node.handle_deal(&dealer, deal.clone(), &mut rng).unwrap() {
+//!             DealOutcome::Valid(Some(ack)) => acks.push((*node.our_id(), ack)),
+//!             outcome => panic!("unexpected outcome {:?}", outcome),
+//!         }
+//!     }
+//! }
+//! for (sender, ack) in acks {
+//!     for node in &mut nodes {
+//!         assert_eq!(AckOutcome::Valid, node.handle_ack(&sender, ack.clone()).unwrap());
+//!     }
+//! }
+//!
+//! let (new_pk_set, share) = nodes[0].generate().unwrap();
+//! assert_eq!(pk_set.public_key(), new_pk_set.public_key());
+//! assert_eq!(2, new_pk_set.threshold());
+//! let sig_share = share.expect("a member has a share").sign("msg");
+//! assert!(new_pk_set.public_key_share(0).verify(&sig_share, "msg"));
+//! ```
+
+use std::collections::btree_map::Entry;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
+
+use ff::Field;
+use group::{CurveAffine, CurveProjective};
+use rand::rngs::OsRng;
+use rand::Rng;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use zeroize::Zeroizing;
+
+use crate::decode::decode;
+use crate::dkg::fr_to_bytes;
+use crate::error::{DecodeResult, Error, Result};
+use crate::poly::{BivarCommitment, BivarPoly, Commitment, Poly};
+use crate::secret::clear_fr;
+use crate::serde_impl::SerdeSecret;
+use crate::state::Resumable;
+use crate::{into_fr_plus_1, lagrange_coefficients};
+use crate::{Ciphertext, DebugDots, Fr, G1Affine, G1, SK_SIZE};
+use crate::{PublicKey, PublicKeySet, SecretKey, SecretKeyShare};
+
+/// A member of the old committee's resharing of its key share: a commitment to a bivariate
+/// polynomial, and the rows for each member of the new committee.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Deal {
+    commit: BivarCommitment,
+    rows: Vec<Ciphertext>,
+}
+
+impl Deal {
+    /// Returns the commitment to the dealer's polynomial. Its value at `(0, 0)` is the dealer's
+    /// public key share.
+    pub fn commitment(&self) -> &BivarCommitment {
+        &self.commit
+    }
+}
+
+/// A new member's confirmation that a deal is valid, with the values for each new member.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Ack {
+    dealer: u64,
+    values: Vec<Ciphertext>,
+}
+
+impl Ack {
+    /// Returns the old committee index of the member whose deal is acknowledged.
+    pub fn dealer(&self) -> u64 {
+        self.dealer
+    }
+}
+
+impl SecretKeyShare {
+    /// Creates a `Deal` that reshares this key share to the new committee with the given public
+    /// keys, in the order of the members' indices, and the given threshold.
+    ///
+    /// Returns `Error::NotEnoughShares` if there are too few new members to tolerate `threshold`
+    /// faulty ones.
+    pub fn reshare_commit<'a, I, R>(
+        &self,
+        threshold: usize,
+        pub_keys: I,
+        rng: &mut R,
+    ) -> Result<Deal>
+    where
+        I: IntoIterator<Item = &'a PublicKey>,
+        R: Rng,
+    {
+        let pub_keys: Vec<&PublicKey> = pub_keys.into_iter().collect();
+        if pub_keys.len() <= 2 * threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        let poly = BivarPoly::with_secret((self.0).0, threshold, rng);
+        let rows = pub_keys
+            .into_iter()
+            .enumerate()
+            .map(|(idx, pk)| {
+                let mut bytes = Zeroizing::new(Vec::with_capacity((threshold + 1) * SK_SIZE));
+                for coeff in &poly.row(idx + 1).coeff {
+                    bytes.extend(fr_to_bytes(coeff).iter());
+                }
+                pk.encrypt_with_rng(rng, &*bytes)
+            })
+            .collect();
+        Ok(Deal {
+            commit: poly.commitment(),
+            rows,
+        })
+    }
+}
+
+/// The result of handling a `Deal`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DealOutcome {
+    /// The deal was valid. If this node is a new member, it must broadcast the `Ack`.
+    Valid(Option<Ack>),
+    /// The deal was invalid, and the dealer is faulty.
+    Invalid(DealFault),
+}
+
+/// A reason why a `Deal` is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DealFault {
+    /// The dealer has already sent a different deal.
+    MultipleDeals,
+    /// The commitment's degree is not the new threshold.
+    DegreeMismatch,
+    /// The commitment's value at `(0, 0)` is not the dealer's public key share.
+    ShareCommitment,
+    /// The number of rows is not the number of new members.
+    RowCount,
+    /// This node's row could not be decrypted or decoded.
+    InvalidRow,
+    /// This node's row doesn't match the commitment.
+    RowCommitment,
+}
+
+/// The result of handling an `Ack`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AckOutcome {
+    /// The ack was valid.
+    Valid,
+    /// The ack was invalid, and its sender is faulty.
+    Invalid(AckFault),
+}
+
+/// A reason why an `Ack` is invalid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AckFault {
+    /// There is no deal by the dealer.
+    MissingDeal,
+    /// The sender has already acknowledged the deal.
+    DuplicateAck,
+    /// The number of values is not the number of new members.
+    ValueCount,
+    /// This node's value could not be decrypted or decoded.
+    InvalidValue,
+    /// This node's value doesn't match the commitment.
+    ValueCommitment,
+}
+
+/// The received deal of a dealer, and the acknowledged values of this node's row.
+#[derive(Clone)]
+struct DealState {
+    commit: BivarCommitment,
+    /// The values `f(s, our_idx + 1)` at `s = sender + 1`, received in the senders' acks.
+    values: BTreeMap<u64, Fr>,
+    /// The new committee indices of the members that acknowledged the deal.
+    acks: BTreeSet<u64>,
+}
+
+impl Drop for DealState {
+    fn drop(&mut self) {
+        for value in self.values.values_mut() {
+            clear_fr(value);
+        }
+    }
+}
+
+/// A node's state in the resharing of a key set to a new committee.
+#[derive(Clone)]
+pub struct Reshare<N> {
+    our_id: N,
+    /// This node's index in the new committee, if it is a member.
+    our_idx: Option<u64>,
+    sec_key: SecretKey,
+    /// The old committee's key set.
+    pk_set: PublicKeySet,
+    old_ids: BTreeSet<N>,
+    pub_keys: BTreeMap<N, PublicKey>,
+    threshold: usize,
+    deals: BTreeMap<u64, DealState>,
+    /// The qualified deals, once they are fixed.
+    qualified: Option<BTreeSet<u64>>,
+    /// The valid messages handled so far, for saving the state.
+    log: Vec<(N, Message)>,
+}
+
+impl<N: fmt::Debug> fmt::Debug for Reshare<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Reshare")
+            .field("our_id", &self.our_id)
+            .field("our_idx", &self.our_idx)
+            .field("sec_key", &DebugDots)
+            .field("threshold", &self.threshold)
+            .field("deals", &self.deals.keys().collect::<Vec<_>>())
+            .field("qualified", &self.qualified)
+            .finish()
+    }
+}
+
+impl<N: Ord + Clone> Reshare<N> {
+    /// Creates the state of node `our_id`, to receive the key set `pk_set` of the old committee
+    /// with the given IDs. The new committee has the given public keys, and any `threshold + 1`
+    /// of its members' shares can sign and decrypt. As in the key generation, member `i` in the
+    /// order of the IDs holds share `i`, in both committees.
+    ///
+    /// `our_id` doesn't need to be a new member: Observers, e.g. old members that are not new
+    /// ones, only learn the new `PublicKeySet`. Returns `Error::NotEnoughShares` if the old
+    /// committee can't have produced the key set, or there are too few new members to tolerate
+    /// `threshold` faulty ones.
+    pub fn new(
+        our_id: N,
+        sec_key: SecretKey,
+        pk_set: PublicKeySet,
+        old_ids: BTreeSet<N>,
+        pub_keys: BTreeMap<N, PublicKey>,
+        threshold: usize,
+    ) -> Result<Self> {
+        if old_ids.len() <= pk_set.threshold() || pub_keys.len() <= 2 * threshold {
+            return Err(Error::NotEnoughShares);
+        }
+        let our_idx = pub_keys
+            .keys()
+            .position(|id| *id == our_id)
+            .map(|idx| idx as u64);
+        Ok(Reshare {
+            our_id,
+            our_idx,
+            sec_key,
+            pk_set,
+            old_ids,
+            pub_keys,
+            threshold,
+            deals: BTreeMap::new(),
+            qualified: None,
+            log: Vec::new(),
+        })
+    }
+
+    /// Returns the ID of this node.
+    pub fn our_id(&self) -> &N {
+        &self.our_id
+    }
+
+    /// Returns the new committee members' public keys.
+    pub fn public_keys(&self) -> &BTreeMap<N, PublicKey> {
+        &self.pub_keys
+    }
+
+    /// Returns the new threshold.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+
+    /// Handles a `Deal` by the old member `sender_id`, and returns the `Ack` to broadcast if it
+    /// is valid. Returns `Error::UnknownParticipant` if the sender is not an old member.
+    pub fn handle_deal<R: Rng>(
+        &mut self,
+        sender_id: &N,
+        deal: Deal,
+        rng: &mut R,
+    ) -> Result<DealOutcome> {
+        let dealer = self
+            .old_ids
+            .iter()
+            .position(|id| id == sender_id)
+            .map(|idx| idx as u64)
+            .ok_or(Error::UnknownParticipant)?;
+        let row = match self.check_deal(dealer, &deal) {
+            Ok(row) => row,
+            Err(fault) => return Ok(DealOutcome::Invalid(fault)),
+        };
+        let ack = row.as_ref().map(|row| self.create_ack(dealer, row, rng));
+        if let Entry::Vacant(entry) = self.deals.entry(dealer) {
+            entry.insert(DealState {
+                commit: deal.commit.clone(),
+                values: BTreeMap::new(),
+                acks: BTreeSet::new(),
+            });
+            self.log.push((sender_id.clone(), Message::Deal(deal)));
+        }
+        Ok(DealOutcome::Valid(ack))
+    }
+
+    /// Handles an `Ack` by the new member `sender_id`. Returns `Error::UnknownParticipant` if the
+    /// sender is not a new member.
+    pub fn handle_ack(&mut self, sender_id: &N, ack: Ack) -> Result<AckOutcome> {
+        let sender_idx = self
+            .pub_keys
+            .keys()
+            .position(|id| id == sender_id)
+            .map(|idx| idx as u64)
+            .ok_or(Error::UnknownParticipant)?;
+        let value = match self.check_ack(sender_idx, &ack) {
+            Ok(value) => value,
+            Err(fault) => return Ok(AckOutcome::Invalid(fault)),
+        };
+        let state = self
+            .deals
+            .get_mut(&ack.dealer)
+            .expect("the deal was checked");
+        state.acks.insert(sender_idx);
+        if let Some(value) = value {
+            state.values.insert(sender_idx + 1, value);
+        }
+        self.log.push((sender_id.clone(), Message::Ack(ack)));
+        if self.qualified.is_none() && self.count_complete() > self.pk_set.threshold() {
+            let qualified = self.complete_deals().map(|(idx, _)| *idx).collect();
+            self.qualified = Some(qualified);
+        }
+        Ok(AckOutcome::Valid)
+    }
+
+    /// Returns the number of complete deals.
+    pub fn count_complete(&self) -> usize {
+        self.complete_deals().count()
+    }
+
+    /// Returns the old committee indices of the qualified dealers, or `None` if they are not
+    /// fixed yet.
+    pub fn qualified(&self) -> Option<&BTreeSet<u64>> {
+        self.qualified.as_ref()
+    }
+
+    /// Returns `true` if the qualified deals are fixed, so that the key set can be generated.
+    pub fn is_ready(&self) -> bool {
+        self.qualified.is_some()
+    }
+
+    /// Returns the new committee's public key set and, if this node is a new member, its secret
+    /// key share.
+    ///
+    /// Returns `Error::NotEnoughShares` if the qualified deals are not fixed yet. All nodes get
+    /// the same key set, with the old committee's master public key.
+    pub fn generate(&self) -> Result<(PublicKeySet, Option<SecretKeyShare>)> {
+        let qualified = self.qualified.as_ref().ok_or(Error::NotEnoughShares)?;
+        let xs: Vec<Fr> = qualified.iter().map(|idx| into_fr_plus_1(*idx)).collect();
+        let lambdas = lagrange_coefficients(&xs)?;
+        let mut commit = Poly::zero().commitment();
+        for (lambda, idx) in lambdas.iter().zip(qualified) {
+            let coeff = self.deals[idx].commit.row(0).coeff;
+            commit += Commitment {
+                coeff: coeff
+                    .iter()
+                    .map(|c| {
+                        let mut c = *c;
+                        c.mul_assign(*lambda);
+                        c
+                    })
+                    .collect(),
+            };
+        }
+        let share = match self.our_idx {
+            None => None,
+            Some(_) => {
+                let mut sk_val = Fr::zero();
+                for (lambda, idx) in lambdas.iter().zip(qualified) {
+                    let samples = self.deals[idx].values.iter().take(self.threshold + 1);
+                    let row = Poly::interpolate(samples.map(|(x, value)| (*x, *value)));
+                    let mut value = row.evaluate(0);
+                    value.mul_assign(lambda);
+                    sk_val.add_assign(&value);
+                    clear_fr(&mut value);
+                }
+                Some(SecretKeyShare::from_mut(&mut sk_val))
+            }
+        };
+        Ok((PublicKeySet::from(commit), share))
+    }
+
+    /// Returns the complete deals.
+    fn complete_deals(&self) -> impl Iterator<Item = (&u64, &DealState)> {
+        let threshold = self.threshold;
+        self.deals
+            .iter()
+            .filter(move |(_, state)| state.acks.len() > 2 * threshold)
+    }
+
+    /// Creates this node's `Ack` of the dealer's deal, given its row.
+    fn create_ack<R: Rng>(&self, dealer: u64, row: &Poly, rng: &mut R) -> Ack {
+        let values = self
+            .pub_keys
+            .values()
+            .enumerate()
+            .map(|(idx, pk)| pk.encrypt_with_rng(rng, &*fr_to_bytes(&row.evaluate(idx + 1))))
+            .collect();
+        Ack { dealer, values }
+    }
+
+    /// Verifies the deal, and returns this node's row if it is a new member.
+    fn check_deal(
+        &self,
+        dealer: u64,
+        deal: &Deal,
+    ) -> ::std::result::Result<Option<Poly>, DealFault> {
+        if let Some(state) = self.deals.get(&dealer) {
+            if state.commit != deal.commit {
+                return Err(DealFault::MultipleDeals);
+            }
+        }
+        if deal.commit.degree() != self.threshold {
+            return Err(DealFault::DegreeMismatch);
+        }
+        let pk_share = self.pk_set.public_key_share(dealer);
+        if deal.commit.evaluate(0, 0) != (pk_share.0).0 {
+            return Err(DealFault::ShareCommitment);
+        }
+        if deal.rows.len() != self.pub_keys.len() {
+            return Err(DealFault::RowCount);
+        }
+        let our_idx = match self.our_idx {
+            Some(our_idx) => our_idx,
+            None => return Ok(None),
+        };
+        let bytes = self
+            .sec_key
+            .decrypt(&deal.rows[our_idx as usize])
+            .map(Zeroizing::new)
+            .ok_or(DealFault::InvalidRow)?;
+        let coeff = decode(&bytes, "Row", |r| {
+            (0..=self.threshold)
+                .map(|_| r.fr("coefficient"))
+                .collect::<DecodeResult<Vec<Fr>>>()
+        })
+        .map_err(|_| DealFault::InvalidRow)?;
+        let row = Poly::from(coeff);
+        if row.commitment() != deal.commit.row(our_idx + 1) {
+            return Err(DealFault::RowCommitment);
+        }
+        Ok(Some(row))
+    }
+
+    /// Verifies the ack, and returns this node's value if it is a new member.
+    fn check_ack(&self, sender_idx: u64, ack: &Ack) -> ::std::result::Result<Option<Fr>, AckFault> {
+        let state = self.deals.get(&ack.dealer).ok_or(AckFault::MissingDeal)?;
+        if state.acks.contains(&sender_idx) {
+            return Err(AckFault::DuplicateAck);
+        }
+        if ack.values.len() != self.pub_keys.len() {
+            return Err(AckFault::ValueCount);
+        }
+        let our_idx = match self.our_idx {
+            Some(our_idx) => our_idx,
+            None => return Ok(None),
+        };
+        let bytes = self
+            .sec_key
+            .decrypt(&ack.values[our_idx as usize])
+            .map(Zeroizing::new)
+            .ok_or(AckFault::InvalidValue)?;
+        let value =
+            decode(&bytes, "Value", |r| r.fr("value")).map_err(|_| AckFault::InvalidValue)?;
+        let expected: G1 = G1Affine::one().mul(value);
+        if state.commit.evaluate(our_idx + 1, sender_idx + 1) != expected {
+            return Err(AckFault::ValueCommitment);
+        }
+        Ok(Some(value))
+    }
+}
+
+/// A valid message handled by a `Reshare`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Message {
+    /// An old member's deal.
+    Deal(Deal),
+    /// A new member's acknowledgment of a deal.
+    Ack(Ack),
+}
+
+/// The saved state of a `Reshare`: its parameters and the valid messages it handled.
+///
+/// Contains the node's secret key, so it must be stored securely.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(bound(serialize = "N: Serialize", deserialize = "N: DeserializeOwned + Ord"))]
+pub struct ReshareState<N> {
+    /// This node's ID.
+    pub our_id: N,
+    /// This node's secret key, for decrypting its rows and values.
+    pub sec_key: SerdeSecret<SecretKey>,
+    /// The old committee's key set.
+    pub pk_set: PublicKeySet,
+    /// The old committee members' IDs.
+    pub old_ids: BTreeSet<N>,
+    /// The new committee members' public keys.
+    pub pub_keys: BTreeMap<N, PublicKey>,
+    /// The new threshold.
+    pub threshold: usize,
+    /// The valid messages handled so far, with their senders, in order.
+    pub messages: Vec<(N, Message)>,
+}
+
+impl<N> Resumable for Reshare<N>
+where
+    N: Ord + Clone + Serialize + DeserializeOwned,
+{
+    const PROTOCOL: &'static str = "reshare";
+    type State = ReshareState<N>;
+
+    fn to_state(&self) -> ReshareState<N> {
+        ReshareState {
+            our_id: self.our_id.clone(),
+            sec_key: SerdeSecret(self.sec_key.clone()),
+            pk_set: self.pk_set.clone(),
+            old_ids: self.old_ids.clone(),
+            pub_keys: self.pub_keys.clone(),
+            threshold: self.threshold,
+            messages: self.log.clone(),
+        }
+    }
+
+    /// Restores the state by handling the saved messages again, so that they are verified.
+    fn from_state(state: ReshareState<N>) -> Result<Self> {
+        let mut reshare = Reshare::new(
+            state.our_id,
+            state.sec_key.into_inner(),
+            state.pk_set,
+            state.old_ids,
+            state.pub_keys,
+            state.threshold,
+        )?;
+        for (sender_id, msg) in state.messages {
+            let valid = match msg {
+                Message::Deal(deal) => {
+                    let outcome = reshare.handle_deal(&sender_id, deal, &mut OsRng)?;
+                    matches!(outcome, DealOutcome::Valid(_))
+                }
+                Message::Ack(ack) => reshare.handle_ack(&sender_id, ack)? == AckOutcome::Valid,
+            };
+            if !valid {
+                let msg = "invalid resharing message".to_string();
+                return Err(Error::InvalidState(msg));
+            }
+        }
+        Ok(reshare)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_reshare() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let old_ids: BTreeSet<usize> = (0..3).collect();
+        // Node 2 is a member of both committees.
+        let sec_keys: BTreeMap<usize, SecretKey> = (2..7).map(|id| (id, rand::random())).collect();
+        let pub_keys: BTreeMap<usize, PublicKey> = sec_keys
+            .iter()
+            .map(|(id, sk)| (*id, sk.public_key()))
+            .collect();
+        let result = Reshare::new(
+            0,
+            rand::random(),
+            pk_set.clone(),
+            old_ids.clone(),
+            pub_keys.clone(),
+            3,
+        );
+        assert_eq!(Some(Error::NotEnoughShares), result.err());
+
+        let mut nodes: Vec<Reshare<usize>> = sec_keys
+            .iter()
+            .map(|(id, sk)| {
+                let (old, new) = (old_ids.clone(), pub_keys.clone());
+                Reshare::new(*id, sk.clone(), pk_set.clone(), old, new, 2).expect("new node")
+            })
+            .collect();
+        let observer_key = rand::random();
+        let mut observer = Reshare::new(
+            0,
+            observer_key,
+            pk_set.clone(),
+            old_ids.clone(),
+            pub_keys.clone(),
+            2,
+        )
+        .expect("observer");
+        let deals: Vec<Deal> = (0..3)
+            .map(|i| sk_set.secret_key_share(i))
+            .map(|share| share.reshare_commit(2, pub_keys.values(), &mut rng))
+            .collect::<Result<_>>()
+            .expect("deals");
+
+        // A deal of the wrong share, or an old threshold, is rejected.
+        let wrong = sk_set
+            .secret_key_share(1)
+            .reshare_commit(2, pub_keys.values(), &mut rng);
+        let result = observer.handle_deal(&0, wrong.expect("deal"), &mut rng);
+        assert_eq!(Ok(DealOutcome::Invalid(DealFault::ShareCommitment)), result);
+        let old = sk_set
+            .secret_key_share(0)
+            .reshare_commit(1, pub_keys.values(), &mut rng);
+        let result = observer.handle_deal(&0, old.expect("deal"), &mut rng);
+        assert_eq!(Ok(DealOutcome::Invalid(DealFault::DegreeMismatch)), result);
+        let result = observer.handle_deal(&3, deals[0].clone(), &mut rng);
+        assert_eq!(Some(Error::UnknownParticipant), result.err());
+
+        // Dealer 1's deal is never acknowledged, so dealers 0 and 2 are qualified.
+        let mut acks = Vec::new();
+        for dealer in &[0, 2] {
+            for node in nodes.iter_mut() {
+                match node.handle_deal(dealer, deals[*dealer].clone(), &mut rng) {
+                    Ok(DealOutcome::Valid(Some(ack))) => acks.push((*node.our_id(), ack)),
+                    outcome => panic!("unexpected outcome {:?}", outcome),
+                }
+            }
+            let outcome = observer.handle_deal(dealer, deals[*dealer].clone(), &mut rng);
+            assert_eq!(Ok(DealOutcome::Valid(None)), outcome);
+        }
+        for node in nodes.iter_mut().chain(Some(&mut observer)) {
+            assert!(node.handle_deal(&1, deals[1].clone(), &mut rng).is_ok());
+        }
+        let saved = bincode::serialize(&nodes[0].save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&saved).expect("deserialize state");
+        nodes[0] = Reshare::restore_state(saved).expect("valid state");
+        for (sender, ack) in &acks {
+            for node in nodes.iter_mut().chain(Some(&mut observer)) {
+                assert_eq!(Ok(AckOutcome::Valid), node.handle_ack(sender, ack.clone()));
+            }
+        }
+        let expected: BTreeSet<u64> = vec![0, 2].into_iter().collect();
+        assert_eq!(Some(&expected), observer.qualified());
+
+        let (new_pk_set, share) = observer.generate().expect("ready");
+        assert!(share.is_none());
+        assert_eq!(pk_set.public_key(), new_pk_set.public_key());
+        assert_eq!(2, new_pk_set.threshold());
+        let mut sig_shares = BTreeMap::new();
+        for (i, node) in nodes.iter().enumerate() {
+            let (node_pk_set, share) = node.generate().expect("ready");
+            assert_eq!(new_pk_set, node_pk_set);
+            let share = share.expect("member");
+            assert_eq!(new_pk_set.public_key_share(i), share.public_key_share());
+            sig_shares.insert(i, share.sign("msg"));
+        }
+        let sig = new_pk_set.combine_signatures(sig_shares.iter().skip(2));
+        assert!(pk_set
+            .public_key()
+            .verify(&sig.expect("enough shares"), "msg"));
+    }
+}
@@ -0,0 +1,234 @@
+//! Share indices derived from the participants' public keys.
+//!
+//! Usually every participant's share index is its position in a list that all parties have to
+//! agree on, including its order. A [`Roster`] only needs the _set_ of participants' public keys
+//! (e.g. their node identity keys), and derives each participant's [`EvaluationPoint`] as a hash
+//! of the group's fingerprint and its key. The fingerprint in turn is the hash of the sorted
+//! keys, so the same set of participants always gets the same points, and a point is only valid
+//! in its group.
+//!
+//! The roster's methods accept the public keys directly and look up the points internally:
+//!
+//! ```
+//! use threshold_crypto::roster::Roster;
+//! use threshold_crypto::{SecretKey, SecretKeySet};
+//!
+//! let mut rng = rand::thread_rng();
+//! let nodes: Vec<_> = (0..3).map(|_| SecretKey::random().public_key()).collect();
+//! let roster = Roster::new(nodes.iter().cloned()).expect("distinct participants");
+//!
+//! // A dealer creates each node's share at its point.
+//! let sk_set = SecretKeySet::random(1, &mut rng);
+//! let shares: Vec<_> = nodes
+//!     .iter()
+//!     .map(|pk| sk_set.secret_key_share(roster.point(pk).unwrap()))
+//!     .collect();
+//!
+//! let sig_shares: Vec<_> = shares.iter().map(|share| share.sign("msg")).collect();
+//! let pk_set = sk_set.public_keys();
+//! let sig = roster.combine_signatures(&pk_set, nodes.iter().zip(&sig_shares).skip(1));
+//! assert!(pk_set.public_key().verify(&sig.unwrap(), "msg"));
+//! ```
+
+use std::collections::BTreeMap;
+
+use ff::Field;
+use rand::SeedableRng;
+use rand_chacha::ChaChaRng;
+
+use crate::error::{Error, Result};
+use crate::util::sha3_256;
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{
+    EvaluationPoint, Fr, PublicKey, PublicKeySet, PublicKeyShare, Signature, SignatureShare,
+};
+
+/// The domain separator of the group fingerprint.
+const GROUP_DST: &[u8] = b"threshold_crypto roster group";
+/// The domain separator of the derived points.
+const POINT_DST: &[u8] = b"threshold_crypto roster point";
+
+/// Returns the evaluation point of the participant in the group with the given fingerprint.
+pub fn derive_point(fingerprint: &[u8; 32], participant: &PublicKey) -> EvaluationPoint {
+    let mut data = POINT_DST.to_vec();
+    data.extend(fingerprint);
+    data.extend(participant.to_bytes().as_ref());
+    let mut rng = ChaChaRng::from_seed(sha3_256(&data));
+    loop {
+        if let Ok(point) = EvaluationPoint::new(Fr::random(&mut rng)) {
+            return point;
+        }
+    }
+}
+
+/// A group of participants, identified by their public keys.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Roster {
+    fingerprint: [u8; 32],
+    points: BTreeMap<PublicKey, EvaluationPoint>,
+}
+
+impl Roster {
+    /// Creates the roster of the given participants, in any order.
+    ///
+    /// Returns `Error::DuplicateEntry` if a key is given twice, or two keys are assigned the same
+    /// point.
+    pub fn new<I: IntoIterator<Item = PublicKey>>(participants: I) -> Result<Self> {
+        let mut keys: Vec<PublicKey> = participants.into_iter().collect();
+        let len = keys.len();
+        keys.sort();
+        keys.dedup();
+        if keys.len() != len {
+            return Err(Error::DuplicateEntry);
+        }
+        let mut data = GROUP_DST.to_vec();
+        data.extend(&(len as u64).to_be_bytes());
+        for pk in &keys {
+            data.extend(pk.to_bytes().as_ref());
+        }
+        let fingerprint = sha3_256(&data);
+        let points: BTreeMap<PublicKey, EvaluationPoint> = keys
+            .into_iter()
+            .map(|pk| (pk, derive_point(&fingerprint, &pk)))
+            .collect();
+        let values: Vec<Fr> = points.values().map(EvaluationPoint::value).collect();
+        if values
+            .iter()
+            .enumerate()
+            .any(|(i, x)| values[..i].contains(x))
+        {
+            return Err(Error::DuplicateEntry);
+        }
+        Ok(Roster {
+            fingerprint,
+            points,
+        })
+    }
+
+    /// Returns the group's fingerprint.
+    pub fn fingerprint(&self) -> &[u8; 32] {
+        &self.fingerprint
+    }
+
+    /// Returns the number of participants.
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    /// Returns `true` if there are no participants.
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    /// Returns the participants' public keys.
+    pub fn participants(&self) -> impl Iterator<Item = &PublicKey> {
+        self.points.keys()
+    }
+
+    /// Returns the participant's evaluation point, or `None` if it is not in the group.
+    pub fn point(&self, participant: &PublicKey) -> Option<EvaluationPoint> {
+        self.points.get(participant).cloned()
+    }
+
+    /// Returns the participant's public key share, or `Error::UnknownParticipant`.
+    pub fn public_key_share(
+        &self,
+        pk_set: &PublicKeySet,
+        participant: &PublicKey,
+    ) -> Result<PublicKeyShare> {
+        Ok(pk_set.public_key_share(self.lookup(participant)?))
+    }
+
+    /// Returns `true` if the participant is in the group and the signature share is valid.
+    pub fn verify_share<M: AsRef<[u8]>>(
+        &self,
+        pk_set: &PublicKeySet,
+        participant: &PublicKey,
+        share: &SignatureShare,
+        msg: M,
+    ) -> bool {
+        self.public_key_share(pk_set, participant)
+            .is_ok_and(|pk_share| pk_share.verify(share, msg))
+    }
+
+    /// Combines the participants' signature shares, or returns `Error::UnknownParticipant` if
+    /// one of them is not in the group.
+    pub fn combine_signatures<'a, I>(&self, pk_set: &PublicKeySet, shares: I) -> Result<Signature>
+    where
+        I: IntoIterator<Item = (&'a PublicKey, &'a SignatureShare)>,
+    {
+        let shares = self.resolve(shares)?;
+        pk_set.combine_signatures(shares)
+    }
+
+    /// Combines the participants' decryption shares to decrypt the ciphertext, or returns
+    /// `Error::UnknownParticipant` if one of them is not in the group.
+    #[cfg(feature = "encryption")]
+    pub fn decrypt<'a, I>(
+        &self,
+        pk_set: &PublicKeySet,
+        shares: I,
+        ct: &Ciphertext,
+    ) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = (&'a PublicKey, &'a DecryptionShare)>,
+    {
+        let shares = self.resolve(shares)?;
+        pk_set.decrypt(shares, ct)
+    }
+
+    /// Returns the participant's point, or `Error::UnknownParticipant`.
+    fn lookup(&self, participant: &PublicKey) -> Result<EvaluationPoint> {
+        self.point(participant).ok_or(Error::UnknownParticipant)
+    }
+
+    /// Replaces the participants' keys with their points.
+    fn resolve<'a, S, I>(&self, shares: I) -> Result<Vec<(EvaluationPoint, &'a S)>>
+    where
+        I: IntoIterator<Item = (&'a PublicKey, &'a S)>,
+    {
+        shares
+            .into_iter()
+            .map(|(pk, share)| Ok((self.lookup(pk)?, share)))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{SecretKey, SecretKeySet};
+
+    #[test]
+    fn test_roster() {
+        let mut rng = rand::thread_rng();
+        let nodes: Vec<PublicKey> = (0..4).map(|_| SecretKey::random().public_key()).collect();
+        let roster = Roster::new(nodes.iter().cloned()).expect("distinct participants");
+        let reversed = Roster::new(nodes.iter().rev().cloned()).expect("distinct participants");
+        assert_eq!(roster, reversed);
+        let other = Roster::new(nodes[1..].iter().cloned()).expect("distinct participants");
+        assert_ne!(roster.point(&nodes[1]), other.point(&nodes[1]));
+        let duplicate = Roster::new(vec![nodes[0], nodes[1], nodes[0]]);
+        assert_eq!(Some(Error::DuplicateEntry), duplicate.err());
+
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let sig_shares: Vec<SignatureShare> = nodes
+            .iter()
+            .map(|pk| sk_set.secret_key_share(roster.point(pk).unwrap()))
+            .map(|share| share.sign("msg"))
+            .collect();
+        assert!(roster.verify_share(&pk_set, &nodes[2], &sig_shares[2], "msg"));
+        assert!(!roster.verify_share(&pk_set, &nodes[2], &sig_shares[3], "msg"));
+        let sig = roster.combine_signatures(&pk_set, nodes.iter().zip(&sig_shares).rev());
+        assert!(pk_set.public_key().verify(&sig.unwrap(), "msg"));
+
+        let stranger = SecretKey::random().public_key();
+        let shares = vec![(&nodes[0], &sig_shares[0]), (&stranger, &sig_shares[1])];
+        let result = roster.combine_signatures(&pk_set, shares);
+        assert_eq!(Some(Error::UnknownParticipant), result.err());
+        assert!(!roster.verify_share(&pk_set, &stranger, &sig_shares[0], "msg"));
+    }
+}
@@ -0,0 +1,328 @@
+//! Epoch-based management of a committee member's rotating key material.
+//!
+//! A long-lived committee periodically replaces its key shares: _refreshing_ gives every member new
+//! shares of the same key, so that shares leaked in earlier epochs become useless; _resharing_
+//! hands the same key to a new committee or threshold; and _rekeying_ replaces the key itself.
+//! The [`KeyRotationManager`] tracks the current epoch's public key set and share holder, tells
+//! the application when a rotation is [due](KeyRotationManager::due) according to its
+//! [`Schedule`], and checks that the result of the rotation protocol is consistent before it
+//! starts the next epoch.
+//!
+//! It keeps the public key sets of a bounded number of previous epochs, so that signatures made
+//! before a rekeying can still be verified. [`EpochHook`]s are notified whenever an epoch starts
+//! or is dropped from the history, e.g. to persist the new share in a
+//! [`vault`](../vault/index.html) and delete the old one.
+//!
+//! The manager doesn't run the rotation protocols itself; it only needs their outcome.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::signer::{IndexedShare, ShareSigner};
+use crate::{PublicKeySet, Signature};
+
+/// A kind of key rotation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Rotation {
+    /// New shares of the same key, for the same committee and threshold.
+    Refresh,
+    /// New shares of the same key, possibly for a different committee or threshold.
+    Reshare,
+    /// A new key.
+    Rekey,
+}
+
+/// When rotations are due, in the same time unit as the timestamps passed to the manager.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Schedule {
+    /// The time after which the shares should be refreshed.
+    pub refresh_interval: Option<u64>,
+    /// The time after which the key should be replaced.
+    pub rekey_interval: Option<u64>,
+}
+
+/// The public information about an epoch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EpochInfo {
+    /// The epoch number, starting at `0`.
+    pub epoch: u64,
+    /// The time the epoch started.
+    pub started_at: u64,
+    /// How the epoch's key material was created, or `None` for the first epoch.
+    pub rotation: Option<Rotation>,
+    /// The epoch's public key set.
+    pub public_key_set: PublicKeySet,
+}
+
+/// Callbacks for the epochs' life cycle, e.g. to persist each epoch's material.
+pub trait EpochHook<S> {
+    /// Called when an epoch starts. `signer` is `None` if this node is not a member anymore.
+    ///
+    /// If this returns an error, the rotation is aborted and the previous epoch stays current.
+    fn epoch_started(&mut self, info: &EpochInfo, signer: Option<&S>) -> Result<()>;
+
+    /// Called when an epoch is dropped from the history, after the next one started.
+    fn epoch_retired(&mut self, _info: &EpochInfo) {}
+}
+
+/// Tracks the current epoch's key material and the history of public key sets.
+pub struct KeyRotationManager<S> {
+    current: EpochInfo,
+    signer: Option<S>,
+    history: VecDeque<EpochInfo>,
+    max_history: usize,
+    schedule: Schedule,
+    last_rekey: u64,
+    hooks: Vec<Box<dyn EpochHook<S>>>,
+}
+
+impl<S: fmt::Debug> fmt::Debug for KeyRotationManager<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyRotationManager")
+            .field("current", &self.current)
+            .field("signer", &self.signer)
+            .field("history", &self.history)
+            .field("max_history", &self.max_history)
+            .field("schedule", &self.schedule)
+            .field("hooks", &self.hooks.len())
+            .finish()
+    }
+}
+
+impl<S: ShareSigner + IndexedShare> KeyRotationManager<S> {
+    /// Creates a manager whose first epoch starts at time `now`, keeping the public key sets of
+    /// up to `max_history` previous epochs.
+    ///
+    /// Returns `Error::InvalidRotation` if the signer's share doesn't belong to the key set.
+    pub fn new(
+        pk_set: PublicKeySet,
+        signer: Option<S>,
+        now: u64,
+        max_history: usize,
+    ) -> Result<Self> {
+        check_signer(&pk_set, signer.as_ref())?;
+        Ok(KeyRotationManager {
+            current: EpochInfo {
+                epoch: 0,
+                started_at: now,
+                rotation: None,
+                public_key_set: pk_set,
+            },
+            signer,
+            history: VecDeque::new(),
+            max_history,
+            schedule: Schedule::default(),
+            last_rekey: now,
+            hooks: Vec::new(),
+        })
+    }
+
+    /// Sets the schedule for `due`.
+    pub fn with_schedule(mut self, schedule: Schedule) -> Self {
+        self.schedule = schedule;
+        self
+    }
+
+    /// Adds a hook that is notified about new and retired epochs.
+    pub fn add_hook(&mut self, hook: Box<dyn EpochHook<S>>) {
+        self.hooks.push(hook);
+    }
+
+    /// Returns the current epoch.
+    pub fn current(&self) -> &EpochInfo {
+        &self.current
+    }
+
+    /// Returns the current share holder, or `None` if this node is not a member.
+    pub fn signer(&self) -> Option<&S> {
+        self.signer.as_ref()
+    }
+
+    /// Returns the public key set of the given epoch, if it is current or still in the history.
+    pub fn public_keys(&self, epoch: u64) -> Option<&PublicKeySet> {
+        self.epochs()
+            .find(|info| info.epoch == epoch)
+            .map(|info| &info.public_key_set)
+    }
+
+    /// Returns `true` if the signature is valid for the message under the given epoch's key.
+    pub fn verify<M: AsRef<[u8]>>(&self, epoch: u64, sig: &Signature, msg: M) -> bool {
+        self.public_keys(epoch)
+            .is_some_and(|pk_set| pk_set.public_key().verify(sig, msg))
+    }
+
+    /// Returns the epoch whose key the signature is valid under, among the current and
+    /// remembered ones.
+    pub fn find_epoch<M: AsRef<[u8]>>(&self, sig: &Signature, msg: M) -> Option<u64> {
+        let msg = msg.as_ref();
+        self.epochs()
+            .find(|info| info.public_key_set.public_key().verify(sig, msg))
+            .map(|info| info.epoch)
+    }
+
+    /// Returns the rotation that is due at time `now`, if any. Rekeying takes precedence.
+    pub fn due(&self, now: u64) -> Option<Rotation> {
+        let elapsed = |since: u64, interval: Option<u64>| {
+            interval.is_some_and(|interval| now.saturating_sub(since) >= interval)
+        };
+        if elapsed(self.last_rekey, self.schedule.rekey_interval) {
+            Some(Rotation::Rekey)
+        } else if elapsed(self.current.started_at, self.schedule.refresh_interval) {
+            Some(Rotation::Refresh)
+        } else {
+            None
+        }
+    }
+
+    /// Starts the next epoch with the outcome of a rotation protocol, and returns its number.
+    ///
+    /// Returns `Error::InvalidRotation` if the new key set doesn't match the kind of rotation,
+    /// e.g. a refresh changed the key, or if the signer's share doesn't belong to it. If a hook
+    /// fails, its error is returned and the current epoch is unchanged.
+    pub fn rotate(
+        &mut self,
+        rotation: Rotation,
+        pk_set: PublicKeySet,
+        signer: Option<S>,
+        now: u64,
+    ) -> Result<u64> {
+        let old = &self.current.public_key_set;
+        let same_key = pk_set.public_key() == old.public_key();
+        match rotation {
+            Rotation::Refresh if !same_key || pk_set.threshold() != old.threshold() => {
+                let msg = "a refresh must keep the key and threshold";
+                return Err(Error::InvalidRotation(msg.to_string()));
+            }
+            Rotation::Reshare if !same_key => {
+                let msg = "resharing must keep the key";
+                return Err(Error::InvalidRotation(msg.to_string()));
+            }
+            Rotation::Rekey if same_key => {
+                let msg = "rekeying must change the key";
+                return Err(Error::InvalidRotation(msg.to_string()));
+            }
+            _ => (),
+        }
+        check_signer(&pk_set, signer.as_ref())?;
+        let info = EpochInfo {
+            epoch: self.current.epoch + 1,
+            started_at: now,
+            rotation: Some(rotation),
+            public_key_set: pk_set,
+        };
+        for hook in &mut self.hooks {
+            hook.epoch_started(&info, signer.as_ref())?;
+        }
+        if rotation == Rotation::Rekey {
+            self.last_rekey = now;
+        }
+        let previous = std::mem::replace(&mut self.current, info);
+        self.signer = signer;
+        self.history.push_front(previous);
+        while self.history.len() > self.max_history {
+            if let Some(retired) = self.history.pop_back() {
+                for hook in &mut self.hooks {
+                    hook.epoch_retired(&retired);
+                }
+            }
+        }
+        Ok(self.current.epoch)
+    }
+
+    /// Returns the current and remembered epochs, newest first.
+    fn epochs(&self) -> impl Iterator<Item = &EpochInfo> {
+        Some(&self.current).into_iter().chain(&self.history)
+    }
+}
+
+/// Returns an error if the signer's public key share doesn't belong to the key set.
+fn check_signer<S: IndexedShare>(pk_set: &PublicKeySet, signer: Option<&S>) -> Result<()> {
+    match signer {
+        Some(s) if s.public_key_share() != pk_set.public_key_share(s.index()) => {
+            let msg = format!("share {} doesn't belong to the key set", s.index());
+            Err(Error::InvalidRotation(msg))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use crate::poly::Poly;
+    use crate::signer::InMemoryShare;
+    use crate::SecretKeySet;
+
+    /// Records the started and retired epochs.
+    struct Recorder(Rc<RefCell<Vec<(bool, u64)>>>);
+
+    impl EpochHook<InMemoryShare> for Recorder {
+        fn epoch_started(&mut self, info: &EpochInfo, _: Option<&InMemoryShare>) -> Result<()> {
+            self.0.borrow_mut().push((true, info.epoch));
+            Ok(())
+        }
+
+        fn epoch_retired(&mut self, info: &EpochInfo) {
+            self.0.borrow_mut().push((false, info.epoch));
+        }
+    }
+
+    /// Returns a key set with the same key and fresh shares.
+    fn refresh(sk_set: &SecretKeySet) -> SecretKeySet {
+        let mut rng = rand::thread_rng();
+        let mut zero = Poly::random(sk_set.threshold(), &mut rng);
+        zero -= Poly::constant(zero.evaluate(0));
+        SecretKeySet::from(&sk_set.poly + zero)
+    }
+
+    #[test]
+    fn test_rotation() {
+        let mut rng = rand::thread_rng();
+        let sk_set0 = SecretKeySet::random(1, &mut rng);
+        let share = |sk_set: &SecretKeySet| InMemoryShare::new(1, sk_set.secret_key_share(1));
+        let mut manager =
+            KeyRotationManager::new(sk_set0.public_keys(), Some(share(&sk_set0)), 100, 1)
+                .expect("valid share")
+                .with_schedule(Schedule {
+                    refresh_interval: Some(10),
+                    rekey_interval: Some(25),
+                });
+        let events = Rc::new(RefCell::new(Vec::new()));
+        manager.add_hook(Box::new(Recorder(events.clone())));
+        let sig0 = sk_set0.secret_key().sign("old");
+
+        assert_eq!(None, manager.due(105));
+        assert_eq!(Some(Rotation::Refresh), manager.due(110));
+        let sk_set1 = refresh(&sk_set0);
+        let rekeyed = SecretKeySet::random(1, &mut rng);
+        let result = manager.rotate(Rotation::Refresh, rekeyed.public_keys(), None, 110);
+        assert!(result.is_err());
+        let wrong_share = Some(share(&sk_set0));
+        let result = manager.rotate(Rotation::Refresh, sk_set1.public_keys(), wrong_share, 110);
+        assert!(result.is_err());
+        let result = manager.rotate(
+            Rotation::Refresh,
+            sk_set1.public_keys(),
+            Some(share(&sk_set1)),
+            110,
+        );
+        assert_eq!(Ok(1), result);
+        assert_eq!(None, manager.due(115));
+        assert_eq!(Some(Rotation::Rekey), manager.due(125));
+
+        let result = manager.rotate(Rotation::Rekey, rekeyed.public_keys(), None, 125);
+        assert_eq!(Ok(2), result);
+        assert!(manager.signer().is_none());
+        // Epoch 1 is remembered, epoch 0 was retired.
+        assert!(manager.verify(1, &sig0, "old"));
+        assert_eq!(Some(1), manager.find_epoch(&sig0, "old"));
+        assert!(manager.public_keys(0).is_none());
+        let expected = vec![(true, 1), (true, 2), (false, 0)];
+        assert_eq!(expected, *events.borrow());
+    }
+}
@@ -1,23 +1,81 @@
 //! Utilities for working with secret values. This module includes functionality for overwriting
-//! memory with zeros.
+//! memory with zeros, and with the `ct` feature, for comparing secret scalars in constant time and
+//! multiplying by them with a uniform sequence of group operations.
 
-use zeroize::Zeroize;
+use ff::{PrimeField, PrimeFieldRepr};
+use group::CurveAffine;
+#[cfg(feature = "ct")]
+use group::CurveProjective;
+#[cfg(feature = "ct")]
+use subtle::{Choice, ConstantTimeEq};
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{Fr, FrRepr};
 
 /// Overwrites a single field element with zeros.
+// With mock cryptography, `Fr` and `FrRepr` are the same type.
+#[allow(clippy::unnecessary_cast)]
 pub(crate) fn clear_fr(fr: &mut Fr) {
     // TODO: Remove this after pairing support `Zeroize`
     let fr_repr = unsafe { &mut *(fr as *mut Fr as *mut FrRepr) };
     fr_repr.0.zeroize();
 }
 
+/// Returns `base` multiplied by the secret scalar.
+///
+/// With the `ct` feature, this is a Montgomery ladder: It performs one addition and one doubling
+/// per bit, instead of one addition per set bit, so the number of group operations doesn't depend
+/// on the scalar. It is not constant time, though: `pairing`'s group arithmetic branches on the
+/// identity and on equal inputs, and the ladder selects its operands by indexing with the bits.
+pub(crate) fn mul_secret<A: CurveAffine<Scalar = Fr>>(base: A, scalar: &Fr) -> A::Projective {
+    #[cfg(not(feature = "ct"))]
+    {
+        base.mul(*scalar)
+    }
+    #[cfg(feature = "ct")]
+    {
+        let bytes = fr_bytes(scalar);
+        // Invariant: `r[1] - r[0] == base`, and `r[0]` is `base` times the bits processed so far.
+        let mut r = [A::Projective::zero(), base.into_projective()];
+        for byte in bytes.iter() {
+            for i in (0..8).rev() {
+                let bit = usize::from((byte >> i) & 1);
+                let mut sum = r[0];
+                sum.add_assign(&r[1]);
+                r[1 - bit] = sum;
+                r[bit].double();
+            }
+        }
+        r[0]
+    }
+}
+
+/// Returns whether the field elements are equal, comparing their representations in constant
+/// time.
+#[cfg(feature = "ct")]
+pub(crate) fn ct_eq_fr(a: &Fr, b: &Fr) -> Choice {
+    fr_bytes(a).ct_eq(&fr_bytes(b))
+}
+
+/// Returns the field element's big-endian representation.
+pub(crate) fn fr_bytes(fr: &Fr) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::new());
+    let mut repr = fr.into_repr();
+    repr.write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    repr.0.zeroize();
+    bytes
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use ff::Field;
+    use group::CurveProjective;
     use rand::thread_rng;
 
+    use crate::{G1Affine, G2Affine, G1};
+
     #[test]
     fn test_clear() {
         let mut rng = thread_rng();
@@ -28,4 +86,23 @@ mod tests {
         clear_fr(&mut fr);
         assert_eq!(fr, Fr::zero());
     }
+
+    #[test]
+    fn test_mul_secret() {
+        let mut rng = thread_rng();
+        let one = G1Affine::one();
+        for _ in 0..5 {
+            let fr: Fr = Fr::random(&mut rng);
+            assert_eq!(one.mul(fr), mul_secret(one, &fr));
+            #[cfg(feature = "ct")]
+            {
+                assert!(bool::from(ct_eq_fr(&fr, &fr)));
+                assert!(!bool::from(ct_eq_fr(&fr, &Fr::one())));
+            }
+        }
+        assert_eq!(G1::zero(), mul_secret(one, &Fr::zero()));
+        let g2 = G2Affine::one();
+        let fr: Fr = Fr::random(&mut rng);
+        assert_eq!(g2.mul(fr), mul_secret(g2, &fr));
+    }
 }
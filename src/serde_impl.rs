@@ -1,20 +1,59 @@
 //! Serialization and deserialization implementations for group and field elements.
+//!
+//! In human-readable formats such as JSON, group elements are serialized as hex strings of their
+//! compressed representation, and field elements, including secret keys, as hex strings of their
+//! big-endian representation. Binary formats such as `bincode` get the compact encodings: tuples
+//! of bytes for group elements and arrays of limbs for field elements.
 
 pub use self::field_vec::FieldWrap;
 
+#[cfg(feature = "dkg")]
 use std::borrow::Cow;
 use std::ops::Deref;
 
+#[cfg(feature = "dkg")]
 use crate::G1;
-use crate::{IntoFr, Fr};
+use crate::{Fr, IntoFr};
+#[cfg(feature = "dkg")]
 use serde::de::Error as DeserializeError;
 use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
+#[cfg(feature = "dkg")]
 use crate::poly::{coeff_pos, BivarCommitment};
 use crate::serde_impl::serialize_secret_internal::SerializeSecret;
 
+#[cfg(feature = "dkg")]
 const ERR_DEG: &str = "commitment degree does not match coefficients";
 
+/// Serializes the field element as a hex string of its big-endian representation.
+fn serialize_fr_hex<S: Serializer>(fr: &Fr, s: S) -> Result<S::Ok, S::Error> {
+    use ff::{PrimeField, PrimeFieldRepr};
+    use zeroize::{Zeroize, Zeroizing};
+
+    let mut bytes = Zeroizing::new(Vec::with_capacity(crate::SK_SIZE));
+    let mut repr = fr.into_repr();
+    repr.write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    repr.0.zeroize();
+    s.serialize_str(&Zeroizing::new(crate::util::to_hex(&*bytes)))
+}
+
+/// Deserializes a field element from a hex string of its big-endian representation.
+fn deserialize_fr_hex<'de, D: Deserializer<'de>>(d: D) -> Result<Fr, D::Error> {
+    use serde::de::Error;
+    use zeroize::Zeroizing;
+
+    let hex = Zeroizing::new(String::deserialize(d)?);
+    let bytes = Zeroizing::new(
+        crate::util::from_hex(&hex).ok_or_else(|| D::Error::custom("invalid hex string"))?,
+    );
+    let mut fr = crate::decode::decode(&bytes, "field element", |r| r.fr("field element"))
+        .map_err(D::Error::custom)?;
+    let result = Ok(fr);
+    crate::secret::clear_fr(&mut fr);
+    result
+}
+
 mod serialize_secret_internal {
     use serde::Serializer;
 
@@ -37,10 +76,7 @@ mod serialize_secret_internal {
 
 /// A serializable wrapper for Fr
 #[derive(Serialize, Deserialize, PartialEq, Eq, Clone, Copy)]
-pub struct WireFr(
-    #[serde(with = "super::serde_impl::field_element")]
-    Fr
-);
+pub struct WireFr(#[serde(with = "super::serde_impl::field_element")] Fr);
 
 /// Allow converting WireFr to Fr
 impl IntoFr for WireFr {
@@ -110,11 +146,15 @@ impl<'de> Deserialize<'de> for crate::SecretKey {
         use ff::PrimeField;
         use serde::de;
 
+        if deserializer.is_human_readable() {
+            let mut fr = deserialize_fr_hex(deserializer)?;
+            return Ok(crate::SecretKey::from_mut(&mut fr));
+        }
         let mut fr = match Fr::from_repr(FrRepr(Deserialize::deserialize(deserializer)?)) {
             Ok(x) => x,
             Err(ff::PrimeFieldDecodingError::NotInField(_)) => {
                 return Err(de::Error::invalid_value(
-                    de::Unexpected::Other(&"Number outside of prime field."),
+                    de::Unexpected::Other("Number outside of prime field."),
                     &"Valid prime field element.",
                 ));
             }
@@ -128,6 +168,9 @@ impl SerializeSecret for crate::SecretKey {
     fn serialize_secret<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
         use ff::PrimeField;
 
+        if serializer.is_human_readable() {
+            return serialize_fr_hex(&self.0, serializer);
+        }
         Serialize::serialize(&self.0.into_repr().0, serializer)
     }
 }
@@ -149,6 +192,7 @@ impl SerializeSecret for crate::SecretKeyShare {
     }
 }
 
+#[cfg(feature = "dkg")]
 /// A type with the same content as `BivarCommitment`, but that has not been validated yet.
 #[derive(Serialize, Deserialize)]
 struct WireBivarCommitment<'a> {
@@ -159,6 +203,7 @@ struct WireBivarCommitment<'a> {
     coeff: Cow<'a, [G1]>,
 }
 
+#[cfg(feature = "dkg")]
 impl Serialize for BivarCommitment {
     fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
         WireBivarCommitment {
@@ -169,6 +214,7 @@ impl Serialize for BivarCommitment {
     }
 }
 
+#[cfg(feature = "dkg")]
 impl<'de> Deserialize<'de> for BivarCommitment {
     fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
         let WireBivarCommitment { degree, coeff } = Deserialize::deserialize(d)?;
@@ -189,15 +235,18 @@ pub(crate) mod projective {
 
     use group::{CurveAffine, CurveProjective, EncodedPoint};
     use serde::de::{Error as DeserializeError, SeqAccess, Visitor};
-    use serde::{ser::SerializeTuple, Deserializer, Serializer};
+    use serde::{ser::SerializeTuple, Deserialize, Deserializer, Serializer};
 
-    const ERR_CODE: &str = "deserialized bytes don't encode a group element";
+    use crate::util::{from_hex, to_hex};
 
     pub fn serialize<S, C>(c: &C, s: S) -> Result<S::Ok, S::Error>
     where
         S: Serializer,
         C: CurveProjective,
     {
+        if s.is_human_readable() {
+            return s.serialize_str(&to_hex(c.into_affine().into_compressed()));
+        }
         let len = <C::Affine as CurveAffine>::Compressed::size();
         let mut tup = s.serialize_tuple(len)?;
         for byte in c.into_affine().into_compressed().as_ref() {
@@ -230,14 +279,70 @@ pub(crate) mod projective {
                     let len_err = || DeserializeError::invalid_length(i, &self);
                     *byte = seq.next_element()?.ok_or_else(len_err)?;
                 }
-                let to_err = |_| DeserializeError::custom(ERR_CODE);
-                Ok(compressed.into_affine().map_err(to_err)?.into_projective())
+                from_compressed(compressed)
             }
         }
 
+        if d.is_human_readable() {
+            let hex = String::deserialize(d)?;
+            let bytes = from_hex(&hex).ok_or_else(|| D::Error::custom("invalid hex string"))?;
+            let mut compressed = <C::Affine as CurveAffine>::Compressed::empty();
+            if bytes.len() != compressed.as_ref().len() {
+                let expected = format!("{} bytes", compressed.as_ref().len());
+                return Err(D::Error::invalid_length(bytes.len(), &expected.as_str()));
+            }
+            compressed.as_mut().copy_from_slice(&bytes);
+            return from_compressed(compressed);
+        }
         let len = <C::Affine as CurveAffine>::Compressed::size();
         d.deserialize_tuple(len, TupleVisitor { _ph: PhantomData })
     }
+
+    /// Returns the decompressed group element, or an error if it is invalid.
+    fn from_compressed<C, E>(compressed: <C::Affine as CurveAffine>::Compressed) -> Result<C, E>
+    where
+        C: CurveProjective,
+        E: DeserializeError,
+    {
+        let to_err = |err| {
+            let reason = crate::error::DecodeReason::from(err);
+            E::custom(format_args!("invalid group element: {}", reason))
+        };
+        Ok(compressed.into_affine().map_err(to_err)?.into_projective())
+    }
+}
+
+/// Like `projective`, but with the `strict` feature, deserializing the identity is an error.
+pub(crate) mod strict_projective {
+    use group::CurveProjective;
+    use serde::de::Error as DeserializeError;
+    use serde::{Deserializer, Serializer};
+
+    use super::projective;
+
+    pub fn serialize<S, C>(c: &C, s: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+        C: CurveProjective,
+    {
+        projective::serialize(c, s)
+    }
+
+    pub fn deserialize<'de, D, C>(d: D) -> Result<C, D::Error>
+    where
+        D: Deserializer<'de>,
+        C: CurveProjective,
+    {
+        let point: C = projective::deserialize(d)?;
+        if cfg!(feature = "strict") && point.is_zero() {
+            let reason = crate::error::DecodeReason::Identity;
+            return Err(D::Error::custom(format_args!(
+                "invalid group element: {}",
+                reason
+            )));
+        }
+        Ok(point)
+    }
 }
 
 /// Serialization and deserialization of vectors of projective curve elements.
@@ -314,12 +419,18 @@ pub(crate) mod field_element {
 
     impl<B: Borrow<Fr>> Serialize for FieldWrap<B> {
         fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            if s.is_human_readable() {
+                return super::serialize_fr_hex(self.0.borrow(), s);
+            }
             self.0.borrow().into_repr().0.serialize(s)
         }
     }
 
     impl<'de> Deserialize<'de> for FieldWrap<Fr> {
         fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            if d.is_human_readable() {
+                return Ok(FieldWrap(super::deserialize_fr_hex(d)?));
+            }
             let repr = FrRepr(Deserialize::deserialize(d)?);
             Ok(FieldWrap(Fr::from_repr(repr).map_err(|_| {
                 D::Error::custom("invalid field element representation")
@@ -336,7 +447,6 @@ pub(crate) mod field_element {
         let wrap_field = <FieldWrap<Fr>>::deserialize(d)?;
         Ok(wrap_field.into_inner())
     }
-
 }
 
 /// Serialization and deserialization of vectors of field elements.
@@ -361,12 +471,18 @@ pub(crate) mod field_vec {
 
     impl<B: Borrow<Fr>> Serialize for FieldWrap<B> {
         fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+            if s.is_human_readable() {
+                return super::serialize_fr_hex(self.0.borrow(), s);
+            }
             self.0.borrow().into_repr().0.serialize(s)
         }
     }
 
     impl<'de> Deserialize<'de> for FieldWrap<Fr> {
         fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+            if d.is_human_readable() {
+                return Ok(FieldWrap(super::deserialize_fr_hex(d)?));
+            }
             let repr = FrRepr(Deserialize::deserialize(d)?);
             Ok(FieldWrap(Fr::from_repr(repr).map_err(|_| {
                 D::Error::custom("invalid field element representation")
@@ -385,6 +501,30 @@ pub(crate) mod field_vec {
     }
 }
 
+/// Serialization and deserialization of matrices of field elements, as vectors of rows.
+pub(crate) mod field_matrix {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    use super::field_vec::FieldWrap;
+    use crate::Fr;
+
+    pub fn serialize<S: Serializer>(rows: &[Vec<Fr>], s: S) -> Result<S::Ok, S::Error> {
+        let wrap_rows: Vec<Vec<FieldWrap<&Fr>>> = rows
+            .iter()
+            .map(|row| row.iter().map(FieldWrap).collect())
+            .collect();
+        wrap_rows.serialize(s)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Vec<Vec<Fr>>, D::Error> {
+        let wrap_rows = <Vec<Vec<FieldWrap<Fr>>>>::deserialize(d)?;
+        Ok(wrap_rows
+            .into_iter()
+            .map(|row| row.into_iter().map(FieldWrap::into_inner).collect())
+            .collect())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::iter::repeat_with;
@@ -393,6 +533,7 @@ mod tests {
     use group::CurveProjective;
     use serde::{Deserialize, Serialize};
 
+    #[cfg(feature = "dkg")]
     use crate::poly::BivarPoly;
     use crate::{Fr, G1};
 
@@ -422,6 +563,7 @@ mod tests {
         assert_eq!(vecs, de_vecs);
     }
 
+    #[cfg(feature = "dkg")]
     #[test]
     fn bivar_commitment() {
         let mut rng = rand::thread_rng();
@@ -484,17 +626,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn human_readable() {
+        use crate::serde_impl::SerdeSecret;
+        use crate::{PublicKey, PublicKeySet, SecretKey, SecretKeySet, Signature, PK_SIZE};
+
+        let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+        let sk = sk_set.secret_key();
+        let pk = sk.public_key();
+        let json = serde_json::to_string(&pk).expect("serialize public key");
+        assert_eq!(format!("\"{}\"", crate::util::to_hex(pk.to_bytes())), json);
+        assert_eq!(2 * PK_SIZE + 2, json.len());
+        let de: PublicKey = serde_json::from_str(&json).expect("deserialize public key");
+        assert_eq!(pk, de);
+
+        let sig = sk.sign("msg");
+        let json = serde_json::to_string(&sig).expect("serialize signature");
+        let de: Signature = serde_json::from_str(&json).expect("deserialize signature");
+        assert_eq!(sig, de);
+
+        let pk_set = sk_set.public_keys();
+        let json = serde_json::to_string(&pk_set).expect("serialize key set");
+        let de: PublicKeySet = serde_json::from_str(&json).expect("deserialize key set");
+        assert_eq!(pk_set, de);
+
+        let json = serde_json::to_string(&SerdeSecret(&sk)).expect("serialize secret key");
+        assert!(json.starts_with('"'));
+        let de: SecretKey = serde_json::from_str(&json).expect("deserialize secret key");
+        assert_eq!(sk, de);
+
+        // Binary formats are unaffected, and malformed strings are rejected.
+        let bytes = bincode::serialize(&pk).expect("serialize public key");
+        assert_eq!(PK_SIZE, bytes.len());
+        assert!(serde_json::from_str::<PublicKey>("\"00\"").is_err());
+        assert!(serde_json::from_str::<PublicKey>("\"xyz\"").is_err());
+    }
+
     #[test]
     fn wire_fr() {
-        use crate::serde_impl::WireFr;
         use super::IntoFr;
+        use crate::serde_impl::WireFr;
 
         let samples = vec![-8, 0, 40, 136, 5, 628];
         for &x in &samples {
             let wire_fr: WireFr = WireFr::from_fr(x.into_fr());
             let ser_fr = bincode::serialize(&wire_fr).expect("cannot serialize wire_fr");
-            let deser_fr: WireFr = bincode::deserialize(&ser_fr).expect("cannot deserialize wire_fr");
-            println!("y_int: {:?}, fr: {:?}, ser_fr: {:?}, deser_fr: {:?}", x, x.into_fr(), ser_fr, deser_fr.into_fr());
+            let deser_fr: WireFr =
+                bincode::deserialize(&ser_fr).expect("cannot deserialize wire_fr");
+            println!(
+                "y_int: {:?}, fr: {:?}, ser_fr: {:?}, deser_fr: {:?}",
+                x,
+                x.into_fr(),
+                ser_fr,
+                deser_fr.into_fr()
+            );
             assert_eq!(x.into_fr(), deser_fr.into_fr());
         }
     }
@@ -0,0 +1,986 @@
+//! Aggregators collecting signature and decryption shares from a committee.
+//!
+//! A [`SigningSession`] is created for a single message and a [`DecryptionSession`] for a single
+//! ciphertext. Shares can be added as they arrive from the network, or produced locally by any
+//! [`ShareSigner`](../signer/trait.ShareSigner.html) or
+//! [`ShareDecryptor`](../signer/trait.ShareDecryptor.html) backend. Every share is verified
+//! against the sender's public key share before it is stored, so once `threshold + 1` shares are
+//! collected, the result is guaranteed to be valid.
+//!
+//! Rejected shares are recorded in the session's [`MisbehaviorReport`]: invalid shares, shares
+//! conflicting with one already received from the same participant, and shares for the wrong
+//! epoch. Participants can be banned, discarding their share and rejecting any further ones for
+//! the rest of the session; to ban them across sessions, ban the previous sessions' offenders in
+//! each new session.
+//!
+//! [`SessionLimits`] bound the work unauthenticated peers can cause: the range of participant
+//! indices, the number of submissions per participant, the size of encoded shares, and, if shares
+//! are queued and verified only once enough of them have arrived, the number of pending shares.
+//! Pending shares are not part of the saved state. Since unauthenticated peers can claim any
+//! index, shares that fail verification are then neither counted nor reported against the
+//! participant they claim to be from, and a queued share is replaced by a different one that is
+//! valid for that participant.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::mem;
+
+use group::{CurveAffine, CurveProjective};
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+#[cfg(feature = "encryption")]
+use crate::signer::ShareDecryptor;
+use crate::signer::{IndexedShare, ShareSigner};
+use crate::state::Resumable;
+use crate::{hash_g2, G2Affine, PublicKeySet, Signature, SignatureShare, G2};
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+
+/// A kind of misbehavior by a participant.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Misbehavior {
+    /// The participant sent a share that failed verification.
+    InvalidShare,
+    /// The participant sent a share different from one it had sent before.
+    ConflictingShare,
+    /// The participant sent a share for the given epoch instead of the session's.
+    WrongEpoch(u64),
+}
+
+/// The misbehavior observed in a session, by participant.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct MisbehaviorReport {
+    offenses: BTreeMap<usize, Vec<Misbehavior>>,
+}
+
+impl MisbehaviorReport {
+    /// Creates an empty report.
+    pub fn new() -> Self {
+        MisbehaviorReport::default()
+    }
+
+    /// Records that participant `i` misbehaved.
+    pub fn record(&mut self, i: usize, misbehavior: Misbehavior) {
+        self.offenses.entry(i).or_default().push(misbehavior);
+    }
+
+    /// Returns participant `i`'s offenses, in the order they were observed.
+    pub fn offenses(&self, i: usize) -> &[Misbehavior] {
+        self.offenses.get(&i).map_or(&[], Vec::as_slice)
+    }
+
+    /// Returns the indices of all participants that misbehaved.
+    pub fn offenders(&self) -> impl Iterator<Item = usize> + '_ {
+        self.offenses.keys().cloned()
+    }
+
+    /// Returns `true` if no misbehavior was observed.
+    pub fn is_empty(&self) -> bool {
+        self.offenses.is_empty()
+    }
+
+    /// Adds the other report's offenses to this one.
+    pub fn merge(&mut self, other: &MisbehaviorReport) {
+        for (i, offenses) in &other.offenses {
+            self.offenses.entry(*i).or_default().extend(offenses);
+        }
+    }
+}
+
+/// Limits on the resources a session spends on its participants' submissions.
+///
+/// The default imposes no limits, verifies every share before storing it, and treats the index a
+/// share is submitted with as authenticated. Sessions fed by unauthenticated peers should set
+/// limits, including `num_participants`, and set `authenticated` to `false`, so that a flood of
+/// bogus shares can't exhaust the aggregator's memory or CPU, or lock out honest participants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SessionLimits {
+    /// The maximum number of unverified shares, if `verify_before_store` is `false`.
+    pub max_pending: usize,
+    /// The maximum number of submissions per participant, including rejected ones.
+    pub max_submissions: usize,
+    /// The maximum size of an encoded share passed to `add_share_bytes`.
+    pub max_message_size: usize,
+    /// Whether shares are verified before they are stored. If `false`, they are queued without
+    /// verification, and only verified in one go by `verify_pending`, e.g. once enough of them
+    /// have arrived.
+    pub verify_before_store: bool,
+    /// The number of participants. Shares with a larger index are rejected with
+    /// `Error::UnknownParticipant` before they are counted.
+    pub num_participants: usize,
+    /// Whether the index a share is submitted with is known to be its sender's, e.g. because the
+    /// transport authenticates peers. If `false`, shares that fail verification against the
+    /// index's key share are rejected without counting them towards `max_submissions` or
+    /// recording them in the report, since they could have been sent by anyone.
+    pub authenticated: bool,
+}
+
+impl Default for SessionLimits {
+    fn default() -> Self {
+        SessionLimits {
+            max_pending: usize::MAX,
+            max_submissions: usize::MAX,
+            max_message_size: usize::MAX,
+            verify_before_store: true,
+            num_participants: usize::MAX,
+            authenticated: true,
+        }
+    }
+}
+
+/// The epoch, banned participants, misbehavior, limits and unverified shares of a session.
+#[derive(Clone, Debug)]
+struct Conduct<S> {
+    epoch: u64,
+    banned: BTreeSet<usize>,
+    report: MisbehaviorReport,
+    limits: SessionLimits,
+    submissions: BTreeMap<usize, usize>,
+    pending: BTreeMap<usize, S>,
+}
+
+impl<S> Default for Conduct<S> {
+    fn default() -> Self {
+        Conduct {
+            epoch: 0,
+            banned: BTreeSet::new(),
+            report: MisbehaviorReport::new(),
+            limits: SessionLimits::default(),
+            submissions: BTreeMap::new(),
+            pending: BTreeMap::new(),
+        }
+    }
+}
+
+impl<S: PartialEq> Conduct<S> {
+    /// Checks participant `i`'s share for the given epoch, and stores it if `verify` accepts it,
+    /// or queues it if shares are not verified before storing.
+    ///
+    /// A share that differs from a queued one is verified, and replaces it if it is valid. A share
+    /// that differs from a stored one is rejected: the stored one is valid, and valid shares are
+    /// unique.
+    fn add_share<F>(
+        &mut self,
+        shares: &mut BTreeMap<usize, S>,
+        i: usize,
+        epoch: u64,
+        share: S,
+        verify: F,
+    ) -> Result<bool>
+    where
+        F: Fn(usize, &S) -> bool,
+    {
+        if i >= self.limits.num_participants {
+            return Err(Error::UnknownParticipant);
+        }
+        if self.banned.contains(&i) {
+            return Err(Error::Banned(i));
+        }
+        if self.submissions.get(&i).cloned().unwrap_or(0) >= self.limits.max_submissions {
+            let limit = format!("submissions by participant {}", i);
+            return Err(Error::LimitExceeded(limit));
+        }
+        if epoch != self.epoch {
+            self.reject(i, Misbehavior::WrongEpoch(epoch));
+            return Err(Error::WrongEpoch(epoch));
+        }
+        if let Some(existing) = shares.get(&i) {
+            if *existing == share {
+                return Ok(false);
+            }
+            self.reject(i, Misbehavior::ConflictingShare);
+            return Err(Error::DuplicateEntry);
+        }
+        match self.pending.get(&i) {
+            Some(pending) if *pending == share => return Ok(false),
+            Some(_) => (),
+            None if !self.limits.verify_before_store => {
+                if self.pending.len() >= self.limits.max_pending {
+                    return Err(Error::LimitExceeded("pending shares".to_string()));
+                }
+                self.pending.insert(i, share);
+                return Ok(true);
+            }
+            None => (),
+        }
+        self.store(shares, i, share, &verify)?;
+        self.pending.remove(&i);
+        Ok(true)
+    }
+
+    /// Verifies the share and stores it.
+    fn store<F>(
+        &mut self,
+        shares: &mut BTreeMap<usize, S>,
+        i: usize,
+        share: S,
+        verify: F,
+    ) -> Result<()>
+    where
+        F: Fn(usize, &S) -> bool,
+    {
+        if !verify(i, &share) {
+            self.reject(i, Misbehavior::InvalidShare);
+            return Err(Error::InvalidShare(i));
+        }
+        self.count(i);
+        shares.insert(i, share);
+        Ok(())
+    }
+
+    /// Verifies the queued shares, stores the valid ones and returns their number.
+    ///
+    /// Queued shares are only counted as submissions once they are verified, so that bogus ones
+    /// can't use up a participant's budget.
+    fn verify_pending<F>(&mut self, shares: &mut BTreeMap<usize, S>, verify: F) -> usize
+    where
+        F: Fn(usize, &S) -> bool,
+    {
+        let pending = mem::take(&mut self.pending);
+        let mut count = 0;
+        for (i, share) in pending {
+            if verify(i, &share) {
+                self.count(i);
+                shares.insert(i, share);
+                count += 1;
+            } else if self.limits.authenticated {
+                self.report.record(i, Misbehavior::InvalidShare);
+            }
+        }
+        count
+    }
+
+    /// Counts a submission by participant `i`.
+    fn count(&mut self, i: usize) {
+        *self.submissions.entry(i).or_insert(0) += 1;
+    }
+
+    /// Counts and reports a rejected submission by participant `i`, if indices are authenticated.
+    fn reject(&mut self, i: usize, misbehavior: Misbehavior) {
+        if self.limits.authenticated {
+            self.count(i);
+            self.report.record(i, misbehavior);
+        }
+    }
+
+    /// Checks participant `i`'s index and the size of its encoded share.
+    fn check_encoded(&self, i: usize, len: usize) -> Result<()> {
+        if i >= self.limits.num_participants {
+            return Err(Error::UnknownParticipant);
+        }
+        if len > self.limits.max_message_size {
+            return Err(Error::LimitExceeded("message size".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Bans participant `i`, discarding its shares.
+    fn ban(&mut self, shares: &mut BTreeMap<usize, S>, i: usize) {
+        self.banned.insert(i);
+        self.pending.remove(&i);
+        shares.remove(&i);
+    }
+
+    /// Restores the saved epoch, bans and report.
+    fn restore(&mut self, epoch: u64, banned: BTreeSet<usize>, report: MisbehaviorReport) {
+        self.epoch = epoch;
+        self.banned = banned;
+        self.report = report;
+    }
+}
+
+/// Collects signature shares for one message until they can be combined.
+#[derive(Clone, Debug)]
+pub struct SigningSession {
+    pk_set: PublicKeySet,
+    hash: G2Affine,
+    shares: BTreeMap<usize, SignatureShare>,
+    conduct: Conduct<SignatureShare>,
+}
+
+impl SigningSession {
+    /// Creates a session collecting signature shares for the given message.
+    pub fn new<M: AsRef<[u8]>>(pk_set: PublicKeySet, msg: M) -> Self {
+        SigningSession::from_g2(pk_set, hash_g2(msg).into_affine())
+    }
+
+    /// Creates a session collecting signature shares for the given element of `G2`.
+    pub fn from_g2(pk_set: PublicKeySet, hash: G2Affine) -> Self {
+        SigningSession {
+            pk_set,
+            hash,
+            shares: BTreeMap::new(),
+            conduct: Conduct::default(),
+        }
+    }
+
+    /// Sets the epoch whose shares the session accepts. The default is `0`.
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.conduct.epoch = epoch;
+        self
+    }
+
+    /// Returns the epoch whose shares the session accepts.
+    pub fn epoch(&self) -> u64 {
+        self.conduct.epoch
+    }
+
+    /// Sets the limits on the participants' submissions.
+    pub fn with_limits(mut self, limits: SessionLimits) -> Self {
+        self.conduct.limits = limits;
+        self
+    }
+
+    /// Returns the limits on the participants' submissions.
+    pub fn limits(&self) -> &SessionLimits {
+        &self.conduct.limits
+    }
+
+    /// Returns the public key set the shares are verified against.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the element of `G2` being signed.
+    pub fn hash(&self) -> G2Affine {
+        self.hash
+    }
+
+    /// Verifies and stores participant `i`'s signature share.
+    ///
+    /// Returns `Ok(true)` if the share was new, and `Ok(false)` if the same share had already been
+    /// added. Returns an error if the share is invalid or conflicts with a previous one, if the
+    /// participant is banned or unknown, or if a limit is exceeded.
+    pub fn add_share(&mut self, i: usize, share: SignatureShare) -> Result<bool> {
+        let epoch = self.conduct.epoch;
+        self.add_epoch_share(i, epoch, share)
+    }
+
+    /// Verifies and stores participant `i`'s signature share, made in the given epoch.
+    ///
+    /// Returns `Error::WrongEpoch` if that is not the session's epoch, otherwise the same as
+    /// `add_share`.
+    pub fn add_epoch_share(&mut self, i: usize, epoch: u64, share: SignatureShare) -> Result<bool> {
+        let (pk_set, hash) = (&self.pk_set, self.hash);
+        self.conduct
+            .add_share(&mut self.shares, i, epoch, share, |i, share| {
+                pk_set.public_key_share(i).verify_g2(share, hash)
+            })
+    }
+
+    /// Decodes participant `i`'s signature share, as returned by `SignatureShare::to_bytes`, and
+    /// adds it.
+    ///
+    /// Returns `Error::LimitExceeded` if the encoding is larger than `max_message_size`,
+    /// `Error::UnknownParticipant` if `i` is not less than `num_participants`, and
+    /// `Error::InvalidShare(i)` if the share can't be decoded.
+    pub fn add_share_bytes(&mut self, i: usize, bytes: &[u8]) -> Result<bool> {
+        self.conduct.check_encoded(i, bytes.len())?;
+        match SignatureShare::from_slice(bytes) {
+            Ok(share) => self.add_share(i, share),
+            Err(_) => {
+                self.conduct.reject(i, Misbehavior::InvalidShare);
+                Err(Error::InvalidShare(i))
+            }
+        }
+    }
+
+    /// Returns the shares that have not been verified yet.
+    pub fn pending(&self) -> &BTreeMap<usize, SignatureShare> {
+        &self.conduct.pending
+    }
+
+    /// Verifies the pending shares, and returns the number of valid ones. The invalid ones are
+    /// discarded and reported.
+    pub fn verify_pending(&mut self) -> usize {
+        let (pk_set, hash) = (&self.pk_set, self.hash);
+        self.conduct.verify_pending(&mut self.shares, |i, share| {
+            pk_set.public_key_share(i).verify_g2(share, hash)
+        })
+    }
+
+    /// Bans participant `i` for the rest of the session, discarding its share.
+    pub fn ban(&mut self, i: usize) {
+        self.conduct.ban(&mut self.shares, i);
+    }
+
+    /// Returns the banned participants.
+    pub fn banned(&self) -> &BTreeSet<usize> {
+        &self.conduct.banned
+    }
+
+    /// Returns the misbehavior observed so far.
+    pub fn report(&self) -> &MisbehaviorReport {
+        &self.conduct.report
+    }
+
+    /// Asks the given share holder for its signature share and adds it.
+    pub fn contribute<S: ShareSigner + IndexedShare + ?Sized>(
+        &mut self,
+        signer: &S,
+    ) -> Result<bool> {
+        let share = signer.sign_g2(self.hash)?;
+        self.add_share(signer.index(), share)
+    }
+
+    /// Returns the collected signature shares.
+    pub fn shares(&self) -> &BTreeMap<usize, SignatureShare> {
+        &self.shares
+    }
+
+    /// Returns `true` if enough shares have been collected to produce the signature.
+    pub fn is_complete(&self) -> bool {
+        self.shares.len() > self.pk_set.threshold()
+    }
+
+    /// Combines the collected shares into a signature that is valid for the main public key.
+    pub fn signature(&self) -> Result<Signature> {
+        self.pk_set.combine_signatures(&self.shares)
+    }
+}
+
+/// The saved state of a `SigningSession`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SigningSessionState {
+    /// The public key set the shares are verified against.
+    pub public_key_set: PublicKeySet,
+    /// The element of `G2` being signed.
+    #[serde(with = "crate::serde_impl::projective")]
+    pub hash: G2,
+    /// The collected signature shares.
+    pub shares: BTreeMap<usize, SignatureShare>,
+    /// The epoch whose shares the session accepts.
+    #[serde(default)]
+    pub epoch: u64,
+    /// The banned participants.
+    #[serde(default)]
+    pub banned: BTreeSet<usize>,
+    /// The misbehavior observed so far.
+    #[serde(default)]
+    pub report: MisbehaviorReport,
+    /// The limits on the participants' submissions.
+    #[serde(default)]
+    pub limits: SessionLimits,
+}
+
+impl Resumable for SigningSession {
+    const PROTOCOL: &'static str = "signing_session";
+    type State = SigningSessionState;
+
+    fn to_state(&self) -> SigningSessionState {
+        SigningSessionState {
+            public_key_set: self.pk_set.clone(),
+            hash: self.hash.into_projective(),
+            shares: self.shares.clone(),
+            epoch: self.conduct.epoch,
+            banned: self.conduct.banned.clone(),
+            report: self.conduct.report.clone(),
+            limits: self.conduct.limits,
+        }
+    }
+
+    fn from_state(state: SigningSessionState) -> Result<Self> {
+        let mut session = SigningSession::from_g2(state.public_key_set, state.hash.into_affine());
+        session
+            .conduct
+            .restore(state.epoch, state.banned, state.report);
+        for (i, share) in state.shares {
+            session.add_share(i, share)?;
+        }
+        // Set after the saved shares are added, so that they are stored rather than queued.
+        session.conduct.limits = state.limits;
+        Ok(session)
+    }
+}
+
+#[cfg(feature = "encryption")]
+/// Collects decryption shares for one ciphertext until they can be combined.
+#[derive(Clone, Debug)]
+pub struct DecryptionSession {
+    pk_set: PublicKeySet,
+    ct: Ciphertext,
+    ad: Vec<u8>,
+    shares: BTreeMap<usize, DecryptionShare>,
+    conduct: Conduct<DecryptionShare>,
+}
+
+#[cfg(feature = "encryption")]
+impl DecryptionSession {
+    /// Creates a session collecting decryption shares for the given ciphertext, or returns
+    /// `Error::InvalidCiphertext` if it isn't valid.
+    pub fn new(pk_set: PublicKeySet, ct: Ciphertext) -> Result<Self> {
+        DecryptionSession::new_with_ad(pk_set, ct, [])
+    }
+
+    /// Creates a session collecting decryption shares for the given ciphertext, bound to the
+    /// given associated data, or returns `Error::InvalidCiphertext` if it isn't valid for it.
+    pub fn new_with_ad<A: AsRef<[u8]>>(
+        pk_set: PublicKeySet,
+        ct: Ciphertext,
+        ad: A,
+    ) -> Result<Self> {
+        if !ct.verify_with_ad(&ad) {
+            return Err(Error::InvalidCiphertext);
+        }
+        Ok(DecryptionSession {
+            pk_set,
+            ct,
+            ad: ad.as_ref().to_vec(),
+            shares: BTreeMap::new(),
+            conduct: Conduct::default(),
+        })
+    }
+
+    /// Sets the epoch whose shares the session accepts. The default is `0`.
+    pub fn with_epoch(mut self, epoch: u64) -> Self {
+        self.conduct.epoch = epoch;
+        self
+    }
+
+    /// Returns the epoch whose shares the session accepts.
+    pub fn epoch(&self) -> u64 {
+        self.conduct.epoch
+    }
+
+    /// Sets the limits on the participants' submissions.
+    pub fn with_limits(mut self, limits: SessionLimits) -> Self {
+        self.conduct.limits = limits;
+        self
+    }
+
+    /// Returns the limits on the participants' submissions.
+    pub fn limits(&self) -> &SessionLimits {
+        &self.conduct.limits
+    }
+
+    /// Returns the public key set the shares are verified against.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the ciphertext being decrypted.
+    pub fn ciphertext(&self) -> &Ciphertext {
+        &self.ct
+    }
+
+    /// Returns the associated data the ciphertext is bound to.
+    pub fn associated_data(&self) -> &[u8] {
+        &self.ad
+    }
+
+    /// Verifies and stores participant `i`'s decryption share.
+    ///
+    /// Returns `Ok(true)` if the share was new, and `Ok(false)` if the same share had already been
+    /// added. Returns an error if the share is invalid or conflicts with a previous one, if the
+    /// participant is banned or unknown, or if a limit is exceeded.
+    pub fn add_share(&mut self, i: usize, share: DecryptionShare) -> Result<bool> {
+        let epoch = self.conduct.epoch;
+        self.add_epoch_share(i, epoch, share)
+    }
+
+    /// Verifies and stores participant `i`'s decryption share, made in the given epoch.
+    ///
+    /// Returns `Error::WrongEpoch` if that is not the session's epoch, otherwise the same as
+    /// `add_share`.
+    pub fn add_epoch_share(
+        &mut self,
+        i: usize,
+        epoch: u64,
+        share: DecryptionShare,
+    ) -> Result<bool> {
+        let (pk_set, ct, ad) = (&self.pk_set, &self.ct, &self.ad);
+        self.conduct
+            .add_share(&mut self.shares, i, epoch, share, |i, share| {
+                pk_set
+                    .public_key_share(i)
+                    .verify_decryption_share_with_ad(share, ct, ad)
+            })
+    }
+
+    /// Decodes participant `i`'s decryption share, as returned by `DecryptionShare::to_bytes`,
+    /// and adds it.
+    ///
+    /// Returns `Error::LimitExceeded` if the encoding is larger than `max_message_size`,
+    /// `Error::UnknownParticipant` if `i` is not less than `num_participants`, and
+    /// `Error::InvalidShare(i)` if the share can't be decoded.
+    pub fn add_share_bytes(&mut self, i: usize, bytes: &[u8]) -> Result<bool> {
+        self.conduct.check_encoded(i, bytes.len())?;
+        match DecryptionShare::from_slice(bytes) {
+            Ok(share) => self.add_share(i, share),
+            Err(_) => {
+                self.conduct.reject(i, Misbehavior::InvalidShare);
+                Err(Error::InvalidShare(i))
+            }
+        }
+    }
+
+    /// Returns the shares that have not been verified yet.
+    pub fn pending(&self) -> &BTreeMap<usize, DecryptionShare> {
+        &self.conduct.pending
+    }
+
+    /// Verifies the pending shares, and returns the number of valid ones. The invalid ones are
+    /// discarded and reported.
+    pub fn verify_pending(&mut self) -> usize {
+        let (pk_set, ct, ad) = (&self.pk_set, &self.ct, &self.ad);
+        self.conduct.verify_pending(&mut self.shares, |i, share| {
+            pk_set
+                .public_key_share(i)
+                .verify_decryption_share_with_ad(share, ct, ad)
+        })
+    }
+
+    /// Bans participant `i` for the rest of the session, discarding its share.
+    pub fn ban(&mut self, i: usize) {
+        self.conduct.ban(&mut self.shares, i);
+    }
+
+    /// Returns the banned participants.
+    pub fn banned(&self) -> &BTreeSet<usize> {
+        &self.conduct.banned
+    }
+
+    /// Returns the misbehavior observed so far.
+    pub fn report(&self) -> &MisbehaviorReport {
+        &self.conduct.report
+    }
+
+    /// Asks the given share holder for its decryption share and adds it.
+    pub fn contribute<D: ShareDecryptor + IndexedShare + ?Sized>(
+        &mut self,
+        decryptor: &D,
+    ) -> Result<bool> {
+        let share = decryptor.decrypt_share_no_verify(&self.ct)?;
+        self.add_share(decryptor.index(), share)
+    }
+
+    /// Returns the collected decryption shares.
+    pub fn shares(&self) -> &BTreeMap<usize, DecryptionShare> {
+        &self.shares
+    }
+
+    /// Returns `true` if enough shares have been collected to decrypt the ciphertext.
+    pub fn is_complete(&self) -> bool {
+        self.shares.len() > self.pk_set.threshold()
+    }
+
+    /// Combines the collected shares to decrypt the ciphertext.
+    pub fn decrypt(&self) -> Result<Vec<u8>> {
+        self.pk_set.decrypt(&self.shares, &self.ct)
+    }
+}
+
+#[cfg(feature = "encryption")]
+/// The saved state of a `DecryptionSession`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DecryptionSessionState {
+    /// The public key set the shares are verified against.
+    pub public_key_set: PublicKeySet,
+    /// The ciphertext being decrypted.
+    pub ciphertext: Ciphertext,
+    /// The collected decryption shares.
+    pub shares: BTreeMap<usize, DecryptionShare>,
+    /// The epoch whose shares the session accepts.
+    #[serde(default)]
+    pub epoch: u64,
+    /// The banned participants.
+    #[serde(default)]
+    pub banned: BTreeSet<usize>,
+    /// The misbehavior observed so far.
+    #[serde(default)]
+    pub report: MisbehaviorReport,
+    /// The associated data the ciphertext is bound to.
+    #[serde(default)]
+    pub associated_data: Vec<u8>,
+    /// The limits on the participants' submissions.
+    #[serde(default)]
+    pub limits: SessionLimits,
+}
+
+#[cfg(feature = "encryption")]
+impl Resumable for DecryptionSession {
+    const PROTOCOL: &'static str = "decryption_session";
+    type State = DecryptionSessionState;
+
+    fn to_state(&self) -> DecryptionSessionState {
+        DecryptionSessionState {
+            public_key_set: self.pk_set.clone(),
+            ciphertext: self.ct.clone(),
+            shares: self.shares.clone(),
+            epoch: self.conduct.epoch,
+            banned: self.conduct.banned.clone(),
+            report: self.conduct.report.clone(),
+            associated_data: self.ad.clone(),
+            limits: self.conduct.limits,
+        }
+    }
+
+    fn from_state(state: DecryptionSessionState) -> Result<Self> {
+        let mut session = DecryptionSession::new_with_ad(
+            state.public_key_set,
+            state.ciphertext,
+            state.associated_data,
+        )?;
+        session
+            .conduct
+            .restore(state.epoch, state.banned, state.report);
+        for (i, share) in state.shares {
+            session.add_share(i, share)?;
+        }
+        // Set after the saved shares are added, so that they are stored rather than queued.
+        session.conduct.limits = state.limits;
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::signer::InMemoryShare;
+    use crate::{SecretKeySet, SIG_SIZE};
+
+    #[test]
+    fn test_signing_session() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let holders: Vec<_> = (0..5)
+            .map(|i| InMemoryShare::new(i, sk_set.secret_key_share(i)))
+            .collect();
+        let msg = "Meet at dawn";
+
+        let mut session = SigningSession::new(pk_set.clone(), msg);
+        assert_eq!(Ok(true), session.contribute(&holders[0]));
+        assert_eq!(Ok(false), session.contribute(&holders[0]));
+
+        // A share for another message, or from the wrong participant, is rejected.
+        let wrong = sk_set.secret_key_share(1).sign("Meet at dusk");
+        assert_eq!(Err(Error::InvalidShare(1)), session.add_share(1, wrong));
+        let stolen = sk_set.secret_key_share(0).sign(msg);
+        assert_eq!(Err(Error::InvalidShare(4)), session.add_share(4, stolen));
+
+        assert_eq!(Ok(true), session.contribute(&holders[3]));
+        assert!(!session.is_complete());
+        assert_eq!(Err(Error::NotEnoughShares), session.signature());
+        assert_eq!(Ok(true), session.contribute(&holders[4]));
+        assert!(session.is_complete());
+
+        let sig = session.signature().expect("enough valid shares");
+        assert!(pk_set.public_key().verify(&sig, msg));
+    }
+
+    #[test]
+    fn test_misbehavior() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let msg = "msg";
+        let mut session = SigningSession::new(sk_set.public_keys(), msg).with_epoch(3);
+        let share = |i: usize, msg: &str| sk_set.secret_key_share(i).sign(msg);
+
+        let result = session.add_share(0, share(0, "other"));
+        assert_eq!(Err(Error::InvalidShare(0)), result);
+        assert_eq!(Ok(true), session.add_share(0, share(0, msg)));
+        let result = session.add_epoch_share(1, 2, share(1, msg));
+        assert_eq!(Err(Error::WrongEpoch(2)), result);
+        assert_eq!(Ok(true), session.add_epoch_share(1, 3, share(1, msg)));
+        let result = session.add_share(1, share(2, msg));
+        assert_eq!(Err(Error::DuplicateEntry), result);
+
+        let report = session.report().clone();
+        assert_eq!(vec![0, 1], report.offenders().collect::<Vec<_>>());
+        assert_eq!(&[Misbehavior::InvalidShare], report.offenses(0));
+        let offenses = [Misbehavior::WrongEpoch(2), Misbehavior::ConflictingShare];
+        assert_eq!(&offenses, report.offenses(1));
+        assert!(report.offenses(2).is_empty());
+
+        // Banned participants' shares are discarded and rejected.
+        session.ban(1);
+        assert!(!session.is_complete());
+        assert_eq!(Err(Error::Banned(1)), session.add_share(1, share(1, msg)));
+
+        // Bans can be carried over to the next session, and survive saving the state.
+        let mut next = SigningSession::new(sk_set.public_keys(), msg);
+        for i in report.offenders() {
+            next.ban(i);
+        }
+        let mut next = SigningSession::restore_state(next.save_state()).expect("valid state");
+        let result = next.add_share(0, share(0, msg));
+        assert_eq!(Err(Error::Banned(0)), result);
+        let restored = SigningSession::restore_state(session.save_state()).expect("valid state");
+        assert_eq!(session.report(), restored.report());
+        assert_eq!(3, restored.epoch());
+    }
+
+    #[test]
+    fn test_limits() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let msg = "msg";
+        let limits = SessionLimits {
+            max_pending: 2,
+            max_submissions: 2,
+            max_message_size: SIG_SIZE,
+            verify_before_store: false,
+            num_participants: 4,
+            authenticated: true,
+        };
+        let mut session = SigningSession::new(sk_set.public_keys(), msg).with_limits(limits);
+        let share = |i: usize, msg: &str| sk_set.secret_key_share(i).sign(msg);
+
+        // Shares are queued without verification, up to `max_pending`. Different ones are verified.
+        assert_eq!(Ok(true), session.add_share(0, share(0, "other")));
+        assert_eq!(Ok(false), session.add_share(0, share(0, "other")));
+        for other in &["other 2", "other 3"] {
+            let result = session.add_share(0, share(0, other));
+            assert_eq!(Err(Error::InvalidShare(0)), result);
+        }
+        let limit = Error::LimitExceeded("submissions by participant 0".to_string());
+        assert_eq!(Err(limit), session.add_share(0, share(0, msg)));
+        let bytes = share(1, msg).to_bytes();
+        assert_eq!(Ok(true), session.add_share_bytes(1, &bytes));
+        let limit = Error::LimitExceeded("pending shares".to_string());
+        assert_eq!(Err(limit), session.add_share(2, share(2, msg)));
+        let limit = Error::LimitExceeded("message size".to_string());
+        assert_eq!(Err(limit), session.add_share_bytes(3, &[0; SIG_SIZE + 1]));
+        assert!(session.add_share_bytes(3, &[0; SIG_SIZE - 1]).is_err());
+        assert_eq!(&[Misbehavior::InvalidShare], session.report().offenses(3));
+        let result = session.add_share(4, share(4, msg));
+        assert_eq!(Err(Error::UnknownParticipant), result);
+        let result = session.add_share_bytes(4, &[0; SIG_SIZE - 1]);
+        assert_eq!(Err(Error::UnknownParticipant), result);
+        assert!(session.report().offenses(4).is_empty());
+
+        // Verification keeps the valid shares and reports the invalid ones.
+        assert!(!session.is_complete());
+        assert_eq!(1, session.verify_pending());
+        assert!(session.pending().is_empty());
+        assert_eq!(3, session.report().offenses(0).len());
+        assert_eq!(Ok(true), session.add_share(2, share(2, msg)));
+        assert_eq!(1, session.verify_pending());
+        assert!(session.is_complete());
+        let sig = session.signature().expect("enough valid shares");
+        assert!(sk_set.public_keys().public_key().verify(&sig, msg));
+    }
+
+    #[test]
+    fn test_unauthenticated_limits() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let msg = "msg";
+        let limits = SessionLimits {
+            max_submissions: 1,
+            verify_before_store: false,
+            num_participants: 3,
+            authenticated: false,
+            ..SessionLimits::default()
+        };
+        let mut session = SigningSession::new(sk_set.public_keys(), msg).with_limits(limits);
+        let share = |i: usize, msg: &str| sk_set.secret_key_share(i).sign(msg);
+
+        // Bogus shares claiming to be participant 0's neither lock it out nor are reported.
+        assert_eq!(Ok(true), session.add_share(0, share(1, msg)));
+        let result = session.add_share(0, share(0, "other"));
+        assert_eq!(Err(Error::InvalidShare(0)), result);
+        assert_eq!(Ok(true), session.add_share(0, share(0, msg)));
+        assert!(session.pending().is_empty());
+        assert_eq!(Some(&share(0, msg)), session.shares().get(&0));
+        assert_eq!(Ok(true), session.add_share(1, share(0, msg)));
+        assert_eq!(0, session.verify_pending());
+        let result = session.add_share_bytes(2, &[0; SIG_SIZE - 1]);
+        assert_eq!(Err(Error::InvalidShare(2)), result);
+        let result = session.add_share(usize::MAX, share(0, msg));
+        assert_eq!(Err(Error::UnknownParticipant), result);
+        assert!(session.report().is_empty());
+
+        assert_eq!(Ok(true), session.add_share(1, share(1, msg)));
+        assert_eq!(1, session.verify_pending());
+        assert!(session.is_complete());
+    }
+
+    #[test]
+    fn test_resume_signing_session() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let mut session = SigningSession::new(sk_set.public_keys(), "msg");
+        let holder = InMemoryShare::new(2, sk_set.secret_key_share(2));
+        assert_eq!(Ok(true), session.contribute(&holder));
+
+        let bytes = bincode::serialize(&session.save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&bytes).expect("deserialize state");
+        let mut restored = SigningSession::restore_state(saved).expect("valid state");
+        assert_eq!(session.shares(), restored.shares());
+        let holder = InMemoryShare::new(3, sk_set.secret_key_share(3));
+        assert_eq!(Ok(true), restored.contribute(&holder));
+        assert!(restored.is_complete());
+
+        // The limits are restored, and the saved shares are stored even if shares are queued.
+        let limits = SessionLimits {
+            num_participants: 4,
+            verify_before_store: false,
+            authenticated: false,
+            ..SessionLimits::default()
+        };
+        let limited = session.clone().with_limits(limits);
+        let bytes = bincode::serialize(&limited.save_state()).expect("serialize state");
+        let saved = bincode::deserialize(&bytes).expect("deserialize state");
+        let mut restored = SigningSession::restore_state(saved).expect("valid state");
+        assert_eq!(&limits, restored.limits());
+        assert_eq!(session.shares(), restored.shares());
+        let share = sk_set.secret_key_share(4).sign("msg");
+        assert_eq!(Err(Error::UnknownParticipant), restored.add_share(4, share));
+
+        // Shares are verified again when restoring.
+        let mut saved = session.save_state();
+        saved
+            .state
+            .shares
+            .insert(0, sk_set.secret_key_share(0).sign("other msg"));
+        let result = SigningSession::restore_state(saved);
+        assert_eq!(Some(Error::InvalidShare(0)), result.err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_decryption_session() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msg = b"Attack at dawn";
+        let ct = pk_set.public_key().encrypt(msg);
+
+        let mut session = DecryptionSession::new(pk_set.clone(), ct.clone()).expect("valid");
+        for i in &[2, 6] {
+            let holder = InMemoryShare::new(*i, sk_set.secret_key_share(*i));
+            assert_eq!(Ok(true), session.contribute(&holder));
+        }
+        let bad_share = sk_set.secret_key_share(7).decrypt_share_no_verify(&ct);
+        assert_eq!(Err(Error::InvalidShare(8)), session.add_share(8, bad_share));
+        assert!(session.is_complete());
+        assert_eq!(msg[..], session.decrypt().expect("enough valid shares")[..]);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_decryption_session_with_ad() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let msg = b"Attack at dawn";
+        let ct = pk_set.public_key().encrypt_with_ad(msg, "epoch 1");
+        assert!(DecryptionSession::new(pk_set.clone(), ct.clone()).is_err());
+        assert!(DecryptionSession::new_with_ad(pk_set.clone(), ct.clone(), "epoch 2").is_err());
+
+        let mut session =
+            DecryptionSession::new_with_ad(pk_set, ct, "epoch 1").expect("valid ciphertext");
+        let holder = InMemoryShare::new(2, sk_set.secret_key_share(2));
+        assert_eq!(Ok(true), session.contribute(&holder));
+
+        // The associated data survives saving and restoring the session.
+        let mut session = DecryptionSession::restore_state(session.save_state()).expect("valid");
+        assert_eq!(b"epoch 1", session.associated_data());
+        let holder = InMemoryShare::new(6, sk_set.secret_key_share(6));
+        assert_eq!(Ok(true), session.contribute(&holder));
+        assert_eq!(msg[..], session.decrypt().expect("enough valid shares")[..]);
+    }
+}
@@ -0,0 +1,272 @@
+//! Shamir secret sharing of arbitrary byte strings.
+//!
+//! Keys of this crate are shared as field elements, but users often need to protect other
+//! secrets with the same committee: seeds, passwords, API keys. A [`ByteSecretSet`] splits a
+//! byte string of any length among participants, so that any `threshold + 1` of the
+//! [`ByteShare`]s reconstruct it with [`combine`].
+//!
+//! The secret is prefixed with its length and followed by its SHA3-256 digest, and the result is
+//! split into chunks that fit into a field element. Each chunk is shared with its own random
+//! polynomial, and share `index` consists of all polynomials' values at `index + 1`. On
+//! reconstruction the digest is checked, so that a corrupted share is detected instead of
+//! silently producing a wrong secret. The number of chunks, and hence roughly the secret's
+//! length, is not hidden.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use ff::{Field, PrimeField, PrimeFieldRepr};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, Result};
+use crate::poly::Poly;
+use crate::secret::clear_fr;
+use crate::util::sha3_256;
+use crate::{into_fr_plus_1, DebugDots, Fr};
+
+/// The length of the digest appended to the secret.
+const DIGEST_LEN: usize = 32;
+
+/// The length of the secret's length prefix.
+const LEN_PREFIX: usize = 8;
+
+/// A byte secret, shared among participants.
+#[derive(Debug)]
+pub struct ByteSecretSet {
+    polys: Vec<Poly>,
+}
+
+impl ByteSecretSet {
+    /// Shares the secret so that any `threshold + 1` shares can reconstruct it.
+    pub fn new<R: Rng>(secret: &[u8], threshold: usize, rng: &mut R) -> Self {
+        let mut payload =
+            Zeroizing::new(Vec::with_capacity(LEN_PREFIX + secret.len() + DIGEST_LEN));
+        payload.extend(&(secret.len() as u64).to_be_bytes());
+        payload.extend(secret);
+        payload.extend(&sha3_256(secret));
+        let polys = payload
+            .chunks(chunk_len())
+            .map(|chunk| {
+                let mut poly = Poly::random(threshold, rng);
+                poly.coeff[0] = chunk_to_fr(chunk);
+                poly
+            })
+            .collect();
+        ByteSecretSet { polys }
+    }
+
+    /// Returns the threshold `t`: any set of `t + 1` shares reconstructs the secret.
+    pub fn threshold(&self) -> usize {
+        self.polys[0].degree()
+    }
+
+    /// Returns the `i`-th share.
+    pub fn share(&self, i: usize) -> ByteShare {
+        ByteShare {
+            index: i,
+            threshold: self.threshold(),
+            values: self.polys.iter().map(|poly| poly.evaluate(i + 1)).collect(),
+        }
+    }
+}
+
+/// A participant's share of a byte secret.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct ByteShare {
+    index: usize,
+    threshold: usize,
+    #[serde(with = "crate::serde_impl::field_vec")]
+    values: Vec<Fr>,
+}
+
+impl Zeroize for ByteShare {
+    fn zeroize(&mut self) {
+        self.values.iter_mut().for_each(clear_fr);
+    }
+}
+
+impl Drop for ByteShare {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl fmt::Debug for ByteShare {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ByteShare")
+            .field("index", &self.index)
+            .field("threshold", &self.threshold)
+            .field("values", &DebugDots)
+            .finish()
+    }
+}
+
+impl ByteShare {
+    /// Returns the share index.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the threshold `t`: any set of `t + 1` shares reconstructs the secret.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+/// Reconstructs the secret from the first `threshold + 1` shares.
+///
+/// Returns `Error::NotEnoughShares` if there are too few, `Error::DuplicateEntry` if an index
+/// appears twice, `Error::InvalidShare` if a share doesn't belong to the same sharing as the
+/// first one, and `Error::IntegrityCheckFailed` if a share was corrupted.
+pub fn combine<'a, I>(shares: I) -> Result<Zeroizing<Vec<u8>>>
+where
+    I: IntoIterator<Item = &'a ByteShare>,
+{
+    let mut shares = shares.into_iter().peekable();
+    let (threshold, chunks) = match shares.peek() {
+        Some(first) => (first.threshold, first.values.len()),
+        None => return Err(Error::NotEnoughShares),
+    };
+    let mut selected = BTreeMap::new();
+    for share in shares.take(threshold + 1) {
+        if share.threshold != threshold || share.values.len() != chunks {
+            return Err(Error::InvalidShare(share.index));
+        }
+        if selected.insert(share.index, share).is_some() {
+            return Err(Error::DuplicateEntry);
+        }
+    }
+    if selected.len() <= threshold {
+        return Err(Error::NotEnoughShares);
+    }
+    let lagrange = lagrange_at_zero(selected.keys().map(|i| into_fr_plus_1(*i)).collect())?;
+
+    let mut payload = Zeroizing::new(Vec::with_capacity(chunks * chunk_len()));
+    for c in 0..chunks {
+        let mut value = Fr::zero();
+        for (share, l) in selected.values().zip(&lagrange) {
+            let mut term = share.values[c];
+            term.mul_assign(l);
+            value.add_assign(&term);
+            clear_fr(&mut term);
+        }
+        payload.extend(fr_to_chunk(&value).iter());
+        clear_fr(&mut value);
+    }
+    let mut len_bytes = [0u8; LEN_PREFIX];
+    len_bytes.copy_from_slice(
+        payload
+            .get(..LEN_PREFIX)
+            .ok_or(Error::IntegrityCheckFailed)?,
+    );
+    let len = u64::from_be_bytes(len_bytes) as usize;
+    let end = LEN_PREFIX
+        .checked_add(len)
+        .and_then(|end| end.checked_add(DIGEST_LEN))
+        .filter(|end| *end <= payload.len() && payload.len() - *end < chunk_len())
+        .ok_or(Error::IntegrityCheckFailed)?;
+    let secret = &payload[LEN_PREFIX..LEN_PREFIX + len];
+    let valid = sha3_256(secret)[..] == payload[LEN_PREFIX + len..end]
+        && payload[end..].iter().all(|b| *b == 0);
+    if !valid {
+        return Err(Error::IntegrityCheckFailed);
+    }
+    Ok(Zeroizing::new(secret.to_vec()))
+}
+
+/// Returns the Lagrange coefficients for the value at `0` of the polynomial through the points.
+fn lagrange_at_zero(xs: Vec<Fr>) -> Result<Vec<Fr>> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, x_i)| {
+            let mut num = Fr::one();
+            let mut denom = Fr::one();
+            for (_, x_j) in xs.iter().enumerate().filter(|(j, _)| *j != i) {
+                num.mul_assign(x_j);
+                let mut diff = *x_j;
+                diff.sub_assign(x_i);
+                denom.mul_assign(&diff);
+            }
+            let mut coeff = denom.inverse().ok_or(Error::InterpolationFailed)?;
+            coeff.mul_assign(&num);
+            Ok(coeff)
+        })
+        .collect()
+}
+
+/// Returns the number of bytes stored in each field element.
+fn chunk_len() -> usize {
+    (Fr::CAPACITY / 8) as usize
+}
+
+/// Returns the length of a field element's big-endian representation.
+fn repr_len() -> usize {
+    let mut bytes = Vec::new();
+    Fr::zero()
+        .into_repr()
+        .write_be(&mut bytes)
+        .expect("writing to a vector can't fail");
+    bytes.len()
+}
+
+/// Converts up to `chunk_len()` bytes into a field element, padding them with zeros.
+fn chunk_to_fr(chunk: &[u8]) -> Fr {
+    let mut bytes = Zeroizing::new(vec![0u8; repr_len()]);
+    let start = bytes.len() - chunk_len();
+    bytes[start..start + chunk.len()].copy_from_slice(chunk);
+    let mut repr = <Fr as PrimeField>::Repr::default();
+    repr.read_be(&bytes[..])
+        .expect("the buffer has the representation's length");
+    Fr::from_repr(repr).expect("chunks are smaller than the modulus")
+}
+
+/// Converts a field element back into `chunk_len()` bytes.
+fn fr_to_chunk(value: &Fr) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(repr_len()));
+    value
+        .into_repr()
+        .write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    let start = bytes.len() - chunk_len();
+    Zeroizing::new(bytes[start..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_secret() {
+        let mut rng = rand::thread_rng();
+        for secret in [&b""[..], b"short", &[0xff; 100][..]].iter() {
+            let set = ByteSecretSet::new(secret, 2, &mut rng);
+            let shares: Vec<ByteShare> = (0..5).map(|i| set.share(i)).collect();
+            let combined = combine(shares.iter().skip(2)).expect("enough shares");
+            assert_eq!(*secret, &combined[..]);
+            let combined = combine(shares.iter().rev()).expect("enough shares");
+            assert_eq!(*secret, &combined[..]);
+            assert_eq!(Some(Error::NotEnoughShares), combine(&shares[..2]).err());
+        }
+    }
+
+    #[test]
+    fn test_corrupted_share() {
+        let mut rng = rand::thread_rng();
+        let set = ByteSecretSet::new(b"correct horse battery staple", 1, &mut rng);
+        let mut shares: Vec<ByteShare> = (0..3).map(|i| set.share(i)).collect();
+        shares[1].values[0].add_assign(&Fr::one());
+        assert_eq!(
+            Some(Error::IntegrityCheckFailed),
+            combine(&shares[..2]).err()
+        );
+        assert!(combine(&[shares[0].clone(), shares[2].clone()]).is_ok());
+
+        let other = ByteSecretSet::new(b"short", 2, &mut rng).share(2);
+        let result = combine(vec![&shares[0], &other]);
+        assert_eq!(Some(Error::InvalidShare(2)), result.err());
+        let result = combine(vec![&shares[0], &shares[0]]);
+        assert_eq!(Some(Error::DuplicateEntry), result.err());
+    }
+}
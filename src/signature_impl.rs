@@ -0,0 +1,90 @@
+//! Implementations of the [`signature`](https://docs.rs/signature) crate's traits.
+//!
+//! `SecretKey` is a `Signer<Signature>` and `SecretKeyShare` a `Signer<SignatureShare>`; signing
+//! can't fail. `PublicKey` and `PublicKeyShare` are the matching `Verifier`s, and return an opaque
+//! `signature::Error` for an invalid signature.
+
+use signature::{SignatureEncoding, Signer, Verifier};
+
+use crate::SIG_SIZE;
+use crate::{PublicKey, PublicKeyShare, SecretKey, SecretKeyShare, Signature, SignatureShare};
+
+impl SignatureEncoding for Signature {
+    type Repr = [u8; SIG_SIZE];
+}
+
+impl SignatureEncoding for SignatureShare {
+    type Repr = [u8; SIG_SIZE];
+}
+
+impl Signer<Signature> for SecretKey {
+    fn try_sign(&self, msg: &[u8]) -> Result<Signature, signature::Error> {
+        Ok(SecretKey::sign(self, msg))
+    }
+}
+
+impl Signer<SignatureShare> for SecretKeyShare {
+    fn try_sign(&self, msg: &[u8]) -> Result<SignatureShare, signature::Error> {
+        Ok(SecretKeyShare::sign(self, msg))
+    }
+}
+
+impl Verifier<Signature> for PublicKey {
+    fn verify(&self, msg: &[u8], sig: &Signature) -> Result<(), signature::Error> {
+        if PublicKey::verify(self, sig, msg) {
+            Ok(())
+        } else {
+            Err(signature::Error::new())
+        }
+    }
+}
+
+impl Verifier<SignatureShare> for PublicKeyShare {
+    fn verify(&self, msg: &[u8], sig: &SignatureShare) -> Result<(), signature::Error> {
+        if PublicKeyShare::verify(self, sig, msg) {
+            Ok(())
+        } else {
+            Err(signature::Error::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::TryFrom;
+
+    use signature::{SignatureEncoding, Signer, Verifier};
+
+    use crate::{SecretKey, SecretKeySet, Signature, SignatureShare};
+
+    /// Signs and verifies only through the traits, as a generic framework would.
+    fn sign_and_verify<S, K, V>(signer: &K, verifier: &V) -> S
+    where
+        S: SignatureEncoding,
+        K: Signer<S>,
+        V: Verifier<S>,
+    {
+        let sig = signer.try_sign(b"msg").expect("signing can't fail");
+        assert!(verifier.verify(b"msg", &sig).is_ok());
+        assert!(verifier.verify(b"other msg", &sig).is_err());
+        sig
+    }
+
+    #[test]
+    fn test_signature_traits() {
+        let sk = SecretKey::random();
+        let sig: Signature = sign_and_verify(&sk, &sk.public_key());
+        assert_eq!(sk.sign(b"msg"), sig);
+        let bytes = SignatureEncoding::to_bytes(&sig);
+        assert_eq!(Ok(sig), Signature::try_from(&bytes[..]));
+
+        let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+        let sk_share = sk_set.secret_key_share(3);
+        let pk_share = sk_set.public_keys().public_key_share(3);
+        let share: SignatureShare = sign_and_verify(&sk_share, &pk_share);
+        assert_eq!(sk_share.sign(b"msg"), share);
+        assert!(
+            Verifier::verify(&sk_set.public_keys().public_key_share(2), b"msg", &share).is_err()
+        );
+    }
+}
@@ -0,0 +1,209 @@
+//! Abstractions over holders of secret key shares.
+//!
+//! A participant in a threshold scheme doesn't necessarily keep its `SecretKeyShare` in memory:
+//! The share may live in an HSM accessed via PKCS#11, in a cloud KMS, or in a secure enclave. The
+//! traits in this module capture the only two operations such a backend has to support, a scalar
+//! multiplication in `G2` for signing and one in `G1` for decrypting, so that the rest of the
+//! protocol, e.g. the aggregators in [`session`](../session/index.html), can be reused unchanged.
+//!
+//! A backend implements [`ShareHolder::public_key_share`], [`ShareSigner::sign_g2`] and
+//! [`ShareDecryptor::decrypt_share_no_verify`]; hashing and ciphertext validation are provided.
+//! A backend that implements both `ShareSigner` and `ShareDecryptor`, e.g. a `SecretKeyShare`,
+//! is a [`ThresholdSigner`]. The aggregators also need to know the share's index, so they take
+//! backends that implement [`IndexedShare`], such as an [`InMemoryShare`].
+//! Failures of the backend should be reported as [`Error::Backend`](../error/enum.Error.html).
+
+use group::CurveProjective;
+
+use crate::error::Result;
+#[cfg(feature = "encryption")]
+use crate::{error::Error, Ciphertext, DecryptionShare};
+use crate::{hash_g2, G2Affine, PublicKeyShare, SecretKeyShare, SignatureShare};
+
+/// Something that holds a secret key share.
+pub trait ShareHolder {
+    /// Returns the public key share matching the held secret key share.
+    fn public_key_share(&self) -> PublicKeyShare;
+}
+
+/// Something that holds the secret key share with a given index.
+pub trait IndexedShare: ShareHolder {
+    /// Returns the index of the held share, as passed to `SecretKeySet::secret_key_share`.
+    fn index(&self) -> usize;
+}
+
+/// Something that holds a secret key share and can sign with it.
+pub trait ShareSigner: ShareHolder {
+    /// Signs the given element of `G2`.
+    fn sign_g2(&self, hash: G2Affine) -> Result<SignatureShare>;
+
+    /// Signs the given message.
+    ///
+    /// This is equivalent to `sign_g2(hash_g2(msg))`.
+    fn sign_share(&self, msg: &[u8]) -> Result<SignatureShare> {
+        self.sign_g2(hash_g2(msg).into_affine())
+    }
+}
+
+#[cfg(feature = "encryption")]
+/// Something that holds a secret key share and can produce decryption shares with it.
+pub trait ShareDecryptor: ShareHolder {
+    /// Returns a decryption share, without validating the ciphertext.
+    fn decrypt_share_no_verify(&self, ct: &Ciphertext) -> Result<DecryptionShare>;
+
+    /// Returns a decryption share, or `Error::InvalidCiphertext` if the ciphertext isn't valid.
+    fn decrypt_share(&self, ct: &Ciphertext) -> Result<DecryptionShare> {
+        self.decrypt_share_with_ad(ct, &[])
+    }
+
+    /// Returns a decryption share, or `Error::InvalidCiphertext` if the ciphertext isn't valid
+    /// for the given associated data.
+    fn decrypt_share_with_ad(&self, ct: &Ciphertext, ad: &[u8]) -> Result<DecryptionShare> {
+        if !ct.verify_with_ad(ad) {
+            return Err(Error::InvalidCiphertext);
+        }
+        self.decrypt_share_no_verify(ct)
+    }
+}
+
+#[cfg(feature = "encryption")]
+/// Something that can both sign and produce decryption shares with the share it holds, e.g. an
+/// HSM, enclave or remote service backing all of a participant's share operations.
+///
+/// Its methods are `sign_share`, `decrypt_share` and `public_key_share`, from its supertraits. It
+/// is implemented for every type that implements both `ShareSigner` and `ShareDecryptor`,
+/// including `SecretKeyShare`.
+pub trait ThresholdSigner: ShareSigner + ShareDecryptor {}
+
+#[cfg(feature = "encryption")]
+impl<T: ShareSigner + ShareDecryptor + ?Sized> ThresholdSigner for T {}
+
+impl ShareHolder for SecretKeyShare {
+    fn public_key_share(&self) -> PublicKeyShare {
+        SecretKeyShare::public_key_share(self)
+    }
+}
+
+impl ShareSigner for SecretKeyShare {
+    fn sign_g2(&self, hash: G2Affine) -> Result<SignatureShare> {
+        Ok(SecretKeyShare::sign_g2(self, hash))
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl ShareDecryptor for SecretKeyShare {
+    fn decrypt_share_no_verify(&self, ct: &Ciphertext) -> Result<DecryptionShare> {
+        Ok(SecretKeyShare::decrypt_share_no_verify(self, ct))
+    }
+}
+
+/// A share holder that keeps the `SecretKeyShare` in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InMemoryShare {
+    index: usize,
+    share: SecretKeyShare,
+}
+
+impl InMemoryShare {
+    /// Creates a share holder for the `index`-th secret key share.
+    pub fn new(index: usize, share: SecretKeyShare) -> Self {
+        InMemoryShare { index, share }
+    }
+
+    /// Returns the held secret key share.
+    pub fn secret_key_share(&self) -> &SecretKeyShare {
+        &self.share
+    }
+}
+
+impl ShareHolder for InMemoryShare {
+    fn public_key_share(&self) -> PublicKeyShare {
+        self.share.public_key_share()
+    }
+}
+
+impl IndexedShare for InMemoryShare {
+    fn index(&self) -> usize {
+        self.index
+    }
+}
+
+impl ShareSigner for InMemoryShare {
+    fn sign_g2(&self, hash: G2Affine) -> Result<SignatureShare> {
+        Ok(self.share.sign_g2(hash))
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl ShareDecryptor for InMemoryShare {
+    fn decrypt_share_no_verify(&self, ct: &Ciphertext) -> Result<DecryptionShare> {
+        Ok(self.share.decrypt_share_no_verify(ct))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_in_memory_share() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let holder = InMemoryShare::new(3, sk_set.secret_key_share(3));
+        assert_eq!(pk_set.public_key_share(3), holder.public_key_share());
+
+        let msg = b"Sign me";
+        let sig_share = holder
+            .sign_share(msg)
+            .expect("in-memory signing can't fail");
+        assert_eq!(sk_set.secret_key_share(3).sign(msg), sig_share);
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_in_memory_decryptor() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let holder = InMemoryShare::new(3, sk_set.secret_key_share(3));
+        let ct = pk_set.public_key().encrypt(b"Decrypt me");
+        let dec_share = holder.decrypt_share(&ct).expect("valid ciphertext");
+        assert!(pk_set
+            .public_key_share(3)
+            .verify_decryption_share(&dec_share, &ct));
+
+        let ct = pk_set
+            .public_key()
+            .encrypt_with_ad(b"Decrypt me", b"epoch 1");
+        assert_eq!(Err(Error::InvalidCiphertext), holder.decrypt_share(&ct));
+        let dec_share = holder
+            .decrypt_share_with_ad(&ct, b"epoch 1")
+            .expect("valid ciphertext");
+        assert!(pk_set
+            .public_key_share(3)
+            .verify_decryption_share_with_ad(&dec_share, &ct, b"epoch 1"));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_threshold_signer() {
+        fn check<S: ThresholdSigner>(signer: &S, pk_share: crate::PublicKeyShare) {
+            assert_eq!(pk_share, signer.public_key_share());
+            let sig_share = signer.sign_share(b"msg").expect("signature share");
+            assert!(pk_share.verify(&sig_share, b"msg"));
+            let ct = pk_share.0.encrypt(b"msg");
+            let dec_share = signer.decrypt_share(&ct).expect("decryption share");
+            assert!(pk_share.verify_decryption_share(&dec_share, &ct));
+        }
+
+        let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+        let pk_share = sk_set.public_keys().public_key_share(2);
+        let holder = InMemoryShare::new(2, sk_set.secret_key_share(2));
+        assert_eq!(2, holder.index());
+        check(&holder, pk_share);
+        check(&sk_set.secret_key_share(2), pk_share);
+    }
+}
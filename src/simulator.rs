@@ -0,0 +1,334 @@
+//! A deterministic in-memory network for testing protocols between several participants.
+//!
+//! A protocol participant implements [`Node`]: It reacts to incoming messages by sending further
+//! messages, and eventually produces an output. A [`Network`] runs a set of nodes, delivering
+//! their messages in an order chosen by a seeded random number generator. It can drop messages
+//! and deliver them out of order, so an interleaving that breaks a protocol can be reproduced from
+//! its seed alone.
+//!
+//! [`SigningNode`] runs a threshold signing round on top of a
+//! [`SigningSession`](../session/struct.SigningSession.html).
+//!
+//! This module is only available with the `testing` feature.
+
+use std::collections::VecDeque;
+use std::fmt;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+
+#[cfg(feature = "sessions")]
+use crate::session::SigningSession;
+#[cfg(feature = "sessions")]
+use crate::signer::{IndexedShare, ShareSigner};
+#[cfg(feature = "sessions")]
+use crate::{PublicKeySet, Signature, SignatureShare};
+
+/// The recipients of a message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Target {
+    /// Every node except the sender.
+    All,
+    /// The node with the given ID.
+    Node(usize),
+}
+
+/// A message sent by a node, before delivery.
+pub type Outgoing<M> = (Target, M);
+
+/// A participant in a protocol.
+pub trait Node {
+    /// The type of messages exchanged between the nodes.
+    type Message: Clone + fmt::Debug;
+    /// The result of the protocol.
+    type Output;
+
+    /// Returns the messages the node sends when the protocol starts.
+    fn start(&mut self) -> Vec<Outgoing<Self::Message>>;
+
+    /// Handles a message from node `from` and returns the messages to send in response.
+    fn handle_message(&mut self, from: usize, msg: Self::Message) -> Vec<Outgoing<Self::Message>>;
+
+    /// Returns the node's output, if it has terminated.
+    fn output(&self) -> Option<Self::Output>;
+}
+
+/// A message in transit.
+#[derive(Clone, Debug)]
+struct Envelope<M> {
+    from: usize,
+    to: usize,
+    msg: M,
+}
+
+/// Runs a set of nodes, delivering their messages in a pseudorandom order.
+///
+/// Node IDs are the nodes' positions in the vector passed to `Network::new`.
+#[derive(Debug)]
+pub struct Network<N: Node> {
+    nodes: Vec<N>,
+    crashed: Vec<bool>,
+    queue: VecDeque<Envelope<N::Message>>,
+    rng: ChaChaRng,
+    drop_probability: f64,
+    reorder: bool,
+    delivered: usize,
+    dropped: usize,
+}
+
+impl<N: Node> Network<N> {
+    /// Creates a network of the given nodes, with scheduling decisions derived from `seed`, and
+    /// queues the messages the nodes send on startup.
+    ///
+    /// By default, messages are neither dropped nor reordered.
+    pub fn new(nodes: Vec<N>, seed: u64) -> Self {
+        let crashed = vec![false; nodes.len()];
+        let mut network = Network {
+            nodes,
+            crashed,
+            queue: VecDeque::new(),
+            rng: ChaChaRng::seed_from_u64(seed),
+            drop_probability: 0.0,
+            reorder: false,
+            delivered: 0,
+            dropped: 0,
+        };
+        for from in 0..network.nodes.len() {
+            let outgoing = network.nodes[from].start();
+            network.send(from, outgoing);
+        }
+        network
+    }
+
+    /// Makes the network drop each message with the given probability.
+    ///
+    /// This also applies to the messages already queued by `Network::new`.
+    pub fn with_drop_probability(mut self, probability: f64) -> Self {
+        self.drop_probability = probability;
+        let queued: Vec<_> = self.queue.drain(..).collect();
+        for envelope in queued {
+            self.enqueue(envelope);
+        }
+        self
+    }
+
+    /// Makes the network deliver queued messages in random order instead of first in, first out.
+    pub fn with_reordering(mut self) -> Self {
+        self.reorder = true;
+        self
+    }
+
+    /// Crashes the node: From now on, messages to and from it are discarded.
+    pub fn crash(&mut self, id: usize) {
+        self.crashed[id] = true;
+        self.queue
+            .retain(|envelope| envelope.to != id && envelope.from != id);
+    }
+
+    /// Returns the nodes.
+    pub fn nodes(&self) -> &[N] {
+        &self.nodes
+    }
+
+    /// Returns the node with the given ID.
+    pub fn node_mut(&mut self, id: usize) -> &mut N {
+        &mut self.nodes[id]
+    }
+
+    /// Returns the outputs of all nodes, in order of their IDs.
+    pub fn outputs(&self) -> Vec<Option<N::Output>> {
+        self.nodes.iter().map(Node::output).collect()
+    }
+
+    /// Returns the number of messages waiting to be delivered.
+    pub fn pending(&self) -> usize {
+        self.queue.len()
+    }
+
+    /// Returns the number of messages delivered so far.
+    pub fn delivered(&self) -> usize {
+        self.delivered
+    }
+
+    /// Returns the number of messages dropped so far.
+    pub fn dropped(&self) -> usize {
+        self.dropped
+    }
+
+    /// Delivers one message and returns `true`, or returns `false` if there are none.
+    pub fn step(&mut self) -> bool {
+        let index = if self.reorder && !self.queue.is_empty() {
+            self.rng.gen_range(0, self.queue.len())
+        } else {
+            0
+        };
+        let envelope = match self.queue.remove(index) {
+            Some(envelope) => envelope,
+            None => return false,
+        };
+        self.delivered += 1;
+        let outgoing = self.nodes[envelope.to].handle_message(envelope.from, envelope.msg);
+        self.send(envelope.to, outgoing);
+        true
+    }
+
+    /// Delivers messages until every node that hasn't crashed has an output, or no messages are
+    /// left, or `max_steps` messages have been delivered. Returns the number of delivered
+    /// messages.
+    pub fn run(&mut self, max_steps: usize) -> usize {
+        let mut steps = 0;
+        while steps < max_steps && !self.is_terminated() && self.step() {
+            steps += 1;
+        }
+        steps
+    }
+
+    /// Returns `true` if every node that hasn't crashed has an output.
+    pub fn is_terminated(&self) -> bool {
+        self.nodes
+            .iter()
+            .zip(&self.crashed)
+            .all(|(node, crashed)| *crashed || node.output().is_some())
+    }
+
+    /// Queues the messages sent by node `from`.
+    fn send(&mut self, from: usize, outgoing: Vec<Outgoing<N::Message>>) {
+        if self.crashed[from] {
+            return;
+        }
+        for (target, msg) in outgoing {
+            match target {
+                Target::Node(to) => self.enqueue(Envelope { from, to, msg }),
+                Target::All => {
+                    for to in (0..self.nodes.len()).filter(|to| *to != from) {
+                        let msg = msg.clone();
+                        self.enqueue(Envelope { from, to, msg });
+                    }
+                }
+            }
+        }
+    }
+
+    /// Queues the message, unless it is dropped or its recipient has crashed.
+    fn enqueue(&mut self, envelope: Envelope<N::Message>) {
+        if self.crashed[envelope.to] {
+            return;
+        }
+        if self.drop_probability > 0.0 && self.rng.gen_bool(self.drop_probability) {
+            self.dropped += 1;
+            return;
+        }
+        self.queue.push_back(envelope);
+    }
+}
+
+#[cfg(feature = "sessions")]
+/// A committee member that broadcasts its signature share for a message and combines the
+/// signature from the shares it receives.
+#[derive(Clone, Debug)]
+pub struct SigningNode<S> {
+    signer: S,
+    session: SigningSession,
+    msg: Vec<u8>,
+}
+
+#[cfg(feature = "sessions")]
+impl<S: ShareSigner + IndexedShare> SigningNode<S> {
+    /// Creates a node signing `msg` with the given share holder.
+    pub fn new<M: AsRef<[u8]>>(signer: S, pk_set: PublicKeySet, msg: M) -> Self {
+        SigningNode {
+            signer,
+            session: SigningSession::new(pk_set, &msg),
+            msg: msg.as_ref().to_vec(),
+        }
+    }
+
+    /// Returns the node's signing session.
+    pub fn session(&self) -> &SigningSession {
+        &self.session
+    }
+}
+
+#[cfg(feature = "sessions")]
+impl<S: ShareSigner + IndexedShare> Node for SigningNode<S> {
+    /// A participant's share index and signature share.
+    type Message = (usize, SignatureShare);
+    type Output = Signature;
+
+    fn start(&mut self) -> Vec<Outgoing<Self::Message>> {
+        match self.signer.sign_share(&self.msg) {
+            Ok(share) => {
+                // Our own share is valid, so it can't be rejected.
+                let _ = self.session.add_share(self.signer.index(), share.clone());
+                vec![(Target::All, (self.signer.index(), share))]
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn handle_message(&mut self, _from: usize, msg: Self::Message) -> Vec<Outgoing<Self::Message>> {
+        // Invalid and conflicting shares are ignored.
+        let (index, share) = msg;
+        let _ = self.session.add_share(index, share);
+        Vec::new()
+    }
+
+    fn output(&self) -> Option<Signature> {
+        self.session.signature().ok()
+    }
+}
+
+#[cfg(all(test, feature = "sessions"))]
+mod tests {
+    use super::*;
+
+    use crate::signer::InMemoryShare;
+    use crate::SecretKeySet;
+
+    type Committee = Network<SigningNode<InMemoryShare>>;
+
+    fn committee(threshold: usize, size: usize, seed: u64) -> (PublicKeySet, Committee) {
+        let mut rng = ChaChaRng::seed_from_u64(seed);
+        let sk_set = SecretKeySet::random(threshold, &mut rng);
+        let nodes = (0..size)
+            .map(|i| {
+                let signer = InMemoryShare::new(i, sk_set.secret_key_share(i));
+                SigningNode::new(signer, sk_set.public_keys(), "Hello")
+            })
+            .collect();
+        (sk_set.public_keys(), Network::new(nodes, seed))
+    }
+
+    #[test]
+    fn test_signing() {
+        for seed in 0..5 {
+            let (pk_set, network) = committee(2, 5, seed);
+            let mut network = network.with_reordering();
+            network.crash(4);
+            network.run(1000);
+            assert!(network.is_terminated());
+            let outputs = network.outputs();
+            assert!(outputs[4].is_none());
+            for sig in outputs.into_iter().take(4) {
+                assert!(pk_set.public_key().verify(&sig.unwrap(), "Hello"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_deterministic() {
+        let run = |seed| {
+            let (_, network) = committee(1, 4, seed);
+            let mut network = network.with_drop_probability(0.5).with_reordering();
+            network.run(1000);
+            let sessions = network.nodes().iter().map(|node| node.session().shares());
+            let indices: Vec<Vec<usize>> = sessions.map(|s| s.keys().cloned().collect()).collect();
+            (indices, network.dropped())
+        };
+        assert_eq!(run(7), run(7));
+        // Each node keeps its own share, even if all messages to it are dropped.
+        let (indices, dropped) = run(7);
+        assert!(dropped > 0);
+        assert!(indices.iter().all(|i| !i.is_empty()));
+    }
+}
@@ -0,0 +1,294 @@
+//! Implementations of the [`ethereum_ssz`](https://docs.rs/ethereum_ssz) crate's `Encode` and
+//! `Decode`, and the [`tree_hash`](https://docs.rs/tree_hash) crate's `TreeHash`, as used by
+//! Ethereum's consensus layer.
+//!
+//! A `PublicKey` is an SSZ `Bytes48` and a `Signature` a `Bytes96`, i.e. `BLSPubkey` and
+//! `BLSSignature` in the consensus specs: Their SSZ serialization is the compressed encoding that
+//! `to_bytes` returns, so they can be fields of containers that derive the traits. A
+//! `PublicKeySet` is a `List[Bytes48, limit]` of its commitment's coefficients, constant term
+//! first. The list's limit is part of the containing schema rather than of the type, so instead of
+//! `TreeHash` the key set has an inherent `hash_tree_root` that takes it as an argument.
+//!
+//! Invalid encodings, e.g. of points that are not in the group, return a
+//! `DecodeError::BytesInvalid` with the crate's own `DecodeError` message.
+//!
+//! ```
+//! use ssz::{Decode, Encode};
+//! use threshold_crypto::{PublicKeySet, SecretKeySet};
+//! use tree_hash::TreeHash;
+//!
+//! let sk_set = SecretKeySet::random(2, &mut rand::thread_rng());
+//! let pk_set = sk_set.public_keys();
+//! let decoded = PublicKeySet::from_ssz_bytes(&pk_set.as_ssz_bytes()).unwrap();
+//! assert_eq!(pk_set.hash_tree_root(16).unwrap(), decoded.hash_tree_root(16).unwrap());
+//! assert!(pk_set.hash_tree_root(2).is_err());
+//! let pk = pk_set.public_key();
+//! assert_eq!(pk.to_bytes().tree_hash_root(), pk.tree_hash_root());
+//! ```
+
+use ssz::{Decode, DecodeError, Encode};
+use tree_hash::{merkle_root, mix_in_length, Hash256, PackedEncoding, TreeHash, TreeHashType};
+
+use crate::error::{Error, Result};
+use crate::{PublicKey, PublicKeySet, Signature, PK_SIZE, SIG_SIZE};
+
+/// Implements the traits for a type whose `to_bytes` and `from_slice` use a fixed-length array.
+macro_rules! impl_ssz_for_array {
+    ($type:ty, $size:expr) => {
+        impl Encode for $type {
+            fn is_ssz_fixed_len() -> bool {
+                true
+            }
+
+            fn ssz_fixed_len() -> usize {
+                $size
+            }
+
+            fn ssz_bytes_len(&self) -> usize {
+                $size
+            }
+
+            fn ssz_append(&self, buf: &mut Vec<u8>) {
+                buf.extend_from_slice(&self.to_bytes());
+            }
+        }
+
+        impl Decode for $type {
+            fn is_ssz_fixed_len() -> bool {
+                true
+            }
+
+            fn ssz_fixed_len() -> usize {
+                $size
+            }
+
+            fn from_ssz_bytes(bytes: &[u8]) -> std::result::Result<Self, DecodeError> {
+                if bytes.len() != $size {
+                    return Err(DecodeError::InvalidByteLength {
+                        len: bytes.len(),
+                        expected: $size,
+                    });
+                }
+                <$type>::from_slice(bytes).map_err(|err| DecodeError::BytesInvalid(err.to_string()))
+            }
+        }
+
+        impl TreeHash for $type {
+            fn tree_hash_type() -> TreeHashType {
+                TreeHashType::Vector
+            }
+
+            fn tree_hash_packed_encoding(&self) -> PackedEncoding {
+                unreachable!("Vector should never be packed.")
+            }
+
+            fn tree_hash_packing_factor() -> usize {
+                unreachable!("Vector should never be packed.")
+            }
+
+            fn tree_hash_root(&self) -> Hash256 {
+                merkle_root(&self.to_bytes(), 0)
+            }
+        }
+    };
+}
+
+impl_ssz_for_array!(PublicKey, PK_SIZE);
+impl_ssz_for_array!(Signature, SIG_SIZE);
+
+impl Encode for PublicKeySet {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn ssz_bytes_len(&self) -> usize {
+        (self.threshold() + 1) * PK_SIZE
+    }
+
+    fn ssz_append(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(&self.to_bytes());
+    }
+}
+
+impl Decode for PublicKeySet {
+    fn is_ssz_fixed_len() -> bool {
+        false
+    }
+
+    fn from_ssz_bytes(bytes: &[u8]) -> std::result::Result<Self, DecodeError> {
+        PublicKeySet::from_slice(bytes).map_err(|err| DecodeError::BytesInvalid(err.to_string()))
+    }
+}
+
+impl PublicKeySet {
+    /// Returns the key set's SSZ hash tree root as a `List[Bytes48, limit]`.
+    ///
+    /// Returns `Error::LimitExceeded` if the key set has more than `limit` coefficients, i.e. if
+    /// its threshold is at least `limit`.
+    pub fn hash_tree_root(&self, limit: usize) -> Result<Hash256> {
+        let len = self.threshold() + 1;
+        if len > limit {
+            return Err(Error::LimitExceeded(format!(
+                "{} coefficients, but the list limit is {}",
+                len, limit
+            )));
+        }
+        let roots: Vec<u8> = self
+            .to_bytes()
+            .chunks(PK_SIZE)
+            .flat_map(|pk| merkle_root(pk, 0).0)
+            .collect();
+        Ok(mix_in_length(&merkle_root(&roots, limit), len))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ssz_derive::{Decode, Encode};
+    use tree_hash_derive::TreeHash;
+
+    use sha2::{Digest, Sha256};
+
+    use crate::SecretKeySet;
+
+    /// `ValidatorRegistrationV1` from the builder specs.
+    #[derive(Debug, PartialEq, Encode, Decode, TreeHash)]
+    struct ValidatorRegistration {
+        fee_recipient: [u8; 20],
+        gas_limit: u64,
+        timestamp: u64,
+        pubkey: PublicKey,
+    }
+
+    /// The consensus specs' `ForkData`, with the `Version` and `Root` as byte arrays.
+    #[cfg(feature = "ietf")]
+    #[derive(TreeHash)]
+    struct ForkData {
+        current_version: [u8; 4],
+        genesis_validators_root: [u8; 32],
+    }
+
+    /// The consensus specs' `SigningData`.
+    #[cfg(feature = "ietf")]
+    #[derive(TreeHash)]
+    struct SigningData {
+        object_root: [u8; 32],
+        domain: [u8; 32],
+    }
+
+    fn sha256(inputs: &[&[u8]]) -> [u8; 32] {
+        inputs
+            .iter()
+            .fold(Sha256::new(), |hasher, input| hasher.chain_update(input))
+            .finalize()
+            .into()
+    }
+
+    fn from_hex<const N: usize>(hex: &str) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).expect("valid hex");
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_hash_tree_roots() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let pk = pk_set.public_key();
+        let pk_bytes = pk.to_bytes();
+        let mut last_chunk = [0u8; 32];
+        last_chunk[..16].copy_from_slice(&pk_bytes[32..]);
+        let pk_root = sha256(&[&pk_bytes[..32], &last_chunk]);
+        assert_eq!(pk_root, pk.tree_hash_root().0);
+        assert_eq!(Ok(pk), PublicKey::from_ssz_bytes(&pk.as_ssz_bytes()));
+
+        let sig = sk_set.secret_key().sign("msg");
+        let sig_bytes = sig.to_bytes();
+        let left = sha256(&[&sig_bytes[..32], &sig_bytes[32..64]]);
+        let right = sha256(&[&sig_bytes[64..], &[0u8; 32]]);
+        assert_eq!(sha256(&[&left, &right]), sig.tree_hash_root().0);
+        assert_eq!(
+            Ok(sig.clone()),
+            Signature::from_ssz_bytes(&sig.as_ssz_bytes())
+        );
+        assert!(matches!(
+            Signature::from_ssz_bytes(&sig_bytes[1..]),
+            Err(DecodeError::InvalidByteLength { .. })
+        ));
+
+        // A list of two keys with limit 4 has one level of zero padding.
+        let bytes = pk_set.as_ssz_bytes();
+        assert_eq!(pk_set.to_bytes(), bytes);
+        let second = PublicKey::from_ssz_bytes(&bytes[PK_SIZE..]).expect("valid key");
+        let leaves = sha256(&[&pk_root, &second.tree_hash_root().0]);
+        let zero = sha256(&[&[0u8; 32], &[0u8; 32]]);
+        let mut length = [0u8; 32];
+        length[0] = 2;
+        let expected = sha256(&[&sha256(&[&leaves, &zero]), &length]);
+        assert_eq!(Ok(expected), pk_set.hash_tree_root(4).map(|root| root.0));
+        assert!(matches!(
+            pk_set.hash_tree_root(1),
+            Err(Error::LimitExceeded(_))
+        ));
+        assert_eq!(Ok(pk_set), PublicKeySet::from_ssz_bytes(&bytes));
+    }
+
+    /// A mainnet validator registration, as sent to MEV-Boost relays. With `ietf`, the test checks
+    /// the validator's signature over the registration's signing root, so the expected root is the
+    /// one the validator signed.
+    #[test]
+    fn test_validator_registration() {
+        let registration = ValidatorRegistration {
+            fee_recipient: from_hex("be87be8ac54fb2a4ecb8d7935d0fc80f72c28f9f"),
+            gas_limit: 30_000_000,
+            timestamp: 1_688_333_351,
+            pubkey: PublicKey::from_bytes(from_hex(
+                "b56ff6826cfa6b82fc6c2974988b1576fe5c34bd6c672f911e1d3eec1134822581d6d68f68992ad1f945b0c80468d941",
+            ))
+            .expect("valid key"),
+        };
+        let bytes = registration.as_ssz_bytes();
+        assert_eq!(20 + 8 + 8 + PK_SIZE, bytes.len());
+        assert_eq!(
+            Ok(&registration),
+            ValidatorRegistration::from_ssz_bytes(&bytes).as_ref()
+        );
+        let root = registration.tree_hash_root();
+        assert_eq!(
+            from_hex::<32>("7e64850f3207b966c8a984c6c7adea29bcfc802f6f5aca36a894a59821665eab"),
+            root.0
+        );
+
+        // The signing root in the `DOMAIN_APPLICATION_BUILDER` domain, which uses the genesis
+        // fork version and a zero genesis validators root.
+        #[cfg(feature = "ietf")]
+        {
+            let fork_data = ForkData {
+                current_version: [0; 4],
+                genesis_validators_root: [0; 32],
+            };
+            let mut domain = [0u8; 32];
+            domain[..4].copy_from_slice(&[0, 0, 0, 1]);
+            domain[4..].copy_from_slice(&fork_data.tree_hash_root()[..28]);
+            let signing_root = SigningData {
+                object_root: root.0,
+                domain,
+            }
+            .tree_hash_root();
+            let sig = Signature::from_bytes(from_hex(
+                "8b42028d248f5a2fd41ab425408470ffde1d941ee83db3d9bde583feb22413608673dc27930383893410ef05e52ed8cf0e0291d8ed111189a065f9598176d1c51cabeaba8f628b2f92626bb58d2068292eb7682673a31473d0cdbe278e67c723",
+            ))
+            .expect("valid signature");
+            assert!(crate::eth2::verify(
+                &registration.pubkey,
+                signing_root,
+                &sig
+            ));
+        }
+    }
+}
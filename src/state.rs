@@ -0,0 +1,114 @@
+//! Saving and restoring the state of long-running protocols.
+//!
+//! A node that crashes in the middle of a protocol can resume it without restarting it for
+//! everyone, if it saved its state. Protocol state machines implement [`Resumable`]:
+//! `save_state` returns a [`SavedState`], which is serde-serializable with any format, and
+//! `restore_state` turns it back into the state machine.
+//!
+//! Saved states carry a format version and the protocol's name, so old or mismatched states are
+//! rejected instead of being misinterpreted. Restoring revalidates the state, e.g. shares are
+//! verified again, so a tampered file can't inject invalid data. Secret parts of a state are
+//! wrapped in [`SerdeSecret`](../serde_impl/struct.SerdeSecret.html), and the file must be
+//! protected accordingly.
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// The version of the saved state format.
+pub const STATE_VERSION: u32 = 1;
+
+/// A protocol's saved state, with its format version and protocol name.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SavedState<T> {
+    /// The format version, `STATE_VERSION` when saved by this version of the crate.
+    pub version: u32,
+    /// The name of the protocol, as in `Resumable::PROTOCOL`.
+    pub protocol: String,
+    /// The state itself.
+    pub state: T,
+}
+
+/// A protocol state machine that can be saved and restored.
+pub trait Resumable: Sized {
+    /// The name of the protocol, identifying its saved states.
+    const PROTOCOL: &'static str;
+
+    /// The serializable representation of the state.
+    type State: Serialize + DeserializeOwned;
+
+    /// Returns the current state.
+    fn to_state(&self) -> Self::State;
+
+    /// Reconstructs the state machine, or returns an error if the state is invalid.
+    fn from_state(state: Self::State) -> Result<Self>;
+
+    /// Returns the current state, to be serialized and restored with `restore_state`.
+    fn save_state(&self) -> SavedState<Self::State> {
+        SavedState {
+            version: STATE_VERSION,
+            protocol: Self::PROTOCOL.to_string(),
+            state: self.to_state(),
+        }
+    }
+
+    /// Restores the state machine from a saved state.
+    ///
+    /// Returns `Error::InvalidState` if the state has a different version, belongs to a
+    /// different protocol, or is invalid.
+    fn restore_state(saved: SavedState<Self::State>) -> Result<Self> {
+        if saved.version != STATE_VERSION {
+            let msg = format!("unsupported version {}", saved.version);
+            return Err(Error::InvalidState(msg));
+        }
+        if saved.protocol != Self::PROTOCOL {
+            let msg = format!("expected {}, found {}", Self::PROTOCOL, saved.protocol);
+            return Err(Error::InvalidState(msg));
+        }
+        Self::from_state(saved.state)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A counter that must stay below ten.
+    #[derive(Debug, PartialEq)]
+    struct Counter(u8);
+
+    impl Resumable for Counter {
+        const PROTOCOL: &'static str = "counter";
+        type State = u8;
+
+        fn to_state(&self) -> u8 {
+            self.0
+        }
+
+        fn from_state(state: u8) -> Result<Self> {
+            match state {
+                0..=9 => Ok(Counter(state)),
+                _ => Err(Error::InvalidState("too large".to_string())),
+            }
+        }
+    }
+
+    #[test]
+    fn test_resumable() {
+        let saved = Counter(3).save_state();
+        let bytes = bincode::serialize(&saved).expect("serialize state");
+        let restored = bincode::deserialize(&bytes).expect("deserialize state");
+        assert_eq!(Ok(Counter(3)), Counter::restore_state(restored));
+
+        let mut future = saved.clone();
+        future.version += 1;
+        assert!(Counter::restore_state(future).is_err());
+        let mut other = saved.clone();
+        other.protocol = "other".to_string();
+        assert!(Counter::restore_state(other).is_err());
+        let mut invalid = saved;
+        invalid.state = 10;
+        assert!(Counter::restore_state(invalid).is_err());
+    }
+}
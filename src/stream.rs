@@ -0,0 +1,367 @@
+//! Streaming hybrid encryption.
+//!
+//! An [`EncryptorWriter`] encrypts data of any size to a `PublicKey` as it is written, and a
+//! [`DecryptorReader`] decrypts it as it is read, each keeping only one chunk of `CHUNK_SIZE`
+//! bytes in memory. Like a [`HybridCiphertext`](../kem/struct.HybridCiphertext.html), the stream
+//! starts with a fresh symmetric key encrypted to the threshold key, so it can be decrypted with
+//! the secret key, or with `threshold + 1` decryption shares of the stream's `kem`.
+//!
+//! After the header, the data is split into chunks, each encrypted with its own ChaCha20 stream
+//! and authenticated with a SHA3-256 MAC over the header, the chunk's index and a flag marking the
+//! last chunk. Reordered, modified or truncated streams are therefore detected, and reading fails
+//! with `io::ErrorKind::InvalidData`. Data is returned chunk by chunk, after its chunk has been
+//! authenticated, but a stream is only complete if reading reaches the end without an error.
+//!
+//! ```
+//! use std::io::{Read, Write};
+//!
+//! use threshold_crypto::stream::{EncryptedStream, EncryptorWriter};
+//! use threshold_crypto::SecretKey;
+//!
+//! let sk = SecretKey::random();
+//! let mut writer = EncryptorWriter::new(&sk.public_key(), Vec::new()).unwrap();
+//! writer.write_all(&[7u8; 200_000]).unwrap();
+//! let encrypted = writer.finish().unwrap();
+//!
+//! let stream = EncryptedStream::new(&encrypted[..]).unwrap();
+//! let mut reader = stream.decrypt(&sk).unwrap();
+//! let mut decrypted = Vec::new();
+//! reader.read_to_end(&mut decrypted).unwrap();
+//! assert_eq!(vec![7u8; 200_000], decrypted);
+//! ```
+
+use std::convert::TryInto;
+use std::io::{self, Read, Write};
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+use crate::error::Result;
+use crate::kem::{ct_eq, encapsulate, DemKeys, KEY_SIZE};
+use crate::{
+    Ciphertext, DecryptionShare, IntoFr, PublicKey, PublicKeySet, SecretKey, PK_SIZE, SIG_SIZE,
+};
+
+/// The number of plaintext bytes in each chunk except the last.
+pub const CHUNK_SIZE: usize = 64 * 1024;
+
+/// The first bytes of an encrypted stream.
+const STREAM_MAGIC: &[u8; 4] = b"TCSE";
+/// The current version of the stream encoding.
+const STREAM_VERSION: u8 = 1;
+/// The size of the encapsulated key's encoding.
+const KEM_SIZE: usize = PK_SIZE + SIG_SIZE + KEY_SIZE;
+/// The size of a chunk's header: the last-chunk flag and the four-byte length.
+const CHUNK_HEADER_SIZE: usize = 5;
+
+/// Encrypts everything written to it, and writes the encrypted stream to the inner writer.
+///
+/// The stream must be completed with `finish`: Otherwise the last chunk is missing, and
+/// decryption fails.
+pub struct EncryptorWriter<W: Write> {
+    inner: W,
+    header: Vec<u8>,
+    keys: DemKeys,
+    buf: Zeroizing<Vec<u8>>,
+    index: u64,
+}
+
+impl<W: Write> EncryptorWriter<W> {
+    /// Writes the header of a stream encrypted to the public key, using the OS random number
+    /// generator.
+    pub fn new(pk: &PublicKey, inner: W) -> io::Result<Self> {
+        EncryptorWriter::with_rng(pk, &mut OsRng, inner)
+    }
+
+    /// Writes the header of a stream encrypted to the public key.
+    pub fn with_rng<R: RngCore>(pk: &PublicKey, rng: &mut R, mut inner: W) -> io::Result<Self> {
+        let (kem, keys) = encapsulate(pk, rng);
+        let mut header = STREAM_MAGIC.to_vec();
+        header.push(STREAM_VERSION);
+        header.extend(kem.to_bytes());
+        inner.write_all(&header)?;
+        Ok(EncryptorWriter {
+            inner,
+            header,
+            keys,
+            buf: Zeroizing::new(Vec::with_capacity(CHUNK_SIZE)),
+            index: 0,
+        })
+    }
+
+    /// Writes the last chunk, and returns the inner writer.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.write_chunk(true)?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    /// Encrypts and writes the buffered data as the next chunk.
+    fn write_chunk(&mut self, last: bool) -> io::Result<()> {
+        let mut chunk_header = [0u8; CHUNK_HEADER_SIZE];
+        chunk_header[0] = last as u8;
+        chunk_header[1..].copy_from_slice(&(self.buf.len() as u32).to_be_bytes());
+        let mut body = std::mem::take(&mut *self.buf);
+        self.keys.apply_keystream(self.index, &mut body);
+        let tag = chunk_tag(&self.keys, &self.header, self.index, &chunk_header, &body);
+        self.inner.write_all(&chunk_header)?;
+        self.inner.write_all(&body)?;
+        self.inner.write_all(&tag)?;
+        body.clear();
+        *self.buf = body;
+        self.index += 1;
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for EncryptorWriter<W> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if data.is_empty() {
+            return Ok(0);
+        }
+        // A full chunk is only written once more data arrives, since the last one is marked.
+        if self.buf.len() == CHUNK_SIZE {
+            self.write_chunk(false)?;
+        }
+        let len = data.len().min(CHUNK_SIZE - self.buf.len());
+        self.buf.extend_from_slice(&data[..len]);
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// The header of an encrypted stream, with the encapsulated key.
+pub struct EncryptedStream<R: Read> {
+    inner: R,
+    header: Vec<u8>,
+    kem: Ciphertext,
+}
+
+impl<R: Read> EncryptedStream<R> {
+    /// Reads the stream's header.
+    pub fn new(mut inner: R) -> io::Result<Self> {
+        let mut header = vec![0u8; STREAM_MAGIC.len() + 1 + KEM_SIZE];
+        inner.read_exact(&mut header)?;
+        if &header[..4] != STREAM_MAGIC || header[4] != STREAM_VERSION {
+            return Err(invalid_data("not an encrypted stream"));
+        }
+        let kem = Ciphertext::from_slice(&header[5..]).map_err(|_| invalid_data("invalid key"))?;
+        Ok(EncryptedStream { inner, header, kem })
+    }
+
+    /// Returns the encapsulated key, for creating and verifying decryption shares.
+    pub fn kem(&self) -> &Ciphertext {
+        &self.kem
+    }
+
+    /// Returns a reader of the decrypted stream, or `None` if the encapsulated key isn't valid.
+    pub fn decrypt(self, sk: &SecretKey) -> Option<DecryptorReader<R>> {
+        let key = Zeroizing::new(sk.decrypt(&self.kem)?);
+        self.reader(&key)
+    }
+
+    /// Combines the decryption shares of the encapsulated key, and returns a reader of the
+    /// decrypted stream.
+    ///
+    /// Like `PublicKeySet::decrypt`, this doesn't verify the shares: If any of them is invalid,
+    /// reading from the stream fails.
+    pub fn decrypt_with_shares<'a, T, I>(
+        self,
+        pk_set: &PublicKeySet,
+        shares: I,
+    ) -> Result<DecryptorReader<R>>
+    where
+        I: IntoIterator<Item = (T, &'a DecryptionShare)>,
+        T: IntoFr,
+    {
+        let key = Zeroizing::new(pk_set.decrypt(shares, &self.kem)?);
+        Ok(self
+            .reader(&key)
+            .expect("the encapsulated key has the right size"))
+    }
+
+    fn reader(self, key: &[u8]) -> Option<DecryptorReader<R>> {
+        let key: &[u8; KEY_SIZE] = key.try_into().ok()?;
+        Some(DecryptorReader {
+            inner: self.inner,
+            header: self.header,
+            keys: DemKeys::new(key),
+            buf: Zeroizing::new(Vec::new()),
+            pos: 0,
+            index: 0,
+            done: false,
+        })
+    }
+}
+
+/// Decrypts an encrypted stream as it is read.
+pub struct DecryptorReader<R: Read> {
+    inner: R,
+    header: Vec<u8>,
+    keys: DemKeys,
+    buf: Zeroizing<Vec<u8>>,
+    pos: usize,
+    index: u64,
+    done: bool,
+}
+
+impl<R: Read> DecryptorReader<R> {
+    /// Reads, authenticates and decrypts the next chunk into the buffer.
+    fn read_chunk(&mut self) -> io::Result<()> {
+        let mut chunk_header = [0u8; CHUNK_HEADER_SIZE];
+        self.inner
+            .read_exact(&mut chunk_header)
+            .map_err(|_| invalid_data("truncated stream"))?;
+        let last = match chunk_header[0] {
+            0 => false,
+            1 => true,
+            _ => return Err(invalid_data("invalid chunk header")),
+        };
+        let len = u32::from_be_bytes(chunk_header[1..].try_into().expect("four bytes")) as usize;
+        if len > CHUNK_SIZE {
+            return Err(invalid_data("invalid chunk header"));
+        }
+        let mut body = std::mem::take(&mut *self.buf);
+        body.resize(len, 0);
+        let mut tag = [0u8; KEY_SIZE];
+        self.inner
+            .read_exact(&mut body)
+            .and_then(|()| self.inner.read_exact(&mut tag))
+            .map_err(|_| invalid_data("truncated stream"))?;
+        let expected = chunk_tag(&self.keys, &self.header, self.index, &chunk_header, &body);
+        if !ct_eq(&expected, &tag) {
+            return Err(invalid_data("chunk authentication failed"));
+        }
+        if last && self.inner.read(&mut [0u8])? != 0 {
+            return Err(invalid_data("data after the last chunk"));
+        }
+        self.keys.apply_keystream(self.index, &mut body);
+        *self.buf = body;
+        self.pos = 0;
+        self.index += 1;
+        self.done = last;
+        Ok(())
+    }
+}
+
+impl<R: Read> Read for DecryptorReader<R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        while self.pos == self.buf.len() {
+            if self.done || out.is_empty() {
+                return Ok(0);
+            }
+            self.read_chunk()?;
+        }
+        let len = out.len().min(self.buf.len() - self.pos);
+        out[..len].copy_from_slice(&self.buf[self.pos..(self.pos + len)]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+/// Returns the MAC tag of a chunk.
+fn chunk_tag(
+    keys: &DemKeys,
+    header: &[u8],
+    index: u64,
+    chunk_header: &[u8],
+    body: &[u8],
+) -> [u8; KEY_SIZE] {
+    keys.tag(&[header, &index.to_be_bytes(), chunk_header, body])
+}
+
+fn invalid_data(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::BTreeMap;
+
+    use crate::SecretKeySet;
+
+    fn encrypt(pk: &PublicKey, data: &[u8]) -> Vec<u8> {
+        let mut writer = EncryptorWriter::new(pk, Vec::new()).expect("writing to a vector");
+        // Write in uneven pieces, to cross chunk boundaries.
+        for piece in data.chunks(10_007) {
+            writer.write_all(piece).expect("writing to a vector");
+        }
+        writer.finish().expect("writing to a vector")
+    }
+
+    fn decrypt(sk: &SecretKey, encrypted: &[u8]) -> io::Result<Vec<u8>> {
+        let stream = EncryptedStream::new(encrypted)?;
+        let mut reader = stream.decrypt(sk).expect("valid key");
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted)?;
+        Ok(decrypted)
+    }
+
+    #[test]
+    fn test_stream_round_trip() {
+        let sk = SecretKey::random();
+        let pk = sk.public_key();
+        for &len in &[0, 1, CHUNK_SIZE, CHUNK_SIZE + 1, 3 * CHUNK_SIZE + 17] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let encrypted = encrypt(&pk, &data);
+            assert_eq!(data, decrypt(&sk, &encrypted).expect("valid stream"));
+        }
+    }
+
+    #[test]
+    fn test_stream_tampering() {
+        let sk = SecretKey::random();
+        let data = vec![3u8; 2 * CHUNK_SIZE + 5];
+        let encrypted = encrypt(&sk.public_key(), &data);
+        let first_chunk = STREAM_MAGIC.len() + 1 + KEM_SIZE;
+        let chunk_len = CHUNK_HEADER_SIZE + CHUNK_SIZE + KEY_SIZE;
+
+        let mut modified = encrypted.clone();
+        modified[first_chunk + 100] ^= 1;
+        assert!(decrypt(&sk, &modified).is_err());
+
+        // Dropping the last chunk, or swapping two chunks, is detected.
+        let truncated = &encrypted[..(first_chunk + 2 * chunk_len)];
+        assert!(decrypt(&sk, truncated).is_err());
+        let mut swapped = encrypted[..first_chunk].to_vec();
+        swapped.extend(&encrypted[(first_chunk + chunk_len)..(first_chunk + 2 * chunk_len)]);
+        swapped.extend(&encrypted[first_chunk..(first_chunk + chunk_len)]);
+        swapped.extend(&encrypted[(first_chunk + 2 * chunk_len)..]);
+        assert!(decrypt(&sk, &swapped).is_err());
+        let mut extended = encrypted.clone();
+        extended.push(0);
+        assert!(decrypt(&sk, &extended).is_err());
+
+        let other_sk = SecretKey::random();
+        assert!(decrypt(&other_sk, &encrypted).is_err());
+        assert!(EncryptedStream::new(&encrypted[1..]).is_err());
+    }
+
+    #[test]
+    fn test_stream_threshold_decryption() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let data = vec![9u8; CHUNK_SIZE + 1];
+        let encrypted = encrypt(&pk_set.public_key(), &data);
+        let stream = EncryptedStream::new(&encrypted[..]).expect("valid header");
+        let shares: BTreeMap<_, _> = (0..2)
+            .map(|i| {
+                let share = sk_set.secret_key_share(i).decrypt_share(stream.kem());
+                (i, share.expect("valid key"))
+            })
+            .collect();
+        let mut reader = stream
+            .decrypt_with_shares(&pk_set, &shares)
+            .expect("enough shares");
+        let mut decrypted = Vec::new();
+        reader.read_to_end(&mut decrypted).expect("valid stream");
+        assert_eq!(data, decrypted);
+    }
+}
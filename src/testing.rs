@@ -0,0 +1,705 @@
+//! Generators of random and adversarial values, for property-testing protocol logic.
+//!
+//! Every generator takes an `Rng`, so it can be driven by any property-testing framework: With
+//! `proptest`, for example, a strategy for key sets is
+//! `any::<[u8; 32]>().prop_map(|seed| key_set(&mut ChaChaRng::from_seed(seed), 5))`, and
+//! shrinking happens on the seed. The values are realistic: Keys, shares and signatures belong
+//! together, and ciphertexts are valid encryptions.
+//!
+//! For testing robustness, [`garbage_signature_share`], [`bad_dealer`] and [`EquivocatingSigner`]
+//! play malicious parties: They produce artifacts that are structurally valid, i.e. correctly
+//! encoded group elements and polynomials of the right degree, but fail cryptographic checks.
+//!
+//! For testing decoders, [`mutate`] corrupts valid encodings, and [`invalid_g1_encodings`],
+//! [`invalid_g2_encodings`] and [`invalid_fr_encodings`] return representations that are
+//! well-formed in size but must be rejected. With mock cryptography, every encoding is valid, so
+//! these are not available.
+//!
+//! The feature also implements [`arbitrary`](https://docs.rs/arbitrary)'s `Arbitrary` for keys,
+//! shares, signatures, key sets and ciphertexts, for fuzzing, and provides
+//! [`proptest`](https://docs.rs/proptest) strategies for them and for corrupted encodings in
+//! [`strategy`]. Both sample from an RNG seeded with 32 bytes of input, so values are always
+//! realistic, but shrinking only simplifies the seed.
+//!
+//! This module is only available with the `testing` feature.
+
+#[cfg(feature = "sessions")]
+use std::cell::Cell;
+
+#[cfg(feature = "testing")]
+use arbitrary::{Arbitrary, Unstructured};
+use group::CurveAffine;
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+use group::EncodedPoint;
+use rand::distributions::{Distribution, Standard};
+use rand::Rng;
+#[cfg(feature = "testing")]
+use rand::SeedableRng;
+#[cfg(feature = "testing")]
+use rand_chacha::ChaChaRng;
+
+#[cfg(feature = "sessions")]
+use crate::error::Result;
+#[cfg(feature = "dkg")]
+use crate::poly::{BivarCommitment, BivarPoly};
+use crate::poly::{Commitment, Poly};
+#[cfg(feature = "sessions")]
+use crate::signer::{InMemoryShare, IndexedShare, ShareHolder, ShareSigner};
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{
+    G1Affine, G2Affine, PublicKey, PublicKeyShare, SecretKey, SecretKeySet, SecretKeyShare,
+    SignatureShare, PK_SIZE, SIG_SIZE,
+};
+#[cfg(feature = "testing")]
+use crate::{PublicKeySet, Signature};
+
+/// The greatest threshold of an arbitrary `SecretKeySet` or `PublicKeySet`.
+#[cfg(feature = "testing")]
+const MAX_ARBITRARY_THRESHOLD: usize = 8;
+
+/// The greatest length of the plaintext of an arbitrary `Ciphertext`.
+#[cfg(all(feature = "testing", feature = "encryption"))]
+const MAX_ARBITRARY_MESSAGE_LEN: usize = 64;
+
+/// Returns a random public key.
+impl Distribution<PublicKey> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> PublicKey {
+        rng.gen::<SecretKey>().public_key()
+    }
+}
+
+/// Returns a random public key share that doesn't belong to any key set.
+impl Distribution<PublicKeyShare> for Standard {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> PublicKeyShare {
+        PublicKeyShare(rng.gen())
+    }
+}
+
+/// Implements `Arbitrary` for a type by sampling it from an RNG seeded with 32 bytes of input.
+#[cfg(feature = "testing")]
+macro_rules! impl_arbitrary_by_seed {
+    ($type:ty) => {
+        impl<'a> Arbitrary<'a> for $type {
+            fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+                Ok(ChaChaRng::from_seed(u.arbitrary()?).gen())
+            }
+
+            fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+                (32, Some(32))
+            }
+        }
+    };
+}
+
+#[cfg(feature = "testing")]
+impl_arbitrary_by_seed!(SecretKey);
+#[cfg(feature = "testing")]
+impl_arbitrary_by_seed!(SecretKeyShare);
+#[cfg(feature = "testing")]
+impl_arbitrary_by_seed!(PublicKey);
+#[cfg(feature = "testing")]
+impl_arbitrary_by_seed!(PublicKeyShare);
+#[cfg(feature = "testing")]
+impl_arbitrary_by_seed!(Signature);
+#[cfg(feature = "testing")]
+impl_arbitrary_by_seed!(SignatureShare);
+#[cfg(all(feature = "testing", feature = "encryption"))]
+impl_arbitrary_by_seed!(DecryptionShare);
+
+/// Returns a random key set with a threshold of at most 8.
+#[cfg(feature = "testing")]
+impl<'a> Arbitrary<'a> for SecretKeySet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let threshold = u.int_in_range(0..=MAX_ARBITRARY_THRESHOLD)?;
+        let mut rng = ChaChaRng::from_seed(u.arbitrary()?);
+        Ok(SecretKeySet::random(threshold, &mut rng))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (33, Some(33))
+    }
+}
+
+/// Returns the public keys of a random key set with a threshold of at most 8.
+#[cfg(feature = "testing")]
+impl<'a> Arbitrary<'a> for PublicKeySet {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(SecretKeySet::arbitrary(u)?.public_keys())
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        SecretKeySet::size_hint(depth)
+    }
+}
+
+/// Returns a valid encryption of a message of at most 64 bytes to a random key.
+#[cfg(all(feature = "testing", feature = "encryption"))]
+impl<'a> Arbitrary<'a> for Ciphertext {
+    fn arbitrary(u: &mut Unstructured<'a>) -> arbitrary::Result<Self> {
+        let mut rng = ChaChaRng::from_seed(u.arbitrary()?);
+        let len = u.int_in_range(0..=MAX_ARBITRARY_MESSAGE_LEN)?;
+        let msg = u.bytes(len)?;
+        Ok(rng
+            .gen::<SecretKey>()
+            .public_key()
+            .encrypt_with_rng(&mut rng, msg))
+    }
+}
+
+/// Returns a random polynomial of degree at most `max_degree`.
+pub fn poly<R: Rng>(rng: &mut R, max_degree: usize) -> Poly {
+    let degree = rng.gen_range(0, max_degree + 1);
+    Poly::random(degree, rng)
+}
+
+/// Returns the commitment to a random polynomial of degree at most `max_degree`.
+pub fn commitment<R: Rng>(rng: &mut R, max_degree: usize) -> Commitment {
+    poly(rng, max_degree).commitment()
+}
+
+/// Returns a random key set with a threshold of at most `max_threshold`.
+pub fn key_set<R: Rng>(rng: &mut R, max_threshold: usize) -> SecretKeySet {
+    SecretKeySet::from(poly(rng, max_threshold))
+}
+
+/// Returns a random message of at most `max_len` bytes.
+pub fn message<R: Rng>(rng: &mut R, max_len: usize) -> Vec<u8> {
+    let len = rng.gen_range(0, max_len + 1);
+    rng.sample_iter(&Standard).take(len).collect()
+}
+
+/// Returns a valid encryption of a random message of at most `max_len` bytes, and the key that
+/// decrypts it.
+#[cfg(feature = "encryption")]
+pub fn ciphertext<R: Rng>(rng: &mut R, max_len: usize) -> (Ciphertext, SecretKey) {
+    let sk: SecretKey = rng.gen();
+    let msg = message(rng, max_len);
+    (sk.public_key().encrypt_with_rng(rng, msg), sk)
+}
+
+/// Returns a signature share of the message made with a random key. It is a valid element of `G2`,
+/// but fails verification against any key set's public key shares.
+pub fn garbage_signature_share<R: Rng, M: AsRef<[u8]>>(rng: &mut R, msg: M) -> SignatureShare {
+    rng.gen::<SecretKeyShare>().sign(msg)
+}
+
+#[cfg(feature = "encryption")]
+/// Returns a decryption share of the ciphertext made with a random key. It is a valid element of
+/// `G1`, but fails verification against any key set's public key shares.
+pub fn garbage_decryption_share<R: Rng>(rng: &mut R, ct: &Ciphertext) -> DecryptionShare {
+    rng.gen::<SecretKeyShare>().decrypt_share_no_verify(ct)
+}
+
+#[cfg(feature = "dkg")]
+/// A dealer's contribution to distributed key generation.
+#[derive(Clone, Debug)]
+pub struct Deal {
+    /// The commitment to the dealer's bivariate polynomial.
+    pub commitment: BivarCommitment,
+    /// The rows of the polynomial sent to the nodes: Node `i` receives row `i + 1`.
+    pub rows: Vec<Poly>,
+}
+
+#[cfg(feature = "dkg")]
+/// Returns a deal of a random polynomial of the given degree to `num_nodes` nodes, in which the
+/// rows sent to the `cheated` nodes don't match the commitment.
+///
+/// The wrong rows still have the right degree, so only the check against
+/// `commitment.row(i + 1)` reveals them.
+pub fn bad_dealer<R: Rng>(rng: &mut R, degree: usize, num_nodes: usize, cheated: &[usize]) -> Deal {
+    let bi_poly = BivarPoly::random(degree, rng);
+    let rows = (0..num_nodes)
+        .map(|i| {
+            let row = bi_poly.row(i + 1);
+            if cheated.contains(&i) {
+                row + 1u64
+            } else {
+                row
+            }
+        })
+        .collect();
+    Deal {
+        commitment: bi_poly.commitment(),
+        rows,
+    }
+}
+
+#[cfg(feature = "sessions")]
+/// A share holder that alternates between signing with its real share and with a random key, so
+/// that different recipients of its signature shares see conflicting answers.
+///
+/// The first signature share is valid, the second is not, and so on.
+#[derive(Debug)]
+pub struct EquivocatingSigner {
+    honest: InMemoryShare,
+    fake: SecretKeyShare,
+    calls: Cell<usize>,
+}
+
+#[cfg(feature = "sessions")]
+impl EquivocatingSigner {
+    /// Creates an equivocating signer posing as the given share holder.
+    pub fn new<R: Rng>(rng: &mut R, honest: InMemoryShare) -> Self {
+        EquivocatingSigner {
+            honest,
+            fake: rng.gen(),
+            calls: Cell::new(0),
+        }
+    }
+}
+
+#[cfg(feature = "sessions")]
+impl ShareHolder for EquivocatingSigner {
+    fn public_key_share(&self) -> PublicKeyShare {
+        self.honest.public_key_share()
+    }
+}
+
+#[cfg(feature = "sessions")]
+impl IndexedShare for EquivocatingSigner {
+    fn index(&self) -> usize {
+        self.honest.index()
+    }
+}
+
+#[cfg(feature = "sessions")]
+impl ShareSigner for EquivocatingSigner {
+    fn sign_g2(&self, hash: G2Affine) -> Result<SignatureShare> {
+        let calls = self.calls.get();
+        self.calls.set(calls + 1);
+        if calls.is_multiple_of(2) {
+            self.honest.sign_g2(hash)
+        } else {
+            Ok(self.fake.sign_g2(hash))
+        }
+    }
+}
+
+/// Returns a copy of the encoding with a random corruption: a flipped bit, a truncation, extra
+/// bytes, or all bytes replaced. The result is guaranteed to differ from `bytes`.
+pub fn mutate<R: Rng>(rng: &mut R, bytes: &[u8]) -> Vec<u8> {
+    let mut result = bytes.to_vec();
+    match rng.gen_range(0, 4) {
+        0 if !bytes.is_empty() => {
+            let bit = rng.gen_range(0, 8 * bytes.len());
+            result[bit / 8] ^= 1 << (bit % 8);
+        }
+        1 if !bytes.is_empty() => result.truncate(rng.gen_range(0, bytes.len())),
+        2 if !bytes.is_empty() => {
+            for b in &mut result {
+                *b = rng.gen();
+            }
+            if result == bytes {
+                result[0] ^= 1;
+            }
+        }
+        _ => {
+            let extra = rng.gen_range(1, 33);
+            result.extend((0..extra).map(|_| rng.gen::<u8>()));
+        }
+    }
+    result
+}
+
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+/// Returns compressed `G1` encodings that must be rejected: without the compression flag, with the
+/// infinity flag but a nonzero coordinate, with a coordinate exceeding the field modulus, and with
+/// a coordinate that is not on the curve.
+pub fn invalid_g1_encodings() -> Vec<[u8; PK_SIZE]> {
+    let mut encodings = flag_encodings::<PK_SIZE>();
+    encodings.push(off_curve::<G1Affine, PK_SIZE>());
+    encodings
+}
+
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+/// Returns compressed `G2` encodings that must be rejected, analogous to `invalid_g1_encodings`.
+pub fn invalid_g2_encodings() -> Vec<[u8; SIG_SIZE]> {
+    let mut encodings = flag_encodings::<SIG_SIZE>();
+    encodings.push(off_curve::<G2Affine, SIG_SIZE>());
+    encodings
+}
+
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+/// Returns big-endian encodings of integers that are not reduced modulo the field order `r`.
+pub fn invalid_fr_encodings() -> Vec<[u8; 32]> {
+    // r = 0x73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001
+    let mut r = [0u8; 32];
+    let r_hex = "73eda753299d7d483339d80809a1d80553bda402fffe5bfeffffffff00000001";
+    for (i, byte) in r.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&r_hex[2 * i..2 * i + 2], 16).expect("valid hex");
+    }
+    let mut r_plus_1 = r;
+    r_plus_1[31] += 1;
+    vec![r, r_plus_1, [0xff; 32]]
+}
+
+/// Returns the identity elements' compressed encodings. They are valid, but degenerate: The
+/// identity is not a secure public key or signature. With the `strict` feature, decoding them as
+/// a `PublicKey` or `Signature` fails.
+pub fn identity_encodings() -> ([u8; PK_SIZE], [u8; SIG_SIZE]) {
+    let mut pk = [0u8; PK_SIZE];
+    pk.copy_from_slice(G1Affine::zero().into_compressed().as_ref());
+    let mut sig = [0u8; SIG_SIZE];
+    sig.copy_from_slice(G2Affine::zero().into_compressed().as_ref());
+    (pk, sig)
+}
+
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+/// Returns the encodings with invalid flags or an unreduced coordinate.
+fn flag_encodings<const N: usize>() -> Vec<[u8; N]> {
+    let mut uncompressed = [0u8; N];
+    uncompressed[N - 1] = 1;
+    let mut bad_infinity = [0u8; N];
+    bad_infinity[0] = 0xc0;
+    bad_infinity[N - 1] = 1;
+    let mut unreduced = [0xff; N];
+    unreduced[0] = 0x9f;
+    vec![uncompressed, bad_infinity, unreduced]
+}
+
+#[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+/// Returns the first encoding with compression flag and small `x` coordinate that isn't a valid
+/// point in the prime-order subgroup.
+fn off_curve<C: CurveAffine, const N: usize>() -> [u8; N] {
+    (1u8..)
+        .map(|x| {
+            let mut bytes = [0u8; N];
+            bytes[0] = 0x80;
+            bytes[N - 1] = x;
+            bytes
+        })
+        .find(|bytes| {
+            let mut compressed = C::Compressed::empty();
+            compressed.as_mut().copy_from_slice(bytes);
+            compressed.into_affine().is_err()
+        })
+        .expect("some small coordinate is not on the curve")
+}
+
+/// `proptest` strategies for keys, shares, signatures, key sets, ciphertexts and corrupted
+/// encodings.
+///
+/// ```
+/// use proptest::prelude::*;
+/// use threshold_crypto::testing::strategy;
+/// use threshold_crypto::PublicKey;
+///
+/// proptest! {
+///     #![proptest_config(ProptestConfig::with_cases(8))]
+///     fn corrupted_keys_differ((pk, bytes) in strategy::corrupted_public_key()) {
+///         prop_assert_ne!(Ok(pk), PublicKey::from_slice(&bytes));
+///     }
+/// }
+/// # corrupted_keys_differ();
+/// ```
+#[cfg(feature = "testing")]
+pub mod strategy {
+    use proptest::collection::vec;
+    use proptest::prelude::*;
+    use rand::distributions::{Distribution, Standard};
+    use rand::{Rng, SeedableRng};
+    use rand_chacha::ChaChaRng;
+
+    #[cfg(feature = "encryption")]
+    use crate::Ciphertext;
+    use crate::{PublicKey, SecretKey, SecretKeySet, Signature};
+    #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+    use crate::{PK_SIZE, SIG_SIZE};
+
+    /// Returns a strategy for values sampled from an RNG with a random seed.
+    pub fn sampled<T: std::fmt::Debug>() -> impl Strategy<Value = T>
+    where
+        Standard: Distribution<T>,
+    {
+        any::<[u8; 32]>().prop_map(|seed| ChaChaRng::from_seed(seed).gen())
+    }
+
+    /// Returns a strategy for random secret keys.
+    pub fn secret_key() -> impl Strategy<Value = SecretKey> {
+        sampled()
+    }
+
+    /// Returns a strategy for random public keys.
+    pub fn public_key() -> impl Strategy<Value = PublicKey> {
+        sampled()
+    }
+
+    /// Returns a strategy for signatures by random keys on random messages.
+    pub fn signature() -> impl Strategy<Value = Signature> {
+        sampled()
+    }
+
+    /// Returns a strategy for random key sets with a threshold of at most `max_threshold`.
+    pub fn key_set(max_threshold: usize) -> impl Strategy<Value = SecretKeySet> {
+        (0..=max_threshold, any::<[u8; 32]>()).prop_map(|(threshold, seed)| {
+            SecretKeySet::random(threshold, &mut ChaChaRng::from_seed(seed))
+        })
+    }
+
+    /// Returns a strategy for messages of at most `max_len` bytes.
+    pub fn message(max_len: usize) -> impl Strategy<Value = Vec<u8>> {
+        vec(any::<u8>(), 0..=max_len)
+    }
+
+    /// Returns a strategy for valid encryptions of messages of at most `max_len` bytes, and the
+    /// keys that decrypt them.
+    #[cfg(feature = "encryption")]
+    pub fn ciphertext(max_len: usize) -> impl Strategy<Value = (Ciphertext, SecretKey)> {
+        (message(max_len), any::<[u8; 32]>()).prop_map(|(msg, seed)| {
+            let mut rng = ChaChaRng::from_seed(seed);
+            let sk: SecretKey = rng.gen();
+            (sk.public_key().encrypt_with_rng(&mut rng, msg), sk)
+        })
+    }
+
+    /// Returns a strategy for corruptions of the encoding, like [`mutate`](super::mutate): a
+    /// flipped bit, a truncation or extra bytes. The corrupted encodings differ from `bytes`, and
+    /// shrink towards flipping the first bit, truncating to nothing or appending a zero.
+    pub fn corrupted(bytes: Vec<u8>) -> BoxedStrategy<Vec<u8>> {
+        let extended = {
+            let bytes = bytes.clone();
+            vec(any::<u8>(), 1..=32).prop_map(move |extra| [&bytes[..], &extra[..]].concat())
+        };
+        if bytes.is_empty() {
+            return extended.boxed();
+        }
+        let flipped = {
+            let bytes = bytes.clone();
+            (0..8 * bytes.len()).prop_map(move |bit| {
+                let mut result = bytes.clone();
+                result[bit / 8] ^= 1 << (bit % 8);
+                result
+            })
+        };
+        let truncated = (0..bytes.len()).prop_map(move |len| bytes[..len].to_vec());
+        prop_oneof![flipped, truncated, extended].boxed()
+    }
+
+    /// Returns a strategy for random public keys and corruptions of their encodings.
+    pub fn corrupted_public_key() -> impl Strategy<Value = (PublicKey, Vec<u8>)> {
+        public_key().prop_flat_map(|pk| (Just(pk), corrupted(pk.to_bytes().to_vec())))
+    }
+
+    /// Returns a strategy for random signatures and corruptions of their encodings.
+    pub fn corrupted_signature() -> impl Strategy<Value = (Signature, Vec<u8>)> {
+        signature().prop_flat_map(|sig| {
+            let bytes = sig.to_bytes().to_vec();
+            (Just(sig), corrupted(bytes))
+        })
+    }
+
+    /// Returns a strategy for valid ciphertexts and corruptions of their encodings.
+    #[cfg(feature = "encryption")]
+    pub fn corrupted_ciphertext(max_len: usize) -> impl Strategy<Value = (Ciphertext, Vec<u8>)> {
+        ciphertext(max_len).prop_flat_map(|(ct, _)| {
+            let bytes = ct.to_bytes();
+            (Just(ct), corrupted(bytes))
+        })
+    }
+
+    /// Returns a strategy for the `G1` encodings of
+    /// [`invalid_g1_encodings`](super::invalid_g1_encodings).
+    #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+    pub fn invalid_public_key() -> impl Strategy<Value = [u8; PK_SIZE]> {
+        proptest::sample::select(super::invalid_g1_encodings())
+    }
+
+    /// Returns a strategy for the `G2` encodings of
+    /// [`invalid_g2_encodings`](super::invalid_g2_encodings).
+    #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+    pub fn invalid_signature() -> impl Strategy<Value = [u8; SIG_SIZE]> {
+        proptest::sample::select(super::invalid_g2_encodings())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use group::CurveProjective;
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    use crate::{Signature, G1, G2};
+
+    #[test]
+    fn test_generated_values() {
+        for seed in 0..20u64 {
+            let mut rng = ChaChaRng::seed_from_u64(seed);
+            let sk_set = key_set(&mut rng, 4);
+            assert!(sk_set.threshold() <= 4);
+            let msg = message(&mut rng, 100);
+            let share = sk_set.secret_key_share(seed);
+            let pk_set = sk_set.public_keys();
+            assert!(pk_set
+                .public_key_share(seed)
+                .verify(&share.sign(&msg), &msg));
+
+            let pk: PublicKey = rng.gen();
+            let bytes = pk.to_bytes();
+            assert_eq!(Ok(pk), PublicKey::from_bytes(bytes));
+            let mutated = mutate(&mut rng, &bytes);
+            assert_ne!(&bytes[..], &mutated[..]);
+            if mutated.len() == PK_SIZE {
+                let mut array = [0u8; PK_SIZE];
+                array.copy_from_slice(&mutated);
+                assert_ne!(Ok(pk), PublicKey::from_bytes(array));
+            }
+
+            #[cfg(feature = "encryption")]
+            {
+                let (ct, sk) = ciphertext(&mut rng, 50);
+                assert!(ct.verify());
+                assert!(sk.decrypt(&ct).is_some());
+            }
+        }
+    }
+
+    #[test]
+    fn test_garbage_shares() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let sk_set = key_set(&mut rng, 3);
+        let pk_share = sk_set.public_keys().public_key_share(1);
+        assert!(!pk_share.verify(&garbage_signature_share(&mut rng, "msg"), "msg"));
+
+        #[cfg(feature = "encryption")]
+        {
+            let ct = sk_set
+                .public_keys()
+                .public_key()
+                .encrypt_with_rng(&mut rng, "msg");
+            let share = garbage_decryption_share(&mut rng, &ct);
+            assert!(!pk_share.verify_decryption_share(&share, &ct));
+        }
+    }
+
+    #[cfg(feature = "dkg")]
+    #[test]
+    fn test_bad_dealer() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let deal = bad_dealer(&mut rng, 2, 5, &[1, 3]);
+        for (i, row) in deal.rows.iter().enumerate() {
+            assert_eq!(2, row.degree());
+            let valid = deal.commitment.row(i + 1) == row.commitment();
+            assert_eq!(i != 1 && i != 3, valid);
+        }
+    }
+
+    #[cfg(feature = "sessions")]
+    #[test]
+    fn test_equivocating_signer() {
+        use crate::error::Error;
+        use crate::session::SigningSession;
+
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let sk_set = key_set(&mut rng, 3);
+        let honest = InMemoryShare::new(2, sk_set.secret_key_share(2));
+        let signer = EquivocatingSigner::new(&mut rng, honest);
+        let mut first = SigningSession::new(sk_set.public_keys(), "msg");
+        let mut second = first.clone();
+        assert_eq!(Ok(true), first.contribute(&signer));
+        assert_eq!(Err(Error::InvalidShare(2)), second.contribute(&signer));
+    }
+
+    #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+    #[test]
+    fn test_invalid_encodings() {
+        use crate::{Fr, FrRepr};
+        use ff::{PrimeField, PrimeFieldRepr};
+
+        for bytes in invalid_g1_encodings() {
+            assert!(PublicKey::from_bytes(bytes).is_err());
+        }
+        for bytes in invalid_g2_encodings() {
+            assert!(Signature::from_bytes(bytes).is_err());
+        }
+        for bytes in invalid_fr_encodings() {
+            let mut repr = FrRepr::default();
+            repr.read_be(&bytes[..]).expect("32 bytes");
+            assert!(Fr::from_repr(repr).is_err());
+        }
+    }
+
+    #[cfg(feature = "testing")]
+    #[test]
+    fn test_arbitrary() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let data: Vec<u8> = (0..256).map(|_| rng.gen()).collect();
+        let sk = SecretKey::arbitrary(&mut Unstructured::new(&data)).expect("enough data");
+        assert_eq!(
+            Ok(sk.clone()),
+            SecretKey::arbitrary(&mut Unstructured::new(&data))
+        );
+        let mut u = Unstructured::new(&data);
+        let sk_set = SecretKeySet::arbitrary(&mut u).expect("enough data");
+        assert!(sk_set.threshold() <= MAX_ARBITRARY_THRESHOLD);
+        let sig = Signature::arbitrary(&mut u).expect("enough data");
+        assert_eq!(Ok(sig.clone()), Signature::from_bytes(sig.to_bytes()));
+        #[cfg(feature = "encryption")]
+        assert!(Ciphertext::arbitrary(&mut u).expect("enough data").verify());
+    }
+
+    #[cfg(feature = "testing")]
+    mod props {
+        use proptest::prelude::*;
+
+        use super::super::strategy;
+        use crate::{PublicKey, Signature};
+
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(32))]
+
+            #[test]
+            fn prop_key_set((sk_set, msg) in (strategy::key_set(4), strategy::message(64))) {
+                prop_assert!(sk_set.threshold() <= 4);
+                let sig = sk_set.secret_key().sign(&msg);
+                prop_assert!(sk_set.public_keys().public_key().verify(&sig, &msg));
+            }
+
+            #[test]
+            fn prop_corrupted_public_key((pk, bytes) in strategy::corrupted_public_key()) {
+                prop_assert_ne!(Ok(pk), PublicKey::from_slice(&bytes));
+            }
+
+            #[test]
+            fn prop_corrupted_signature((sig, bytes) in strategy::corrupted_signature()) {
+                prop_assert_ne!(Ok(sig), Signature::from_slice(&bytes));
+            }
+        }
+
+        #[cfg(feature = "encryption")]
+        proptest! {
+            #![proptest_config(ProptestConfig::with_cases(32))]
+
+            #[test]
+            fn prop_corrupted_ciphertext((ct, bytes) in strategy::corrupted_ciphertext(64)) {
+                prop_assert_ne!(Ok(ct), crate::Ciphertext::from_slice(&bytes));
+            }
+        }
+
+        #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+        proptest! {
+            #[test]
+            fn prop_invalid_encodings(
+                pk in strategy::invalid_public_key(),
+                sig in strategy::invalid_signature(),
+            ) {
+                prop_assert!(PublicKey::from_bytes(pk).is_err());
+                prop_assert!(Signature::from_bytes(sig).is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn test_identity_encodings() {
+        let (pk, sig) = identity_encodings();
+        if cfg!(feature = "strict") {
+            assert!(PublicKey::from_bytes(pk).is_err());
+            assert!(Signature::from_bytes(sig).is_err());
+        } else {
+            assert_eq!(Ok(PublicKey(G1::zero())), PublicKey::from_bytes(pk));
+            assert_eq!(Ok(Signature(G2::zero())), Signature::from_bytes(sig));
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Archived protocol transcripts, for regression testing against known-good runs.
+//!
+//! A transcript records the public inputs and every message of one protocol run, in the same
+//! JSON format and hex-encoded canonical representations as the [`vectors`](../vectors/index.html)
+//! module. Downstream projects can pin transcripts from their deployments, and assert after each
+//! upgrade that [`Transcript::verify`] still accepts them:
+//!
+//! ```no_run
+//! # fn main() -> threshold_crypto::error::Result<()> {
+//! use threshold_crypto::transcript::Transcript;
+//!
+//! let json = std::fs::read_to_string("tests/golden/signing.json").expect("read transcript");
+//! Transcript::from_json(&json)?.verify()?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! This module is only available with the `test-vectors` feature.
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::util::{from_hex, to_hex};
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{PublicKeySet, Signature, SignatureShare};
+
+/// A recorded run of one of the crate's protocols.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(tag = "protocol", rename_all = "snake_case")]
+pub enum Transcript {
+    /// A threshold signing round.
+    Signing(SigningTranscript),
+    /// A threshold decryption round.
+    #[cfg(feature = "encryption")]
+    Decryption(DecryptionTranscript),
+}
+
+impl Transcript {
+    /// Parses a transcript from JSON.
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).map_err(|err| Error::InvalidTranscript(err.to_string()))
+    }
+
+    /// Returns the transcript as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("transcripts are serializable")
+    }
+
+    /// Checks that every share in the transcript is valid, and that combining them produces the
+    /// recorded result.
+    pub fn verify(&self) -> Result<()> {
+        match self {
+            Transcript::Signing(transcript) => transcript.verify(),
+            #[cfg(feature = "encryption")]
+            Transcript::Decryption(transcript) => transcript.verify(),
+        }
+    }
+}
+
+/// The messages of a threshold signing round.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SigningTranscript {
+    /// The committee's public key set, as in `PublicKeySet::to_bytes`.
+    pub public_key_set: String,
+    /// The signed message.
+    pub message: String,
+    /// The signature shares received.
+    pub shares: Vec<TranscriptShare>,
+    /// The combined signature.
+    pub signature: String,
+}
+
+impl SigningTranscript {
+    /// Records a signing round.
+    pub fn new<'a, M, I>(pk_set: &PublicKeySet, msg: M, shares: I, sig: &Signature) -> Self
+    where
+        M: AsRef<[u8]>,
+        I: IntoIterator<Item = (usize, &'a SignatureShare)>,
+    {
+        SigningTranscript {
+            public_key_set: to_hex(pk_set.to_bytes()),
+            message: to_hex(msg),
+            shares: shares
+                .into_iter()
+                .map(|(index, share)| TranscriptShare {
+                    index,
+                    share: to_hex(share.to_bytes()),
+                })
+                .collect(),
+            signature: to_hex(sig.to_bytes()),
+        }
+    }
+
+    /// Checks the shares and the signature against the public key set.
+    pub fn verify(&self) -> Result<()> {
+        let pk_set = decode_hex(
+            "public_key_set",
+            &self.public_key_set,
+            PublicKeySet::from_slice,
+        )?;
+        let msg = from_hex(&self.message).ok_or_else(|| invalid("message", "not hex"))?;
+        let mut shares = BTreeMap::new();
+        for entry in &self.shares {
+            let share = decode_hex("signature share", &entry.share, SignatureShare::from_slice)?;
+            if !pk_set.public_key_share(entry.index).verify(&share, &msg) {
+                return Err(Error::InvalidShare(entry.index));
+            }
+            if shares.insert(entry.index, share).is_some() {
+                return Err(Error::DuplicateEntry);
+            }
+        }
+        let sig = decode_hex("signature", &self.signature, Signature::from_slice)?;
+        if pk_set.combine_signatures(&shares)? != sig {
+            return Err(invalid("signature", "differs from the combined shares"));
+        }
+        if !pk_set.public_key().verify(&sig, &msg) {
+            return Err(invalid("signature", "is not valid"));
+        }
+        Ok(())
+    }
+}
+
+/// A share sent in a protocol round.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TranscriptShare {
+    /// The sender's share index.
+    pub index: usize,
+    /// The share, in its `to_bytes` representation.
+    pub share: String,
+}
+
+/// The messages of a threshold decryption round.
+#[cfg(feature = "encryption")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct DecryptionTranscript {
+    /// The committee's public key set, as in `PublicKeySet::to_bytes`.
+    pub public_key_set: String,
+    /// The ciphertext, as in `Ciphertext::to_bytes`.
+    pub ciphertext: String,
+    /// The decryption shares received.
+    pub shares: Vec<TranscriptShare>,
+    /// The decrypted message.
+    pub plaintext: String,
+}
+
+#[cfg(feature = "encryption")]
+impl DecryptionTranscript {
+    /// Records a decryption round.
+    pub fn new<'a, M, I>(pk_set: &PublicKeySet, ct: &Ciphertext, shares: I, plaintext: M) -> Self
+    where
+        M: AsRef<[u8]>,
+        I: IntoIterator<Item = (usize, &'a DecryptionShare)>,
+    {
+        DecryptionTranscript {
+            public_key_set: to_hex(pk_set.to_bytes()),
+            ciphertext: to_hex(ct.to_bytes()),
+            shares: shares
+                .into_iter()
+                .map(|(index, share)| TranscriptShare {
+                    index,
+                    share: to_hex(share.to_bytes()),
+                })
+                .collect(),
+            plaintext: to_hex(plaintext),
+        }
+    }
+
+    /// Checks the ciphertext, the shares and the plaintext against the public key set.
+    pub fn verify(&self) -> Result<()> {
+        let pk_set = decode_hex(
+            "public_key_set",
+            &self.public_key_set,
+            PublicKeySet::from_slice,
+        )?;
+        let ct = decode_hex("ciphertext", &self.ciphertext, Ciphertext::from_slice)?;
+        if !ct.verify() {
+            return Err(Error::InvalidCiphertext);
+        }
+        let mut shares = BTreeMap::new();
+        for entry in &self.shares {
+            let share = decode_hex(
+                "decryption share",
+                &entry.share,
+                DecryptionShare::from_slice,
+            )?;
+            if !pk_set
+                .public_key_share(entry.index)
+                .verify_decryption_share(&share, &ct)
+            {
+                return Err(Error::InvalidShare(entry.index));
+            }
+            if shares.insert(entry.index, share).is_some() {
+                return Err(Error::DuplicateEntry);
+            }
+        }
+        let plaintext = from_hex(&self.plaintext).ok_or_else(|| invalid("plaintext", "not hex"))?;
+        if pk_set.decrypt(&shares, &ct)? != plaintext {
+            return Err(invalid("plaintext", "differs from the decrypted shares"));
+        }
+        Ok(())
+    }
+}
+
+/// Returns an `Error::InvalidTranscript` for the field.
+fn invalid(field: &str, problem: &str) -> Error {
+    Error::InvalidTranscript(format!("{} {}", field, problem))
+}
+
+/// Decodes the hex-encoded field with the given decoder.
+fn decode_hex<T, E, F>(field: &str, hex: &str, decode: F) -> Result<T>
+where
+    E: std::fmt::Display,
+    F: FnOnce(&[u8]) -> std::result::Result<T, E>,
+{
+    let bytes = from_hex(hex).ok_or_else(|| invalid(field, "is not hex"))?;
+    decode(&bytes).map_err(|err| Error::InvalidTranscript(format!("{}: {}", field, err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use rand::SeedableRng;
+    use rand_chacha::ChaChaRng;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_signing_transcript() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let shares: BTreeMap<_, _> = (0..3)
+            .map(|i| (i, sk_set.secret_key_share(i).sign("golden")))
+            .collect();
+        let sig = pk_set.combine_signatures(&shares).expect("enough shares");
+        let recorded =
+            SigningTranscript::new(&pk_set, "golden", shares.iter().map(|(i, s)| (*i, s)), &sig);
+        let transcript = Transcript::Signing(recorded.clone());
+        let loaded = Transcript::from_json(&transcript.to_json()).expect("valid JSON");
+        assert_eq!(transcript, loaded);
+        assert_eq!(Ok(()), loaded.verify());
+
+        let mut tampered = recorded.clone();
+        tampered.message = to_hex("tampered");
+        assert_eq!(Err(Error::InvalidShare(0)), tampered.verify());
+        let mut tampered = recorded;
+        tampered.shares[1].share = tampered.shares[2].share.clone();
+        assert_eq!(Err(Error::InvalidShare(1)), tampered.verify());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_decryption_transcript() {
+        let mut rng = ChaChaRng::seed_from_u64(0);
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let ct = pk_set.public_key().encrypt_with_rng(&mut rng, "golden");
+        let shares: BTreeMap<_, _> = (0..2)
+            .map(|i| (i, sk_set.secret_key_share(i).decrypt_share_no_verify(&ct)))
+            .collect();
+        let recorded =
+            DecryptionTranscript::new(&pk_set, &ct, shares.iter().map(|(i, s)| (*i, s)), "golden");
+        let transcript = Transcript::Decryption(recorded.clone());
+        assert!(transcript
+            .to_json()
+            .contains("\"protocol\": \"decryption\""));
+        assert_eq!(Ok(()), transcript.verify());
+
+        let mut tampered = recorded;
+        tampered.plaintext = to_hex("tampered");
+        let expected = invalid("plaintext", "differs from the decrypted shares");
+        assert_eq!(Err(expected), tampered.verify());
+    }
+}
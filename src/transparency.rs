@@ -0,0 +1,237 @@
+//! Key transparency: an auditable, append-only log of a committee's keys.
+//!
+//! A deployment publishes every change of its public key material as a [`LogEntry`]: the epoch,
+//! the committee's `PublicKeySet` and the number of participants, and through them every
+//! participant's `PublicKeyShare`. Entries are hash-chained, each containing the hash of its
+//! predecessor, and threshold-signed: the first one by its own committee, and each later one by
+//! the committee of the entry before it, so that only a quorum of the current committee can hand
+//! over to a new one.
+//!
+//! An [`Auditor`] replays the log, checking the chain and the signatures. It keeps the hash of
+//! the last entry it saw, so if it is later presented with a log that doesn't extend that one,
+//! e.g. because an entry was changed or removed, it detects the inconsistency.
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+use crate::util::sha3_256;
+use crate::{PublicKeySet, PublicKeyShare, Signature};
+
+/// The domain separator of the signed entries.
+const DST: &[u8] = b"threshold_crypto key transparency";
+
+/// A record of a committee's key material.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LogEntry {
+    sequence: u64,
+    prev_hash: [u8; 32],
+    epoch: u64,
+    pk_set: PublicKeySet,
+    num_shares: usize,
+}
+
+impl LogEntry {
+    /// Creates the entry following `prev`, or the first entry if `prev` is `None`.
+    pub fn new(
+        prev: Option<&SignedEntry>,
+        epoch: u64,
+        pk_set: PublicKeySet,
+        num_shares: usize,
+    ) -> Self {
+        let (sequence, prev_hash) = match prev {
+            Some(prev) => (prev.entry.sequence + 1, prev.hash()),
+            None => (0, [0; 32]),
+        };
+        LogEntry {
+            sequence,
+            prev_hash,
+            epoch,
+            pk_set,
+            num_shares,
+        }
+    }
+
+    /// Returns the entry's position in the log.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+
+    /// Returns the hash of the previous entry, or zeros for the first one.
+    pub fn prev_hash(&self) -> &[u8; 32] {
+        &self.prev_hash
+    }
+
+    /// Returns the epoch the key set is used in.
+    pub fn epoch(&self) -> u64 {
+        self.epoch
+    }
+
+    /// Returns the committee's key set.
+    pub fn public_keys(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns the participants' public key shares.
+    pub fn public_key_shares(&self) -> Vec<PublicKeyShare> {
+        (0..self.num_shares)
+            .map(|i| self.pk_set.public_key_share(i))
+            .collect()
+    }
+
+    /// Returns the message to be signed for the entry.
+    pub fn message(&self) -> Vec<u8> {
+        let mut msg = DST.to_vec();
+        msg.extend(&self.sequence.to_be_bytes());
+        msg.extend(&self.prev_hash);
+        msg.extend(&self.epoch.to_be_bytes());
+        msg.extend(&(self.num_shares as u64).to_be_bytes());
+        msg.extend(self.pk_set.to_bytes());
+        msg
+    }
+}
+
+/// A log entry with its signature.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignedEntry {
+    entry: LogEntry,
+    signature: Signature,
+}
+
+impl SignedEntry {
+    /// Creates a signed entry from the entry and the signature of its `message()`.
+    pub fn new(entry: LogEntry, signature: Signature) -> Self {
+        SignedEntry { entry, signature }
+    }
+
+    /// Returns the entry.
+    pub fn entry(&self) -> &LogEntry {
+        &self.entry
+    }
+
+    /// Returns the signature.
+    pub fn signature(&self) -> &Signature {
+        &self.signature
+    }
+
+    /// Returns the hash the next entry refers to.
+    pub fn hash(&self) -> [u8; 32] {
+        let mut bytes = self.entry.message();
+        bytes.extend(self.signature.to_bytes().as_ref());
+        sha3_256(&bytes)
+    }
+}
+
+/// A verifier replaying a key transparency log.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Auditor {
+    head: Option<SignedEntry>,
+}
+
+impl Auditor {
+    /// Creates an auditor that hasn't seen any entries yet.
+    pub fn new() -> Self {
+        Auditor::default()
+    }
+
+    /// Returns the last verified entry.
+    pub fn head(&self) -> Option<&SignedEntry> {
+        self.head.as_ref()
+    }
+
+    /// Verifies the next entry and appends it.
+    ///
+    /// Returns `Error::InvalidLogEntry` with the entry's sequence number if it doesn't follow the
+    /// last verified entry, or is not signed by the right committee.
+    pub fn apply(&mut self, signed: &SignedEntry) -> Result<()> {
+        let entry = &signed.entry;
+        let expected = LogEntry::new(
+            self.head.as_ref(),
+            entry.epoch,
+            entry.pk_set.clone(),
+            entry.num_shares,
+        );
+        let signer = match self.head {
+            Some(ref head) => &head.entry,
+            None => entry,
+        };
+        let valid = *entry == expected
+            && entry.num_shares > entry.pk_set.threshold()
+            && self
+                .head
+                .as_ref()
+                .is_none_or(|head| head.entry.epoch < entry.epoch)
+            && signer
+                .pk_set
+                .public_key()
+                .verify(&signed.signature, entry.message());
+        if !valid {
+            return Err(Error::InvalidLogEntry(entry.sequence));
+        }
+        self.head = Some(signed.clone());
+        Ok(())
+    }
+
+    /// Verifies the log, which must contain all entries after the last verified one.
+    pub fn verify_log<'a, I>(&mut self, entries: I) -> Result<()>
+    where
+        I: IntoIterator<Item = &'a SignedEntry>,
+    {
+        entries
+            .into_iter()
+            .try_for_each(|signed| self.apply(signed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    /// Signs the entry with a quorum of the key set.
+    fn sign(sk_set: &SecretKeySet, entry: LogEntry) -> SignedEntry {
+        let msg = entry.message();
+        let shares: Vec<_> = (0..=sk_set.threshold())
+            .map(|i| sk_set.secret_key_share(i).sign(&msg))
+            .collect();
+        let sig = sk_set
+            .public_keys()
+            .combine_signatures(shares.iter().enumerate());
+        SignedEntry::new(entry, sig.expect("enough shares"))
+    }
+
+    #[test]
+    fn test_log() {
+        let mut rng = rand::thread_rng();
+        let sk_sets: Vec<SecretKeySet> =
+            (0..3).map(|_| SecretKeySet::random(1, &mut rng)).collect();
+        let mut log: Vec<SignedEntry> = Vec::new();
+        for (i, sk_set) in sk_sets.iter().enumerate() {
+            let entry = LogEntry::new(log.last(), 10 * i as u64, sk_set.public_keys(), 4);
+            let signer = if i == 0 { sk_set } else { &sk_sets[i - 1] };
+            log.push(sign(signer, entry));
+        }
+        assert_eq!(4, log[1].entry().public_key_shares().len());
+
+        let mut auditor = Auditor::new();
+        auditor.verify_log(&log[..2]).expect("valid log");
+        auditor.verify_log(&log[2..]).expect("valid log");
+        assert_eq!(Some(&log[2]), auditor.head());
+
+        // The new committee can't sign its own entry.
+        let mut auditor = Auditor::new();
+        auditor.apply(&log[0]).expect("valid entry");
+        let self_signed = sign(&sk_sets[1], log[1].entry().clone());
+        assert_eq!(Err(Error::InvalidLogEntry(1)), auditor.apply(&self_signed));
+
+        // Entries can't be skipped, replaced or reordered.
+        assert_eq!(Err(Error::InvalidLogEntry(2)), auditor.apply(&log[2]));
+        let other = LogEntry::new(Some(&log[0]), 5, sk_sets[2].public_keys(), 4);
+        let fork = sign(&sk_sets[0], other);
+        auditor.apply(&fork).expect("valid entry");
+        assert_eq!(Err(Error::InvalidLogEntry(2)), auditor.apply(&log[2]));
+        let stale = LogEntry::new(Some(&fork), 5, sk_sets[1].public_keys(), 4);
+        let stale = sign(&sk_sets[2], stale);
+        assert_eq!(Err(Error::InvalidLogEntry(2)), auditor.apply(&stale));
+    }
+}
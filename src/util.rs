@@ -1,5 +1,9 @@
+use std::io::{self, Read};
+
 use tiny_keccak::{Hasher, Sha3};
 
+use hex_fmt::HexFmt;
+
 pub(crate) fn sha3_256(data: &[u8]) -> [u8; 32] {
     let mut sha3 = Sha3::v256();
     sha3.update(data);
@@ -7,3 +11,58 @@ pub(crate) fn sha3_256(data: &[u8]) -> [u8; 32] {
     sha3.finalize(&mut output);
     output
 }
+
+/// Returns the SHA3-256 digest of the reader's contents, reading them in chunks.
+pub(crate) fn sha3_256_reader<R: Read>(mut reader: R) -> io::Result<[u8; 32]> {
+    let mut sha3 = Sha3::v256();
+    let mut buf = vec![0u8; 64 * 1024];
+    loop {
+        match reader.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => sha3.update(&buf[..n]),
+            Err(ref err) if err.kind() == io::ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        }
+    }
+    let mut output = [0u8; 32];
+    sha3.finalize(&mut output);
+    Ok(output)
+}
+
+/// Returns the bytes in lowercase hexadecimal.
+pub(crate) fn to_hex<B: AsRef<[u8]>>(bytes: B) -> String {
+    HexFmt(bytes).to_string()
+}
+
+/// Parses a hexadecimal string, or returns `None` if it is invalid.
+pub(crate) fn from_hex(hex: &str) -> Option<Vec<u8>> {
+    if !hex.len().is_multiple_of(2) || !hex.is_ascii() {
+        return None;
+    }
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..(i + 2)], 16).ok())
+        .collect()
+}
+
+/// SHA-256, for the features that hash with it.
+#[cfg(any(
+    all(feature = "ietf", not(feature = "use-insecure-test-only-mock-crypto")),
+    feature = "keystore",
+    all(
+        feature = "mnemonic",
+        not(feature = "use-insecure-test-only-mock-crypto")
+    )
+))]
+pub(crate) mod digest {
+    use sha2::{Digest, Sha256};
+
+    /// Returns the SHA-256 digest of the concatenation of the inputs.
+    pub(crate) fn sha256(inputs: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for input in inputs {
+            hasher.update(input);
+        }
+        hasher.finalize().into()
+    }
+}
@@ -0,0 +1,390 @@
+//! Storage of secret key shares in platform secure storage.
+//!
+//! A [`ShareVault`] seals opaque secrets under a label and unseals them again, e.g. in an OS
+//! keychain, a mobile secure enclave or a TPM-backed store. [`store_share`] and [`load_share`]
+//! handle encoding a `SecretKeyShare` for the vault and make sure that no unencoded copies of the
+//! secret are left behind on the way in or out.
+//!
+//! [`MemoryVault`] keeps the sealed shares in memory and is meant for tests. [`DirectoryVault`]
+//! keeps them in files, and should only be used with secrets that are encrypted already, e.g. by
+//! a [`WrappedVault`](../kms/struct.WrappedVault.html). On Linux, `SecretServiceVault` stores
+//! them via the Secret Service API (GNOME Keyring, KWallet), and on macOS `KeychainVault` stores
+//! them in the login keychain. Both use the platform's command line client, passing secrets only
+//! through pipes, never as arguments.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::PathBuf;
+#[cfg(unix)]
+use std::process::{Command, Stdio};
+
+use ff::{PrimeField, PrimeFieldRepr};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{Error, Result};
+use crate::{Fr, FrRepr, SecretKeyShare, SK_SIZE};
+
+/// Platform storage that can seal and unseal secrets under a label.
+pub trait ShareVault {
+    /// Seals the secret under the given label, replacing any previous entry.
+    fn seal(&mut self, label: &str, secret: &[u8]) -> Result<()>;
+
+    /// Returns the secret sealed under the given label, or `Error::VaultEntryNotFound`.
+    fn unseal(&self, label: &str) -> Result<Zeroizing<Vec<u8>>>;
+
+    /// Removes the entry with the given label, if it exists.
+    fn remove(&mut self, label: &str) -> Result<()>;
+}
+
+/// Seals the secret key share in the vault under the given label.
+pub fn store_share<V: ShareVault + ?Sized>(
+    vault: &mut V,
+    label: &str,
+    share: &SecretKeyShare,
+) -> Result<()> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+    let mut repr = ((share.0).0).into_repr();
+    let written = repr.write_be(&mut *bytes);
+    repr.0.zeroize();
+    written.map_err(|err| Error::Backend(err.to_string()))?;
+    vault.seal(label, &bytes)
+}
+
+/// Unseals the secret key share with the given label from the vault.
+pub fn load_share<V: ShareVault + ?Sized>(vault: &V, label: &str) -> Result<SecretKeyShare> {
+    let bytes = vault.unseal(label)?;
+    if bytes.len() != SK_SIZE {
+        return Err(Error::InvalidVaultEntry(label.to_string()));
+    }
+    let mut repr = FrRepr::default();
+    repr.read_be(&bytes[..])
+        .map_err(|err| Error::Backend(err.to_string()))?;
+    let result = Fr::from_repr(repr);
+    repr.0.zeroize();
+    let mut fr = result.map_err(|_| Error::InvalidVaultEntry(label.to_string()))?;
+    Ok(SecretKeyShare::from_mut(&mut fr))
+}
+
+/// A vault that keeps the sealed secrets in memory. Intended for tests.
+#[derive(Clone, Default)]
+pub struct MemoryVault {
+    entries: BTreeMap<String, Zeroizing<Vec<u8>>>,
+}
+
+impl fmt::Debug for MemoryVault {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MemoryVault")
+            .field("labels", &self.entries.keys().collect::<Vec<_>>())
+            .finish()
+    }
+}
+
+impl MemoryVault {
+    /// Creates an empty vault.
+    pub fn new() -> Self {
+        MemoryVault::default()
+    }
+}
+
+impl ShareVault for MemoryVault {
+    fn seal(&mut self, label: &str, secret: &[u8]) -> Result<()> {
+        let entry = Zeroizing::new(secret.to_vec());
+        self.entries.insert(label.to_string(), entry);
+        Ok(())
+    }
+
+    fn unseal(&self, label: &str) -> Result<Zeroizing<Vec<u8>>> {
+        self.entries
+            .get(label)
+            .cloned()
+            .ok_or_else(|| Error::VaultEntryNotFound(label.to_string()))
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        self.entries.remove(label);
+        Ok(())
+    }
+}
+
+/// A vault that stores each entry in a file in a directory.
+///
+/// The entries are not encrypted: On Unix, the files are only readable by their owner, but sealing
+/// plain secret key shares in a `DirectoryVault` is not recommended. Labels may only contain
+/// ASCII letters, digits, `-`, `_` and `.`, and must not start with a `.`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DirectoryVault {
+    dir: PathBuf,
+}
+
+impl DirectoryVault {
+    /// Creates a vault storing entries in the given directory, which is created if necessary.
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Result<Self> {
+        let dir = dir.into();
+        fs::create_dir_all(&dir).map_err(|err| Error::Backend(err.to_string()))?;
+        Ok(DirectoryVault { dir })
+    }
+
+    /// Returns the path of the file containing the entry with the given label.
+    fn path(&self, label: &str) -> Result<PathBuf> {
+        let valid_char = |c: char| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.';
+        if label.is_empty() || label.starts_with('.') || !label.chars().all(valid_char) {
+            return Err(Error::Backend(format!(
+                "unsupported vault label {:?}",
+                label
+            )));
+        }
+        Ok(self.dir.join(label))
+    }
+}
+
+impl ShareVault for DirectoryVault {
+    fn seal(&mut self, label: &str, secret: &[u8]) -> Result<()> {
+        let path = self.path(label)?;
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path)
+            .map_err(|err| Error::Backend(err.to_string()))?;
+        file.write_all(secret)
+            .and_then(|()| file.sync_all())
+            .map_err(|err| Error::Backend(err.to_string()))
+    }
+
+    fn unseal(&self, label: &str) -> Result<Zeroizing<Vec<u8>>> {
+        match fs::read(self.path(label)?) {
+            Ok(bytes) => Ok(Zeroizing::new(bytes)),
+            Err(ref err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(Error::VaultEntryNotFound(label.to_string()))
+            }
+            Err(err) => Err(Error::Backend(err.to_string())),
+        }
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        match fs::remove_file(self.path(label)?) {
+            Err(ref err) if err.kind() != io::ErrorKind::NotFound => {
+                Err(Error::Backend(err.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// A vault using the Secret Service API via the `secret-tool` client from `libsecret`.
+///
+/// Entries are stored with the attributes `service` and `label`, hex-encoded.
+#[cfg(target_os = "linux")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SecretServiceVault {
+    service: String,
+}
+
+#[cfg(target_os = "linux")]
+impl SecretServiceVault {
+    /// Creates a vault storing entries under the given service name.
+    pub fn new<S: Into<String>>(service: S) -> Self {
+        SecretServiceVault {
+            service: service.into(),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+impl ShareVault for SecretServiceVault {
+    fn seal(&mut self, label: &str, secret: &[u8]) -> Result<()> {
+        let mut cmd = Command::new("secret-tool");
+        cmd.arg("store")
+            .arg(format!("--label={} {}", self.service, label))
+            .args(["service", &self.service, "label", label]);
+        run(&mut cmd, Some(&encode_hex(secret))).map(drop)
+    }
+
+    fn unseal(&self, label: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["lookup", "service", &self.service, "label", label]);
+        let output =
+            run(&mut cmd, None).map_err(|_| Error::VaultEntryNotFound(label.to_string()))?;
+        decode_hex(&output).ok_or_else(|| Error::InvalidVaultEntry(label.to_string()))
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        let mut cmd = Command::new("secret-tool");
+        cmd.args(["clear", "service", &self.service, "label", label]);
+        run(&mut cmd, None).map(drop)
+    }
+}
+
+/// A vault using the macOS keychain via the `security` tool.
+///
+/// Entries are stored as generic passwords with the service name and the label as account,
+/// hex-encoded. Secrets are passed to `security` in interactive mode on standard input.
+/// Labels must not contain quotes, backslashes or line breaks.
+#[cfg(target_os = "macos")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeychainVault {
+    service: String,
+}
+
+#[cfg(target_os = "macos")]
+impl KeychainVault {
+    /// Creates a vault storing entries under the given service name.
+    pub fn new<S: Into<String>>(service: S) -> Self {
+        KeychainVault {
+            service: service.into(),
+        }
+    }
+}
+
+#[cfg(target_os = "macos")]
+impl ShareVault for KeychainVault {
+    fn seal(&mut self, label: &str, secret: &[u8]) -> Result<()> {
+        let forbidden = |c: char| c == '"' || c == '\\' || c == '\n' || c == '\r';
+        if label.contains(forbidden) || self.service.contains(forbidden) {
+            return Err(Error::Backend(format!(
+                "unsupported keychain label {:?}",
+                label
+            )));
+        }
+        let hex = encode_hex(secret);
+        let mut script = Zeroizing::new(Vec::new());
+        script.extend_from_slice(b"add-generic-password -U -s \"");
+        script.extend_from_slice(self.service.as_bytes());
+        script.extend_from_slice(b"\" -a \"");
+        script.extend_from_slice(label.as_bytes());
+        script.extend_from_slice(b"\" -w ");
+        script.extend_from_slice(&hex);
+        script.push(b'\n');
+        run(Command::new("security").arg("-i"), Some(&script)).map(drop)
+    }
+
+    fn unseal(&self, label: &str) -> Result<Zeroizing<Vec<u8>>> {
+        let mut cmd = Command::new("security");
+        cmd.args([
+            "find-generic-password",
+            "-s",
+            &self.service,
+            "-a",
+            label,
+            "-w",
+        ]);
+        let output =
+            run(&mut cmd, None).map_err(|_| Error::VaultEntryNotFound(label.to_string()))?;
+        decode_hex(&output).ok_or_else(|| Error::InvalidVaultEntry(label.to_string()))
+    }
+
+    fn remove(&mut self, label: &str) -> Result<()> {
+        let mut cmd = Command::new("security");
+        cmd.args(["delete-generic-password", "-s", &self.service, "-a", label]);
+        run(&mut cmd, None).map(drop)
+    }
+}
+
+/// Runs the command, writing `input` to its standard input, and returns its standard output.
+#[cfg(unix)]
+pub(crate) fn run(cmd: &mut Command, input: Option<&[u8]>) -> Result<Zeroizing<Vec<u8>>> {
+    let backend_err = |err: io::Error| Error::Backend(err.to_string());
+    let mut child = cmd
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(backend_err)?;
+    if let Some(input) = input {
+        let stdin = child.stdin.as_mut().expect("stdin is piped");
+        stdin.write_all(input).map_err(backend_err)?;
+    }
+    drop(child.stdin.take());
+    let output = child.wait_with_output().map_err(backend_err)?;
+    let stdout = Zeroizing::new(output.stdout);
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(Error::Backend(stderr.trim().to_string()));
+    }
+    Ok(stdout)
+}
+
+/// Returns the lowercase hex encoding of the bytes.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn encode_hex(bytes: &[u8]) -> Zeroizing<Vec<u8>> {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = Zeroizing::new(Vec::with_capacity(2 * bytes.len()));
+    for b in bytes {
+        hex.push(DIGITS[(b >> 4) as usize]);
+        hex.push(DIGITS[(b & 0xf) as usize]);
+    }
+    hex
+}
+
+/// Decodes a hex string, ignoring surrounding whitespace.
+#[cfg(any(target_os = "linux", target_os = "macos"))]
+fn decode_hex(hex: &[u8]) -> Option<Zeroizing<Vec<u8>>> {
+    let hex = hex.trim_ascii();
+    if !hex.len().is_multiple_of(2) {
+        return None;
+    }
+    let digit = |c: u8| (c as char).to_digit(16).map(|d| d as u8);
+    let mut bytes = Zeroizing::new(Vec::with_capacity(hex.len() / 2));
+    for pair in hex.chunks(2) {
+        bytes.push(digit(pair[0])? << 4 | digit(pair[1])?);
+    }
+    Some(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_memory_vault() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let share = sk_set.secret_key_share(4);
+        let mut vault = MemoryVault::new();
+
+        store_share(&mut vault, "share-4", &share).expect("store share");
+        assert_eq!(SK_SIZE, vault.unseal("share-4").expect("sealed").len());
+        assert_eq!(Ok(share), load_share(&vault, "share-4"));
+
+        vault.seal("corrupt", &[0xff; SK_SIZE]).expect("seal");
+        let err = Error::InvalidVaultEntry("corrupt".to_string());
+        assert_eq!(Err(err), load_share(&vault, "corrupt"));
+
+        vault.remove("share-4").expect("remove");
+        let err = Error::VaultEntryNotFound("share-4".to_string());
+        assert_eq!(Err(err), load_share(&vault, "share-4"));
+    }
+
+    #[test]
+    fn test_directory_vault() {
+        let dir = std::env::temp_dir().join(format!("tc-vault-{}", rand::random::<u64>()));
+        let mut vault = DirectoryVault::new(&dir).expect("create vault directory");
+        let share = rand::random();
+
+        store_share(&mut vault, "share.0", &share).expect("store share");
+        assert_eq!(Ok(share), load_share(&vault, "share.0"));
+        assert!(vault.seal("../escape", b"secret").is_err());
+        vault.remove("share.0").expect("remove");
+        vault
+            .remove("share.0")
+            .expect("removing a missing entry is a no-op");
+        let err = Error::VaultEntryNotFound("share.0".to_string());
+        assert_eq!(Err(err), load_share(&vault, "share.0"));
+        fs::remove_dir(dir).expect("vault directory is empty");
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "macos"))]
+    #[test]
+    fn test_hex() {
+        let bytes: Vec<u8> = (0..=255).collect();
+        let hex = encode_hex(&bytes);
+        assert_eq!(b"000102", &hex[..6]);
+        assert_eq!(Some(bytes), decode_hex(&hex).map(|b| b.to_vec()));
+        assert_eq!(None, decode_hex(b"0g"));
+    }
+}
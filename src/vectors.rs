@@ -0,0 +1,230 @@
+//! Export of deterministic test vectors, for checking other implementations against this one.
+//!
+//! [`generate`] derives a key set, signatures and, with the `encryption` feature, ciphertexts
+//! from a seed, and returns them together with every intermediate value an independent
+//! implementation needs to reproduce: the secret and public key shares, the messages' hashes to
+//! `G2`, and all signature and decryption shares. [`TestVectors::to_json`] serializes them as
+//! JSON, with all values hex-encoded:
+//!
+//! * Secret keys are 32-byte big-endian integers.
+//! * Public keys, signatures and other group elements use the compressed encoding, as in
+//!   `PublicKey::to_bytes` and `Signature::to_bytes`.
+//! * Share indices are the `i` passed to `SecretKeySet::secret_key_share`, i.e. share `i` is the
+//!   polynomial's value at `i + 1`.
+//!
+//! This module is only available with the `test-vectors` feature.
+
+use ff::{PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective};
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaChaRng;
+use serde::{Deserialize, Serialize};
+
+use crate::poly::Poly;
+use crate::util::to_hex as hex;
+use crate::{hash_g2, Fr, SecretKeySet};
+
+/// The lengths of the generated messages and plaintexts: empty, short, and longer than the
+/// threshold above which `hash_g1_g2` hashes the message first.
+const MESSAGE_LENGTHS: [usize; 3] = [0, 13, 100];
+
+/// A complete, internally consistent set of test vectors.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TestVectors {
+    /// The seed everything was derived from.
+    pub seed: u64,
+    /// The threshold: any `threshold + 1` shares can sign or decrypt.
+    pub threshold: usize,
+    /// The master secret key.
+    pub secret_key: String,
+    /// The master public key.
+    pub public_key: String,
+    /// The coefficients of the public key set's commitment, constant term first.
+    pub commitment: Vec<String>,
+    /// The participants' key shares.
+    pub shares: Vec<KeyShareVector>,
+    /// Messages with their signature shares and signatures.
+    pub signatures: Vec<SignatureVector>,
+    /// Ciphertexts with their decryption shares and plaintexts.
+    #[cfg(feature = "encryption")]
+    pub ciphertexts: Vec<CiphertextVector>,
+}
+
+/// A participant's key shares.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct KeyShareVector {
+    /// The share index.
+    pub index: usize,
+    /// The secret key share.
+    pub secret_key_share: String,
+    /// The public key share.
+    pub public_key_share: String,
+}
+
+/// A message signed by the committee.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct SignatureVector {
+    /// The message.
+    pub message: String,
+    /// The message's hash to `G2`, as computed by `hash_g2`.
+    pub hash: String,
+    /// Every participant's signature share, in order of their indices.
+    pub signature_shares: Vec<String>,
+    /// The signature, combined from the first `threshold + 1` shares.
+    pub signature: String,
+}
+
+/// A message encrypted to the committee.
+#[cfg(feature = "encryption")]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CiphertextVector {
+    /// The plaintext.
+    pub plaintext: String,
+    /// The ciphertext's first component, `U` in `G1`.
+    pub u: String,
+    /// The ciphertext's second component, `V`, the masked plaintext.
+    pub v: String,
+    /// The ciphertext's third component, `W` in `G2`.
+    pub w: String,
+    /// Every participant's decryption share, in order of their indices.
+    pub decryption_shares: Vec<String>,
+}
+
+impl TestVectors {
+    /// Returns the test vectors as pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("test vectors are serializable")
+    }
+
+    /// Parses test vectors from JSON.
+    pub fn from_json(json: &str) -> serde_json::Result<Self> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Generates test vectors for a key set with the given threshold and number of participants.
+///
+/// The same arguments always produce the same vectors.
+///
+/// # Panics
+///
+/// Panics if `num_shares` is not greater than `threshold`.
+pub fn generate(seed: u64, threshold: usize, num_shares: usize) -> TestVectors {
+    assert!(num_shares > threshold, "not enough shares to sign");
+    let mut rng = ChaChaRng::seed_from_u64(seed);
+    let poly = Poly::random(threshold, &mut rng);
+    let secret_key = fr_hex(&poly.evaluate(0));
+    let sk_set = SecretKeySet::from(poly);
+    let pk_set = sk_set.public_keys();
+    let sk_shares: Vec<_> = (0..num_shares)
+        .map(|i| sk_set.secret_key_share(i))
+        .collect();
+
+    let shares = sk_shares
+        .iter()
+        .enumerate()
+        .map(|(index, sk_share)| KeyShareVector {
+            index,
+            secret_key_share: fr_hex(&(sk_share.0).0),
+            public_key_share: hex(pk_set.public_key_share(index).to_bytes()),
+        })
+        .collect();
+
+    let signatures = MESSAGE_LENGTHS
+        .iter()
+        .map(|len| {
+            let msg: Vec<u8> = (0..*len).map(|_| rng.gen()).collect();
+            let sig_shares: Vec<_> = sk_shares.iter().map(|sk| sk.sign(&msg)).collect();
+            let sig = pk_set
+                .combine_signatures(sig_shares.iter().enumerate().take(threshold + 1))
+                .expect("enough shares");
+            SignatureVector {
+                hash: hex(hash_g2(&msg).into_affine().into_compressed()),
+                message: hex(&msg),
+                signature_shares: sig_shares.iter().map(|s| hex(s.to_bytes())).collect(),
+                signature: hex(sig.to_bytes()),
+            }
+        })
+        .collect();
+
+    #[cfg(feature = "encryption")]
+    let ciphertexts = MESSAGE_LENGTHS
+        .iter()
+        .map(|len| {
+            let plaintext: Vec<u8> = (0..*len).map(|_| rng.gen()).collect();
+            let ct = pk_set.public_key().encrypt_with_rng(&mut rng, &plaintext);
+            let dec_shares = sk_shares.iter().map(|sk| sk.decrypt_share_no_verify(&ct));
+            CiphertextVector {
+                plaintext: hex(&plaintext),
+                u: hex(ct.0.into_affine().into_compressed()),
+                v: hex(&ct.1),
+                w: hex(ct.2.into_affine().into_compressed()),
+                decryption_shares: dec_shares
+                    .map(|share| hex(share.0.into_affine().into_compressed()))
+                    .collect(),
+            }
+        })
+        .collect();
+
+    TestVectors {
+        seed,
+        threshold,
+        secret_key,
+        public_key: hex(pk_set.public_key().to_bytes()),
+        commitment: pk_set
+            .commit
+            .coeff
+            .iter()
+            .map(|c| hex(c.into_affine().into_compressed()))
+            .collect(),
+        shares,
+        signatures,
+        #[cfg(feature = "encryption")]
+        ciphertexts,
+    }
+}
+
+/// Returns the field element's big-endian representation in hexadecimal.
+fn fr_hex(fr: &Fr) -> String {
+    let mut bytes = Vec::new();
+    fr.into_repr()
+        .write_be(&mut bytes)
+        .expect("writing to a vector can't fail");
+    hex(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{PublicKey, Signature, PK_SIZE, SIG_SIZE};
+
+    fn decode<const N: usize>(s: &str) -> [u8; N] {
+        let mut bytes = [0u8; N];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).expect("valid hex");
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_vectors() {
+        let vectors = generate(42, 2, 4);
+        assert_eq!(vectors, generate(42, 2, 4));
+        assert_ne!(vectors, generate(43, 2, 4));
+        assert_eq!(4, vectors.shares.len());
+        assert_eq!(3, vectors.commitment.len());
+
+        let json = vectors.to_json();
+        assert_eq!(vectors, TestVectors::from_json(&json).expect("valid JSON"));
+
+        let pk = PublicKey::from_bytes(decode::<PK_SIZE>(&vectors.public_key)).unwrap();
+        for sig_vector in &vectors.signatures {
+            let sig = Signature::from_bytes(decode::<SIG_SIZE>(&sig_vector.signature)).unwrap();
+            let msg: Vec<u8> = (0..sig_vector.message.len() / 2)
+                .map(|i| u8::from_str_radix(&sig_vector.message[2 * i..2 * i + 2], 16).unwrap())
+                .collect();
+            assert!(pk.verify(&sig, msg));
+        }
+    }
+}
@@ -0,0 +1,175 @@
+//! A bounded cache of signature verification results.
+//!
+//! Gossip protocols often deliver the same signed message several times. A
+//! [`VerificationCache`] remembers the result of each verification, keyed by a fingerprint of the
+//! public key, the hash of the message and the signature bytes, so that verifying a duplicate
+//! costs two hashes instead of two pairings. When the cache is full, the oldest entries are
+//! evicted first.
+
+use std::collections::{HashMap, VecDeque};
+
+use crate::util::sha3_256;
+use crate::{PublicKey, PublicKeyShare, Signature, SignatureShare, SIG_SIZE};
+
+/// The key of a cached result: public key fingerprint, message hash and signature.
+type Key = ([u8; 32], [u8; 32], [u8; SIG_SIZE]);
+
+/// How often the cache was used.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// The number of verifications answered from the cache.
+    pub hits: u64,
+    /// The number of verifications that had to be computed.
+    pub misses: u64,
+    /// The number of entries evicted to make room for new ones.
+    pub evictions: u64,
+}
+
+/// A cache of the results of signature and signature share verifications.
+#[derive(Clone, Debug)]
+pub struct VerificationCache {
+    capacity: usize,
+    results: HashMap<Key, bool>,
+    order: VecDeque<Key>,
+    stats: CacheStats,
+}
+
+impl VerificationCache {
+    /// Creates a cache holding up to `capacity` results.
+    pub fn new(capacity: usize) -> Self {
+        VerificationCache {
+            capacity,
+            results: HashMap::new(),
+            order: VecDeque::new(),
+            stats: CacheStats::default(),
+        }
+    }
+
+    /// Returns the maximum number of results.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Changes the maximum number of results, evicting the oldest ones if necessary.
+    pub fn set_capacity(&mut self, capacity: usize) {
+        self.capacity = capacity;
+        self.evict();
+    }
+
+    /// Returns the number of cached results.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns `true` if no results are cached.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Returns the cache's statistics.
+    pub fn stats(&self) -> CacheStats {
+        self.stats
+    }
+
+    /// Removes all cached results, keeping the statistics.
+    pub fn clear(&mut self) {
+        self.results.clear();
+        self.order.clear();
+    }
+
+    /// Returns `true` if the signature matches the message and public key.
+    pub fn verify<M: AsRef<[u8]>>(&mut self, pk: &PublicKey, sig: &Signature, msg: M) -> bool {
+        let key = (
+            sha3_256(&pk.to_bytes()),
+            sha3_256(msg.as_ref()),
+            sig.to_bytes(),
+        );
+        self.get_or_insert(key, || pk.verify(sig, msg))
+    }
+
+    /// Returns `true` if the signature share matches the message and public key share.
+    pub fn verify_share<M: AsRef<[u8]>>(
+        &mut self,
+        pk_share: &PublicKeyShare,
+        sig: &SignatureShare,
+        msg: M,
+    ) -> bool {
+        let key = (
+            sha3_256(&pk_share.to_bytes()),
+            sha3_256(msg.as_ref()),
+            sig.to_bytes(),
+        );
+        self.get_or_insert(key, || pk_share.verify(sig, msg))
+    }
+
+    /// Returns the cached result, or computes and caches it.
+    fn get_or_insert<F: FnOnce() -> bool>(&mut self, key: Key, verify: F) -> bool {
+        if let Some(result) = self.results.get(&key) {
+            self.stats.hits += 1;
+            return *result;
+        }
+        self.stats.misses += 1;
+        let result = verify();
+        if self.capacity > 0 {
+            self.results.insert(key, result);
+            self.order.push_back(key);
+            self.evict();
+        }
+        result
+    }
+
+    /// Evicts the oldest results until the cache is within its capacity.
+    fn evict(&mut self) {
+        while self.results.len() > self.capacity {
+            if let Some(key) = self.order.pop_front() {
+                self.results.remove(&key);
+                self.stats.evictions += 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_cache() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(1, &mut rng);
+        let pk = sk_set.public_keys().public_key();
+        let sig = sk_set.secret_key().sign("msg");
+        let mut cache = VerificationCache::new(2);
+
+        assert!(cache.verify(&pk, &sig, "msg"));
+        assert!(cache.verify(&pk, &sig, "msg"));
+        assert!(!cache.verify(&pk, &sig, "other"));
+        assert!(!cache.verify(&pk, &sig, "other"));
+        let stats = CacheStats {
+            hits: 2,
+            misses: 2,
+            evictions: 0,
+        };
+        assert_eq!(stats, cache.stats());
+
+        // A share is cached under its public key share, so it doesn't hit the master key's entry.
+        let pk_share = sk_set.public_keys().public_key_share(0);
+        let share = sk_set.secret_key_share(0).sign("msg");
+        assert!(cache.verify_share(&pk_share, &share, "msg"));
+        assert_eq!((2, 3), (cache.len(), cache.stats().misses));
+
+        // The oldest result was evicted.
+        assert_eq!(1, cache.stats().evictions);
+        assert!(!cache.verify(&pk, &sig, "other"));
+        assert_eq!(3, cache.stats().hits);
+        assert!(cache.verify(&pk, &sig, "msg"));
+        assert_eq!(4, cache.stats().misses);
+
+        cache.set_capacity(0);
+        assert!(cache.is_empty());
+        assert!(cache.verify(&pk, &sig, "msg"));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,374 @@
+//! Allocation-free signature verification.
+//!
+//! This module is meant for constrained environments, such as microcontrollers and bootloaders,
+//! that only need to check a committee's signature against a known master public key. The inputs
+//! are the canonical compressed encodings produced by `PublicKey::to_bytes` and
+//! `Signature::to_bytes`, and every intermediate value lives on the stack: Unlike
+//! `PublicKey::verify`, the pairing check here does not go through `pairing`'s prepared `G2`
+//! points or `Engine::miller_loop`, both of which collect the line coefficients in a `Vec`.
+//! Instead, the Miller loop evaluates the lines on the fly for both pairs at once.
+//!
+//! Verification is stricter than `PublicKey::verify`: The identity element is rejected both as a
+//! public key and as a signature.
+
+use ff::{BitIterator, Field, PrimeField, PrimeFieldRepr};
+use group::{CurveAffine, CurveProjective, EncodedPoint};
+use pairing::bls12_381::{
+    Bls12, Fq, Fq12, Fq2, FqRepr, G1Affine, G1Compressed, G2Affine, G2Compressed,
+};
+use pairing::Engine;
+
+use crate::{hash_g2, PK_SIZE, SIG_SIZE};
+
+/// The BLS parameter `x` for BLS12-381 is `-0xd201000000010000`.
+const BLS_X: u64 = 0xd201_0000_0001_0000;
+
+/// The size of a base field element's big-endian representation in bytes.
+const FQ_SIZE: usize = 48;
+
+/// Returns `true` if `sig` is a valid signature of `msg` under the public key `pk`, both given in
+/// their compressed representation.
+///
+/// This is equivalent to `PublicKey::verify`, except that identity elements are rejected.
+pub fn verify<M: AsRef<[u8]>>(pk: &[u8; PK_SIZE], sig: &[u8; SIG_SIZE], msg: M) -> bool {
+    verify_g2(pk, sig, hash_g2(msg).into_affine())
+}
+
+/// Returns `true` if `sig` is a valid signature of the element `hash` of `G2` under the public
+/// key `pk`, both given in their compressed representation.
+pub fn verify_g2(pk: &[u8; PK_SIZE], sig: &[u8; SIG_SIZE], hash: G2Affine) -> bool {
+    let (pk, sig) = match (decode_g1(pk), decode_g2(sig)) {
+        (Some(pk), Some(sig)) => (pk, sig),
+        _ => return false,
+    };
+    if hash.is_zero() {
+        return false;
+    }
+    // `e(pk, hash) == e(g1, sig)` if and only if `e(pk, hash) * e(-g1, sig) == 1`.
+    let mut minus_g1 = G1Affine::one();
+    minus_g1.negate();
+    count!(Pairing, 2);
+    let f = miller_loop(&[(pk, hash), (minus_g1, sig)]);
+    Bls12::final_exponentiation(&f) == Some(Fq12::one())
+}
+
+/// Decodes a compressed, non-identity element of `G1`, checking that it is in the subgroup.
+fn decode_g1(bytes: &[u8; PK_SIZE]) -> Option<G1Affine> {
+    let mut compressed = G1Compressed::empty();
+    compressed.as_mut().copy_from_slice(bytes);
+    compressed.into_affine().ok().filter(|p| !p.is_zero())
+}
+
+/// Decodes a compressed, non-identity element of `G2`, checking that it is in the subgroup.
+fn decode_g2(bytes: &[u8; SIG_SIZE]) -> Option<G2Affine> {
+    let mut compressed = G2Compressed::empty();
+    compressed.as_mut().copy_from_slice(bytes);
+    compressed.into_affine().ok().filter(|p| !p.is_zero())
+}
+
+/// Returns the affine coordinates of a non-identity element of `G1`.
+fn g1_coordinates(p: &G1Affine) -> (Fq, Fq) {
+    let uncomp = p.into_uncompressed();
+    let bytes = uncomp.as_ref();
+    (read_fq(&bytes[..FQ_SIZE]), read_fq(&bytes[FQ_SIZE..]))
+}
+
+/// Returns the affine coordinates of a non-identity element of `G2`.
+fn g2_coordinates(q: &G2Affine) -> (Fq2, Fq2) {
+    let uncomp = q.into_uncompressed();
+    let bytes = uncomp.as_ref();
+    // The uncompressed encoding is `x.c1 || x.c0 || y.c1 || y.c0`.
+    let x = Fq2 {
+        c1: read_fq(&bytes[..FQ_SIZE]),
+        c0: read_fq(&bytes[FQ_SIZE..2 * FQ_SIZE]),
+    };
+    let y = Fq2 {
+        c1: read_fq(&bytes[2 * FQ_SIZE..3 * FQ_SIZE]),
+        c0: read_fq(&bytes[3 * FQ_SIZE..]),
+    };
+    (x, y)
+}
+
+/// Reads a base field element from its big-endian representation, ignoring the three flag bits
+/// that the point encodings keep in the leading byte.
+fn read_fq(bytes: &[u8]) -> Fq {
+    let mut buf = [0u8; FQ_SIZE];
+    buf.copy_from_slice(bytes);
+    buf[0] &= 0x1f;
+    let mut repr = FqRepr::default();
+    repr.read_be(&buf[..]).expect("buffer has the right length");
+    Fq::from_repr(repr).expect("coordinates of a decoded point are in the field")
+}
+
+/// A point of the twist in Jacobian coordinates, used to compute the Miller loop lines.
+#[derive(Clone, Copy)]
+struct G2Jacobian {
+    x: Fq2,
+    y: Fq2,
+    z: Fq2,
+}
+
+/// Computes the product of the Miller loops of the given pairs, without final exponentiation.
+///
+/// This follows `pairing`'s `Engine::miller_loop` for BLS12-381, but computes each line's
+/// coefficients when they are needed instead of storing them in a precomputed `G2Prepared`.
+fn miller_loop(pairs: &[(G1Affine, G2Affine); 2]) -> Fq12 {
+    let mut ps = [(Fq::zero(), Fq::zero()); 2];
+    let mut qs = [(Fq2::zero(), Fq2::zero()); 2];
+    let mut rs = [G2Jacobian {
+        x: Fq2::zero(),
+        y: Fq2::zero(),
+        z: Fq2::one(),
+    }; 2];
+    for (k, (p, q)) in pairs.iter().enumerate() {
+        ps[k] = g1_coordinates(p);
+        qs[k] = g2_coordinates(q);
+        rs[k].x = qs[k].0;
+        rs[k].y = qs[k].1;
+    }
+
+    let mut f = Fq12::one();
+    let mut found_one = false;
+    for bit in BitIterator::new([BLS_X >> 1]) {
+        if !found_one {
+            found_one = bit;
+            continue;
+        }
+        for k in 0..pairs.len() {
+            let coeffs = doubling_step(&mut rs[k]);
+            ell(&mut f, &coeffs, &ps[k]);
+        }
+        if bit {
+            for k in 0..pairs.len() {
+                let coeffs = addition_step(&mut rs[k], &qs[k]);
+                ell(&mut f, &coeffs, &ps[k]);
+            }
+        }
+        f.square();
+    }
+    for k in 0..pairs.len() {
+        let coeffs = doubling_step(&mut rs[k]);
+        ell(&mut f, &coeffs, &ps[k]);
+    }
+    // The BLS parameter is negative.
+    f.conjugate();
+    f
+}
+
+/// Multiplies `f` by the line with the given coefficients, evaluated at `p`.
+fn ell(f: &mut Fq12, coeffs: &(Fq2, Fq2, Fq2), p: &(Fq, Fq)) {
+    let (ref px, ref py) = *p;
+    let mut c0 = coeffs.0;
+    let mut c1 = coeffs.1;
+    c0.c0.mul_assign(py);
+    c0.c1.mul_assign(py);
+    c1.c0.mul_assign(px);
+    c1.c1.mul_assign(px);
+    f.mul_by_014(&coeffs.2, &c1, &c0);
+}
+
+/// Doubles `r` and returns the coefficients of the tangent line.
+///
+/// Adaptation of Algorithm 26, <https://eprint.iacr.org/2010/354.pdf>.
+fn doubling_step(r: &mut G2Jacobian) -> (Fq2, Fq2, Fq2) {
+    let mut tmp0 = r.x;
+    tmp0.square();
+
+    let mut tmp1 = r.y;
+    tmp1.square();
+
+    let mut tmp2 = tmp1;
+    tmp2.square();
+
+    let mut tmp3 = tmp1;
+    tmp3.add_assign(&r.x);
+    tmp3.square();
+    tmp3.sub_assign(&tmp0);
+    tmp3.sub_assign(&tmp2);
+    tmp3.double();
+
+    let mut tmp4 = tmp0;
+    tmp4.double();
+    tmp4.add_assign(&tmp0);
+
+    let mut tmp6 = r.x;
+    tmp6.add_assign(&tmp4);
+
+    let mut tmp5 = tmp4;
+    tmp5.square();
+
+    let mut zsquared = r.z;
+    zsquared.square();
+
+    r.x = tmp5;
+    r.x.sub_assign(&tmp3);
+    r.x.sub_assign(&tmp3);
+
+    r.z.add_assign(&r.y);
+    r.z.square();
+    r.z.sub_assign(&tmp1);
+    r.z.sub_assign(&zsquared);
+
+    r.y = tmp3;
+    r.y.sub_assign(&r.x);
+    r.y.mul_assign(&tmp4);
+
+    tmp2.double();
+    tmp2.double();
+    tmp2.double();
+
+    r.y.sub_assign(&tmp2);
+
+    tmp3 = tmp4;
+    tmp3.mul_assign(&zsquared);
+    tmp3.double();
+    tmp3.negate();
+
+    tmp6.square();
+    tmp6.sub_assign(&tmp0);
+    tmp6.sub_assign(&tmp5);
+
+    tmp1.double();
+    tmp1.double();
+
+    tmp6.sub_assign(&tmp1);
+
+    tmp0 = r.z;
+    tmp0.mul_assign(&zsquared);
+    tmp0.double();
+
+    (tmp0, tmp3, tmp6)
+}
+
+/// Adds the affine point `q` to `r` and returns the coefficients of the line through both.
+///
+/// Adaptation of Algorithm 27, <https://eprint.iacr.org/2010/354.pdf>.
+fn addition_step(r: &mut G2Jacobian, q: &(Fq2, Fq2)) -> (Fq2, Fq2, Fq2) {
+    let (ref qx, ref qy) = *q;
+
+    let mut zsquared = r.z;
+    zsquared.square();
+
+    let mut ysquared = *qy;
+    ysquared.square();
+
+    let mut t0 = zsquared;
+    t0.mul_assign(qx);
+
+    let mut t1 = *qy;
+    t1.add_assign(&r.z);
+    t1.square();
+    t1.sub_assign(&ysquared);
+    t1.sub_assign(&zsquared);
+    t1.mul_assign(&zsquared);
+
+    let mut t2 = t0;
+    t2.sub_assign(&r.x);
+
+    let mut t3 = t2;
+    t3.square();
+
+    let mut t4 = t3;
+    t4.double();
+    t4.double();
+
+    let mut t5 = t4;
+    t5.mul_assign(&t2);
+
+    let mut t6 = t1;
+    t6.sub_assign(&r.y);
+    t6.sub_assign(&r.y);
+
+    let mut t9 = t6;
+    t9.mul_assign(qx);
+
+    let mut t7 = t4;
+    t7.mul_assign(&r.x);
+
+    r.x = t6;
+    r.x.square();
+    r.x.sub_assign(&t5);
+    r.x.sub_assign(&t7);
+    r.x.sub_assign(&t7);
+
+    r.z.add_assign(&t2);
+    r.z.square();
+    r.z.sub_assign(&zsquared);
+    r.z.sub_assign(&t3);
+
+    let mut t10 = *qy;
+    t10.add_assign(&r.z);
+
+    let mut t8 = t7;
+    t8.sub_assign(&r.x);
+    t8.mul_assign(&t6);
+
+    t0 = r.y;
+    t0.mul_assign(&t5);
+    t0.double();
+
+    r.y = t8;
+    r.y.sub_assign(&t0);
+
+    t10.square();
+    t10.sub_assign(&ysquared);
+
+    let mut ztsquared = r.z;
+    ztsquared.square();
+
+    t10.sub_assign(&ztsquared);
+
+    t9.double();
+    t9.sub_assign(&t10);
+
+    t10 = r.z;
+    t10.double();
+
+    t6.negate();
+
+    t1 = t6;
+    t1.double();
+
+    (t10, t1, t9)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::{SecretKey, Signature};
+
+    #[test]
+    fn test_verify_matches_public_key() {
+        let sk0 = SecretKey::random();
+        let sk1 = SecretKey::random();
+        let pk0 = sk0.public_key().to_bytes();
+        let msg0 = b"Real news";
+        let msg1 = b"Fake news";
+        assert!(verify(&pk0, &sk0.sign(msg0).to_bytes(), msg0));
+        assert!(!verify(&pk0, &sk1.sign(msg0).to_bytes(), msg0)); // Wrong key.
+        assert!(!verify(&pk0, &sk0.sign(msg1).to_bytes(), msg0)); // Wrong message.
+    }
+
+    #[test]
+    fn test_verify_rejects_invalid_encodings() {
+        let sk = SecretKey::random();
+        let msg = b"Totally real news";
+        let pk = sk.public_key().to_bytes();
+        let sig = sk.sign(msg).to_bytes();
+
+        // Garbage bytes don't decode to group elements.
+        assert!(!verify(&[0xff; PK_SIZE], &sig, msg));
+        assert!(!verify(&pk, &[0xff; SIG_SIZE], msg));
+
+        // The identity is rejected, even though it "verifies" with the identity public key.
+        let zero_sk = SecretKey::default();
+        let zero_sig: Signature = zero_sk.sign(msg);
+        assert!(zero_sk.public_key().verify(&zero_sig, msg));
+        assert!(!verify(
+            &zero_sk.public_key().to_bytes(),
+            &zero_sig.to_bytes(),
+            msg
+        ));
+    }
+}
@@ -0,0 +1,200 @@
+//! A threshold verifiable random function.
+//!
+//! BLS signatures are unique: For a given key and message, there is exactly one valid signature.
+//! Hashing the signature therefore gives a pseudorandom output that anyone with the public key can
+//! check, and that nobody can predict without the secret key, or `threshold + 1` of its shares.
+//!
+//! The VRF signs the input under its own domain separator, so that a proof is never a valid
+//! signature of the same message and vice versa, and hashes the proof together with the public key
+//! into a uniformly distributed 32-byte output.
+//!
+//! ```
+//! use threshold_crypto::SecretKeySet;
+//!
+//! let sk_set = SecretKeySet::random(1, &mut rand::thread_rng());
+//! let pk_set = sk_set.public_keys();
+//! let shares: Vec<_> = (0..2).map(|i| sk_set.secret_key_share(i).vrf("epoch 7")).collect();
+//! let (output, proof) = pk_set.combine_vrf(shares.iter().enumerate()).unwrap();
+//! assert_eq!(Some(output), pk_set.public_key().verify_vrf("epoch 7", &proof));
+//! ```
+
+use std::fmt;
+
+use hex_fmt::HexFmt;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{DecodeResult, Result};
+use crate::util::sha3_256;
+use crate::{
+    hash_g2, IntoFr, PublicKey, PublicKeySet, PublicKeyShare, SecretKey, SecretKeyShare, Signature,
+    SignatureShare, G2, SIG_SIZE,
+};
+
+/// The domain separator for VRF inputs.
+const INPUT_DST: &[u8] = b"threshold_crypto VRF input";
+
+/// The domain separator for VRF outputs.
+const OUTPUT_DST: &[u8] = b"threshold_crypto VRF output";
+
+/// The pseudorandom output of the VRF.
+#[derive(Deserialize, Serialize, Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct VrfOutput([u8; 32]);
+
+impl fmt::Debug for VrfOutput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "VrfOutput({:0.10})", HexFmt(&self.0))
+    }
+}
+
+impl AsRef<[u8]> for VrfOutput {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl VrfOutput {
+    /// Returns the output's bytes.
+    pub fn to_bytes(&self) -> [u8; 32] {
+        self.0
+    }
+}
+
+/// A proof that a VRF output belongs to an input and public key.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct VrfProof(Signature);
+
+impl VrfProof {
+    /// Returns the proof with the given representation, if valid.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        Ok(VrfProof(Signature::from_slice(bytes)?))
+    }
+
+    /// Returns a byte string representation of the proof.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        self.0.to_bytes()
+    }
+}
+
+/// A share of a VRF proof, created with a secret key share.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct VrfProofShare(SignatureShare);
+
+impl VrfProofShare {
+    /// Returns the share with the given representation, if valid.
+    pub fn from_slice(bytes: &[u8]) -> DecodeResult<Self> {
+        Ok(VrfProofShare(SignatureShare::from_slice(bytes)?))
+    }
+
+    /// Returns a byte string representation of the share.
+    pub fn to_bytes(&self) -> [u8; SIG_SIZE] {
+        self.0.to_bytes()
+    }
+}
+
+impl SecretKey {
+    /// Returns the VRF output for the input, and its proof.
+    pub fn vrf<M: AsRef<[u8]>>(&self, input: M) -> (VrfOutput, VrfProof) {
+        let proof = VrfProof(self.sign_g2(hash_input(input)));
+        (output(&self.public_key(), &proof), proof)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns a share of the VRF proof for the input.
+    pub fn vrf<M: AsRef<[u8]>>(&self, input: M) -> VrfProofShare {
+        VrfProofShare(self.sign_g2(hash_input(input)))
+    }
+}
+
+impl PublicKey {
+    /// Returns the VRF output if the proof is valid for the input, or `None` otherwise.
+    pub fn verify_vrf<M: AsRef<[u8]>>(&self, input: M, proof: &VrfProof) -> Option<VrfOutput> {
+        if self.verify_g2(&proof.0, hash_input(input)) {
+            Some(output(self, proof))
+        } else {
+            None
+        }
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns `true` if the proof share is valid for the input.
+    pub fn verify_vrf<M: AsRef<[u8]>>(&self, input: M, share: &VrfProofShare) -> bool {
+        self.verify_g2(&share.0, hash_input(input))
+    }
+}
+
+impl PublicKeySet {
+    /// Combines the proof shares into the VRF output and its proof for the main public key.
+    ///
+    /// Like `combine_signatures`, this doesn't check the shares' validity, and only returns an
+    /// error if there is a duplicate index or too few shares. Combining invalid shares yields a
+    /// proof that doesn't verify.
+    pub fn combine_vrf<'a, T, I>(&self, shares: I) -> Result<(VrfOutput, VrfProof)>
+    where
+        I: IntoIterator<Item = (T, &'a VrfProofShare)>,
+        T: IntoFr,
+    {
+        let sig = self.combine_signatures(shares.into_iter().map(|(i, share)| (i, &share.0)))?;
+        let proof = VrfProof(sig);
+        Ok((output(&self.public_key(), &proof), proof))
+    }
+}
+
+/// Returns the hash of the VRF input in `G2`.
+fn hash_input<M: AsRef<[u8]>>(input: M) -> G2 {
+    let mut bytes = INPUT_DST.to_vec();
+    bytes.extend(input.as_ref());
+    hash_g2(bytes)
+}
+
+/// Returns the output for the public key and proof.
+fn output(pk: &PublicKey, proof: &VrfProof) -> VrfOutput {
+    let mut bytes = OUTPUT_DST.to_vec();
+    bytes.extend(pk.to_bytes().as_ref());
+    bytes.extend(proof.to_bytes().as_ref());
+    VrfOutput(sha3_256(&bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::SecretKeySet;
+
+    #[test]
+    fn test_threshold_vrf() {
+        let mut rng = rand::thread_rng();
+        let sk_set = SecretKeySet::random(2, &mut rng);
+        let pk_set = sk_set.public_keys();
+        let pk = pk_set.public_key();
+        let shares: Vec<VrfProofShare> = (0..5)
+            .map(|i| {
+                let share = sk_set.secret_key_share(i).vrf("input");
+                assert!(pk_set.public_key_share(i).verify_vrf("input", &share));
+                assert!(!pk_set.public_key_share(i).verify_vrf("other", &share));
+                share
+            })
+            .collect();
+
+        // Any `threshold + 1` shares give the same output, which is the secret key's.
+        let (output, proof) = pk_set
+            .combine_vrf(shares.iter().enumerate().take(3))
+            .expect("enough shares");
+        let (other_output, _) = pk_set
+            .combine_vrf(shares.iter().enumerate().skip(2))
+            .expect("enough shares");
+        assert_eq!(output, other_output);
+        assert_eq!((output, proof.clone()), sk_set.secret_key().vrf("input"));
+        assert_eq!(Some(output), pk.verify_vrf("input", &proof));
+        assert_eq!(None, pk.verify_vrf("other", &proof));
+        let bytes = proof.to_bytes();
+        assert_eq!(Ok(proof.clone()), VrfProof::from_slice(&bytes));
+
+        // Proofs and signatures are not interchangeable.
+        assert!(!pk.verify(&proof.0, "input"));
+        let sig = sk_set.secret_key().sign("input");
+        assert_eq!(None, pk.verify_vrf("input", &VrfProof(sig)));
+        assert_ne!(output, sk_set.secret_key().vrf("other").0);
+    }
+}
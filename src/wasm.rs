@@ -0,0 +1,200 @@
+//! WebAssembly exports for dealing key sets, and signing and combining signature shares.
+//!
+//! The functions and the [`Dealing`] class are exported with `#[wasm_bindgen]`, under camel-case
+//! names such as `signShare`. They only take and return integers, byte slices, `Vec<u8>` and
+//! `String` errors, so a JavaScript caller passes `Uint8Array`s and catches exceptions. Keys,
+//! shares and signatures use their canonical `to_bytes` encodings, and lists of signature shares
+//! are concatenated.
+//!
+//! On `wasm32-unknown-unknown`, the feature enables `getrandom`'s `wasm-bindgen` support, so
+//! [`deal_random`] takes its seed from `crypto.getRandomValues`. [`deal`] instead expands a
+//! 32-byte seed that the caller provides. Secret key shares are copied into the JavaScript heap
+//! by `Dealing.secretKeyShare`, and zeroed in WebAssembly memory.
+//!
+//! This module is only available with the `wasm` feature. To build it for the browser:
+//!
+//! ```text
+//! cargo build --target wasm32-unknown-unknown --no-default-features --features wasm
+//! ```
+//!
+//! ```
+//! use threshold_crypto::wasm;
+//!
+//! let dealing = wasm::deal(1, 3, &[7; 32]).unwrap();
+//! let pk_set = dealing.public_key_set();
+//! let mut shares = Vec::new();
+//! for i in 0..2 {
+//!     shares.extend(wasm::sign_share(&dealing.secret_key_share(i).unwrap(), b"msg").unwrap());
+//! }
+//! let sig = wasm::combine_signatures(&pk_set, &[0, 1], &shares).unwrap();
+//! assert_eq!(Ok(true), wasm::verify(&pk_set, &sig, b"msg"));
+//! ```
+
+use ff::{PrimeField, PrimeFieldRepr};
+use rand::rngs::OsRng;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaChaRng;
+use wasm_bindgen::prelude::wasm_bindgen;
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::{PublicKeySet, SecretKeySet, SecretKeyShare, Signature, SignatureShare};
+use crate::{SIG_SIZE, SK_SIZE};
+
+/// The length of the seed that `deal` expects.
+pub const SEED_SIZE: usize = 32;
+
+/// A key set dealt by `deal`.
+#[wasm_bindgen]
+pub struct Dealing {
+    sk_set: SecretKeySet,
+    num_shares: u32,
+}
+
+#[wasm_bindgen]
+impl Dealing {
+    /// Returns the encoded public key set.
+    #[wasm_bindgen(js_name = publicKeySet)]
+    pub fn public_key_set(&self) -> Vec<u8> {
+        self.sk_set.public_keys().to_bytes()
+    }
+
+    /// Returns the encoded secret key share with index `i` as a new JavaScript array, or
+    /// `undefined` if `i` is not less than the number of shares.
+    #[cfg(target_arch = "wasm32")]
+    #[wasm_bindgen(js_name = secretKeyShare)]
+    pub fn secret_key_share_array(&self, i: u32) -> Option<js_sys::Uint8Array> {
+        let share = self.secret_key_share(i)?;
+        Some(js_sys::Uint8Array::from(&share[..]))
+    }
+}
+
+impl Dealing {
+    /// Returns the encoded secret key share with index `i`, or `None` if `i` is not less than
+    /// the number of shares.
+    pub fn secret_key_share(&self, i: u32) -> Option<Zeroizing<Vec<u8>>> {
+        if i >= self.num_shares {
+            return None;
+        }
+        Some(share_bytes(&self.sk_set.secret_key_share(i as u64)))
+    }
+}
+
+/// Deals a key set with the given threshold and number of shares, generated from the seed.
+///
+/// The seed must be `SEED_SIZE` uniformly random bytes, and must not be used again.
+#[wasm_bindgen]
+pub fn deal(threshold: u32, num_shares: u32, seed: &[u8]) -> Result<Dealing, String> {
+    if seed.len() != SEED_SIZE {
+        return Err(format!("the seed must have {} bytes", SEED_SIZE));
+    }
+    if num_shares <= threshold {
+        return Err("the number of shares must exceed the threshold".to_string());
+    }
+    let mut rng_seed = Zeroizing::new([0u8; SEED_SIZE]);
+    rng_seed.copy_from_slice(seed);
+    let mut rng = ChaChaRng::from_seed(*rng_seed);
+    let sk_set = SecretKeySet::try_random(threshold as usize, &mut rng).map_err(err)?;
+    Ok(Dealing { sk_set, num_shares })
+}
+
+/// Deals a key set with the given threshold and number of shares, from a seed taken from the
+/// operating system or, in the browser, from `crypto.getRandomValues`.
+#[wasm_bindgen(js_name = dealRandom)]
+pub fn deal_random(threshold: u32, num_shares: u32) -> Result<Dealing, String> {
+    let mut seed = Zeroizing::new([0u8; SEED_SIZE]);
+    OsRng.try_fill_bytes(&mut *seed).map_err(err)?;
+    deal(threshold, num_shares, &*seed)
+}
+
+/// Signs the message with the encoded secret key share, and returns the signature share.
+#[wasm_bindgen(js_name = signShare)]
+pub fn sign_share(share: &[u8], msg: &[u8]) -> Result<Vec<u8>, String> {
+    let share = SecretKeyShare::from_slice(share).map_err(err)?;
+    Ok(share.sign(msg).to_bytes().to_vec())
+}
+
+/// Returns whether the signature share is valid for the share index and message.
+#[wasm_bindgen(js_name = verifyShare)]
+pub fn verify_share(pk_set: &[u8], i: u32, share: &[u8], msg: &[u8]) -> Result<bool, String> {
+    let pk_set = PublicKeySet::from_slice(pk_set).map_err(err)?;
+    let share = SignatureShare::from_slice(share).map_err(err)?;
+    Ok(pk_set.public_key_share(i as u64).verify(&share, msg))
+}
+
+/// Combines the concatenated signature shares with the given indices into a signature.
+///
+/// The shares are not verified: Use `verify_share` first if they could be invalid.
+#[wasm_bindgen(js_name = combineSignatures)]
+pub fn combine_signatures(
+    pk_set: &[u8],
+    indices: &[u32],
+    shares: &[u8],
+) -> Result<Vec<u8>, String> {
+    if shares.len() != indices.len() * SIG_SIZE {
+        return Err(format!("expected {} signature shares", indices.len()));
+    }
+    let pk_set = PublicKeySet::from_slice(pk_set).map_err(err)?;
+    let shares = shares
+        .chunks(SIG_SIZE)
+        .map(SignatureShare::from_slice)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(err)?;
+    let indexed = indices.iter().map(|i| *i as u64).zip(&shares);
+    let sig = pk_set.combine_signatures(indexed).map_err(err)?;
+    Ok(sig.to_bytes().to_vec())
+}
+
+/// Returns whether the signature is valid for the encoded public key set's master key.
+#[wasm_bindgen]
+pub fn verify(pk_set: &[u8], sig: &[u8], msg: &[u8]) -> Result<bool, String> {
+    let pk_set = PublicKeySet::from_slice(pk_set).map_err(err)?;
+    let sig = Signature::from_slice(sig).map_err(err)?;
+    Ok(pk_set.public_key().verify(&sig, msg))
+}
+
+/// Returns the key share's big-endian representation.
+fn share_bytes(share: &SecretKeyShare) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(SK_SIZE));
+    let mut repr = ((share.0).0).into_repr();
+    repr.write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    repr.0.zeroize();
+    bytes
+}
+
+/// Converts an error to the message that is passed to JavaScript.
+fn err<E: ToString>(err: E) -> String {
+    err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wasm_wrappers() {
+        let dealing = deal(1, 3, &[1; SEED_SIZE]).expect("deal");
+        let pk_set = dealing.public_key_set();
+        assert!(dealing.secret_key_share(3).is_none());
+        let share2 = dealing.secret_key_share(2).expect("share 2");
+        let sig2 = sign_share(&share2, b"msg").expect("sign");
+        assert_eq!(Ok(true), verify_share(&pk_set, 2, &sig2, b"msg"));
+        assert_eq!(Ok(false), verify_share(&pk_set, 1, &sig2, b"msg"));
+        let share0 = dealing.secret_key_share(0).expect("share 0");
+        let mut shares = sign_share(&share0, b"msg").expect("sign");
+        shares.extend(sig2);
+        let sig = combine_signatures(&pk_set, &[0, 2], &shares).expect("combine");
+        assert_eq!(Ok(true), verify(&pk_set, &sig, b"msg"));
+        assert_eq!(Ok(false), verify(&pk_set, &sig, b"other"));
+
+        // The same seed deals the same keys.
+        let again = deal(1, 3, &[1; SEED_SIZE]).expect("deal");
+        assert_eq!(pk_set, again.public_key_set());
+        assert!(deal(1, 3, &[1; 16]).is_err());
+        assert!(deal(2, 2, &[1; SEED_SIZE]).is_err());
+        assert!(combine_signatures(&pk_set, &[0], &shares).is_err());
+
+        let random = deal_random(1, 3).expect("deal");
+        assert_ne!(pk_set, random.public_key_set());
+    }
+}
@@ -0,0 +1,462 @@
+//! Weighted threshold key sets, where participants hold different numbers of shares.
+//!
+//! A [`WeightedSecretKeySet`] assigns each participant a weight, and hands participant `p` a
+//! [`ShareBundle`] of as many consecutive shares of an ordinary `SecretKeySet` as its weight. Any
+//! group of participants whose total weight exceeds the threshold can sign and decrypt: Each
+//! signs with all shares in its bundle, and [`WeightedPublicKeySet`] combines the bundles. The
+//! combined signatures and plaintexts are the same as those of the underlying key set.
+//!
+//! ```
+//! use threshold_crypto::weighted::WeightedSecretKeySet;
+//!
+//! let mut rng = rand::thread_rng();
+//! // Participant 0 has weight 3, participants 1 and 2 have weight 1. Signing needs weight 4.
+//! let sk_set = WeightedSecretKeySet::random(3, vec![3, 1, 1], &mut rng).unwrap();
+//! let pk_set = sk_set.public_keys();
+//! let bundles: Vec<_> = (0..2)
+//!     .map(|p| sk_set.share_bundle(p).unwrap().sign("msg"))
+//!     .collect();
+//! let sig = pk_set.combine_signatures(&bundles).unwrap();
+//! assert!(pk_set.public_key().verify(&sig, "msg"));
+//! assert!(pk_set.combine_signatures(&bundles[1..]).is_err());
+//! ```
+
+use std::collections::BTreeSet;
+use std::convert::TryFrom;
+
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{PublicKey, PublicKeySet, SecretKeySet, SecretKeyShare, Signature, SignatureShare};
+
+/// A secret key set whose participants hold as many shares as their weights.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct WeightedSecretKeySet {
+    sk_set: SecretKeySet,
+    weights: Vec<usize>,
+}
+
+impl WeightedSecretKeySet {
+    /// Creates a random key set where participants with a total weight greater than `threshold`
+    /// can sign and decrypt.
+    ///
+    /// Returns `Error::InvalidAccessStructure` if a weight is zero, or if the total weight
+    /// doesn't exceed the threshold.
+    pub fn random<R: Rng>(threshold: usize, weights: Vec<usize>, rng: &mut R) -> Result<Self> {
+        let sk_set = SecretKeySet::try_random(threshold, rng)?;
+        WeightedSecretKeySet::new(sk_set, weights)
+    }
+
+    /// Assigns the shares of the key set to participants with the given weights: Participant `p`
+    /// gets the `weights[p]` shares following those of participants `0` to `p - 1`.
+    ///
+    /// Returns `Error::InvalidAccessStructure` if a weight is zero, or if the total weight
+    /// doesn't exceed the threshold.
+    pub fn new(sk_set: SecretKeySet, weights: Vec<usize>) -> Result<Self> {
+        check_weights(sk_set.threshold(), &weights)?;
+        Ok(WeightedSecretKeySet { sk_set, weights })
+    }
+
+    /// Returns the threshold: Participants need a total weight greater than this.
+    pub fn threshold(&self) -> usize {
+        self.sk_set.threshold()
+    }
+
+    /// Returns the participants' weights.
+    pub fn weights(&self) -> &[usize] {
+        &self.weights
+    }
+
+    /// Returns the underlying key set.
+    pub fn secret_key_set(&self) -> &SecretKeySet {
+        &self.sk_set
+    }
+
+    /// Returns the shares of participant `p`, or `None` if there is no such participant.
+    pub fn share_bundle(&self, p: usize) -> Option<ShareBundle> {
+        let weight = *self.weights.get(p)?;
+        let first = first_index(&self.weights, p);
+        let shares = (first..(first + weight))
+            .map(|i| self.sk_set.secret_key_share(i))
+            .collect();
+        Some(ShareBundle {
+            participant: p,
+            shares,
+        })
+    }
+
+    /// Returns the corresponding public key set.
+    pub fn public_keys(&self) -> WeightedPublicKeySet {
+        WeightedPublicKeySet {
+            pk_set: self.sk_set.public_keys(),
+            weights: self.weights.clone(),
+        }
+    }
+}
+
+/// A public key set whose participants hold as many shares as their weights.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+#[serde(try_from = "UncheckedPublicKeySet")]
+pub struct WeightedPublicKeySet {
+    pk_set: PublicKeySet,
+    weights: Vec<usize>,
+}
+
+/// A deserialized `WeightedPublicKeySet` whose weights haven't been checked yet.
+#[derive(Deserialize)]
+struct UncheckedPublicKeySet {
+    pk_set: PublicKeySet,
+    weights: Vec<usize>,
+}
+
+impl TryFrom<UncheckedPublicKeySet> for WeightedPublicKeySet {
+    type Error = Error;
+
+    fn try_from(unchecked: UncheckedPublicKeySet) -> Result<Self> {
+        WeightedPublicKeySet::new(unchecked.pk_set, unchecked.weights)
+    }
+}
+
+impl WeightedPublicKeySet {
+    /// Assigns the public key shares to participants with the given weights, as in
+    /// `WeightedSecretKeySet::new`.
+    pub fn new(pk_set: PublicKeySet, weights: Vec<usize>) -> Result<Self> {
+        check_weights(pk_set.threshold(), &weights)?;
+        Ok(WeightedPublicKeySet { pk_set, weights })
+    }
+
+    /// Returns the threshold: Participants need a total weight greater than this.
+    pub fn threshold(&self) -> usize {
+        self.pk_set.threshold()
+    }
+
+    /// Returns the participants' weights.
+    pub fn weights(&self) -> &[usize] {
+        &self.weights
+    }
+
+    /// Returns the master public key.
+    pub fn public_key(&self) -> PublicKey {
+        self.pk_set.public_key()
+    }
+
+    /// Returns the underlying public key set.
+    pub fn public_key_set(&self) -> &PublicKeySet {
+        &self.pk_set
+    }
+
+    /// Returns `true` if the bundle has one valid signature share of the message for each of
+    /// its participant's shares.
+    pub fn verify_signature_shares<M: AsRef<[u8]>>(
+        &self,
+        bundle: &SignatureShareBundle,
+        msg: M,
+    ) -> bool {
+        match self.indices(bundle.participant, bundle.shares.len()) {
+            Ok(first) => bundle.shares.iter().enumerate().all(|(k, share)| {
+                self.pk_set
+                    .public_key_share(first + k)
+                    .verify(share, msg.as_ref())
+            }),
+            Err(_) => false,
+        }
+    }
+
+    /// Combines the participants' signature shares into a signature.
+    ///
+    /// Returns `Error::InvalidShare(p)` if participant `p` doesn't exist or its bundle has the
+    /// wrong number of shares, `Error::DuplicateEntry` if a participant has more than one bundle,
+    /// and `Error::NotEnoughShares` if the total weight doesn't exceed the threshold. The shares are not verified: Use `verify_signature_shares` first if they
+    /// could be invalid.
+    pub fn combine_signatures<'a, I>(&self, bundles: I) -> Result<Signature>
+    where
+        I: IntoIterator<Item = &'a SignatureShareBundle>,
+    {
+        let mut participants = BTreeSet::new();
+        let mut shares = Vec::new();
+        for bundle in bundles {
+            let first = self.indices(bundle.participant, bundle.shares.len())?;
+            if !participants.insert(bundle.participant) {
+                return Err(Error::DuplicateEntry);
+            }
+            shares.extend((first..).zip(&bundle.shares));
+        }
+        self.pk_set.combine_signatures(shares)
+    }
+
+    /// Returns `true` if the bundle has one valid decryption share of the ciphertext for each of
+    /// its participant's shares.
+    #[cfg(feature = "encryption")]
+    pub fn verify_decryption_shares(
+        &self,
+        bundle: &DecryptionShareBundle,
+        ct: &Ciphertext,
+    ) -> bool {
+        self.verify_decryption_shares_with_ad(bundle, ct, [])
+    }
+
+    /// Returns `true` if the bundle has one valid decryption share of the ciphertext with the
+    /// associated data for each of its participant's shares.
+    #[cfg(feature = "encryption")]
+    pub fn verify_decryption_shares_with_ad<A: AsRef<[u8]>>(
+        &self,
+        bundle: &DecryptionShareBundle,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> bool {
+        match self.indices(bundle.participant, bundle.shares.len()) {
+            Ok(first) => bundle.shares.iter().enumerate().all(|(k, share)| {
+                self.pk_set
+                    .public_key_share(first + k)
+                    .verify_decryption_share_with_ad(share, ct, ad.as_ref())
+            }),
+            Err(_) => false,
+        }
+    }
+
+    /// Combines the participants' decryption shares and decrypts the ciphertext.
+    ///
+    /// Returns the same errors as `combine_signatures`. The shares are not verified: Use
+    /// `verify_decryption_shares` first if they could be invalid.
+    #[cfg(feature = "encryption")]
+    pub fn decrypt<'a, I>(&self, bundles: I, ct: &Ciphertext) -> Result<Vec<u8>>
+    where
+        I: IntoIterator<Item = &'a DecryptionShareBundle>,
+    {
+        let mut participants = BTreeSet::new();
+        let mut shares = Vec::new();
+        for bundle in bundles {
+            let first = self.indices(bundle.participant, bundle.shares.len())?;
+            if !participants.insert(bundle.participant) {
+                return Err(Error::DuplicateEntry);
+            }
+            shares.extend((first..).zip(&bundle.shares));
+        }
+        self.pk_set.decrypt(shares, ct)
+    }
+
+    /// Returns the index of participant `p`'s first share, or an error if `p` doesn't exist or
+    /// doesn't have `len` shares.
+    fn indices(&self, p: usize, len: usize) -> Result<usize> {
+        match self.weights.get(p) {
+            Some(weight) if *weight == len => Ok(first_index(&self.weights, p)),
+            _ => Err(Error::InvalidShare(p)),
+        }
+    }
+}
+
+/// A participant's secret key shares.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct ShareBundle {
+    participant: usize,
+    shares: Vec<SecretKeyShare>,
+}
+
+impl ShareBundle {
+    /// Returns the participant's index.
+    pub fn participant(&self) -> usize {
+        self.participant
+    }
+
+    /// Returns the secret key shares.
+    pub fn shares(&self) -> &[SecretKeyShare] {
+        &self.shares
+    }
+
+    /// Signs the message with each share.
+    pub fn sign<M: AsRef<[u8]>>(&self, msg: M) -> SignatureShareBundle {
+        SignatureShareBundle {
+            participant: self.participant,
+            shares: self
+                .shares
+                .iter()
+                .map(|share| share.sign(msg.as_ref()))
+                .collect(),
+        }
+    }
+
+    /// Returns a decryption share for each share, or `None` if the ciphertext isn't valid.
+    #[cfg(feature = "encryption")]
+    pub fn decrypt_share(&self, ct: &Ciphertext) -> Option<DecryptionShareBundle> {
+        self.decrypt_share_with_ad(ct, [])
+    }
+
+    /// Returns a decryption share for each share, or `None` if the ciphertext isn't valid with
+    /// the associated data.
+    #[cfg(feature = "encryption")]
+    pub fn decrypt_share_with_ad<A: AsRef<[u8]>>(
+        &self,
+        ct: &Ciphertext,
+        ad: A,
+    ) -> Option<DecryptionShareBundle> {
+        if !ct.verify_with_ad(ad) {
+            return None;
+        }
+        Some(DecryptionShareBundle {
+            participant: self.participant,
+            shares: self
+                .shares
+                .iter()
+                .map(|share| share.decrypt_share_no_verify(ct))
+                .collect(),
+        })
+    }
+}
+
+/// A participant's signature shares of a message.
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct SignatureShareBundle {
+    participant: usize,
+    shares: Vec<SignatureShare>,
+}
+
+impl SignatureShareBundle {
+    /// Returns the participant's index.
+    pub fn participant(&self) -> usize {
+        self.participant
+    }
+
+    /// Returns the signature shares.
+    pub fn shares(&self) -> &[SignatureShare] {
+        &self.shares
+    }
+}
+
+/// A participant's decryption shares of a ciphertext.
+#[cfg(feature = "encryption")]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, Debug)]
+pub struct DecryptionShareBundle {
+    participant: usize,
+    shares: Vec<DecryptionShare>,
+}
+
+#[cfg(feature = "encryption")]
+impl DecryptionShareBundle {
+    /// Returns the participant's index.
+    pub fn participant(&self) -> usize {
+        self.participant
+    }
+
+    /// Returns the decryption shares.
+    pub fn shares(&self) -> &[DecryptionShare] {
+        &self.shares
+    }
+}
+
+/// Returns an error if a weight is zero or the total weight doesn't exceed the threshold.
+fn check_weights(threshold: usize, weights: &[usize]) -> Result<()> {
+    if weights.contains(&0) {
+        let msg = "weights must be positive".to_string();
+        return Err(Error::InvalidAccessStructure(msg));
+    }
+    let total = weights
+        .iter()
+        .try_fold(0usize, |total, weight| total.checked_add(*weight));
+    match total {
+        Some(total) if total > threshold => Ok(()),
+        Some(_) => {
+            let msg = "the total weight must exceed the threshold".to_string();
+            Err(Error::InvalidAccessStructure(msg))
+        }
+        None => {
+            let msg = "the total weight must fit into a usize".to_string();
+            Err(Error::InvalidAccessStructure(msg))
+        }
+    }
+}
+
+/// Returns the index of participant `p`'s first share.
+fn first_index(weights: &[usize], p: usize) -> usize {
+    weights[..p].iter().sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_weighted_signatures() {
+        let mut rng = rand::thread_rng();
+        let sk_set = WeightedSecretKeySet::random(4, vec![2, 3, 1, 1], &mut rng).expect("valid");
+        let pk_set = sk_set.public_keys();
+        let sigs: Vec<_> = (0..4)
+            .map(|p| sk_set.share_bundle(p).expect("participant").sign("msg"))
+            .collect();
+        assert!(sk_set.share_bundle(4).is_none());
+        assert!(sigs
+            .iter()
+            .all(|b| pk_set.verify_signature_shares(b, "msg")));
+        assert!(!pk_set.verify_signature_shares(&sigs[0], "other"));
+
+        // Weights 2 + 3 and 3 + 1 + 1 exceed the threshold, 2 + 1 + 1 doesn't.
+        let sig = pk_set.combine_signatures(&sigs[..2]).expect("weight 5");
+        assert!(pk_set.public_key().verify(&sig, "msg"));
+        assert_eq!(Ok(sig), pk_set.combine_signatures(&sigs[1..]));
+        let light = [&sigs[0], &sigs[2], &sigs[3]];
+        let twice = [&sigs[0], &sigs[2], &sigs[3], &sigs[3]];
+        assert_eq!(
+            Err(Error::DuplicateEntry),
+            pk_set.combine_signatures(twice.iter().cloned())
+        );
+        assert_eq!(
+            Err(Error::NotEnoughShares),
+            pk_set.combine_signatures(light.iter().cloned())
+        );
+
+        // A bundle with a missing share is rejected.
+        let mut truncated = sigs[1].clone();
+        truncated.shares.pop();
+        assert!(!pk_set.verify_signature_shares(&truncated, "msg"));
+        assert_eq!(
+            Err(Error::InvalidShare(1)),
+            pk_set.combine_signatures(vec![&sigs[0], &truncated])
+        );
+
+        let bad = WeightedSecretKeySet::random(4, vec![2, 2], &mut rng);
+        assert!(matches!(bad, Err(Error::InvalidAccessStructure(_))));
+        let bad = WeightedSecretKeySet::random(1, vec![2, 0], &mut rng);
+        assert!(matches!(bad, Err(Error::InvalidAccessStructure(_))));
+
+        // Deserialization checks the weights, too.
+        let bytes = bincode::serialize(&pk_set).expect("serialize");
+        let de: WeightedPublicKeySet = bincode::deserialize(&bytes).expect("deserialize");
+        assert_eq!(pk_set, de);
+        let bytes = bincode::serialize(&(pk_set.public_key_set(), vec![2, 2])).expect("serialize");
+        assert!(bincode::deserialize::<WeightedPublicKeySet>(&bytes).is_err());
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_weighted_decryption() {
+        let mut rng = rand::thread_rng();
+        let sk_set = WeightedSecretKeySet::random(2, vec![2, 1], &mut rng).expect("valid");
+        let pk_set = sk_set.public_keys();
+        let ct = pk_set.public_key().encrypt(b"secret");
+        let dec: Vec<_> = (0..2)
+            .map(|p| {
+                let bundle = sk_set.share_bundle(p).expect("participant");
+                bundle.decrypt_share(&ct).expect("valid ciphertext")
+            })
+            .collect();
+        assert!(dec.iter().all(|b| pk_set.verify_decryption_shares(b, &ct)));
+        assert_eq!(Ok(b"secret".to_vec()), pk_set.decrypt(&dec, &ct));
+        assert_eq!(Err(Error::NotEnoughShares), pk_set.decrypt(&dec[..1], &ct));
+        let twice = [&dec[0], &dec[0]];
+        assert_eq!(
+            Err(Error::DuplicateEntry),
+            pk_set.decrypt(twice.iter().cloned(), &ct)
+        );
+
+        let ct = pk_set.public_key().encrypt_with_ad(b"secret", b"epoch 1");
+        let bundle = sk_set.share_bundle(0).expect("participant");
+        assert_eq!(None, bundle.decrypt_share(&ct));
+        let dec = bundle
+            .decrypt_share_with_ad(&ct, b"epoch 1")
+            .expect("valid ciphertext");
+        assert!(pk_set.verify_decryption_shares_with_ad(&dec, &ct, b"epoch 1"));
+        assert!(!pk_set.verify_decryption_shares(&dec, &ct));
+    }
+}
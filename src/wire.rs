@@ -0,0 +1,354 @@
+//! A versioned binary envelope around the canonical encodings, for storing values long-term.
+//!
+//! `to_wire` returns a value's encoding with a header, and `from_wire` checks the header and
+//! decodes it. The encoding is:
+//!
+//! | bytes  | contents                                                        |
+//! |--------|-----------------------------------------------------------------|
+//! | 0..4   | the magic bytes `TCWF`                                          |
+//! | 4      | the format version, currently 1                                 |
+//! | 5      | the type tag, see below                                         |
+//! | 6..    | the payload: in version 1, the value's `to_bytes` encoding      |
+//!
+//! The type tags are 1 for `PublicKey`, 2 for `PublicKeyShare`, 3 for `Signature`, 4 for
+//! `SignatureShare`, 5 for `SecretKey`, 6 for `SecretKeyShare`, 7 for `PublicKeySet`, 8 for
+//! `Ciphertext` and 9 for `DecryptionShare`. Secret keys and key shares are 32-byte big-endian
+//! integers.
+//!
+//! If a payload encoding ever changes, e.g. because a new version of the pairing library
+//! serializes points differently, the new encoding gets a new version number, and `from_wire`
+//! keeps decoding all earlier versions. Values stored with `to_wire` therefore remain readable.
+//!
+//! [`golden_secret_key_set`] and [`GOLDEN_MESSAGE`] are fixed inputs whose encodings this crate's
+//! tests pin down, so that a change to any of them is caught before it ships. Other
+//! implementations can use them to check their compatibility.
+//!
+//! ```
+//! use threshold_crypto::{wire, PublicKeySet};
+//!
+//! let pk_set = wire::golden_secret_key_set().public_keys();
+//! let bytes = pk_set.to_wire();
+//! assert_eq!(b"TCWF", &bytes[..4]);
+//! assert_eq!(Ok(pk_set), PublicKeySet::from_wire(&bytes));
+//! ```
+
+use ff::{PrimeField, PrimeFieldRepr};
+use zeroize::{Zeroize, Zeroizing};
+
+use crate::error::{DecodeResult, WireError, WireResult};
+use crate::into_fr::IntoFr;
+use crate::poly::Poly;
+use crate::util::sha3_256;
+#[cfg(feature = "encryption")]
+use crate::{Ciphertext, DecryptionShare};
+use crate::{PublicKey, PublicKeySet, PublicKeyShare, SecretKey, SecretKeySet, SecretKeyShare};
+use crate::{Signature, SignatureShare, SK_SIZE};
+
+/// The first bytes of every wire encoding.
+pub const WIRE_MAGIC: &[u8; 4] = b"TCWF";
+/// The format version that `to_wire` writes.
+pub const WIRE_VERSION: u8 = 1;
+/// The length of the header before the payload.
+const HEADER_SIZE: usize = 6;
+
+/// The message signed in the golden vectors.
+pub const GOLDEN_MESSAGE: &[u8] = b"threshold_crypto golden vector";
+
+/// The threshold of the golden key set.
+const GOLDEN_THRESHOLD: usize = 2;
+
+/// Returns a fixed key set with threshold 2, for golden vectors.
+///
+/// Coefficient `i` of the key set's polynomial is the integer whose big-endian representation is
+/// the first 8 bytes of the SHA3-256 hash of `threshold_crypto golden coefficient ` followed by
+/// the decimal number `i`. It doesn't depend on any random number generator, so it stays the same
+/// across versions.
+///
+/// *WARNING*: This key is public. Never use it for anything but tests.
+pub fn golden_secret_key_set() -> SecretKeySet {
+    let coeffs = (0..=GOLDEN_THRESHOLD)
+        .map(|i| {
+            let hash = sha3_256(format!("threshold_crypto golden coefficient {}", i).as_bytes());
+            let mut word = [0u8; 8];
+            word.copy_from_slice(&hash[..8]);
+            u64::from_be_bytes(word).into_fr()
+        })
+        .collect::<Vec<_>>();
+    SecretKeySet::from(Poly::from(coeffs))
+}
+
+/// The numeric type tags.
+mod tag {
+    pub const PUBLIC_KEY: u8 = 1;
+    pub const PUBLIC_KEY_SHARE: u8 = 2;
+    pub const SIGNATURE: u8 = 3;
+    pub const SIGNATURE_SHARE: u8 = 4;
+    pub const SECRET_KEY: u8 = 5;
+    pub const SECRET_KEY_SHARE: u8 = 6;
+    pub const PUBLIC_KEY_SET: u8 = 7;
+    #[cfg(feature = "encryption")]
+    pub const CIPHERTEXT: u8 = 8;
+    #[cfg(feature = "encryption")]
+    pub const DECRYPTION_SHARE: u8 = 9;
+}
+
+impl PublicKey {
+    /// Returns the key's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::PUBLIC_KEY, &self.to_bytes())
+    }
+
+    /// Decodes the key from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(bytes, tag::PUBLIC_KEY, "PublicKey", PublicKey::from_slice)
+    }
+}
+
+impl PublicKeyShare {
+    /// Returns the key share's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::PUBLIC_KEY_SHARE, &self.to_bytes())
+    }
+
+    /// Decodes the key share from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(
+            bytes,
+            tag::PUBLIC_KEY_SHARE,
+            "PublicKeyShare",
+            PublicKeyShare::from_slice,
+        )
+    }
+}
+
+impl Signature {
+    /// Returns the signature's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::SIGNATURE, &self.to_bytes())
+    }
+
+    /// Decodes the signature from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(bytes, tag::SIGNATURE, "Signature", Signature::from_slice)
+    }
+}
+
+impl SignatureShare {
+    /// Returns the signature share's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::SIGNATURE_SHARE, &self.to_bytes())
+    }
+
+    /// Decodes the signature share from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(
+            bytes,
+            tag::SIGNATURE_SHARE,
+            "SignatureShare",
+            SignatureShare::from_slice,
+        )
+    }
+}
+
+impl SecretKey {
+    /// Returns the key's versioned wire encoding.
+    pub fn to_wire(&self) -> Zeroizing<Vec<u8>> {
+        seal_secret(tag::SECRET_KEY, self)
+    }
+
+    /// Decodes the key from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(bytes, tag::SECRET_KEY, "SecretKey", SecretKey::from_slice)
+    }
+}
+
+impl SecretKeyShare {
+    /// Returns the key share's versioned wire encoding.
+    pub fn to_wire(&self) -> Zeroizing<Vec<u8>> {
+        seal_secret(tag::SECRET_KEY_SHARE, &self.0)
+    }
+
+    /// Decodes the key share from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(
+            bytes,
+            tag::SECRET_KEY_SHARE,
+            "SecretKeyShare",
+            SecretKeyShare::from_slice,
+        )
+    }
+}
+
+impl PublicKeySet {
+    /// Returns the key set's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::PUBLIC_KEY_SET, &self.to_bytes())
+    }
+
+    /// Decodes the key set from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(
+            bytes,
+            tag::PUBLIC_KEY_SET,
+            "PublicKeySet",
+            PublicKeySet::from_slice,
+        )
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl Ciphertext {
+    /// Returns the ciphertext's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::CIPHERTEXT, &self.to_bytes())
+    }
+
+    /// Decodes the ciphertext from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(bytes, tag::CIPHERTEXT, "Ciphertext", Ciphertext::from_slice)
+    }
+}
+
+#[cfg(feature = "encryption")]
+impl DecryptionShare {
+    /// Returns the decryption share's versioned wire encoding.
+    pub fn to_wire(&self) -> Vec<u8> {
+        seal(tag::DECRYPTION_SHARE, &self.to_bytes())
+    }
+
+    /// Decodes the decryption share from its versioned wire encoding.
+    pub fn from_wire(bytes: &[u8]) -> WireResult<Self> {
+        open(
+            bytes,
+            tag::DECRYPTION_SHARE,
+            "DecryptionShare",
+            DecryptionShare::from_slice,
+        )
+    }
+}
+
+/// Returns the header for the type tag, followed by the payload.
+fn seal(tag: u8, payload: &[u8]) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(HEADER_SIZE + payload.len());
+    bytes.extend_from_slice(WIRE_MAGIC);
+    bytes.push(WIRE_VERSION);
+    bytes.push(tag);
+    bytes.extend_from_slice(payload);
+    bytes
+}
+
+/// Returns the header for the type tag, followed by the secret key's big-endian representation.
+fn seal_secret(tag: u8, sk: &SecretKey) -> Zeroizing<Vec<u8>> {
+    let mut bytes = Zeroizing::new(Vec::with_capacity(HEADER_SIZE + SK_SIZE));
+    bytes.extend_from_slice(WIRE_MAGIC);
+    bytes.push(WIRE_VERSION);
+    bytes.push(tag);
+    let mut repr = sk.0.into_repr();
+    repr.write_be(&mut *bytes)
+        .expect("writing to a vector can't fail");
+    repr.0.zeroize();
+    bytes
+}
+
+/// Checks the header and decodes the payload of a value with the given type tag and name.
+fn open<T, F>(bytes: &[u8], tag: u8, name: &'static str, decode: F) -> WireResult<T>
+where
+    F: FnOnce(&[u8]) -> DecodeResult<T>,
+{
+    if bytes.len() < WIRE_MAGIC.len() || bytes[..WIRE_MAGIC.len()] != WIRE_MAGIC[..] {
+        return Err(WireError::Magic);
+    }
+    match bytes.get(4) {
+        Some(&WIRE_VERSION) => (),
+        Some(&version) => return Err(WireError::UnsupportedVersion(version)),
+        None => return Err(WireError::Magic),
+    }
+    match bytes.get(5) {
+        Some(&found) if found == tag => (),
+        Some(&found) => {
+            return Err(WireError::Type {
+                expected: name,
+                found,
+            })
+        }
+        None => return Err(WireError::Magic),
+    }
+    Ok(decode(&bytes[HEADER_SIZE..])?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wire_round_trips() {
+        let sk_set = golden_secret_key_set();
+        let sk = sk_set.secret_key();
+        assert_eq!(Ok(sk.clone()), SecretKey::from_wire(&sk.to_wire()));
+        let sk_share = sk_set.secret_key_share(1);
+        let bytes = sk_share.to_wire();
+        assert_eq!(Ok(sk_share.clone()), SecretKeyShare::from_wire(&bytes));
+        let pk_set = sk_set.public_keys();
+        assert_eq!(
+            Ok(pk_set.clone()),
+            PublicKeySet::from_wire(&pk_set.to_wire())
+        );
+        let pk = pk_set.public_key();
+        assert_eq!(Ok(pk), PublicKey::from_wire(&pk.to_wire()));
+        let pk_share = pk_set.public_key_share(1);
+        let pk_share_bytes = pk_share.to_wire();
+        assert_eq!(Ok(pk_share), PublicKeyShare::from_wire(&pk_share_bytes));
+        let sig = sk.sign(GOLDEN_MESSAGE);
+        assert_eq!(Ok(sig.clone()), Signature::from_wire(&sig.to_wire()));
+        let sig_share = sk_share.sign(GOLDEN_MESSAGE);
+        let sig_share_bytes = sig_share.to_wire();
+        assert_eq!(Ok(sig_share), SignatureShare::from_wire(&sig_share_bytes));
+
+        // The header is checked before the payload.
+        assert_eq!(
+            Err(WireError::Type {
+                expected: "PublicKey",
+                found: tag::PUBLIC_KEY_SHARE,
+            }),
+            PublicKey::from_wire(&pk_share_bytes)
+        );
+        let mut future = pk_share_bytes.clone();
+        future[4] = WIRE_VERSION + 1;
+        assert_eq!(
+            Err(WireError::UnsupportedVersion(WIRE_VERSION + 1)),
+            PublicKeyShare::from_wire(&future)
+        );
+        assert_eq!(Err(WireError::Magic), PublicKey::from_wire(&pk.to_bytes()));
+        assert_eq!(Err(WireError::Magic), PublicKey::from_wire(&bytes[..5]));
+        assert!(matches!(
+            PublicKey::from_wire(&pk_share_bytes[..HEADER_SIZE + 1]),
+            Err(WireError::Type { .. })
+        ));
+        let mut truncated = pk.to_wire();
+        truncated.pop();
+        assert!(matches!(
+            PublicKey::from_wire(&truncated),
+            Err(WireError::Decode(_))
+        ));
+    }
+
+    /// The golden vectors' encodings must never change: Otherwise, stored values become unreadable.
+    #[cfg(not(feature = "use-insecure-test-only-mock-crypto"))]
+    #[test]
+    fn test_golden_vectors() {
+        use crate::util::to_hex;
+
+        const GOLDEN_SECRET_KEY: &str =
+            "54435746010500000000000000000000000000000000000000000000000038227dcae23109e6";
+        const GOLDEN_PUBLIC_KEY_SET: &str = "544357460107b563549d5d5d2d6e18f9122cce7bff40bda3dbeb439f31b9e5bbbb62cf72addc5efdc40790567a23b6840343297d15cab201bcdd125a2db1f120e5aef76c0717893d06babd21716d26d8acab5e46b3cfe5065ddb76c228d0a65411ad6574fea3b748a4431d4f7854008e27cfc1e6755bebd28c70a0b6ce029bd99597d229b7673d610192ab307e9f4619dce714919af4";
+        const GOLDEN_SIGNATURE: &str = "544357460103af8825c41bda911c96f0bae9716a5d457ff6ae7f36128342f434a5c77e9c77aaf7535d90165f01d2ed6b1404e463b6301007158fd20d2ce3e04d07f8e2594cbc5786a39ac05751a79a0f49a76f70d1f68014ad3611bdf17489bd415b09f20811";
+
+        let sk_set = golden_secret_key_set();
+        let sk = sk_set.secret_key();
+        let pk_set = sk_set.public_keys();
+        let sig = sk.sign(GOLDEN_MESSAGE);
+        assert_eq!(GOLDEN_SECRET_KEY, to_hex(sk.to_wire()));
+        assert_eq!(GOLDEN_PUBLIC_KEY_SET, to_hex(pk_set.to_wire()));
+        assert_eq!(GOLDEN_SIGNATURE, to_hex(sig.to_wire()));
+    }
+}
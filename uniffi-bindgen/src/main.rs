@@ -0,0 +1,6 @@
+//! UniFFI's binding generator, run in library mode on `threshold_crypto` built with the `mobile`
+//! feature. See the `mobile` module's documentation.
+
+fn main() {
+    uniffi::uniffi_bindgen_main()
+}
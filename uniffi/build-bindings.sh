@@ -0,0 +1,28 @@
+#!/bin/sh
+
+# Generates the Kotlin and Swift bindings of the `mobile` feature, and compiles them with `kotlinc`
+# and `swiftc` where those are installed. Compiling the Kotlin bindings needs the JNA jar, whose
+# path is read from `JNA_JAR`.
+
+set -xe
+
+out=target/uniffi
+case "$(uname)" in
+    Darwin) lib=libthreshold_crypto.dylib ;;
+    *) lib=libthreshold_crypto.so ;;
+esac
+
+cargo rustc --lib --release --features mobile --crate-type cdylib
+cargo run -p uniffi-bindgen -- generate --library "target/release/$lib" \
+    --language kotlin --language swift --no-format --out-dir "$out"
+
+if command -v kotlinc > /dev/null; then
+    kotlinc -classpath "$JNA_JAR" -d "$out/threshold_crypto.jar" \
+        "$out/uniffi/threshold_crypto/threshold_crypto.kt"
+fi
+
+if command -v swiftc > /dev/null; then
+    swiftc -module-name threshold_crypto -emit-library -o "$out/libthreshold_crypto_swift" \
+        -Xcc -fmodule-map-file="$out/threshold_cryptoFFI.modulemap" -I "$out" \
+        -L target/release -lthreshold_crypto "$out/threshold_crypto.swift"
+fi